@@ -0,0 +1,298 @@
+//! Benchmarks the stages of the build pipeline.
+//!
+//! Run with `cargo bench --bench pipeline`. Each stage is measured for the
+//! fixtures in `benches/fixtures`: a small world, the full `wasi:cli` command
+//! world and a workspace of 50 component packages sharing a target dependency.
+//! The WIT of the `wasi:cli` packages is vendored, so the benchmarks don't
+//! access the network.
+//!
+//! * `bindings` generates the bindings in process, as `cargo component
+//!   bindings` does, both regenerating the bindings and with the bindings
+//!   up-to-date, so that a broken cache shows up as a regression of the
+//!   latter.
+//! * `resolution` decodes the target dependencies of a world and merges them
+//!   with the local target.
+//! * `componentization` encodes a core module of a world as a component.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::Result;
+use cargo_component::{
+    config::{CargoArguments, Config},
+    load_component_metadata, load_metadata, update_bindings,
+};
+use cargo_component_core::{
+    registry::{merge_dependencies, DependencyResolution, LocalResolution},
+    terminal::{Color, Terminal, Verbosity},
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+use wasm_pkg_client::caching::{CachingClient, FileCache};
+use wit_component::{ComponentEncoder, StringEncoding};
+use wit_parser::{Mangling, Resolve, UnresolvedPackageGroup, WorldId};
+
+/// The directory of the benchmark fixtures.
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/fixtures");
+
+/// The number of packages of the workspace fixture.
+const WORKSPACE_MEMBERS: usize = 50;
+
+/// Represents a world of the fixtures.
+struct Fixture {
+    /// The name of the fixture.
+    name: &'static str,
+    /// The directory of the local target.
+    path: PathBuf,
+    /// The names and paths of the target dependencies.
+    dependencies: Vec<(String, PathBuf)>,
+}
+
+impl Fixture {
+    fn small() -> Self {
+        Self {
+            name: "small",
+            path: Path::new(FIXTURES).join("small/wit"),
+            dependencies: Vec::new(),
+        }
+    }
+
+    fn wasi_cli() -> Self {
+        let path = Path::new(FIXTURES).join("wasi-cli/wit");
+        let dependencies = ["cli", "clocks", "filesystem", "io", "random", "sockets"]
+            .into_iter()
+            .map(|name| (format!("wasi:{name}"), path.join("deps").join(name)))
+            .collect();
+
+        Self {
+            name: "wasi-cli",
+            path,
+            dependencies,
+        }
+    }
+
+    /// Resolves the world of the fixture the way a local target is resolved.
+    async fn resolve(&self) -> Result<(Resolve, WorldId)> {
+        let resolutions = self
+            .dependencies
+            .iter()
+            .map(|(name, path)| {
+                Ok(DependencyResolution::Local(LocalResolution {
+                    name: name.parse()?,
+                    path: path.clone(),
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut deps = Vec::new();
+        for resolution in &resolutions {
+            deps.push(resolution.decode().await?);
+        }
+
+        let (mut resolve, _) = merge_dependencies(deps)?;
+        let package = resolve.push_group(UnresolvedPackageGroup::parse_dir(&self.path)?)?;
+        let world = resolve.select_world(package, None)?;
+        Ok((resolve, world))
+    }
+
+    /// Writes a component project targeting the world of the fixture.
+    fn write_project(&self, dir: &Path, name: &str) -> Result<()> {
+        let dependencies: String = self
+            .dependencies
+            .iter()
+            .map(|(name, path)| format!("\"{name}\" = {{ path = {path:?} }}\n"))
+            .collect();
+
+        write_project(dir, name, &self.path, &dependencies)
+    }
+}
+
+/// Creates the configuration and registry client of the benchmarks.
+///
+/// The fixtures only have local dependencies, so the cache in the given
+/// directory stays empty.
+async fn client(cache: &Path) -> Result<(Config, Arc<CachingClient<FileCache>>)> {
+    let config = Config::new(Terminal::new(Verbosity::Quiet, Color::Never), None)
+        .await?
+        .with_cache_dir(Some(cache.to_owned()));
+    let client = config.client(false).await?;
+    Ok((config, client))
+}
+
+/// Writes a component package to the given directory.
+fn write_project(dir: &Path, name: &str, target: &Path, dependencies: &str) -> Result<()> {
+    fs::create_dir_all(dir.join("src"))?;
+    fs::write(dir.join("src/lib.rs"), "")?;
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib"]
+
+[package.metadata.component]
+package = "bench:{name}"
+
+[package.metadata.component.target]
+path = {target:?}
+
+[package.metadata.component.target.dependencies]
+{dependencies}"#
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Writes the workspace fixture of component packages sharing a target
+/// dependency.
+fn write_workspace(dir: &Path) -> Result<()> {
+    let shared = Path::new(FIXTURES).join("workspace/shared.wit");
+    let mut members = Vec::new();
+    for i in 0..WORKSPACE_MEMBERS {
+        let name = format!("member{i}");
+        let member = dir.join(&name);
+        fs::create_dir_all(member.join("wit"))?;
+        fs::write(
+            member.join("wit/world.wit"),
+            format!(
+                "package bench:{name};
+
+world {name} {{
+    import bench:shared/greet;
+
+    export run: func() -> string;
+}}
+"
+            ),
+        )?;
+        write_project(
+            &member,
+            &name,
+            &member.join("wit"),
+            &format!("\"bench:shared\" = {{ path = {shared:?} }}\n"),
+        )?;
+        members.push(format!("{name:?}"));
+    }
+
+    fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            "[workspace]\nmembers = [{members}]\nresolver = \"2\"\n",
+            members = members.join(", ")
+        ),
+    )?;
+
+    Ok(())
+}
+
+/// Generates the bindings of the project in the given directory.
+///
+/// In the root of the workspace fixture, the bindings of all of its members
+/// are generated.
+async fn generate_bindings(
+    config: &Config,
+    client: Arc<CachingClient<FileCache>>,
+    dir: &Path,
+) -> Result<()> {
+    let manifest_path = dir.join("Cargo.toml");
+    let metadata = load_metadata(Some(&manifest_path))?;
+    let packages = load_component_metadata(&metadata, [].iter(), true, None)?;
+    let cargo_args = CargoArguments {
+        manifest_path: Some(manifest_path),
+        ..Default::default()
+    };
+
+    update_bindings(client, config, &metadata, &packages, &cargo_args, false).await
+}
+
+fn bindings(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = TempDir::new().unwrap();
+    let (config, client) = runtime.block_on(client(&dir.path().join("cache"))).unwrap();
+    let generate = |path: &Path| {
+        runtime
+            .block_on(generate_bindings(&config, client.clone(), path))
+            .unwrap()
+    };
+
+    let mut projects = Vec::new();
+    for fixture in [Fixture::small(), Fixture::wasi_cli()] {
+        let path = dir.path().join(fixture.name);
+        fixture.write_project(&path, "component").unwrap();
+        projects.push((fixture.name, path));
+    }
+
+    let workspace = dir.path().join("workspace");
+    write_workspace(&workspace).unwrap();
+    projects.push(("workspace", workspace));
+
+    let mut group = c.benchmark_group("bindings");
+    group.sample_size(10);
+    for (name, path) in &projects {
+        group.bench_function(format!("generate/{name}"), |b| {
+            b.iter_batched(
+                || {
+                    // The state of the bindings is kept in the target directory
+                    let _ = fs::remove_dir_all(path.join("target"));
+                },
+                |()| generate(path),
+                BatchSize::PerIteration,
+            )
+        });
+
+        generate(path);
+        group.bench_function(format!("fresh/{name}"), |b| b.iter(|| generate(path)));
+    }
+
+    group.finish();
+}
+
+fn resolution(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("resolution");
+    for fixture in [Fixture::small(), Fixture::wasi_cli()] {
+        group.bench_function(fixture.name, |b| {
+            b.iter(|| runtime.block_on(fixture.resolve()).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn componentization(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("componentization");
+    for fixture in [Fixture::small(), Fixture::wasi_cli()] {
+        let (resolve, world) = runtime.block_on(fixture.resolve()).unwrap();
+        let mut module = wit_component::dummy_module(&resolve, world, Mangling::Legacy);
+        wit_component::embed_component_metadata(&mut module, &resolve, world, StringEncoding::UTF8)
+            .unwrap();
+
+        group.bench_function(fixture.name, |b| {
+            b.iter(|| {
+                ComponentEncoder::default()
+                    .module(&module)
+                    .unwrap()
+                    .validate(true)
+                    .encode()
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bindings, resolution, componentization);
+criterion_main!(benches);
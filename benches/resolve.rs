@@ -0,0 +1,118 @@
+//! Benchmarks the merging of target dependencies into a single resolve.
+//!
+//! Run with `cargo bench --bench resolve`. Each benchmark merges a chain of
+//! synthetic WIT packages where every package uses the types of the previous
+//! one, so the encoded packages contain copies of all of their dependencies.
+//! The one-pass merge is compared with merging a resolve per dependency.
+
+use std::{fs, path::Path};
+
+use anyhow::Result;
+use cargo_component_core::registry::{
+    merge_dependencies, DecodedDependency, DependencyResolution, LocalResolution,
+};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use tokio::runtime::Runtime;
+use wit_parser::Resolve;
+
+/// The numbers of dependencies to benchmark.
+const SIZES: &[usize] = &[8, 32, 64];
+
+fn merge(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let dir = tempfile::tempdir().unwrap();
+
+    let mut group = c.benchmark_group("merge");
+    for &size in SIZES {
+        let resolutions = write_packages(dir.path(), size).unwrap();
+        let decode = || runtime.block_on(decode(&resolutions)).unwrap();
+
+        group.bench_function(format!("one-pass/{size}"), |b| {
+            b.iter_batched(
+                decode,
+                |deps| merge_dependencies(deps).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function(format!("per-dependency/{size}"), |b| {
+            b.iter_batched(
+                decode,
+                |deps| {
+                    let mut merged = Resolve::default();
+                    for dep in deps {
+                        let (resolve, _, _) = dep.resolve().unwrap();
+                        merged.merge(resolve).unwrap();
+                    }
+                    merged
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+/// Writes a chain of encoded WIT packages of the given length.
+fn write_packages(dir: &Path, size: usize) -> Result<Vec<DependencyResolution>> {
+    let mut resolve = Resolve::default();
+    let mut resolutions = Vec::new();
+    for i in 0..size {
+        let source = if i == 0 {
+            "package bench:dep0;
+
+interface api {
+    record shape {
+        width: u32,
+        height: u32,
+    }
+
+    area: func(shape: shape) -> u64;
+}
+"
+            .to_string()
+        } else {
+            format!(
+                "package bench:dep{i};
+
+interface api {{
+    use bench:dep{prev}/api.{{shape}};
+
+    record shape{i} {{
+        inner: shape,
+        depth: u32,
+    }}
+
+    volume: func(shape: shape{i}) -> u64;
+}}
+",
+                prev = i - 1
+            )
+        };
+
+        let package = resolve.push_str(format!("dep{i}.wit"), &source)?;
+        let path = dir.join(format!("dep{size}-{i}.wasm"));
+        fs::write(&path, wit_component::encode(&resolve, package)?)?;
+
+        resolutions.push(DependencyResolution::Local(LocalResolution {
+            name: format!("bench:dep{i}").parse()?,
+            path,
+        }));
+    }
+
+    Ok(resolutions)
+}
+
+/// Decodes the given dependencies.
+async fn decode(resolutions: &[DependencyResolution]) -> Result<Vec<DecodedDependency<'_>>> {
+    let mut deps = Vec::new();
+    for resolution in resolutions {
+        deps.push(resolution.decode().await?);
+    }
+
+    Ok(deps)
+}
+
+criterion_group!(benches, merge);
+criterion_main!(benches);
@@ -15,6 +15,7 @@ use std::time::Duration;
 // note that this list must be topologically sorted by dependencies
 const CRATES_TO_PUBLISH: &[&str] = &[
     "cargo-component-core",
+    "cargo-component-test-support",
     "wit",
     "cargo-component",
 ];
@@ -25,6 +26,7 @@ const CRATES_TO_PUBLISH: &[&str] = &[
 // that no one else should rely on.
 const PUBLIC_CRATES: &[&str] = &[
     "cargo-component-core",
+    "cargo-component-test-support",
     "wit",
     "cargo-component",
 ];
@@ -0,0 +1,384 @@
+//! Module for recording and replaying the registry interactions of dependency
+//! resolution.
+//!
+//! A cassette is a directory containing the recorded interactions in
+//! `cassette.toml` and the contents of the fetched packages in `content`.
+//!
+//! When recording, the version listings, releases and contents the dependency
+//! resolver gets from component registries are appended to the cassette. When
+//! replaying, they are answered from the cassette without accessing a
+//! registry; the interactions for the same package are replayed in the order
+//! they were recorded, with the last one repeating once all were replayed.
+//! The replay position is kept in the cassette directory so that it carries
+//! over between invocations of `cargo component`.
+//!
+//! Cassettes are plain TOML, so recorded ones can be edited by hand, for
+//! example to mark a version as yanked.
+
+use std::{
+    collections::BTreeMap,
+    env, fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Mutex,
+};
+
+use anyhow::{bail, Context, Result};
+use semver::Version;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use wasm_pkg_client::{ContentDigest, PackageRef, Release, VersionInfo};
+
+use crate::command::{REGISTRY_CASSETTE_ENV_VAR, REGISTRY_CASSETTE_MODE_ENV_VAR};
+
+/// The name of the file of the recorded interactions of a cassette.
+pub const CASSETTE_FILE_NAME: &str = "cassette.toml";
+
+/// The name of the file of the replay position of a cassette.
+const CURSOR_FILE_NAME: &str = "cursor.toml";
+
+/// The name of the directory of the recorded package contents of a cassette.
+const CONTENT_DIR_NAME: &str = "content";
+
+/// Represents whether a cassette is recorded or replayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Registry interactions are performed and appended to the cassette.
+    Record,
+    /// Registry interactions are answered from the cassette.
+    Replay,
+}
+
+impl FromStr for CassetteMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            _ => bail!("invalid cassette mode `{s}`: expected `record` or `replay`"),
+        }
+    }
+}
+
+impl fmt::Display for CassetteMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Record => write!(f, "record"),
+            Self::Replay => write!(f, "replay"),
+        }
+    }
+}
+
+/// Represents the recorded interactions of a cassette.
+#[derive(Default, Serialize, Deserialize)]
+struct Interactions {
+    /// The recorded version listings.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    list: Vec<RecordedList>,
+    /// The recorded releases.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    release: Vec<RecordedRelease>,
+}
+
+/// Represents a recorded listing of the versions of a package.
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedList {
+    /// The name of the package.
+    package: PackageRef,
+    /// The versions of the package.
+    ///
+    /// A value of `None` indicates that the package was not found.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    versions: Option<Vec<RecordedVersion>>,
+}
+
+/// Represents a recorded version of a package.
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedVersion {
+    /// The version of the package.
+    version: Version,
+    /// Whether or not the version is yanked.
+    #[serde(default)]
+    yanked: bool,
+}
+
+/// Represents a recorded release of a package.
+#[derive(Clone, Serialize, Deserialize)]
+struct RecordedRelease {
+    /// The name of the package.
+    package: PackageRef,
+    /// The version of the release.
+    version: Version,
+    /// The digest of the release content.
+    digest: ContentDigest,
+}
+
+/// Represents a cassette of registry interactions.
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    // Serializes the updates of the cassette files
+    lock: Mutex<()>,
+}
+
+impl Cassette {
+    /// Creates a cassette in the given directory.
+    pub fn new(path: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        Self {
+            path: path.into(),
+            mode,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Creates the cassette given by the `CARGO_COMPONENT_REGISTRY_CASSETTE`
+    /// and `CARGO_COMPONENT_REGISTRY_CASSETTE_MODE` environment variables.
+    ///
+    /// Returns `None` if no cassette directory is set or the `testing`
+    /// feature is disabled. The cassette is replayed unless the mode is set
+    /// to `record`.
+    pub fn from_env() -> Result<Option<Self>> {
+        if !cfg!(feature = "testing") {
+            return Ok(None);
+        }
+
+        let Some(path) = env::var_os(REGISTRY_CASSETTE_ENV_VAR) else {
+            return Ok(None);
+        };
+
+        let mode = match env::var(REGISTRY_CASSETTE_MODE_ENV_VAR) {
+            Ok(mode) => mode
+                .parse()
+                .with_context(|| format!("invalid value of `{REGISTRY_CASSETTE_MODE_ENV_VAR}`"))?,
+            Err(_) => CassetteMode::Replay,
+        };
+
+        Ok(Some(Self::new(path, mode)))
+    }
+
+    /// Gets the directory of the cassette.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the mode of the cassette.
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// Replays the listing of the versions of a package.
+    ///
+    /// Returns `None` if the package was recorded as not found.
+    pub(crate) fn versions(&self, package: &PackageRef) -> Result<Option<Vec<VersionInfo>>> {
+        log::debug!(
+            "replaying versions of package `{package}` from cassette `{path}`",
+            path = self.path.display()
+        );
+
+        let list = self
+            .replay(format!("list {package}"), |interactions| {
+                interactions
+                    .list
+                    .into_iter()
+                    .filter(|l| &l.package == package)
+                    .collect()
+            })?
+            .with_context(|| {
+                format!(
+                    "cassette `{path}` has no recorded versions of package `{package}`",
+                    path = self.path.display()
+                )
+            })?;
+
+        Ok(list.versions.map(|versions| {
+            versions
+                .into_iter()
+                .map(|v| VersionInfo {
+                    version: v.version,
+                    yanked: v.yanked,
+                })
+                .collect()
+        }))
+    }
+
+    /// Records the listing of the versions of a package.
+    ///
+    /// A value of `None` records that the package was not found.
+    pub(crate) fn record_versions(
+        &self,
+        package: &PackageRef,
+        versions: Option<&[VersionInfo]>,
+    ) -> Result<()> {
+        self.record(|interactions| {
+            interactions.list.push(RecordedList {
+                package: package.clone(),
+                versions: versions.map(|versions| {
+                    versions
+                        .iter()
+                        .map(|v| RecordedVersion {
+                            version: v.version.clone(),
+                            yanked: v.yanked,
+                        })
+                        .collect()
+                }),
+            })
+        })
+    }
+
+    /// Replays the fetching of a release of a package.
+    pub(crate) fn release(&self, package: &PackageRef, version: &Version) -> Result<Release> {
+        log::debug!(
+            "replaying release v{version} of package `{package}` from cassette `{path}`",
+            path = self.path.display()
+        );
+
+        let release = self
+            .replay(format!("release {package}@{version}"), |interactions| {
+                interactions
+                    .release
+                    .into_iter()
+                    .filter(|r| &r.package == package && &r.version == version)
+                    .collect()
+            })?
+            .with_context(|| {
+                format!(
+                    "cassette `{path}` has no recorded release v{version} of package `{package}`",
+                    path = self.path.display()
+                )
+            })?;
+
+        Ok(Release {
+            version: release.version,
+            content_digest: release.digest,
+        })
+    }
+
+    /// Records the fetching of a release of a package.
+    pub(crate) fn record_release(&self, package: &PackageRef, release: &Release) -> Result<()> {
+        self.record(|interactions| {
+            interactions.release.push(RecordedRelease {
+                package: package.clone(),
+                version: release.version.clone(),
+                digest: release.content_digest.clone(),
+            })
+        })
+    }
+
+    /// Replays the content with the given digest.
+    ///
+    /// Fails if the recorded content does not match the digest.
+    pub(crate) fn content(&self, digest: &ContentDigest) -> Result<Vec<u8>> {
+        let path = self.content_path(digest);
+        let content = fs::read(&path).with_context(|| {
+            format!(
+                "cassette `{cassette}` has no recorded content with digest `{digest}`",
+                cassette = self.path.display()
+            )
+        })?;
+
+        let actual = ContentDigest::from(Sha256::new_with_prefix(&content));
+        if &actual != digest {
+            bail!(
+                "cassette content `{path}` has digest `{actual}` but digest `{digest}` was expected",
+                path = path.display()
+            );
+        }
+
+        Ok(content)
+    }
+
+    /// Records the content with the given digest.
+    pub(crate) fn record_content(&self, digest: &ContentDigest, content: &[u8]) -> Result<()> {
+        let path = self.content_path(digest);
+        if path.is_file() {
+            return Ok(());
+        }
+
+        let _guard = self.lock.lock().unwrap();
+        fs::create_dir_all(path.parent().unwrap()).with_context(|| {
+            format!(
+                "failed to create cassette directory `{path}`",
+                path = self.path.display()
+            )
+        })?;
+        fs::write(&path, content).with_context(|| {
+            format!(
+                "failed to write cassette content `{path}`",
+                path = path.display()
+            )
+        })
+    }
+
+    fn content_path(&self, digest: &ContentDigest) -> PathBuf {
+        self.path
+            .join(CONTENT_DIR_NAME)
+            .join(digest.to_string().replace(':', "-"))
+    }
+
+    /// Replays the next of the recorded interactions selected from the
+    /// cassette, advancing the replay position of the given key.
+    ///
+    /// Returns `None` if no interaction was selected.
+    fn replay<T: Clone>(
+        &self,
+        key: String,
+        select: impl FnOnce(Interactions) -> Vec<T>,
+    ) -> Result<Option<T>> {
+        let _guard = self.lock.lock().unwrap();
+        let recorded = select(self.read(CASSETTE_FILE_NAME)?);
+        if recorded.is_empty() {
+            return Ok(None);
+        }
+
+        let mut cursor: BTreeMap<String, usize> = self.read(CURSOR_FILE_NAME)?;
+        let position = cursor.entry(key).or_default();
+        let interaction = recorded[(*position).min(recorded.len() - 1)].clone();
+        *position += 1;
+        self.write(CURSOR_FILE_NAME, &cursor)?;
+
+        Ok(Some(interaction))
+    }
+
+    /// Appends an interaction to the cassette.
+    fn record(&self, append: impl FnOnce(&mut Interactions)) -> Result<()> {
+        let _guard = self.lock.lock().unwrap();
+        let mut interactions = self.read(CASSETTE_FILE_NAME)?;
+        append(&mut interactions);
+        self.write(CASSETTE_FILE_NAME, &interactions)
+    }
+
+    /// Reads a file of the cassette, defaulting its contents if it does not
+    /// exist.
+    fn read<T: Default + DeserializeOwned>(&self, name: &str) -> Result<T> {
+        let path = self.path.join(name);
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml_edit::de::from_str(&contents)
+                .with_context(|| format!("invalid cassette file `{path}`", path = path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+            Err(e) => Err(e).with_context(|| {
+                format!(
+                    "failed to read cassette file `{path}`",
+                    path = path.display()
+                )
+            }),
+        }
+    }
+
+    /// Writes a file of the cassette.
+    fn write(&self, name: &str, value: &impl Serialize) -> Result<()> {
+        let path = self.path.join(name);
+        fs::create_dir_all(&self.path).with_context(|| {
+            format!(
+                "failed to create cassette directory `{path}`",
+                path = self.path.display()
+            )
+        })?;
+        fs::write(&path, toml_edit::ser::to_string_pretty(value)?).with_context(|| {
+            format!(
+                "failed to write cassette file `{path}`",
+                path = path.display()
+            )
+        })
+    }
+}
@@ -3,12 +3,24 @@ use std::path::PathBuf;
 
 use clap::{ArgAction, Args};
 
-use crate::terminal::{Color, Terminal, Verbosity};
+use crate::terminal::{Color, OutputFormat, Terminal, Verbosity};
 
 /// The environment variable name for setting a cache directory location
 pub const CACHE_DIR_ENV_VAR: &str = "CARGO_COMPONENT_CACHE_DIR";
 /// The environment variable name for setting a path to a config file
 pub const CONFIG_FILE_ENV_VAR: &str = "CARGO_COMPONENT_CONFIG_FILE";
+/// The environment variable name for setting a path to the cargo-component user config file
+pub const USER_CONFIG_FILE_ENV_VAR: &str = "CARGO_COMPONENT_USER_CONFIG_FILE";
+/// The environment variable name for setting the number of retries of failed registry operations
+pub const NET_RETRY_ENV_VAR: &str = "CARGO_COMPONENT_NET_RETRY";
+/// The environment variable name for setting the timeout, in seconds, of a registry operation attempt
+pub const NET_TIMEOUT_ENV_VAR: &str = "CARGO_COMPONENT_NET_TIMEOUT";
+/// The environment variable name for setting a cassette directory to record registry interactions to or replay them from
+///
+/// This is only honored with the `testing` feature of this crate.
+pub const REGISTRY_CASSETTE_ENV_VAR: &str = "CARGO_COMPONENT_REGISTRY_CASSETTE";
+/// The environment variable name for setting whether the registry cassette is recorded or replayed
+pub const REGISTRY_CASSETTE_MODE_ENV_VAR: &str = "CARGO_COMPONENT_REGISTRY_CASSETTE_MODE";
 
 /// Common options for commands.
 #[derive(Args)]
@@ -32,6 +44,10 @@ pub struct CommonOptions {
     #[clap(long = "color", value_name = "WHEN")]
     pub color: Option<Color>,
 
+    /// Diagnostic format: human, github
+    #[clap(long = "output-format", value_name = "FMT")]
+    pub output_format: Option<OutputFormat>,
+
     /// The path to the cache directory to store component dependencies.
     #[clap(long = "cache-dir", env = CACHE_DIR_ENV_VAR)]
     pub cache_dir: Option<PathBuf>,
@@ -45,15 +61,9 @@ impl CommonOptions {
     /// Creates a new terminal from the common options.
     pub fn new_terminal(&self) -> Terminal {
         Terminal::new(
-            if self.quiet {
-                Verbosity::Quiet
-            } else {
-                match self.verbose {
-                    0 => Verbosity::Normal,
-                    _ => Verbosity::Verbose,
-                }
-            },
+            Verbosity::from_flags(self.quiet, self.verbose.into()),
             self.color.unwrap_or_default(),
         )
+        .with_output_format(self.output_format.unwrap_or_default())
     }
 }
@@ -0,0 +1,166 @@
+//! Module for rendering errors as diagnostics.
+//!
+//! `wit-parser` reports the location of a parse or resolution error by
+//! appending a highlighted snippet of the WIT source to the error message.
+//! When such an error is nested in a chain of contexts, the snippet ends up
+//! indented in the middle of the chain; this module brings it to the front and
+//! renders it as an annotated snippet, with the contexts as notes.
+
+use std::{env, fmt::Write, path::Path};
+
+/// Represents a WIT error with its highlighted source snippet.
+#[derive(Debug, PartialEq)]
+struct WitDiagnostic<'a> {
+    /// The error message.
+    message: &'a str,
+    /// The path of the WIT file.
+    file: &'a str,
+    /// The one-based line of the error.
+    line: usize,
+    /// The one-based column of the error.
+    col: usize,
+    /// The source line of the error.
+    source: &'a str,
+    /// The number of characters spanned by the error.
+    len: usize,
+}
+
+impl<'a> WitDiagnostic<'a> {
+    /// Parses a `wit-parser` error message with a highlighted snippet.
+    ///
+    /// The snippet has the form:
+    ///
+    /// ```text
+    /// <message>
+    ///      --> <file>:<line>:<col>
+    ///       |
+    ///  <line> | <source>
+    ///       | ^----
+    /// ```
+    fn parse(error: &'a str) -> Option<Self> {
+        let (message, rest) = error.split_once("\n     --> ")?;
+        let mut lines = rest.lines();
+
+        let mut location = lines.next()?.rsplitn(3, ':');
+        let col = location.next()?.parse().ok()?;
+        let line = location.next()?.parse().ok()?;
+        let file = location.next()?;
+
+        lines.next()?;
+        let (_, source) = lines.next()?.split_once(" | ")?;
+        let marker = lines.next()?.split_once('|')?.1.trim();
+        if !marker.starts_with('^') {
+            return None;
+        }
+
+        Some(Self {
+            message,
+            file,
+            line,
+            col,
+            source,
+            len: marker.chars().count(),
+        })
+    }
+
+    /// Renders the diagnostic as an annotated snippet followed by notes.
+    fn render(&self, notes: &[String]) -> String {
+        let file = env::current_dir()
+            .ok()
+            .and_then(|cwd| Path::new(self.file).strip_prefix(cwd).ok())
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| self.file.to_string());
+
+        let line = self.line.to_string();
+        let gutter = " ".repeat(line.len());
+
+        let mut rendered = String::new();
+        writeln!(rendered, "{message}", message = self.message).unwrap();
+        writeln!(rendered, "{gutter}--> {file}:{line}:{col}", col = self.col).unwrap();
+        writeln!(rendered, "{gutter} |").unwrap();
+        writeln!(rendered, "{line} | {source}", source = self.source).unwrap();
+        write!(
+            rendered,
+            "{gutter} | {pad}{marker}",
+            pad = " ".repeat(self.col - 1),
+            marker = "^".repeat(self.len)
+        )
+        .unwrap();
+
+        if !notes.is_empty() {
+            write!(rendered, "\n{gutter} |").unwrap();
+        }
+
+        for note in notes {
+            write!(rendered, "\n{gutter} = note: {note}").unwrap();
+        }
+
+        rendered
+    }
+}
+
+/// Renders an error for printing to the terminal.
+///
+/// If the error was caused by an error in a WIT source file, the WIT error is
+/// rendered first as an annotated snippet of the source, followed by the
+/// contexts of the error as notes. Otherwise, the error is rendered with its
+/// chain of causes as usual.
+pub fn render_error(error: &anyhow::Error) -> String {
+    let chain: Vec<_> = error.chain().map(ToString::to_string).collect();
+    for (i, cause) in chain.iter().enumerate() {
+        if let Some(diagnostic) = WitDiagnostic::parse(cause) {
+            return diagnostic.render(&chain[..i]);
+        }
+    }
+
+    format!("{error:?}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HIGHLIGHTED: &str = "name `strin` is not defined
+     --> /ws/wit/world.wit:4:35
+      |
+    4 |     export hello-world: func() -> strin;
+      |                                   ^----";
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            WitDiagnostic::parse(HIGHLIGHTED),
+            Some(WitDiagnostic {
+                message: "name `strin` is not defined",
+                file: "/ws/wit/world.wit",
+                line: 4,
+                col: 35,
+                source: "    export hello-world: func() -> strin;",
+                len: 5,
+            })
+        );
+        assert_eq!(WitDiagnostic::parse("failed to spawn `cargo`"), None);
+    }
+
+    #[test]
+    fn test_render_error() {
+        let error = anyhow::anyhow!(HIGHLIGHTED)
+            .context("failed to parse local target from directory `/ws/wit`")
+            .context("failed to create a target world for package `foo`");
+
+        assert_eq!(
+            render_error(&error),
+            "name `strin` is not defined
+ --> /ws/wit/world.wit:4:35
+  |
+4 |     export hello-world: func() -> strin;
+  |                                   ^^^^^
+  |
+  = note: failed to create a target world for package `foo`
+  = note: failed to parse local target from directory `/ws/wit`"
+        );
+
+        let error = anyhow::anyhow!("failed to spawn `cargo`");
+        assert_eq!(render_error(&error), format!("{error:?}"));
+    }
+}
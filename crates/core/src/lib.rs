@@ -7,13 +7,17 @@ use std::str::FromStr;
 
 use anyhow::Context;
 use semver::VersionReq;
-use wasm_pkg_client::PackageRef;
+use wasm_pkg_client::{ContentDigest, PackageRef};
 
+pub mod cassette;
 pub mod command;
+pub mod diagnostic;
 pub mod lock;
 pub mod progress;
 pub mod registry;
 pub mod terminal;
+#[cfg(feature = "testing")]
+pub mod testing;
 
 /// The root directory name used for default cargo component directories
 pub const CARGO_COMPONENT_DIR: &str = "cargo-component";
@@ -36,12 +40,20 @@ pub fn cache_dir(dir: Option<PathBuf>) -> anyhow::Result<PathBuf> {
 }
 
 /// Represents a versioned component package name.
+///
+/// The package name may be followed by `@` and either a version requirement,
+/// such as `ns:pkg@1.2.3`, `ns:pkg@=1.2.3` or `ns:pkg@^1`, or the digest of the
+/// content of a release, such as `ns:pkg@sha256:<digest>`.
 #[derive(Clone)]
 pub struct VersionedPackageName {
     /// The package name.
     pub name: PackageRef,
     /// The optional package version.
     pub version: Option<VersionReq>,
+    /// The optional digest of the content of the release to pin.
+    ///
+    /// A package name with a digest has no version requirement.
+    pub digest: Option<ContentDigest>,
 }
 
 impl FromStr for VersionedPackageName {
@@ -49,6 +61,16 @@ impl FromStr for VersionedPackageName {
 
     fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
         match s.split_once('@') {
+            // Version requirements never contain a colon, digests always do
+            Some((name, digest)) if digest.contains(':') => Ok(Self {
+                name: name.parse()?,
+                version: None,
+                digest: Some(
+                    digest
+                        .parse()
+                        .with_context(|| format!("invalid package digest `{digest}`"))?,
+                ),
+            }),
             Some((name, version)) => Ok(Self {
                 name: name.parse()?,
                 version: Some(
@@ -56,10 +78,12 @@ impl FromStr for VersionedPackageName {
                         .parse()
                         .with_context(|| format!("invalid package version `{version}`"))?,
                 ),
+                digest: None,
             }),
             None => Ok(Self {
                 name: s.parse()?,
                 version: None,
+                digest: None,
             }),
         }
     }
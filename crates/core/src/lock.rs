@@ -54,6 +54,11 @@ pub struct LockedPackageVersion {
     pub version: Version,
     /// The digest of the package contents.
     pub digest: ContentDigest,
+    /// The registry that served the package contents.
+    ///
+    /// This is only recorded for packages of namespaces with mirror registries.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
 }
 
 impl LockedPackageVersion {
@@ -142,6 +147,43 @@ impl LockFile {
         }
     }
 
+    /// Locks a version of a package, replacing the version locked for the same
+    /// requirement, if any.
+    ///
+    /// The packages and their versions are kept sorted.
+    pub fn lock(
+        &mut self,
+        name: &PackageRef,
+        registry: Option<&str>,
+        version: LockedPackageVersion,
+    ) {
+        let key = (
+            name.namespace().as_ref(),
+            name.name().as_ref(),
+            registry.unwrap_or(DEFAULT_REGISTRY_NAME),
+        );
+        let index = match self.packages.binary_search_by_key(&key, LockedPackage::key) {
+            Ok(index) => index,
+            Err(index) => {
+                self.packages.insert(
+                    index,
+                    LockedPackage {
+                        name: name.clone(),
+                        registry: registry.map(ToString::to_string),
+                        versions: Vec::new(),
+                    },
+                );
+                index
+            }
+        };
+
+        let versions = &mut self.packages[index].versions;
+        match versions.binary_search_by_key(&version.key(), LockedPackageVersion::key) {
+            Ok(index) => versions[index] = version,
+            Err(index) => versions.insert(index, version),
+        }
+    }
+
     /// Reads the lock file from the given file object.
     pub fn read(mut file: &File) -> Result<Self> {
         let mut contents = String::new();
@@ -439,7 +481,7 @@ mod sys {
     }
 
     pub(super) fn error_contended(err: &Error) -> bool {
-        err.raw_os_error().map_or(false, |x| x == libc::EWOULDBLOCK)
+        err.raw_os_error() == Some(libc::EWOULDBLOCK)
     }
 
     pub(super) fn error_unsupported(err: &Error) -> bool {
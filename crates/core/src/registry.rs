@@ -1,34 +1,203 @@
 //! Module for resolving dependencies from a component registry.
 use std::{
-    collections::{hash_map, HashMap},
-    fmt::Debug,
+    collections::{hash_map, HashMap, HashSet},
+    env,
+    fmt::{self, Debug},
+    future::Future,
+    io,
     path::{Path, PathBuf},
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
 
 use anyhow::{bail, Context, Result};
 use futures::TryStreamExt;
-use indexmap::IndexMap;
+use id_arena::Arena;
+use indexmap::{IndexMap, IndexSet};
+use oci_client::errors::OciDistributionError;
 use semver::{Comparator, Op, Version, VersionReq};
 use serde::{
     de::{self, value::MapAccessDeserializer},
     Deserialize, Serialize,
 };
 
+use sha2::{Digest, Sha256};
 use tokio::io::AsyncReadExt;
+use tokio_util::io::{ReaderStream, StreamReader};
 use wasm_pkg_client::{
-    caching::{CachingClient, FileCache},
-    Client, Config, ContentDigest, Error as WasmPkgError, PackageRef, Release, VersionInfo,
+    caching::{Cache, CachingClient, FileCache},
+    Client, Config, ContentDigest, ContentStream, Error as WasmPkgError, PackageRef,
+    RegistryMapping, Release, VersionInfo,
 };
 use wit_component::DecodedWasm;
 use wit_parser::{PackageId, PackageName, Resolve, UnresolvedPackageGroup, WorldId};
 
-use crate::lock::{LockFileResolver, LockedPackageVersion};
+use crate::{
+    cache_dir,
+    cassette::{Cassette, CassetteMode},
+    command::{CACHE_DIR_ENV_VAR, NET_RETRY_ENV_VAR, NET_TIMEOUT_ENV_VAR},
+    lock::{LockFileResolver, LockedPackageVersion},
+    terminal::Terminal,
+};
 
 /// The name of the default registry.
 pub const DEFAULT_REGISTRY_NAME: &str = "default";
 
+/// Represents the policy for retrying failed registry operations.
+///
+/// Only errors that may be caused by an unreliable network are retried, with
+/// an exponential backoff between the attempts.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts of an operation, including the first one.
+    pub max_attempts: u32,
+    /// The delay before the first retry; it doubles with every further retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two attempts.
+    pub max_backoff: Duration,
+    /// The timeout of a single attempt.
+    ///
+    /// A value of `None` indicates that attempts do not time out.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            timeout: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Creates the default retry policy with the overrides of the
+    /// `CARGO_COMPONENT_NET_RETRY` and `CARGO_COMPONENT_NET_TIMEOUT`
+    /// environment variables applied.
+    pub fn from_env() -> Result<Self> {
+        let mut policy = Self::default();
+
+        if let Ok(retries) = std::env::var(NET_RETRY_ENV_VAR) {
+            let retries: u32 = retries.parse().with_context(|| {
+                format!("invalid number of retries `{retries}` in `{NET_RETRY_ENV_VAR}`")
+            })?;
+            policy.max_attempts = retries.saturating_add(1);
+        }
+
+        if let Ok(timeout) = std::env::var(NET_TIMEOUT_ENV_VAR) {
+            let secs: u64 = timeout.parse().with_context(|| {
+                format!("invalid timeout `{timeout}` in `{NET_TIMEOUT_ENV_VAR}`")
+            })?;
+            policy.timeout = (secs > 0).then(|| Duration::from_secs(secs));
+        }
+
+        Ok(policy)
+    }
+
+    /// Runs the given registry operation, retrying it according to the policy.
+    ///
+    /// A warning is printed for every failed attempt that is retried.
+    pub async fn run<T, F, Fut>(
+        &self,
+        what: impl fmt::Display,
+        mut op: F,
+    ) -> Result<T, WasmPkgError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, WasmPkgError>>,
+    {
+        let mut attempt = 1;
+        let mut backoff = self.initial_backoff;
+        loop {
+            match self.attempt(op()).await {
+                Err(e) if attempt < self.max_attempts && is_retryable(&e) => {
+                    Terminal::current()
+                        .warn(format!(
+                            "failed to {what} (attempt {attempt} of {max}): {e}; retrying in {delay:.1}s",
+                            max = self.max_attempts,
+                            delay = backoff.as_secs_f64()
+                        ))
+                        .ok();
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(self.max_backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Runs a single attempt of a registry operation, applying the timeout of
+    /// the policy.
+    async fn attempt<T>(
+        &self,
+        op: impl Future<Output = Result<T, WasmPkgError>>,
+    ) -> Result<T, WasmPkgError> {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, op).await.unwrap_or_else(|_| {
+                Err(WasmPkgError::IoError(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("timed out after {secs}s", secs = timeout.as_secs()),
+                )))
+            }),
+            None => op.await,
+        }
+    }
+}
+
+/// Determines if a registry error may be caused by an unreliable network.
+///
+/// Only transport failures, timeouts and server errors are retried; errors such
+/// as a failed authentication or a missing package are returned immediately.
+fn is_retryable(e: &WasmPkgError) -> bool {
+    match e {
+        WasmPkgError::IoError(_) => true,
+        WasmPkgError::RegistryError(e) | WasmPkgError::RegistryMetadataError(e) => {
+            e.chain().any(is_transient)
+        }
+        _ => false,
+    }
+}
+
+/// Determines if an error in the source chain of a registry error is transient.
+fn is_transient(e: &(dyn std::error::Error + 'static)) -> bool {
+    if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+        return is_transient_request(e);
+    }
+
+    if let Some(e) = e.downcast_ref::<OciDistributionError>() {
+        return match e {
+            OciDistributionError::RequestError(e) => is_transient_request(e),
+            OciDistributionError::ServerError { code, .. } => *code >= 500,
+            _ => false,
+        };
+    }
+
+    if let Some(warg_client::ClientError::Api(e)) = e.downcast_ref::<warg_client::ClientError>() {
+        return match e {
+            warg_client::api::ClientError::Communication(e) => is_transient_request(e),
+            warg_client::api::ClientError::UnexpectedResponse { status, .. } => {
+                status.is_server_error()
+            }
+            _ => false,
+        };
+    }
+
+    e.is::<io::Error>()
+}
+
+/// Determines if a failed HTTP request may succeed when sent again.
+fn is_transient_request(e: &reqwest::Error) -> bool {
+    e.is_timeout()
+        || e.is_connect()
+        || e.is_request()
+        || e.is_body()
+        || e.status().is_some_and(|s| s.is_server_error())
+}
+
 /// Represents a WIT package dependency.
 #[derive(Debug, Clone)]
 pub enum Dependency {
@@ -200,8 +369,122 @@ pub struct RegistryResolution {
     pub version: Version,
     /// The digest of the package contents.
     pub digest: ContentDigest,
+    /// The registry that served the package.
+    ///
+    /// This is only recorded for packages of namespaces with mirrors.
+    pub source: Option<String>,
     /// The client to use for fetching the package contents.
     client: Arc<CachingClient<FileCache>>,
+    /// The client of the mirror that served the package, if any.
+    mirror: Option<CachingClient<FileCache>>,
+    /// The cache directory of the clients.
+    cache_dir: PathBuf,
+    /// The policy for retrying the fetching of the package contents.
+    retry: RetryPolicy,
+    /// The cassette to record the package contents to or replay them from.
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl RegistryResolution {
+    /// Gets the content of the resolved package.
+    ///
+    /// The content is downloaded unless it is already in the cache. Downloaded
+    /// content is only written to the cache once it is complete and its digest
+    /// is verified, so that a failed attempt leaves nothing behind.
+    async fn content(&self) -> Result<Vec<u8>> {
+        if let Some(cassette) = self.replaying() {
+            return cassette.content(&self.digest);
+        }
+
+        let cache = FileCache::new(&self.cache_dir).await?;
+        if let Some(stream) = cache.get_data(&self.digest).await? {
+            let content = read_content(stream).await?;
+            if ContentDigest::from(Sha256::new_with_prefix(&content)) == self.digest {
+                return Ok(content);
+            }
+
+            log::debug!(
+                "cached content of package `{name}` v{version} is corrupt; downloading it again",
+                name = self.package,
+                version = self.version
+            );
+        }
+
+        let release = Release {
+            version: self.version.clone(),
+            content_digest: self.digest.clone(),
+        };
+        let client = self.mirror.as_ref().unwrap_or(&self.client).client()?;
+        let content = self
+            .retry
+            .run(
+                format_args!(
+                    "download content of package `{name}` v{version}",
+                    name = self.package,
+                    version = self.version
+                ),
+                || async {
+                    // Start over on every attempt as the content cannot be requested
+                    // from an offset
+                    read_content(client.stream_content(&self.package, &release).await?).await
+                },
+            )
+            .await?;
+
+        let digest = ContentDigest::from(Sha256::new_with_prefix(&content));
+        if digest != self.digest {
+            bail!(
+                "downloaded content of package `{name}` v{version} has digest `{digest}` but digest `{expected}` was expected",
+                name = self.package,
+                version = self.version,
+                expected = self.digest
+            );
+        }
+
+        let stream = ReaderStream::new(io::Cursor::new(content.clone()));
+        cache
+            .put_data(
+                self.digest.clone(),
+                Box::pin(stream.map_err(WasmPkgError::IoError)),
+            )
+            .await?;
+
+        if let Some(cassette) = &self.cassette {
+            cassette.record_content(&self.digest, &content)?;
+        }
+
+        Ok(content)
+    }
+
+    /// Gets the cassette the package contents are replayed from, if any.
+    fn replaying(&self) -> Option<&Cassette> {
+        replaying(&self.cassette)
+    }
+
+    /// Writes the WIT source of the resolved package to the cache so that
+    /// errors in the source can refer to a file.
+    ///
+    /// Returns the path of the written file.
+    async fn write_wit_source(&self, source: &str) -> Result<PathBuf> {
+        let dir = self
+            .cache_dir
+            .join("wit")
+            .join(self.package.namespace().as_ref())
+            .join(self.package.name().as_ref());
+        tokio::fs::create_dir_all(&dir).await.with_context(|| {
+            format!(
+                "failed to create WIT source directory `{dir}`",
+                dir = dir.display()
+            )
+        })?;
+
+        let path = dir.join(format!("{version}.wit", version = self.version));
+        tokio::fs::write(&path, source).await.with_context(|| {
+            format!("failed to write WIT source `{path}`", path = path.display())
+        })?;
+
+        Ok(path)
+    }
 }
 
 impl Debug for RegistryResolution {
@@ -213,6 +496,7 @@ impl Debug for RegistryResolution {
             .field("requirement", &self.requirement)
             .field("version", &self.version)
             .field("digest", &self.digest)
+            .field("source", &self.source)
             .finish()
     }
 }
@@ -265,8 +549,19 @@ impl DependencyResolution {
         }
     }
 
+    /// Fetches the content of the resolved dependency into the cache.
+    ///
+    /// This does nothing for dependencies resolved from a local path.
+    pub async fn fetch(&self) -> Result<()> {
+        if let Self::Registry(res) = self {
+            res.content().await?;
+        }
+
+        Ok(())
+    }
+
     /// Decodes the resolved dependency.
-    pub async fn decode(&self) -> Result<DecodedDependency> {
+    pub async fn decode(&self) -> Result<DecodedDependency<'_>> {
         // If the dependency path is a directory, assume it contains wit to parse as a package.
         let bytes = match self {
             DependencyResolution::Local(LocalResolution { path, .. })
@@ -288,41 +583,35 @@ impl DependencyResolution {
                     )
                 })?
             }
-            DependencyResolution::Registry(res) => {
-                let stream = res
-                    .client
-                    .get_content(
-                        &res.package,
-                        &Release {
-                            version: res.version.clone(),
-                            content_digest: res.digest.clone(),
-                        },
-                    )
-                    .await?;
-
-                let mut buf = Vec::new();
-                tokio_util::io::StreamReader::new(
-                    stream.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e)),
-                )
-                .read_to_end(&mut buf)
-                .await?;
-                buf
-            }
+            DependencyResolution::Registry(res) => res.content().await?,
         };
 
         if &bytes[0..4] != b"\0asm" {
+            let source = std::str::from_utf8(&bytes).with_context(|| {
+                format!(
+                    "dependency `{name}` is not UTF-8 encoded",
+                    name = self.name()
+                )
+            })?;
+
+            // Parse errors refer to the path of the source, so registry
+            // packages are written to the cache to give them a path
+            let path = match self {
+                DependencyResolution::Local(LocalResolution { path, .. }) => path.clone(),
+                DependencyResolution::Registry(res) => match res.write_wit_source(source).await {
+                    Ok(path) => path,
+                    Err(e) => {
+                        log::debug!("{e:?}");
+                        PathBuf::from(self.name().to_string())
+                    }
+                },
+            };
+
             return Ok(DecodedDependency::Wit {
                 resolution: self,
-                package: UnresolvedPackageGroup::parse(
-                    // This is fake, but it's needed for the parser to work.
-                    self.name().to_string(),
-                    std::str::from_utf8(&bytes).with_context(|| {
-                        format!(
-                            "dependency `{name}` is not UTF-8 encoded",
-                            name = self.name()
-                        )
-                    })?,
-                )?,
+                package: UnresolvedPackageGroup::parse(&path, source).with_context(|| {
+                    format!("failed to parse dependency `{name}`", name = self.name())
+                })?,
             });
         }
 
@@ -383,6 +672,37 @@ impl<'a> DecodedDependency<'a> {
         }
     }
 
+    /// Gets the resolution of the decoded dependency.
+    pub fn resolution(&self) -> &'a DependencyResolution {
+        match self {
+            Self::Wit { resolution, .. } | Self::Wasm { resolution, .. } => resolution,
+        }
+    }
+
+    /// Gets the number of items the decoded dependency adds to a resolve.
+    fn capacity(&self) -> Capacity {
+        match self {
+            Self::Wit { package, .. } => std::iter::once(&package.main)
+                .chain(&package.nested)
+                .map(|package| Capacity {
+                    worlds: package.worlds.len(),
+                    interfaces: package.interfaces.len(),
+                    types: package.types.len(),
+                    packages: 1,
+                })
+                .fold(Capacity::default(), Capacity::add),
+            Self::Wasm { decoded, .. } => {
+                let resolve = decoded.resolve();
+                Capacity {
+                    worlds: resolve.worlds.len(),
+                    interfaces: resolve.interfaces.len(),
+                    types: resolve.types.len(),
+                    packages: resolve.packages.len(),
+                }
+            }
+        }
+    }
+
     /// Gets the package name of the decoded dependency.
     pub fn package_name(&self) -> &PackageName {
         match self {
@@ -405,9 +725,166 @@ impl<'a> DecodedDependency<'a> {
     }
 }
 
+/// Merges decoded dependencies into a single resolve in one pass.
+///
+/// Decoded Wasm packages are merged first, in the order of their number of
+/// packages so that the full definitions of packages are merged before the
+/// partial copies of them in their dependents; ties keep the given order.
+/// Unresolved WIT packages are then pushed in the topological order of their
+/// foreign dependencies. The arenas of the resolve are sized for all of the
+/// decoded packages up front.
+///
+/// Returns the merged resolve and the source files of the WIT packages of
+/// dependencies resolved from a local path.
+pub fn merge_dependencies<'a>(
+    dependencies: impl IntoIterator<Item = DecodedDependency<'a>>,
+) -> Result<(Resolve, Vec<PathBuf>)> {
+    let mut deps = IndexMap::new();
+    for dependency in dependencies {
+        let name = dependency.package_name().clone();
+        if let Some(prev) = deps.insert(name, dependency) {
+            bail!(
+                "duplicate definitions of package `{name}` found while decoding dependency `{id}`",
+                name = prev.package_name(),
+                id = prev.resolution().name()
+            );
+        }
+    }
+
+    deps.sort_by_cached_key(|_, dependency| match dependency {
+        DecodedDependency::Wasm { decoded, .. } => (0, decoded.resolve().packages.len()),
+        DecodedDependency::Wit { .. } => (1, 0),
+    });
+
+    let mut order = IndexSet::new();
+    let mut visiting = HashSet::new();
+    for dep in deps.values() {
+        visit(dep, &deps, &mut order, &mut visiting)?;
+    }
+
+    assert!(visiting.is_empty());
+
+    let capacity = deps
+        .values()
+        .map(DecodedDependency::capacity)
+        .fold(Capacity::default(), Capacity::add);
+    let mut merged = Resolve {
+        worlds: Arena::with_capacity(capacity.worlds),
+        interfaces: Arena::with_capacity(capacity.interfaces),
+        types: Arena::with_capacity(capacity.types),
+        packages: Arena::with_capacity(capacity.packages),
+        ..Default::default()
+    };
+
+    let mut source_files = Vec::new();
+    for name in order {
+        match deps.swap_remove(&name).unwrap() {
+            DecodedDependency::Wit {
+                resolution,
+                package,
+            } => {
+                // The WIT of registry packages is only a copy in the cache
+                if let DependencyResolution::Local(_) = resolution {
+                    source_files.extend(package.source_map.source_files().map(Path::to_path_buf));
+                }
+
+                merged.push_group(package).with_context(|| {
+                    format!(
+                        "failed to merge dependency `{name}`",
+                        name = resolution.name()
+                    )
+                })?;
+            }
+            DecodedDependency::Wasm {
+                resolution,
+                decoded,
+            } => {
+                let resolve = match decoded {
+                    DecodedWasm::WitPackage(resolve, _) => resolve,
+                    DecodedWasm::Component(resolve, _) => resolve,
+                };
+
+                merged.merge(resolve).with_context(|| {
+                    format!(
+                        "failed to merge dependency `{name}`",
+                        name = resolution.name()
+                    )
+                })?;
+            }
+        }
+    }
+
+    return Ok((merged, source_files));
+
+    fn visit<'a>(
+        dep: &'a DecodedDependency<'a>,
+        deps: &'a IndexMap<PackageName, DecodedDependency>,
+        order: &mut IndexSet<PackageName>,
+        visiting: &mut HashSet<&'a PackageName>,
+    ) -> Result<()> {
+        if order.contains(dep.package_name()) {
+            return Ok(());
+        }
+
+        // Visit any unresolved foreign dependencies
+        match dep {
+            DecodedDependency::Wit {
+                package,
+                resolution,
+            } => {
+                for name in package.main.foreign_deps.keys() {
+                    if !visiting.insert(name) {
+                        bail!("foreign dependency `{name}` forms a dependency cycle while parsing dependency `{other}`", other = resolution.name());
+                    }
+
+                    // Only visit known dependencies
+                    // wit-parser will error on unknown foreign dependencies when
+                    // the package is resolved
+                    if let Some(dep) = deps.get(name) {
+                        visit(dep, deps, order, visiting)?
+                    }
+
+                    assert!(visiting.remove(name));
+                }
+            }
+            DecodedDependency::Wasm { .. } => {
+                // No unresolved foreign dependencies for decoded wasm files
+            }
+        }
+
+        assert!(order.insert(dep.package_name().clone()));
+
+        Ok(())
+    }
+}
+
+/// The number of items of each arena of a resolve.
+#[derive(Default, Clone, Copy)]
+struct Capacity {
+    worlds: usize,
+    interfaces: usize,
+    types: usize,
+    packages: usize,
+}
+
+impl Capacity {
+    fn add(self, other: Self) -> Self {
+        Self {
+            worlds: self.worlds + other.worlds,
+            interfaces: self.interfaces + other.interfaces,
+            types: self.types + other.types,
+            packages: self.packages + other.packages,
+        }
+    }
+}
+
 /// Used to resolve dependencies for a WIT package.
 pub struct DependencyResolver<'a> {
     client: Arc<CachingClient<FileCache>>,
+    retry: RetryPolicy,
+    cassette: Option<Arc<Cassette>>,
+    mirrors: Arc<HashMap<String, Vec<wasm_pkg_client::Registry>>>,
+    cache_dir: Option<PathBuf>,
     lock_file: Option<LockFileResolver<'a>>,
     registries: IndexMap<&'a str, Registry<'a>>,
     resolutions: HashMap<PackageRef, DependencyResolution>,
@@ -417,6 +894,9 @@ impl<'a> DependencyResolver<'a> {
     /// Creates a new dependency resolver. If `config` is `None`, then the resolver will be set to
     /// offline mode and a lock file must be given as well. Anything that will require network
     /// access will fail in offline mode.
+    ///
+    /// Registry operations are retried according to [`RetryPolicy::from_env`]
+    /// and recorded or replayed according to [`Cassette::from_env`].
     pub fn new(
         config: Option<Config>,
         lock_file: Option<LockFileResolver<'a>>,
@@ -428,6 +908,10 @@ impl<'a> DependencyResolver<'a> {
         let client = CachingClient::new(config.map(Client::new), cache);
         Ok(DependencyResolver {
             client: Arc::new(client),
+            retry: RetryPolicy::from_env()?,
+            cassette: Cassette::from_env()?.map(Arc::new),
+            mirrors: Default::default(),
+            cache_dir: None,
             lock_file,
             registries: Default::default(),
             resolutions: Default::default(),
@@ -437,6 +921,9 @@ impl<'a> DependencyResolver<'a> {
     /// Creates a new dependency resolver with the given client. This is useful when you already
     /// have a client available. If the client is set to offline mode, then a lock file must be
     /// given or this will error
+    ///
+    /// Registry operations are retried according to [`RetryPolicy::from_env`]
+    /// and recorded or replayed according to [`Cassette::from_env`].
     pub fn new_with_client(
         client: Arc<CachingClient<FileCache>>,
         lock_file: Option<LockFileResolver<'a>>,
@@ -446,12 +933,54 @@ impl<'a> DependencyResolver<'a> {
         }
         Ok(DependencyResolver {
             client,
+            retry: RetryPolicy::from_env()?,
+            cassette: Cassette::from_env()?.map(Arc::new),
+            mirrors: Default::default(),
+            cache_dir: None,
             lock_file,
             registries: Default::default(),
             resolutions: Default::default(),
         })
     }
 
+    /// Sets the policy for retrying failed registry operations.
+    pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the mirror registries to fall back to, in order, when the registry
+    /// of a package namespace is unreachable.
+    ///
+    /// The key to the map is the package namespace.
+    pub fn with_mirrors(
+        mut self,
+        mirrors: HashMap<String, Vec<wasm_pkg_client::Registry>>,
+    ) -> Self {
+        self.mirrors = Arc::new(mirrors);
+        self
+    }
+
+    /// Sets the cache directory of the client.
+    ///
+    /// Downloaded package contents and the packages served by mirror
+    /// registries are written to it so that they can be used offline.
+    ///
+    /// If not set, the cache directory is taken from the
+    /// `CARGO_COMPONENT_CACHE_DIR` environment variable.
+    pub fn with_cache_dir(mut self, cache_dir: PathBuf) -> Self {
+        self.cache_dir = Some(cache_dir);
+        self
+    }
+
+    /// Gets the cache directory of the client.
+    fn cache_dir(&self) -> Result<PathBuf> {
+        match &self.cache_dir {
+            Some(dir) => Ok(dir.clone()),
+            None => cache_dir(env::var_os(CACHE_DIR_ENV_VAR).map(PathBuf::from)),
+        }
+    }
+
     /// Add a dependency to the resolver.
     pub async fn add_dependency(
         &mut self,
@@ -475,10 +1004,15 @@ impl<'a> DependencyResolver<'a> {
                     _ => None,
                 };
 
+                let cache_dir = self.cache_dir()?;
                 let registry = match self.registries.entry(registry_name) {
                     indexmap::map::Entry::Occupied(e) => e.into_mut(),
                     indexmap::map::Entry::Vacant(e) => e.insert(Registry {
                         client: self.client.clone(),
+                        retry: self.retry.clone(),
+                        cassette: self.cassette.clone(),
+                        mirrors: self.mirrors.clone(),
+                        cache_dir,
                         packages: HashMap::new(),
                         dependencies: Vec::new(),
                     }),
@@ -490,6 +1024,13 @@ impl<'a> DependencyResolver<'a> {
             }
             Dependency::Local(p) => {
                 // A local path dependency, insert a resolution immediately
+                Terminal::current().verbose(|t| {
+                    t.status(
+                        "Resolved",
+                        format!("`{name}` to local path `{path}`", path = p.display()),
+                    )
+                })?;
+
                 let res = DependencyResolution::Local(LocalResolution {
                     name: name.clone(),
                     path: p.clone(),
@@ -519,9 +1060,14 @@ impl<'a> DependencyResolver<'a> {
             .into_values()
             .flat_map(|r| r.dependencies.into_iter())
             .map(|d| {
-                DependencyResolution::Registry(
-                    d.resolution.expect("dependency should have been resolved"),
-                )
+                let resolution = d.resolution.expect("dependency should have been resolved");
+                log::debug!(
+                    "resolved dependency `{name}` to `{package}` v{version}",
+                    name = resolution.name,
+                    package = resolution.package,
+                    version = resolution.version
+                );
+                DependencyResolution::Registry(resolution)
             })
         {
             let prev = self
@@ -534,9 +1080,47 @@ impl<'a> DependencyResolver<'a> {
     }
 }
 
+/// Finds the release of a registry package with the given content digest.
+///
+/// The releases are searched from the latest version down, including yanked
+/// releases, as a digest pins an exact release.
+pub async fn find_release_by_digest(
+    client: &CachingClient<FileCache>,
+    package: &PackageRef,
+    digest: &ContentDigest,
+) -> Result<Release> {
+    let retry = RetryPolicy::from_env()?;
+    let mut versions = retry
+        .run(format_args!("list versions of package `{package}`"), || {
+            client.list_all_versions(package)
+        })
+        .await
+        .with_context(|| format!("failed to list the versions of package `{package}`"))?;
+    versions.sort_by(|a, b| b.version.cmp(&a.version));
+
+    for VersionInfo { version, .. } in versions {
+        let release = retry
+            .run(
+                format_args!("fetch release v{version} of package `{package}`"),
+                || client.get_release(package, &version),
+            )
+            .await?;
+
+        if &release.content_digest == digest {
+            return Ok(release);
+        }
+    }
+
+    bail!("component registry package `{package}` has no release with digest `{digest}`")
+}
+
 struct Registry<'a> {
     client: Arc<CachingClient<FileCache>>,
-    packages: HashMap<PackageRef, Vec<VersionInfo>>,
+    retry: RetryPolicy,
+    cassette: Option<Arc<Cassette>>,
+    mirrors: Arc<HashMap<String, Vec<wasm_pkg_client::Registry>>>,
+    cache_dir: PathBuf,
+    packages: HashMap<PackageRef, (Option<Mirror>, Vec<VersionInfo>)>,
     dependencies: Vec<RegistryDependency<'a>>,
 }
 
@@ -552,7 +1136,7 @@ impl<'a> Registry<'a> {
             name,
             package: package.clone(),
             version,
-            locked: locked.map(|l| (l.version.clone(), l.digest.clone())),
+            locked: locked.map(|l| (l.version.clone(), l.digest.clone(), l.source.clone())),
             resolution: None,
         };
 
@@ -567,27 +1151,53 @@ impl<'a> Registry<'a> {
             // be worth replacing the mutable borrow with a RwLock down the line.
             let client = self.client.clone();
 
-            let (selected_version, digest) = if client.is_readonly() {
+            let (selected_version, digest, mirror) = if client.is_readonly() {
                 dependency
                     .locked
                     .as_ref()
-                    .map(|(ver, digest)| (ver, Some(digest)))
+                    .map(|(ver, digest, _)| (ver, Some(digest), None))
                     .ok_or_else(|| {
                         anyhow::anyhow!("Couldn't find locked dependency while in offline mode")
                     })?
             } else {
-                let versions =
-                    load_package(&mut self.packages, &self.client, dependency.package.clone())
-                        .await?
-                        .with_context(|| {
-                            format!(
-                                "package `{name}` was not found in component registry `{registry}`",
-                                name = dependency.package
-                            )
-                        })?;
+                let (mirror, versions) = load_package(
+                    &mut self.packages,
+                    &self.client,
+                    &self.retry,
+                    self.mirrors.get(dependency.package.namespace().as_ref()),
+                    &self.cache_dir,
+                    self.cassette.as_deref(),
+                    dependency.package.clone(),
+                )
+                .await?
+                .with_context(|| {
+                    format!(
+                        "package `{name}` was not found in component registry `{registry}`",
+                        name = dependency.package
+                    )
+                })?;
+
+                Terminal::current().very_verbose(|t| {
+                    t.status(
+                        "Considering",
+                        format!(
+                            "releases of `{name}`: {releases}",
+                            name = dependency.package,
+                            releases = versions
+                                .iter()
+                                .map(|v| if v.yanked {
+                                    format!("v{version} (yanked)", version = v.version)
+                                } else {
+                                    format!("v{version}", version = v.version)
+                                })
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    )
+                })?;
 
                 match &dependency.locked {
-                    Some((version, digest)) => {
+                    Some((version, digest, _)) => {
                         // The dependency had a lock file entry, so attempt to do an exact match first
                         let exact_req = VersionReq {
                             comparators: vec![Comparator {
@@ -603,17 +1213,95 @@ impl<'a> Registry<'a> {
                         // the version requirement; this can happen when packages are yanked. If we did
                         // find an exact match, return the digest for comparison after fetching the
                         // release
-                        find_latest_release(versions, &exact_req).map(|v| (&v.version, Some(digest))).or_else(|| find_latest_release(versions, dependency.version).map(|v| (&v.version, None)))
+                        find_latest_release(versions, &exact_req).map(|v| (&v.version, Some(digest), mirror.clone())).or_else(|| find_latest_release(versions, dependency.version).map(|v| (&v.version, None, mirror.clone())))
                     }
-                    None => find_latest_release(versions, dependency.version).map(|v| (&v.version, None)),
+                    None => find_latest_release(versions, dependency.version).map(|v| (&v.version, None, mirror.clone())),
                 }.with_context(|| format!("component registry package `{name}` has no release matching version requirement `{version}`", name = dependency.package, version = dependency.version))?
             };
 
+            Terminal::current().verbose(|t| {
+                t.status(
+                    "Resolved",
+                    format!(
+                        "`{name}` to v{selected_version}{mirror} ({reason})",
+                        name = dependency.package,
+                        mirror = match &mirror {
+                            Some(mirror) => format!(" from mirror `{}`", mirror.registry),
+                            None => String::new(),
+                        },
+                        reason = match (&dependency.locked, digest) {
+                            (Some(_), Some(_)) => "the locked version".to_string(),
+                            (Some((locked, ..)), None) => format!(
+                                "the latest release matching `{req}`; locked version \
+                                 v{locked} is unavailable",
+                                req = dependency.version
+                            ),
+                            (None, _) => format!(
+                                "the latest release matching `{req}`",
+                                req = dependency.version
+                            ),
+                        }
+                    ),
+                )
+            })?;
+
             // We need to clone a handle to the client because we mutably borrow self above. Might
             // be worth replacing the mutable borrow with a RwLock down the line.
-            let release = client
-                .get_release(&dependency.package, selected_version)
-                .await?;
+            let release = match replaying(&self.cassette) {
+                Some(cassette) => cassette.release(&dependency.package, selected_version)?,
+                None => {
+                    let release = self
+                        .retry
+                        .run(
+                            format_args!(
+                                "fetch release v{selected_version} of package `{name}`",
+                                name = dependency.package
+                            ),
+                            || async {
+                                match &mirror {
+                                    Some(mirror) => {
+                                        mirror
+                                            .client
+                                            .get_release(&dependency.package, selected_version)
+                                            .await
+                                    }
+                                    None => {
+                                        client
+                                            .get_release(&dependency.package, selected_version)
+                                            .await
+                                    }
+                                }
+                            },
+                        )
+                        .await?;
+
+                    if let Some(cassette) = &self.cassette {
+                        cassette.record_release(&dependency.package, &release)?;
+                    }
+
+                    release
+                }
+            };
+
+            let source = if !self
+                .mirrors
+                .contains_key(dependency.package.namespace().as_ref())
+            {
+                None
+            } else if let Some(mirror) = &mirror {
+                Some(mirror.registry.to_string())
+            } else if client.is_readonly() {
+                dependency
+                    .locked
+                    .as_ref()
+                    .and_then(|(_, _, source)| source.clone())
+            } else {
+                client
+                    .client()?
+                    .config()
+                    .resolve_registry(&dependency.package)
+                    .map(ToString::to_string)
+            };
             if let Some(digest) = digest {
                 if &release.content_digest != digest {
                     bail!(
@@ -636,7 +1324,12 @@ impl<'a> Registry<'a> {
                 requirement: dependency.version.clone(),
                 version: release.version.clone(),
                 digest: release.content_digest.clone(),
+                source,
                 client: self.client.clone(),
+                mirror: mirror.map(|m| m.client),
+                cache_dir: self.cache_dir.clone(),
+                retry: self.retry.clone(),
+                cassette: self.cassette.clone(),
             });
         }
 
@@ -644,19 +1337,153 @@ impl<'a> Registry<'a> {
     }
 }
 
+/// Represents a mirror registry that served a package.
+///
+/// The client of the mirror shares the cache of the registry client.
+#[derive(Clone)]
+struct Mirror {
+    registry: wasm_pkg_client::Registry,
+    client: CachingClient<FileCache>,
+}
+
 async fn load_package<'b>(
-    packages: &'b mut HashMap<PackageRef, Vec<VersionInfo>>,
+    packages: &'b mut HashMap<PackageRef, (Option<Mirror>, Vec<VersionInfo>)>,
     client: &CachingClient<FileCache>,
+    retry: &RetryPolicy,
+    mirrors: Option<&Vec<wasm_pkg_client::Registry>>,
+    cache_dir: &Path,
+    cassette: Option<&Cassette>,
     package: PackageRef,
-) -> Result<Option<&'b Vec<VersionInfo>>> {
-    match packages.entry(package) {
-        hash_map::Entry::Occupied(e) => Ok(Some(e.into_mut())),
-        hash_map::Entry::Vacant(e) => match client.list_all_versions(e.key()).await {
-            Ok(p) => Ok(Some(e.insert(p))),
-            Err(WasmPkgError::PackageNotFound) => Ok(None),
-            Err(err) => Err(err.into()),
-        },
+) -> Result<Option<&'b (Option<Mirror>, Vec<VersionInfo>)>> {
+    let e = match packages.entry(package) {
+        hash_map::Entry::Occupied(e) => return Ok(Some(e.into_mut())),
+        hash_map::Entry::Vacant(e) => e,
+    };
+
+    let loaded = match cassette {
+        Some(cassette) if cassette.mode() == CassetteMode::Replay => {
+            cassette.versions(e.key())?.map(|versions| (None, versions))
+        }
+        _ => {
+            let loaded = list_versions(client, retry, mirrors, cache_dir, e.key()).await?;
+            if let Some(cassette) = cassette {
+                cassette.record_versions(e.key(), loaded.as_ref().map(|(_, v)| v.as_slice()))?;
+            }
+            loaded
+        }
+    };
+
+    Ok(loaded.map(|loaded| &*e.insert(loaded)))
+}
+
+/// Lists the versions of a package, falling back to the mirrors of its
+/// namespace if its registry is unreachable.
+///
+/// Every mirror is tried after the first transient failure of the registry;
+/// the retry policy applies to rounds over the registry and all its mirrors.
+///
+/// Returns `None` if the package was not found.
+async fn list_versions(
+    client: &CachingClient<FileCache>,
+    retry: &RetryPolicy,
+    mirrors: Option<&Vec<wasm_pkg_client::Registry>>,
+    cache_dir: &Path,
+    package: &PackageRef,
+) -> Result<Option<(Option<Mirror>, Vec<VersionInfo>)>> {
+    let registries = match mirrors {
+        Some(mirrors) if !mirrors.is_empty() => mirrors,
+        _ => {
+            return match retry
+                .run(format_args!("list versions of package `{package}`"), || {
+                    client.list_all_versions(package)
+                })
+                .await
+            {
+                Ok(p) => Ok(Some((None, p))),
+                Err(WasmPkgError::PackageNotFound) => Ok(None),
+                Err(err) => Err(err.into()),
+            };
+        }
+    };
+
+    // Packages served by a mirror are cached with those of the registry
+    let mut mirrors = Vec::with_capacity(registries.len());
+    for registry in registries {
+        let mut config = client.client()?.config().clone();
+        config.set_namespace_registry(
+            package.namespace().clone(),
+            RegistryMapping::Registry(registry.clone()),
+        );
+        mirrors.push(Mirror {
+            registry: registry.clone(),
+            client: CachingClient::new(Some(Client::new(config)), FileCache::new(cache_dir).await?),
+        });
     }
+
+    // The timeout applies to every registry of a round rather than the round
+    let round = RetryPolicy {
+        timeout: None,
+        ..retry.clone()
+    };
+    let result = round
+        .run(
+            format_args!("list versions of package `{package}` from any of its registries"),
+            || async {
+                let mut err = match retry.attempt(client.list_all_versions(package)).await {
+                    Ok(p) => return Ok((None, p)),
+                    Err(err) if is_retryable(&err) => err,
+                    Err(err) => return Err(err),
+                };
+
+                for mirror in &mirrors {
+                    Terminal::current()
+                        .warn(format!(
+                            "the registry of package `{package}` is unreachable; falling back to mirror `{registry}`",
+                            registry = mirror.registry
+                        ))
+                        .ok();
+
+                    match retry.attempt(mirror.client.list_all_versions(package)).await {
+                        Ok(p) => return Ok((Some(mirror.clone()), p)),
+                        Err(e) if is_retryable(&e) => err = e,
+                        Err(e) => return Err(e),
+                    }
+                }
+
+                Err(err)
+            },
+        )
+        .await;
+
+    match result {
+        Ok(p) => Ok(Some(p)),
+        Err(WasmPkgError::PackageNotFound) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Reads the content of a package from a stream.
+///
+/// If the stream fails, the error reports the number of bytes received.
+async fn read_content(stream: ContentStream) -> Result<Vec<u8>, WasmPkgError> {
+    let mut buf = Vec::new();
+    StreamReader::new(stream.map_err(io::Error::other))
+        .read_to_end(&mut buf)
+        .await
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("{e} after receiving {len} bytes", len = buf.len()),
+            )
+        })?;
+    Ok(buf)
+}
+
+/// Gets the given cassette if it is being replayed.
+fn replaying(cassette: &Option<Arc<Cassette>>) -> Option<&Cassette> {
+    cassette
+        .as_deref()
+        .filter(|c| c.mode() == CassetteMode::Replay)
 }
 
 struct RegistryDependency<'a> {
@@ -665,7 +1492,7 @@ struct RegistryDependency<'a> {
     /// The package name of the registry package.
     package: PackageRef,
     version: &'a VersionReq,
-    locked: Option<(Version, ContentDigest)>,
+    locked: Option<(Version, ContentDigest, Option<String>)>,
     resolution: Option<RegistryResolution>,
 }
 
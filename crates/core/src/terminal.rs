@@ -6,9 +6,11 @@ use anyhow::{bail, Result};
 use owo_colors::{AnsiColors, OwoColorize};
 use std::{
     cell::RefCell,
-    fmt,
-    io::{stderr, stdout, IsTerminal, Write},
+    env, fmt,
+    io::{stderr, stdin, stdout, BufRead, IsTerminal, Write},
+    path::Path,
     str::FromStr,
+    sync::Mutex,
 };
 
 pub use owo_colors::AnsiColors as Colors;
@@ -49,10 +51,44 @@ impl fmt::Display for Color {
     }
 }
 
+/// The supported formats of diagnostic output.
+#[derive(Default, Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum OutputFormat {
+    /// Print diagnostics for humans.
+    #[default]
+    Human,
+    /// Also print diagnostics as GitHub Actions workflow commands so that
+    /// they are shown as annotations.
+    Github,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "human" => Ok(Self::Human),
+            "github" => Ok(Self::Github),
+            _ => bail!("argument for --output-format must be human or github, but found `{value}`"),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Human => write!(f, "human"),
+            Self::Github => write!(f, "github"),
+        }
+    }
+}
+
 /// The requested verbosity of output.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Verbosity {
-    /// Verbose output.
+    /// Very verbose output (`-vv`).
+    VeryVerbose,
+    /// Verbose output (`-v`).
     Verbose,
     /// Normal output.
     Normal,
@@ -60,9 +96,29 @@ pub enum Verbosity {
     Quiet,
 }
 
+impl Verbosity {
+    /// Gets the verbosity requested by the `--quiet` flag and the number of
+    /// `--verbose` flags, as cargo does.
+    pub fn from_flags(quiet: bool, verbose: usize) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::VeryVerbose,
+        }
+    }
+}
+
+/// The verbosity and color of the most recently created terminal.
+///
+/// Code that prints without access to the terminal of the command, such as
+/// the registry operations, uses [`Terminal::current`] to respect them.
+static CURRENT: Mutex<Option<(Verbosity, Color)>> = Mutex::new(None);
+
 pub(crate) struct TerminalState {
     pub(crate) output: Output,
     verbosity: Verbosity,
+    output_format: OutputFormat,
     pub(crate) needs_clear: bool,
 }
 
@@ -104,12 +160,28 @@ pub struct Terminal(RefCell<TerminalState>);
 impl Terminal {
     /// Creates a new terminal with the given verbosity and color.
     pub fn new(verbosity: Verbosity, color: Color) -> Self {
+        *CURRENT.lock().unwrap() = Some((verbosity, color));
+        Self::with_verbosity(verbosity, color)
+    }
+
+    /// Creates a new terminal with the verbosity and color of the most
+    /// recently created terminal.
+    pub fn current() -> Self {
+        let (verbosity, color) = CURRENT
+            .lock()
+            .unwrap()
+            .unwrap_or((Verbosity::Normal, Color::Auto));
+        Self::with_verbosity(verbosity, color)
+    }
+
+    fn with_verbosity(verbosity: Verbosity, color: Color) -> Self {
         Self(RefCell::new(TerminalState {
             output: Output::Stream {
                 is_terminal: stderr().is_terminal(),
                 color,
             },
             verbosity,
+            output_format: OutputFormat::default(),
             needs_clear: false,
         }))
     }
@@ -118,11 +190,40 @@ impl Terminal {
     pub fn from_write(out: Box<dyn Write>) -> Self {
         Self(RefCell::new(TerminalState {
             output: Output::Write(out),
-            verbosity: Verbosity::Verbose,
+            verbosity: Verbosity::VeryVerbose,
+            output_format: OutputFormat::default(),
             needs_clear: false,
         }))
     }
 
+    /// Sets the format of the diagnostics printed by the terminal.
+    pub fn with_output_format(mut self, format: OutputFormat) -> Self {
+        self.0.get_mut().output_format = format;
+        self
+    }
+
+    /// Runs the given callback only if the terminal is verbose (`-v`).
+    pub fn verbose<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnOnce(&Self) -> Result<()>,
+    {
+        match self.verbosity() {
+            Verbosity::Verbose | Verbosity::VeryVerbose => callback(self),
+            _ => Ok(()),
+        }
+    }
+
+    /// Runs the given callback only if the terminal is very verbose (`-vv`).
+    pub fn very_verbose<F>(&self, callback: F) -> Result<()>
+    where
+        F: FnOnce(&Self) -> Result<()>,
+    {
+        match self.verbosity() {
+            Verbosity::VeryVerbose => callback(self),
+            _ => Ok(()),
+        }
+    }
+
     /// Prints a green 'status' message.
     pub fn status<T, U>(&self, status: T, message: U) -> Result<()>
     where
@@ -187,7 +288,16 @@ impl Terminal {
             &status
         };
 
-        self.print(status, Some(&message), false)
+        self.print(status, Some(&message), false)?;
+
+        let mut state = self.0.borrow_mut();
+        if state.output_format == OutputFormat::Github && state.verbosity != Verbosity::Quiet {
+            state
+                .output
+                .write_stdout(annotation("warning", &message.to_string()), None)?;
+        }
+
+        Ok(())
     }
 
     /// Prints a red 'error' message.
@@ -204,7 +314,38 @@ impl Terminal {
         // This doesn't call print as errors are always printed even when quiet
         let mut state = self.0.borrow_mut();
         state.clear_stderr();
-        state.output.print(status, Some(&message), false)
+        state.output.print(status, Some(&message), false)?;
+
+        if state.output_format == OutputFormat::Github {
+            state
+                .output
+                .write_stdout(annotation("error", &message.to_string()), None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Asks the user a yes or no question, defaulting to no.
+    ///
+    /// Returns `None` without asking when the terminal is quiet, does not
+    /// write to stderr, or stdin is not a terminal.
+    pub fn confirm<T: fmt::Display>(&self, question: T) -> Result<Option<bool>> {
+        let mut state = self.0.borrow_mut();
+        if state.verbosity == Verbosity::Quiet
+            || !matches!(state.output, Output::Stream { .. })
+            || !stdin().is_terminal()
+        {
+            return Ok(None);
+        }
+
+        state.clear_stderr();
+        let mut stderr = stderr();
+        write!(stderr, "{question} [y/N] ")?;
+        stderr.flush()?;
+
+        let mut answer = String::new();
+        stdin().lock().read_line(&mut answer)?;
+        Ok(Some(matches!(answer.trim(), "y" | "Y" | "yes" | "Yes")))
     }
 
     /// Write a styled fragment to stdout.
@@ -253,6 +394,102 @@ impl Terminal {
     }
 }
 
+/// The source location of a diagnostic.
+#[derive(Debug, Default, PartialEq)]
+struct Location<'a> {
+    file: &'a str,
+    line: Option<u32>,
+    col: Option<u32>,
+}
+
+/// Finds the source location of a diagnostic from its message.
+///
+/// WIT parse errors point at their location with a `--> <file>:<line>:<col>`
+/// line; other diagnostics refer to the manifest or WIT file they concern in
+/// backticks, possibly with the line and column of a TOML parse error.
+fn find_location(message: &str) -> Option<Location<'_>> {
+    for line in message.lines() {
+        let Some(rest) = line.trim_start().strip_prefix("--> ") else {
+            continue;
+        };
+
+        let mut parts = rest.rsplitn(3, ':');
+        if let (Some(col), Some(line), Some(file)) = (parts.next(), parts.next(), parts.next()) {
+            if let (Ok(line), Ok(col)) = (line.parse(), col.parse()) {
+                return Some(Location {
+                    file,
+                    line: Some(line),
+                    col: Some(col),
+                });
+            }
+        }
+    }
+
+    let file = message
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .find(|s| s.ends_with(".toml") || s.ends_with(".wit"))?;
+
+    let (line, col) = message
+        .split_once("at line ")
+        .and_then(|(_, rest)| {
+            let (line, rest) = rest.split_once(", column ")?;
+            let col = rest.split(|c: char| !c.is_ascii_digit()).next()?;
+            Some((line.parse().ok()?, col.parse().ok()?))
+        })
+        .unzip();
+
+    Some(Location { file, line, col })
+}
+
+/// Escapes the data of a GitHub Actions workflow command.
+fn escape_data(s: &str) -> String {
+    s.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Escapes a property of a GitHub Actions workflow command.
+fn escape_property(s: &str) -> String {
+    escape_data(s).replace(':', "%3A").replace(',', "%2C")
+}
+
+/// Formats a diagnostic as a GitHub Actions workflow command.
+///
+/// Files are made relative to the current directory so that they resolve
+/// against the checkout of the repository.
+fn annotation(kind: &str, message: &str) -> String {
+    let mut properties = Vec::new();
+    if let Some(location) = find_location(message) {
+        let file = env::current_dir()
+            .ok()
+            .and_then(|cwd| {
+                Path::new(location.file)
+                    .strip_prefix(cwd)
+                    .ok()
+                    .map(|p| p.display().to_string())
+            })
+            .unwrap_or_else(|| location.file.to_string());
+        properties.push(format!("file={file}", file = escape_property(&file)));
+
+        if let Some(line) = location.line {
+            properties.push(format!("line={line}"));
+        }
+
+        if let Some(col) = location.col {
+            properties.push(format!("col={col}"));
+        }
+    }
+
+    format!(
+        "::{kind}{space}{properties}::{message}\n",
+        space = if properties.is_empty() { "" } else { " " },
+        properties = properties.join(","),
+        message = escape_data(message.trim_end())
+    )
+}
+
 /// A `Write`able object, either with or without color support.
 pub(crate) enum Output {
     /// A plain write object without color support.
@@ -446,3 +683,89 @@ mod imp {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_find_location() {
+        assert_eq!(
+            find_location(
+                "failed to parse WIT\n\nCaused by:\n    expected `;`, found `}`\n     --> /ws/wit/world.wit:3:5\n      |"
+            ),
+            Some(Location {
+                file: "/ws/wit/world.wit",
+                line: Some(3),
+                col: Some(5),
+            })
+        );
+        assert_eq!(
+            find_location(
+                "failed to parse manifest at `/ws/Cargo.toml`\n\nCaused by:\n  TOML parse error at line 7, column 12"
+            ),
+            Some(Location {
+                file: "/ws/Cargo.toml",
+                line: Some(7),
+                col: Some(12),
+            })
+        );
+        assert_eq!(
+            find_location("invalid `features.wasm` table in `/ws/Cargo.toml`"),
+            Some(Location {
+                file: "/ws/Cargo.toml",
+                line: None,
+                col: None,
+            })
+        );
+        assert_eq!(find_location("failed to spawn `cargo`"), None);
+    }
+
+    #[test]
+    fn test_verbosity_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 3), Verbosity::VeryVerbose);
+        assert_eq!(Verbosity::from_flags(true, 2), Verbosity::Quiet);
+    }
+
+    #[test]
+    fn test_verbose_callbacks() {
+        let terminal = Terminal::with_verbosity(Verbosity::Verbose, Color::Never);
+        let mut called = Vec::new();
+        terminal
+            .verbose(|_| {
+                called.push("verbose");
+                Ok(())
+            })
+            .unwrap();
+        terminal
+            .very_verbose(|_| {
+                called.push("very verbose");
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(called, ["verbose"]);
+    }
+
+    #[test]
+    fn test_confirm_without_interaction() {
+        let terminal = Terminal::with_verbosity(Verbosity::Quiet, Color::Never);
+        assert_eq!(terminal.confirm("continue?").unwrap(), None);
+
+        let terminal = Terminal::from_write(Box::new(Vec::new()));
+        assert_eq!(terminal.confirm("continue?").unwrap(), None);
+    }
+
+    #[test]
+    fn test_annotation() {
+        assert_eq!(
+            annotation("warning", "100% done\nnext line"),
+            "::warning::100%25 done%0Anext line\n"
+        );
+        assert_eq!(
+            annotation("error", "bad value in `a,b:c.toml` at line 2, column 3\n"),
+            "::error file=a%2Cb%3Ac.toml,line=2,col=3::bad value in `a,b:c.toml` at line 2, column 3\n"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Module for running a throwaway registry in end-to-end tests.
+//!
+//! This module is only available with the `testing` feature enabled.
+//!
+//! [`spawn_registry`] starts an in-process warg registry on a random local
+//! port and writes the configuration files needed to publish to and fetch from
+//! it with `cargo component`.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use indexmap::IndexSet;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use warg_client::{
+    storage::{PublishEntry, PublishInfo},
+    FileSystemClient,
+};
+use warg_crypto::signing::PrivateKey;
+use warg_protocol::{operator::NamespaceState, registry::PackageName};
+use warg_server::{policy::content::WasmContentPolicy, Config, Server};
+use wasm_pkg_client::{Registry, RegistryMapping};
+
+/// The name of the warg client configuration file of a test registry.
+pub const WARG_CONFIG_NAME: &str = "warg-config.json";
+
+/// The name of the wasm-pkg-tools configuration file of a test registry.
+pub const WASM_PKG_CONFIG_NAME: &str = "wasm-pkg-config.json";
+
+/// Gets the operator key of test registries.
+pub fn test_operator_key() -> &'static str {
+    "ecdsa-p256:I+UlDo0HxyBBFeelhPPWmD+LnklOpqZDkrFP5VduASk="
+}
+
+/// Gets the key used to sign packages published to test registries.
+pub fn test_signing_key() -> &'static str {
+    "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew="
+}
+
+/// Represents a registry running as a background task.
+///
+/// The registry is shut down when dropped.
+pub struct TestRegistry {
+    task: Option<JoinHandle<()>>,
+    shutdown: CancellationToken,
+    root: PathBuf,
+    config: wasm_pkg_client::Config,
+    registry: Registry,
+}
+
+impl TestRegistry {
+    /// Gets the directory containing the state and configuration files of the
+    /// registry.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Gets the client configuration to use to access the registry.
+    pub fn config(&self) -> &wasm_pkg_client::Config {
+        &self.config
+    }
+
+    /// Gets the registry to use to access the registry.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Gets the path to the client configuration file of the registry.
+    ///
+    /// Setting the `CARGO_COMPONENT_CONFIG_FILE` environment variable to this
+    /// path makes `cargo component` use the registry.
+    pub fn config_file(&self) -> PathBuf {
+        self.root.join(WASM_PKG_CONFIG_NAME)
+    }
+
+    /// Disables automatic package initialization in the warg client
+    /// configuration of the registry.
+    pub fn disable_auto_package_init(&self) -> Result<()> {
+        let path = self.root.join(WARG_CONFIG_NAME);
+        let mut config = warg_client::Config::from_file(&path)?;
+        config.disable_auto_package_init = true;
+        config.write_to_file(&path)
+    }
+
+    /// Yanks a release of a package published to the registry.
+    pub async fn yank(&self, package: &str, version: &str) -> Result<()> {
+        let config = warg_client::Config::from_file(self.root.join(WARG_CONFIG_NAME))?;
+        let client = FileSystemClient::new_with_config(None, &config, None).await?;
+        let name: PackageName = package
+            .parse()
+            .with_context(|| format!("invalid package name `{package}`"))?;
+
+        let record = client
+            .publish_with_info(
+                &PrivateKey::decode(test_signing_key().to_string())?,
+                PublishInfo {
+                    name: name.clone(),
+                    head: None,
+                    entries: vec![PublishEntry::Yank {
+                        version: version
+                            .parse()
+                            .with_context(|| format!("invalid version `{version}`"))?,
+                    }],
+                },
+            )
+            .await
+            .with_context(|| format!("failed to yank v{version} of package `{package}`"))?;
+        client
+            .wait_for_publish(&name, &record, Duration::from_millis(100))
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Drop for TestRegistry {
+    fn drop(&mut self) {
+        futures::executor::block_on(async move {
+            self.shutdown.cancel();
+            self.task.take().unwrap().await.ok();
+        });
+    }
+}
+
+/// Spawns a registry as a background task of the current tokio runtime.
+///
+/// The state and configuration files of the registry are written to the given
+/// directory, which must outlive the returned registry.
+///
+/// The `test` namespace and the given additional namespaces are defined in the
+/// registry and mapped to it in the client configuration.
+pub async fn spawn_registry<I, S>(
+    root: impl Into<PathBuf>,
+    additional_namespaces: I,
+) -> Result<TestRegistry>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let root = root.into();
+    let additional_namespaces = additional_namespaces
+        .into_iter()
+        .map(|ns| ns.as_ref().to_string())
+        .collect::<Vec<_>>();
+
+    let shutdown = CancellationToken::new();
+    let config = Config::new(
+        PrivateKey::decode(test_operator_key().to_string())?,
+        Some(
+            std::iter::once("test".to_string())
+                .chain(additional_namespaces.iter().cloned())
+                .map(|ns| (ns, NamespaceState::Defined))
+                .collect(),
+        ),
+        root.join("server"),
+    )
+    .with_addr(([127, 0, 0, 1], 0))
+    .with_shutdown(shutdown.clone().cancelled_owned())
+    .with_checkpoint_interval(Duration::from_millis(100))
+    .with_content_policy(WasmContentPolicy::default());
+
+    let server = Server::new(config)
+        .initialize()
+        .await
+        .context("failed to initialize test registry")?;
+    let addr = server.local_addr()?;
+
+    let task = tokio::spawn(async move {
+        server.serve().await.unwrap();
+    });
+
+    let warg_config = warg_client::Config {
+        home_url: Some(format!("http://{addr}")),
+        registries_dir: Some(root.join("registries")),
+        content_dir: Some(root.join("content")),
+        namespace_map_path: Some(root.join("namespaces")),
+        keys: IndexSet::new(),
+        keyring_auth: false,
+        keyring_backend: None,
+        ignore_federation_hints: false,
+        disable_auto_accept_federation_hints: false,
+        disable_auto_package_init: false,
+        disable_interactive: true,
+    };
+
+    let config_file = root.join(WARG_CONFIG_NAME);
+    warg_config.write_to_file(&config_file)?;
+
+    let mut config = wasm_pkg_client::Config::default();
+    // We should probably update wasm-pkg-tools to use http for "localhost" or "127.0.0.1"
+    let registry: Registry = format!("localhost:{}", addr.port()).parse().unwrap();
+    let registry_mapping = RegistryMapping::Registry(registry.clone());
+    config.set_namespace_registry("test".parse().unwrap(), registry_mapping.clone());
+    for ns in &additional_namespaces {
+        config.set_namespace_registry(
+            ns.parse()
+                .with_context(|| format!("invalid namespace `{ns}`"))?,
+            registry_mapping.clone(),
+        );
+    }
+    let reg_conf = config.get_or_insert_registry_config_mut(&registry);
+    reg_conf.set_default_backend(Some("warg".to_string()));
+    reg_conf
+        .set_backend_config(
+            "warg",
+            wasm_pkg_client::warg::WargRegistryConfig {
+                client_config: warg_config,
+                auth_token: None,
+                signing_key: Some(Arc::new(test_signing_key().to_string().try_into()?)),
+                config_file: Some(config_file),
+            },
+        )
+        .expect("Should be able to set backend config");
+
+    config.to_file(root.join(WASM_PKG_CONFIG_NAME)).await?;
+
+    Ok(TestRegistry {
+        task: Some(task),
+        shutdown,
+        root,
+        config,
+        registry,
+    })
+}
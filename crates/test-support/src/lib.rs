@@ -0,0 +1,540 @@
+//! Support for writing integration tests against cargo-component.
+//!
+//! This crate provides the helpers used by the cargo-component test suite:
+//! spawning a throwaway warg registry, publishing packages to it, creating
+//! projects with `cargo component new` and validating built components.
+//!
+//! Tests of registry-dependent behavior can use [`spawn_recorded_server`] to
+//! replay the registry interactions recorded in a cassette instead of
+//! spawning a registry.
+//!
+//! The helpers run the `cargo-component` binary found next to the directory
+//! of the running test executable, as cargo places it when building the
+//! integration tests of a crate depending on `cargo-component`.
+
+#![deny(missing_docs)]
+use std::{
+    ffi::OsStr,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    rc::Rc,
+};
+
+use anyhow::{bail, Context, Result};
+use assert_cmd::prelude::OutputAssertExt;
+use cargo_component_core::{
+    cassette::{CassetteMode, CASSETTE_FILE_NAME},
+    command::{
+        CACHE_DIR_ENV_VAR, CONFIG_FILE_ENV_VAR, REGISTRY_CASSETTE_ENV_VAR,
+        REGISTRY_CASSETTE_MODE_ENV_VAR,
+    },
+    testing::{spawn_registry, TestRegistry, WASM_PKG_CONFIG_NAME},
+};
+use tempfile::TempDir;
+use toml_edit::DocumentMut;
+use wasm_pkg_client::{Client, PackageRef, PublishOpts, Registry};
+use wasmparser::{Chunk, Encoding, Parser, Payload, Validator};
+use wit_parser::{Resolve, UnresolvedPackageGroup};
+
+pub use cargo_component_core::testing::{test_operator_key, test_signing_key};
+
+/// Creates a command running `cargo component` with the given arguments.
+pub fn cargo_component<I, S>(args: I) -> Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut exe = std::env::current_exe().unwrap();
+    exe.pop(); // remove test exe name
+    exe.pop(); // remove `deps`
+    exe.push("cargo-component");
+    exe.set_extension(std::env::consts::EXE_EXTENSION);
+
+    let mut cmd = Command::new(&exe);
+    cmd.arg("component");
+    cmd.args(args);
+
+    cmd
+}
+
+/// Publishes the given content as a release of a package.
+pub async fn publish(
+    config: wasm_pkg_client::Config,
+    name: &PackageRef,
+    version: &str,
+    content: Vec<u8>,
+) -> Result<()> {
+    let client = Client::new(config);
+
+    client
+        .publish_release_data(
+            Box::pin(std::io::Cursor::new(content)),
+            PublishOpts {
+                package: Some((name.to_owned(), version.parse().unwrap())),
+                ..Default::default()
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Publishes a component, given in the WebAssembly text format, as a release of a package.
+pub async fn publish_component(
+    config: wasm_pkg_client::Config,
+    id: &str,
+    version: &str,
+    wat: &str,
+) -> Result<()> {
+    publish(
+        config,
+        &id.parse()?,
+        version,
+        wat::parse_str(wat).context("failed to parse component for publishing")?,
+    )
+    .await
+}
+
+/// Publishes a WIT package as a release of a package.
+pub async fn publish_wit(
+    config: wasm_pkg_client::Config,
+    id: &str,
+    version: &str,
+    wit: &str,
+) -> Result<()> {
+    publish_wit_with_deps(config, id, version, wit, &[]).await
+}
+
+/// Publishes a WIT package that references the given dependency packages.
+pub async fn publish_wit_with_deps(
+    config: wasm_pkg_client::Config,
+    id: &str,
+    version: &str,
+    wit: &str,
+    deps: &[&str],
+) -> Result<()> {
+    let mut resolve = Resolve::new();
+    for dep in deps {
+        resolve
+            .push_group(
+                UnresolvedPackageGroup::parse(Path::new("dep.wit"), dep)
+                    .context("failed to parse wit dependency for publishing")?,
+            )
+            .context("failed to resolve wit dependency for publishing")?;
+    }
+
+    let pkg = resolve
+        .push_group(
+            UnresolvedPackageGroup::parse(Path::new("foo.wit"), wit)
+                .context("failed to parse wit for publishing")?,
+        )
+        .context("failed to resolve wit for publishing")?;
+
+    let bytes =
+        wit_component::encode(&resolve, pkg).context("failed to encode wit for publishing")?;
+
+    publish(config, &id.parse()?, version, bytes).await
+}
+
+/// Represents a test registry running as a background task.
+///
+/// The registry is shut down when the instance is dropped.
+pub struct ServerInstance {
+    // Declared before `root` so the registry shuts down before its directory is removed
+    registry: TestRegistry,
+    root: Rc<TempDir>,
+}
+
+impl ServerInstance {
+    /// Returns a `Project` that is configured to use the server instance with the correct config.
+    pub fn project<I, S>(&self, name: &str, lib: bool, additional_args: I) -> Result<Project>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let proj = Project {
+            dir: self.root.clone(),
+            root: self.root.path().join(name),
+            config_file: Some(self.registry.config_file()),
+            cassette: None,
+        };
+
+        proj.new_inner(name, lib, additional_args)?;
+        Ok(proj)
+    }
+
+    /// Disables automatic package initialization in the server's warg client config.
+    pub fn disable_auto_package_init(&self) -> Result<()> {
+        self.registry.disable_auto_package_init()
+    }
+
+    /// Gets the client configuration to use to access the server instance.
+    pub fn config(&self) -> wasm_pkg_client::Config {
+        self.registry.config().clone()
+    }
+
+    /// Yanks a release of a package published to the server instance.
+    pub async fn yank(&self, package: &str, version: &str) -> Result<()> {
+        self.registry.yank(package, version).await
+    }
+}
+
+/// Spawns a test registry as a background task.
+///
+/// The `test` namespace and the given additional namespaces are defined in the
+/// registry. Returns the server instance, along with a client configuration
+/// and the registry to use to access it.
+pub async fn spawn_server<I, S>(
+    additional_namespaces: I,
+) -> Result<(ServerInstance, wasm_pkg_client::Config, Registry)>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let root = Rc::new(TempDir::new().context("failed to create temp dir")?);
+    let registry = spawn_registry(root.path(), additional_namespaces).await?;
+    let config = registry.config().clone();
+    let reg = registry.registry().clone();
+
+    Ok((ServerInstance { registry, root }, config, reg))
+}
+
+/// Represents a test registry whose interactions with `cargo component` are
+/// recorded to, or replayed from, a cassette.
+///
+/// The cassette is replayed unless the `CARGO_COMPONENT_REGISTRY_CASSETTE_MODE`
+/// environment variable of the test is set to `record`, in which case a
+/// registry is spawned and the cassette is recorded anew.
+pub struct RecordedServer {
+    server: Option<ServerInstance>,
+    root: Rc<TempDir>,
+    cassette: PathBuf,
+    mode: CassetteMode,
+}
+
+impl RecordedServer {
+    /// Gets the server instance to publish the packages of the test to.
+    ///
+    /// Returns `None` when replaying, as no registry is spawned.
+    pub fn recording(&self) -> Option<&ServerInstance> {
+        self.server.as_ref()
+    }
+
+    /// Gets the directory of the cassette.
+    ///
+    /// When replaying, this is the temporary copy of the cassette.
+    pub fn cassette(&self) -> &Path {
+        &self.cassette
+    }
+
+    /// Returns a `Project` that records its registry interactions to, or
+    /// replays them from, the cassette.
+    pub fn project<I, S>(&self, name: &str, lib: bool, additional_args: I) -> Result<Project>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let config_file = match &self.server {
+            Some(server) => server.registry.config_file(),
+            None => self.root.path().join(WASM_PKG_CONFIG_NAME),
+        };
+
+        let proj = Project {
+            dir: self.root.clone(),
+            root: self.root.path().join(name),
+            config_file: Some(config_file),
+            cassette: Some((self.cassette.clone(), self.mode)),
+        };
+
+        proj.new_inner(name, lib, additional_args)?;
+        Ok(proj)
+    }
+}
+
+/// Spawns a test registry recorded to, or replayed from, the cassette in the
+/// given directory.
+///
+/// When replaying, the cassette is copied to a temporary directory, as
+/// replaying keeps track of the replayed interactions in the cassette, and
+/// an empty client configuration is used so that no registry is accessed.
+pub async fn spawn_recorded_server(cassette: impl AsRef<Path>) -> Result<RecordedServer> {
+    let cassette = cassette.as_ref();
+    let mode = match std::env::var(REGISTRY_CASSETTE_MODE_ENV_VAR) {
+        Ok(mode) => mode.parse()?,
+        Err(_) => CassetteMode::Replay,
+    };
+
+    match mode {
+        CassetteMode::Record => {
+            if cassette.exists() {
+                fs::remove_dir_all(cassette).with_context(|| {
+                    format!(
+                        "failed to remove cassette `{path}`",
+                        path = cassette.display()
+                    )
+                })?;
+            }
+
+            let (server, _, _) = spawn_server(Vec::<String>::new()).await?;
+            Ok(RecordedServer {
+                root: server.root.clone(),
+                server: Some(server),
+                cassette: cassette.to_owned(),
+                mode,
+            })
+        }
+        CassetteMode::Replay => {
+            if !cassette.join(CASSETTE_FILE_NAME).is_file() {
+                bail!(
+                    "cassette `{path}` does not exist; run the test with \
+                     `{REGISTRY_CASSETTE_MODE_ENV_VAR}=record` to record it",
+                    path = cassette.display()
+                );
+            }
+
+            let root = Rc::new(TempDir::new().context("failed to create temp dir")?);
+            let copy = root.path().join("cassette");
+            copy_dir(cassette, &copy)?;
+            fs::write(root.path().join(WASM_PKG_CONFIG_NAME), "")?;
+
+            Ok(RecordedServer {
+                server: None,
+                root,
+                cassette: copy,
+                mode,
+            })
+        }
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let path = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &path)?;
+        } else {
+            fs::copy(entry.path(), &path)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Represents a cargo-component project created in a temporary directory.
+#[derive(Debug)]
+pub struct Project {
+    /// The temporary directory containing the project.
+    pub dir: Rc<TempDir>,
+    /// The root directory of the project.
+    pub root: PathBuf,
+    config_file: Option<PathBuf>,
+    cassette: Option<(PathBuf, CassetteMode)>,
+}
+
+impl Project {
+    /// Creates a new project with the given name and whether or not to create a library instead of
+    /// a binary. This should only be used if you want an "empty" project that doesn't have things
+    /// like warg config or wasm pkg tools config configured. If you want a project with a warg
+    /// config and wasm pkg tools config, use the `project` method of `ServerInstance`.
+    pub fn new(name: &str, lib: bool) -> Result<Self> {
+        let dir = TempDir::new()?;
+        let root = dir.path().join(name);
+        let proj = Self {
+            dir: Rc::new(dir),
+            root,
+            config_file: None,
+            cassette: None,
+        };
+
+        proj.new_inner(name, lib, Vec::<String>::new())?;
+
+        Ok(proj)
+    }
+
+    /// Same as `new` but allows you to specify additional arguments to pass to `cargo component
+    /// new`
+    pub fn new_with_args<I, S>(name: &str, lib: bool, additional_args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let dir = TempDir::new()?;
+        let root = dir.path().join(name);
+        let proj = Self {
+            dir: Rc::new(dir),
+            root,
+            config_file: None,
+            cassette: None,
+        };
+
+        proj.new_inner(name, lib, additional_args)?;
+
+        Ok(proj)
+    }
+
+    fn new_inner<I, S>(&self, name: &str, lib: bool, additional_args: I) -> Result<()>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut args = vec!["new".to_string(), name.to_string()];
+        if lib {
+            args.push("--lib".to_string());
+        }
+        args.extend(additional_args.into_iter().map(|arg| arg.into()));
+
+        self.cargo_component(args)
+            .current_dir(self.dir.path())
+            .assert()
+            .try_success()?;
+
+        let m = self.root.join("Cargo.toml"); if m.exists() { fs::write(&m, fs::read_to_string(&m)?.replace("edition = \"2024\"", "edition = \"2021\""))?; }
+        Ok(())
+    }
+
+    /// Same as `new` but uses the given temp directory instead of creating a new one.
+    pub fn with_dir<I, S>(dir: Rc<TempDir>, name: &str, lib: bool, args: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let root = dir.path().join(name);
+        let proj = Self {
+            dir,
+            root,
+            config_file: None,
+            cassette: None,
+        };
+
+        proj.new_inner(name, lib, args)?;
+
+        Ok(proj)
+    }
+
+    /// Creates a new project that hasn't been initialized yet. This is useful for testing workflows
+    /// of `cargo component new`
+    pub fn new_uninitialized(dir: Rc<TempDir>, root: PathBuf) -> Self {
+        Self {
+            dir,
+            root,
+            config_file: None,
+            cassette: None,
+        }
+    }
+
+    /// Gets the root directory of the project.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Gets the temporary directory containing the project.
+    pub fn dir(&self) -> &Rc<TempDir> {
+        &self.dir
+    }
+
+    /// Writes a file relative to the root of the project.
+    pub fn file<B: AsRef<Path>>(&self, path: B, body: impl AsRef<[u8]>) -> Result<&Self> {
+        let path = self.root().join(path);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, body)?;
+        Ok(self)
+    }
+
+    /// Reads the `Cargo.toml` of the project.
+    pub fn read_manifest(&self) -> Result<DocumentMut> {
+        let manifest_path = self.root.join("Cargo.toml");
+        let manifest_text = fs::read_to_string(manifest_path)?;
+        Ok(manifest_text.parse()?)
+    }
+
+    /// Updates the `Cargo.toml` of the project with the given function.
+    pub fn update_manifest(
+        &self,
+        f: impl FnOnce(DocumentMut) -> Result<DocumentMut>,
+    ) -> Result<()> {
+        let manifest = self.read_manifest()?;
+        let manifest_path = self.root.join("Cargo.toml");
+        fs::write(manifest_path, f(manifest)?.to_string())?;
+        Ok(())
+    }
+
+    /// Gets the target directory of the project.
+    pub fn build_dir(&self) -> PathBuf {
+        self.root().join("target")
+    }
+
+    /// Gets the path to the debug build of the given component.
+    pub fn debug_wasm(&self, name: &str) -> PathBuf {
+        self.build_dir()
+            .join("wasm32-wasip1")
+            .join("debug")
+            .join(format!("{name}.wasm"))
+    }
+
+    /// Gets the path to the release build of the given component.
+    pub fn release_wasm(&self, name: &str) -> PathBuf {
+        self.build_dir()
+            .join("wasm32-wasip1")
+            .join("release")
+            .join(format!("{name}.wasm"))
+    }
+
+    /// Gets the cache directory used by commands run for the project.
+    pub fn cache_dir(&self) -> PathBuf {
+        self.dir.path().join("cache")
+    }
+
+    /// Gets the configuration file used by commands run for the project, if any.
+    pub fn config_file(&self) -> Option<&Path> {
+        self.config_file.as_deref()
+    }
+
+    /// Creates a command running `cargo component` in the root of the project.
+    pub fn cargo_component<I, S>(&self, args: I) -> Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd = cargo_component(args);
+        // Set the cache dir and the config file env var for every command
+        if let Some(config_file) = self.config_file() {
+            cmd.env(CONFIG_FILE_ENV_VAR, config_file);
+        }
+        if let Some((cassette, mode)) = &self.cassette {
+            cmd.env(REGISTRY_CASSETTE_ENV_VAR, cassette);
+            cmd.env(REGISTRY_CASSETTE_MODE_ENV_VAR, mode.to_string());
+        }
+        cmd.env(CACHE_DIR_ENV_VAR, self.cache_dir());
+        cmd.current_dir(&self.root);
+        cmd
+    }
+}
+
+/// Validates that the file at the given path is a component.
+pub fn validate_component(path: &Path) -> Result<()> {
+    let bytes = fs::read(path)
+        .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+    // Validate the bytes as either a component or a module
+    Validator::new_with_features(Default::default()).validate_all(&bytes)?;
+
+    // Check that the bytes are for a component and not a module
+    let mut parser = Parser::new(0);
+    match parser.parse(&bytes, true)? {
+        Chunk::Parsed {
+            payload:
+                Payload::Version {
+                    encoding: Encoding::Component,
+                    ..
+                },
+            ..
+        } => Ok(()),
+        Chunk::Parsed { payload, .. } => {
+            bail!("expected component version payload, got {:?}", payload)
+        }
+        Chunk::NeedMoreData(_) => unreachable!(),
+    }
+}
@@ -2,12 +2,19 @@ use std::path::PathBuf;
 
 use anyhow::{bail, Result};
 use cargo_component::{
-    commands::{AddCommand, BindingsCommand, NewCommand, PublishCommand, UpdateCommand},
+    commands::{
+        AddCommand, BindingsCommand, BundleCommand, BundleSubcommand, ContainerizeCommand,
+        DeployCommand, EmbedCommand, FetchCommand, FuzzCommand, GraphCommand, ImportsCommand,
+        LoginCommand, LogoutCommand, NewCommand, PublishCommand, RenameCommand, StatusCommand,
+        UpdateCommand, WhoamiCommand, WhyCommand,
+    },
     config::{CargoArguments, Config},
-    load_component_metadata, load_metadata, run_cargo_command,
+    external::{find_external_subcommand, run_external_subcommand},
+    load_component_metadata, load_metadata, run_cargo_command, CommandStatus,
 };
 use cargo_component_core::{
-    command::{CACHE_DIR_ENV_VAR, CONFIG_FILE_ENV_VAR},
+    command::{CommonOptions, CACHE_DIR_ENV_VAR, CONFIG_FILE_ENV_VAR},
+    diagnostic::render_error,
     terminal::{Color, Terminal, Verbosity},
 };
 use clap::{CommandFactory, Parser};
@@ -20,15 +27,29 @@ fn version() -> &'static str {
 const BUILTIN_COMMANDS: &[&str] = &[
     "add",
     "bindings",
+    "bundle",
     "component", // for indirection via `cargo component`
+    "containerize",
+    "deploy",
+    "embed",
+    "fetch",
+    "fuzz",
+    "graph",
     "help",
+    "imports",
     "init",
+    "login",
+    "logout",
     "new",
     "publish",
     "remove",
+    "rename",
     "rm",
+    "status",
     "update",
     "vendor",
+    "whoami",
+    "why",
     "yank",
 ];
 
@@ -36,18 +57,12 @@ const BUILTIN_COMMANDS: &[&str] = &[
 ///
 /// These commands are intended to integrate with `crates.io` and have no
 /// analog in `cargo-component` currently.
-const UNSUPPORTED_COMMANDS: &[&str] = &[
-    "install",
-    "login",
-    "logout",
-    "owner",
-    "package",
-    "search",
-    "uninstall",
-];
+const UNSUPPORTED_COMMANDS: &[&str] = &["install", "owner", "package", "search", "uninstall"];
 
-const AFTER_HELP: &str = "Unrecognized subcommands will be passed to cargo verbatim after\n\
-     relevant component bindings are updated.\n\
+const AFTER_HELP: &str =
+    "Unrecognized subcommands will run an installed `cargo-component-<name>`\n\
+     executable, if any, or be passed to cargo verbatim after relevant\n\
+     component bindings are updated.\n\
      \n\
      See `cargo help` for more information on available cargo commands.";
 
@@ -73,15 +88,58 @@ enum CargoComponent {
 enum Command {
     Add(AddCommand),
     Bindings(BindingsCommand),
+    Bundle(BundleCommand),
+    Containerize(ContainerizeCommand),
+    Deploy(DeployCommand),
+    Embed(EmbedCommand),
+    Fetch(FetchCommand),
+    Fuzz(FuzzCommand),
+    Graph(GraphCommand),
+    Imports(ImportsCommand),
     // TODO: Init(InitCommand),
+    Login(LoginCommand),
+    Logout(LogoutCommand),
     New(NewCommand),
     // TODO: Remove(RemoveCommand),
+    Rename(RenameCommand),
+    Status(StatusCommand),
     Update(UpdateCommand),
     Publish(PublishCommand),
+    Whoami(WhoamiCommand),
+    Why(WhyCommand),
     // TODO: Yank(YankCommand),
     // TODO: Vendor(VendorCommand),
 }
 
+impl Command {
+    fn common(&self) -> &CommonOptions {
+        match self {
+            Self::Add(cmd) => &cmd.common,
+            Self::Bindings(cmd) => &cmd.common,
+            Self::Bundle(cmd) => match &cmd.command {
+                BundleSubcommand::Export(cmd) => &cmd.common,
+                BundleSubcommand::Import(cmd) => &cmd.common,
+            },
+            Self::Containerize(cmd) => &cmd.common,
+            Self::Deploy(cmd) => &cmd.common,
+            Self::Embed(cmd) => &cmd.common,
+            Self::Fetch(cmd) => &cmd.common,
+            Self::Fuzz(cmd) => &cmd.common,
+            Self::Graph(cmd) => &cmd.common,
+            Self::Imports(cmd) => &cmd.common,
+            Self::Login(cmd) => &cmd.common,
+            Self::Logout(cmd) => &cmd.common,
+            Self::New(cmd) => &cmd.common,
+            Self::Rename(cmd) => &cmd.common,
+            Self::Status(cmd) => &cmd.common,
+            Self::Update(cmd) => &cmd.common,
+            Self::Publish(cmd) => &cmd.common,
+            Self::Whoami(cmd) => &cmd.common,
+            Self::Why(cmd) => &cmd.common,
+        }
+    }
+}
+
 fn detect_subcommand() -> Option<String> {
     let mut iter = std::env::args().skip(1).peekable();
 
@@ -114,17 +172,36 @@ async fn main() -> Result<()> {
     match subcommand.as_deref() {
         // Check for built-in command or no command (shows help)
         Some(cmd) if BUILTIN_COMMANDS.contains(&cmd) => {
-            if let Err(e) = match CargoComponent::parse() {
-                CargoComponent::Component(cmd) | CargoComponent::Command(cmd) => match cmd {
-                    Command::Add(cmd) => cmd.exec().await,
-                    Command::Bindings(cmd) => cmd.exec().await,
-                    Command::New(cmd) => cmd.exec().await,
-                    Command::Update(cmd) => cmd.exec().await,
-                    Command::Publish(cmd) => cmd.exec().await,
+            let (CargoComponent::Component(cmd) | CargoComponent::Command(cmd)) =
+                CargoComponent::parse();
+            let output_format = cmd.common().output_format.unwrap_or_default();
+            if let Err(e) = match cmd {
+                Command::Add(cmd) => cmd.exec().await,
+                Command::Bindings(cmd) => cmd.exec().await,
+                Command::Bundle(cmd) => cmd.exec().await,
+                Command::Containerize(cmd) => cmd.exec().await,
+                Command::Deploy(cmd) => cmd.exec().await,
+                Command::Embed(cmd) => cmd.exec().await,
+                Command::Fetch(cmd) => cmd.exec().await,
+                Command::Fuzz(cmd) => cmd.exec().await,
+                Command::Graph(cmd) => cmd.exec().await,
+                Command::Imports(cmd) => cmd.exec().await,
+                Command::Login(cmd) => cmd.exec().await,
+                Command::Logout(cmd) => cmd.exec().await,
+                Command::New(cmd) => match cmd.exec().await {
+                    Ok(CommandStatus::Exit(code)) => std::process::exit(code),
+                    result => result.map(|_| ()),
                 },
+                Command::Rename(cmd) => cmd.exec().await,
+                Command::Status(cmd) => cmd.exec().await,
+                Command::Update(cmd) => cmd.exec().await,
+                Command::Publish(cmd) => cmd.exec().await,
+                Command::Whoami(cmd) => cmd.exec().await,
+                Command::Why(cmd) => cmd.exec().await,
             } {
-                let terminal = Terminal::new(Verbosity::Normal, Color::Auto);
-                terminal.error(format!("{e:?}"))?;
+                let terminal =
+                    Terminal::new(Verbosity::Normal, Color::Auto).with_output_format(output_format);
+                terminal.error(render_error(&e))?;
                 std::process::exit(1);
             }
         }
@@ -156,26 +233,36 @@ async fn main() -> Result<()> {
             let config_file = std::env::var(CONFIG_FILE_ENV_VAR).map(PathBuf::from).ok();
             let config = Config::new(
                 Terminal::new(
-                    if cargo_args.quiet {
-                        Verbosity::Quiet
-                    } else {
-                        match cargo_args.verbose {
-                            0 => Verbosity::Normal,
-                            _ => Verbosity::Verbose,
-                        }
-                    },
+                    Verbosity::from_flags(cargo_args.quiet, cargo_args.verbose),
                     cargo_args.color.unwrap_or_default(),
-                ),
+                )
+                .with_output_format(cargo_args.output_format.unwrap_or_default()),
                 config_file,
             )
-            .await?;
+            .await?
+            .with_cache_dir(cache_dir);
 
-            let metadata = load_metadata(cargo_args.manifest_path.as_deref())?;
-            let packages = load_component_metadata(
+            // Errors in the manifests are reported through the terminal so that
+            // they respect `--output-format`
+            let metadata = match load_metadata(cargo_args.manifest_path.as_deref()) {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    config.terminal().error(render_error(&e))?;
+                    std::process::exit(1);
+                }
+            };
+            let packages = match load_component_metadata(
                 &metadata,
                 cargo_args.packages.iter(),
                 cargo_args.workspace,
-            )?;
+                Some(cargo_args.profile_name()),
+            ) {
+                Ok(packages) => packages,
+                Err(e) => {
+                    config.terminal().error(render_error(&e))?;
+                    std::process::exit(1);
+                }
+            };
 
             if packages.is_empty() {
                 bail!(
@@ -185,8 +272,30 @@ async fn main() -> Result<()> {
             }
 
             let spawn_args: Vec<_> = std::env::args().skip(1).collect();
-            let client = config.client(cache_dir, cargo_args.offline).await?;
-            if let Err(e) = run_cargo_command(
+
+            // Dispatch to an external `cargo-component-<name>` subcommand if one is installed
+            if let Some((name, path)) = subcommand
+                .as_deref()
+                .and_then(|name| Some((name, find_external_subcommand(name)?)))
+            {
+                let args = spawn_args
+                    .iter()
+                    .position(|a| a == name)
+                    .map(|i| &spawn_args[i + 1..])
+                    .unwrap_or_default();
+                match run_external_subcommand(&path, name, args, &metadata, &packages, &cargo_args)
+                {
+                    Ok(CommandStatus::Success) => return Ok(()),
+                    Ok(status) => std::process::exit(status.code()),
+                    Err(e) => {
+                        config.terminal().error(render_error(&e))?;
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            let client = config.client(cargo_args.offline).await?;
+            match run_cargo_command(
                 client,
                 &config,
                 &metadata,
@@ -197,8 +306,12 @@ async fn main() -> Result<()> {
             )
             .await
             {
-                config.terminal().error(format!("{e:?}"))?;
-                std::process::exit(1);
+                Ok((CommandStatus::Success, _)) => {}
+                Ok((status, _)) => std::process::exit(status.code()),
+                Err(e) => {
+                    config.terminal().error(render_error(&e))?;
+                    std::process::exit(1);
+                }
             }
         }
     }
@@ -1,54 +1,814 @@
 //! Module for bindings generation.
 use std::{
     collections::{HashMap, HashSet},
-    mem,
+    fs, mem,
+    ops::Range,
     path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
 };
 
-use anyhow::{bail, Context, Result};
-use cargo_component_core::registry::DecodedDependency;
-use heck::ToKebabCase;
-use indexmap::{IndexMap, IndexSet};
+use anyhow::{anyhow, bail, Context, Result};
+use base64::prelude::*;
+use cargo_component_core::{
+    registry::{merge_dependencies, DecodedDependency, DependencyResolution, LocalResolution},
+    terminal::Terminal,
+};
+use heck::{ToKebabCase, ToSnakeCase};
+use indexmap::IndexMap;
 use semver::Version;
-use wasm_pkg_client::PackageRef;
-use wit_bindgen_core::Files;
-use wit_bindgen_rust::{Opts, WithOption};
+use syn::punctuated::Punctuated;
+use wasm_pkg_client::{ContentDigest, PackageRef};
+use wit_bindgen_core::{name_package_module, Files};
+use wit_bindgen_rust::{to_rust_ident, Opts, WithOption};
 use wit_component::DecodedWasm;
 use wit_parser::{
-    Interface, Package, PackageName, Resolve, Type, TypeDefKind, TypeOwner, UnresolvedPackageGroup,
-    World, WorldId, WorldItem, WorldKey,
+    Docs, Interface, InterfaceId, Package, PackageId, PackageName, Resolve, Type, TypeDefKind,
+    TypeOwner, UnresolvedPackage, UnresolvedPackageGroup, World, WorldId, WorldItem, WorldKey,
 };
 
-use crate::{metadata::Ownership, registry::PackageDependencyResolution};
+use crate::{
+    bindings_path,
+    freshness::{rust_sources, HostFileSystem},
+    metadata::{
+        ComponentMetadata, InterfaceSettings, Ownership, TARGET_DEPS_DIR, WIT_DEPS_LOCK,
+        WIT_DEPS_MANIFEST,
+    },
+    registry::PackageDependencyResolution,
+    wit_cache::WitCache,
+};
 
-// Used to format `unlocked-dep` import names for dependencies on
-// other components.
-fn format_dep_import(package: &Package, name: Option<&str>, version: Option<&Version>) -> String {
-    match (name, version) {
-        (Some(name), Some(version)) => format!(
-            "unlocked-dep=<{ns}:{pkg}/{name}@{{>={min} <{max}}}>",
-            ns = package.name.namespace,
-            pkg = package.name.name,
-            min = version,
-            max = Version::new(version.major, version.minor + 1, 0)
-        ),
-        (Some(name), None) => format!(
-            "unlocked-dep=<{ns}:{pkg}/{name}>",
-            ns = package.name.namespace,
-            pkg = package.name.name
-        ),
-        (None, Some(version)) => format!(
-            "unlocked-dep=<{ns}:{pkg}@{{>={min} <{max}}}>",
-            ns = package.name.namespace,
-            pkg = package.name.name,
+/// The name of the module containing the bindings of interfaces with settings
+/// of their own.
+const INTERFACE_SETTINGS_MODULE: &str = "interface_settings";
+
+/// The WIT of the interface exported when `version_export` is enabled.
+const VERSION_WIT: &str = r#"package cargo-component:metadata;
+
+/// Describes the crate a component was built from.
+interface version {
+    /// The version of the crate.
+    version: func() -> string;
+
+    /// The git commit the crate was built from, if known.
+    revision: func() -> option<string>;
+}
+"#;
+
+/// The implementation of the version interface appended to the bindings when
+/// `version_export` is enabled.
+///
+/// The interface is implemented for every type so that it is exported by the
+/// `export!` macro along with the rest of the world.
+const VERSION_IMPL: &str = r#"
+impl<T> exports::cargo_component::metadata::version::Guest for T {
+    fn version() -> _rt::String {
+        _rt::String::from(env!("CARGO_PKG_VERSION"))
+    }
+
+    fn revision() -> Option<_rt::String> {
+        REVISION.map(_rt::String::from)
+    }
+}
+"#;
+
+/// The module appended to the bindings when `arbitrary` is enabled.
+///
+/// Generated flags types are defined with the wrapped `bitflags!` macro
+/// as they cannot derive `Arbitrary` from their internal representation.
+const ARBITRARY_MODULE: &str = r#"
+/// Support for generating arbitrary values of the binding types.
+///
+/// This module re-exports the `arbitrary` crate so that the binding types
+/// may be generated from unstructured data in generative tests.
+pub mod arbitrary {
+    pub use ::arbitrary::*;
+
+    /// Generates an arbitrary value from the given data.
+    pub fn from_bytes<'a, T: Arbitrary<'a>>(data: &'a [u8]) -> Result<T> {
+        T::arbitrary_take_rest(Unstructured::new(data))
+    }
+
+    #[doc(hidden)]
+    macro_rules! bitflags {
+        ($(#[$attr:meta])* pub struct $name:ident: $repr:ty { $($body:tt)* }) => {
+            wit_bindgen_rt::bitflags::bitflags! {
+                $(#[$attr])*
+                pub struct $name: $repr { $($body)* }
+            }
+
+            impl<'a> ::arbitrary::Arbitrary<'a> for $name {
+                fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                    Ok(Self::from_bits_truncate(u.arbitrary()?))
+                }
+            }
+        };
+    }
+
+    #[doc(hidden)]
+    pub(crate) use bitflags;
+}
+"#;
+
+/// The module appended to the bindings when `proptest` is enabled.
+///
+/// The strategies generate values from random bytes with `Arbitrary`, so
+/// shrinking a failing case shrinks the bytes it was generated from.
+const PROPTEST_MODULE: &str = r#"
+/// `proptest` strategies for the binding types.
+pub mod proptest {
+    use ::proptest::prelude::*;
+
+    /// The maximum number of bytes a value is generated from.
+    pub const MAX_SIZE: usize = 4096;
+
+    /// Creates a strategy generating arbitrary values of a binding type.
+    pub fn strategy<T>() -> impl Strategy<Value = T>
+    where
+        T: for<'a> super::arbitrary::Arbitrary<'a> + ::core::fmt::Debug,
+    {
+        ::proptest::collection::vec(any::<u8>(), 0..MAX_SIZE).prop_filter_map(
+            "not enough data for an arbitrary value",
+            |data| super::arbitrary::from_bytes(&data).ok(),
+        )
+    }
+}
+"#;
+
+/// Removes the `Arbitrary` derive from the generated types that carry resource
+/// handles, as handles cannot be generated from unstructured data.
+///
+/// Resources and their borrows do not derive `Arbitrary`, so the derive is
+/// removed from every type referring to a type without it, directly or
+/// through other generated types.
+fn skip_arbitrary_for_handles(source: String) -> Result<String> {
+    /// A generated type and the names of the types its fields refer to.
+    struct GeneratedType {
+        name: String,
+        derived: bool,
+        references: HashSet<String>,
+    }
+
+    fn is_arbitrary(path: &syn::Path) -> bool {
+        let segments: Vec<_> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        segments == ["arbitrary", "Arbitrary"]
+    }
+
+    fn derives(attr: &syn::Attribute) -> Option<Punctuated<syn::Path, syn::Token![,]>> {
+        if !attr.path().is_ident("derive") {
+            return None;
+        }
+
+        attr.parse_args_with(Punctuated::parse_terminated).ok()
+    }
+
+    fn references(ty: &syn::Type, names: &mut HashSet<String>) {
+        match ty {
+            syn::Type::Path(ty) => {
+                for segment in &ty.path.segments {
+                    names.insert(segment.ident.to_string());
+                    if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                        for arg in &args.args {
+                            if let syn::GenericArgument::Type(ty) = arg {
+                                references(ty, names);
+                            }
+                        }
+                    }
+                }
+            }
+            syn::Type::Reference(ty) => references(&ty.elem, names),
+            syn::Type::Slice(ty) => references(&ty.elem, names),
+            syn::Type::Array(ty) => references(&ty.elem, names),
+            syn::Type::Paren(ty) => references(&ty.elem, names),
+            syn::Type::Group(ty) => references(&ty.elem, names),
+            syn::Type::Tuple(ty) => ty.elems.iter().for_each(|ty| references(ty, names)),
+            _ => {}
+        }
+    }
+
+    fn collect(items: &[syn::Item], types: &mut Vec<GeneratedType>) {
+        for item in items {
+            let (ident, attrs, fields): (_, _, Vec<&syn::Field>) = match item {
+                // The runtime support module only contains helpers of the bindings
+                syn::Item::Mod(m) if m.ident == "_rt" => continue,
+                syn::Item::Mod(m) => {
+                    if let Some((_, items)) = &m.content {
+                        collect(items, types);
+                    }
+                    continue;
+                }
+                syn::Item::Struct(s) => (&s.ident, &s.attrs, s.fields.iter().collect()),
+                syn::Item::Enum(e) => (
+                    &e.ident,
+                    &e.attrs,
+                    e.variants.iter().flat_map(|v| &v.fields).collect(),
+                ),
+                _ => continue,
+            };
+
+            let mut names = HashSet::new();
+            for field in fields {
+                references(&field.ty, &mut names);
+            }
+
+            types.push(GeneratedType {
+                name: ident.to_string(),
+                derived: attrs
+                    .iter()
+                    .filter_map(derives)
+                    .any(|d| d.iter().any(is_arbitrary)),
+                references: names,
+            });
+        }
+    }
+
+    fn remove(items: &mut [syn::Item], names: &HashSet<String>) {
+        for item in items {
+            let attrs = match item {
+                syn::Item::Mod(m) => {
+                    if let Some((_, items)) = &mut m.content {
+                        remove(items, names);
+                    }
+                    continue;
+                }
+                syn::Item::Struct(s) if names.contains(&s.ident.to_string()) => &mut s.attrs,
+                syn::Item::Enum(e) if names.contains(&e.ident.to_string()) => &mut e.attrs,
+                _ => continue,
+            };
+
+            attrs.retain_mut(|attr| {
+                let Some(derives) = derives(attr) else {
+                    return true;
+                };
+
+                let derives: Punctuated<syn::Path, syn::Token![,]> =
+                    derives.into_iter().filter(|d| !is_arbitrary(d)).collect();
+                if derives.is_empty() {
+                    return false;
+                }
+
+                *attr = syn::parse_quote!(#[derive(#derives)]);
+                true
+            });
+        }
+    }
+
+    // Comments are not preserved by parsing, so the leading comments of the
+    // bindings are kept aside
+    let body = source
+        .lines()
+        .take_while(|l| l.is_empty() || l.starts_with("//"))
+        .map(|l| l.len() + 1)
+        .sum::<usize>()
+        .min(source.len());
+    let (preamble, body) = source.split_at(body);
+    let mut file = syn::parse_file(body).context("failed to parse the generated bindings")?;
+
+    let mut types = Vec::new();
+    collect(&file.items, &mut types);
+
+    let mut skipped: HashSet<String> = types
+        .iter()
+        .filter(|t| !t.derived)
+        .map(|t| t.name.clone())
+        .collect();
+    let mut removed = HashSet::new();
+    loop {
+        let carrying: Vec<_> = types
+            .iter()
+            .filter(|t| {
+                t.derived
+                    && !skipped.contains(&t.name)
+                    && t.references.iter().any(|r| skipped.contains(r))
+            })
+            .map(|t| t.name.clone())
+            .collect();
+        if carrying.is_empty() {
+            break;
+        }
+
+        skipped.extend(carrying.iter().cloned());
+        removed.extend(carrying);
+    }
+
+    if removed.is_empty() {
+        return Ok(source);
+    }
+
+    remove(&mut file.items, &removed);
+    Ok(format!(
+        "{preamble}{body}",
+        body = prettyplease::unparse(&file)
+    ))
+}
+
+/// The name of the static holding the encoded component type in the bindings
+/// generated by wit-bindgen.
+const COMPONENT_TYPE_STATIC: &str = "pub static __WIT_BINDGEN_COMPONENT_TYPE";
+
+/// Finds the definitions of the encoded component type statics in the given
+/// bindings source.
+fn component_type_ranges(source: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut offset = 0;
+    while let Some(start) = source[offset..].find(COMPONENT_TYPE_STATIC) {
+        let start = offset + start;
+        // The static is initialized from a byte string literal
+        let literal = start
+            + source[start..]
+                .find("*b\"")
+                .expect("expected a byte string")
+            + 3;
+        let mut chars = source[literal..].char_indices();
+        let mut end = source.len();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => {
+                    end = literal + i + 1;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        ranges.push(start..end);
+        offset = end;
+    }
+
+    ranges
+}
+
+/// Replaces the encoded component types in the given bindings source with
+/// those of another generation of the same bindings.
+fn replace_component_types(source: &str, types: &str) -> Result<String> {
+    let ranges = component_type_ranges(source);
+    let replacements = component_type_ranges(types);
+    if ranges.len() != replacements.len() {
+        bail!("the generated bindings differ in their number of component types");
+    }
+
+    let mut result = String::with_capacity(source.len());
+    let mut offset = 0;
+    for (range, replacement) in ranges.into_iter().zip(replacements) {
+        result.push_str(&source[offset..range.start]);
+        result.push_str(&types[replacement]);
+        offset = range.end;
+    }
+    result.push_str(&source[offset..]);
+
+    Ok(result)
+}
+
+/// Adds a reference to the defining WIT interface or world to the
+/// documentation of each item in the resolve.
+fn add_doc_origins(resolve: &mut Resolve) {
+    fn append(docs: &mut Docs, origin: &str) {
+        let contents = docs.contents.get_or_insert_with(String::new);
+        if !contents.is_empty() {
+            contents.push_str("\n\n");
+        }
+
+        contents.push_str(&format!("Defined in WIT by `{origin}`."));
+    }
+
+    let interfaces = resolve
+        .interfaces
+        .iter()
+        .filter_map(|(id, iface)| match (iface.package, &iface.name) {
+            (Some(pkg), Some(name)) => Some((id, resolve.id_of_name(pkg, name))),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (id, origin) in interfaces {
+        let iface = &mut resolve.interfaces[id];
+        append(&mut iface.docs, &origin);
+
+        for func in iface.functions.values_mut() {
+            append(
+                &mut func.docs,
+                &format!("{origin}#{name}", name = func.name),
+            );
+        }
+
+        for ty in iface.types.values() {
+            let ty = &mut resolve.types[*ty];
+            let name = ty.name.as_deref().unwrap_or_default();
+            append(&mut ty.docs, &format!("{origin}#{name}"));
+        }
+    }
+
+    let worlds = resolve
+        .worlds
+        .iter()
+        .filter_map(|(id, world)| {
+            world
+                .package
+                .map(|pkg| (id, resolve.id_of_name(pkg, &world.name)))
+        })
+        .collect::<Vec<_>>();
+
+    for (id, origin) in worlds {
+        let world = &mut resolve.worlds[id];
+        append(&mut world.docs, &origin);
+
+        for item in world.imports.values_mut().chain(world.exports.values_mut()) {
+            match item {
+                WorldItem::Function(func) => {
+                    append(
+                        &mut func.docs,
+                        &format!("{origin}#{name}", name = func.name),
+                    );
+                }
+                WorldItem::Type(ty) => {
+                    let ty = &mut resolve.types[*ty];
+                    if ty.owner == TypeOwner::World(id) {
+                        let name = ty.name.as_deref().unwrap_or_default();
+                        append(&mut ty.docs, &format!("{origin}#{name}"));
+                    }
+                }
+                WorldItem::Interface { .. } => {}
+            }
+        }
+    }
+}
+
+/// Selects a world of the given package.
+///
+/// In addition to the world specifiers of [`Resolve::select_world`], a world
+/// may be qualified by a package name without its namespace (e.g. `bar/world`).
+///
+/// If no world could be selected, the error lists the worlds of all packages
+/// and suggests the one closest to the specified name.
+pub(crate) fn select_world(
+    resolve: &Resolve,
+    package: PackageId,
+    world: Option<&str>,
+) -> Result<WorldId> {
+    let result = match world.and_then(|w| w.split_once('/')) {
+        Some((name, world)) if !name.contains(':') => {
+            let candidates: Vec<_> = resolve
+                .packages
+                .iter()
+                .filter(|(_, p)| p.name.name == name)
+                .collect();
+            match candidates[..] {
+                [(id, _)] => resolve.select_world(id, Some(world)),
+                [] => Err(anyhow!("unknown package `{name}`")),
+                _ => Err(anyhow!(
+                    "package name `{name}` is ambiguous; qualify the world with one of{list}",
+                    list = candidates
+                        .iter()
+                        .map(|(_, p)| format!("\n  {name}/{world}", name = p.name))
+                        .collect::<String>()
+                )),
+            }
+        }
+        _ => resolve.select_world(package, world),
+    };
+
+    let Some(world) = world else {
+        return result;
+    };
+
+    result.map_err(|e| {
+        let available: Vec<_> = resolve
+            .worlds
+            .iter()
+            .filter_map(|(_, w)| {
+                let pkg = &resolve.packages[w.package?];
+                Some((
+                    w.name.as_str(),
+                    format!("{pkg}/{w}", pkg = pkg.name, w = w.name),
+                ))
+            })
+            .collect();
+
+        let mut message = e.to_string();
+        if available.is_empty() {
+            return anyhow!(message);
+        }
+
+        message.push_str("\n\navailable worlds:");
+        for (_, qualified) in &available {
+            message.push_str(&format!("\n  {qualified}"));
+        }
+
+        let name = world.rsplit_once('/').map(|(_, w)| w).unwrap_or(world);
+        let name = name.split_once('@').map(|(w, _)| w).unwrap_or(name);
+        if let Some((_, qualified)) = available
+            .iter()
+            .map(|(w, qualified)| (strsim::levenshtein(name, w), qualified))
+            .filter(|(distance, _)| *distance <= name.len().max(3) / 3)
+            .min_by_key(|(distance, _)| *distance)
+        {
+            message.push_str(&format!(
+                "\n\nhelp: a world with a similar name exists: `{qualified}`"
+            ));
+        }
+
+        anyhow!(message)
+    })
+}
+
+/// Gets the `deps` directory of a local target.
+///
+/// This is the `deps` directory next to a single-file target, or in a target
+/// directory with a `wit-deps` manifest.
+pub(crate) fn local_target_deps_dir(path: &Path) -> Option<PathBuf> {
+    if path.is_dir() {
+        path.join(WIT_DEPS_MANIFEST)
+            .is_file()
+            .then(|| path.join(TARGET_DEPS_DIR))
+    } else {
+        path.parent().map(|parent| parent.join(TARGET_DEPS_DIR))
+    }
+}
+
+/// Represents a package parsed from the `deps` directory of a local target.
+pub(crate) enum DepsDirPackage {
+    /// A WIT package parsed from a directory or a WIT file.
+    Wit(UnresolvedPackageGroup),
+    /// A WIT package or component decoded from a Wasm file.
+    Wasm(DecodedWasm),
+}
+
+/// Parses the packages in the `deps` directory of a local target.
+///
+/// Each entry of the directory is either a directory of WIT files, a WIT file
+/// or a Wasm file; other entries are ignored. The packages are returned as
+/// local resolutions named after the packages they contain.
+pub(crate) fn parse_deps_dir(dir: &Path) -> Result<Vec<(DependencyResolution, DepsDirPackage)>> {
+    let mut entries = fs::read_dir(dir)
+        .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?
+        .map(|e| Ok(e?.path()))
+        .collect::<Result<Vec<_>>>()
+        .with_context(|| format!("failed to read directory `{dir}`", dir = dir.display()))?;
+    entries.sort();
+
+    let mut packages = Vec::new();
+    for path in entries {
+        let (name, package) = if path.is_dir() {
+            let group = UnresolvedPackageGroup::parse_dir(&path).with_context(|| {
+                format!(
+                    "failed to parse dependency from directory `{path}`",
+                    path = path.display()
+                )
+            })?;
+            (group.main.name.clone(), DepsDirPackage::Wit(group))
+        } else {
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("wit") => {
+                    let group = UnresolvedPackageGroup::parse_file(&path).with_context(|| {
+                        format!("failed to parse dependency `{path}`", path = path.display())
+                    })?;
+                    (group.main.name.clone(), DepsDirPackage::Wit(group))
+                }
+                Some("wasm") => {
+                    let bytes = fs::read(&path).with_context(|| {
+                        format!("failed to read dependency `{path}`", path = path.display())
+                    })?;
+                    let decoded = wit_component::decode(&bytes).with_context(|| {
+                        format!(
+                            "failed to decode dependency `{path}`",
+                            path = path.display()
+                        )
+                    })?;
+                    let name = decoded.resolve().packages[decoded.package()].name.clone();
+                    (name, DepsDirPackage::Wasm(decoded))
+                }
+                _ => continue,
+            }
+        };
+
+        let name = format!("{ns}:{name}", ns = name.namespace, name = name.name)
+            .parse()
+            .with_context(|| {
+                format!(
+                    "invalid name of package `{name}` in dependency `{path}`",
+                    path = path.display()
+                )
+            })?;
+        packages.push((
+            DependencyResolution::Local(LocalResolution { name, path }),
+            package,
+        ));
+    }
+
+    Ok(packages)
+}
+
+/// Checks that the packages listed in the `wit-deps` manifest and lock file of
+/// a local target directory were fetched into its `deps` directory.
+fn check_wit_deps(dir: &Path) -> Result<()> {
+    let deps_dir = dir.join(TARGET_DEPS_DIR);
+    let mut missing = Vec::new();
+    for file in [WIT_DEPS_MANIFEST, WIT_DEPS_LOCK] {
+        let path = dir.join(file);
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("failed to read `{path}`", path = path.display()))
+            }
+        };
+
+        let document: toml_edit::DocumentMut = contents
+            .parse()
+            .with_context(|| format!("failed to parse `{path}`", path = path.display()))?;
+        for (name, _) in document.iter() {
+            if !deps_dir.join(name).is_dir() && !missing.iter().any(|m| m == name) {
+                missing.push(name.to_string());
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = format!(
+        "the `wit-deps` dependencies of local target `{dir}` were not found in `{deps_dir}`:",
+        dir = dir.display(),
+        deps_dir = deps_dir.display()
+    );
+    for name in &missing {
+        message.push_str(&format!("\n  {name}"));
+    }
+    message.push_str("\n\nhelp: run `wit-deps` to fetch them");
+    bail!(message)
+}
+
+/// Gets the names of the foreign packages referenced by a WIT package group
+/// that are neither in the given resolve nor in the group itself.
+fn unresolved_foreign_deps(resolve: &Resolve, group: &UnresolvedPackageGroup) -> Vec<PackageName> {
+    let packages = || std::iter::once(&group.main).chain(&group.nested);
+    let mut unresolved: Vec<_> = packages()
+        .flat_map(|p| p.foreign_deps.keys())
+        .filter(|name| {
+            !resolve.package_names.contains_key(*name) && packages().all(|p| p.name != **name)
+        })
+        .cloned()
+        .collect();
+
+    unresolved.sort_by_key(ToString::to_string);
+    unresolved.dedup();
+    unresolved
+}
+
+/// Renames the functions of a world to the names they are imported or
+/// exported as.
+///
+/// A world that includes another world with `include ... with { a as b }` has
+/// the function `a` under the name `b`, but the function itself keeps the name
+/// `a`, which is the name used for the bindings.
+fn rename_included_functions(resolve: &mut Resolve, world: WorldId) {
+    let world = &mut resolve.worlds[world];
+    for (key, item) in world.imports.iter_mut().chain(world.exports.iter_mut()) {
+        if let (WorldKey::Name(name), WorldItem::Function(func)) = (key, item) {
+            if func.name != *name {
+                log::debug!(
+                    "renaming included function `{old}` to `{name}`",
+                    old = func.name
+                );
+                func.name = name.clone();
+            }
+        }
+    }
+}
+
+/// Adds the version of the matching package to each unversioned foreign
+/// package referenced by the given package.
+///
+/// This allows a local target to `use` or `include` items of a versioned
+/// target dependency without restating its version, provided only one version
+/// of the package is present.
+fn version_foreign_deps(resolve: &Resolve, package: &mut UnresolvedPackage) {
+    let existing: HashSet<_> = package.foreign_deps.keys().cloned().collect();
+    package.foreign_deps = mem::take(&mut package.foreign_deps)
+        .into_iter()
+        .map(|(name, items)| {
+            if name.version.is_some() {
+                return (name, items);
+            }
+
+            let mut versions = resolve.packages.iter().filter_map(|(_, p)| {
+                (p.name.namespace == name.namespace && p.name.name == name.name)
+                    .then_some(p.name.version.as_ref())
+                    .flatten()
+            });
+
+            let versioned = match (versions.next(), versions.next()) {
+                (Some(version), None) => PackageName {
+                    version: Some(version.clone()),
+                    ..name.clone()
+                },
+                _ => return (name, items),
+            };
+
+            // Both forms may be referenced, in which case the names are left as is
+            if existing.contains(&versioned) {
+                return (name, items);
+            }
+
+            log::debug!("resolving foreign package `{name}` as `{versioned}`");
+            (versioned, items)
+        })
+        .collect();
+}
+
+/// Represents how a dependency on another component is imported.
+#[derive(Clone, Copy)]
+enum DependencyImport<'a> {
+    /// The dependency is imported with an `unlocked-dep` name matching any
+    /// version compatible with the resolved version, if any.
+    Unlocked(Option<&'a Version>),
+    /// The dependency is imported with a `locked-dep` name of the resolved
+    /// version and the digest of its contents.
+    Locked(&'a Version, &'a ContentDigest),
+}
+
+// Used to format the integrity metadata of `locked-dep` import names.
+fn format_integrity(digest: &ContentDigest) -> Result<String> {
+    match digest {
+        ContentDigest::Sha256 { hex } => {
+            let bytes = (0..hex.len())
+                .step_by(2)
+                .map(|i| {
+                    hex.get(i..i + 2)
+                        .and_then(|b| u8::from_str_radix(b, 16).ok())
+                        .with_context(|| format!("invalid content digest `{digest}`"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(format!("sha256-{}", BASE64_STANDARD.encode(bytes)))
+        }
+    }
+}
+
+// Used to format `unlocked-dep` and `locked-dep` import names for
+// dependencies on other components.
+fn format_dep_import(
+    dependency: &PackageRef,
+    name: Option<&str>,
+    import: DependencyImport<'_>,
+) -> Result<String> {
+    let path = match name {
+        Some(name) => format!("{dependency}/{name}"),
+        None => dependency.to_string(),
+    };
+
+    Ok(match import {
+        DependencyImport::Unlocked(Some(version)) => format!(
+            "unlocked-dep=<{path}@{{>={min} <{max}}}>",
             min = version,
             max = Version::new(version.major, version.minor + 1, 0)
         ),
-        (None, None) => format!(
-            "unlocked-dep=<{ns}:{pkg}>",
-            ns = package.name.namespace,
-            pkg = package.name.name
+        DependencyImport::Unlocked(None) => format!("unlocked-dep=<{path}>"),
+        DependencyImport::Locked(version, digest) => format!(
+            "locked-dep=<{path}@{version}>,integrity=<{integrity}>",
+            integrity = format_integrity(digest)?
         ),
+    })
+}
+
+/// A cache of the target worlds created from registry target packages.
+///
+/// The cache lives for a single invocation, so that the packages of a
+/// workspace targeting the same registry package decode and merge it once.
+#[derive(Default)]
+pub struct TargetCache {
+    worlds: HashMap<String, Arc<(Resolve, WorldId)>>,
+}
+
+impl TargetCache {
+    /// Gets the key of the target world of a package in the cache.
+    ///
+    /// The key identifies the selected world and the content of every target
+    /// dependency; returns `None` if any of them is not a registry package.
+    fn key(resolution: &PackageDependencyResolution<'_>, world: Option<&str>) -> Option<String> {
+        let mut packages = resolution
+            .target_resolutions
+            .values()
+            .map(|dependency| match dependency {
+                DependencyResolution::Registry(res) => Some(format!(
+                    "{package}={digest}",
+                    package = res.package,
+                    digest = res.digest
+                )),
+                DependencyResolution::Local(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        packages.sort();
+
+        Some(format!(
+            "{world}:{packages}",
+            world = world.unwrap_or_default(),
+            packages = packages.join(",")
+        ))
     }
 }
 
@@ -60,31 +820,51 @@ pub struct BindingsGenerator<'a> {
     resolution: &'a PackageDependencyResolution<'a>,
     resolve: Resolve,
     world: WorldId,
+    source_files: Vec<PathBuf>,
 }
 
 impl<'a> BindingsGenerator<'a> {
-    /// Creates a new bindings generator for the given bindings directory
-    /// and package dependency resolution.
+    /// Creates a new bindings generator for the given package dependency
+    /// resolution.
+    ///
+    /// Target worlds parsed from local WIT are cached in the given target
+    /// directory.
+    ///
+    /// Target worlds created from registry target packages are reused from
+    /// the given cache.
+    ///
+    /// If a cargo profile is given, the worlds included for the profile are
+    /// merged into the target world.
     ///
     /// Returns a tuple of the bindings generator and a map of import names.
     pub async fn new(
         resolution: &'a PackageDependencyResolution<'a>,
+        target_dir: &Path,
+        cache: &mut TargetCache,
+        profile: Option<&str>,
     ) -> Result<Option<(Self, HashMap<String, String>)>> {
         let mut import_name_map = Default::default();
-        match Self::create_target_world(resolution, &mut import_name_map)
-            .await
-            .with_context(|| {
-                format!(
-                    "failed to create a target world for package `{name}` ({path})",
-                    name = resolution.metadata.name,
-                    path = resolution.metadata.manifest_path.display()
-                )
-            })? {
-            Some((resolve, world, _)) => Ok(Some((
+        match Self::create_target_world(
+            resolution,
+            target_dir,
+            cache,
+            profile,
+            &mut import_name_map,
+        )
+        .await
+        .with_context(|| {
+            format!(
+                "failed to create a target world for package `{name}` ({path})",
+                name = resolution.metadata.name,
+                path = resolution.metadata.manifest_path.display()
+            )
+        })? {
+            Some((resolve, world, source_files)) => Ok(Some((
                 Self {
                     resolution,
                     resolve,
                     world,
+                    source_files,
                 },
                 import_name_map,
             ))),
@@ -92,9 +872,58 @@ impl<'a> BindingsGenerator<'a> {
         }
     }
 
+    /// Gets the resolve and the target world of the bindings.
+    pub fn target_world(&self) -> (&Resolve, WorldId) {
+        (&self.resolve, self.world)
+    }
+
+    /// Gets the local WIT files the target world was parsed from.
+    pub fn source_files(&self) -> &[PathBuf] {
+        &self.source_files
+    }
+
     /// Generates the bindings source for a package.
-    pub fn generate(self) -> Result<String> {
+    pub fn generate(mut self) -> Result<String> {
         let settings = &self.resolution.metadata.section.bindings;
+        if settings.trim_imports {
+            trim_imports(&mut self.resolve, self.world, self.resolution.metadata)?;
+        }
+
+        let source = self.generate_bindings()?;
+        if !settings.doc_origin {
+            return Ok(source);
+        }
+
+        // The origins only document the bindings; the type information
+        // embedded in them is kept as generated from the unmodified resolve
+        add_doc_origins(&mut self.resolve);
+        replace_component_types(&self.generate_bindings()?, &source)
+    }
+
+    /// Generates the bindings source from the resolve of the target world.
+    fn generate_bindings(&self) -> Result<String> {
+        let settings = &self.resolution.metadata.section.bindings;
+        let arbitrary = settings.arbitrary || settings.proptest;
+        let mut derives = settings.derives.clone();
+        if arbitrary {
+            derives.push("::arbitrary::Arbitrary".to_string());
+        }
+
+        // Interfaces with their own settings are generated separately and
+        // the bindings of the world refer to them with `with`
+        let interfaces = self.configured_interfaces()?;
+        let mut with: Vec<_> = settings
+            .with
+            .iter()
+            .map(|(key, value)| (key.clone(), WithOption::Path(value.clone())))
+            .collect();
+        for (key, _) in &interfaces {
+            with.push((
+                self.resolve.name_world_key(key),
+                WithOption::Path(self.interface_path(key)),
+            ));
+        }
+
         let opts = Opts {
             format: settings.format,
             ownership: match settings.ownership {
@@ -108,7 +937,7 @@ impl<'a> BindingsGenerator<'a> {
                     }
                 }
             },
-            additional_derive_attributes: settings.derives.clone(),
+            additional_derive_attributes: derives,
             std_feature: settings.std_feature,
             // We use pregenerated bindings, rather than the `generate!` macro
             // from the `wit-bindgen` crate, so instead of getting the runtime
@@ -116,45 +945,212 @@ impl<'a> BindingsGenerator<'a> {
             // of the `wit-bindgen-rt` API, we just use the `wit-bindgen-rt`
             // crate directly.
             runtime_path: Some("wit_bindgen_rt".to_string()),
-            bitflags_path: None,
+            bitflags_path: arbitrary.then(|| "crate::bindings::arbitrary".to_string()),
             raw_strings: settings.raw_strings,
             skip: settings.skip.clone(),
             stubs: settings.stubs,
             export_prefix: settings.export_prefix.clone(),
-            with: settings
-                .with
+            with,
+            generate_all: settings.generate_all,
+            type_section_suffix: settings.type_section_suffix.clone(),
+            disable_run_ctors_once_workaround: settings.disable_run_ctors_once_workaround,
+            default_bindings_module: settings.default_bindings_module.clone(),
+            export_macro_name: settings.export_macro_name.clone(),
+            pub_export_macro: settings.pub_export_macro,
+            generate_unused_types: settings.generate_unused_types,
+            disable_custom_section_link_helpers: settings.disable_custom_section_link_helpers,
+        };
+
+        let mut source = generate_source(&opts, &self.resolve, self.world)
+            .context("failed to generate bindings")?;
+
+        if !interfaces.is_empty() {
+            source.push_str(&format!(
+                "\n#[allow(dead_code, clippy::all)]\npub mod {INTERFACE_SETTINGS_MODULE} {{\n"
+            ));
+            for (key, interface_settings) in &interfaces {
+                let interface_source = self
+                    .generate_interface(&opts, key, interface_settings)
+                    .with_context(|| {
+                        format!(
+                            "failed to generate bindings for interface `{name}`",
+                            name = self.resolve.name_world_key(key)
+                        )
+                    })?;
+
+                source.push_str(&format!(
+                    "    pub mod {module} {{\n",
+                    module = interface_module_name(&self.resolve, key)
+                ));
+                for line in interface_source.lines() {
+                    if !line.is_empty() {
+                        source.push_str("        ");
+                        source.push_str(line);
+                    }
+                    source.push('\n');
+                }
+                source.push_str(&format!(
+                    "\n        pub use self::{path}::*;\n    }}\n",
+                    path = interface_module_path(&self.resolve, key)
+                ));
+            }
+            source.push_str("}\n");
+        }
+
+        if arbitrary {
+            source = skip_arbitrary_for_handles(source)?;
+            source.push_str(ARBITRARY_MODULE);
+        }
+
+        if settings.proptest {
+            source.push_str(PROPTEST_MODULE);
+        }
+
+        if settings.version_export {
+            let revision = git_revision(&self.resolution.metadata.manifest_path);
+            source.push_str(&format!(
+                "\nconst REVISION: Option<&str> = {revision:?};\n{VERSION_IMPL}"
+            ));
+        }
+
+        Ok(source)
+    }
+
+    /// Gets the imported interfaces of the target world that have settings of
+    /// their own, in the order the world imports them.
+    fn configured_interfaces(&self) -> Result<Vec<(WorldKey, &'a InterfaceSettings)>> {
+        let configured = &self.resolution.metadata.section.bindings.interface_settings;
+        let world = &self.resolve.worlds[self.world];
+
+        for name in configured.keys() {
+            let exported = world.exports.iter().any(|(key, item)| {
+                matches!(item, WorldItem::Interface { .. })
+                    && self.resolve.name_world_key(key) == *name
+            });
+            if exported {
+                bail!(
+                    "interface `{name}` in `interface_settings` is exported by the target world; \
+                     only the bindings of imported interfaces can be configured"
+                );
+            }
+        }
+
+        let mut interfaces = Vec::new();
+        for (key, item) in &world.imports {
+            let WorldItem::Interface { id, .. } = item else {
+                continue;
+            };
+
+            let Some(interface_settings) = configured.get(&self.resolve.name_world_key(key)) else {
+                continue;
+            };
+
+            let interface = &self.resolve.interfaces[*id];
+            for name in &interface_settings.skip {
+                if !interface.functions.contains_key(name) {
+                    bail!(
+                        "function `{name}` to skip is not defined by interface `{interface}`",
+                        interface = self.resolve.name_world_key(key)
+                    );
+                }
+            }
+
+            interfaces.push((key.clone(), interface_settings));
+        }
+
+        if let Some(name) = configured.keys().find(|name| {
+            !interfaces
+                .iter()
+                .any(|(key, _)| self.resolve.name_world_key(key) == **name)
+        }) {
+            bail!("interface `{name}` in `interface_settings` is not imported by the target world");
+        }
+
+        Ok(interfaces)
+    }
+
+    /// Gets the Rust path of the bindings of an imported interface.
+    fn interface_path(&self, key: &WorldKey) -> String {
+        let settings = &self.resolution.metadata.section.bindings;
+        let name = self.resolve.name_world_key(key);
+        if let Some(path) = settings.with.get(&name) {
+            return path.clone();
+        }
+
+        let path = interface_module_path(&self.resolve, key);
+        if settings.interface_settings.contains_key(&name) {
+            format!(
+                "crate::bindings::{INTERFACE_SETTINGS_MODULE}::{module}::{path}",
+                module = interface_module_name(&self.resolve, key)
+            )
+        } else {
+            format!("crate::bindings::{path}")
+        }
+    }
+
+    /// Generates the bindings of an imported interface with its own settings.
+    ///
+    /// The bindings are generated for a world importing the interfaces of the
+    /// target world, with every other interface referring to the bindings of
+    /// the target world.
+    fn generate_interface(
+        &self,
+        opts: &Opts,
+        key: &WorldKey,
+        interface_settings: &InterfaceSettings,
+    ) -> Result<String> {
+        let mut resolve = self.resolve.clone();
+        let target = &resolve.worlds[self.world];
+        let world = World {
+            name: format!(
+                "{name}-{module}",
+                name = target.name,
+                module = interface_module_name(&self.resolve, key).to_kebab_case()
+            ),
+            imports: target
+                .imports
                 .iter()
-                .map(|(key, value)| (key.clone(), WithOption::Path(value.clone())))
+                .filter(|(_, item)| matches!(item, WorldItem::Interface { .. }))
+                .map(|(key, item)| (key.clone(), item.clone()))
                 .collect(),
-            generate_all: settings.generate_all,
-            type_section_suffix: settings.type_section_suffix.clone(),
-            disable_run_ctors_once_workaround: settings.disable_run_ctors_once_workaround,
-            default_bindings_module: settings.default_bindings_module.clone(),
-            export_macro_name: settings.export_macro_name.clone(),
-            pub_export_macro: settings.pub_export_macro,
-            generate_unused_types: settings.generate_unused_types,
-            disable_custom_section_link_helpers: settings.disable_custom_section_link_helpers,
+            exports: Default::default(),
+            package: target.package,
+            docs: Default::default(),
+            stability: target.stability.clone(),
+            includes: Default::default(),
+            include_names: Default::default(),
         };
 
-        let mut files = Files::default();
-        opts.build()
-            .generate(&self.resolve, self.world, &mut files)
-            .context("failed to generate bindings")?;
-
-        let sources: Vec<_> = files
-            .iter()
-            .map(|(_, s)| std::str::from_utf8(s).expect("expected utf-8 bindings source"))
+        let with = world
+            .imports
+            .keys()
+            .map(|k| {
+                let option = if k == key {
+                    WithOption::Generate
+                } else {
+                    WithOption::Path(self.interface_path(k))
+                };
+                (self.resolve.name_world_key(k), option)
+            })
             .collect();
-        assert!(
-            sources.len() == 1,
-            "expected exactly one source file to be generated"
-        );
 
-        Ok(sources[0].to_string())
+        let world = resolve.worlds.alloc(world);
+
+        let mut opts = opts.clone();
+        opts.additional_derive_attributes
+            .extend(interface_settings.derives.iter().cloned());
+        opts.skip.extend(interface_settings.skip.iter().cloned());
+        opts.with = with;
+        opts.stubs = false;
+        opts.pub_export_macro = false;
+        generate_source(&opts, &resolve, world)
     }
 
     async fn create_target_world(
         resolution: &PackageDependencyResolution<'_>,
+        target_dir: &Path,
+        cache: &mut TargetCache,
+        profile: Option<&str>,
         import_name_map: &mut HashMap<String, String>,
     ) -> Result<Option<(Resolve, WorldId, Vec<PathBuf>)>> {
         log::debug!(
@@ -166,94 +1162,160 @@ impl<'a> BindingsGenerator<'a> {
         // A flag used to determine whether the target is empty. It must meet two conditions:
         // no wit files and no dependencies.
         let mut empty_target = false;
-        let (mut merged, world_id, source_files) = if let Some(name) =
-            resolution.metadata.target_package()
-        {
-            Self::target_package(resolution, name, resolution.metadata.target_world()).await?
-        } else if let Some(path) = resolution.metadata.target_path() {
-            Self::target_local_path(resolution, &path, resolution.metadata.target_world()).await?
-        } else {
-            empty_target = true;
-            let (merged, world) = Self::target_empty_world(resolution);
-            (merged, world, Vec::new())
-        };
+        let (mut merged, world_id, source_files) =
+            if let Some(name) = resolution.metadata.target_package() {
+                Self::cached_target_package(resolution, name, cache).await?
+            } else if let Some(path) = resolution.metadata.target_path() {
+                Self::cached_target_local_path(resolution, &path, target_dir).await?
+            } else {
+                empty_target = true;
+                let (merged, world) = Self::target_empty_world(resolution);
+                (merged, world, Vec::new())
+            };
 
-        // Merge all component dependencies as interface imports
+        if let Some(profile) = profile {
+            Self::include_profile_worlds(resolution, &mut merged, world_id, profile)?;
+        }
+
+        rename_included_functions(&mut merged, world_id);
+
+        let mut dependencies = Vec::new();
         for (id, dependency) in &resolution.resolutions {
+            let (resolve, component_world_id) =
+                dependency
+                    .decode()
+                    .await?
+                    .into_component_world()
+                    .with_context(|| format!("failed to decode component dependency `{id}`"))?;
+            dependencies.push((id, dependency, resolve, component_world_id));
+        }
+
+        // Merge all component dependencies as interface imports
+        for (id, dependency, mut resolve, component_world_id) in merge_order(dependencies) {
             log::debug!("importing component dependency `{id}`");
             empty_target = false;
 
-            let (mut resolve, component_world_id) = dependency
-                .decode()
-                .await?
-                .into_component_world()
-                .with_context(|| format!("failed to decode component dependency `{id}`"))?;
-
-            // Set the world name as currently it defaults to "root"
-            // For now, set it to the name from the id
-            let world = &mut resolve.worlds[component_world_id];
-            let old_name = mem::replace(&mut world.name, id.name().to_string());
-
-            let pkg = &mut resolve.packages[world.package.unwrap()];
-            pkg.name.namespace = id.namespace().to_string();
-            pkg.name.name = id.name().to_string();
-
-            // Update the world name in the `pkg.worlds` map too. Don't use
-            // `MutableKeys` because the new world name may not have the same
-            // hash as the old world name.
-            let mut new_worlds = IndexMap::new();
-            for (name, world) in pkg.worlds.iter() {
-                if name == &old_name {
-                    new_worlds.insert(id.name().to_string(), *world);
-                } else {
-                    new_worlds.insert(name.clone(), *world);
-                }
-            }
-            assert_eq!(pkg.worlds.len(), new_worlds.len());
-            pkg.worlds = new_worlds;
+            let name = Self::import_package_name(
+                &merged,
+                world_id,
+                &resolve,
+                id,
+                resolution.metadata.import_names.get(id),
+            )?;
+            Self::name_component_world(&mut resolve, component_world_id, id, &name);
 
             let source = merged
                 .merge(resolve)
                 .with_context(|| format!("failed to merge world of dependency `{id}`"))?
                 .worlds[component_world_id.index()]
             .unwrap();
-            Self::import_world(
-                &mut merged,
-                source,
-                world_id,
-                dependency.version(),
-                import_name_map,
-            )?;
+
+            let import = match dependency {
+                DependencyResolution::Registry(res)
+                    if resolution.metadata.locked_dependencies.contains(id) =>
+                {
+                    DependencyImport::Locked(&res.version, &res.digest)
+                }
+                _ => DependencyImport::Unlocked(dependency.version()),
+            };
+            Self::import_world(&mut merged, source, world_id, id, import, import_name_map)?;
         }
 
         if empty_target {
             return Ok(None);
         };
+
+        if resolution.metadata.section.bindings.version_export {
+            Self::export_version(&mut merged, world_id)?;
+        }
         Ok(Some((merged, world_id, source_files)))
     }
 
+    /// Gets the target world from a target package, reusing the target world
+    /// created for another package with the same target dependencies.
+    async fn cached_target_package(
+        resolution: &PackageDependencyResolution<'_>,
+        name: &PackageRef,
+        cache: &mut TargetCache,
+    ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
+        let world = resolution.metadata.target_world();
+        let key = match TargetCache::key(resolution, world) {
+            Some(key) => key,
+            None => return Self::target_package(resolution, name, world).await,
+        };
+
+        if let Some(target) = cache.worlds.get(&key) {
+            log::debug!(
+                "reusing target world of package `{name}` for package `{package}`",
+                package = resolution.metadata.name
+            );
+            let (resolve, world) = target.as_ref();
+            return Ok((resolve.clone(), *world, Vec::new()));
+        }
+
+        let (resolve, world, source_files) = Self::target_package(resolution, name, world).await?;
+        cache.worlds.insert(key, Arc::new((resolve.clone(), world)));
+        Ok((resolve, world, source_files))
+    }
+
     async fn target_package(
         resolution: &PackageDependencyResolution<'_>,
         name: &PackageRef,
         world: Option<&str>,
     ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
         // We must have resolved a target package dependency at this point
-        assert_eq!(resolution.target_resolutions.len(), 1);
+        let dependency = resolution
+            .target_resolutions
+            .get(name)
+            .expect("missing target package resolution");
 
-        // Decode the target package dependency
-        let dependency = resolution.target_resolutions.values().next().unwrap();
-        let (resolve, pkg, source_files) =
-            dependency.decode().await?.resolve().with_context(|| {
-                format!(
-                    "failed to resolve target package `{name}`",
-                    name = dependency.name()
-                )
-            })?;
+        // Decode the dependencies of the target package, followed by the
+        // target package itself so that their full definitions are merged
+        // before the copies of them in the target package
+        let mut deps = Vec::new();
+        for (id, dependency) in &resolution.target_resolutions {
+            if id != name {
+                deps.push(dependency.decode().await?);
+            }
+        }
+
+        let target = dependency.decode().await?;
+        let package_name = target.package_name().clone();
+        deps.push(target);
+
+        let (merged, source_files) = merge_dependencies(deps)
+            .with_context(|| format!("failed to resolve target package `{name}`"))?;
+        let pkg = merged.package_names[&package_name];
 
-        let world = resolve
-            .select_world(pkg, world)
+        let world = select_world(&merged, pkg, world)
             .with_context(|| format!("failed to select world from target package `{name}`"))?;
 
+        Ok((merged, world, source_files))
+    }
+
+    /// Gets the target world from a local path, reusing the cached target
+    /// world if none of its sources changed.
+    async fn cached_target_local_path(
+        resolution: &PackageDependencyResolution<'_>,
+        path: &Path,
+        target_dir: &Path,
+    ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
+        let world = resolution.metadata.target_world();
+        let cache = match WitCache::new(&HostFileSystem, target_dir, resolution, path, world) {
+            Ok(cache) => cache,
+            Err(e) => {
+                log::debug!("not caching target world: {e:?}");
+                return Self::target_local_path(resolution, path, world).await;
+            }
+        };
+
+        if let Some(target) = cache.load() {
+            return Ok(target);
+        }
+
+        let (resolve, world, source_files) =
+            Self::target_local_path(resolution, path, world).await?;
+        cache.store(&resolve, world, &source_files);
         Ok((resolve, world, source_files))
     }
 
@@ -262,21 +1324,66 @@ impl<'a> BindingsGenerator<'a> {
         path: &Path,
         world: Option<&str>,
     ) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
-        let mut merged = Resolve::default();
-
         // Start by decoding all of the target dependencies
-        let mut deps = IndexMap::new();
-        for (id, resolution) in &resolution.target_resolutions {
-            let decoded = resolution.decode().await?;
-            let name = decoded.package_name();
+        let mut deps = Vec::new();
+        for resolution in resolution.target_resolutions.values() {
+            deps.push(resolution.decode().await?);
+        }
+
+        // Followed by the packages in the `deps` directory next to a
+        // single-file target, or of a target directory with a `wit-deps`
+        // manifest, that are not target dependencies
+        let wit_deps = path.is_dir() && path.join(WIT_DEPS_MANIFEST).is_file();
+        if wit_deps {
+            check_wit_deps(path)?;
+        }
+
+        let deps_dir = local_target_deps_dir(path);
+        let (dir_resolutions, dir_packages): (Vec<_>, Vec<_>) = match &deps_dir {
+            Some(dir) if dir.is_dir() => parse_deps_dir(dir)?.into_iter().unzip(),
+            _ => Default::default(),
+        };
+
+        let mut wasm_files = Vec::new();
+        for (resolution, package) in dir_resolutions.iter().zip(dir_packages) {
+            let dependency = match package {
+                DepsDirPackage::Wit(package) => DecodedDependency::Wit {
+                    resolution,
+                    package,
+                },
+                DepsDirPackage::Wasm(decoded) => DecodedDependency::Wasm {
+                    resolution,
+                    decoded,
+                },
+            };
+
+            let name = dependency.package_name();
+            if deps.iter().any(|d: &DecodedDependency| {
+                let other = d.package_name();
+                other.namespace == name.namespace && other.name == name.name
+            }) {
+                log::debug!(
+                    "ignoring package `{name}` in directory `{dir}` as it is a target dependency",
+                    dir = deps_dir.as_deref().unwrap().display()
+                );
+                continue;
+            }
 
-            if let Some(prev) = deps.insert(name.clone(), decoded) {
-                bail!("duplicate definitions of package `{name}` found while decoding target dependency `{id}`", name = prev.package_name());
+            if let (DecodedDependency::Wasm { .. }, DependencyResolution::Local(local)) =
+                (&dependency, resolution)
+            {
+                wasm_files.push(local.path.clone());
             }
+
+            deps.push(dependency);
         }
 
+        let (mut merged, mut source_files) =
+            merge_dependencies(deps).context("failed to merge target dependencies")?;
+        source_files.extend(wasm_files);
+
         // Parse the target package itself
-        let root = if path.is_dir() {
+        let mut root = if path.is_dir() {
             UnresolvedPackageGroup::parse_dir(path).with_context(|| {
                 format!(
                     "failed to parse local target from directory `{}`",
@@ -292,53 +1399,39 @@ impl<'a> BindingsGenerator<'a> {
             })?
         };
 
-        let mut source_files: Vec<_> = root
-            .source_map
-            .source_files()
-            .map(Path::to_path_buf)
-            .collect();
+        source_files.extend(root.source_map.source_files().map(Path::to_path_buf));
 
-        // Do a topological sort of the dependencies
-        let mut order = IndexSet::new();
-        let mut visiting = HashSet::new();
-        for dep in deps.values() {
-            visit(dep, &deps, &mut order, &mut visiting)?;
+        version_foreign_deps(&merged, &mut root.main);
+        for nested in &mut root.nested {
+            version_foreign_deps(&merged, nested);
         }
 
-        assert!(visiting.is_empty());
-
-        // Merge all of the dependencies first
-        for name in order {
-            match deps.swap_remove(&name).unwrap() {
-                DecodedDependency::Wit {
-                    resolution,
-                    package,
-                } => {
-                    source_files.extend(package.source_map.source_files().map(Path::to_path_buf));
-                    merged.push_group(package).with_context(|| {
-                        format!(
-                            "failed to merge target dependency `{name}`",
-                            name = resolution.name()
-                        )
-                    })?;
-                }
-                DecodedDependency::Wasm {
-                    resolution,
-                    decoded,
-                } => {
-                    let resolve = match decoded {
-                        DecodedWasm::WitPackage(resolve, _) => resolve,
-                        DecodedWasm::Component(resolve, _) => resolve,
-                    };
+        let unresolved = unresolved_foreign_deps(&merged, &root);
+        if !unresolved.is_empty() {
+            let mut message = format!(
+                "local target `{path}` depends on foreign packages that were not found:",
+                path = path.display()
+            );
+            for name in &unresolved {
+                message.push_str(&format!("\n  {name}"));
+            }
 
-                    merged.merge(resolve).with_context(|| {
-                        format!(
-                            "failed to merge world of target dependency `{name}`",
-                            name = resolution.name()
-                        )
-                    })?;
-                }
+            message.push_str(
+                "\n\nhelp: add them to `[package.metadata.component.target.dependencies]`",
+            );
+            if wit_deps {
+                message.push_str(&format!(
+                    " or to `{manifest}` and run `wit-deps` to fetch them",
+                    manifest = path.join(WIT_DEPS_MANIFEST).display()
+                ));
+            } else if let Some(dir) = &deps_dir {
+                message.push_str(&format!(
+                    " or to the `{dir}` directory",
+                    dir = dir.display()
+                ));
             }
+
+            bail!(message);
         }
 
         let package = merged.push_group(root).with_context(|| {
@@ -348,70 +1441,82 @@ impl<'a> BindingsGenerator<'a> {
             )
         })?;
 
-        let world = merged
-            .select_world(package, world)
-            .with_context(|| match world {
-                Some(world) => {
-                    format!(
-                        "failed to select the specified world `{world}` for local target `{path}`",
-                        path = path.display()
-                    )
-                }
-                None => format!(
-                    "failed to select the default world to use for local target `{path}`",
+        let world = select_world(&merged, package, world).with_context(|| match world {
+            Some(world) => {
+                format!(
+                    "failed to select the specified world `{world}` for local target `{path}`",
                     path = path.display()
-                ),
-            })?;
+                )
+            }
+            None => format!(
+                "failed to select the default world to use for local target `{path}`",
+                path = path.display()
+            ),
+        })?;
 
-        return Ok((merged, world, source_files));
+        Ok((merged, world, source_files))
+    }
 
-        fn visit<'a>(
-            dep: &'a DecodedDependency<'a>,
-            deps: &'a IndexMap<PackageName, DecodedDependency>,
-            order: &mut IndexSet<PackageName>,
-            visiting: &mut HashSet<&'a PackageName>,
-        ) -> Result<()> {
-            if order.contains(dep.package_name()) {
-                return Ok(());
-            }
+    /// Merges the worlds included for a cargo profile into the target world.
+    ///
+    /// The included worlds are selected relative to the package of the target
+    /// world.
+    fn include_profile_worlds(
+        resolution: &PackageDependencyResolution<'_>,
+        resolve: &mut Resolve,
+        world: WorldId,
+        profile: &str,
+    ) -> Result<()> {
+        let Some(section) = resolution.metadata.section.profile.get(profile) else {
+            return Ok(());
+        };
 
-            // Visit any unresolved foreign dependencies
-            match dep {
-                DecodedDependency::Wit {
-                    package,
-                    resolution,
-                } => {
-                    for name in package.main.foreign_deps.keys() {
-                        if !visiting.insert(name) {
-                            bail!("foreign dependency `{name}` forms a dependency cycle while parsing target dependency `{other}`", other = resolution.name());
-                        }
+        for name in &section.include {
+            let package = resolve.worlds[world]
+                .package
+                .context("the target world has no package")?;
+            let include = select_world(resolve, package, Some(name)).with_context(|| {
+                format!("failed to select world `{name}` included for profile `{profile}`")
+            })?;
 
-                        // Only visit known dependencies
-                        // wit-parser will error on unknown foreign dependencies when
-                        // the package is resolved
-                        if let Some(dep) = deps.get(name) {
-                            visit(dep, deps, order, visiting)?
-                        }
+            log::debug!("including world `{name}` for profile `{profile}`");
+            resolve.merge_worlds(include, world).with_context(|| {
+                format!("failed to include world `{name}` for profile `{profile}`")
+            })?;
+        }
 
-                        assert!(visiting.remove(name));
-                    }
-                }
-                DecodedDependency::Wasm { .. } => {
-                    // No unresolved foreign dependencies for decoded wasm files
-                }
-            }
+        Ok(())
+    }
 
-            assert!(order.insert(dep.package_name().clone()));
+    /// Adds the export of the version interface to the target world.
+    fn export_version(resolve: &mut Resolve, world: WorldId) -> Result<()> {
+        let package = resolve
+            .push_str("cargo-component-metadata.wit", VERSION_WIT)
+            .context("failed to add the version interface")?;
+        let id = resolve.packages[package].interfaces["version"];
 
-            Ok(())
+        if resolve.worlds[world]
+            .exports
+            .insert(
+                WorldKey::Interface(id),
+                WorldItem::Interface {
+                    id,
+                    stability: Default::default(),
+                },
+            )
+            .is_some()
+        {
+            bail!("the target world already exports `cargo-component:metadata/version`");
         }
+
+        Ok(())
     }
 
     fn target_empty_world(resolution: &PackageDependencyResolution) -> (Resolve, WorldId) {
         let mut resolve = Resolve::default();
         let name = resolution.metadata.name.to_kebab_case();
         let pkg_name = PackageName {
-            namespace: "component".to_string(),
+            namespace: resolution.metadata.namespace.clone(),
             name: name.clone(),
             version: None,
         };
@@ -441,6 +1546,144 @@ impl<'a> BindingsGenerator<'a> {
         (resolve, world)
     }
 
+    /// Chooses the name of the package a component dependency is imported as.
+    ///
+    /// The name is the name of the dependency unless the dependency sets
+    /// `import-name`. A name that conflicts with a package or an import of the
+    /// target world is suffixed with a number (e.g. `bar2`) so that both can
+    /// be imported.
+    ///
+    /// A package of the same name defined by the dependency itself (one whose
+    /// interfaces it exports) is not a conflict, as the world of the dependency
+    /// joins that package.
+    fn import_package_name(
+        resolve: &Resolve,
+        world: WorldId,
+        dependency_resolve: &Resolve,
+        dependency: &PackageRef,
+        import_name: Option<&String>,
+    ) -> Result<String> {
+        let namespace = dependency.namespace().as_ref();
+        let conflicts = |name: &str| {
+            let interface = format!("{namespace}-{name}");
+            let package = PackageName {
+                namespace: namespace.to_string(),
+                name: name.to_string(),
+                version: None,
+            };
+            (resolve.package_names.contains_key(&package)
+                && !dependency_resolve.package_names.contains_key(&package))
+                || resolve.worlds[world]
+                    .imports
+                    .keys()
+                    .any(|key| resolve.name_world_key(key) == interface)
+        };
+
+        if let Some(name) = import_name {
+            if conflicts(name) {
+                bail!(
+                    "import name `{name}` of dependency `{dependency}` conflicts with an import \
+                     in the target world"
+                );
+            }
+
+            return Ok(name.clone());
+        }
+
+        let name = dependency.name().as_ref();
+        if !conflicts(name) {
+            return Ok(name.to_string());
+        }
+
+        let renamed = (2..)
+            .map(|n| format!("{name}{n}"))
+            .find(|n| !conflicts(n))
+            .expect("a name should not conflict");
+        Terminal::current().warn(format!(
+            "dependency `{dependency}` conflicts with an import in the target world and is \
+             imported as `{namespace}:{renamed}`; set `import-name` in its dependency entry to \
+             choose the name"
+        ))?;
+
+        Ok(renamed)
+    }
+
+    /// Adds an interface allocated for the imports of a component dependency
+    /// to its package, so that the resolve stays valid for later merges.
+    fn add_package_interface(
+        resolve: &mut Resolve,
+        interface: InterfaceId,
+        dependency: &PackageRef,
+    ) -> Result<()> {
+        let iface = &resolve.interfaces[interface];
+        let name = iface.name.clone().expect("interface should be named");
+        let package = iface.package.expect("interface should have a package");
+        if resolve.packages[package]
+            .interfaces
+            .insert(name.clone(), interface)
+            .is_some()
+        {
+            bail!("cannot import dependency `{dependency}` because its import `{name}` conflicts with an interface of its package");
+        }
+
+        Ok(())
+    }
+
+    /// Names the world of a decoded component dependency and its package.
+    ///
+    /// The world of a decoded component is in a `root:component` package; it
+    /// is renamed to the name the dependency is imported as. If the dependency
+    /// exports interfaces of a package by that name, the world joins that
+    /// package instead so that the dependencies using the types of those
+    /// interfaces share a single definition of them.
+    fn name_component_world(
+        resolve: &mut Resolve,
+        world_id: WorldId,
+        dependency: &PackageRef,
+        name: &str,
+    ) {
+        let root = resolve.worlds[world_id].package.unwrap();
+        let old_name = mem::replace(&mut resolve.worlds[world_id].name, name.to_string());
+        let package_name = PackageName {
+            namespace: dependency.namespace().to_string(),
+            name: name.to_string(),
+            version: None,
+        };
+
+        match resolve.package_names.get(&package_name).copied() {
+            Some(package) if package != root => {
+                resolve.packages[root].worlds.shift_remove(&old_name);
+                resolve.packages[package]
+                    .worlds
+                    .insert(name.to_string(), world_id);
+                resolve.worlds[world_id].package = Some(package);
+            }
+            _ => {
+                let pkg = &mut resolve.packages[root];
+                let old_package_name = mem::replace(&mut pkg.name, package_name.clone());
+                if resolve.package_names.get(&old_package_name) == Some(&root) {
+                    resolve.package_names.shift_remove(&old_package_name);
+                }
+                resolve.package_names.insert(package_name, root);
+
+                // Update the world name in the `pkg.worlds` map too. Don't use
+                // `MutableKeys` because the new world name may not have the same
+                // hash as the old world name.
+                let pkg = &mut resolve.packages[root];
+                let mut new_worlds = IndexMap::new();
+                for (world_name, world) in pkg.worlds.iter() {
+                    if world_name == &old_name {
+                        new_worlds.insert(name.to_string(), *world);
+                    } else {
+                        new_worlds.insert(world_name.clone(), *world);
+                    }
+                }
+                assert_eq!(pkg.worlds.len(), new_worlds.len());
+                pkg.worlds = new_worlds;
+            }
+        }
+    }
+
     /// This function imports in the target world the exports of the source world.
     ///
     /// This is used for dependencies on other components so that their exports may
@@ -453,7 +1696,8 @@ impl<'a> BindingsGenerator<'a> {
         resolve: &mut Resolve,
         source_id: WorldId,
         target_id: WorldId,
-        version: Option<&Version>,
+        dependency: &PackageRef,
+        import: DependencyImport<'_>,
         import_name_map: &mut HashMap<String, String>,
     ) -> Result<()> {
         let mut functions = IndexMap::default();
@@ -549,8 +1793,21 @@ impl<'a> BindingsGenerator<'a> {
             }
         }
 
-        // Import the used interfaces
-        resolve.worlds[target_id].imports.extend(used);
+        // Import the used interfaces, except for those imported already under
+        // the name of a component dependency, including this one
+        let imports = &mut resolve.worlds[target_id].imports;
+        let imported: HashSet<_> = imports
+            .values()
+            .filter_map(|item| match item {
+                WorldItem::Interface { id, .. } => Some(*id),
+                _ => None,
+            })
+            .chain(interfaces.values().copied())
+            .collect();
+        imports.extend(used.into_iter().filter(|(_, item)| match item {
+            WorldItem::Interface { id, .. } => !imported.contains(id),
+            _ => true,
+        }));
 
         // Import the exported interfaces
         for (name, id) in interfaces {
@@ -565,22 +1822,24 @@ impl<'a> BindingsGenerator<'a> {
                 package,
                 stability: Default::default(),
             });
+            Self::add_package_interface(resolve, name_id, dependency)?;
 
-            let import_name =
-                format_dep_import(&resolve.packages[package.unwrap()], Some(&name), version);
+            let import_name = format_dep_import(dependency, Some(&name), import)?;
             import_name_map.insert(resolve.id_of(name_id).unwrap(), import_name);
 
-            if resolve.worlds[target_id]
-                .imports
-                .insert(
-                    WorldKey::Interface(name_id),
-                    WorldItem::Interface {
-                        id,
-                        stability: Default::default(),
-                    },
-                )
-                .is_some()
-            {
+            let imports = &mut resolve.worlds[target_id].imports;
+            let item = WorldItem::Interface {
+                id,
+                stability: Default::default(),
+            };
+
+            // An interface used by the types of a dependency merged earlier is
+            // imported under the name of this dependency in its place, so that
+            // the types are only defined once
+            if let Some(index) = imports.get_index_of(&WorldKey::Interface(id)) {
+                imports.shift_remove_index(index);
+                imports.shift_insert(index, WorldKey::Interface(name_id), item);
+            } else if imports.insert(WorldKey::Interface(name_id), item).is_some() {
                 let iface = &resolve.interfaces[id];
                 let package = &resolve.packages[iface.package.expect("interface has no package")];
                 let id = package
@@ -600,7 +1859,7 @@ impl<'a> BindingsGenerator<'a> {
                 pkg = package.name.name
             );
 
-            import_name_map.insert(name.clone(), format_dep_import(package, None, version));
+            import_name_map.insert(name.clone(), format_dep_import(dependency, None, import)?);
 
             let interface = resolve.interfaces.alloc(Interface {
                 name: Some(name.clone()),
@@ -610,6 +1869,7 @@ impl<'a> BindingsGenerator<'a> {
                 package: source.package,
                 stability: Default::default(),
             });
+            Self::add_package_interface(resolve, interface, dependency)?;
 
             // Add any types owned by the world to the interface
             for (id, ty) in resolve.types.iter() {
@@ -639,3 +1899,213 @@ impl<'a> BindingsGenerator<'a> {
         Ok(())
     }
 }
+
+/// Orders decoded component dependencies for merging into the target world.
+///
+/// A dependency whose exported interfaces are used by another dependency (for
+/// example, for the types of its own exports) is merged first. The interfaces
+/// of the other dependency then merge into the complete interface of the
+/// exporting dependency, so that both share a single definition of each type.
+fn merge_order<'a>(
+    mut dependencies: Vec<(&'a PackageRef, &'a DependencyResolution, Resolve, WorldId)>,
+) -> Vec<(&'a PackageRef, &'a DependencyResolution, Resolve, WorldId)> {
+    // Start from the order of the names so the result is deterministic
+    dependencies.sort_by_key(|(id, ..)| id.to_string());
+
+    let packages = |resolve: &Resolve, world: WorldId, exports: bool| {
+        let world = &resolve.worlds[world];
+        let items = if exports {
+            &world.exports
+        } else {
+            &world.imports
+        };
+        items
+            .values()
+            .filter_map(|item| match item {
+                WorldItem::Interface { id, .. } => resolve.interfaces[*id].package,
+                _ => None,
+            })
+            .filter(|package| Some(*package) != world.package)
+            .map(|package| resolve.packages[package].name.to_string())
+            .collect::<HashSet<_>>()
+    };
+
+    let mut remaining: Vec<_> = dependencies
+        .into_iter()
+        .map(|dependency| {
+            let exported = packages(&dependency.2, dependency.3, true);
+            let imported = packages(&dependency.2, dependency.3, false);
+            (dependency, exported, imported)
+        })
+        .collect();
+
+    let mut ordered = Vec::with_capacity(remaining.len());
+    while !remaining.is_empty() {
+        // Take the first dependency that uses no package exported by another
+        // remaining dependency; on a cycle, fall back to the first one
+        let index = (0..remaining.len())
+            .find(|&i| {
+                remaining
+                    .iter()
+                    .enumerate()
+                    .all(|(j, (_, exported, _))| i == j || exported.is_disjoint(&remaining[i].2))
+            })
+            .unwrap_or(0);
+        ordered.push(remaining.remove(index).0);
+    }
+
+    ordered
+}
+
+/// Removes the imported interfaces of a world that are not referenced by the
+/// Rust sources of a package.
+///
+/// An interface is referenced when the names of its package and interface
+/// modules both appear in the sources next to the bindings file. Interfaces
+/// configured with `with` or `interface_settings`, and interfaces the kept
+/// imports or the exports depend on, are always kept.
+fn trim_imports(resolve: &mut Resolve, world: WorldId, metadata: &ComponentMetadata) -> Result<()> {
+    let bindings_path = bindings_path(metadata);
+    let dir = bindings_path.parent().unwrap();
+    let mut words = HashSet::new();
+    for source in rust_sources(&HostFileSystem, dir, &bindings_path).with_context(|| {
+        format!(
+            "failed to read source directory `{dir}`",
+            dir = dir.display()
+        )
+    })? {
+        let contents = fs::read_to_string(&source).with_context(|| {
+            format!(
+                "failed to read source file `{path}`",
+                path = source.display()
+            )
+        })?;
+        words.extend(
+            contents
+                .split(|c: char| !c.is_alphanumeric() && c != '_')
+                .filter(|w| !w.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    let settings = &metadata.section.bindings;
+    let referenced = |key: &WorldKey| match key {
+        WorldKey::Name(name) => words.contains(&to_rust_ident(name)),
+        WorldKey::Interface(id) => {
+            let interface = &resolve.interfaces[*id];
+            let package = interface.package.expect("interface should have a package");
+            words.contains(&name_package_module(resolve, package))
+                && words.contains(&to_rust_ident(
+                    interface
+                        .name
+                        .as_deref()
+                        .expect("interface should be named"),
+                ))
+        }
+    };
+
+    let mut pending = Vec::new();
+    for (key, item) in &resolve.worlds[world].imports {
+        match item {
+            WorldItem::Interface { id, .. } => {
+                let name = resolve.name_world_key(key);
+                if settings.with.contains_key(&name)
+                    || settings.interface_settings.contains_key(&name)
+                    || referenced(key)
+                {
+                    pending.push(*id);
+                }
+            }
+            WorldItem::Type(ty) => pending.extend(resolve.type_interface_dep(*ty)),
+            WorldItem::Function(_) => {}
+        }
+    }
+
+    for item in resolve.worlds[world].exports.values() {
+        if let WorldItem::Interface { id, .. } = item {
+            pending.extend(resolve.interface_direct_deps(*id));
+        }
+    }
+
+    let mut kept = HashSet::new();
+    while let Some(id) = pending.pop() {
+        if kept.insert(id) {
+            pending.extend(resolve.interface_direct_deps(id));
+        }
+    }
+
+    let imports = &mut resolve.worlds[world].imports;
+    let len = imports.len();
+    imports.retain(|_, item| match item {
+        WorldItem::Interface { id, .. } => kept.contains(id),
+        _ => true,
+    });
+
+    log::debug!(
+        "trimmed {count} unreferenced imported interface(s) from the bindings",
+        count = len - imports.len()
+    );
+
+    Ok(())
+}
+
+/// Generates the bindings source for a world.
+fn generate_source(opts: &Opts, resolve: &Resolve, world: WorldId) -> Result<String> {
+    let mut files = Files::default();
+    opts.clone().build().generate(resolve, world, &mut files)?;
+
+    let sources: Vec<_> = files
+        .iter()
+        .map(|(_, s)| std::str::from_utf8(s).expect("expected utf-8 bindings source"))
+        .collect();
+    assert!(
+        sources.len() == 1,
+        "expected exactly one source file to be generated"
+    );
+
+    Ok(sources[0].to_string())
+}
+
+/// Gets the path of the module wit-bindgen generates for an imported
+/// interface, relative to the root of the bindings.
+fn interface_module_path(resolve: &Resolve, key: &WorldKey) -> String {
+    match key {
+        WorldKey::Name(name) => to_rust_ident(name),
+        WorldKey::Interface(id) => {
+            let interface = &resolve.interfaces[*id];
+            let package = interface.package.expect("interface should have a package");
+            format!(
+                "{namespace}::{package}::{name}",
+                namespace = to_rust_ident(&resolve.packages[package].name.namespace),
+                package = name_package_module(resolve, package),
+                name = to_rust_ident(
+                    interface
+                        .name
+                        .as_deref()
+                        .expect("interface should be named")
+                )
+            )
+        }
+    }
+}
+
+/// Gets the name of the module containing the separately generated bindings
+/// of an interface with its own settings.
+fn interface_module_name(resolve: &Resolve, key: &WorldKey) -> String {
+    resolve.name_world_key(key).to_snake_case()
+}
+
+/// Gets the git commit of the repository containing the given manifest.
+fn git_revision(manifest_path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(manifest_path.parent()?)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_string())
+}
@@ -0,0 +1,236 @@
+//! Module for bundles of registry dependencies used for offline builds.
+//!
+//! A bundle starts with a magic value and a format version, followed by a
+//! sequence of entries. Each entry is a length-prefixed JSON header that
+//! describes the entry followed by the length-prefixed entry data.
+
+use std::{
+    fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use cargo_component_core::lock::LockFile;
+use futures::TryStreamExt;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncReadExt;
+use wasm_pkg_client::{
+    caching::{Cache, CachingClient, FileCache},
+    ContentDigest, PackageRef, Release,
+};
+
+/// The magic value at the start of every bundle.
+const BUNDLE_MAGIC: &[u8; 8] = b"CCBUNDLE";
+
+/// The format version of bundles.
+const BUNDLE_VERSION: u32 = 1;
+
+/// Represents the header of an entry in a bundle.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum BundleEntry {
+    /// The entry is the content of a locked registry package.
+    Package {
+        /// The name of the package.
+        name: PackageRef,
+        /// The locked version of the package.
+        version: Version,
+        /// The digest of the package content.
+        digest: ContentDigest,
+    },
+    /// The entry is a module adapter.
+    Adapter {
+        /// The path to the adapter, relative to the workspace root.
+        path: PathBuf,
+    },
+}
+
+/// Writes a bundle with the given entries.
+pub fn write_bundle(mut writer: impl Write, entries: &[(BundleEntry, Vec<u8>)]) -> Result<()> {
+    writer.write_all(BUNDLE_MAGIC)?;
+    writer.write_all(&BUNDLE_VERSION.to_le_bytes())?;
+
+    for (entry, data) in entries {
+        let header = serde_json::to_vec(entry)?;
+        writer.write_all(&u32::try_from(header.len())?.to_le_bytes())?;
+        writer.write_all(&header)?;
+        writer.write_all(&u64::try_from(data.len())?.to_le_bytes())?;
+        writer.write_all(data)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads the entries of a bundle.
+pub fn read_bundle(mut reader: impl Read) -> Result<Vec<(BundleEntry, Vec<u8>)>> {
+    let mut magic = [0; 8];
+    reader
+        .read_exact(&mut magic)
+        .context("failed to read bundle header")?;
+    if &magic != BUNDLE_MAGIC {
+        bail!("file is not a cargo-component bundle");
+    }
+
+    let mut version = [0; 4];
+    reader
+        .read_exact(&mut version)
+        .context("failed to read bundle header")?;
+    let version = u32::from_le_bytes(version);
+    if version != BUNDLE_VERSION {
+        bail!("unsupported bundle format version {version}");
+    }
+
+    let mut entries = Vec::new();
+    loop {
+        let mut len = [0; 4];
+        match reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).context("failed to read bundle entry"),
+        }
+
+        let mut header = vec![0; u32::from_le_bytes(len) as usize];
+        reader
+            .read_exact(&mut header)
+            .context("failed to read bundle entry")?;
+        let entry: BundleEntry =
+            serde_json::from_slice(&header).context("invalid bundle entry header")?;
+
+        let mut len = [0; 8];
+        reader
+            .read_exact(&mut len)
+            .context("failed to read bundle entry")?;
+        let mut data = vec![0; usize::try_from(u64::from_le_bytes(len))?];
+        reader
+            .read_exact(&mut data)
+            .context("failed to read bundle entry")?;
+
+        entries.push((entry, data));
+    }
+
+    Ok(entries)
+}
+
+/// Gets the bundle entries for the packages locked in the given lock file.
+///
+/// Package contents are fetched from the registry unless already cached.
+pub async fn locked_package_entries(
+    client: Arc<CachingClient<FileCache>>,
+    lock_file: &LockFile,
+) -> Result<Vec<(BundleEntry, Vec<u8>)>> {
+    let mut entries = Vec::new();
+    for package in &lock_file.packages {
+        for locked in &package.versions {
+            let release = Release {
+                version: locked.version.clone(),
+                content_digest: locked.digest.clone(),
+            };
+
+            let stream = client
+                .get_content(&package.name, &release)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to fetch content of package `{name}` v{version}",
+                        name = package.name,
+                        version = locked.version
+                    )
+                })?;
+
+            let mut data = Vec::new();
+            tokio_util::io::StreamReader::new(stream.map_err(io::Error::other))
+                .read_to_end(&mut data)
+                .await?;
+
+            entries.push((
+                BundleEntry::Package {
+                    name: package.name.clone(),
+                    version: locked.version.clone(),
+                    digest: locked.digest.clone(),
+                },
+                data,
+            ));
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Seeds the cache with a package from a bundle.
+///
+/// The content is validated against the digest of the entry.
+pub async fn import_package(
+    cache: &FileCache,
+    name: &PackageRef,
+    version: &Version,
+    digest: &ContentDigest,
+    data: Vec<u8>,
+) -> Result<()> {
+    // Validate the content before anything is written to the cache
+    let data = Bytes::from(data);
+    digest
+        .validating_stream(futures::stream::iter([Ok(data.clone())]))
+        .try_collect::<Vec<_>>()
+        .await
+        .with_context(|| format!("bundled content of package `{name}` v{version} is invalid"))?;
+
+    cache
+        .put_data(digest.clone(), Box::pin(futures::stream::iter([Ok(data)])))
+        .await
+        .with_context(|| {
+            format!("failed to import content of package `{name}` v{version} into the cache")
+        })?;
+
+    cache
+        .put_release(
+            name,
+            &Release {
+                version: version.clone(),
+                content_digest: digest.clone(),
+            },
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Writes an adapter from a bundle to the given workspace.
+///
+/// Existing adapter files are left unchanged.
+///
+/// Returns `true` if the adapter was written.
+pub fn import_adapter(workspace_root: &Path, path: &Path, data: &[u8]) -> Result<bool> {
+    if path.is_absolute()
+        || path
+            .components()
+            .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        bail!(
+            "bundled adapter path `{path}` is not relative to the workspace",
+            path = path.display()
+        );
+    }
+
+    let path = workspace_root.join(path);
+    if path.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create directory `{parent}`",
+                parent = parent.display()
+            )
+        })?;
+    }
+
+    fs::write(&path, data)
+        .with_context(|| format!("failed to write adapter `{path}`", path = path.display()))?;
+    Ok(true)
+}
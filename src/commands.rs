@@ -2,12 +2,40 @@
 
 mod add;
 mod bindings;
+mod bundle;
+mod containerize;
+mod deploy;
+mod embed;
+mod fetch;
+mod fuzz;
+mod graph;
+mod imports;
+mod login;
+mod logout;
 mod new;
 mod publish;
+mod rename;
+mod status;
 mod update;
+mod whoami;
+mod why;
 
 pub use self::add::*;
 pub use self::bindings::*;
+pub use self::bundle::*;
+pub use self::containerize::*;
+pub use self::deploy::*;
+pub use self::embed::*;
+pub use self::fetch::*;
+pub use self::fuzz::*;
+pub use self::graph::*;
+pub use self::imports::*;
+pub use self::login::*;
+pub use self::logout::*;
 pub use self::new::*;
 pub use self::publish::*;
+pub use self::rename::*;
+pub use self::status::*;
 pub use self::update::*;
+pub use self::whoami::*;
+pub use self::why::*;
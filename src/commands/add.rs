@@ -1,27 +1,37 @@
 use std::{
-    fs,
+    env, fs,
+    io::{self, BufRead, IsTerminal, Write},
     path::{Path, PathBuf},
     sync::Arc,
 };
 
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
+    cache_dir,
     command::CommonOptions,
-    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryPackage},
+    lock::{LockFile, LockedPackageVersion},
+    registry::{
+        find_release_by_digest, Dependency, DependencyResolution, DependencyResolver,
+        RegistryPackage,
+    },
+    terminal::Terminal,
     VersionedPackageName,
 };
-use cargo_metadata::Package;
+use cargo_metadata::{DependencyKind, Metadata, Package};
 use clap::Args;
 use semver::VersionReq;
-use toml_edit::{value, DocumentMut, InlineTable, Item, Table, Value};
+use toml_edit::{table, value, Array, DocumentMut, InlineTable, Item, Table, Value};
 use wasm_pkg_client::{
     caching::{CachingClient, FileCache},
-    PackageRef,
+    PackageRef, Release,
 };
+use wit_parser::{PackageId, Resolve, WorldId};
 
+use super::new::WIT_BINDGEN_RT_CRATE;
 use crate::{
-    config::CargoPackageSpec,
-    load_component_metadata, load_metadata,
+    config::{CargoArguments, CargoPackageSpec},
+    generate_bindings, load_component_metadata, load_metadata,
+    lock::{acquire_lock_file_ro, acquire_lock_file_rw},
     metadata::{ComponentMetadata, Target},
     Config, PackageComponentMetadata,
 };
@@ -59,38 +69,67 @@ pub struct AddCommand {
     pub package: VersionedPackageName,
 
     /// Add the dependency to the list of target dependencies
+    ///
+    /// If the component has no local target, the package becomes the target
+    /// of the component instead.
     #[clap(long = "target")]
     pub target: bool,
 
+    /// The world of the package to target; implies targeting the package
+    #[clap(
+        long = "world",
+        value_name = "WORLD",
+        requires = "target",
+        conflicts_with = "path"
+    )]
+    pub world: Option<String>,
+
+    /// Add the dependency to the list of dev-dependencies imported only by tests and benchmarks
+    #[clap(long = "dev", conflicts_with = "target")]
+    pub dev: bool,
+
     /// Add a package dependency to a file or directory.
     #[clap(long = "path", value_name = "PATH")]
     pub path: Option<PathBuf>,
+
+    /// Add the dependency to a new crate of bindings under `crates/`
+    #[clap(long = "as-crate", conflicts_with_all = ["target", "dev", "path", "dry_run"])]
+    pub as_crate: bool,
 }
 
 impl AddCommand {
     /// Executes the command
     pub async fn exec(self) -> Result<()> {
-        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
-        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let cargo_metadata = load_metadata(self.manifest_path.as_deref())?;
+        let workspace_root = cargo_metadata.workspace_root.clone().into_std_path_buf();
 
-        let client = config.client(self.common.cache_dir.clone(), false).await?;
+        let client = config.client(false).await?;
 
         let spec = match &self.spec {
             Some(spec) => Some(spec.clone()),
-            None => CargoPackageSpec::find_current_package_spec(&metadata),
+            None => CargoPackageSpec::find_current_package_spec(&cargo_metadata),
         };
 
         let PackageComponentMetadata { package, metadata }: PackageComponentMetadata<'_> =
             match &spec {
                 Some(spec) => {
-                    let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false)?;
+                    let pkgs = load_component_metadata(
+                        &cargo_metadata,
+                        std::iter::once(spec),
+                        false,
+                        None,
+                    )?;
                     assert!(pkgs.len() == 1, "one package should be present");
                     pkgs.into_iter().next().unwrap()
                 }
                 None => PackageComponentMetadata::new(
-                    metadata
+                    cargo_metadata
                         .root_package()
                         .context("no root package found in metadata")?,
+                    None,
                 )?,
             };
 
@@ -101,7 +140,52 @@ impl AddCommand {
 
         self.validate(&metadata, name)?;
 
-        if let Some(path) = self.path.as_ref() {
+        if self.targets_package(&metadata) {
+            let (requirement, release) = match &self.package.digest {
+                Some(digest) => {
+                    let release =
+                        find_release_by_digest(&client, &self.package.name, digest).await?;
+                    let requirement: VersionReq =
+                        format!("={version}", version = release.version).parse()?;
+                    (Some(requirement), Some(release))
+                }
+                None => (self.package.version.clone(), None),
+            };
+
+            let resolution = self
+                .resolve(
+                    client,
+                    &metadata,
+                    &self.package.name,
+                    requirement.as_ref().unwrap_or(&VersionReq::STAR),
+                )
+                .await?;
+            let version = match (&requirement, &resolution) {
+                (Some(requirement), _) => requirement.to_string(),
+                (None, DependencyResolution::Registry(resolution)) => {
+                    resolution.version.to_string()
+                }
+                _ => unreachable!(),
+            };
+            let version = version.trim_start_matches('^');
+
+            let (resolve, id, _) = resolution.decode().await?.resolve()?;
+            let world = self.select_world(&resolve, id)?;
+            self.set_target(package, version, &world)?;
+            if let (Some(requirement), Some(release), false) =
+                (&requirement, &release, self.dry_run)
+            {
+                self.lock(config.terminal(), &cargo_metadata, requirement, release)?;
+            }
+
+            config.terminal().status(
+                "Added",
+                format!(
+                    "target world `{world}` of package `{name}` with version `{version}`",
+                    name = self.package.name
+                ),
+            )?;
+        } else if let Some(path) = self.path.as_ref() {
             self.add_from_path(package, path)?;
 
             config.terminal().status(
@@ -111,8 +195,65 @@ impl AddCommand {
                     path = path.to_str().unwrap()
                 ),
             )?;
+        } else if let Some(digest) = &self.package.digest {
+            let release = find_release_by_digest(&client, &self.package.name, digest).await?;
+            let requirement: VersionReq =
+                format!("={version}", version = release.version).parse()?;
+            self.add(package, &requirement.to_string())?;
+            if !self.dry_run {
+                self.lock(config.terminal(), &cargo_metadata, &requirement, &release)?;
+            }
+
+            config.terminal().status(
+                "Added",
+                format!(
+                    "dependency `{name}` with version `{requirement}` pinned to digest `{digest}`"
+                ),
+            )?;
+        } else if self.as_crate {
+            let version = self
+                .resolve_version(client.clone(), &metadata, name)
+                .await?;
+            let version = version.trim_start_matches('^');
+            let (crate_name, manifest_path) =
+                self.add_as_crate(&workspace_root, package, version)?;
+
+            // Generate the bindings of the new crate so that it can be used immediately
+            let cargo_args = CargoArguments {
+                color: self.common.color,
+                verbose: self.common.verbose as usize,
+                quiet: self.common.quiet,
+                manifest_path: Some(manifest_path.clone()),
+                ..Default::default()
+            };
+            // The crate is not a member of a standalone package, so it is loaded on
+            // its own, sharing the target directory of the package
+            let mut metadata = load_metadata(Some(&manifest_path))?;
+            metadata.target_directory = cargo_metadata.target_directory.clone();
+            let spec = CargoPackageSpec::new(crate_name.clone())?;
+            let packages = load_component_metadata(
+                &metadata,
+                std::iter::once(&spec),
+                false,
+                Some(cargo_args.profile_name()),
+            )?;
+            generate_bindings(
+                client,
+                &config,
+                &metadata,
+                &packages,
+                &cargo_args,
+                false,
+                None,
+            )
+            .await?;
+
+            config.terminal().status(
+                "Added",
+                format!("dependency `{name}` with version `{version}` as crate `{crate_name}`"),
+            )?;
         } else {
-            let version = self.resolve_version(client, name).await?;
+            let version = self.resolve_version(client, &metadata, name).await?;
             let version = version.trim_start_matches('^');
             self.add(package, version)?;
 
@@ -125,20 +266,19 @@ impl AddCommand {
         Ok(())
     }
 
-    async fn resolve_version(
+    async fn resolve(
         &self,
         client: Arc<CachingClient<FileCache>>,
+        metadata: &ComponentMetadata,
         name: &PackageRef,
-    ) -> Result<String> {
-        let mut resolver = DependencyResolver::new_with_client(client, None)?;
+        version: &VersionReq,
+    ) -> Result<DependencyResolution> {
+        let mut resolver = DependencyResolver::new_with_client(client, None)?
+            .with_mirrors(metadata.section.mirrors.clone())
+            .with_cache_dir(cache_dir(self.common.cache_dir.clone())?);
         let dependency = Dependency::Package(RegistryPackage {
             name: Some(self.package.name.clone()),
-            version: self
-                .package
-                .version
-                .as_ref()
-                .unwrap_or(&VersionReq::STAR)
-                .clone(),
+            version: version.clone(),
             registry: self.registry.clone(),
         });
 
@@ -147,7 +287,20 @@ impl AddCommand {
         let dependencies = resolver.resolve().await?;
         assert_eq!(dependencies.len(), 1);
 
-        match dependencies.values().next().expect("expected a resolution") {
+        Ok(dependencies
+            .into_values()
+            .next()
+            .expect("expected a resolution"))
+    }
+
+    async fn resolve_version(
+        &self,
+        client: Arc<CachingClient<FileCache>>,
+        metadata: &ComponentMetadata,
+        name: &PackageRef,
+    ) -> Result<String> {
+        let version = self.package.version.clone().unwrap_or(VersionReq::STAR);
+        match self.resolve(client, metadata, name, &version).await? {
             DependencyResolution::Registry(resolution) => Ok(self
                 .package
                 .version
@@ -158,7 +311,176 @@ impl AddCommand {
         }
     }
 
+    /// Records the release a dependency is pinned to in the lock file.
+    fn lock(
+        &self,
+        terminal: &Terminal,
+        metadata: &Metadata,
+        requirement: &VersionReq,
+        release: &Release,
+    ) -> Result<()> {
+        let orig = match acquire_lock_file_ro(terminal, metadata)? {
+            Some(file_lock) => LockFile::read(file_lock.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = file_lock.path().display()
+                )
+            })?,
+            None => LockFile::default(),
+        };
+
+        let mut lock_file = orig.clone();
+        lock_file.lock(
+            &self.package.name,
+            self.registry.as_deref(),
+            LockedPackageVersion {
+                requirement: requirement.to_string(),
+                version: release.version.clone(),
+                digest: release.content_digest.clone(),
+                source: None,
+            },
+        );
+
+        let file_lock = acquire_lock_file_rw(terminal, metadata, (&orig, &lock_file), true, false)?;
+        lock_file
+            .write(file_lock.file(), "cargo-component")
+            .with_context(|| {
+                format!(
+                    "failed to write lock file `{path}`",
+                    path = file_lock.path().display()
+                )
+            })
+    }
+
+    /// Selects the world of the package to target.
+    ///
+    /// Without `--world`, the only world of the package is selected; if the
+    /// package has several worlds, the user is asked to pick one.
+    fn select_world(&self, resolve: &Resolve, package: PackageId) -> Result<String> {
+        let name = &self.package.name;
+        if let Some(world) = &self.world {
+            crate::bindings::select_world(resolve, package, Some(world))?;
+            return Ok(world.clone());
+        }
+
+        let worlds: Vec<_> = resolve.packages[package]
+            .worlds
+            .iter()
+            .map(|(world, id)| (world.as_str(), export_summary(resolve, *id)))
+            .collect();
+
+        match worlds[..] {
+            [] => bail!("package `{name}` has no worlds to target"),
+            [(world, _)] => return Ok(world.to_string()),
+            _ => {}
+        }
+
+        if !io::stdin().is_terminal() {
+            bail!(
+                "package `{name}` has multiple worlds; use `--world` to select one of:{list}",
+                list = worlds
+                    .iter()
+                    .map(|(world, summary)| format!("\n  {world} ({summary})"))
+                    .collect::<String>()
+            );
+        }
+
+        let mut stderr = io::stderr();
+        writeln!(stderr, "package `{name}` has multiple worlds:")?;
+        for (i, (world, summary)) in worlds.iter().enumerate() {
+            writeln!(stderr, "  {n}) {world} ({summary})", n = i + 1)?;
+        }
+        write!(
+            stderr,
+            "select a world to target [1-{n}]: ",
+            n = worlds.len()
+        )?;
+        stderr.flush()?;
+
+        let mut answer = String::new();
+        io::stdin()
+            .lock()
+            .read_line(&mut answer)
+            .context("failed to read the answer from stdin")?;
+
+        let answer = answer.trim();
+        answer
+            .parse::<usize>()
+            .ok()
+            .and_then(|n| worlds.get(n.checked_sub(1)?))
+            .or_else(|| worlds.iter().find(|(world, _)| *world == answer))
+            .map(|(world, _)| world.to_string())
+            .with_context(|| format!("invalid world selection `{answer}`"))
+    }
+
+    /// Determines whether the package becomes the target of the component.
+    ///
+    /// This is the case for `--target` with `--world`, or when the component
+    /// has no local target to add a target dependency to.
+    fn targets_package(&self, metadata: &ComponentMetadata) -> bool {
+        self.target
+            && self.path.is_none()
+            && (self.world.is_some()
+                || matches!(
+                    &metadata.section.target,
+                    Target::Local { dependencies, .. } if dependencies.is_empty()
+                ) && metadata.target_path().is_none())
+    }
+
+    /// Writes the package, version and world of the target of the component.
+    fn set_target(&self, pkg: &Package, version: &str, world: &str) -> Result<()> {
+        self.with_component(pkg, |component| {
+            let mut target = Table::new();
+            target["package"] = value(self.package.name.to_string());
+            target["version"] = value(version);
+            target["world"] = value(world);
+            if let Some(registry) = &self.registry {
+                target["registry"] = value(registry);
+            }
+
+            component["target"] = Item::Table(target);
+            Ok(())
+        })
+    }
+
     fn with_dependencies<F>(&self, pkg: &Package, body: F) -> Result<()>
+    where
+        F: FnOnce(&mut Table) -> Result<()>,
+    {
+        self.with_component(pkg, |component| {
+            let dependencies = if self.target {
+                let target = component["target"]
+                    .or_insert(Item::Table(Table::new()))
+                    .as_table_mut()
+                    .context("section `package.metadata.component.target` is not a table")?;
+
+                target.set_implicit(true);
+
+                target["dependencies"]
+                    .or_insert(Item::Table(Table::new()))
+                    .as_table_mut()
+                    .context(
+                        "section `package.metadata.component.target.dependencies` is not a table",
+                    )?
+            } else if self.dev {
+                component["dev-dependencies"]
+                    .or_insert(Item::Table(Table::new()))
+                    .as_table_mut()
+                    .context(
+                        "section `package.metadata.component.dev-dependencies` is not a table",
+                    )?
+            } else {
+                component["dependencies"]
+                    .or_insert(Item::Table(Table::new()))
+                    .as_table_mut()
+                    .context("section `package.metadata.component.dependencies` is not a table")?
+            };
+
+            body(dependencies)
+        })
+    }
+
+    fn with_component<F>(&self, pkg: &Package, body: F) -> Result<()>
     where
         F: FnOnce(&mut Table) -> Result<()>,
     {
@@ -190,28 +512,7 @@ impl AddCommand {
 
         component.set_implicit(true);
 
-        let dependencies = if self.target {
-            let target = component["target"]
-                .or_insert(Item::Table(Table::new()))
-                .as_table_mut()
-                .context("section `package.metadata.component.target` is not a table")?;
-
-            target.set_implicit(true);
-
-            target["dependencies"]
-                .or_insert(Item::Table(Table::new()))
-                .as_table_mut()
-                .context(
-                    "section `package.metadata.component.target.dependencies` is not a table",
-                )?
-        } else {
-            component["dependencies"]
-                .or_insert(Item::Table(Table::new()))
-                .as_table_mut()
-                .context("section `package.metadata.component.dependencies` is not a table")?
-        };
-
-        body(dependencies)?;
+        body(component)?;
 
         if self.dry_run {
             println!("{document}");
@@ -229,23 +530,173 @@ impl AddCommand {
 
     fn add(&self, pkg: &Package, version: &str) -> Result<()> {
         self.with_dependencies(pkg, |dependencies| {
-            match self.name.as_ref() {
-                Some(name) => {
-                    let str_name = name.to_string();
-                    dependencies[&str_name] = value(InlineTable::from_iter([
-                        ("package", Value::from(self.package.name.to_string())),
-                        ("version", Value::from(version)),
-                    ]));
-                }
-                _ => {
-                    let str_name = self.package.name.to_string();
-                    dependencies[&str_name] = value(version);
-                }
-            }
+            self.insert_dependency(dependencies, version);
             Ok(())
         })
     }
 
+    fn insert_dependency(&self, dependencies: &mut Table, version: &str) {
+        match self.name.as_ref() {
+            Some(name) => {
+                let str_name = name.to_string();
+                dependencies[&str_name] = value(InlineTable::from_iter([
+                    ("package", Value::from(self.package.name.to_string())),
+                    ("version", Value::from(version)),
+                ]));
+            }
+            _ => {
+                let str_name = self.package.name.to_string();
+                dependencies[&str_name] = value(version);
+            }
+        }
+    }
+
+    /// Creates a crate of bindings for the dependency and adds it to the
+    /// dependencies of the package.
+    ///
+    /// Returns the name and the manifest path of the new crate.
+    fn add_as_crate(
+        &self,
+        workspace_root: &Path,
+        pkg: &Package,
+        version: &str,
+    ) -> Result<(String, PathBuf)> {
+        let name = self.name.as_ref().unwrap_or(&self.package.name);
+        let crate_name = format!("{ns}-{name}", ns = name.namespace(), name = name.name());
+        let pkg_dir = pkg.manifest_path.parent().unwrap().as_std_path();
+        let crate_dir = pkg_dir.join("crates").join(&crate_name);
+        if crate_dir.exists() {
+            bail!(
+                "cannot add dependency `{name}` as a crate because directory `{path}` already exists",
+                path = crate_dir.display()
+            );
+        }
+
+        let mut doc = DocumentMut::new();
+        doc["package"] = table();
+        doc["package"]["name"] = value(&crate_name);
+        doc["package"]["version"] = value("0.1.0");
+        doc["package"]["edition"] = value(pkg.edition.as_str());
+        doc["package"]["publish"] = value(false);
+
+        let mut dependencies = Table::new();
+        self.insert_dependency(&mut dependencies, version);
+        let mut component = Table::new();
+        component.set_implicit(true);
+        component["dependencies"] = Item::Table(dependencies);
+        let mut metadata = Table::new();
+        metadata.set_implicit(true);
+        metadata["component"] = Item::Table(component);
+        doc["package"]["metadata"] = Item::Table(metadata);
+
+        // Use the same runtime crate as the package the dependency is added to
+        let runtime = pkg.dependencies.iter().find(|d| {
+            d.name == WIT_BINDGEN_RT_CRATE && d.kind == DependencyKind::Normal && d.path.is_none()
+        });
+        if let Some(runtime) = runtime {
+            let mut dependency = InlineTable::new();
+            dependency.insert("version", runtime.req.to_string().into());
+            if !runtime.features.is_empty() {
+                dependency.insert("features", Array::from_iter(&runtime.features).into());
+            }
+            doc["dependencies"][WIT_BINDGEN_RT_CRATE] = value(dependency);
+        }
+
+        let src_dir = crate_dir.join("src");
+        fs::create_dir_all(&src_dir).with_context(|| {
+            format!(
+                "failed to create directory `{path}`",
+                path = src_dir.display()
+            )
+        })?;
+
+        let manifest_path = crate_dir.join("Cargo.toml");
+        fs::write(&manifest_path, doc.to_string()).with_context(|| {
+            format!(
+                "failed to write manifest file `{path}`",
+                path = manifest_path.display()
+            )
+        })?;
+
+        let source_path = src_dir.join("lib.rs");
+        fs::write(
+            &source_path,
+            format!(
+                "//! Bindings for the `{name}` component.\n\
+                 \n\
+                 #[allow(warnings)]\n\
+                 mod bindings;\n\
+                 \n\
+                 pub use bindings::*;\n"
+            ),
+        )
+        .with_context(|| {
+            format!(
+                "failed to write source file `{path}`",
+                path = source_path.display()
+            )
+        })?;
+
+        // Add the crate as a member of the workspace, if there is one; the crate of
+        // a standalone package is only a path dependency of it
+        let member = crate_dir
+            .strip_prefix(workspace_root)
+            .unwrap_or(&crate_dir)
+            .to_str()
+            .context("crate path is not valid UTF-8")?
+            .replace('\\', "/");
+        edit_manifest(&workspace_root.join("Cargo.toml"), |doc| {
+            let Some(workspace) = doc.get_mut("workspace") else {
+                return Ok(());
+            };
+
+            let members = workspace
+                .as_table_mut()
+                .context("section `workspace` is not a table")?["members"]
+                .or_insert(value(Array::new()))
+                .as_array_mut()
+                .context("`workspace.members` is not an array")?;
+
+            if !members.iter().any(|m| m.as_str() == Some(&member)) {
+                members.push(member.as_str());
+            }
+
+            Ok(())
+        })?;
+
+        edit_manifest(pkg.manifest_path.as_std_path(), |doc| {
+            doc["dependencies"]
+                .or_insert(table())
+                .as_table_mut()
+                .context("section `dependencies` is not a table")?[&crate_name] = value(
+                InlineTable::from_iter([("path", Value::from(format!("crates/{crate_name}")))]),
+            );
+            Ok(())
+        })?;
+
+        if runtime.is_none() {
+            let cargo = env::var("CARGO")
+                .map(PathBuf::from)
+                .unwrap_or_else(|_| PathBuf::from("cargo"));
+            let status = std::process::Command::new(cargo)
+                .args([
+                    "add",
+                    "--quiet",
+                    WIT_BINDGEN_RT_CRATE,
+                    "--features",
+                    "bitflags",
+                ])
+                .current_dir(&crate_dir)
+                .status()
+                .context("failed to execute `cargo add` command")?;
+            if !status.success() {
+                bail!("`cargo add {WIT_BINDGEN_RT_CRATE} --features bitflags` command exited with non-zero status");
+            }
+        }
+
+        Ok((crate_name, manifest_path))
+    }
+
     fn add_from_path(&self, pkg: &Package, path: &Path) -> Result<()> {
         self.with_dependencies(pkg, |dependencies| {
             let key = match self.name.as_ref() {
@@ -263,7 +714,26 @@ impl AddCommand {
     }
 
     fn validate(&self, metadata: &ComponentMetadata, name: &PackageRef) -> Result<()> {
-        if self.target {
+        if self.as_crate && self.package.digest.is_some() {
+            bail!("cannot add dependency `{name}` pinned to a digest as a crate");
+        }
+
+        if self.targets_package(metadata) {
+            match &metadata.section.target {
+                Target::Local {
+                    path: Some(path), ..
+                } => bail!(
+                    "cannot target package `{name}` because the component targets local WIT package `{path}`",
+                    name = self.package.name,
+                    path = path.display()
+                ),
+                Target::Local { dependencies, .. } if !dependencies.is_empty() => bail!(
+                    "cannot target package `{name}` because the component has target dependencies",
+                    name = self.package.name
+                ),
+                _ => {}
+            }
+        } else if self.target {
             match &metadata.section.target {
                 Target::Package { .. } => {
                     bail!("cannot add dependency `{name}` to a registry package target")
@@ -274,10 +744,50 @@ impl AddCommand {
                     }
                 }
             }
-        } else if metadata.section.dependencies.contains_key(name) {
+        } else if metadata.all_dependencies().any(|(n, _)| n == name) {
             bail!("cannot add dependency `{name}` as it conflicts with an existing dependency");
         }
 
         Ok(())
     }
 }
+
+/// Summarizes the exports of a world.
+fn export_summary(resolve: &Resolve, world: WorldId) -> String {
+    let exports: Vec<_> = resolve.worlds[world]
+        .exports
+        .keys()
+        .map(|key| resolve.name_world_key(key))
+        .collect();
+
+    if exports.is_empty() {
+        "no exports".to_string()
+    } else {
+        format!("exports: {exports}", exports = exports.join(", "))
+    }
+}
+
+fn edit_manifest(path: &Path, body: impl FnOnce(&mut DocumentMut) -> Result<()>) -> Result<()> {
+    let manifest = fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    let mut document: DocumentMut = manifest.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    body(&mut document)?;
+
+    fs::write(path, document.to_string()).with_context(|| {
+        format!(
+            "failed to write manifest file `{path}`",
+            path = path.display()
+        )
+    })
+}
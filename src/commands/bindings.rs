@@ -1,9 +1,12 @@
+use std::path::PathBuf;
+
 use anyhow::Result;
 use cargo_component_core::command::CommonOptions;
 use clap::Args;
 
 use crate::{
-    config::Config, generate_bindings, load_component_metadata, load_metadata, CargoArguments,
+    config::Config, generate_consumer_bindings, load_component_metadata, load_metadata,
+    update_bindings, CargoArguments,
 };
 
 /// Just update the generated bindings.
@@ -17,6 +20,30 @@ pub struct BindingsCommand {
     /// The common command options.
     #[clap(flatten)]
     pub common: CommonOptions,
+
+    /// Also generate bindings for consuming the component from the given language
+    #[clap(long = "lang", value_name = "LANG", value_parser = ["c", "js", "python"])]
+    pub languages: Vec<String>,
+
+    /// The directory to write consumer bindings to (defaults to `target/bindings`)
+    #[clap(long = "out-dir", value_name = "DIRECTORY", requires = "languages")]
+    pub out_dir: Option<PathBuf>,
+
+    /// Space or comma separated list of features that enable optional dependencies
+    #[clap(long = "features", short = 'F', value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Import the dev-dependencies as when building tests and benchmarks
+    #[clap(long = "dev")]
+    pub dev: bool,
 }
 
 impl BindingsCommand {
@@ -24,16 +51,46 @@ impl BindingsCommand {
     pub async fn exec(self) -> Result<()> {
         log::debug!("generating bindings");
 
-        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
 
-        let client = config.client(self.common.cache_dir.clone(), false).await?;
+        let client = config.client(false).await?;
 
         let cargo_args = CargoArguments::parse()?;
         let metadata = load_metadata(None)?;
-        let packages =
-            load_component_metadata(&metadata, cargo_args.packages.iter(), cargo_args.workspace)?;
-        let _import_name_map =
-            generate_bindings(client, &config, &metadata, &packages, &cargo_args).await?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            Some(cargo_args.profile_name()),
+        )?;
+        update_bindings(
+            client.clone(),
+            &config,
+            &metadata,
+            &packages,
+            &cargo_args,
+            self.dev,
+        )
+        .await?;
+
+        if !self.languages.is_empty() {
+            let out_dir = self.out_dir.unwrap_or_else(|| {
+                metadata
+                    .target_directory
+                    .join("bindings")
+                    .into_std_path_buf()
+            });
+            generate_consumer_bindings(
+                client,
+                &config,
+                &metadata,
+                &packages,
+                (&self.languages, &out_dir),
+            )
+            .await?;
+        }
 
         Ok(())
     }
@@ -0,0 +1,237 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use cargo_component_core::{command::CommonOptions, lock::LockFile};
+use clap::{Args, Subcommand};
+use wasm_pkg_client::caching::FileCache;
+
+use crate::{
+    bundle::{
+        import_adapter, import_package, locked_package_entries, read_bundle, write_bundle,
+        BundleEntry,
+    },
+    load_component_metadata, load_metadata,
+    lock::acquire_lock_file_ro,
+    Config,
+};
+
+/// Export or import a bundle of the locked registry dependencies for offline builds
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BundleCommand {
+    /// The bundle subcommand to execute.
+    #[clap(subcommand)]
+    pub command: BundleSubcommand,
+}
+
+/// Represents a subcommand of the bundle command.
+#[derive(Subcommand)]
+pub enum BundleSubcommand {
+    /// Pack all locked registry dependencies into a bundle file
+    Export(BundleExportCommand),
+    /// Seed the cache with the dependencies from a bundle file
+    Import(BundleImportCommand),
+}
+
+impl BundleCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        match self.command {
+            BundleSubcommand::Export(cmd) => cmd.exec().await,
+            BundleSubcommand::Import(cmd) => cmd.exec().await,
+        }
+    }
+}
+
+/// Pack all locked registry dependencies into a bundle file
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BundleExportCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Include the custom module adapters of the workspace packages
+    #[clap(long = "adapter")]
+    pub adapter: bool,
+
+    /// The path of the bundle file to write
+    #[clap(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+impl BundleExportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing bundle export command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config)
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+
+        let file_lock = acquire_lock_file_ro(config.terminal(), &metadata)?.with_context(|| {
+            format!(
+                "workspace `{root}` has no component lock file; use `cargo component update` to create one",
+                root = metadata.workspace_root
+            )
+        })?;
+        let lock_file = LockFile::read(file_lock.file()).with_context(|| {
+            format!(
+                "failed to read lock file `{path}`",
+                path = file_lock.path().display()
+            )
+        })?;
+
+        let client = config.client(false).await?;
+        let mut entries = locked_package_entries(client, &lock_file).await?;
+        let packages = entries.len();
+
+        if self.adapter {
+            let workspace_root = metadata.workspace_root.as_std_path();
+            for package in load_component_metadata(&metadata, [].iter(), true, None)? {
+                let Some(adapter) = &package.metadata.section.adapter else {
+                    continue;
+                };
+
+                let path = adapter.strip_prefix(workspace_root).with_context(|| {
+                    format!(
+                        "adapter `{adapter}` of package `{name}` is not in the workspace",
+                        adapter = adapter.display(),
+                        name = package.package.name
+                    )
+                })?;
+
+                if entries
+                    .iter()
+                    .any(|(e, _)| matches!(e, BundleEntry::Adapter { path: p } if p == path))
+                {
+                    continue;
+                }
+
+                let data = std::fs::read(adapter).with_context(|| {
+                    format!(
+                        "failed to read module adapter `{path}`",
+                        path = adapter.display()
+                    )
+                })?;
+                entries.push((
+                    BundleEntry::Adapter {
+                        path: path.to_path_buf(),
+                    },
+                    data,
+                ));
+            }
+        }
+
+        let file = File::create(&self.file).with_context(|| {
+            format!(
+                "failed to create bundle `{path}`",
+                path = self.file.display()
+            )
+        })?;
+        write_bundle(BufWriter::new(file), &entries).with_context(|| {
+            format!(
+                "failed to write bundle `{path}`",
+                path = self.file.display()
+            )
+        })?;
+
+        config.terminal().status(
+            "Exported",
+            format!(
+                "{packages} package(s) and {adapters} adapter(s) to `{path}`",
+                adapters = entries.len() - packages,
+                path = self.file.display()
+            ),
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Seed the cache with the dependencies from a bundle file
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct BundleImportCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// The path of the bundle file to read
+    #[clap(value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+impl BundleImportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing bundle import command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config)
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+
+        let file = File::open(&self.file).with_context(|| {
+            format!("failed to open bundle `{path}`", path = self.file.display())
+        })?;
+        let entries = read_bundle(BufReader::new(file)).with_context(|| {
+            format!("failed to read bundle `{path}`", path = self.file.display())
+        })?;
+
+        let cache = FileCache::new(config.cache_dir()?).await?;
+        let mut workspace_root = None;
+        let mut packages = 0;
+        let mut adapters = 0;
+        for (entry, data) in entries {
+            match entry {
+                BundleEntry::Package {
+                    name,
+                    version,
+                    digest,
+                } => {
+                    import_package(&cache, &name, &version, &digest, data).await?;
+                    packages += 1;
+                }
+                BundleEntry::Adapter { path } => {
+                    if workspace_root.is_none() {
+                        let metadata = load_metadata(self.manifest_path.as_deref())?;
+                        workspace_root = Some(metadata.workspace_root.into_std_path_buf());
+                    }
+
+                    let root = workspace_root.as_deref().unwrap();
+                    if import_adapter(root, &path, &data)? {
+                        adapters += 1;
+                    } else {
+                        config.terminal().warn(format!(
+                            "not overwriting existing adapter `{path}`",
+                            path = root.join(&path).display()
+                        ))?;
+                    }
+                }
+            }
+        }
+
+        config.terminal().status(
+            "Imported",
+            format!(
+                "{packages} package(s) and {adapters} adapter(s) from `{path}`",
+                path = self.file.display()
+            ),
+        )?;
+
+        Ok(())
+    }
+}
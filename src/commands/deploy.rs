@@ -0,0 +1,252 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+
+use crate::{
+    config::{CargoArguments, CargoPackageSpec, Config},
+    deploy::{deploy, DeployOptions},
+    is_wasm_target, load_metadata, run_cargo_command, CommandStatus, PackageComponentMetadata,
+};
+
+/// Build a component and deploy it with its configured provider
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct DeployCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Build for the target triple (defaults to `wasm32-wasip1`)
+    #[clap(long = "target", value_name = "TRIPLE")]
+    pub target: Option<String>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Cargo package to deploy (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<CargoPackageSpec>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+
+    /// Space or comma separated list of features to activate
+    #[clap(long = "features", value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Build the component but do not deploy it
+    #[clap(long = "dry-run")]
+    pub dry_run: bool,
+}
+
+impl DeployCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing deploy command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+
+        if let Some(target) = &self.target {
+            if !is_wasm_target(target) {
+                bail!("target `{target}` is not a WebAssembly target");
+            }
+        }
+
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let spec = match &self.cargo_package {
+            Some(spec) => Some(spec.clone()),
+            None => CargoPackageSpec::find_current_package_spec(&metadata),
+        };
+        let mut packages = [PackageComponentMetadata::new(
+            if let Some(spec) = &spec {
+                metadata
+                    .packages
+                    .iter()
+                    .find(|p| {
+                        p.name == spec.name
+                            && match spec.version.as_ref() {
+                                Some(v) => &p.version == v,
+                                None => true,
+                            }
+                    })
+                    .with_context(|| {
+                        format!("package ID specification `{spec}` did not match any packages")
+                    })?
+            } else {
+                metadata
+                    .root_package()
+                    .context("no root package found in manifest")?
+            },
+            Some("release"),
+        )?];
+        packages[0]
+            .metadata
+            .resolve_workspace_members(&metadata, Some("release"))?;
+
+        let package = packages[0].package;
+        let component_metadata = &packages[0].metadata;
+        let Some(deploy_config) = &component_metadata.section.deploy else {
+            bail!(
+                "package `{name}` has no deployment configuration; add a \
+                 `[package.metadata.component.deploy]` section to manifest `{path}`",
+                name = package.name,
+                path = package.manifest_path
+            );
+        };
+
+        let cargo_build_args = CargoArguments {
+            color: self.common.color,
+            verbose: self.common.verbose as usize,
+            help: false,
+            quiet: self.common.quiet,
+            targets: self.target.clone().into_iter().collect(),
+            manifest_path: self.manifest_path.clone(),
+            message_format: None,
+            frozen: self.frozen,
+            locked: self.locked,
+            release: true,
+            profile: None,
+            offline: self.offline,
+            configs: Vec::new(),
+            unstable_flags: Vec::new(),
+            workspace: false,
+            packages: self.cargo_package.clone().into_iter().collect(),
+            bins: Vec::new(),
+            examples: Vec::new(),
+            tests: Vec::new(),
+            benches: Vec::new(),
+            lib: false,
+            all_bins: false,
+            all_examples: false,
+            all_tests: false,
+            all_benches: false,
+            all_targets: false,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            doc: false,
+            open: false,
+            test_jobs: None,
+            stats: false,
+            no_producers: false,
+            stub_missing_imports: false,
+            emit: Vec::new(),
+            output_format: self.common.output_format,
+        };
+
+        let client = config.client(self.offline).await?;
+        let spawn_args = self.build_args();
+        let (status, outputs) = run_cargo_command(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            Some("build"),
+            &cargo_build_args,
+            &spawn_args,
+        )
+        .await?;
+        if let CommandStatus::Exit(code) = status {
+            bail!("failed to build the component to deploy (exit code {code})");
+        }
+
+        if outputs.len() != 1 {
+            bail!(
+                "expected one output from `cargo build`, got {len}",
+                len = outputs.len()
+            );
+        }
+
+        let options = DeployOptions {
+            metadata: component_metadata,
+            deploy: deploy_config,
+            path: &outputs[0],
+            dry_run: self.dry_run,
+        };
+
+        deploy(&config, &options).await
+    }
+
+    fn build_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        args.push("build".to_string());
+        args.push("--release".to_string());
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat_n("-v", self.common.verbose as usize).map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        if let Some(target) = &self.target {
+            args.push("--target".to_string());
+            args.push(target.clone());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if let Some(spec) = &self.cargo_package {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(manifest_path.display().to_string());
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+
+        args
+    }
+}
@@ -0,0 +1,178 @@
+use std::{env, fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use semver::Version;
+use wit_component::StringEncoding;
+use wit_parser::Resolve;
+
+use crate::{
+    add_producers,
+    bindings::select_world,
+    config::{default_namespace, Config},
+    encode_component, has_component_type_section,
+    metadata::{ComponentMetadata, ComponentSection},
+    EncodeOptions,
+};
+
+/// Encode a core WebAssembly module as a component.
+///
+/// The module is encoded the same way `cargo component build` encodes the
+/// modules built by cargo, with the same adapters, validation and producers
+/// sections, so that other build systems can produce identical components.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct EmbedCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to a WIT file or directory with the world to embed in the module
+    #[clap(long = "wit", value_name = "PATH")]
+    pub wit: Option<PathBuf>,
+
+    /// The world of the WIT package to embed in the module
+    #[clap(long = "world", value_name = "WORLD", requires = "wit")]
+    pub world: Option<String>,
+
+    /// Encode the module as a command component
+    #[clap(long = "command")]
+    pub command: bool,
+
+    /// Use the built-in proxy adapter for a reactor component
+    #[clap(long = "proxy", conflicts_with = "adapter")]
+    pub proxy: bool,
+
+    /// Path to the WASI preview1 adapter module to use
+    #[clap(long = "adapter", value_name = "PATH")]
+    pub adapter: Option<PathBuf>,
+
+    /// Stub the imports that the adapter does not satisfy with functions that trap
+    #[clap(long = "stub-missing-imports")]
+    pub stub_missing_imports: bool,
+
+    /// Remove the producers sections from the component
+    #[clap(long = "no-producers")]
+    pub no_producers: bool,
+
+    /// Path to write the component to [default: <MODULE> with a `.component.wasm` extension]
+    #[clap(long = "output", short = 'o', value_name = "PATH")]
+    pub output: Option<PathBuf>,
+
+    /// Path to the core module to encode
+    #[clap(value_name = "MODULE")]
+    pub module: PathBuf,
+}
+
+impl EmbedCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing embed command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
+        let cwd =
+            env::current_dir().context("couldn't get the current directory of the process")?;
+
+        let mut bytes = fs::read(&self.module).with_context(|| {
+            format!(
+                "failed to read module `{path}`",
+                path = self.module.display()
+            )
+        })?;
+
+        if let Some(wit) = &self.wit {
+            let mut resolve = Resolve::default();
+            let (package, _) = resolve.push_path(wit).with_context(|| {
+                format!("failed to parse WIT package `{path}`", path = wit.display())
+            })?;
+            let world = select_world(&resolve, package, self.world.as_deref())?;
+            wit_component::embed_component_metadata(
+                &mut bytes,
+                &resolve,
+                world,
+                StringEncoding::UTF8,
+            )
+            .with_context(|| {
+                format!(
+                    "failed to embed the world of `{wit}` in module `{path}`",
+                    wit = wit.display(),
+                    path = self.module.display()
+                )
+            })?;
+        } else if !has_component_type_section(&bytes)? {
+            config.terminal().warn(format!(
+                "module `{path}` has no `component-type` custom section, so the component will \
+                 only import WASI; use `--wit` to specify the world of the component",
+                path = self.module.display()
+            ))?;
+        }
+
+        let output = self
+            .output
+            .clone()
+            .unwrap_or_else(|| self.module.with_extension("component.wasm"));
+
+        let metadata = ComponentMetadata {
+            name: self
+                .module
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            version: Version::new(0, 0, 0),
+            manifest_path: self.wit.clone().unwrap_or_else(|| self.module.clone()),
+            digest: String::new(),
+            section: ComponentSection {
+                adapter: self.adapter.clone(),
+                proxy: self.proxy,
+                ..Default::default()
+            },
+            section_present: true,
+            optional_dependencies: Default::default(),
+            locked_dependencies: Default::default(),
+            import_names: Default::default(),
+            workspace_members: Default::default(),
+            namespace: default_namespace()?,
+            deprecations: Default::default(),
+        };
+
+        config.terminal().status(
+            "Creating",
+            format!(
+                "component {path}",
+                path = output.strip_prefix(&cwd).unwrap_or(&output).display()
+            ),
+        )?;
+
+        let (component, _) = encode_component(
+            &config,
+            &metadata,
+            &bytes,
+            EncodeOptions {
+                path: &output,
+                command: self.command,
+                stub_missing_imports: self.stub_missing_imports,
+                print_status: true,
+                import_name_map: Default::default(),
+                libraries: Vec::new(),
+            },
+        )?;
+
+        let component =
+            add_producers(&component, &metadata, self.no_producers).with_context(|| {
+                format!(
+                    "failed to add metadata to output component `{path}`",
+                    path = output.display()
+                )
+            })?;
+
+        fs::write(&output, component).with_context(|| {
+            format!(
+                "failed to write output component `{path}`",
+                path = output.display()
+            )
+        })?;
+
+        Ok(())
+    }
+}
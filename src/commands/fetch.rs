@@ -0,0 +1,116 @@
+use std::{path::PathBuf, process::Command};
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+
+use crate::{fetch_dependencies, load_component_metadata, load_metadata, Config};
+
+/// Fetch the dependencies of a package into the cache without building it
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct FetchCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Fetch crate dependencies for the target triple
+    #[clap(long = "target", value_name = "TRIPLE")]
+    pub targets: Vec<String>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+}
+
+impl FetchCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing fetch command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(&metadata, [].iter(), true, None)?;
+
+        let client = config.client(self.frozen).await?;
+        let fetched = fetch_dependencies(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            !self.frozen && !self.locked,
+            self.locked || self.frozen,
+        )
+        .await?;
+
+        config.terminal().status(
+            "Fetched",
+            format!("{fetched} component dependency package(s)"),
+        )?;
+
+        // Fetch the crate dependencies too
+        let cargo = std::env::var("CARGO")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("cargo"));
+        let mut cmd = Command::new(&cargo);
+        cmd.arg("fetch");
+        cmd.args(self.cargo_args());
+
+        log::debug!("spawning command {cmd:?}");
+        let status = cmd
+            .status()
+            .with_context(|| format!("failed to spawn `{cargo}`", cargo = cargo.display()))?;
+        if !status.success() {
+            bail!("failed to fetch crate dependencies ({status})");
+        }
+
+        Ok(())
+    }
+
+    fn cargo_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat_n("-v", self.common.verbose as usize).map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(manifest_path.display().to_string());
+        }
+
+        for target in &self.targets {
+            args.push("--target".to_string());
+            args.push(target.clone());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        args
+    }
+}
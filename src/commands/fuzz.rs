@@ -0,0 +1,444 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use cargo_config2::PathAndArgs;
+use clap::Args;
+use wit_component::DecodedWasm;
+use wit_parser::Resolve;
+
+use crate::{
+    config::{CargoArguments, CargoPackageSpec, Config},
+    fuzz::{fuzz_targets, FuzzTarget, InputGenerator},
+    get_runner, load_metadata, run_cargo_command, CommandStatus, PackageComponentMetadata,
+};
+
+/// The maximum number of lines of runner output to display for a crash.
+const MAX_MESSAGE_LINES: usize = 5;
+
+/// Fuzz the exported functions of a component.
+///
+/// Inputs are generated from the WIT types of each exported function and
+/// the function is invoked with the configured runner. Inputs that cause a
+/// crash are saved to the corpus directory and are replayed on the next run.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct FuzzCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The exported function to fuzz (defaults to all exported functions)
+    #[clap(value_name = "FUNCTION")]
+    pub function: Option<String>,
+
+    /// The number of inputs to generate for each function
+    #[clap(
+        long = "iterations",
+        short = 'n',
+        value_name = "N",
+        default_value_t = 100
+    )]
+    pub iterations: u64,
+
+    /// The seed used to generate inputs (defaults to a random seed)
+    #[clap(long = "seed", value_name = "SEED")]
+    pub seed: Option<u64>,
+
+    /// The directory to store crashing inputs (defaults to `fuzz/corpus` in the package directory)
+    #[clap(long = "corpus", value_name = "DIRECTORY")]
+    pub corpus: Option<PathBuf>,
+
+    /// Cargo package to fuzz (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub cargo_package: Option<CargoPackageSpec>,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Require lock file and cache are up to date
+    #[clap(long = "frozen")]
+    pub frozen: bool,
+
+    /// Require lock file is up to date
+    #[clap(long = "locked")]
+    pub locked: bool,
+
+    /// Run without accessing the network
+    #[clap(long = "offline")]
+    pub offline: bool,
+}
+
+impl FuzzCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing fuzz command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let client = config.client(false).await?;
+
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let spec = match &self.cargo_package {
+            Some(spec) => Some(spec.clone()),
+            None => CargoPackageSpec::find_current_package_spec(&metadata),
+        };
+        let mut packages = [PackageComponentMetadata::new(
+            if let Some(spec) = &spec {
+                metadata
+                    .packages
+                    .iter()
+                    .find(|p| {
+                        p.name == spec.name
+                            && match spec.version.as_ref() {
+                                Some(v) => &p.version == v,
+                                None => true,
+                            }
+                    })
+                    .with_context(|| {
+                        format!("package ID specification `{spec}` did not match any packages")
+                    })?
+            } else {
+                metadata
+                    .root_package()
+                    .context("no root package found in manifest")?
+            },
+            Some("dev"),
+        )?];
+        packages[0]
+            .metadata
+            .resolve_workspace_members(&metadata, Some("dev"))?;
+
+        let package = packages[0].package;
+
+        let cargo_build_args = CargoArguments {
+            color: self.common.color,
+            verbose: self.common.verbose as usize,
+            help: false,
+            quiet: self.common.quiet,
+            targets: Vec::new(),
+            manifest_path: self.manifest_path.clone(),
+            message_format: None,
+            frozen: self.frozen,
+            locked: self.locked,
+            release: false,
+            profile: None,
+            offline: self.offline,
+            configs: Vec::new(),
+            unstable_flags: Vec::new(),
+            workspace: false,
+            packages: self.cargo_package.clone().into_iter().collect(),
+            bins: Vec::new(),
+            examples: Vec::new(),
+            tests: Vec::new(),
+            benches: Vec::new(),
+            lib: false,
+            all_bins: false,
+            all_examples: false,
+            all_tests: false,
+            all_benches: false,
+            all_targets: false,
+            features: Vec::new(),
+            all_features: false,
+            no_default_features: false,
+            doc: false,
+            open: false,
+            test_jobs: None,
+            stats: false,
+            no_producers: false,
+            stub_missing_imports: false,
+            emit: Vec::new(),
+            output_format: self.common.output_format,
+        };
+
+        let spawn_args = self.build_args()?;
+        let (status, outputs) = run_cargo_command(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            Some("build"),
+            &cargo_build_args,
+            &spawn_args,
+        )
+        .await?;
+        if let CommandStatus::Exit(code) = status {
+            bail!("failed to build the component to fuzz (exit code {code})");
+        }
+
+        let runner = get_runner(&cargo_config2::Config::load()?, false)?;
+        let corpus = self.corpus.clone().unwrap_or_else(|| {
+            package
+                .manifest_path
+                .parent()
+                .unwrap()
+                .as_std_path()
+                .join("fuzz")
+                .join("corpus")
+        });
+        let seed = self.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or_default()
+        });
+
+        let mut generator = InputGenerator::new(seed);
+        let mut fuzzed = 0;
+        let mut crashes = 0;
+        for path in &outputs {
+            let bytes = fs::read(path).with_context(|| {
+                format!(
+                    "failed to read output component `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            let (resolve, world) = match wit_component::decode(&bytes).with_context(|| {
+                format!(
+                    "failed to decode output component `{path}`",
+                    path = path.display()
+                )
+            })? {
+                DecodedWasm::Component(resolve, world) => (resolve, world),
+                DecodedWasm::WitPackage(..) => continue,
+            };
+
+            for target in fuzz_targets(&resolve, world) {
+                if let Some(function) = &self.function {
+                    if target.name != *function && target.func.name != *function {
+                        continue;
+                    }
+                }
+
+                fuzzed += 1;
+                crashes += self.fuzz(
+                    &config,
+                    &runner,
+                    path,
+                    (&resolve, &target),
+                    &corpus,
+                    &mut generator,
+                )?;
+            }
+        }
+
+        if fuzzed == 0 {
+            match &self.function {
+                Some(function) => {
+                    bail!("package `{name}` does not export a function named `{function}` that can be fuzzed", name = package.name)
+                }
+                None => bail!(
+                    "package `{name}` does not export any functions that can be fuzzed",
+                    name = package.name
+                ),
+            }
+        }
+
+        if crashes > 0 {
+            bail!(
+                "found {crashes} crashing input{s} (seed {seed}); crashing inputs are saved to `{corpus}`",
+                s = if crashes == 1 { "" } else { "s" },
+                corpus = corpus.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fuzzes a single target, returning the number of crashing inputs.
+    fn fuzz(
+        &self,
+        config: &Config,
+        runner: &PathAndArgs,
+        component: &Path,
+        (resolve, target): (&Resolve, &FuzzTarget),
+        corpus: &Path,
+        generator: &mut InputGenerator,
+    ) -> Result<usize> {
+        let dir = corpus.join(target.corpus_name());
+        fs::create_dir_all(&dir).with_context(|| {
+            format!(
+                "failed to create corpus directory `{dir}`",
+                dir = dir.display()
+            )
+        })?;
+
+        // Start by replaying any previously saved inputs
+        let mut saved = fs::read_dir(&dir)
+            .with_context(|| {
+                format!(
+                    "failed to read corpus directory `{dir}`",
+                    dir = dir.display()
+                )
+            })?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.is_file())
+            .collect::<Vec<_>>();
+        saved.sort();
+
+        let mut crashes = 0;
+        for path in &saved {
+            let call = fs::read_to_string(path).with_context(|| {
+                format!(
+                    "failed to read corpus input `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            if let Some(message) = invoke(runner, component, call.trim())? {
+                crashes += 1;
+                config.terminal().warn(format!(
+                    "saved input `{path}` still crashes `{name}`:\n\n{message}",
+                    path = path.display(),
+                    name = target.name,
+                ))?;
+            }
+        }
+
+        config.terminal().status(
+            "Fuzzing",
+            format!(
+                "`{name}` with {iterations} inputs",
+                name = target.name,
+                iterations = self.iterations,
+            ),
+        )?;
+
+        for _ in 0..self.iterations {
+            let call = generator.call(resolve, target);
+            if let Some(message) = invoke(runner, component, &call)? {
+                let path = dir.join(format!("crash-{hash:016x}", hash = fnv1a(&call)));
+                fs::write(&path, &call).with_context(|| {
+                    format!(
+                        "failed to write crashing input `{path}`",
+                        path = path.display()
+                    )
+                })?;
+
+                config.terminal().warn(format!(
+                    "input `{call}` crashes `{name}`:\n\n{message}\n\nthe input was saved to `{path}`",
+                    name = target.name,
+                    path = path.display(),
+                ))?;
+
+                // Stop fuzzing this target after the first crash
+                return Ok(crashes + 1);
+            }
+        }
+
+        Ok(crashes)
+    }
+
+    fn build_args(&self) -> Result<Vec<String>> {
+        let mut args = Vec::new();
+        args.push("build".to_string());
+
+        if self.common.quiet {
+            args.push("-q".to_string());
+        }
+
+        args.extend(
+            std::iter::repeat_n("-v", self.common.verbose as usize).map(ToString::to_string),
+        );
+
+        if let Some(color) = self.common.color {
+            args.push("--color".to_string());
+            args.push(color.to_string());
+        }
+
+        if self.frozen {
+            args.push("--frozen".to_string());
+        }
+
+        if self.locked {
+            args.push("--locked".to_string());
+        }
+
+        if let Some(spec) = &self.cargo_package {
+            args.push("--package".to_string());
+            args.push(spec.to_string());
+        }
+
+        if let Some(manifest_path) = &self.manifest_path {
+            args.push("--manifest-path".to_string());
+            args.push(
+                manifest_path
+                    .as_os_str()
+                    .to_str()
+                    .with_context(|| {
+                        format!(
+                            "manifest path `{path}` is not valid UTF-8",
+                            path = manifest_path.display()
+                        )
+                    })?
+                    .to_string(),
+            );
+        }
+
+        if self.offline {
+            args.push("--offline".to_string());
+        }
+
+        Ok(args)
+    }
+}
+
+/// Invokes a function of the component with the given WAVE-encoded call.
+///
+/// Returns a description of the crash if the invocation failed.
+fn invoke(runner: &PathAndArgs, component: &Path, call: &str) -> Result<Option<String>> {
+    let mut cmd = Command::new(&runner.path);
+    cmd.args(&runner.args)
+        .arg("--invoke")
+        .arg(call)
+        .arg("--")
+        .arg(component);
+    log::debug!("spawning command {:?}", cmd);
+
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to spawn `{runner}`", runner = runner.path.display()))?;
+
+    if output.status.success() {
+        return Ok(None);
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("Failed to parse invoke") || stderr.contains("while interpreting parameters")
+    {
+        bail!(
+            "`{runner}` failed to parse the generated input `{call}`:\n\n{stderr}",
+            runner = runner.path.display()
+        );
+    }
+
+    // Use the start of the error output as the description
+    let message = stderr
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .take(MAX_MESSAGE_LINES)
+        .map(|l| format!("  {l}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(Some(if message.is_empty() {
+        format!("  the runner exited with {status}", status = output.status)
+    } else {
+        message
+    }))
+}
+
+/// Hashes the input so that identical crashing inputs share a file name.
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
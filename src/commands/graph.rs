@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+
+use crate::{
+    config::Config, for_each_target_world, graph::WorldGraph, load_component_metadata,
+    load_metadata, CargoArguments,
+};
+
+/// Print the import and export graph of a component's target world.
+///
+/// Each imported and exported interface is grouped by the package or
+/// dependency that contributed it; the graph can be rendered with Graphviz
+/// (`dot`) or embedded in Markdown documentation (`mermaid`).
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct GraphCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Package to print the graph of
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<String>,
+
+    /// Print the graphs of all packages in the workspace
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+
+    /// The format of the graph
+    #[clap(long = "format", value_name = "FORMAT", value_parser = ["dot", "mermaid"], default_value = "dot")]
+    pub format: String,
+}
+
+impl GraphCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing graph command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let client = config.client(false).await?;
+
+        let cargo_args = CargoArguments::parse()?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            None,
+        )?;
+
+        let mut graphs = Vec::new();
+        for_each_target_world(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            |package, resolution, resolve, world| {
+                let graph = WorldGraph::new(&package.name, resolution, resolve, world);
+                graphs.push(graph.render(&self.format)?);
+                Ok(())
+            },
+        )
+        .await?;
+
+        config.terminal().write_stdout(graphs.join("\n"), None)?;
+
+        Ok(())
+    }
+}
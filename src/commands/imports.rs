@@ -0,0 +1,111 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::Result;
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+
+use crate::{
+    config::Config, generate_bindings, load_component_metadata, load_metadata, CargoArguments,
+};
+
+/// Print the `unlocked-dep` import names a component requires of its host.
+///
+/// The import names of dependencies are chosen when the bindings are generated
+/// and are also written to `target/component/<package>/imports.json`.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct ImportsCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Package to print the imports of
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<String>,
+
+    /// Print the imports of all packages in the workspace
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+
+    /// Space or comma separated list of features that enable optional dependencies
+    #[clap(long = "features", short = 'F', value_name = "FEATURES")]
+    pub features: Vec<String>,
+
+    /// Activate all available features
+    #[clap(long = "all-features")]
+    pub all_features: bool,
+
+    /// Do not activate the `default` feature
+    #[clap(long = "no-default-features")]
+    pub no_default_features: bool,
+
+    /// Include the imports of dev-dependencies as when building tests and benchmarks
+    #[clap(long = "dev")]
+    pub dev: bool,
+
+    /// Print the import names as JSON
+    #[clap(long = "json")]
+    pub json: bool,
+}
+
+impl ImportsCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing imports command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let client = config.client(false).await?;
+
+        let cargo_args = CargoArguments::parse()?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            Some(cargo_args.profile_name()),
+        )?;
+        let bindings = generate_bindings(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            &cargo_args,
+            self.dev,
+            None,
+        )
+        .await?;
+
+        let sorted: BTreeMap<_, BTreeMap<_, _>> = packages
+            .iter()
+            .filter_map(|p| {
+                let bindings = bindings.get(&p.package.id)?;
+                Some((&p.package.name, bindings.import_name_map.iter().collect()))
+            })
+            .collect();
+
+        let terminal = config.terminal();
+        if self.json {
+            terminal.write_stdout(serde_json::to_string_pretty(&sorted)? + "\n", None)?;
+            return Ok(());
+        }
+
+        for (name, map) in sorted {
+            terminal.write_stdout(format!("{name}:\n"), None)?;
+            if map.is_empty() {
+                terminal.write_stdout("  (no dependency imports)\n", None)?;
+            }
+
+            for (import, import_name) in map {
+                terminal.write_stdout(format!("  {import} => {import_name}\n"), None)?;
+            }
+        }
+
+        Ok(())
+    }
+}
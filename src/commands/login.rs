@@ -0,0 +1,116 @@
+use std::{
+    io::{self, BufRead},
+    sync::Arc,
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::{command::CommonOptions, terminal::Colors};
+use clap::Args;
+use wasm_pkg_client::{
+    oci::{BasicCredentials, OciRegistryConfig},
+    warg::WargRegistryConfig,
+    Registry,
+};
+
+use crate::config::Config;
+
+/// Log in to a registry by storing an auth token or signing key
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct LoginCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The registry to log in to (defaults to the default registry).
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<Registry>,
+
+    /// Read the token or signing key from stdin instead of prompting for it
+    #[clap(long = "token-stdin")]
+    pub token_stdin: bool,
+
+    /// Store a signing key for publishing to a warg registry instead of an auth token
+    #[clap(long = "signing-key", conflicts_with = "username")]
+    pub signing_key: bool,
+
+    /// The user name for the credentials of an OCI registry; the token is used as the password
+    #[clap(long = "username", value_name = "USERNAME")]
+    pub username: Option<String>,
+}
+
+impl LoginCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing login command");
+
+        let config =
+            Config::new_or_empty(self.common.new_terminal(), self.common.config.clone()).await?;
+        let registry = config.registry_or_default(self.registry.as_ref())?;
+
+        let what = if self.signing_key {
+            "signing key"
+        } else if self.username.is_some() {
+            "password"
+        } else {
+            "token"
+        };
+
+        let secret = if self.token_stdin {
+            let mut line = String::new();
+            io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .with_context(|| format!("failed to read the {what} from stdin"))?;
+            line.trim().to_string()
+        } else {
+            rpassword::prompt_password(format!("{what} for `{registry}`: "))
+                .with_context(|| format!("failed to read the {what}"))?
+                .trim()
+                .to_string()
+        };
+
+        if secret.is_empty() {
+            bail!("the {what} for registry `{registry}` cannot be empty");
+        }
+
+        let path = Config::update_pkg_config_file(self.common.config, |pkg_config| {
+            let reg_config = pkg_config.get_or_insert_registry_config_mut(&registry);
+            match self.username {
+                Some(username) => {
+                    let mut oci_conf = OciRegistryConfig::try_from(&*reg_config)?;
+                    oci_conf.credentials = Some(BasicCredentials {
+                        username,
+                        password: secret.into(),
+                    });
+                    reg_config.set_backend_config("oci", oci_conf)?;
+                }
+                None => {
+                    let mut warg_conf = WargRegistryConfig::try_from(&*reg_config)?;
+                    if self.signing_key {
+                        warg_conf.signing_key = Some(Arc::new(
+                            secret.try_into().context("failed to parse signing key")?,
+                        ));
+                    } else {
+                        warg_conf.auth_token = Some(secret.into());
+                    }
+                    reg_config.set_backend_config("warg", warg_conf)?;
+                }
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        config.terminal().status_with_color(
+            "Logged in",
+            format!(
+                "to registry `{registry}` (credentials stored in `{path}`)",
+                path = path.display()
+            ),
+            Colors::Green,
+        )?;
+
+        Ok(())
+    }
+}
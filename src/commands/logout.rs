@@ -0,0 +1,76 @@
+use anyhow::Result;
+use cargo_component_core::{command::CommonOptions, terminal::Colors};
+use clap::Args;
+use wasm_pkg_client::{oci::OciRegistryConfig, warg::WargRegistryConfig, Registry};
+
+use crate::config::Config;
+
+/// Log out of a registry by removing its stored credentials
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct LogoutCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The registry to log out of (defaults to the default registry).
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<Registry>,
+}
+
+impl LogoutCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing logout command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
+        let registry = config.registry_or_default(self.registry.as_ref())?;
+
+        let mut removed = false;
+        Config::update_pkg_config_file(self.common.config, |pkg_config| {
+            if pkg_config.registry_config(&registry).is_none() {
+                return Ok(());
+            }
+
+            let reg_config = pkg_config.get_or_insert_registry_config_mut(&registry);
+            let backends = reg_config
+                .configured_backend_types()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            for backend in backends {
+                match backend.as_str() {
+                    "warg" => {
+                        let mut warg_conf = WargRegistryConfig::try_from(&*reg_config)?;
+                        removed |= warg_conf.auth_token.take().is_some();
+                        removed |= warg_conf.signing_key.take().is_some();
+                        reg_config.set_backend_config("warg", warg_conf)?;
+                    }
+                    "oci" => {
+                        let mut oci_conf = OciRegistryConfig::try_from(&*reg_config)?;
+                        removed |= oci_conf.credentials.take().is_some();
+                        reg_config.set_backend_config("oci", oci_conf)?;
+                    }
+                    _ => continue,
+                }
+            }
+
+            Ok(())
+        })
+        .await?;
+
+        if removed {
+            config.terminal().status_with_color(
+                "Logged out",
+                format!("of registry `{registry}`"),
+                Colors::Green,
+            )?;
+        } else {
+            config
+                .terminal()
+                .warn(format!("not logged in to registry `{registry}`"))?;
+        }
+
+        Ok(())
+    }
+}
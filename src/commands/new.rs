@@ -8,8 +8,12 @@ use std::{
 
 use anyhow::{bail, Context, Result};
 use cargo_component_core::{
+    cache_dir,
     command::CommonOptions,
-    registry::{Dependency, DependencyResolution, DependencyResolver, RegistryResolution},
+    registry::{
+        find_release_by_digest, Dependency, DependencyResolution, DependencyResolver,
+        RegistryResolution,
+    },
 };
 use clap::Args;
 use heck::ToKebabCase;
@@ -17,14 +21,62 @@ use semver::VersionReq;
 use toml_edit::{table, value, DocumentMut, Item, Table, Value};
 use wasm_pkg_client::caching::{CachingClient, FileCache};
 
+use wit_bindgen_rust::to_rust_ident;
+
 use crate::{
-    config::Config, generate_bindings, generator::SourceGenerator, load_component_metadata,
-    load_metadata, metadata, metadata::DEFAULT_WIT_DIR, CargoArguments,
+    config::{default_namespace, Config, NewSettings},
+    generate_bindings,
+    generator::{SourceGenerator, DEFAULT_IMPLEMENTOR},
+    load_component_metadata, load_metadata, metadata,
+    metadata::DEFAULT_WIT_DIR,
+    CargoArguments, CommandStatus,
 };
 
-const WIT_BINDGEN_RT_CRATE: &str = "wit-bindgen-rt";
+pub(crate) const WIT_BINDGEN_RT_CRATE: &str = "wit-bindgen-rt";
+
+/// Represents a curated target for a WASI proposal.
+struct CuratedTarget {
+    /// The package name accepted by `--target`.
+    package: &'static str,
+    /// The world of the package to target.
+    world: &'static str,
+    /// The exact version of the package that is pinned.
+    version: &'static str,
+    /// The summary added to the generated source file.
+    summary: &'static str,
+}
 
-fn escape_wit(s: &str) -> Cow<str> {
+/// The curated targets for `wasi-cloud` style components.
+///
+/// The WASI proposals are still drafts that may change between releases, so
+/// the targets pin an exact version.
+const CURATED_TARGETS: &[CuratedTarget] = &[
+    CuratedTarget {
+        package: "wasi:keyvalue",
+        world: "watch-service",
+        version: "0.2.0-draft2",
+        summary: "A component that is notified of changes to a key-value store.\n\n\
+                  The key-value stores of the host are accessed with the \
+                  `bindings::wasi::keyvalue` modules.",
+    },
+    CuratedTarget {
+        package: "wasi:messaging",
+        world: "messaging-core",
+        version: "0.2.0-draft",
+        summary: "A component that handles incoming messages.\n\n\
+                  Messages are sent with the `bindings::wasi::messaging` modules.",
+    },
+    CuratedTarget {
+        package: "wasi:blobstore",
+        world: "imports",
+        version: "0.2.0-draft",
+        summary: "A component that uses a blob store.\n\n\
+                  The containers of the host are accessed with the \
+                  `bindings::wasi::blobstore` modules.",
+    },
+];
+
+fn escape_wit(s: &str) -> Cow<'_, str> {
     match s {
         "use" | "type" | "func" | "u8" | "u16" | "u32" | "u64" | "s8" | "s16" | "s32" | "s64"
         | "float32" | "float64" | "char" | "record" | "flags" | "variant" | "enum" | "union"
@@ -36,6 +88,47 @@ fn escape_wit(s: &str) -> Cow<str> {
     }
 }
 
+/// Validates that the name of the type implementing the exports is a legal
+/// Rust identifier.
+fn validate_implementor(implementor: &str) -> Result<()> {
+    let mut chars = implementor.chars();
+    let valid = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !matches!(implementor, "_" | "Self")
+        && to_rust_ident(implementor) != format!("{implementor}_");
+    if !valid {
+        bail!("implementor `{implementor}` is not a legal Rust identifier");
+    }
+
+    Ok(())
+}
+
+/// Reads the edition of the package with the manifest in the given directory.
+fn manifest_edition(dir: &Path) -> Result<String> {
+    let manifest_path = dir.join("Cargo.toml");
+    let manifest = fs::read_to_string(&manifest_path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = manifest_path.display()
+        )
+    })?;
+
+    let doc: DocumentMut = manifest.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = manifest_path.display()
+        )
+    })?;
+
+    // Cargo defaults to the 2015 edition when none is specified
+    Ok(doc["package"]["edition"]
+        .as_str()
+        .unwrap_or("2015")
+        .to_string())
+}
+
 /// Create a new WebAssembly component package at <path>
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -67,13 +160,10 @@ pub struct NewCommand {
     #[clap(long = "edition", value_name = "YEAR", value_parser = ["2015", "2018", "2021"])]
     pub edition: Option<String>,
 
-    /// The component package namespace to use.
-    #[clap(
-        long = "namespace",
-        value_name = "NAMESPACE",
-        default_value = "component"
-    )]
-    pub namespace: String,
+    /// The component package namespace to use, defaults to the `namespace`
+    /// setting of the user configuration or `component`.
+    #[clap(long = "namespace", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
 
     /// Set the resulting package name, defaults to the directory name
     #[clap(long = "name", value_name = "NAME")]
@@ -84,9 +174,18 @@ pub struct NewCommand {
     pub editor: Option<String>,
 
     /// Use the specified target world from a WIT package.
+    ///
+    /// The package may be pinned with a version requirement, such as
+    /// `ns:pkg/world@=1.2.3`, or the digest of a release, such as
+    /// `ns:pkg/world@sha256:<digest>`.
     #[clap(long = "target", short = 't', value_name = "TARGET", requires = "lib")]
     pub target: Option<String>,
 
+    /// The name of the type implementing the exports in the generated source,
+    /// defaults to `Component`.
+    #[clap(long = "implementor", value_name = "NAME", requires = "lib")]
+    pub implementor: Option<String>,
+
     /// Use the specified default registry when generating the package.
     #[clap(long = "registry", value_name = "REGISTRY")]
     pub registry: Option<String>,
@@ -95,6 +194,10 @@ pub struct NewCommand {
     #[clap(long = "no-rustfmt")]
     pub no_rustfmt: bool,
 
+    /// Generate a `Containerfile` that packages the component in an image.
+    #[clap(long = "containerize")]
+    pub containerize: bool,
+
     /// The path for the generated package.
     #[clap(value_name = "path")]
     pub path: PathBuf,
@@ -145,31 +248,66 @@ impl<'a> PackageName<'a> {
 
 impl NewCommand {
     /// Executes the command.
-    pub async fn exec(self) -> Result<()> {
+    ///
+    /// Returns [`CommandStatus::Exit`] if `cargo new` fails.
+    pub async fn exec(self) -> Result<CommandStatus> {
         log::debug!("executing new command");
 
-        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
 
-        let name = PackageName::new(&self.namespace, self.name.as_deref(), &self.path)?;
+        let namespace = match &self.namespace {
+            Some(namespace) => namespace.clone(),
+            None => default_namespace()?,
+        };
+        let name = PackageName::new(&namespace, self.name.as_deref(), &self.path)?;
+        let settings = NewSettings::load()?;
+        if let Some(implementor) = &self.implementor {
+            validate_implementor(implementor)?;
+        }
 
         let out_dir = std::env::current_dir()
             .with_context(|| "couldn't get the current directory of the process")?
             .join(&self.path);
 
-        let target: Option<metadata::Target> = match self.target.as_deref() {
-            Some(s) if s.contains('@') => Some(s.parse()?),
-            Some(s) => Some(format!("{s}@{version}", version = VersionReq::STAR).parse()?),
-            None => None,
+        let curated = self
+            .target
+            .as_deref()
+            .and_then(|s| CURATED_TARGETS.iter().find(|t| t.package == s));
+
+        let client = config.client(false).await?;
+        let target: Option<metadata::Target> = match (curated, self.target.as_deref()) {
+            (Some(curated), _) => Some(
+                format!(
+                    "{package}/{world}@={version}",
+                    package = curated.package,
+                    world = curated.world,
+                    version = curated.version
+                )
+                .parse()?,
+            ),
+            // Digests always contain a colon, version requirements never do
+            (None, Some(s)) if s.split_once('@').is_some_and(|(_, v)| v.contains(':')) => {
+                let (target, digest) = s.split_once('@').unwrap();
+                let name = target.split_once('/').map(|(n, _)| n).unwrap_or(target);
+                let digest = digest
+                    .parse()
+                    .with_context(|| format!("invalid target digest `{digest}`"))?;
+                let release = find_release_by_digest(&client, &name.parse()?, &digest).await?;
+                Some(format!("{target}@={version}", version = release.version).parse()?)
+            }
+            (None, Some(s)) if s.contains('@') => Some(s.parse()?),
+            (None, Some(s)) => Some(format!("{s}@{version}", version = VersionReq::STAR).parse()?),
+            (None, None) => None,
         };
-        let client = config.client(self.common.cache_dir.clone(), false).await?;
         let target = self.resolve_target(Arc::clone(&client), target).await?;
-        let source = self.generate_source(&target).await?;
 
         let mut command = self.new_command();
         match command.status() {
             Ok(status) => {
                 if !status.success() {
-                    std::process::exit(status.code().unwrap_or(1));
+                    return Ok(CommandStatus::Exit(status.code().unwrap_or(1)));
                 }
             }
             Err(e) => {
@@ -177,6 +315,24 @@ impl NewCommand {
             }
         }
 
+        // Format the source for the edition `cargo new` selected
+        let edition = manifest_edition(&out_dir)?;
+        let mut source = self.generate_source(&target, &edition).await?;
+        if let Some(curated) = curated {
+            let summary = curated
+                .summary
+                .lines()
+                .map(|l| {
+                    if l.is_empty() {
+                        "//!\n".to_string()
+                    } else {
+                        format!("//! {l}\n")
+                    }
+                })
+                .collect::<String>();
+            source = format!("{summary}\n{source}").into();
+        }
+
         let target = target.map(|(res, world)| {
             match res {
                 DependencyResolution::Registry(reg) => (reg, world),
@@ -185,22 +341,35 @@ impl NewCommand {
                 _ => unreachable!(),
             }
         });
-        self.update_manifest(&config, &name, &out_dir, &target)?;
+        self.update_manifest(&config, &settings, &name, &out_dir, &target)?;
         self.create_source_file(&config, &out_dir, source.as_ref(), &target)?;
         self.create_targets_file(&name, &out_dir)?;
         self.create_editor_settings_file(&out_dir)?;
+        self.create_containerfile(&out_dir)?;
 
         // Now that we've created the project, generate the bindings so that
         // users can start looking at code with an IDE and not see red squiggles.
         let cargo_args = CargoArguments::parse()?;
         let manifest_path = out_dir.join("Cargo.toml");
         let metadata = load_metadata(Some(&manifest_path))?;
-        let packages =
-            load_component_metadata(&metadata, cargo_args.packages.iter(), cargo_args.workspace)?;
-        let _import_name_map =
-            generate_bindings(client, &config, &metadata, &packages, &cargo_args).await?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            Some(cargo_args.profile_name()),
+        )?;
+        let _bindings = generate_bindings(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            &cargo_args,
+            false,
+            None,
+        )
+        .await?;
 
-        Ok(())
+        Ok(CommandStatus::Success)
     }
 
     fn new_command(&self) -> Command {
@@ -223,7 +392,7 @@ impl NewCommand {
             command.arg("-q");
         }
 
-        command.args(std::iter::repeat("-v").take(self.common.verbose as usize));
+        command.args(std::iter::repeat_n("-v", self.common.verbose as usize));
 
         if let Some(color) = self.common.color {
             command.arg("--color").arg(color.to_string());
@@ -240,6 +409,7 @@ impl NewCommand {
     fn update_manifest(
         &self,
         config: &Config,
+        settings: &NewSettings,
         name: &PackageName,
         out_dir: &Path,
         target: &Option<(RegistryResolution, Option<String>)>,
@@ -262,6 +432,8 @@ impl NewCommand {
         if !self.is_command() {
             doc["lib"] = table();
             doc["lib"]["crate-type"] = value(Value::from_iter(["cdylib"]));
+            // Doctests cannot be run for WebAssembly components
+            doc["lib"]["doctest"] = value(false);
         }
 
         let mut component = Table::new();
@@ -298,12 +470,27 @@ impl NewCommand {
             component["proxy"] = value(true);
         }
 
+        if let Some(implementor) = &self.implementor {
+            let mut bindings = Table::new();
+            bindings["implementor"] = value(implementor);
+            component["bindings"] = Item::Table(bindings);
+        }
+
         let mut metadata = Table::new();
         metadata.set_implicit(true);
         metadata.set_position(doc.len());
         metadata["component"] = Item::Table(component);
         doc["package"]["metadata"] = Item::Table(metadata);
 
+        if let Some(release) = &settings.release_profile {
+            let mut release = release.clone();
+            release.set_position(doc.len());
+            let mut profile = Table::new();
+            profile.set_implicit(true);
+            profile["release"] = Item::Table(release);
+            doc["profile"] = Item::Table(profile);
+        }
+
         fs::write(&manifest_path, doc.to_string()).with_context(|| {
             format!(
                 "failed to write manifest file `{path}`",
@@ -312,10 +499,14 @@ impl NewCommand {
         })?;
 
         // Run cargo add for wit-bindgen and bitflags
+        let dependency = match &settings.wit_bindgen_rt {
+            Some(version) => format!("{WIT_BINDGEN_RT_CRATE}@{version}"),
+            None => WIT_BINDGEN_RT_CRATE.to_string(),
+        };
         let mut cargo_add_command = std::process::Command::new("cargo");
         cargo_add_command.arg("add");
         cargo_add_command.arg("--quiet");
-        cargo_add_command.arg(WIT_BINDGEN_RT_CRATE);
+        cargo_add_command.arg(&dependency);
         cargo_add_command.arg("--features");
         cargo_add_command.arg("bitflags");
         cargo_add_command.current_dir(out_dir);
@@ -323,7 +514,9 @@ impl NewCommand {
             .status()
             .context("failed to execute `cargo add` command")?;
         if !status.success() {
-            bail!("`cargo add {WIT_BINDGEN_RT_CRATE} --features bitflags` command exited with non-zero status");
+            bail!(
+                "`cargo add {dependency} --features bitflags` command exited with non-zero status"
+            );
         }
 
         config.terminal().status(
@@ -338,14 +531,21 @@ impl NewCommand {
         self.bin || !self.lib
     }
 
+    fn implementor(&self) -> &str {
+        self.implementor.as_deref().unwrap_or(DEFAULT_IMPLEMENTOR)
+    }
+
     async fn generate_source(
         &self,
         target: &Option<(DependencyResolution, Option<String>)>,
-    ) -> Result<Cow<str>> {
+        edition: &str,
+    ) -> Result<Cow<'_, str>> {
         match target {
             Some((resolution, world)) => {
                 let generator =
-                    SourceGenerator::new(resolution, resolution.name(), !self.no_rustfmt);
+                    SourceGenerator::new(resolution, resolution.name(), !self.no_rustfmt)
+                        .with_edition(edition)
+                        .with_implementor(self.implementor());
                 generator.generate(world.as_deref()).await.map(Into::into)
             }
             None => {
@@ -356,22 +556,25 @@ impl NewCommand {
 "#
                     .into())
                 } else {
-                    Ok(r#"#[allow(warnings)]
+                    Ok(format!(
+                        r#"#[allow(warnings)]
 mod bindings;
 
 use bindings::Guest;
 
-struct Component;
+struct {implementor};
 
-impl Guest for Component {
+impl Guest for {implementor} {{
     /// Say hello!
-    fn hello_world() -> String {
+    fn hello_world() -> String {{
         "Hello, World!".to_string()
-    }
-}
+    }}
+}}
 
-bindings::export!(Component with_types_in bindings);
-"#
+bindings::export!({implementor} with_types_in bindings);
+"#,
+                        implementor = self.implementor()
+                    )
                     .into())
                 }
             }
@@ -519,6 +722,53 @@ world example {{
         }
     }
 
+    fn create_containerfile(&self, out_dir: &Path) -> Result<()> {
+        if !self.containerize {
+            return Ok(());
+        }
+
+        let crate_name = match &self.name {
+            Some(name) => name.clone(),
+            None => self
+                .path
+                .file_name()
+                .expect("invalid path")
+                .to_string_lossy()
+                .into_owned(),
+        };
+        let file_name = if self.is_command() {
+            format!("{crate_name}.wasm")
+        } else {
+            format!("{name}.wasm", name = crate_name.replace('-', "_"))
+        };
+
+        let path = out_dir.join("Containerfile");
+        fs::write(
+            &path,
+            format!(
+                r#"# An image containing only the component.
+#
+# Build the component first with `cargo component build --release`, then
+# build the image with a container engine that supports WebAssembly:
+#
+#   docker buildx build --platform wasi/wasm -t {crate_name} .
+#
+# Alternatively, `cargo component containerize` writes an equivalent image
+# without a container engine.
+FROM scratch
+COPY target/wasm32-wasip1/release/{file_name} /{file_name}
+ENTRYPOINT ["/{file_name}"]
+"#
+            ),
+        )
+        .with_context(|| {
+            format!(
+                "failed to write containerfile `{path}`",
+                path = path.display()
+            )
+        })
+    }
+
     /// This will always return a registry resolution if it is `Some`, but we return the
     /// `DependencyResolution` instead so we can actually resolve the dependency.
     async fn resolve_target(
@@ -532,7 +782,8 @@ world example {{
                 package,
                 world,
             }) => {
-                let mut resolver = DependencyResolver::new_with_client(client, None)?;
+                let mut resolver = DependencyResolver::new_with_client(client, None)?
+                    .with_cache_dir(cache_dir(self.common.cache_dir.clone())?);
                 let dependency = Dependency::Package(package);
 
                 resolver.add_dependency(&name, &dependency).await?;
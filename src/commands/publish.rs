@@ -2,13 +2,16 @@ use std::{path::PathBuf, sync::Arc};
 
 use anyhow::{bail, Context, Result};
 use cargo_component_core::command::CommonOptions;
+use cargo_metadata::Package;
 use clap::Args;
 use wasm_pkg_client::{warg::WargRegistryConfig, Registry};
+use wit_parser::UnresolvedPackageGroup;
 
 use crate::{
     config::{CargoArguments, CargoPackageSpec, Config},
-    is_wasm_target, load_metadata, publish, run_cargo_command, PackageComponentMetadata,
-    PublishOptions,
+    is_wasm_target, load_metadata,
+    metadata::{Access, ComponentMetadata},
+    publish, run_cargo_command, CommandStatus, PackageComponentMetadata, PublishOptions,
 };
 
 /// Publish a package to a registry.
@@ -70,6 +73,14 @@ pub struct PublishCommand {
     /// The registry to publish to.
     #[clap(long = "registry", value_name = "REGISTRY")]
     pub registry: Option<Registry>,
+
+    /// Initialize the package in the registry if it does not yet exist
+    #[clap(long = "init")]
+    pub init: bool,
+
+    /// Publish even if the `package` setting does not match the WIT package
+    #[clap(long = "allow-mismatch")]
+    pub allow_mismatch: bool,
 }
 
 impl PublishCommand {
@@ -77,9 +88,10 @@ impl PublishCommand {
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing publish command");
 
-        let mut config =
-            Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
-        let client = config.client(self.common.cache_dir.clone(), false).await?;
+        let mut config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let client = config.client(false).await?;
 
         if let Some(target) = &self.target {
             if !is_wasm_target(target) {
@@ -92,39 +104,51 @@ impl PublishCommand {
             Some(spec) => Some(spec.clone()),
             None => CargoPackageSpec::find_current_package_spec(&metadata),
         };
-        let packages = [PackageComponentMetadata::new(if let Some(spec) = &spec {
-            metadata
-                .packages
-                .iter()
-                .find(|p| {
-                    p.name == spec.name
-                        && match spec.version.as_ref() {
-                            Some(v) => &p.version == v,
-                            None => true,
-                        }
-                })
-                .with_context(|| {
-                    format!("package ID specification `{spec}` did not match any packages")
-                })?
-        } else {
-            metadata
-                .root_package()
-                .context("no root package found in manifest")?
-        })?];
+        let mut packages = [PackageComponentMetadata::new(
+            if let Some(spec) = &spec {
+                metadata
+                    .packages
+                    .iter()
+                    .find(|p| {
+                        p.name == spec.name
+                            && match spec.version.as_ref() {
+                                Some(v) => &p.version == v,
+                                None => true,
+                            }
+                    })
+                    .with_context(|| {
+                        format!("package ID specification `{spec}` did not match any packages")
+                    })?
+            } else {
+                metadata
+                    .root_package()
+                    .context("no root package found in manifest")?
+            },
+            Some("release"),
+        )?];
+        packages[0]
+            .metadata
+            .resolve_workspace_members(&metadata, Some("release"))?;
 
         let package = packages[0].package;
         let component_metadata = &packages[0].metadata;
 
-        let name = component_metadata.section.package.as_ref().with_context(|| {
-            format!(
-                "package `{name}` is missing a `package.metadata.component.package` setting in manifest `{path}`",
-                name = package.name,
+        let name = &component_metadata.publish_package()?;
+        let publish_config = &component_metadata.section.publish;
+        let registry = self.registry.as_ref().or(publish_config.registry.as_ref());
+        if publish_config.access == Access::Private && registry.is_none() {
+            bail!(
+                "package `{name}` has private access and must be published to an explicit registry; \
+                 set `registry` in the `[package.metadata.component.publish]` section of manifest \
+                 `{path}` or use the `--registry` option",
                 path = package.manifest_path
-            )
-        })?;
+            );
+        }
+
+        self.check_wit_package(&config, package, component_metadata)?;
 
         if let Ok(key) = std::env::var("CARGO_COMPONENT_PUBLISH_KEY") {
-            let registry = config.pkg_config.resolve_registry(name).ok_or_else(|| anyhow::anyhow!("Tried to set a signing key, but registry was not set and no default registry was found. Try setting the `--registry` option."))?.to_owned();
+            let registry = registry.or_else(|| config.pkg_config.resolve_registry(name)).ok_or_else(|| anyhow::anyhow!("Tried to set a signing key, but registry was not set and no default registry was found. Try setting the `--registry` option."))?.to_owned();
             // NOTE(thomastaylor312): If config doesn't already exist, this will essentially force warg
             // usage because we'll be creating a config for warg, which means it will default to that
             // protocol. So for all intents and purposes, setting a publish key forces warg usage.
@@ -149,13 +173,37 @@ impl PublishCommand {
             frozen: self.frozen,
             locked: self.locked,
             release: true,
+            profile: None,
             offline: self.offline,
+            configs: Vec::new(),
+            unstable_flags: Vec::new(),
             workspace: false,
             packages: self.cargo_package.clone().into_iter().collect(),
+            bins: Vec::new(),
+            examples: Vec::new(),
+            tests: Vec::new(),
+            benches: Vec::new(),
+            lib: false,
+            all_bins: false,
+            all_examples: false,
+            all_tests: false,
+            all_benches: false,
+            all_targets: false,
+            features: self.features.clone(),
+            all_features: self.all_features,
+            no_default_features: self.no_default_features,
+            doc: false,
+            open: false,
+            test_jobs: None,
+            stats: false,
+            no_producers: false,
+            stub_missing_imports: false,
+            emit: Vec::new(),
+            output_format: self.common.output_format,
         };
 
         let spawn_args = self.build_args()?;
-        let outputs = run_cargo_command(
+        let (status, outputs) = run_cargo_command(
             client.clone(),
             &config,
             &metadata,
@@ -165,6 +213,10 @@ impl PublishCommand {
             &spawn_args,
         )
         .await?;
+        if let CommandStatus::Exit(code) = status {
+            bail!("failed to build the component to publish (exit code {code})");
+        }
+
         if outputs.len() != 1 {
             bail!(
                 "expected one output from `cargo build`, got {len}",
@@ -175,15 +227,65 @@ impl PublishCommand {
         let options = PublishOptions {
             package,
             name,
-            registry: self.registry.as_ref(),
+            registry,
             version: &component_metadata.version,
             path: &outputs[0],
             dry_run: self.dry_run,
+            init: self.init,
         };
 
         publish(&config, client, &options).await
     }
 
+    /// Checks that the `package` setting of the component matches the name
+    /// of the WIT package of its local target.
+    fn check_wit_package(
+        &self,
+        config: &Config,
+        package: &Package,
+        metadata: &ComponentMetadata,
+    ) -> Result<()> {
+        let (Some(name), Some(path)) = (&metadata.section.package, metadata.target_path()) else {
+            return Ok(());
+        };
+
+        let group = if path.is_dir() {
+            UnresolvedPackageGroup::parse_dir(&path)
+        } else {
+            UnresolvedPackageGroup::parse_file(&path)
+        }
+        .with_context(|| {
+            format!(
+                "failed to parse local target `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        let wit = &group.main.name;
+        if wit.namespace == name.namespace().as_ref() && wit.name == name.name().as_ref() {
+            return Ok(());
+        }
+
+        if !self.allow_mismatch {
+            bail!(
+                "package `{name}` in manifest `{manifest}` does not match WIT package \
+                 `{ns}:{pkg}` of local target `{path}`; use the `--allow-mismatch` option to \
+                 publish it anyway",
+                manifest = package.manifest_path,
+                ns = wit.namespace,
+                pkg = wit.name,
+                path = path.display()
+            );
+        }
+
+        config.terminal().warn(format!(
+            "package `{name}` does not match WIT package `{ns}:{pkg}` of local target `{path}`",
+            ns = wit.namespace,
+            pkg = wit.name,
+            path = path.display()
+        ))
+    }
+
     fn build_args(&self) -> Result<Vec<String>> {
         let mut args = Vec::new();
         args.push("build".to_string());
@@ -194,9 +296,7 @@ impl PublishCommand {
         }
 
         args.extend(
-            std::iter::repeat("-v")
-                .take(self.common.verbose as usize)
-                .map(ToString::to_string),
+            std::iter::repeat_n("-v", self.common.verbose as usize).map(ToString::to_string),
         );
 
         if let Some(color) = self.common.color {
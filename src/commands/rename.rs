@@ -0,0 +1,453 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::command::CommonOptions;
+use cargo_metadata::{Metadata, Package};
+use clap::Args;
+use heck::{ToKebabCase, ToSnakeCase};
+use toml_edit::{value, DocumentMut, Item};
+use wit_bindgen_rust::to_rust_ident;
+
+use crate::{
+    config::{CargoArguments, CargoPackageSpec, Config},
+    freshness::{rust_sources, HostFileSystem},
+    generate_bindings, load_component_metadata, load_metadata,
+    metadata::ComponentMetadata,
+    PackageComponentMetadata,
+};
+
+/// The dependency tables of a manifest that may refer to a renamed package.
+const DEPENDENCY_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+
+/// Rename a WebAssembly component package
+///
+/// The crate name, the `package` setting, the WIT package declaration and
+/// the generated bindings are renamed together so that they stay in sync.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct RenameCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to the manifest of the package to rename
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Cargo package to rename (see `cargo help pkgid`)
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub spec: Option<CargoPackageSpec>,
+
+    /// The new name of the package
+    #[clap(value_name = "NAME")]
+    pub name: String,
+}
+
+/// The WIT package name of a component (e.g. `my-org:my-component`).
+struct WitPackageName {
+    namespace: String,
+    name: String,
+}
+
+impl WitPackageName {
+    fn of(metadata: &ComponentMetadata) -> Self {
+        match &metadata.section.package {
+            Some(package) => Self {
+                namespace: package.namespace().to_string(),
+                name: package.name().to_string(),
+            },
+            None => Self {
+                namespace: metadata.namespace.clone(),
+                name: metadata.name.to_kebab_case(),
+            },
+        }
+    }
+
+    /// Gets the module path of the package in the generated bindings.
+    fn module_path(&self) -> String {
+        format!(
+            "::{ns}::{name}::",
+            ns = to_rust_ident(&self.namespace.to_snake_case()),
+            name = to_rust_ident(&self.name.to_snake_case())
+        )
+    }
+}
+
+impl std::fmt::Display for WitPackageName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{ns}:{name}", ns = self.namespace, name = self.name)
+    }
+}
+
+impl RenameCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing rename command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+
+        let spec = match &self.spec {
+            Some(spec) => Some(spec.clone()),
+            None => CargoPackageSpec::find_current_package_spec(&metadata),
+        };
+
+        let PackageComponentMetadata {
+            package,
+            metadata: component,
+        } = match &spec {
+            Some(spec) => {
+                let pkgs = load_component_metadata(&metadata, std::iter::once(spec), false, None)?;
+                assert!(pkgs.len() == 1, "one package should be present");
+                pkgs.into_iter().next().unwrap()
+            }
+            None => PackageComponentMetadata::new(
+                metadata
+                    .root_package()
+                    .context("no root package found in metadata")?,
+                None,
+            )?,
+        };
+
+        self.validate(&metadata, package)?;
+
+        let old = WitPackageName::of(&component);
+        let new = WitPackageName {
+            namespace: old.namespace.clone(),
+            name: self.name.to_kebab_case(),
+        };
+
+        update_manifest(
+            package.manifest_path.as_std_path(),
+            &self.name,
+            &new,
+            component.section.package.is_some(),
+        )?;
+
+        if let Some(path) = component.target_path() {
+            for path in wit_sources(&path)? {
+                rename_in_file(&path, |source| rename_wit_package(source, &old, &new))?;
+            }
+        }
+
+        // References to the interfaces of the package in the generated bindings
+        // change with the package name, so update the sources that use them
+        let src_dir = package.manifest_path.parent().unwrap().join("src");
+        if src_dir.is_dir() {
+            let bindings_path = src_dir.join("bindings.rs").into_std_path_buf();
+            let (old_path, new_path) = (old.module_path(), new.module_path());
+            for path in rust_sources(&HostFileSystem, src_dir.as_std_path(), &bindings_path)? {
+                rename_in_file(&path, |source| {
+                    source
+                        .contains(&old_path)
+                        .then(|| source.replace(&old_path, &new_path))
+                })?;
+            }
+        }
+
+        for dependent in metadata.workspace_packages() {
+            if dependent.id != package.id {
+                update_dependent(dependent, package, &self.name)?;
+            }
+        }
+
+        update_workspace_dependencies(&metadata, package, &self.name)?;
+
+        update_lock_file(&metadata)?;
+
+        let manifest_path = package.manifest_path.as_std_path();
+        let metadata = load_metadata(Some(manifest_path))?;
+        let spec = CargoPackageSpec::new(self.name.clone())?;
+        let packages = load_component_metadata(&metadata, std::iter::once(&spec), false, None)?;
+        let cargo_args = CargoArguments::parse()?;
+        let client = config.client(false).await?;
+        generate_bindings(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            &cargo_args,
+            false,
+            None,
+        )
+        .await?;
+
+        config.terminal().status(
+            "Renamed",
+            format!(
+                "package `{old_name}` to `{new_name}` (WIT package `{new}`)",
+                old_name = package.name,
+                new_name = self.name
+            ),
+        )?;
+
+        Ok(())
+    }
+
+    fn validate(&self, metadata: &Metadata, package: &Package) -> Result<()> {
+        let mut chars = self.name.chars();
+        let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            bail!(
+                "package name `{name}` is not a legal crate name",
+                name = self.name
+            );
+        }
+
+        wit_parser::validate_id(&self.name.to_kebab_case()).with_context(|| {
+            format!(
+                "package name `{name}` is not a legal WIT identifier",
+                name = self.name
+            )
+        })?;
+
+        if package.name == self.name {
+            bail!(
+                "package `{name}` is already named `{name}`",
+                name = self.name
+            );
+        }
+
+        if metadata
+            .workspace_packages()
+            .iter()
+            .any(|p| p.name == self.name)
+        {
+            bail!(
+                "a package named `{name}` already exists in the workspace",
+                name = self.name
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<DocumentMut> {
+    let manifest = fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read manifest file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    manifest.parse().with_context(|| {
+        format!(
+            "failed to parse manifest file `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+fn write_manifest(path: &Path, doc: &DocumentMut) -> Result<()> {
+    fs::write(path, doc.to_string()).with_context(|| {
+        format!(
+            "failed to write manifest file `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+fn update_manifest(
+    path: &Path,
+    name: &str,
+    package: &WitPackageName,
+    has_package_setting: bool,
+) -> Result<()> {
+    let mut doc = read_manifest(path)?;
+    doc["package"]["name"] = value(name);
+
+    if has_package_setting {
+        doc["package"]["metadata"]["component"]["package"] = value(package.to_string());
+    }
+
+    write_manifest(path, &doc)
+}
+
+/// Points the path dependencies of a workspace member on the renamed package
+/// at the new name.
+///
+/// The dependency keeps its name in the dependent (via the `package` key)
+/// so that the sources of the dependent continue to compile.
+fn update_dependent(dependent: &Package, package: &Package, name: &str) -> Result<()> {
+    let dir = package.manifest_path.parent().unwrap();
+    let keys: Vec<_> = dependent
+        .dependencies
+        .iter()
+        .filter(|d| d.name == package.name && d.path.as_deref() == Some(dir))
+        .map(|d| d.rename.clone().unwrap_or_else(|| d.name.clone()))
+        .collect();
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let path = dependent.manifest_path.as_std_path();
+    let mut doc = read_manifest(path)?;
+    let mut changed = false;
+
+    for target in doc
+        .get_mut("target")
+        .and_then(Item::as_table_like_mut)
+        .into_iter()
+        .flat_map(|t| t.iter_mut().map(|(_, v)| v))
+    {
+        for table in DEPENDENCY_TABLES {
+            if let Some(table) = target.get_mut(table) {
+                changed |= set_package_key(table, &keys, name);
+            }
+        }
+    }
+
+    for table in DEPENDENCY_TABLES {
+        if let Some(table) = doc.get_mut(table) {
+            changed |= set_package_key(table, &keys, name);
+        }
+    }
+
+    if changed {
+        write_manifest(path, &doc)?;
+    }
+
+    Ok(())
+}
+
+/// Points the `[workspace.dependencies]` entry of the renamed package at the
+/// new name.
+fn update_workspace_dependencies(metadata: &Metadata, package: &Package, name: &str) -> Result<()> {
+    let path = metadata.workspace_root.join("Cargo.toml");
+    let mut doc = read_manifest(path.as_std_path())?;
+    let keys = [package.name.clone()];
+    let changed = match doc
+        .get_mut("workspace")
+        .and_then(|w| w.get_mut("dependencies"))
+    {
+        Some(table) => set_package_key(table, &keys, name),
+        None => false,
+    };
+
+    if changed {
+        write_manifest(path.as_std_path(), &doc)?;
+    }
+
+    Ok(())
+}
+
+/// Sets the `package` key of the path dependencies with the given keys in a
+/// dependency table.
+fn set_package_key(table: &mut Item, keys: &[String], name: &str) -> bool {
+    let mut changed = false;
+    let Some(table) = table.as_table_like_mut() else {
+        return false;
+    };
+
+    for key in keys {
+        let Some(dependency) = table
+            .get_mut(key)
+            .and_then(|d| d.as_table_like_mut())
+            .filter(|d| d.contains_key("path"))
+        else {
+            continue;
+        };
+
+        if key == name {
+            dependency.remove("package");
+        } else {
+            dependency.insert("package", value(name));
+        }
+
+        dependency.fmt();
+        changed = true;
+    }
+
+    changed
+}
+
+/// Updates the workspace packages recorded in the lock file of the workspace.
+fn update_lock_file(metadata: &Metadata) -> Result<()> {
+    if !metadata.workspace_root.join("Cargo.lock").is_file() {
+        return Ok(());
+    }
+
+    let cargo = std::env::var("CARGO")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("cargo"));
+    let mut cmd = Command::new(&cargo);
+    cmd.args(["update", "--workspace", "--quiet", "--manifest-path"]);
+    cmd.arg(metadata.workspace_root.join("Cargo.toml"));
+
+    log::debug!("spawning command {cmd:?}");
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to spawn `{cargo}`", cargo = cargo.display()))?;
+    if !status.success() {
+        bail!("failed to update the lock file of the workspace ({status})");
+    }
+
+    Ok(())
+}
+
+/// Gets the WIT files of a local target, excluding its dependencies.
+fn wit_sources(path: &Path) -> Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut sources = Vec::new();
+    for entry in fs::read_dir(path)
+        .with_context(|| format!("failed to read directory `{path}`", path = path.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() && path.extension().is_some_and(|e| e == "wit") {
+            sources.push(path);
+        }
+    }
+
+    sources.sort();
+    Ok(sources)
+}
+
+fn rename_in_file(path: &Path, rename: impl FnOnce(&str) -> Option<String>) -> Result<()> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+
+    if let Some(renamed) = rename(&source) {
+        fs::write(path, renamed)
+            .with_context(|| format!("failed to write `{path}`", path = path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Renames the references to a WIT package in a WIT source.
+///
+/// This covers both the package declaration (e.g. `package ns:name;`) and
+/// qualified references to its interfaces (e.g. `use ns:name/iface.{ty}`).
+///
+/// Returns `None` if the source does not reference the package.
+fn rename_wit_package(source: &str, old: &WitPackageName, new: &WitPackageName) -> Option<String> {
+    let (old, new) = (old.to_string(), new.to_string());
+    let is_id_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == ':' || c == '%';
+
+    let mut renamed = String::with_capacity(source.len());
+    let mut rest = source;
+    let mut changed = false;
+    while let Some(index) = rest.find(&old) {
+        let (before, after) = (&rest[..index], &rest[index + old.len()..]);
+        let boundary = !before.ends_with(is_id_char) && !after.starts_with(is_id_char);
+
+        renamed.push_str(before);
+        renamed.push_str(if boundary { &new } else { &old });
+        changed |= boundary;
+        rest = after;
+    }
+
+    renamed.push_str(rest);
+    changed.then_some(renamed)
+}
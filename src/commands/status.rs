@@ -0,0 +1,337 @@
+use std::{env, path::PathBuf};
+
+use anyhow::{Context, Result};
+use cargo_component_core::{
+    command::CommonOptions,
+    lock::{LockFile, LockFileResolver},
+    registry::{Dependency, DEFAULT_REGISTRY_NAME},
+    terminal::Terminal,
+};
+use cargo_metadata::{CrateType, Package};
+use clap::Args;
+
+use crate::{
+    bindings_path,
+    config::Config,
+    configured_adapter,
+    freshness::{content_hash, HostFileSystem},
+    load_component_metadata, load_metadata,
+    lock::acquire_lock_file_ro,
+    metadata::ComponentMetadata,
+    state::{PackageState, ResolutionState, WorkspaceState},
+    CargoArguments, PackageComponentMetadata,
+};
+
+/// The number of characters of the digests to print.
+const DIGEST_LEN: usize = 12;
+
+/// Print a summary of the state of component packages.
+///
+/// The summary is read from the manifests, the lock file and the state
+/// recorded in `target/component/state.json` by the last build; no registry is
+/// contacted.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct StatusCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Package to print the status of
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<String>,
+
+    /// Print the status of all packages in the workspace
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+
+    /// Check the bindings generated for the release profile
+    #[clap(long = "release", short = 'r')]
+    pub release: bool,
+
+    /// Check the bindings generated for the given profile
+    #[clap(long = "profile", value_name = "PROFILE-NAME")]
+    pub profile: Option<String>,
+}
+
+impl StatusCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing status command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone()).await?;
+
+        let cargo_args = CargoArguments::parse()?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            Some(cargo_args.profile_name()),
+        )?;
+
+        let file_lock = acquire_lock_file_ro(config.terminal(), &metadata)?;
+        let lock_file = file_lock
+            .as_ref()
+            .map(|f| {
+                LockFile::read(f.file()).with_context(|| {
+                    format!(
+                        "failed to read lock file `{path}`",
+                        path = f.path().display()
+                    )
+                })
+            })
+            .transpose()?;
+        drop(file_lock);
+
+        let state = WorkspaceState::read(&HostFileSystem, metadata.target_directory.as_std_path());
+        let status = Status {
+            config: &config,
+            lock_file: lock_file.as_ref().map(LockFileResolver::new),
+            profile: cargo_args.profile_name(),
+            cwd: env::current_dir().context("couldn't get the current directory of the process")?,
+        };
+
+        let terminal = config.terminal();
+        let mut printed = false;
+        for PackageComponentMetadata { package, metadata } in &packages {
+            if !metadata.section_present && metadata.target_path().is_none() {
+                continue;
+            }
+
+            if printed {
+                terminal.write_stdout("\n", None)?;
+            }
+
+            let default = PackageState::default();
+            let package_state = state
+                .packages
+                .get(package.name.as_str())
+                .unwrap_or(&default);
+            status.print_package(terminal, package, metadata, package_state)?;
+            printed = true;
+        }
+
+        if !printed {
+            terminal.write_stdout("no component packages\n", None)?;
+            return Ok(());
+        }
+
+        terminal.write_stdout(
+            format!(
+                "\ndefault registry: {registry}\n",
+                registry = config
+                    .pkg_config()
+                    .default_registry()
+                    .map(ToString::to_string)
+                    .unwrap_or_else(|| "(none)".to_string())
+            ),
+            None,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// The context for printing the status of packages.
+struct Status<'a> {
+    config: &'a Config,
+    lock_file: Option<LockFileResolver<'a>>,
+    profile: &'a str,
+    cwd: PathBuf,
+}
+
+impl Status<'_> {
+    fn print_package(
+        &self,
+        terminal: &Terminal,
+        package: &Package,
+        metadata: &ComponentMetadata,
+        state: &PackageState,
+    ) -> Result<()> {
+        terminal.write_stdout(
+            format!(
+                "{name} v{version} ({path})\n",
+                name = package.name,
+                version = package.version,
+                path = package.manifest_path
+            ),
+            None,
+        )?;
+
+        let world = match &state.bindings {
+            Some(bindings) => bindings.world.clone(),
+            None => "(not resolved)".to_string(),
+        };
+        terminal.write_stdout(format!("  world:    {world}\n"), None)?;
+
+        let bindings_path = bindings_path(metadata);
+        let fresh =
+            state.bindings_fresh(&HostFileSystem, metadata, self.profile, &bindings_path)?;
+        let bindings = if state.bindings.is_none() || !bindings_path.exists() {
+            "not generated"
+        } else if fresh {
+            "up-to-date"
+        } else {
+            "out of date"
+        };
+        terminal.write_stdout(
+            format!(
+                "  bindings: {bindings} ({path})\n",
+                path = bindings_path
+                    .strip_prefix(&self.cwd)
+                    .unwrap_or(&bindings_path)
+                    .display()
+            ),
+            None,
+        )?;
+
+        for command in adapter_kinds(package) {
+            let adapter = match &metadata.section.adapter {
+                Some(path) => format!("`{path}`", path = path.display()),
+                None if command => "built-in command".to_string(),
+                None if metadata.section.proxy => "built-in proxy".to_string(),
+                None => "built-in reactor".to_string(),
+            };
+            let digest = match configured_adapter(metadata, command) {
+                Ok(bytes) => content_hash(&bytes)[..DIGEST_LEN].to_string(),
+                Err(_) => "missing".to_string(),
+            };
+            terminal.write_stdout(format!("  adapter:  {adapter} ({digest})\n"), None)?;
+        }
+
+        let target_dependencies = metadata.section.target.dependencies();
+        let mut dependencies: Vec<_> = target_dependencies
+            .iter()
+            .map(|(name, dependency)| {
+                (
+                    name,
+                    dependency,
+                    state.target_dependencies.get(&name.to_string()),
+                )
+            })
+            .chain(
+                metadata
+                    .section
+                    .dependencies
+                    .iter()
+                    .map(|(name, dependency)| {
+                        (name, dependency, state.dependencies.get(&name.to_string()))
+                    }),
+            )
+            .collect();
+        dependencies.sort_by_key(|(name, ..)| name.to_string());
+
+        if !dependencies.is_empty() {
+            terminal.write_stdout("  dependencies:\n", None)?;
+        }
+
+        for (name, dependency, resolved) in dependencies {
+            let line = match dependency {
+                Dependency::Package(package) => {
+                    let package_name = package.name.as_ref().unwrap_or(name);
+                    let registry = match &package.registry {
+                        Some(registry) => registry.clone(),
+                        None => self
+                            .config
+                            .pkg_config()
+                            .resolve_registry(package_name)
+                            .map(ToString::to_string)
+                            .unwrap_or_else(|| "(no registry)".to_string()),
+                    };
+
+                    let locked = match &self.lock_file {
+                        Some(lock_file) => lock_file.resolve(
+                            package.registry.as_deref().unwrap_or(DEFAULT_REGISTRY_NAME),
+                            package_name,
+                            &package.version,
+                        )?,
+                        None => None,
+                    };
+
+                    let mut line = match locked {
+                        Some(locked) => format!(
+                            "{name} {requirement} => {version} from {registry}",
+                            requirement = package.version,
+                            version = locked.version
+                        ),
+                        None => format!(
+                            "{name} {requirement} => not locked",
+                            requirement = package.version
+                        ),
+                    };
+
+                    if let Some(ResolutionState::Registry { version, .. }) = resolved {
+                        if locked.map(|l| &l.version) != Some(version) {
+                            line.push_str(&format!(" (last built with {version})"));
+                        }
+                    }
+
+                    line
+                }
+                Dependency::Local(path) => format!(
+                    "{name} => {path}",
+                    path = path.strip_prefix(&self.cwd).unwrap_or(path).display()
+                ),
+            };
+
+            terminal.write_stdout(format!("    {line}\n"), None)?;
+        }
+
+        if !state.adapters.is_empty() {
+            terminal.write_stdout("  outputs:\n", None)?;
+        }
+
+        for (path, adapter) in &state.adapters {
+            let status = if !path.exists() {
+                "missing"
+            } else if configured_adapter(metadata, adapter.command)
+                .map(|bytes| content_hash(&bytes) != adapter.digest)
+                .unwrap_or(true)
+            {
+                "adapter changed"
+            } else if !fresh {
+                "stale"
+            } else {
+                "up-to-date"
+            };
+
+            terminal.write_stdout(
+                format!(
+                    "    {path}: {status}\n",
+                    path = path.strip_prefix(&self.cwd).unwrap_or(path).display()
+                ),
+                None,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the kinds of components the targets of a package are built as, as
+/// whether they are command components.
+fn adapter_kinds(package: &Package) -> Vec<bool> {
+    let mut kinds = Vec::new();
+    if package
+        .targets
+        .iter()
+        .any(|t| t.crate_types.iter().any(|ty| *ty != CrateType::Bin))
+    {
+        kinds.push(false);
+    }
+
+    if package
+        .targets
+        .iter()
+        .any(|t| t.crate_types.contains(&CrateType::Bin))
+    {
+        kinds.push(true);
+    }
+
+    kinds
+}
@@ -38,12 +38,14 @@ impl UpdateCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
         log::debug!("executing update command");
-        let config = Config::new(self.common.new_terminal(), self.common.config).await?;
+        let config = Config::new(self.common.new_terminal(), self.common.config)
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
         let metadata = load_metadata(self.manifest_path.as_deref())?;
-        let packages = load_component_metadata(&metadata, [].iter(), true)?;
+        let packages = load_component_metadata(&metadata, [].iter(), true, None)?;
 
         let lock_update_allowed = !self.frozen && !self.locked;
-        let client = config.client(self.common.cache_dir, false).await?;
+        let client = config.client(false).await?;
         crate::update_lockfile(
             client,
             &config,
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use wasm_pkg_client::{oci::OciRegistryConfig, warg::WargRegistryConfig, Registry};
+
+use crate::config::Config;
+
+/// Show the credentials stored for a registry
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct WhoamiCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// The registry to query (defaults to the default registry).
+    #[clap(long = "registry", value_name = "REGISTRY")]
+    pub registry: Option<Registry>,
+}
+
+impl WhoamiCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing whoami command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config).await?;
+        let registry = config.registry_or_default(self.registry.as_ref())?;
+
+        let mut identities = Vec::new();
+        if let Some(reg_config) = config.pkg_config().registry_config(&registry) {
+            for backend in reg_config.configured_backend_types() {
+                match backend {
+                    "warg" => {
+                        let warg_conf = WargRegistryConfig::try_from(reg_config)?;
+                        if warg_conf.auth_token.is_some() {
+                            identities.push("auth token".to_string());
+                        }
+
+                        if let Some(key) = &warg_conf.signing_key {
+                            identities.push(format!(
+                                "signing key `{id}`",
+                                id = key.public_key().fingerprint()
+                            ));
+                        }
+                    }
+                    "oci" => {
+                        if let Some(credentials) =
+                            OciRegistryConfig::try_from(reg_config)?.credentials
+                        {
+                            identities.push(format!(
+                                "user `{username}`",
+                                username = credentials.username
+                            ));
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        if identities.is_empty() {
+            config
+                .terminal()
+                .warn(format!("not logged in to registry `{registry}`"))?;
+        } else {
+            for identity in identities {
+                config
+                    .terminal()
+                    .write_stdout(format!("{registry}: {identity}\n"), None)?;
+            }
+        }
+
+        Ok(())
+    }
+}
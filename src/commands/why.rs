@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use cargo_component_core::command::CommonOptions;
+use clap::Args;
+use wasm_pkg_client::PackageRef;
+
+use crate::{
+    config::Config, for_each_target_world, load_component_metadata, load_metadata, why::Why,
+    CargoArguments,
+};
+
+/// Explain why a package is in the dependency resolution of a component.
+///
+/// Like `cargo tree -i`, the package is printed as the root of an inverted
+/// tree of the manifest entries, target world items and packages that need
+/// it.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct WhyCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+
+    /// Path to Cargo.toml
+    #[clap(long = "manifest-path", value_name = "PATH")]
+    pub manifest_path: Option<PathBuf>,
+
+    /// Package to explain the resolution of
+    #[clap(long = "package", short = 'p', value_name = "SPEC")]
+    pub packages: Vec<String>,
+
+    /// Explain the resolutions of all packages in the workspace
+    #[clap(long = "workspace")]
+    pub workspace: bool,
+
+    /// The name of the dependency to explain (e.g. `wasi:io`)
+    #[clap(value_name = "DEPENDENCY")]
+    pub dependency: PackageRef,
+}
+
+impl WhyCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        log::debug!("executing why command");
+
+        let config = Config::new(self.common.new_terminal(), self.common.config.clone())
+            .await?
+            .with_cache_dir(self.common.cache_dir.clone());
+        let client = config.client(false).await?;
+
+        let cargo_args = CargoArguments::parse()?;
+        let metadata = load_metadata(self.manifest_path.as_deref())?;
+        let packages = load_component_metadata(
+            &metadata,
+            cargo_args.packages.iter(),
+            cargo_args.workspace,
+            None,
+        )?;
+
+        let mut trees = Vec::new();
+        for_each_target_world(
+            client,
+            &config,
+            &metadata,
+            &packages,
+            |package, resolution, resolve, world| {
+                if let Some(why) =
+                    Why::new(&package.name, &self.dependency, resolution, resolve, world)
+                {
+                    trees.push(why.render());
+                }
+                Ok(())
+            },
+        )
+        .await?;
+
+        if trees.is_empty() {
+            bail!(
+                "package `{name}` is not in the dependency resolution of any selected component \
+                 package",
+                name = self.dependency
+            );
+        }
+
+        config.terminal().write_stdout(trees.join("\n"), None)?;
+
+        Ok(())
+    }
+}
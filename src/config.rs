@@ -20,17 +20,24 @@
 
 use anyhow::{anyhow, bail, Context, Result};
 use cargo_component_core::cache_dir;
-use cargo_component_core::terminal::{Color, Terminal};
-use cargo_metadata::Metadata;
+use cargo_component_core::command::USER_CONFIG_FILE_ENV_VAR;
+use cargo_component_core::terminal::{Color, OutputFormat, Terminal};
+use cargo_metadata::{Metadata, Package, Target};
 use parse_arg::{iter_short, match_arg};
-use semver::Version;
+use semver::{Version, VersionReq};
 use std::fmt;
 use std::str::FromStr;
 use std::sync::Arc;
-use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
-use toml_edit::DocumentMut;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+use toml_edit::{DocumentMut, Table};
 use wasm_pkg_client::caching::{CachingClient, FileCache};
-use wasm_pkg_client::Client;
+use wasm_pkg_client::{Client, Registry};
+
+use crate::events::{BuildEvent, BuildEvents, EventSink};
 
 /// Represents a cargo package specifier.
 ///
@@ -360,6 +367,37 @@ impl Args {
     }
 }
 
+/// An output written for each componentized module, selected with `--emit`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Emit {
+    /// The component, which replaces the module built by cargo.
+    ///
+    /// The component is always written.
+    Component,
+    /// The core module built by cargo, written as `<name>.core.wasm`.
+    CoreModule,
+    /// The adapter modules as embedded in the component, after they were
+    /// trimmed to the imports of the module, written as
+    /// `<name>.<adapter>.wasm`.
+    AdapterModule,
+}
+
+impl FromStr for Emit {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "component" => Ok(Self::Component),
+            "core-module" => Ok(Self::CoreModule),
+            "adapter-module" => Ok(Self::AdapterModule),
+            _ => bail!(
+                "argument for --emit must be component, core-module or adapter-module, but found \
+                 `{value}`"
+            ),
+        }
+    }
+}
+
 /// Represents known cargo arguments.
 ///
 /// This is a subset of the arguments that cargo supports that
@@ -386,12 +424,74 @@ pub struct CargoArguments {
     pub locked: bool,
     /// The --release argument.
     pub release: bool,
+    /// The --profile argument.
+    pub profile: Option<String>,
     /// The --offline argument.
     pub offline: bool,
+    /// The --config argument.
+    pub configs: Vec<String>,
+    /// The -Z argument.
+    pub unstable_flags: Vec<String>,
     /// The --workspace argument.
     pub workspace: bool,
     /// The --package argument.
     pub packages: Vec<CargoPackageSpec>,
+    /// The --bin argument.
+    pub bins: Vec<String>,
+    /// The --example argument.
+    pub examples: Vec<String>,
+    /// The --test argument.
+    pub tests: Vec<String>,
+    /// The --bench argument.
+    pub benches: Vec<String>,
+    /// The --lib argument.
+    pub lib: bool,
+    /// The --bins argument.
+    pub all_bins: bool,
+    /// The --examples argument.
+    pub all_examples: bool,
+    /// The --tests argument.
+    pub all_tests: bool,
+    /// The --benches argument.
+    pub all_benches: bool,
+    /// The --all-targets argument.
+    pub all_targets: bool,
+    /// The --features argument.
+    pub features: Vec<String>,
+    /// The --all-features argument.
+    pub all_features: bool,
+    /// The --no-default-features argument.
+    pub no_default_features: bool,
+    /// The --doc argument.
+    pub doc: bool,
+    /// The --open argument.
+    ///
+    /// For `cargo component doc`, this is handled by `cargo-component` and is not passed to cargo.
+    pub open: bool,
+    /// The --test-jobs argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub test_jobs: Option<usize>,
+    /// The --stats argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub stats: bool,
+    /// The --no-producers argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub no_producers: bool,
+    /// The --stub-missing-imports argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub stub_missing_imports: bool,
+    /// The --emit argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub emit: Vec<Emit>,
+    /// The --output-format argument.
+    ///
+    /// This argument is specific to `cargo-component` and is not passed to cargo.
+    pub output_format: Option<OutputFormat>,
 }
 
 impl CargoArguments {
@@ -405,6 +505,89 @@ impl CargoArguments {
         !self.frozen && !self.locked
     }
 
+    /// Gets the name of the cargo profile selected by the arguments.
+    pub fn profile_name(&self) -> &str {
+        match &self.profile {
+            Some(profile) => profile,
+            None if self.release => "release",
+            None => "dev",
+        }
+    }
+
+    /// Gets the name of the directory cargo writes the artifacts of the
+    /// selected profile to.
+    pub fn profile_dir(&self) -> &str {
+        match self.profile_name() {
+            "dev" | "test" => "debug",
+            "bench" => "release",
+            profile => profile,
+        }
+    }
+
+    /// Determines if specific targets were selected with `--bin` or `--example`.
+    pub fn has_target_selection(&self) -> bool {
+        !self.bins.is_empty() || !self.examples.is_empty()
+    }
+
+    /// Determines if the given target was selected with `--bin` or `--example`.
+    pub fn selects(&self, target: &Target) -> bool {
+        (target.is_bin() && self.bins.contains(&target.name))
+            || (target.is_example() && self.examples.contains(&target.name))
+    }
+
+    /// Determines if cargo runs the doctests of the selected packages.
+    ///
+    /// Doctests are run with `--doc` or when no targets are selected.
+    pub fn runs_doctests(&self) -> bool {
+        self.doc
+            || !(self.lib
+                || self.all_bins
+                || self.all_examples
+                || self.all_tests
+                || self.all_benches
+                || self.all_targets
+                || self.has_target_selection()
+                || !self.tests.is_empty()
+                || !self.benches.is_empty())
+    }
+
+    /// Gets the cargo features of the given package enabled by the arguments.
+    ///
+    /// Features enabled by other enabled features are included.
+    pub fn enabled_features(&self, package: &Package) -> HashSet<String> {
+        let mut pending: Vec<&str> = if self.all_features {
+            package.features.keys().map(String::as_str).collect()
+        } else {
+            self.features
+                .iter()
+                .flat_map(|f| f.split([',', ' ']))
+                .filter(|f| !f.is_empty())
+                .filter_map(|f| match f.split_once('/') {
+                    Some((name, f)) if name == package.name => Some(f),
+                    Some(_) => None,
+                    None => Some(f),
+                })
+                .collect()
+        };
+
+        if !self.no_default_features {
+            pending.push("default");
+        }
+
+        let mut enabled = HashSet::new();
+        while let Some(feature) = pending.pop() {
+            let Some(implied) = package.features.get(feature) else {
+                continue;
+            };
+
+            if enabled.insert(feature.to_string()) {
+                pending.extend(implied.iter().map(String::as_str));
+            }
+        }
+
+        enabled
+    }
+
     /// Parses the arguments from the environment.
     pub fn parse() -> Result<Self> {
         Self::parse_from(std::env::args().skip(1))
@@ -419,14 +602,38 @@ impl CargoArguments {
             .single("--color", "WHEN", Some('c'))
             .single("--manifest-path", "PATH", None)
             .single("--message-format", "FMT", None)
+            .single("--profile", "PROFILE-NAME", None)
+            .single("--test-jobs", "N", None)
+            .single("--output-format", "FMT", None)
             .multiple("--package", "SPEC", Some('p'))
+            .multiple("--emit", "KINDS", None)
             .multiple("--target", "TRIPLE", None)
+            .multiple("--bin", "NAME", None)
+            .multiple("--example", "NAME", None)
+            .multiple("--test", "NAME", None)
+            .multiple("--bench", "NAME", None)
+            .multiple("--features", "FEATURES", Some('F'))
+            .multiple("--config", "KEY=VALUE|PATH", None)
+            .multiple("-Z", "FLAG", Some('Z'))
             .flag("--release", Some('r'))
             .flag("--frozen", None)
             .flag("--locked", None)
             .flag("--offline", None)
+            .flag("--all-features", None)
+            .flag("--no-default-features", None)
             .flag("--all", None)
             .flag("--workspace", None)
+            .flag("--lib", None)
+            .flag("--bins", None)
+            .flag("--examples", None)
+            .flag("--tests", None)
+            .flag("--benches", None)
+            .flag("--all-targets", None)
+            .flag("--doc", None)
+            .flag("--open", None)
+            .flag("--stats", None)
+            .flag("--no-producers", None)
+            .flag("--stub-missing-imports", None)
             .counting("--verbose", Some('v'))
             .flag("--quiet", Some('q'))
             .flag("--help", Some('h'));
@@ -472,7 +679,10 @@ impl CargoArguments {
             frozen: args.get("--frozen").unwrap().count() > 0,
             locked: args.get("--locked").unwrap().count() > 0,
             offline: args.get("--offline").unwrap().count() > 0,
+            configs: args.get_mut("--config").unwrap().take_multiple(),
+            unstable_flags: args.get_mut("-Z").unwrap().take_multiple(),
             release: args.get("--release").unwrap().count() > 0,
+            profile: args.get_mut("--profile").unwrap().take_single(),
             workspace: args.get("--workspace").unwrap().count() > 0
                 || args.get("--all").unwrap().count() > 0,
             packages: args
@@ -482,6 +692,57 @@ impl CargoArguments {
                 .into_iter()
                 .map(CargoPackageSpec::new)
                 .collect::<Result<_>>()?,
+            bins: args.get_mut("--bin").unwrap().take_multiple(),
+            examples: args.get_mut("--example").unwrap().take_multiple(),
+            tests: args.get_mut("--test").unwrap().take_multiple(),
+            benches: args.get_mut("--bench").unwrap().take_multiple(),
+            lib: args.get("--lib").unwrap().count() > 0,
+            all_bins: args.get("--bins").unwrap().count() > 0,
+            all_examples: args.get("--examples").unwrap().count() > 0,
+            all_tests: args.get("--tests").unwrap().count() > 0,
+            all_benches: args.get("--benches").unwrap().count() > 0,
+            all_targets: args.get("--all-targets").unwrap().count() > 0,
+            features: args.get_mut("--features").unwrap().take_multiple(),
+            all_features: args.get("--all-features").unwrap().count() > 0,
+            no_default_features: args.get("--no-default-features").unwrap().count() > 0,
+            doc: args.get("--doc").unwrap().count() > 0,
+            open: args.get("--open").unwrap().count() > 0,
+            test_jobs: args
+                .get_mut("--test-jobs")
+                .unwrap()
+                .take_single()
+                .map(|v| match v.parse() {
+                    Ok(0) | Err(_) => {
+                        bail!("invalid value `{v}` for '--test-jobs <N>': expected a positive integer")
+                    }
+                    Ok(n) => Ok(n),
+                })
+                .transpose()?,
+            stats: args.get("--stats").unwrap().count() > 0,
+            no_producers: args.get("--no-producers").unwrap().count() > 0,
+            stub_missing_imports: args.get("--stub-missing-imports").unwrap().count() > 0,
+            emit: {
+                let mut emit = Vec::new();
+                for kind in args
+                    .get_mut("--emit")
+                    .unwrap()
+                    .take_multiple()
+                    .iter()
+                    .flat_map(|v| v.split(','))
+                {
+                    let kind = kind.trim().parse()?;
+                    if !emit.contains(&kind) {
+                        emit.push(kind);
+                    }
+                }
+                emit
+            },
+            output_format: args
+                .get_mut("--output-format")
+                .unwrap()
+                .take_single()
+                .map(|v| v.parse())
+                .transpose()?,
         })
     }
 }
@@ -495,6 +756,10 @@ pub struct Config {
     pub pkg_config: wasm_pkg_client::Config,
     /// The terminal to use.
     terminal: Terminal,
+    /// The receiver of the events emitted by commands.
+    events: EventSink,
+    /// The cache directory to use, if one was given.
+    cache_dir: Option<PathBuf>,
 }
 
 impl Config {
@@ -507,9 +772,53 @@ impl Config {
         Ok(Self {
             pkg_config,
             terminal,
+            events: EventSink::default(),
+            cache_dir: None,
         })
     }
 
+    /// Create a new `Config` with the given terminal for a command that
+    /// creates the given configuration file.
+    ///
+    /// If the given configuration file does not exist yet, an empty
+    /// configuration is used.
+    pub async fn new_or_empty(terminal: Terminal, config_path: Option<PathBuf>) -> Result<Self> {
+        match config_path {
+            Some(path) if !path.exists() => Ok(Self {
+                pkg_config: wasm_pkg_client::Config::empty(),
+                terminal,
+                events: EventSink::default(),
+                cache_dir: None,
+            }),
+            config_path => Self::new(terminal, config_path).await,
+        }
+    }
+
+    /// Sets the receiver of the events emitted while running commands with
+    /// this configuration.
+    pub fn with_events(mut self, events: impl BuildEvents + 'static) -> Self {
+        self.events = EventSink::new(events);
+        self
+    }
+
+    /// Sets the cache directory to use.
+    ///
+    /// If not set, the default cache directory is used.
+    pub fn with_cache_dir(mut self, cache_dir: Option<PathBuf>) -> Self {
+        self.cache_dir = cache_dir;
+        self
+    }
+
+    /// Gets the path to the cache directory.
+    pub fn cache_dir(&self) -> Result<PathBuf> {
+        cache_dir(self.cache_dir.clone())
+    }
+
+    /// Emits an event to the registered receiver, if any.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> BuildEvent) {
+        self.events.emit(event)
+    }
+
     /// Gets the package configuration.
     pub fn pkg_config(&self) -> &wasm_pkg_client::Config {
         &self.pkg_config
@@ -520,19 +829,245 @@ impl Config {
         &self.terminal
     }
 
+    /// Gets the given registry or the default registry if one is not given.
+    pub fn registry_or_default(&self, registry: Option<&Registry>) -> Result<Registry> {
+        registry
+            .or_else(|| self.pkg_config.default_registry())
+            .cloned()
+            .context("no registry was specified and no default registry is configured; use the `--registry` option to specify one")
+    }
+
+    /// Updates the package configuration file with the given function.
+    ///
+    /// The file at `config_path` is updated if given; otherwise the global
+    /// configuration file is updated. Only the settings from the file are
+    /// written back so that built-in defaults are not persisted.
+    ///
+    /// Returns the path to the updated file.
+    pub async fn update_pkg_config_file(
+        config_path: Option<PathBuf>,
+        update: impl FnOnce(&mut wasm_pkg_client::Config) -> Result<()>,
+    ) -> Result<PathBuf> {
+        let path = match config_path {
+            Some(path) => path,
+            None => wasm_pkg_client::Config::global_config_path()
+                .context("failed to determine the path to the global configuration file")?,
+        };
+
+        let mut pkg_config = if path.exists() {
+            wasm_pkg_client::Config::from_file(&path).await?
+        } else {
+            wasm_pkg_client::Config::empty()
+        };
+
+        update(&mut pkg_config)?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!(
+                    "failed to create directory `{parent}`",
+                    parent = parent.display()
+                )
+            })?;
+        }
+
+        // The file may hold registry credentials, so only its owner may read it
+        restrict_permissions(&path).with_context(|| {
+            format!(
+                "failed to restrict the permissions of configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        pkg_config.to_file(&path).await.with_context(|| {
+            format!(
+                "failed to write configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        Ok(path)
+    }
+
     /// Creates a [`Client`] from this configuration.
-    pub async fn client(
-        &self,
-        cache: Option<PathBuf>,
-        offline: bool,
-    ) -> anyhow::Result<Arc<CachingClient<FileCache>>> {
+    pub async fn client(&self, offline: bool) -> anyhow::Result<Arc<CachingClient<FileCache>>> {
         Ok(Arc::new(CachingClient::new(
             (!offline).then(|| Client::new(self.pkg_config.clone())),
-            FileCache::new(cache_dir(cache)?).await?,
+            FileCache::new(self.cache_dir()?).await?,
         )))
     }
 }
 
+/// Gets the path to the cargo-component user configuration file.
+///
+/// The path is taken from the `CARGO_COMPONENT_USER_CONFIG_FILE` environment
+/// variable, defaulting to `cargo-component/config.toml` in the configuration
+/// directory of the user.
+pub fn user_config_path() -> Option<PathBuf> {
+    match std::env::var_os(USER_CONFIG_FILE_ENV_VAR) {
+        Some(path) => Some(path.into()),
+        None => dirs::config_dir().map(|dir| dir.join("cargo-component").join("config.toml")),
+    }
+}
+
+/// The namespace of component packages if none is configured.
+pub const DEFAULT_NAMESPACE: &str = "component";
+
+/// Gets the default namespace of component packages.
+///
+/// The namespace is read from the `namespace` setting of the cargo-component
+/// user configuration file, defaulting to `component`. It is used for the WIT
+/// packages of new packages and of packages without a target world, and for
+/// the names packages are published as.
+pub fn default_namespace() -> Result<String> {
+    let path = match user_config_path() {
+        Some(path) if path.is_file() => path,
+        _ => return Ok(DEFAULT_NAMESPACE.to_string()),
+    };
+
+    let contents = std::fs::read_to_string(&path).with_context(|| {
+        format!(
+            "failed to read configuration file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    namespace_from_toml(&contents).with_context(|| {
+        format!(
+            "failed to parse configuration file `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+fn namespace_from_toml(contents: &str) -> Result<String> {
+    let doc: DocumentMut = contents.parse()?;
+    let Some(item) = doc.get("namespace") else {
+        return Ok(DEFAULT_NAMESPACE.to_string());
+    };
+
+    let namespace = item
+        .as_str()
+        .context("the `namespace` setting must be a string")?;
+    wit_parser::validate_id(namespace)
+        .with_context(|| format!("namespace `{namespace}` is not a legal WIT identifier"))?;
+    Ok(namespace.to_string())
+}
+
+/// The settings `cargo component new` applies to new packages.
+///
+/// The settings are read from the `[new]` table of the cargo-component user
+/// configuration file so that the packages of an organization can be
+/// scaffolded the same way.
+#[derive(Debug, Default)]
+pub struct NewSettings {
+    /// The version requirement of the `wit-bindgen-rt` dependency.
+    pub wit_bindgen_rt: Option<String>,
+    /// The `[profile.release]` settings of the manifest.
+    pub release_profile: Option<Table>,
+}
+
+impl NewSettings {
+    /// Loads the settings from the user configuration file.
+    ///
+    /// Default settings are used if the file does not exist.
+    pub fn load() -> Result<Self> {
+        match user_config_path() {
+            Some(path) if path.is_file() => Self::from_file(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Reads the settings from the given user configuration file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| {
+            format!(
+                "failed to read configuration file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        Self::from_toml(&contents).with_context(|| {
+            format!(
+                "failed to parse configuration file `{path}`",
+                path = path.display()
+            )
+        })
+    }
+
+    fn from_toml(contents: &str) -> Result<Self> {
+        let doc: DocumentMut = contents.parse()?;
+        let mut settings = Self::default();
+        let Some(new) = doc.get("new") else {
+            return Ok(settings);
+        };
+
+        let new = new
+            .as_table_like()
+            .context("the `new` setting must be a table")?;
+        for (key, item) in new.iter() {
+            match key {
+                "wit-bindgen-rt" => {
+                    let version = item
+                        .as_str()
+                        .context("the `new.wit-bindgen-rt` setting must be a string")?;
+                    VersionReq::parse(version).with_context(|| {
+                        format!("invalid `new.wit-bindgen-rt` version requirement `{version}`")
+                    })?;
+                    settings.wit_bindgen_rt = Some(version.to_string());
+                }
+                "profile" => {
+                    let profile = item
+                        .as_table_like()
+                        .context("the `new.profile` setting must be a table")?;
+                    for (name, item) in profile.iter() {
+                        if name != "release" {
+                            bail!("unsupported profile `new.profile.{name}`; only the release profile can be configured");
+                        }
+
+                        let mut release = Table::new();
+                        for (key, value) in item
+                            .as_table_like()
+                            .context("the `new.profile.release` setting must be a table")?
+                            .iter()
+                        {
+                            release.insert(key, value.clone());
+                        }
+                        settings.release_profile = Some(release);
+                    }
+                }
+                _ => bail!("unknown setting `new.{key}`"),
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Creates the given file if it does not exist and makes it readable and
+/// writable only by its owner.
+#[cfg(unix)]
+fn restrict_permissions(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o600)
+        .open(path)?;
+    file.set_permissions(std::fs::Permissions::from_mode(0o600))
+}
+
+/// Creates the given file if it does not exist.
+#[cfg(not(unix))]
+fn restrict_permissions(path: &Path) -> std::io::Result<()> {
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(drop)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -805,11 +1340,35 @@ mod test {
                 manifest_path: None,
                 message_format: None,
                 release: false,
+                profile: None,
                 frozen: false,
                 locked: false,
                 offline: false,
+                configs: Vec::new(),
+                unstable_flags: Vec::new(),
                 workspace: true,
                 packages: Vec::new(),
+                bins: Vec::new(),
+                examples: Vec::new(),
+                tests: Vec::new(),
+                benches: Vec::new(),
+                lib: false,
+                all_bins: false,
+                all_examples: false,
+                all_tests: false,
+                all_benches: false,
+                all_targets: false,
+                features: Vec::new(),
+                all_features: false,
+                no_default_features: false,
+                doc: false,
+                open: false,
+                test_jobs: None,
+                stats: false,
+                no_producers: false,
+                stub_missing_imports: false,
+                emit: Vec::new(),
+                output_format: None,
             }
         );
 
@@ -825,17 +1384,55 @@ mod test {
                 "--message-format",
                 "json-render-diagnostics",
                 "--release",
+                "--profile=custom",
                 "--package",
                 "package1",
                 "-p=package2@1.1.1",
                 "--target=foo",
                 "--target",
                 "bar",
+                "--bin",
+                "bin1",
+                "--bin=bin2",
+                "--example",
+                "example1",
+                "--test",
+                "test1",
+                "--bench=bench1",
+                "--lib",
+                "--bins",
+                "--examples",
+                "--tests",
+                "--benches",
+                "--all-targets",
+                "--features",
+                "foo,bar baz",
+                "-F=qux",
+                "--all-features",
+                "--no-default-features",
                 "--quiet",
                 "--frozen",
                 "--locked",
                 "--offline",
+                "--config",
+                "net.retry=5",
+                "--config=build.jobs=2",
+                "-Zbuild-std",
+                "-Z",
+                "unstable-options",
                 "--all",
+                "--doc",
+                "--open",
+                "--test-jobs=4",
+                "--stats",
+                "--no-producers",
+                "--stub-missing-imports",
+                "--emit",
+                "core-module,component",
+                "--emit=core-module",
+                "--emit=adapter-module",
+                "--output-format",
+                "github",
                 "--not-an-option",
             ]
             .into_iter(),
@@ -852,9 +1449,12 @@ mod test {
                 manifest_path: Some("Cargo.toml".into()),
                 message_format: Some("json-render-diagnostics".into()),
                 release: true,
+                profile: Some("custom".to_string()),
                 frozen: true,
                 locked: true,
                 offline: true,
+                configs: vec!["net.retry=5".to_string(), "build.jobs=2".to_string()],
+                unstable_flags: vec!["build-std".to_string(), "unstable-options".to_string()],
                 workspace: true,
                 packages: vec![
                     CargoPackageSpec {
@@ -866,7 +1466,131 @@ mod test {
                         version: Some(Version::parse("1.1.1").unwrap())
                     }
                 ],
+                bins: vec!["bin1".to_string(), "bin2".to_string()],
+                examples: vec!["example1".to_string()],
+                tests: vec!["test1".to_string()],
+                benches: vec!["bench1".to_string()],
+                lib: true,
+                all_bins: true,
+                all_examples: true,
+                all_tests: true,
+                all_benches: true,
+                all_targets: true,
+                features: vec!["foo,bar baz".to_string(), "qux".to_string()],
+                all_features: true,
+                no_default_features: true,
+                doc: true,
+                open: true,
+                test_jobs: Some(4),
+                stats: true,
+                no_producers: true,
+                stub_missing_imports: true,
+                emit: vec![Emit::CoreModule, Emit::Component, Emit::AdapterModule],
+                output_format: Some(OutputFormat::Github),
             }
         );
     }
+
+    #[test]
+    fn it_determines_if_doctests_are_run() {
+        let runs_doctests = |args: &[&str]| {
+            CargoArguments::parse_from(["component", "test"].iter().chain(args).copied())
+                .unwrap()
+                .runs_doctests()
+        };
+
+        assert!(runs_doctests(&[]));
+        assert!(runs_doctests(&["--doc"]));
+        assert!(runs_doctests(&["--workspace", "--release"]));
+        for args in [
+            &["--lib"][..],
+            &["--bin", "foo"],
+            &["--bins"],
+            &["--example=foo"],
+            &["--test", "foo"],
+            &["--tests"],
+            &["--bench", "foo"],
+            &["--all-targets"],
+        ] {
+            assert!(!runs_doctests(args), "{args:?}");
+        }
+    }
+
+    #[test]
+    fn it_determines_the_profile_dir() {
+        let profile_dir = |args: &[&str]| {
+            CargoArguments::parse_from(["component", "build"].iter().chain(args).copied())
+                .unwrap()
+                .profile_dir()
+                .to_string()
+        };
+
+        assert_eq!(profile_dir(&[]), "debug");
+        assert_eq!(profile_dir(&["--release"]), "release");
+        assert_eq!(profile_dir(&["--profile", "test"]), "debug");
+        assert_eq!(profile_dir(&["--profile", "bench"]), "release");
+        assert_eq!(profile_dir(&["--profile=ci"]), "ci");
+    }
+
+    #[test]
+    fn it_parses_the_default_namespace() -> Result<()> {
+        assert_eq!(namespace_from_toml("")?, DEFAULT_NAMESPACE);
+        assert_eq!(
+            namespace_from_toml("[new]\nwit-bindgen-rt = \"0.36.0\"")?,
+            DEFAULT_NAMESPACE
+        );
+        assert_eq!(namespace_from_toml("namespace = \"my-org\"")?, "my-org");
+        assert_eq!(
+            namespace_from_toml("namespace = 1")
+                .unwrap_err()
+                .to_string(),
+            "the `namespace` setting must be a string"
+        );
+        assert_eq!(
+            namespace_from_toml("namespace = \"My Org\"")
+                .unwrap_err()
+                .to_string(),
+            "namespace `My Org` is not a legal WIT identifier"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_parses_new_settings() -> Result<()> {
+        let settings = NewSettings::from_toml(
+            r#"
+[new]
+wit-bindgen-rt = "0.36.0"
+
+[new.profile.release]
+lto = true
+opt-level = "s"
+strip = true
+"#,
+        )?;
+
+        assert_eq!(settings.wit_bindgen_rt.as_deref(), Some("0.36.0"));
+        let release = settings.release_profile.unwrap();
+        assert_eq!(release.get("lto").and_then(|v| v.as_bool()), Some(true));
+        assert_eq!(release.get("opt-level").and_then(|v| v.as_str()), Some("s"));
+        assert_eq!(release.get("strip").and_then(|v| v.as_bool()), Some(true));
+
+        let settings = NewSettings::from_toml("")?;
+        assert!(settings.wit_bindgen_rt.is_none());
+        assert!(settings.release_profile.is_none());
+
+        assert_eq!(
+            NewSettings::from_toml("[new]\nwit-bindgen = \"1\"")
+                .unwrap_err()
+                .to_string(),
+            "unknown setting `new.wit-bindgen`"
+        );
+        assert_eq!(
+            NewSettings::from_toml("[new.profile.dev]\nopt-level = 1")
+                .unwrap_err()
+                .to_string(),
+            "unsupported profile `new.profile.dev`; only the release profile can be configured"
+        );
+        Ok(())
+    }
 }
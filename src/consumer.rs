@@ -0,0 +1,1395 @@
+//! Module for generating consumer bindings.
+//!
+//! Consumer bindings describe the exports of a component to hosts written
+//! in other languages so that the component may be called without mirroring
+//! its WIT definitions by hand.
+//!
+//! The JavaScript bindings are TypeScript declarations following the
+//! conventions of `jco transpile`; the Python bindings are type stubs
+//! following the conventions of `componentize-py`; the C bindings are a
+//! header of inline functions for instantiating the component and calling
+//! its exports with the Wasmtime C API.
+
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::{bail, Result};
+use heck::{ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
+use indexmap::IndexSet;
+use wit_parser::{
+    Function, FunctionKind, Handle, InterfaceId, Resolve, Results, Type, TypeDefKind, TypeId,
+    TypeOwner, WorldId, WorldItem, WorldKey,
+};
+
+/// The header of each generated consumer bindings file.
+const HEADER: &str = "Generated by `cargo component bindings`. DO NOT EDIT!";
+
+/// Gets the file extension of the consumer bindings of a language.
+pub fn extension(language: &str) -> &'static str {
+    match language {
+        "js" => "d.ts",
+        "python" => "pyi",
+        "c" => "h",
+        _ => unreachable!("unsupported language `{language}`"),
+    }
+}
+
+/// An exported function callable through the C bindings.
+struct CExport<'a> {
+    /// The name of the function's field and wrapper.
+    field: String,
+    /// The name of the exported interface of the function.
+    interface: Option<String>,
+    /// The exported function.
+    func: &'a Function,
+}
+
+/// A generator of consumer bindings for the exports of a world.
+pub struct ConsumerGenerator<'a> {
+    name: &'a str,
+    resolve: &'a Resolve,
+    world: WorldId,
+    /// The exported interfaces and their names.
+    interfaces: Vec<(InterfaceId, String)>,
+    /// The exported world functions.
+    functions: Vec<&'a Function>,
+    /// The named types referenced by the exports.
+    types: IndexSet<TypeId>,
+    /// The namespace names of the interfaces owning referenced types.
+    namespaces: HashMap<InterfaceId, String>,
+}
+
+impl<'a> ConsumerGenerator<'a> {
+    /// Creates a new consumer generator for the exports of the given world.
+    ///
+    /// The name of the component is used to prefix the C bindings.
+    pub fn new(name: &'a str, resolve: &'a Resolve, world: WorldId) -> Self {
+        let mut generator = Self {
+            name,
+            resolve,
+            world,
+            interfaces: Vec::new(),
+            functions: Vec::new(),
+            types: IndexSet::new(),
+            namespaces: HashMap::new(),
+        };
+
+        let world = &resolve.worlds[world];
+        for (key, item) in world.imports.iter().chain(&world.exports) {
+            if let (WorldKey::Name(name), WorldItem::Interface { id, .. }) = (key, item) {
+                generator.namespaces.insert(*id, name.clone());
+            }
+        }
+
+        for (key, item) in &world.exports {
+            match item {
+                WorldItem::Function(func) => {
+                    generator.function_types(func);
+                    generator.functions.push(func);
+                }
+                WorldItem::Interface { id, .. } => {
+                    let iface = &resolve.interfaces[*id];
+                    for ty in iface.types.values() {
+                        generator.visit(&Type::Id(*ty));
+                    }
+
+                    for func in iface.functions.values() {
+                        generator.function_types(func);
+                    }
+
+                    let name = match key {
+                        WorldKey::Name(name) => name.clone(),
+                        WorldKey::Interface(_) => {
+                            iface.name.clone().expect("interface has no name")
+                        }
+                    };
+                    generator.interfaces.push((*id, name));
+                }
+                WorldItem::Type(ty) => generator.visit(&Type::Id(*ty)),
+            }
+        }
+
+        generator
+    }
+
+    /// Generates the consumer bindings for the given language.
+    pub fn generate(&self, language: &str) -> Result<String> {
+        match language {
+            "js" => Ok(self.typescript()),
+            "python" => Ok(self.python()),
+            "c" => Ok(self.c()),
+            _ => bail!("consumer bindings for language `{language}` are not supported"),
+        }
+    }
+
+    fn function_types(&mut self, func: &Function) {
+        for (_, ty) in &func.params {
+            self.visit(ty);
+        }
+
+        for ty in func.results.iter_types() {
+            self.visit(ty);
+        }
+    }
+
+    fn visit(&mut self, ty: &Type) {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return,
+        };
+
+        let def = &self.resolve.types[id];
+        if def.name.is_some() && !self.types.insert(id) {
+            return;
+        }
+
+        match &def.kind {
+            TypeDefKind::Record(r) => {
+                for field in &r.fields {
+                    self.visit(&field.ty);
+                }
+            }
+            TypeDefKind::Tuple(t) => {
+                for ty in &t.types {
+                    self.visit(ty);
+                }
+            }
+            TypeDefKind::Variant(v) => {
+                for case in &v.cases {
+                    if let Some(ty) = &case.ty {
+                        self.visit(ty);
+                    }
+                }
+            }
+            TypeDefKind::Option(ty)
+            | TypeDefKind::List(ty)
+            | TypeDefKind::Type(ty)
+            | TypeDefKind::Future(Some(ty)) => self.visit(ty),
+            TypeDefKind::Result(r) => {
+                if let Some(ty) = &r.ok {
+                    self.visit(ty);
+                }
+
+                if let Some(ty) = &r.err {
+                    self.visit(ty);
+                }
+            }
+            TypeDefKind::Stream(s) => {
+                if let Some(ty) = &s.element {
+                    self.visit(ty);
+                }
+
+                if let Some(ty) = &s.end {
+                    self.visit(ty);
+                }
+            }
+            TypeDefKind::Handle(Handle::Own(r) | Handle::Borrow(r)) => self.visit(&Type::Id(*r)),
+            TypeDefKind::Flags(_)
+            | TypeDefKind::Enum(_)
+            | TypeDefKind::Resource
+            | TypeDefKind::Future(None)
+            | TypeDefKind::Unknown => {}
+        }
+    }
+
+    /// Gets the namespace of the given interface, e.g. `MyPkgIface`.
+    fn namespace(&self, id: InterfaceId) -> String {
+        if let Some(name) = self.namespaces.get(&id) {
+            return name.to_upper_camel_case();
+        }
+
+        let iface = &self.resolve.interfaces[id];
+        let name = iface.name.as_deref().expect("interface has no name");
+        match iface.package {
+            Some(pkg) => {
+                let pkg = &self.resolve.packages[pkg].name;
+                format!("{ns}-{pkg}-{name}", ns = pkg.namespace, pkg = pkg.name)
+                    .to_upper_camel_case()
+            }
+            None => name.to_upper_camel_case(),
+        }
+    }
+
+    /// Gets the interfaces owning the referenced types that are not exported.
+    fn type_owners(&self) -> Vec<InterfaceId> {
+        let mut owners = IndexSet::new();
+        for id in &self.types {
+            if let TypeOwner::Interface(iface) = self.resolve.types[*id].owner {
+                if !self.interfaces.iter().any(|(i, _)| *i == iface) {
+                    owners.insert(iface);
+                }
+            }
+        }
+
+        owners.into_iter().collect()
+    }
+
+    fn owned_types(&self, owner: TypeOwner) -> impl Iterator<Item = TypeId> + '_ {
+        self.types
+            .iter()
+            .copied()
+            .filter(move |id| self.resolve.types[*id].owner == owner)
+    }
+
+    fn resource_functions(&self, resource: TypeId) -> impl Iterator<Item = &'a Function> + '_ {
+        let funcs = match self.resolve.types[resource].owner {
+            TypeOwner::Interface(id) => Some(self.resolve.interfaces[id].functions.values()),
+            _ => None,
+        };
+
+        funcs.into_iter().flatten().filter(move |f| match f.kind {
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                id == resource
+            }
+            FunctionKind::Freestanding => false,
+        })
+    }
+
+    fn typescript(&self) -> String {
+        let mut out = format!("// {HEADER}\n\n");
+        out.push_str(
+            "export type Result<T, E> = { tag: 'ok', val: T } | { tag: 'err', val: E };\n",
+        );
+
+        for id in self.owned_types(TypeOwner::None) {
+            self.ts_typedef(&mut out, id, None, "");
+        }
+
+        for id in self.owned_types(TypeOwner::World(self.world)) {
+            self.ts_typedef(&mut out, id, None, "");
+        }
+
+        for func in &self.functions {
+            out.push('\n');
+            self.ts_docs(&mut out, &func.docs.contents, "");
+            writeln!(
+                out,
+                "export function {name}({params}): {result};",
+                name = ts_ident(&func.name.to_lower_camel_case()),
+                params = self.ts_params(func, None),
+                result = self.ts_result(func, None),
+            )
+            .unwrap();
+        }
+
+        let owners = self.type_owners();
+        let namespaces = self
+            .interfaces
+            .iter()
+            .map(|(id, _)| (*id, true))
+            .chain(owners.into_iter().map(|id| (id, false)));
+
+        for (id, exported) in namespaces {
+            let iface = &self.resolve.interfaces[id];
+            out.push('\n');
+            self.ts_docs(&mut out, &iface.docs.contents, "");
+            writeln!(out, "export namespace {ns} {{", ns = self.namespace(id)).unwrap();
+
+            for ty in self.owned_types(TypeOwner::Interface(id)) {
+                self.ts_typedef(&mut out, ty, Some(id), "  ");
+            }
+
+            if exported {
+                for func in iface.functions.values() {
+                    if func.kind != FunctionKind::Freestanding {
+                        continue;
+                    }
+
+                    self.ts_docs(&mut out, &func.docs.contents, "  ");
+                    writeln!(
+                        out,
+                        "  export function {name}({params}): {result};",
+                        name = ts_ident(&func.name.to_lower_camel_case()),
+                        params = self.ts_params(func, Some(id)),
+                        result = self.ts_result(func, Some(id)),
+                    )
+                    .unwrap();
+                }
+            }
+
+            out.push_str("}\n");
+        }
+
+        if !self.interfaces.is_empty() {
+            out.push('\n');
+        }
+
+        for (id, name) in &self.interfaces {
+            writeln!(
+                out,
+                "export const {name}: typeof {ns};",
+                name = ts_ident(&name.to_lower_camel_case()),
+                ns = self.namespace(*id)
+            )
+            .unwrap();
+        }
+
+        out
+    }
+
+    fn ts_docs(&self, out: &mut String, docs: &Option<String>, indent: &str) {
+        if let Some(docs) = docs {
+            writeln!(out, "{indent}/**").unwrap();
+            for line in docs.lines() {
+                writeln!(out, "{indent} * {line}", line = line.trim_end()).unwrap();
+            }
+            writeln!(out, "{indent} */").unwrap();
+        }
+    }
+
+    fn ts_typedef(&self, out: &mut String, id: TypeId, from: Option<InterfaceId>, indent: &str) {
+        let def = &self.resolve.types[id];
+        let name = def
+            .name
+            .as_deref()
+            .expect("type has no name")
+            .to_upper_camel_case();
+
+        self.ts_docs(out, &def.docs.contents, indent);
+        match &def.kind {
+            TypeDefKind::Record(r) => {
+                writeln!(out, "{indent}export interface {name} {{").unwrap();
+                for field in &r.fields {
+                    self.ts_docs(out, &field.docs.contents, &format!("{indent}  "));
+                    writeln!(
+                        out,
+                        "{indent}  {field}: {ty},",
+                        field = field.name.to_lower_camel_case(),
+                        ty = self.ts_type(&field.ty, from),
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "{indent}}}").unwrap();
+            }
+            TypeDefKind::Flags(f) => {
+                writeln!(out, "{indent}export interface {name} {{").unwrap();
+                for flag in &f.flags {
+                    writeln!(
+                        out,
+                        "{indent}  {flag}?: boolean,",
+                        flag = flag.name.to_lower_camel_case()
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "{indent}}}").unwrap();
+            }
+            TypeDefKind::Enum(e) => {
+                let cases = e
+                    .cases
+                    .iter()
+                    .map(|c| format!("'{name}'", name = c.name))
+                    .collect::<Vec<_>>();
+                writeln!(
+                    out,
+                    "{indent}export type {name} = {cases};",
+                    cases = cases.join(" | ")
+                )
+                .unwrap();
+            }
+            TypeDefKind::Variant(v) => {
+                let cases = v
+                    .cases
+                    .iter()
+                    .map(|c| format!("{name}{case}", case = c.name.to_upper_camel_case()))
+                    .collect::<Vec<_>>();
+                writeln!(
+                    out,
+                    "{indent}export type {name} = {cases};",
+                    cases = cases.join(" | ")
+                )
+                .unwrap();
+
+                for case in &v.cases {
+                    write!(
+                        out,
+                        "{indent}export interface {name}{case} {{\n{indent}  tag: '{tag}',\n",
+                        case = case.name.to_upper_camel_case(),
+                        tag = case.name
+                    )
+                    .unwrap();
+                    if let Some(ty) = &case.ty {
+                        writeln!(out, "{indent}  val: {ty},", ty = self.ts_type(ty, from)).unwrap();
+                    }
+                    writeln!(out, "{indent}}}").unwrap();
+                }
+            }
+            TypeDefKind::Resource => {
+                writeln!(out, "{indent}export class {name} {{").unwrap();
+                for func in self.resource_functions(id) {
+                    self.ts_docs(out, &func.docs.contents, &format!("{indent}  "));
+                    match func.kind {
+                        FunctionKind::Constructor(_) => writeln!(
+                            out,
+                            "{indent}  constructor({params});",
+                            params = self.ts_params(func, from)
+                        ),
+                        FunctionKind::Method(_) => writeln!(
+                            out,
+                            "{indent}  {name}({params}): {result};",
+                            name = ts_ident(&func.item_name().to_lower_camel_case()),
+                            params = self.ts_params(func, from),
+                            result = self.ts_result(func, from),
+                        ),
+                        _ => writeln!(
+                            out,
+                            "{indent}  static {name}({params}): {result};",
+                            name = ts_ident(&func.item_name().to_lower_camel_case()),
+                            params = self.ts_params(func, from),
+                            result = self.ts_result(func, from),
+                        ),
+                    }
+                    .unwrap();
+                }
+                writeln!(out, "{indent}}}").unwrap();
+            }
+            _ => {
+                writeln!(
+                    out,
+                    "{indent}export type {name} = {ty};",
+                    ty = self.ts_anonymous(&def.kind, from)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    fn ts_params(&self, func: &Function, from: Option<InterfaceId>) -> String {
+        func.params
+            .iter()
+            .skip(matches!(func.kind, FunctionKind::Method(_)) as usize)
+            .map(|(name, ty)| {
+                format!(
+                    "{name}: {ty}",
+                    name = ts_ident(&name.to_lower_camel_case()),
+                    ty = self.ts_type(ty, from)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn ts_result(&self, func: &Function, from: Option<InterfaceId>) -> String {
+        match &func.results {
+            Results::Named(params) if params.is_empty() => "void".to_string(),
+            Results::Named(params) => format!(
+                "[{types}]",
+                types = params
+                    .iter()
+                    .map(|(_, ty)| self.ts_type(ty, from))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Results::Anon(ty) => {
+                // Errors of a returned `result` are thrown by the function
+                if let Type::Id(id) = ty {
+                    if let TypeDefKind::Result(r) = &self.resolve.types[*id].kind {
+                        if self.resolve.types[*id].name.is_none() {
+                            return r
+                                .ok
+                                .as_ref()
+                                .map(|ty| self.ts_type(ty, from))
+                                .unwrap_or_else(|| "void".to_string());
+                        }
+                    }
+                }
+
+                self.ts_type(ty, from)
+            }
+        }
+    }
+
+    fn ts_type(&self, ty: &Type, from: Option<InterfaceId>) -> String {
+        match ty {
+            Type::Bool => "boolean".to_string(),
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::S8
+            | Type::S16
+            | Type::S32
+            | Type::F32
+            | Type::F64 => "number".to_string(),
+            Type::U64 | Type::S64 => "bigint".to_string(),
+            Type::Char | Type::String => "string".to_string(),
+            Type::Id(id) => {
+                let def = &self.resolve.types[*id];
+                match &def.name {
+                    Some(name) => {
+                        let name = name.to_upper_camel_case();
+                        match def.owner {
+                            TypeOwner::Interface(owner) if Some(owner) != from => {
+                                format!("{ns}.{name}", ns = self.namespace(owner))
+                            }
+                            _ => name,
+                        }
+                    }
+                    None => self.ts_anonymous(&def.kind, from),
+                }
+            }
+        }
+    }
+
+    fn ts_anonymous(&self, kind: &TypeDefKind, from: Option<InterfaceId>) -> String {
+        match kind {
+            TypeDefKind::Tuple(t) => format!(
+                "[{types}]",
+                types = t
+                    .types
+                    .iter()
+                    .map(|ty| self.ts_type(ty, from))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Option(ty) => format!("{ty} | undefined", ty = self.ts_type(ty, from)),
+            TypeDefKind::Result(r) => format!(
+                "Result<{ok}, {err}>",
+                ok =
+                    r.ok.as_ref()
+                        .map(|ty| self.ts_type(ty, from))
+                        .unwrap_or_else(|| "void".to_string()),
+                err = r
+                    .err
+                    .as_ref()
+                    .map(|ty| self.ts_type(ty, from))
+                    .unwrap_or_else(|| "void".to_string())
+            ),
+            TypeDefKind::List(ty) => match ty {
+                Type::U8 => "Uint8Array".to_string(),
+                Type::U16 => "Uint16Array".to_string(),
+                Type::U32 => "Uint32Array".to_string(),
+                Type::U64 => "BigUint64Array".to_string(),
+                Type::S8 => "Int8Array".to_string(),
+                Type::S16 => "Int16Array".to_string(),
+                Type::S32 => "Int32Array".to_string(),
+                Type::S64 => "BigInt64Array".to_string(),
+                Type::F32 => "Float32Array".to_string(),
+                Type::F64 => "Float64Array".to_string(),
+                ty => format!("Array<{ty}>", ty = self.ts_type(ty, from)),
+            },
+            TypeDefKind::Type(ty) => self.ts_type(ty, from),
+            TypeDefKind::Handle(Handle::Own(r) | Handle::Borrow(r)) => {
+                self.ts_type(&Type::Id(*r), from)
+            }
+            _ => "unknown".to_string(),
+        }
+    }
+
+    fn c(&self) -> String {
+        let prefix = c_ident(self.name);
+        let guard = format!("{prefix}_H").to_uppercase();
+        let exports = self.c_exports();
+
+        let mut out = format!(
+            "\
+// {HEADER}
+
+#ifndef {guard}
+#define {guard}
+
+#include <string.h>
+#include <wasmtime.h>
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+/**
+ * An instance of the `{name}` component.
+ */
+typedef struct {prefix} {{
+  wasmtime_component_instance_t instance;
+",
+            name = self.name
+        );
+
+        for export in &exports {
+            writeln!(
+                out,
+                "  wasmtime_component_func_t {field};",
+                field = export.field
+            )
+            .unwrap();
+        }
+
+        write!(
+            out,
+            "\
+}} {prefix}_t;
+
+/**
+ * Looks up an exported function of an instance of the component.
+ */
+static inline wasmtime_error_t *{prefix}_get_func(
+    wasmtime_component_instance_t *instance, wasmtime_context_t *context,
+    const char *instance_name, const char *name, wasmtime_component_func_t *func) {{
+  wasmtime_component_export_index_t *parent = NULL;
+  if (instance_name != NULL) {{
+    parent = wasmtime_component_instance_get_export_index(
+        instance, context, NULL, instance_name, strlen(instance_name));
+    if (parent == NULL) {{
+      return wasmtime_error_new(\"the component does not export an expected interface\");
+    }}
+  }}
+
+  wasmtime_component_export_index_t *index =
+      wasmtime_component_instance_get_export_index(instance, context, parent,
+                                                   name, strlen(name));
+  if (parent != NULL) {{
+    wasmtime_component_export_index_delete(parent);
+  }}
+
+  if (index == NULL) {{
+    return wasmtime_error_new(\"the component does not export an expected function\");
+  }}
+
+  bool found = wasmtime_component_instance_get_func(instance, context, index, func);
+  wasmtime_component_export_index_delete(index);
+  if (!found) {{
+    return wasmtime_error_new(\"an expected export of the component is not a function\");
+  }}
+
+  return NULL;
+}}
+
+/**
+ * Instantiates the `{name}` component and looks up its exports.
+ *
+ * The linker must define the imports of the component, e.g. with
+ * `wasmtime_component_linker_add_wasip2`.
+ */
+static inline wasmtime_error_t *{prefix}_instantiate(
+    const wasmtime_component_linker_t *linker, wasmtime_context_t *context,
+    const wasmtime_component_t *component, {prefix}_t *out) {{
+  wasmtime_error_t *error = wasmtime_component_linker_instantiate(
+      linker, context, component, &out->instance);
+  if (error != NULL) {{
+    return error;
+  }}
+",
+            name = self.name
+        )
+        .unwrap();
+
+        for export in &exports {
+            let interface = export
+                .interface
+                .as_ref()
+                .map(|i| format!("\"{i}\""))
+                .unwrap_or_else(|| "NULL".to_string());
+            write!(
+                out,
+                "
+  error = {prefix}_get_func(&out->instance, context, {interface}, \"{name}\",
+      &out->{field});
+  if (error != NULL) {{
+    return error;
+  }}
+",
+                name = export.func.name,
+                field = export.field
+            )
+            .unwrap();
+        }
+
+        out.push_str("\n  return NULL;\n}\n");
+
+        for export in &exports {
+            out.push('\n');
+            self.c_function(&mut out, &prefix, export);
+        }
+
+        out.push_str(&format!(
+            "
+#ifdef __cplusplus
+}} // extern \"C\"
+#endif
+
+#endif // {guard}
+"
+        ));
+
+        out
+    }
+
+    /// Gets the exported functions that can be called through the C API.
+    fn c_exports(&self) -> Vec<CExport<'a>> {
+        let mut exports = Vec::new();
+        for (key, item) in &self.resolve.worlds[self.world].exports {
+            match item {
+                WorldItem::Function(func) => exports.push(CExport {
+                    field: c_ident(&func.name),
+                    interface: None,
+                    func,
+                }),
+                WorldItem::Interface { id, .. } => {
+                    let interface = self.resolve.name_world_key(key);
+                    for func in self.resolve.interfaces[*id].functions.values() {
+                        if func.kind != FunctionKind::Freestanding {
+                            continue;
+                        }
+
+                        exports.push(CExport {
+                            field: c_ident(&format!("{interface}-{name}", name = func.name)),
+                            interface: Some(interface.clone()),
+                            func,
+                        });
+                    }
+                }
+                WorldItem::Type(_) => continue,
+            }
+        }
+
+        exports.retain(|export| {
+            let supported = export.func.results.len() <= 1
+                && export
+                    .func
+                    .params
+                    .iter()
+                    .map(|(_, ty)| ty)
+                    .chain(export.func.results.iter_types())
+                    .all(|ty| self.c_supported(ty));
+
+            if !supported {
+                log::debug!(
+                    "skipping C bindings for function `{name}` as its signature is not supported",
+                    name = export.func.name
+                );
+            }
+
+            supported
+        });
+
+        exports
+    }
+
+    fn c_supported(&self, ty: &Type) -> bool {
+        let id = match ty {
+            Type::Id(id) => *id,
+            _ => return true,
+        };
+
+        match &self.resolve.types[id].kind {
+            TypeDefKind::Record(r) => r.fields.iter().all(|f| self.c_supported(&f.ty)),
+            TypeDefKind::Tuple(t) => t.types.iter().all(|ty| self.c_supported(ty)),
+            TypeDefKind::Variant(v) => v
+                .cases
+                .iter()
+                .all(|c| c.ty.as_ref().is_none_or(|ty| self.c_supported(ty))),
+            TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+                self.c_supported(ty)
+            }
+            TypeDefKind::Result(r) => {
+                r.ok.as_ref().is_none_or(|ty| self.c_supported(ty))
+                    && r.err.as_ref().is_none_or(|ty| self.c_supported(ty))
+            }
+            TypeDefKind::Flags(_) | TypeDefKind::Enum(_) => true,
+            TypeDefKind::Resource
+            | TypeDefKind::Handle(_)
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Unknown => false,
+        }
+    }
+
+    /// Gets the C type, value kind, and value field of a primitive type.
+    fn c_primitive(&self, ty: &Type) -> Option<(&'static str, &'static str, &'static str)> {
+        match ty {
+            Type::Bool => Some(("bool", "BOOL", "boolean")),
+            Type::U8 => Some(("uint8_t", "U8", "u8")),
+            Type::U16 => Some(("uint16_t", "U16", "u16")),
+            Type::U32 => Some(("uint32_t", "U32", "u32")),
+            Type::U64 => Some(("uint64_t", "U64", "u64")),
+            Type::S8 => Some(("int8_t", "S8", "s8")),
+            Type::S16 => Some(("int16_t", "S16", "s16")),
+            Type::S32 => Some(("int32_t", "S32", "s32")),
+            Type::S64 => Some(("int64_t", "S64", "s64")),
+            Type::F32 => Some(("float", "F32", "f32")),
+            Type::F64 => Some(("double", "F64", "f64")),
+            Type::Char => Some(("uint32_t", "CHAR", "character")),
+            Type::String => None,
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Type(ty) => self.c_primitive(ty),
+                _ => None,
+            },
+        }
+    }
+
+    fn c_is_string(&self, ty: &Type) -> bool {
+        match ty {
+            Type::String => true,
+            Type::Id(id) => match &self.resolve.types[*id].kind {
+                TypeDefKind::Type(ty) => self.c_is_string(ty),
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn c_function(&self, out: &mut String, prefix: &str, export: &CExport) {
+        let func = export.func;
+        let mut params = vec![
+            format!("{prefix}_t *self"),
+            "wasmtime_context_t *context".to_string(),
+        ];
+        let mut args = String::new();
+        for (i, (name, ty)) in func.params.iter().enumerate() {
+            let name = c_param(name);
+            if let Some((ty, kind, field)) = self.c_primitive(ty) {
+                params.push(format!("{ty} {name}"));
+                write!(
+                    args,
+                    "  args[{i}].kind = WASMTIME_COMPONENT_{kind};\n  args[{i}].of.{field} = {name};\n"
+                )
+                .unwrap();
+            } else if self.c_is_string(ty) {
+                params.push(format!("const char *{name}, size_t {name}_len"));
+                write!(
+                    args,
+                    "  args[{i}].kind = WASMTIME_COMPONENT_STRING;\n  args[{i}].of.string.size = {name}_len;\n  args[{i}].of.string.data = (char *){name};\n"
+                )
+                .unwrap();
+            } else {
+                params.push(format!("const wasmtime_component_val_t *{name}"));
+                writeln!(args, "  args[{i}] = *{name};").unwrap();
+            }
+        }
+
+        let mut ownership = None;
+        let result = func.results.iter_types().next().map(|ty| {
+            if let Some((ty, _, field)) = self.c_primitive(ty) {
+                params.push(format!("{ty} *ret"));
+                format!("  *ret = result.of.{field};\n")
+            } else if self.c_is_string(ty) {
+                params.push("wasm_name_t *ret".to_string());
+                ownership = Some("The returned string must be deleted with `wasm_name_delete`.");
+                "  *ret = result.of.string;\n".to_string()
+            } else {
+                params.push("wasmtime_component_val_t *ret".to_string());
+                ownership = Some(
+                    "The returned value must be deleted with `wasmtime_component_val_delete`.",
+                );
+                "  *ret = result;\n".to_string()
+            }
+        });
+
+        let name = match &export.interface {
+            Some(interface) => format!("{interface}#{name}", name = func.name),
+            None => func.name.clone(),
+        };
+
+        let mut docs = format!(
+            "Calls the `{name}` export of the component.\n\n`{signature}`",
+            signature = self.wit_signature(func)
+        );
+        if let Some(ownership) = ownership {
+            write!(docs, "\n\n{ownership}").unwrap();
+        }
+        if let Some(contents) = &func.docs.contents {
+            write!(docs, "\n\n{contents}").unwrap();
+        }
+
+        out.push_str("/**\n");
+        for line in docs.lines() {
+            writeln!(
+                out,
+                " *{sep}{line}",
+                sep = if line.is_empty() { "" } else { " " }
+            )
+            .unwrap();
+        }
+        out.push_str(" */\n");
+
+        writeln!(
+            out,
+            "static inline wasmtime_error_t *{prefix}_{field}(\n    {params}) {{",
+            field = export.field,
+            params = params.join(", ")
+        )
+        .unwrap();
+
+        let nargs = func.params.len();
+        let (args_ptr, result_ptr, nresults) = (
+            if nargs == 0 { "NULL" } else { "args" },
+            if result.is_some() { "&result" } else { "NULL" },
+            result.is_some() as usize,
+        );
+
+        if nargs > 0 {
+            writeln!(out, "  wasmtime_component_val_t args[{nargs}];").unwrap();
+            out.push_str(&args);
+        }
+
+        if result.is_some() {
+            out.push_str("  wasmtime_component_val_t result;\n");
+        }
+
+        write!(
+            out,
+            "  wasmtime_error_t *error = wasmtime_component_func_call(
+      &self->{field}, context, {args_ptr}, {nargs}, {result_ptr}, {nresults});
+  if (error != NULL) {{
+    return error;
+  }}
+",
+            field = export.field
+        )
+        .unwrap();
+
+        if let Some(result) = result {
+            out.push_str(&result);
+        }
+
+        writeln!(
+            out,
+            "  return wasmtime_component_func_post_return(&self->{field}, context);\n}}",
+            field = export.field
+        )
+        .unwrap();
+    }
+
+    /// Gets the WIT signature of a function for documentation.
+    fn wit_signature(&self, func: &Function) -> String {
+        let mut signature = format!(
+            "{name}: func({params})",
+            name = func.item_name(),
+            params = func
+                .params
+                .iter()
+                .map(|(name, ty)| format!("{name}: {ty}", ty = self.wit_type(ty)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+
+        if let Some(ty) = func.results.iter_types().next() {
+            write!(signature, " -> {ty}", ty = self.wit_type(ty)).unwrap();
+        }
+
+        signature
+    }
+
+    fn wit_type(&self, ty: &Type) -> String {
+        let id = match ty {
+            Type::Bool => return "bool".to_string(),
+            Type::U8 => return "u8".to_string(),
+            Type::U16 => return "u16".to_string(),
+            Type::U32 => return "u32".to_string(),
+            Type::U64 => return "u64".to_string(),
+            Type::S8 => return "s8".to_string(),
+            Type::S16 => return "s16".to_string(),
+            Type::S32 => return "s32".to_string(),
+            Type::S64 => return "s64".to_string(),
+            Type::F32 => return "f32".to_string(),
+            Type::F64 => return "f64".to_string(),
+            Type::Char => return "char".to_string(),
+            Type::String => return "string".to_string(),
+            Type::Id(id) => *id,
+        };
+
+        let def = &self.resolve.types[id];
+        if let Some(name) = &def.name {
+            return name.clone();
+        }
+
+        let optional =
+            |ty: Option<&Type>| ty.map(|ty| self.wit_type(ty)).unwrap_or("_".to_string());
+        match &def.kind {
+            TypeDefKind::Tuple(t) => format!(
+                "tuple<{types}>",
+                types = t
+                    .types
+                    .iter()
+                    .map(|ty| self.wit_type(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Option(ty) => format!("option<{ty}>", ty = self.wit_type(ty)),
+            TypeDefKind::List(ty) => format!("list<{ty}>", ty = self.wit_type(ty)),
+            TypeDefKind::Result(r) => match (&r.ok, &r.err) {
+                (None, None) => "result".to_string(),
+                (ok, err) => format!(
+                    "result<{ok}, {err}>",
+                    ok = optional(ok.as_ref()),
+                    err = optional(err.as_ref())
+                ),
+            },
+            TypeDefKind::Type(ty) => self.wit_type(ty),
+            _ => "_".to_string(),
+        }
+    }
+
+    fn python(&self) -> String {
+        let mut out = format!("# {HEADER}\n\n");
+        out.push_str(
+            "\
+from dataclasses import dataclass
+from enum import Enum, Flag, auto
+from typing import Any, Generic, List, Optional, Protocol, Tuple, TypeVar, Union
+
+T = TypeVar(\"T\")
+E = TypeVar(\"E\")
+
+
+@dataclass
+class Ok(Generic[T]):
+    value: T
+
+
+@dataclass
+class Err(Generic[E]):
+    value: E
+
+
+Result = Union[Ok[T], Err[E]]
+",
+        );
+
+        for id in &self.types {
+            // Types brought into scope with `use` share the module namespace
+            let def = &self.resolve.types[*id];
+            if let TypeDefKind::Type(Type::Id(other)) = def.kind {
+                if self.resolve.types[other].name == def.name {
+                    continue;
+                }
+            }
+
+            out.push_str("\n\n");
+            self.py_typedef(&mut out, *id);
+        }
+
+        for (id, name) in &self.interfaces {
+            let iface = &self.resolve.interfaces[*id];
+            out.push_str("\n\n");
+            writeln!(
+                out,
+                "class {name}(Protocol):",
+                name = name.to_upper_camel_case()
+            )
+            .unwrap();
+            self.py_docs(&mut out, &iface.docs.contents, "    ");
+
+            let mut empty = true;
+            for func in iface.functions.values() {
+                if func.kind != FunctionKind::Freestanding {
+                    continue;
+                }
+
+                self.py_function(&mut out, func, "self", "    ");
+                empty = false;
+            }
+
+            if empty {
+                out.push_str("    ...\n");
+            }
+        }
+
+        if !self.functions.is_empty() || !self.interfaces.is_empty() {
+            out.push_str("\n\nclass Exports(Protocol):\n");
+            for (_, name) in &self.interfaces {
+                writeln!(
+                    out,
+                    "    {field}: {ty}",
+                    field = py_ident(&name.to_snake_case()),
+                    ty = name.to_upper_camel_case()
+                )
+                .unwrap();
+            }
+
+            for func in &self.functions {
+                self.py_function(&mut out, func, "self", "    ");
+            }
+        }
+
+        out
+    }
+
+    fn py_docs(&self, out: &mut String, docs: &Option<String>, indent: &str) {
+        if let Some(docs) = docs {
+            writeln!(out, "{indent}\"\"\"").unwrap();
+            for line in docs.lines() {
+                writeln!(out, "{indent}{line}", line = line.trim_end()).unwrap();
+            }
+            writeln!(out, "{indent}\"\"\"").unwrap();
+        }
+    }
+
+    fn py_function(&self, out: &mut String, func: &Function, receiver: &str, indent: &str) {
+        let mut params = vec![receiver.to_string()];
+        params.extend(
+            func.params
+                .iter()
+                .skip(matches!(func.kind, FunctionKind::Method(_)) as usize)
+                .map(|(name, ty)| {
+                    format!(
+                        "{name}: {ty}",
+                        name = py_ident(&name.to_snake_case()),
+                        ty = self.py_type(ty)
+                    )
+                }),
+        );
+
+        let name = match func.kind {
+            FunctionKind::Constructor(_) => "__init__".to_string(),
+            _ => py_ident(&func.item_name().to_snake_case()),
+        };
+
+        let mut raises = None;
+        let result = match &func.results {
+            _ if matches!(func.kind, FunctionKind::Constructor(_)) => "None".to_string(),
+            Results::Named(params) if params.is_empty() => "None".to_string(),
+            Results::Named(params) => format!(
+                "Tuple[{types}]",
+                types = params
+                    .iter()
+                    .map(|(_, ty)| self.py_type(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Results::Anon(Type::Id(id))
+                if self.resolve.types[*id].name.is_none()
+                    && matches!(self.resolve.types[*id].kind, TypeDefKind::Result(_)) =>
+            {
+                // Errors of a returned `result` are raised as `Err`
+                let TypeDefKind::Result(r) = &self.resolve.types[*id].kind else {
+                    unreachable!()
+                };
+
+                raises = Some(
+                    r.err
+                        .as_ref()
+                        .map(|ty| self.py_type(ty))
+                        .unwrap_or_else(|| "None".to_string()),
+                );
+                r.ok.as_ref()
+                    .map(|ty| self.py_type(ty))
+                    .unwrap_or_else(|| "None".to_string())
+            }
+            Results::Anon(ty) => self.py_type(ty),
+        };
+
+        if matches!(func.kind, FunctionKind::Static(_)) {
+            writeln!(out, "{indent}@staticmethod").unwrap();
+            params.remove(0);
+        }
+
+        writeln!(
+            out,
+            "{indent}def {name}({params}) -> {result}:",
+            params = params.join(", ")
+        )
+        .unwrap();
+
+        let mut docs = func.docs.contents.clone();
+        if let Some(err) = raises {
+            let docs = docs.get_or_insert_with(String::new);
+            if !docs.is_empty() {
+                docs.push_str("\n\n");
+            }
+            write!(docs, "Raises: `Err[{err}]`").unwrap();
+        }
+
+        self.py_docs(out, &docs, &format!("{indent}    "));
+        writeln!(out, "{indent}    ...").unwrap();
+    }
+
+    fn py_typedef(&self, out: &mut String, id: TypeId) {
+        let def = &self.resolve.types[id];
+        let name = def
+            .name
+            .as_deref()
+            .expect("type has no name")
+            .to_upper_camel_case();
+
+        match &def.kind {
+            TypeDefKind::Record(r) => {
+                writeln!(out, "@dataclass\nclass {name}:").unwrap();
+                self.py_docs(out, &def.docs.contents, "    ");
+                for field in &r.fields {
+                    writeln!(
+                        out,
+                        "    {field}: {ty}",
+                        field = py_ident(&field.name.to_snake_case()),
+                        ty = self.py_type(&field.ty)
+                    )
+                    .unwrap();
+                }
+
+                if r.fields.is_empty() {
+                    out.push_str("    pass\n");
+                }
+            }
+            TypeDefKind::Flags(f) => {
+                writeln!(out, "class {name}(Flag):").unwrap();
+                self.py_docs(out, &def.docs.contents, "    ");
+                for flag in &f.flags {
+                    writeln!(
+                        out,
+                        "    {flag} = auto()",
+                        flag = flag.name.to_shouty_snake_case()
+                    )
+                    .unwrap();
+                }
+            }
+            TypeDefKind::Enum(e) => {
+                writeln!(out, "class {name}(Enum):").unwrap();
+                self.py_docs(out, &def.docs.contents, "    ");
+                for (i, case) in e.cases.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "    {case} = {i}",
+                        case = case.name.to_shouty_snake_case()
+                    )
+                    .unwrap();
+                }
+            }
+            TypeDefKind::Variant(v) => {
+                for case in &v.cases {
+                    writeln!(
+                        out,
+                        "@dataclass\nclass {name}{case}:",
+                        case = case.name.to_upper_camel_case()
+                    )
+                    .unwrap();
+                    match &case.ty {
+                        Some(ty) => {
+                            writeln!(out, "    value: {ty}\n\n", ty = self.py_type(ty)).unwrap()
+                        }
+                        None => out.push_str("    pass\n\n\n"),
+                    }
+                }
+
+                self.py_docs(out, &def.docs.contents, "");
+                writeln!(
+                    out,
+                    "{name} = Union[{cases}]",
+                    cases = v
+                        .cases
+                        .iter()
+                        .map(|c| format!("{name}{case}", case = c.name.to_upper_camel_case()))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+                .unwrap();
+            }
+            TypeDefKind::Resource => {
+                writeln!(out, "class {name}:").unwrap();
+                self.py_docs(out, &def.docs.contents, "    ");
+
+                let mut empty = true;
+                for func in self.resource_functions(id) {
+                    self.py_function(out, func, "self", "    ");
+                    empty = false;
+                }
+
+                if empty {
+                    out.push_str("    ...\n");
+                }
+            }
+            _ => {
+                self.py_docs(out, &def.docs.contents, "");
+                writeln!(out, "{name} = {ty}", ty = self.py_anonymous(&def.kind)).unwrap();
+            }
+        }
+    }
+
+    fn py_type(&self, ty: &Type) -> String {
+        match ty {
+            Type::Bool => "bool".to_string(),
+            Type::U8
+            | Type::U16
+            | Type::U32
+            | Type::U64
+            | Type::S8
+            | Type::S16
+            | Type::S32
+            | Type::S64 => "int".to_string(),
+            Type::F32 | Type::F64 => "float".to_string(),
+            Type::Char | Type::String => "str".to_string(),
+            Type::Id(id) => {
+                let def = &self.resolve.types[*id];
+                match &def.name {
+                    Some(name) => name.to_upper_camel_case(),
+                    None => self.py_anonymous(&def.kind),
+                }
+            }
+        }
+    }
+
+    fn py_anonymous(&self, kind: &TypeDefKind) -> String {
+        match kind {
+            TypeDefKind::Tuple(t) => format!(
+                "Tuple[{types}]",
+                types = t
+                    .types
+                    .iter()
+                    .map(|ty| self.py_type(ty))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            TypeDefKind::Option(ty) => format!("Optional[{ty}]", ty = self.py_type(ty)),
+            TypeDefKind::Result(r) => format!(
+                "Result[{ok}, {err}]",
+                ok =
+                    r.ok.as_ref()
+                        .map(|ty| self.py_type(ty))
+                        .unwrap_or_else(|| "None".to_string()),
+                err = r
+                    .err
+                    .as_ref()
+                    .map(|ty| self.py_type(ty))
+                    .unwrap_or_else(|| "None".to_string())
+            ),
+            TypeDefKind::List(Type::U8) => "bytes".to_string(),
+            TypeDefKind::List(ty) => format!("List[{ty}]", ty = self.py_type(ty)),
+            TypeDefKind::Type(ty) => self.py_type(ty),
+            TypeDefKind::Handle(Handle::Own(r) | Handle::Borrow(r)) => self.py_type(&Type::Id(*r)),
+            _ => "Any".to_string(),
+        }
+    }
+}
+
+fn c_ident(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn c_param(name: &str) -> String {
+    let name = c_ident(name);
+    match name.as_str() {
+        "auto" | "break" | "case" | "char" | "const" | "continue" | "default" | "do" | "double"
+        | "else" | "enum" | "extern" | "float" | "for" | "goto" | "if" | "inline" | "int"
+        | "long" | "register" | "restrict" | "return" | "short" | "signed" | "sizeof"
+        | "static" | "struct" | "switch" | "typedef" | "union" | "unsigned" | "void"
+        | "volatile" | "while" | "bool" | "true" | "false" | "self" | "context" | "args"
+        | "result" | "error" | "ret" => format!("{name}_"),
+        _ => name,
+    }
+}
+
+fn ts_ident(name: &str) -> String {
+    match name {
+        "break" | "case" | "catch" | "class" | "const" | "continue" | "debugger" | "default"
+        | "delete" | "do" | "else" | "enum" | "export" | "extends" | "false" | "finally"
+        | "for" | "function" | "if" | "import" | "in" | "instanceof" | "new" | "null"
+        | "return" | "super" | "switch" | "this" | "throw" | "true" | "try" | "typeof" | "var"
+        | "void" | "while" | "with" | "yield" | "let" | "static" | "await" => {
+            format!("{name}_")
+        }
+        _ => name.to_string(),
+    }
+}
+
+fn py_ident(name: &str) -> String {
+    match name {
+        "and" | "as" | "assert" | "async" | "await" | "break" | "class" | "continue" | "def"
+        | "del" | "elif" | "else" | "except" | "finally" | "for" | "from" | "global" | "if"
+        | "import" | "in" | "is" | "lambda" | "nonlocal" | "not" | "or" | "pass" | "raise"
+        | "return" | "try" | "while" | "with" | "yield" | "self" => format!("{name}_"),
+        _ => name.to_string(),
+    }
+}
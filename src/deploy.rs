@@ -0,0 +1,206 @@
+//! Module for deploying components with `cargo component deploy`.
+//!
+//! Components are pushed to OCI registries directly; the other providers
+//! run the provider's own CLI (`spin` or `wash`) with the built component.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_component_core::terminal::Colors;
+use oci_client::{client::ClientConfig, secrets::RegistryAuth, Client, Reference};
+use oci_wasm::{WasmClient, WasmConfig};
+use secrecy::ExposeSecret;
+use wasm_pkg_client::{oci::OciRegistryConfig, Registry};
+
+use crate::{
+    config::Config,
+    metadata::{ComponentMetadata, Deploy},
+};
+
+/// Represents the options for deploying a component.
+pub struct DeployOptions<'a> {
+    /// The component metadata of the package being deployed.
+    pub metadata: &'a ComponentMetadata,
+    /// The deployment configuration of the package.
+    pub deploy: &'a Deploy,
+    /// The path to the built component.
+    pub path: &'a Path,
+    /// Whether to print what would be deployed without deploying.
+    pub dry_run: bool,
+}
+
+/// Adds the version of the package as the tag of a reference without a tag
+/// or digest.
+fn tagged_reference(reference: &str, metadata: &ComponentMetadata) -> String {
+    let name = reference.rsplit('/').next().unwrap_or(reference);
+    if name.contains(':') || name.contains('@') {
+        reference.to_string()
+    } else {
+        format!("{reference}:{version}", version = metadata.version)
+    }
+}
+
+/// Deploys a component with the given options.
+pub async fn deploy(config: &Config, options: &DeployOptions<'_>) -> Result<()> {
+    match options.deploy {
+        Deploy::Oci {
+            reference,
+            annotations,
+        } => {
+            let reference = tagged_reference(reference, options.metadata);
+            if options.dry_run {
+                config.terminal().status_with_color(
+                    "Skipping",
+                    format!("push of `{reference}` (dry run)"),
+                    Colors::Yellow,
+                )?;
+                return Ok(());
+            }
+
+            push_oci(config, options.path, &reference, annotations).await
+        }
+        Deploy::Spin {
+            reference,
+            manifest,
+        } => {
+            let reference = tagged_reference(reference, options.metadata);
+            let mut args = vec!["registry".into(), "push".into(), reference];
+            args.extend(spin_manifest_args(manifest.as_deref()));
+            run_tool(config, "spin", &args, options)
+        }
+        Deploy::FermyonCloud { manifest } => {
+            let mut args = vec!["cloud".into(), "deploy".into()];
+            args.extend(spin_manifest_args(manifest.as_deref()));
+            run_tool(config, "spin", &args, options)
+        }
+        Deploy::Wasmcloud {
+            reference,
+            manifest,
+        } => {
+            let reference = tagged_reference(reference, options.metadata);
+            run_tool(
+                config,
+                "wash",
+                &["push".into(), reference, options.path.display().to_string()],
+                options,
+            )?;
+
+            if let Some(manifest) = manifest {
+                run_tool(
+                    config,
+                    "wash",
+                    &[
+                        "app".into(),
+                        "deploy".into(),
+                        manifest.display().to_string(),
+                    ],
+                    options,
+                )?;
+            }
+
+            Ok(())
+        }
+    }
+}
+
+fn spin_manifest_args(manifest: Option<&Path>) -> Vec<String> {
+    manifest
+        .map(|m| vec!["--from".to_string(), m.display().to_string()])
+        .unwrap_or_default()
+}
+
+/// Runs the CLI of a deployment provider.
+fn run_tool(
+    config: &Config,
+    tool: &str,
+    args: &[String],
+    options: &DeployOptions<'_>,
+) -> Result<()> {
+    let command_line = format!("{tool} {args}", args = args.join(" "));
+    if options.dry_run {
+        config.terminal().status_with_color(
+            "Skipping",
+            format!("`{command_line}` (dry run)"),
+            Colors::Yellow,
+        )?;
+        return Ok(());
+    }
+
+    let path: PathBuf = which::which(tool).with_context(|| {
+        format!("failed to find `{tool}` on PATH; ensure it is installed to deploy with it")
+    })?;
+
+    config
+        .terminal()
+        .status("Running", format!("`{command_line}`"))?;
+
+    let mut cmd = Command::new(path);
+    cmd.args(args);
+    if let Some(dir) = options.metadata.manifest_path.parent() {
+        cmd.current_dir(dir);
+    }
+
+    log::debug!("spawning command {cmd:?}");
+    let status = cmd
+        .status()
+        .with_context(|| format!("failed to spawn `{tool}`"))?;
+    if !status.success() {
+        bail!("`{command_line}` failed ({status})");
+    }
+
+    Ok(())
+}
+
+/// Pushes a component to an OCI registry.
+///
+/// Credentials stored for the registry with `cargo component login` are
+/// used if present.
+async fn push_oci(
+    config: &Config,
+    path: &Path,
+    reference: &str,
+    annotations: &BTreeMap<String, String>,
+) -> Result<()> {
+    let parsed: Reference = reference
+        .parse()
+        .with_context(|| format!("invalid OCI reference `{reference}`"))?;
+
+    let mut auth = RegistryAuth::Anonymous;
+    if let Ok(registry) = parsed.registry().parse::<Registry>() {
+        if let Some(reg_config) = config.pkg_config().registry_config(&registry) {
+            if let Some(credentials) = OciRegistryConfig::try_from(reg_config)?.credentials {
+                auth = RegistryAuth::Basic(
+                    credentials.username,
+                    credentials.password.expose_secret().clone(),
+                );
+            }
+        }
+    }
+
+    let (wasm_config, layer) = WasmConfig::from_component(path, None)
+        .await
+        .with_context(|| format!("failed to read component `{path}`", path = path.display()))?;
+
+    config
+        .terminal()
+        .status("Pushing", format!("component to `{reference}`"))?;
+
+    let client = WasmClient::new(Client::new(ClientConfig::default()));
+    let annotations = (!annotations.is_empty()).then(|| annotations.clone());
+    let response = client
+        .push(&parsed, &auth, layer, wasm_config, annotations)
+        .await
+        .with_context(|| format!("failed to push component to `{reference}`"))?;
+
+    config.terminal().status_with_color(
+        "Deployed",
+        format!("`{reference}` ({url})", url = response.manifest_url),
+        Colors::Green,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,408 @@
+//! Module for documenting the bindings of components.
+//!
+//! The WIT packages of a component's target world are rendered to an HTML
+//! page next to the `rustdoc` output and the `rustdoc` pages of the
+//! generated bindings are linked back to the WIT definitions they were
+//! generated from.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use wit_component::WitPrinter;
+use wit_parser::{FunctionKind, Resolve, TypeDefKind, TypeOwner, WorldId, WorldItem};
+
+/// The class of the links added to `rustdoc` pages.
+///
+/// This is also used to detect pages that were already linked.
+const LINK_CLASS: &str = "wit-origin";
+
+/// Rust keywords that `wit-bindgen` escapes with a trailing underscore.
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+/// Used to document the WIT definitions of a component's target world.
+pub struct WitDocs<'a> {
+    resolve: &'a Resolve,
+    world: WorldId,
+}
+
+impl<'a> WitDocs<'a> {
+    /// Creates a new WIT documentation generator for the given world.
+    pub fn new(resolve: &'a Resolve, world: WorldId) -> Self {
+        Self { resolve, world }
+    }
+
+    /// Renders the WIT packages of the world as an HTML page.
+    ///
+    /// Every interface, world and item of the page has an anchor with the
+    /// same identifier used by the `doc_origin` bindings setting.
+    pub fn render(&self, name: &str) -> Result<String> {
+        let mut html = format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>WIT definitions for {name}</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em auto; max-width: 60em; padding: 0 1em; }}
+pre {{ background-color: #f5f5f5; border-radius: 6px; padding: 1em; overflow-x: auto; }}
+:target {{ background-color: #fdffd3; }}
+</style>
+</head>
+<body>
+<h1>WIT definitions for <code>{name}</code></h1>
+"#,
+            name = escape(name)
+        );
+
+        let world_pkg = self.resolve.worlds[self.world].package;
+        let packages = world_pkg.into_iter().chain(
+            self.resolve
+                .packages
+                .iter()
+                .map(|(id, _)| id)
+                .filter(|id| Some(*id) != world_pkg),
+        );
+
+        for pkg in packages {
+            let source = WitPrinter::default()
+                .print(self.resolve, pkg, &[])
+                .with_context(|| {
+                    format!(
+                        "failed to print WIT package `{name}`",
+                        name = self.resolve.packages[pkg].name
+                    )
+                })?;
+
+            let package = &self.resolve.packages[pkg];
+            html.push_str(&format!(
+                "<section>\n<h2 id=\"{id}\">package {id}</h2>\n<pre>",
+                id = package.name
+            ));
+
+            let mut owner = None;
+            for line in source.lines() {
+                let escaped = escape(line);
+                match anchor(line) {
+                    Some((0, name, true)) => {
+                        let id = self.resolve.id_of_name(pkg, name);
+                        html.push_str(&format!("<span id=\"{id}\">{escaped}</span>"));
+                        owner = Some(id);
+                    }
+                    Some((2, name, false)) if owner.is_some() => {
+                        html.push_str(&format!(
+                            "<span id=\"{owner}#{name}\">{escaped}</span>",
+                            owner = owner.as_deref().unwrap()
+                        ));
+                    }
+                    _ => html.push_str(&escaped),
+                }
+
+                html.push('\n');
+            }
+
+            html.push_str("</pre>\n</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        Ok(html)
+    }
+
+    /// Gets the `rustdoc` pages of the generated bindings paired with the
+    /// identifier of the WIT definition they document.
+    ///
+    /// The page paths are relative to the documentation directory of the
+    /// bindings module.
+    pub fn pages(&self) -> Vec<(PathBuf, String)> {
+        let mut pages = Vec::new();
+
+        let world = &self.resolve.worlds[self.world];
+        if let Some(pkg) = world.package {
+            let origin = self.resolve.id_of_name(pkg, &world.name);
+            pages.push((PathBuf::from("index.html"), origin.clone()));
+
+            for item in world.imports.values() {
+                match item {
+                    WorldItem::Function(func) if func.kind == FunctionKind::Freestanding => {
+                        pages.push((
+                            PathBuf::from(format!("fn.{name}.html", name = rust_ident(&func.name))),
+                            format!("{origin}#{name}", name = func.name),
+                        ));
+                    }
+                    WorldItem::Type(ty) => {
+                        let ty = &self.resolve.types[*ty];
+                        if ty.owner != TypeOwner::World(self.world) {
+                            continue;
+                        }
+
+                        if let (Some(name), Some(page)) = (&ty.name, self.type_page(&ty.kind)) {
+                            pages.push((
+                                PathBuf::from(format!(
+                                    "{page}.{name}.html",
+                                    name = rust_type_ident(name)
+                                )),
+                                format!("{origin}#{name}"),
+                            ));
+                        }
+                    }
+                    _ => continue,
+                }
+            }
+        }
+
+        for (_, iface) in &self.resolve.interfaces {
+            let (pkg, name) = match (iface.package, &iface.name) {
+                (Some(pkg), Some(name)) => (pkg, name),
+                _ => continue,
+            };
+
+            let origin = self.resolve.id_of_name(pkg, name);
+            let package = &self.resolve.packages[pkg].name;
+            let module = PathBuf::from(rust_ident(&package.namespace))
+                .join(rust_ident(&package.name))
+                .join(rust_ident(name));
+
+            // Interfaces may be generated as both imports and exports
+            for dir in [module.clone(), Path::new("exports").join(&module)] {
+                pages.push((dir.join("index.html"), origin.clone()));
+
+                for func in iface.functions.values() {
+                    if func.kind == FunctionKind::Freestanding {
+                        pages.push((
+                            dir.join(format!("fn.{name}.html", name = rust_ident(&func.name))),
+                            format!("{origin}#{name}", name = func.name),
+                        ));
+                    }
+                }
+
+                for (name, ty) in &iface.types {
+                    if let Some(page) = self.type_page(&self.resolve.types[*ty].kind) {
+                        pages.push((
+                            dir.join(format!("{page}.{name}.html", name = rust_type_ident(name))),
+                            format!("{origin}#{name}"),
+                        ));
+                    }
+                }
+            }
+        }
+
+        pages
+    }
+
+    /// Gets the kind of `rustdoc` page generated for a type definition.
+    fn type_page(&self, kind: &TypeDefKind) -> Option<&'static str> {
+        match kind {
+            TypeDefKind::Record(_) | TypeDefKind::Flags(_) | TypeDefKind::Resource => {
+                Some("struct")
+            }
+            TypeDefKind::Variant(_) | TypeDefKind::Enum(_) => Some("enum"),
+            TypeDefKind::Tuple(_)
+            | TypeDefKind::Option(_)
+            | TypeDefKind::Result(_)
+            | TypeDefKind::List(_)
+            | TypeDefKind::Handle(_)
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_)
+            | TypeDefKind::Type(_) => Some("type"),
+            TypeDefKind::Unknown => None,
+        }
+    }
+}
+
+/// Links the `rustdoc` pages of generated bindings to a rendered WIT page.
+///
+/// The `doc_dir` is the root documentation directory, `bindings_dir` is the
+/// documentation directory of a bindings module and `wit_page` is the path
+/// of the rendered WIT page relative to `doc_dir`.
+///
+/// Returns the number of pages that were linked.
+pub fn link_pages(
+    doc_dir: &Path,
+    bindings_dir: &Path,
+    wit_page: &Path,
+    pages: &[(PathBuf, String)],
+) -> Result<usize> {
+    let mut linked = 0;
+    for (page, id) in pages {
+        let path = bindings_dir.join(page);
+        if !path.is_file() {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "failed to read documentation file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        // Skip pages that were linked by a previous run
+        if contents.contains(&format!("class=\"{LINK_CLASS}\"")) {
+            continue;
+        }
+
+        let depth = path
+            .strip_prefix(doc_dir)
+            .unwrap_or(&path)
+            .components()
+            .count()
+            .saturating_sub(1);
+
+        let code = format!("<code>{id}</code>");
+        let link = format!(
+            "<a class=\"{LINK_CLASS}\" href=\"{up}{page}#{fragment}\">{code}</a>",
+            up = "../".repeat(depth),
+            page = wit_page.display().to_string().replace('\\', "/"),
+            fragment = id.replace('#', "%23"),
+        );
+
+        let contents = if contents.contains(&code) {
+            // The page already references the WIT definition (e.g. with the
+            // `doc_origin` bindings setting), so link the reference
+            contents.replacen(&code, &link, 1)
+        } else {
+            // Otherwise, insert the reference right after the main heading
+            let position = match contents
+                .find("<h1")
+                .and_then(|start| contents[start..].find("</div>").map(|end| start + end + 6))
+            {
+                Some(position) => position,
+                None => continue,
+            };
+
+            format!(
+                "{before}<div class=\"docblock\"><p>Defined in WIT by {link}.</p></div>{after}",
+                before = &contents[..position],
+                after = &contents[position..]
+            )
+        };
+
+        fs::write(&path, contents).with_context(|| {
+            format!(
+                "failed to write documentation file `{path}`",
+                path = path.display()
+            )
+        })?;
+
+        linked += 1;
+    }
+
+    Ok(linked)
+}
+
+/// Opens the given documentation file in a browser.
+///
+/// Like `cargo doc --open`, the `BROWSER` environment variable is used
+/// as the browser if it is set.
+pub fn open(path: &Path) -> Result<()> {
+    let mut cmd = match env::var_os("BROWSER") {
+        Some(browser) => Command::new(browser),
+        None if cfg!(target_os = "macos") => Command::new("open"),
+        None if cfg!(windows) => {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", "start", ""]);
+            cmd
+        }
+        None => Command::new("xdg-open"),
+    };
+
+    cmd.arg(path);
+    log::debug!("spawning command {:?}", cmd);
+
+    let status = cmd.status().with_context(|| {
+        format!(
+            "failed to open documentation file `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    if !status.success() {
+        bail!(
+            "failed to open documentation file `{path}`: the browser exited with {status}",
+            path = path.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Gets the name of the item defined by a line of printed WIT.
+///
+/// Returns the indentation of the line, the name of the item and whether or
+/// not the item is an interface or world.
+fn anchor(line: &str) -> Option<(usize, &str, bool)> {
+    let trimmed = line.trim_start();
+    let indent = line.len() - trimmed.len();
+    let trimmed = trimmed
+        .strip_prefix("import ")
+        .or_else(|| trimmed.strip_prefix("export "))
+        .unwrap_or(trimmed);
+
+    for (keyword, container) in [
+        ("interface ", true),
+        ("world ", true),
+        ("record ", false),
+        ("variant ", false),
+        ("enum ", false),
+        ("flags ", false),
+        ("resource ", false),
+        ("type ", false),
+    ] {
+        if let Some(rest) = trimmed.strip_prefix(keyword) {
+            return Some((indent, ident(rest)?, container));
+        }
+    }
+
+    let (name, rest) = trimmed.split_once(':')?;
+    let rest = rest.trim_start();
+    if rest.starts_with("func") || rest.starts_with("async func") {
+        return Some((indent, ident(name)?, false));
+    }
+
+    None
+}
+
+/// Gets the WIT identifier at the start of the string.
+fn ident(s: &str) -> Option<&str> {
+    let s = s.strip_prefix('%').unwrap_or(s);
+    let end = s
+        .find(|c: char| !c.is_alphanumeric() && c != '-')
+        .unwrap_or(s.len());
+    (end > 0).then(|| &s[..end])
+}
+
+fn rust_ident(name: &str) -> String {
+    let ident = name.to_snake_case();
+    if RUST_KEYWORDS.contains(&ident.as_str()) {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+fn rust_type_ident(name: &str) -> String {
+    let ident = name.to_upper_camel_case();
+    if ident == "Self" {
+        format!("{ident}_")
+    } else {
+        ident
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,94 @@
+//! Module for reporting the progress of commands to embedding tools.
+//!
+//! Tools that run `cargo-component` as a library, such as GUI wrappers and IDE
+//! extensions, can register a [`BuildEvents`] implementation with
+//! [`Config::with_events`](crate::config::Config::with_events) to receive typed
+//! events as a command makes progress, instead of parsing the terminal output.
+
+use std::{fmt, path::PathBuf, sync::mpsc::Sender};
+
+use semver::Version;
+
+/// Represents an event emitted while running a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildEvent {
+    /// The component dependencies of a package are about to be resolved.
+    ResolveStarted {
+        /// The name of the package.
+        package: String,
+    },
+    /// The content of a registry package was downloaded to the cache.
+    PackageDownloaded {
+        /// The name of the registry package.
+        name: String,
+        /// The version of the registry package.
+        version: Version,
+    },
+    /// The bindings of a package were generated.
+    BindingsGenerated {
+        /// The name of the package.
+        package: String,
+        /// The path to the bindings file.
+        path: PathBuf,
+    },
+    /// A core module output by cargo was encoded as a component.
+    Componentized {
+        /// The path to the component.
+        path: PathBuf,
+        /// The size of the component, in bytes.
+        size: u64,
+    },
+}
+
+/// A receiver of the events emitted while running a command.
+///
+/// Events are emitted from the thread running the command, in the order they
+/// happen.
+pub trait BuildEvents {
+    /// Called when an event is emitted.
+    fn on_event(&self, event: BuildEvent);
+}
+
+impl<F> BuildEvents for F
+where
+    F: Fn(BuildEvent),
+{
+    fn on_event(&self, event: BuildEvent) {
+        self(event)
+    }
+}
+
+/// Forwards events to a channel, ignoring them once the receiver is dropped.
+impl BuildEvents for Sender<BuildEvent> {
+    fn on_event(&self, event: BuildEvent) {
+        let _ = self.send(event);
+    }
+}
+
+/// The optional receiver of events of a configuration.
+#[derive(Default)]
+pub(crate) struct EventSink(Option<Box<dyn BuildEvents>>);
+
+impl EventSink {
+    /// Creates a sink sending events to the given receiver.
+    pub(crate) fn new(events: impl BuildEvents + 'static) -> Self {
+        Self(Some(Box::new(events)))
+    }
+
+    /// Emits an event if a receiver is registered.
+    ///
+    /// The event is only created when it will be received.
+    pub(crate) fn emit(&self, event: impl FnOnce() -> BuildEvent) {
+        if let Some(events) = &self.0 {
+            events.on_event(event());
+        }
+    }
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventSink")
+            .field(&self.0.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
@@ -0,0 +1,170 @@
+//! Module for external `cargo-component-<name>` subcommands.
+//!
+//! Like cargo, an unrecognized subcommand `<name>` is dispatched to a
+//! `cargo-component-<name>` executable found in `$CARGO_HOME/bin` or on
+//! `PATH`. The executable receives the subcommand name followed by the
+//! remaining arguments, and the resolved metadata of the selected packages
+//! as JSON in the `CARGO_COMPONENT_METADATA` environment variable.
+
+use std::{
+    env,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use cargo_metadata::{CrateType, Metadata, Package};
+use serde::Serialize;
+
+use crate::{
+    config::CargoArguments, is_wasm_target, CargoCommand, CommandStatus, PackageComponentMetadata,
+};
+
+/// The environment variable that holds the metadata passed to external subcommands.
+pub const METADATA_ENV_VAR: &str = "CARGO_COMPONENT_METADATA";
+
+/// The version of the metadata schema passed to external subcommands.
+///
+/// This must be incremented for any change that is not backwards compatible.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Represents the metadata passed to an external subcommand.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ExternalMetadata<'a> {
+    /// The version of the metadata schema.
+    schema_version: u32,
+    /// The root directory of the workspace.
+    workspace_root: &'a Path,
+    /// The target directory of the workspace.
+    target_directory: &'a Path,
+    /// The target triple components are built for.
+    target: &'a str,
+    /// The profile components are built with.
+    profile: &'a str,
+    /// The selected packages.
+    packages: Vec<ExternalPackage<'a>>,
+}
+
+/// Represents a package in the metadata passed to an external subcommand.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct ExternalPackage<'a> {
+    /// The name of the crate.
+    name: &'a str,
+    /// The version of the crate.
+    version: String,
+    /// The path to the cargo manifest of the crate.
+    manifest_path: &'a Path,
+    /// The component package name, if one is configured.
+    package: Option<String>,
+    /// The paths of the components built for the crate.
+    ///
+    /// The components may not exist if the crate has not been built.
+    artifacts: Vec<PathBuf>,
+}
+
+/// Finds the executable of an external subcommand.
+///
+/// Returns `None` for the cargo commands that `cargo-component` handles
+/// itself or if no executable was found.
+pub fn find_external_subcommand(name: &str) -> Option<PathBuf> {
+    if CargoCommand::from(name) != CargoCommand::Other {
+        return None;
+    }
+
+    let file_name = format!(
+        "cargo-component-{name}{suffix}",
+        suffix = env::consts::EXE_SUFFIX
+    );
+    let cargo_home = env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+
+    cargo_home
+        .map(|home| home.join("bin"))
+        .into_iter()
+        .chain(env::var_os("PATH").iter().flat_map(env::split_paths))
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file())
+}
+
+/// Gets the paths of the components built for the binary and `cdylib`
+/// targets of a package.
+fn component_artifacts(package: &Package, output_dir: &Path) -> Vec<PathBuf> {
+    package
+        .targets
+        .iter()
+        .filter_map(|t| {
+            if t.is_bin() {
+                Some(output_dir.join(format!("{name}.wasm", name = t.name)))
+            } else if t.crate_types.contains(&CrateType::CDyLib) {
+                Some(output_dir.join(format!("{name}.wasm", name = t.name.replace('-', "_"))))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Runs an external subcommand with the given arguments.
+///
+/// The arguments are those following the subcommand name.
+pub fn run_external_subcommand(
+    path: &Path,
+    name: &str,
+    args: &[String],
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+) -> Result<CommandStatus> {
+    let target = cargo_args
+        .targets
+        .iter()
+        .map(String::as_str)
+        .find(|t| is_wasm_target(t))
+        .unwrap_or("wasm32-wasip1");
+    let output_dir = metadata
+        .target_directory
+        .as_std_path()
+        .join(target)
+        .join(cargo_args.profile_dir());
+
+    let external = ExternalMetadata {
+        schema_version: SCHEMA_VERSION,
+        workspace_root: metadata.workspace_root.as_std_path(),
+        target_directory: metadata.target_directory.as_std_path(),
+        target,
+        profile: cargo_args.profile_name(),
+        packages: packages
+            .iter()
+            .map(
+                |PackageComponentMetadata { package, metadata }| ExternalPackage {
+                    name: &package.name,
+                    version: package.version.to_string(),
+                    manifest_path: package.manifest_path.as_std_path(),
+                    package: metadata.section.package.as_ref().map(ToString::to_string),
+                    artifacts: component_artifacts(package, &output_dir),
+                },
+            )
+            .collect(),
+    };
+
+    let mut cmd = Command::new(path);
+    cmd.arg(name);
+    cmd.args(args);
+    cmd.env(METADATA_ENV_VAR, serde_json::to_string(&external)?);
+    if let Ok(exe) = env::current_exe() {
+        cmd.env("CARGO_COMPONENT", exe);
+    }
+
+    log::debug!("spawning external subcommand {cmd:?}");
+    let status = cmd.status().with_context(|| {
+        format!(
+            "failed to spawn external subcommand `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    Ok(CommandStatus::from_exit_status(status))
+}
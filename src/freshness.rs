@@ -0,0 +1,382 @@
+//! Module for the file system operations that decide whether outputs are
+//! up-to-date.
+//!
+//! Freshness is decided by BLAKE3 hashes of contents rather than modification
+//! times, which change without the contents changing on CI caches and fresh
+//! git checkouts. Generated files are only rewritten when their contents
+//! change, so that cargo doesn't rebuild the crate, the WIT cache is keyed by
+//! the hash of its inputs, and the bindings of a package are not generated
+//! again while the hashes recorded in their [`BindingsState`] still match.
+//!
+//! These decisions go through the [`FileSystem`] trait so that they can be
+//! exercised against an in-memory file system.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use blake3::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::metadata::TARGET_DEPS_DIR;
+
+/// The file system operations used to decide whether outputs are up-to-date.
+pub trait FileSystem {
+    /// Reads the contents of a file.
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>>;
+
+    /// Writes the contents of a file, replacing any existing contents.
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Determines if the path is a directory.
+    fn is_dir(&self, path: &Path) -> bool;
+
+    /// Gets the paths of the entries of a directory, in no particular order.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>>;
+
+    /// Creates a directory and all of its missing parents.
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+}
+
+/// The file system of the host.
+pub struct HostFileSystem;
+
+impl FileSystem for HostFileSystem {
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::read_dir(path)?.map(|e| e.map(|e| e.path())).collect()
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::create_dir_all(path)
+    }
+}
+
+/// Writes a file unless it already has the given contents.
+///
+/// Returns whether the file was written.
+pub fn write_if_changed(fs: &dyn FileSystem, path: &Path, contents: &[u8]) -> io::Result<bool> {
+    if fs.read(path).is_ok_and(|existing| existing == contents) {
+        return Ok(false);
+    }
+
+    fs.write(path, contents)?;
+    Ok(true)
+}
+
+/// Gets the hash of the given contents.
+pub fn content_hash(contents: &[u8]) -> String {
+    blake3::hash(contents).to_hex().to_string()
+}
+
+/// Updates a hasher with a length-prefixed value.
+pub fn update(hasher: &mut Hasher, value: impl AsRef<[u8]>) {
+    let value = value.as_ref();
+    hasher.update(&(value.len() as u64).to_le_bytes());
+    hasher.update(value);
+}
+
+/// Updates a hasher with the names and contents of the files at a path.
+///
+/// Directories are walked recursively in sorted order and names are hashed
+/// relative to the given root so that the hash only depends on the names and
+/// contents of the files, not on where they are located.
+pub fn hash_path(fs: &dyn FileSystem, hasher: &mut Hasher, root: &Path, path: &Path) -> Result<()> {
+    let name = path.strip_prefix(root).unwrap_or(path);
+    update(hasher, name.as_os_str().as_encoded_bytes());
+
+    if !fs.is_dir(path) {
+        let contents = fs
+            .read(path)
+            .with_context(|| format!("failed to read `{path}`", path = path.display()))?;
+        update(hasher, contents);
+        return Ok(());
+    }
+
+    let mut entries = fs
+        .read_dir(path)
+        .with_context(|| format!("failed to read directory `{path}`", path = path.display()))?;
+    entries.sort();
+
+    for entry in entries {
+        hash_path(fs, hasher, root, &entry)?;
+    }
+
+    Ok(())
+}
+
+/// Updates a hasher with the WIT of a local target at a path.
+///
+/// The `deps` directory next to a single-file target is hashed along with it.
+pub fn hash_target_path(fs: &dyn FileSystem, hasher: &mut Hasher, path: &Path) -> Result<()> {
+    hash_path(fs, hasher, path, path)?;
+
+    if !fs.is_dir(path) {
+        if let Some(deps) = path.parent().map(|p| p.join(TARGET_DEPS_DIR)) {
+            if fs.is_dir(&deps) {
+                hash_path(fs, hasher, &deps, &deps)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Gets the paths of the Rust source files in a directory, in sorted order.
+///
+/// Subdirectories are walked recursively and the file at the excluded path is
+/// skipped.
+pub fn rust_sources(fs: &dyn FileSystem, dir: &Path, exclude: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    let mut entries = fs.read_dir(dir)?;
+    entries.sort();
+
+    for entry in entries {
+        if fs.is_dir(&entry) {
+            sources.extend(rust_sources(fs, &entry, exclude)?);
+        } else if entry.extension().is_some_and(|e| e == "rs") && entry != exclude {
+            sources.push(entry);
+        }
+    }
+
+    Ok(sources)
+}
+
+/// Represents the state of the generated bindings of a package.
+///
+/// The state is recorded in the workspace state after the bindings are
+/// generated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BindingsState {
+    /// The hash of the inputs the bindings were generated from.
+    pub inputs: String,
+    /// The qualified name of the target world of the bindings.
+    pub world: String,
+    /// The hash of the generated bindings file.
+    pub bindings: String,
+    /// The import name map of the package.
+    pub import_name_map: BTreeMap<String, String>,
+    /// The names of the exports of the target world.
+    pub exports: BTreeSet<String>,
+}
+
+impl BindingsState {
+    /// Determines if the state is up-to-date with the given inputs and the
+    /// bindings file at the given path.
+    pub fn is_fresh(&self, fs: &dyn FileSystem, inputs: &str, bindings_path: &Path) -> bool {
+        self.inputs == inputs
+            && fs
+                .read(bindings_path)
+                .is_ok_and(|bindings| content_hash(&bindings) == self.bindings)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        cell::{Cell, RefCell},
+        time::{Duration, SystemTime},
+    };
+
+    /// An in-memory file system with a clock that ticks on every write.
+    #[derive(Default)]
+    struct MemoryFileSystem {
+        files: RefCell<BTreeMap<PathBuf, (Vec<u8>, SystemTime)>>,
+        ticks: Cell<u64>,
+    }
+
+    impl MemoryFileSystem {
+        fn touch(&self, path: &Path) {
+            let now = self.now();
+            self.files.borrow_mut().get_mut(path).unwrap().1 = now;
+        }
+
+        fn now(&self) -> SystemTime {
+            self.ticks.set(self.ticks.get() + 1);
+            SystemTime::UNIX_EPOCH + Duration::from_secs(self.ticks.get())
+        }
+
+        fn modified(&self, path: &Path) -> SystemTime {
+            self.files.borrow()[path].1
+        }
+    }
+
+    impl FileSystem for MemoryFileSystem {
+        fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+            self.files
+                .borrow()
+                .get(path)
+                .map(|(contents, _)| contents.clone())
+                .ok_or_else(|| io::ErrorKind::NotFound.into())
+        }
+
+        fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+            let now = self.now();
+            self.files
+                .borrow_mut()
+                .insert(path.to_path_buf(), (contents.to_vec(), now));
+            Ok(())
+        }
+
+        fn is_dir(&self, path: &Path) -> bool {
+            self.files
+                .borrow()
+                .keys()
+                .any(|file| file != path && file.starts_with(path))
+        }
+
+        fn read_dir(&self, path: &Path) -> io::Result<Vec<PathBuf>> {
+            let entries: BTreeSet<_> = self
+                .files
+                .borrow()
+                .keys()
+                .filter_map(|file| file.ancestors().find(|a| a.parent() == Some(path)))
+                .map(Path::to_path_buf)
+                .collect();
+            Ok(entries.into_iter().collect())
+        }
+
+        fn create_dir_all(&self, _: &Path) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn hash(fs: &dyn FileSystem, path: &Path) -> Result<String> {
+        let mut hasher = Hasher::new();
+        hash_path(fs, &mut hasher, path, path)?;
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    #[test]
+    fn it_only_writes_changed_files() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        let path = Path::new("/src/bindings.rs");
+
+        assert!(write_if_changed(&fs, path, b"foo")?);
+        let modified = fs.modified(path);
+
+        assert!(!write_if_changed(&fs, path, b"foo")?);
+        assert_eq!(fs.modified(path), modified);
+
+        assert!(write_if_changed(&fs, path, b"bar")?);
+        assert!(fs.modified(path) > modified);
+        assert_eq!(fs.read(path)?, b"bar");
+        Ok(())
+    }
+
+    #[test]
+    fn it_hashes_contents_rather_than_modified_times() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        let dir = Path::new("/wit");
+        fs.write(&dir.join("a.wit"), b"package a:a;")?;
+        fs.write(&dir.join("b.wit"), b"package b:b;")?;
+
+        let original = hash(&fs, dir)?;
+        fs.touch(&dir.join("a.wit"));
+        assert_eq!(hash(&fs, dir)?, original);
+
+        fs.write(&dir.join("a.wit"), b"package a:b;")?;
+        assert_ne!(hash(&fs, dir)?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn it_hashes_the_names_of_files() -> Result<()> {
+        let dir = Path::new("/wit");
+        let original = MemoryFileSystem::default();
+        original.write(&dir.join("world.wit"), b"package a:a;")?;
+        let renamed = MemoryFileSystem::default();
+        renamed.write(&dir.join("other.wit"), b"package a:a;")?;
+
+        assert_ne!(hash(&original, dir)?, hash(&renamed, dir)?);
+        Ok(())
+    }
+
+    #[test]
+    fn it_hashes_names_relative_to_the_root() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        fs.write(Path::new("/a/wit/world.wit"), b"package a:a;")?;
+        fs.write(Path::new("/b/wit/world.wit"), b"package a:a;")?;
+
+        assert_eq!(
+            hash(&fs, Path::new("/a/wit"))?,
+            hash(&fs, Path::new("/b/wit"))?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_hashes_the_deps_of_single_file_targets() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        let path = Path::new("/wit/world.wit");
+        fs.write(path, b"package a:a;")?;
+        fs.write(Path::new("/wit/deps/b.wit"), b"package b:b;")?;
+
+        let hash = |fs: &MemoryFileSystem| -> Result<String> {
+            let mut hasher = Hasher::new();
+            hash_target_path(fs, &mut hasher, path)?;
+            Ok(hasher.finalize().to_hex().to_string())
+        };
+
+        let original = hash(&fs)?;
+        fs.write(Path::new("/wit/deps/b.wit"), b"package b:c;")?;
+        assert_ne!(hash(&fs)?, original);
+        Ok(())
+    }
+
+    #[test]
+    fn it_finds_rust_sources() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        let dir = Path::new("/src");
+        fs.write(&dir.join("lib.rs"), b"mod bindings;")?;
+        fs.write(&dir.join("bindings.rs"), b"bindings")?;
+        fs.write(&dir.join("foo/mod.rs"), b"")?;
+        fs.write(&dir.join("foo/data.json"), b"{}")?;
+
+        assert_eq!(
+            rust_sources(&fs, dir, &dir.join("bindings.rs"))?,
+            [dir.join("foo/mod.rs"), dir.join("lib.rs")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn it_checks_the_freshness_of_bindings() -> Result<()> {
+        let fs = MemoryFileSystem::default();
+        let bindings_path = Path::new("/src/bindings.rs");
+        fs.write(bindings_path, b"bindings")?;
+
+        let state = BindingsState {
+            inputs: "inputs".to_string(),
+            world: "foo:bar/baz".to_string(),
+            bindings: content_hash(b"bindings"),
+            import_name_map: Default::default(),
+            exports: ["bar".to_string()].into(),
+        };
+        assert!(state.is_fresh(&fs, "inputs", bindings_path));
+        assert!(!state.is_fresh(&fs, "changed", bindings_path));
+
+        // Touching the bindings doesn't matter, but editing them does
+        fs.touch(bindings_path);
+        assert!(state.is_fresh(&fs, "inputs", bindings_path));
+        fs.write(bindings_path, b"edited")?;
+        assert!(!state.is_fresh(&fs, "inputs", bindings_path));
+        Ok(())
+    }
+}
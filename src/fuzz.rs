@@ -0,0 +1,373 @@
+//! Module for fuzzing the exports of components.
+//!
+//! Inputs are generated from the WIT types of exported functions and
+//! encoded using the WebAssembly Value Encoding (WAVE) so that the
+//! functions may be invoked with `wasmtime run --invoke`.
+
+use std::fmt::Write;
+
+use wit_parser::{Function, Resolve, Type, TypeDefKind, WorldId, WorldItem, WorldKey};
+
+/// The maximum number of elements to generate for lists.
+const MAX_LIST_LEN: u64 = 8;
+/// The maximum number of characters to generate for strings.
+const MAX_STRING_LEN: u64 = 32;
+
+/// Represents an exported function that can be fuzzed.
+pub struct FuzzTarget<'a> {
+    /// The name used to invoke the function.
+    pub name: String,
+    /// The exported function.
+    pub func: &'a Function,
+}
+
+impl FuzzTarget<'_> {
+    /// Gets the name of the directory used to store the target's corpus.
+    pub fn corpus_name(&self) -> String {
+        self.name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Gets the exported functions of the given world that can be fuzzed.
+///
+/// Functions with parameters that cannot be generated (e.g. resources)
+/// and functions of exported WASI interfaces (e.g. `wasi:cli/run`) are
+/// skipped.
+pub fn fuzz_targets(resolve: &Resolve, world: WorldId) -> Vec<FuzzTarget<'_>> {
+    let mut targets = Vec::new();
+    for (key, item) in &resolve.worlds[world].exports {
+        match item {
+            WorldItem::Function(func) => {
+                targets.push(FuzzTarget {
+                    name: func.name.clone(),
+                    func,
+                });
+            }
+            WorldItem::Interface { id, .. } => {
+                let iface = &resolve.interfaces[*id];
+                if iface
+                    .package
+                    .is_some_and(|p| resolve.packages[p].name.namespace == "wasi")
+                {
+                    continue;
+                }
+
+                for func in iface.functions.values() {
+                    // The version of the package follows the function name
+                    let name = match (key, iface.package) {
+                        (WorldKey::Interface(_), Some(pkg)) => {
+                            let pkg = &resolve.packages[pkg].name;
+                            let mut name = format!(
+                                "{ns}:{pkg}/{iface}.{func}",
+                                ns = pkg.namespace,
+                                pkg = pkg.name,
+                                iface = iface.name.as_deref().expect("interface has no name"),
+                                func = func.name
+                            );
+                            if let Some(version) = &pkg.version {
+                                write!(name, "@{version}").unwrap();
+                            }
+                            name
+                        }
+                        _ => format!(
+                            "{iface}.{func}",
+                            iface = resolve.name_world_key(key),
+                            func = func.name
+                        ),
+                    };
+
+                    targets.push(FuzzTarget { name, func });
+                }
+            }
+            WorldItem::Type(_) => continue,
+        }
+    }
+
+    targets.retain(|target| {
+        let supported = target
+            .func
+            .params
+            .iter()
+            .all(|(_, ty)| is_supported(resolve, ty));
+
+        if !supported {
+            log::debug!(
+                "skipping function `{name}` as its parameters cannot be generated",
+                name = target.name
+            );
+        }
+
+        supported
+    });
+
+    targets
+}
+
+fn is_supported(resolve: &Resolve, ty: &Type) -> bool {
+    let id = match ty {
+        Type::Id(id) => *id,
+        _ => return true,
+    };
+
+    match &resolve.types[id].kind {
+        TypeDefKind::Record(r) => r.fields.iter().all(|f| is_supported(resolve, &f.ty)),
+        TypeDefKind::Tuple(t) => t.types.iter().all(|ty| is_supported(resolve, ty)),
+        TypeDefKind::Variant(v) => v
+            .cases
+            .iter()
+            .all(|c| c.ty.as_ref().is_none_or(|ty| is_supported(resolve, ty))),
+        TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+            is_supported(resolve, ty)
+        }
+        TypeDefKind::Result(r) => {
+            r.ok.as_ref().is_none_or(|ty| is_supported(resolve, ty))
+                && r.err.as_ref().is_none_or(|ty| is_supported(resolve, ty))
+        }
+        TypeDefKind::Flags(_) | TypeDefKind::Enum(_) => true,
+        TypeDefKind::Resource
+        | TypeDefKind::Handle(_)
+        | TypeDefKind::Future(_)
+        | TypeDefKind::Stream(_)
+        | TypeDefKind::Unknown => false,
+    }
+}
+
+/// A generator of WAVE-encoded inputs for fuzz targets.
+///
+/// The generator is deterministic for a given seed so that a fuzzing
+/// session can be reproduced.
+pub struct InputGenerator {
+    state: u64,
+}
+
+impl InputGenerator {
+    /// Creates a new input generator with the given seed.
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generates a WAVE-encoded call of the given target.
+    pub fn call(&mut self, resolve: &Resolve, target: &FuzzTarget) -> String {
+        let mut call = format!("{name}(", name = target.name);
+        for (i, (_, ty)) in target.func.params.iter().enumerate() {
+            if i > 0 {
+                call.push_str(", ");
+            }
+
+            self.value(resolve, ty, &mut call);
+        }
+
+        call.push(')');
+        call
+    }
+
+    // An implementation of SplitMix64
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn chance(&mut self, n: u64) -> bool {
+        self.below(n) == 0
+    }
+
+    fn value(&mut self, resolve: &Resolve, ty: &Type, out: &mut String) {
+        // Bias the integer values towards edge cases
+        macro_rules! int {
+            ($ty:ty) => {{
+                let v = match self.below(8) {
+                    0 => 0 as $ty,
+                    1 => <$ty>::MIN,
+                    2 => <$ty>::MAX,
+                    3 => 1 as $ty,
+                    _ => self.next() as $ty,
+                };
+                write!(out, "{v}").unwrap();
+            }};
+        }
+
+        macro_rules! float {
+            ($ty:ty) => {{
+                match self.below(16) {
+                    0 => out.push_str("nan"),
+                    1 => out.push_str("inf"),
+                    2 => out.push_str("-inf"),
+                    3 => out.push_str("0"),
+                    _ => {
+                        let v = (self.next() as i32 as $ty) / (self.below(1000) + 1) as $ty;
+                        write!(out, "{v}").unwrap();
+                    }
+                }
+            }};
+        }
+
+        match ty {
+            Type::Bool => out.push_str(if self.chance(2) { "true" } else { "false" }),
+            Type::U8 => int!(u8),
+            Type::U16 => int!(u16),
+            Type::U32 => int!(u32),
+            Type::U64 => int!(u64),
+            Type::S8 => int!(i8),
+            Type::S16 => int!(i16),
+            Type::S32 => int!(i32),
+            Type::S64 => int!(i64),
+            Type::F32 => float!(f32),
+            Type::F64 => float!(f64),
+            Type::Char => {
+                out.push('\'');
+                let c = self.char();
+                push_escaped(out, c);
+                out.push('\'');
+            }
+            Type::String => {
+                out.push('"');
+                for _ in 0..self.below(MAX_STRING_LEN + 1) {
+                    let c = self.char();
+                    push_escaped(out, c);
+                }
+                out.push('"');
+            }
+            Type::Id(id) => match &resolve.types[*id].kind {
+                TypeDefKind::Record(r) => {
+                    out.push('{');
+                    for (i, field) in r.fields.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(", ");
+                        }
+
+                        push_label(out, &field.name);
+                        out.push_str(": ");
+                        self.value(resolve, &field.ty, out);
+                    }
+                    out.push('}');
+                }
+                TypeDefKind::Tuple(t) => {
+                    out.push('(');
+                    for (i, ty) in t.types.iter().enumerate() {
+                        if i > 0 {
+                            out.push_str(", ");
+                        }
+
+                        self.value(resolve, ty, out);
+                    }
+                    out.push(')');
+                }
+                TypeDefKind::Flags(f) => {
+                    out.push('{');
+                    let mut first = true;
+                    for flag in &f.flags {
+                        if self.chance(2) {
+                            if !first {
+                                out.push_str(", ");
+                            }
+
+                            first = false;
+                            push_label(out, &flag.name);
+                        }
+                    }
+                    out.push('}');
+                }
+                TypeDefKind::Variant(v) => {
+                    let case = &v.cases[self.below(v.cases.len() as u64) as usize];
+                    push_label(out, &case.name);
+                    self.payload(resolve, case.ty.as_ref(), out);
+                }
+                TypeDefKind::Enum(e) => {
+                    let case = &e.cases[self.below(e.cases.len() as u64) as usize];
+                    push_label(out, &case.name);
+                }
+                TypeDefKind::Option(ty) => {
+                    if self.chance(4) {
+                        out.push_str("none");
+                    } else {
+                        out.push_str("some");
+                        self.payload(resolve, Some(ty), out);
+                    }
+                }
+                TypeDefKind::Result(r) => {
+                    if self.chance(2) {
+                        out.push_str("ok");
+                        self.payload(resolve, r.ok.as_ref(), out);
+                    } else {
+                        out.push_str("err");
+                        self.payload(resolve, r.err.as_ref(), out);
+                    }
+                }
+                TypeDefKind::List(ty) => {
+                    out.push('[');
+                    for i in 0..self.below(MAX_LIST_LEN + 1) {
+                        if i > 0 {
+                            out.push_str(", ");
+                        }
+
+                        self.value(resolve, ty, out);
+                    }
+                    out.push(']');
+                }
+                TypeDefKind::Type(ty) => self.value(resolve, ty, out),
+                TypeDefKind::Resource
+                | TypeDefKind::Handle(_)
+                | TypeDefKind::Future(_)
+                | TypeDefKind::Stream(_)
+                | TypeDefKind::Unknown => unreachable!("unsupported type for fuzzing"),
+            },
+        }
+    }
+
+    fn payload(&mut self, resolve: &Resolve, ty: Option<&Type>, out: &mut String) {
+        if let Some(ty) = ty {
+            out.push('(');
+            self.value(resolve, ty, out);
+            out.push(')');
+        }
+    }
+
+    fn char(&mut self) -> char {
+        // Mostly generate printable ASCII characters
+        if self.chance(8) {
+            loop {
+                if let Some(c) = char::from_u32(self.below(0x110000) as u32) {
+                    return c;
+                }
+            }
+        }
+
+        (b' ' + self.below(95) as u8) as char
+    }
+}
+
+fn push_label(out: &mut String, name: &str) {
+    // Labels that are WAVE keywords must be prefixed with `%`
+    if matches!(
+        name,
+        "true" | "false" | "some" | "none" | "ok" | "err" | "inf" | "nan"
+    ) {
+        out.push('%');
+    }
+
+    out.push_str(name);
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '\\' | '"' | '\'' | '\t' | '\r' | '\n' => write!(out, "{}", c.escape_default()).unwrap(),
+        c if c.is_control() => write!(out, "{}", c.escape_unicode()).unwrap(),
+        c => write!(out, "{}", c.escape_debug()).unwrap(),
+    }
+}
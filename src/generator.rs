@@ -15,12 +15,17 @@ use indexmap::{map::Entry, IndexMap, IndexSet};
 use wasm_pkg_client::PackageRef;
 use wit_bindgen_rust::to_rust_ident;
 use wit_parser::{
-    Function, FunctionKind, Handle, Interface, Resolve, Type, TypeDef, TypeDefKind, TypeId,
-    TypeOwner, World, WorldId, WorldItem, WorldKey,
+    Function, FunctionKind, Handle, Interface, Resolve, Results, Type, TypeDef, TypeDefKind,
+    TypeId, TypeOwner, World, WorldId, WorldItem, WorldKey,
 };
 
-/// The type name that implements the export traits.
-const IMPLEMENTER: &str = "Component";
+use crate::bindings::select_world;
+
+/// The default name of the type that implements the export traits.
+pub const DEFAULT_IMPLEMENTOR: &str = "Component";
+
+/// The default Rust edition the generated source is formatted for.
+pub const DEFAULT_EDITION: &str = "2021";
 
 /// Represents a node in a "use" trie.
 #[derive(Default)]
@@ -114,7 +119,7 @@ impl UseTrie {
     /// This method handles the proper casing for path segments and type names.
     ///
     /// Returns the string to use when printing the type reference.
-    fn insert<'a, I>(&mut self, path: I, ty: &str) -> Cow<str>
+    fn insert<'a, I>(&mut self, path: I, ty: &str) -> Cow<'_, str>
     where
         I: IntoIterator<Item = &'a str>,
         I::IntoIter: Clone,
@@ -170,7 +175,7 @@ impl UseTrie {
         resolve: &Resolve,
         interface: &Interface,
         ty: &str,
-    ) -> Cow<str> {
+    ) -> Cow<'_, str> {
         let pkg = &resolve.packages[interface.package.expect("interface should have a package")];
         let name = interface.name.as_deref().expect("unnamed interface");
 
@@ -187,7 +192,7 @@ impl UseTrie {
     }
 
     /// Inserts an export trait for the given world key.
-    fn insert_export_trait(&mut self, resolve: &Resolve, key: &WorldKey) -> Cow<str> {
+    fn insert_export_trait(&mut self, resolve: &Resolve, key: &WorldKey) -> Cow<'_, str> {
         match key {
             WorldKey::Name(name) => self.insert(["bindings", "exports", name.as_str()], "Guest"),
             WorldKey::Interface(id) => {
@@ -233,14 +238,17 @@ impl ReservedNames {
     }
 }
 
-/// Used to write an unimplemented trait function.
-struct UnimplementedFunction<'a> {
+/// Used to write a stub of a trait function.
+///
+/// The body of the stub is `todo!()` unless one is given, and the WIT
+/// signature of the function is written as its doc comment.
+struct StubFunction<'a> {
     resolve: &'a Resolve,
     func: &'a Function,
     target_world: &'a World,
 }
 
-impl<'a> UnimplementedFunction<'a> {
+impl<'a> StubFunction<'a> {
     fn new(resolve: &'a Resolve, func: &'a Function, target_world: &'a World) -> Self {
         Self {
             resolve,
@@ -249,40 +257,19 @@ impl<'a> UnimplementedFunction<'a> {
         }
     }
 
-    fn print(&self, trie: &mut UseTrie, source: &mut String) -> Result<()> {
+    fn print(&self, trie: &mut UseTrie, source: &mut String, body: Option<&str>) -> Result<()> {
         let (name, self_param, constructor) = match self.func.kind {
             FunctionKind::Freestanding => {
                 (Cow::Owned(to_rust_ident(&self.func.name)), false, false)
             }
-            FunctionKind::Method(_) => (
-                to_rust_ident(
-                    self.func
-                        .name
-                        .split_once('.')
-                        .expect("invalid method name")
-                        .1,
-                )
-                .into(),
-                true,
-                false,
-            ),
-            FunctionKind::Static(_) => (
-                to_rust_ident(
-                    self.func
-                        .name
-                        .split_once('.')
-                        .expect("invalid method name")
-                        .1,
-                )
-                .into(),
-                false,
-                false,
-            ),
+            FunctionKind::Method(_) => (to_rust_ident(self.method_name()).into(), true, false),
+            FunctionKind::Static(_) => (to_rust_ident(self.method_name()).into(), false, false),
             FunctionKind::Constructor(_) => ("new".into(), false, true),
         };
 
         // TODO: it would be nice to share the printing of the signature of the function
         // with wit-bindgen, but right now it's tightly coupled with interface generation.
+        writeln!(source, "    /// `{wit}`", wit = self.wit_signature())?;
         write!(source, "    fn {name}(")?;
 
         for (i, (name, param)) in self.func.params.iter().enumerate() {
@@ -322,10 +309,138 @@ impl<'a> UnimplementedFunction<'a> {
                 source.push(')');
             }
         }
-        source.push_str(" {\n        unimplemented!()\n    }\n");
+        writeln!(
+            source,
+            " {{\n        {body}\n    }}",
+            body = body.unwrap_or("todo!()")
+        )?;
         Ok(())
     }
 
+    /// Gets the signature of the function as it is written in WIT.
+    fn wit_signature(&self) -> String {
+        let mut signature = match self.func.kind {
+            FunctionKind::Freestanding => format!("{name}: func(", name = self.func.name),
+            FunctionKind::Method(_) => format!("{name}: func(", name = self.method_name()),
+            FunctionKind::Static(_) => format!("{name}: static func(", name = self.method_name()),
+            FunctionKind::Constructor(_) => "constructor(".to_string(),
+        };
+
+        let skip = matches!(self.func.kind, FunctionKind::Method(_)) as usize;
+        for (i, (name, param)) in self.func.params.iter().skip(skip).enumerate() {
+            if i > 0 {
+                signature.push_str(", ");
+            }
+
+            write!(signature, "{name}: ").unwrap();
+            self.print_wit_type(param, &mut signature);
+        }
+        signature.push(')');
+
+        if let FunctionKind::Constructor(_) = self.func.kind {
+            return signature;
+        }
+
+        match &self.func.results {
+            Results::Anon(ty) => {
+                signature.push_str(" -> ");
+                self.print_wit_type(ty, &mut signature);
+            }
+            Results::Named(results) if results.is_empty() => {}
+            Results::Named(results) => {
+                signature.push_str(" -> (");
+                for (i, (name, ty)) in results.iter().enumerate() {
+                    if i > 0 {
+                        signature.push_str(", ");
+                    }
+
+                    write!(signature, "{name}: ").unwrap();
+                    self.print_wit_type(ty, &mut signature);
+                }
+                signature.push(')');
+            }
+        }
+
+        signature
+    }
+
+    fn method_name(&self) -> &str {
+        self.func
+            .name
+            .split_once('.')
+            .expect("invalid method name")
+            .1
+    }
+
+    fn print_wit_type(&self, ty: &Type, source: &mut String) {
+        let id = match ty {
+            Type::Bool => return source.push_str("bool"),
+            Type::U8 => return source.push_str("u8"),
+            Type::U16 => return source.push_str("u16"),
+            Type::U32 => return source.push_str("u32"),
+            Type::U64 => return source.push_str("u64"),
+            Type::S8 => return source.push_str("s8"),
+            Type::S16 => return source.push_str("s16"),
+            Type::S32 => return source.push_str("s32"),
+            Type::S64 => return source.push_str("s64"),
+            Type::F32 => return source.push_str("f32"),
+            Type::F64 => return source.push_str("f64"),
+            Type::Char => return source.push_str("char"),
+            Type::String => return source.push_str("string"),
+            Type::Id(id) => *id,
+        };
+
+        let ty = &self.resolve.types[id];
+        if let Some(name) = &ty.name {
+            source.push_str(name);
+            return;
+        }
+
+        let (name, types) = match &ty.kind {
+            TypeDefKind::List(ty) => ("list", vec![Some(*ty)]),
+            TypeDefKind::Option(ty) => ("option", vec![Some(*ty)]),
+            TypeDefKind::Result(r) => match (r.ok, r.err) {
+                (None, None) => ("result", vec![]),
+                (Some(ok), None) => ("result", vec![Some(ok)]),
+                (ok, Some(err)) => ("result", vec![ok, Some(err)]),
+            },
+            TypeDefKind::Tuple(t) => ("tuple", t.types.iter().copied().map(Some).collect()),
+            TypeDefKind::Future(ty) => ("future", ty.iter().copied().map(Some).collect()),
+            TypeDefKind::Stream(stream) => {
+                ("stream", stream.element.iter().copied().map(Some).collect())
+            }
+            TypeDefKind::Handle(Handle::Borrow(id)) => ("borrow", vec![Some(Type::Id(*id))]),
+            TypeDefKind::Handle(Handle::Own(id)) => {
+                return self.print_wit_type(&Type::Id(*id), source)
+            }
+            TypeDefKind::Type(ty) => return self.print_wit_type(ty, source),
+            TypeDefKind::Record(_)
+            | TypeDefKind::Flags(_)
+            | TypeDefKind::Variant(_)
+            | TypeDefKind::Enum(_)
+            | TypeDefKind::Resource
+            | TypeDefKind::Unknown => unreachable!("unsupported anonymous type"),
+        };
+
+        source.push_str(name);
+        if types.is_empty() {
+            return;
+        }
+
+        source.push('<');
+        for (i, ty) in types.iter().enumerate() {
+            if i > 0 {
+                source.push_str(", ");
+            }
+
+            match ty {
+                Some(ty) => self.print_wit_type(ty, source),
+                None => source.push('_'),
+            }
+        }
+        source.push('>');
+    }
+
     fn print_type(&self, ty: &Type, trie: &mut UseTrie, source: &mut String) -> Result<()> {
         match ty {
             Type::Bool => source.push_str("bool"),
@@ -401,17 +516,13 @@ impl<'a> UnimplementedFunction<'a> {
             TypeDefKind::Enum(_) => {
                 bail!("unsupported anonymous enum type found in WIT package")
             }
-            TypeDefKind::Future(ty) => {
-                source.push_str("Future<");
-                self.print_optional_type(ty.as_ref(), trie, source)?;
-                source.push('>');
+            // The version of wit-bindgen used to generate the bindings has no
+            // support for the async ABI, so there is no type to print
+            TypeDefKind::Future(_) => {
+                bail!("unsupported future type found in WIT package")
             }
-            TypeDefKind::Stream(stream) => {
-                source.push_str("Stream<");
-                self.print_optional_type(stream.element.as_ref(), trie, source)?;
-                source.push_str(", ");
-                self.print_optional_type(stream.end.as_ref(), trie, source)?;
-                source.push('>');
+            TypeDefKind::Stream(_) => {
+                bail!("unsupported stream type found in WIT package")
             }
             TypeDefKind::Type(ty) => self.print_type(ty, trie, source)?,
             TypeDefKind::Handle(Handle::Own(id)) => self.print_type_id(*id, trie, source, false)?,
@@ -524,6 +635,168 @@ struct Resource<'a> {
     functions: Vec<&'a Function>,
 }
 
+impl<'a> Resource<'a> {
+    /// Gets the name of the module the implementation of the resource is
+    /// generated in.
+    fn module_name(&self) -> String {
+        let name = to_rust_ident(&self.impl_name.to_snake_case());
+        match name.as_str() {
+            // Don't shadow the crates used by the generated source
+            "bindings" | "std" => format!("{name}_"),
+            _ => name,
+        }
+    }
+
+    /// Infers the fields of the implementation of the resource.
+    ///
+    /// A field is inferred for every pair of `set-x` and `x` (or `get-x`)
+    /// methods that agree on the type of the property.
+    fn fields(&self, resolve: &Resolve) -> Vec<Field<'a>> {
+        let mut fields = Vec::new();
+        for setter in &self.functions {
+            let FunctionKind::Method(_) = setter.kind else {
+                continue;
+            };
+
+            let (_, method) = setter.name.split_once('.').expect("invalid method name");
+            let (Some(property), [_, (_, ty)], 0) = (
+                method.strip_prefix("set-"),
+                setter.params.as_slice(),
+                setter.results.len(),
+            ) else {
+                continue;
+            };
+
+            let getter = self.functions.iter().find(|getter| {
+                let FunctionKind::Method(_) = getter.kind else {
+                    return false;
+                };
+
+                let (_, method) = getter.name.split_once('.').expect("invalid method name");
+                (method == property || method.strip_prefix("get-") == Some(property))
+                    && getter.params.len() == 1
+                    && getter.results.len() == 1
+                    && getter.results.iter_types().next() == Some(ty)
+            });
+
+            if let Some(getter) = getter {
+                if is_cloneable(resolve, ty) {
+                    fields.push(Field {
+                        name: to_rust_ident(property),
+                        ty: *ty,
+                        getter,
+                        setter,
+                    });
+                }
+            }
+        }
+
+        fields
+    }
+
+    /// Gets the body of a function of the resource that accesses the given
+    /// fields, if there is one.
+    fn body(&self, func: &Function, fields: &[Field], trie: &mut UseTrie) -> Option<String> {
+        if let FunctionKind::Constructor(_) = func.kind {
+            // Only initialize the fields if the constructor takes all of them
+            let initialized = fields.iter().all(|field| {
+                func.params
+                    .iter()
+                    .any(|(name, ty)| to_rust_ident(name) == field.name && *ty == field.ty)
+            });
+            if fields.is_empty() || !initialized {
+                return None;
+            }
+
+            let mut body = "Self {".to_string();
+            for (i, field) in fields.iter().enumerate() {
+                write!(
+                    body,
+                    "{sep} {name}: {cell}::new({name})",
+                    sep = if i > 0 { "," } else { "" },
+                    name = field.name,
+                    cell = field.cell(trie)
+                )
+                .unwrap();
+            }
+            body.push_str(" }");
+            return Some(body);
+        }
+
+        fields.iter().find_map(|field| {
+            let name = &field.name;
+            if std::ptr::eq(func, field.getter) {
+                Some(if field.is_copy() {
+                    format!("self.{name}.get()")
+                } else {
+                    format!("self.{name}.borrow().clone()")
+                })
+            } else if std::ptr::eq(func, field.setter) {
+                let value = to_rust_ident(&field.setter.params[1].0);
+                Some(if field.is_copy() {
+                    format!("self.{name}.set({value})")
+                } else {
+                    format!("*self.{name}.borrow_mut() = {value};")
+                })
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// A field of a resource implementation inferred from a pair of getter and
+/// setter methods.
+struct Field<'a> {
+    name: String,
+    ty: Type,
+    getter: &'a Function,
+    setter: &'a Function,
+}
+
+impl Field<'_> {
+    /// Determines if the field is stored in a `Cell` rather than a `RefCell`.
+    fn is_copy(&self) -> bool {
+        !matches!(self.ty, Type::String | Type::Id(_))
+    }
+
+    /// Gets the name of the cell type the field is stored in.
+    fn cell(&self, trie: &mut UseTrie) -> String {
+        let cell = if self.is_copy() { "Cell" } else { "RefCell" };
+        trie.insert(["std", "cell"], cell).into_owned()
+    }
+}
+
+/// Determines if the Rust type generated for a WIT type implements `Clone`.
+fn is_cloneable(resolve: &Resolve, ty: &Type) -> bool {
+    let Type::Id(id) = ty else {
+        return true;
+    };
+
+    match &resolve.types[*id].kind {
+        TypeDefKind::Record(r) => r.fields.iter().all(|f| is_cloneable(resolve, &f.ty)),
+        TypeDefKind::Variant(v) => v
+            .cases
+            .iter()
+            .all(|c| c.ty.iter().all(|ty| is_cloneable(resolve, ty))),
+        TypeDefKind::Tuple(t) => t.types.iter().all(|ty| is_cloneable(resolve, ty)),
+        TypeDefKind::Result(r) => {
+            r.ok.iter()
+                .chain(&r.err)
+                .all(|ty| is_cloneable(resolve, ty))
+        }
+        TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+            is_cloneable(resolve, ty)
+        }
+        TypeDefKind::Flags(_) | TypeDefKind::Enum(_) => true,
+        TypeDefKind::Resource
+        | TypeDefKind::Handle(_)
+        | TypeDefKind::Future(_)
+        | TypeDefKind::Stream(_)
+        | TypeDefKind::Unknown => false,
+    }
+}
+
 /// A generator for implementing the interface exports of a world.
 struct InterfaceGenerator<'a> {
     resolve: &'a Resolve,
@@ -601,13 +874,44 @@ impl<'a> InterfaceGenerator<'a> {
         }
     }
 
-    fn generate(&self, trie: &mut UseTrie) -> Result<String> {
+    fn generate(&self, trie: &mut UseTrie, implementor: &str) -> Result<String> {
         let mut source: String = String::new();
 
         for resource in self.resources.values() {
+            let fields = resource.fields(self.resolve);
+            let mut module = String::new();
+            if fields.is_empty() {
+                writeln!(
+                    &mut module,
+                    "pub struct {impl_name};",
+                    impl_name = resource.impl_name
+                )?;
+            } else {
+                writeln!(
+                    &mut module,
+                    "pub struct {impl_name} {{",
+                    impl_name = resource.impl_name
+                )?;
+                for field in &fields {
+                    write!(
+                        &mut module,
+                        "    {name}: {cell}<",
+                        name = field.name,
+                        cell = field.cell(trie)
+                    )?;
+                    StubFunction::new(self.resolve, field.getter, self.target_world).print_type(
+                        &field.ty,
+                        trie,
+                        &mut module,
+                    )?;
+                    module.push_str(">,\n");
+                }
+                module.push_str("}\n");
+            }
+
             writeln!(
-                &mut source,
-                "struct {impl_name};\n\nimpl {impl_trait} for {impl_name} {{",
+                &mut module,
+                "\nimpl {impl_trait} for {impl_name} {{",
                 impl_name = resource.impl_name,
                 impl_trait = trie.insert_interface_type(
                     self.resolve,
@@ -616,34 +920,52 @@ impl<'a> InterfaceGenerator<'a> {
                 )
             )?;
 
-            for func in &resource.functions {
-                UnimplementedFunction::new(self.resolve, func, self.target_world)
-                    .print(trie, &mut source)?;
+            for (i, func) in resource.functions.iter().enumerate() {
+                if i > 0 {
+                    module.push('\n');
+                }
+
+                let body = resource.body(func, &fields, trie);
+                StubFunction::new(self.resolve, func, self.target_world).print(
+                    trie,
+                    &mut module,
+                    body.as_deref(),
+                )?;
             }
 
-            source.push_str("}\n");
-        }
+            module.push_str("}\n");
 
-        if !self.resources.is_empty() {
-            source.push('\n');
+            writeln!(
+                &mut source,
+                "mod {name} {{\n    use super::*;\n",
+                name = resource.module_name()
+            )?;
+            for line in module.lines() {
+                if !line.is_empty() {
+                    write!(&mut source, "    {line}")?;
+                }
+                source.push('\n');
+            }
+            source.push_str("}\n\n");
         }
 
         writeln!(
             &mut source,
-            "impl {name} for {IMPLEMENTER} {{",
+            "impl {name} for {implementor} {{",
             name = trie.insert_export_trait(self.resolve, self.key),
         )?;
 
         for resource in self.resources.values() {
             writeln!(
                 &mut source,
-                "    type {name} = {impl_name};",
+                "    type {name} = {module}::{impl_name};",
                 name = resource
                     .ty
                     .name
                     .as_deref()
                     .expect("unnamed resource type")
                     .to_upper_camel_case(),
+                module = resource.module_name(),
                 impl_name = resource.impl_name,
             )?;
         }
@@ -657,8 +979,11 @@ impl<'a> InterfaceGenerator<'a> {
                 source.push('\n');
             }
 
-            UnimplementedFunction::new(self.resolve, func, self.target_world)
-                .print(trie, &mut source)?;
+            StubFunction::new(self.resolve, func, self.target_world).print(
+                trie,
+                &mut source,
+                None,
+            )?;
         }
 
         source.push_str("}\n");
@@ -705,14 +1030,14 @@ impl<'a> ImplementationGenerator<'a> {
         }
     }
 
-    fn generate(&self, trie: &mut UseTrie) -> Result<Vec<String>> {
+    fn generate(&self, trie: &mut UseTrie, implementor: &str) -> Result<Vec<String>> {
         let mut impls = Vec::new();
         if !self.functions.is_empty() {
             let mut source = String::new();
 
             writeln!(
                 &mut source,
-                "\nimpl {name} for {IMPLEMENTER} {{",
+                "\nimpl {name} for {implementor} {{",
                 name = trie.insert(["bindings"], "Guest")
             )?;
 
@@ -721,8 +1046,11 @@ impl<'a> ImplementationGenerator<'a> {
                     source.push('\n');
                 }
 
-                UnimplementedFunction::new(self.resolve, func, self.target_world)
-                    .print(trie, &mut source)?;
+                StubFunction::new(self.resolve, func, self.target_world).print(
+                    trie,
+                    &mut source,
+                    None,
+                )?;
             }
 
             source.push_str("}\n");
@@ -730,7 +1058,7 @@ impl<'a> ImplementationGenerator<'a> {
         }
 
         for interface in &self.interfaces {
-            impls.push(interface.generate(trie)?);
+            impls.push(interface.generate(trie, implementor)?);
         }
 
         Ok(impls)
@@ -745,21 +1073,42 @@ pub struct SourceGenerator<'a> {
     resolution: &'a DependencyResolution,
     name: &'a PackageRef,
     format: bool,
+    edition: &'a str,
+    implementor: &'a str,
 }
 
 impl<'a> SourceGenerator<'a> {
     /// Creates a new source generator for the given path to
     /// a binary-encoded target wit package.
     ///
-    /// If `format` is true, then `cargo fmt` will be run on the generated source.
+    /// If `format` is true, then the generated source is formatted with
+    /// `rustfmt`, or with `prettyplease` if `rustfmt` is not installed.
     pub fn new(resolution: &'a DependencyResolution, name: &'a PackageRef, format: bool) -> Self {
         Self {
             resolution,
             name,
             format,
+            edition: DEFAULT_EDITION,
+            implementor: DEFAULT_IMPLEMENTOR,
         }
     }
 
+    /// Sets the Rust edition the generated source is formatted for.
+    ///
+    /// Defaults to [`DEFAULT_EDITION`].
+    pub fn with_edition(mut self, edition: &'a str) -> Self {
+        self.edition = edition;
+        self
+    }
+
+    /// Sets the name of the type that implements the export traits.
+    ///
+    /// Defaults to [`DEFAULT_IMPLEMENTOR`].
+    pub fn with_implementor(mut self, implementor: &'a str) -> Self {
+        self.implementor = implementor;
+        self
+    }
+
     /// Generates the Rust source code for the given world.
     pub async fn generate(&self, world: Option<&str>) -> Result<String> {
         let (resolve, world) = self.decode(world).await?;
@@ -769,7 +1118,7 @@ impl<'a> SourceGenerator<'a> {
         let mut trie = UseTrie::default();
         trie.reserve_names(&names);
 
-        let impls = generator.generate(&mut trie)?;
+        let impls = generator.generate(&mut trie, self.implementor)?;
 
         let mut source = String::new();
         writeln!(&mut source, "#[allow(warnings)]\nmod bindings;")?;
@@ -780,7 +1129,11 @@ impl<'a> SourceGenerator<'a> {
             nl = if trie.is_empty() { "" } else { "\n" }
         )?;
 
-        writeln!(&mut source, "struct {IMPLEMENTER};\n")?;
+        writeln!(
+            &mut source,
+            "struct {implementor};\n",
+            implementor = self.implementor
+        )?;
 
         for (i, imp) in impls.iter().enumerate() {
             if i > 0 {
@@ -792,29 +1145,12 @@ impl<'a> SourceGenerator<'a> {
 
         writeln!(
             &mut source,
-            "\nbindings::export!({IMPLEMENTER} with_types_in bindings);"
+            "\nbindings::export!({implementor} with_types_in bindings);",
+            implementor = self.implementor
         )?;
 
         if self.format {
-            let mut child = Command::new("rustfmt")
-                .arg("--edition=2018")
-                .stdin(Stdio::piped())
-                .stdout(Stdio::piped())
-                .spawn()
-                .context("failed to spawn `rustfmt`")?;
-            std::io::Write::write_all(&mut child.stdin.take().unwrap(), source.as_bytes())
-                .context("failed to write to `rustfmt`")?;
-            source.truncate(0);
-            child
-                .stdout
-                .take()
-                .unwrap()
-                .read_to_string(&mut source)
-                .context("failed to write to `rustfmt`")?;
-            let status = child.wait().context("failed to wait for `rustfmt`")?;
-            if !status.success() {
-                bail!("execution of `rustfmt` returned a non-zero exit code {status}");
-            }
+            source = format_source(source, self.edition);
         }
 
         Ok(source)
@@ -822,7 +1158,7 @@ impl<'a> SourceGenerator<'a> {
 
     async fn decode(&self, world: Option<&str>) -> Result<(Resolve, WorldId)> {
         let (resolve, pkg_id, _) = self.resolution.decode().await?.resolve()?;
-        let world = resolve.select_world(pkg_id, world).with_context(|| {
+        let world = select_world(&resolve, pkg_id, world).with_context(|| {
             format!(
                 "failed to select world from target package `{name}`",
                 name = self.name
@@ -831,3 +1167,49 @@ impl<'a> SourceGenerator<'a> {
         Ok((resolve, world))
     }
 }
+
+/// Formats the given Rust source for the given edition.
+///
+/// `prettyplease` is used when `rustfmt` fails, such as when it isn't
+/// installed; if the source can't be formatted at all, it is returned as-is.
+fn format_source(source: String, edition: &str) -> String {
+    let err = match rustfmt(&source, edition) {
+        Ok(formatted) => return formatted,
+        Err(e) => e,
+    };
+
+    log::debug!("failed to format generated source with `rustfmt`: {err:#}");
+    match syn::parse_file(&source) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(e) => {
+            log::debug!("failed to parse generated source: {e}");
+            source
+        }
+    }
+}
+
+/// Formats the given Rust source with `rustfmt`.
+fn rustfmt(source: &str, edition: &str) -> Result<String> {
+    let mut child = Command::new("rustfmt")
+        .arg(format!("--edition={edition}"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("failed to spawn `rustfmt`")?;
+    std::io::Write::write_all(&mut child.stdin.take().unwrap(), source.as_bytes())
+        .context("failed to write to `rustfmt`")?;
+    let mut formatted = String::new();
+    child
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_string(&mut formatted)
+        .context("failed to read from `rustfmt`")?;
+    let status = child.wait().context("failed to wait for `rustfmt`")?;
+    if !status.success() {
+        bail!("execution of `rustfmt` returned a non-zero exit code {status}");
+    }
+
+    Ok(formatted)
+}
@@ -0,0 +1,262 @@
+//! Module for rendering the interface graph of a component's target world.
+//!
+//! The graph has a node for the target world and a node for each of its
+//! imported and exported items. Items are grouped by the package that
+//! contributed them: the target package itself, a target or component
+//! dependency of the crate, or a package those dependencies depend on.
+
+use std::fmt::Write;
+
+use anyhow::{bail, Result};
+use cargo_component_core::registry::{DependencyResolution, DependencyResolutionMap};
+use indexmap::IndexMap;
+use wit_parser::{PackageId, Resolve, WorldId, WorldItem, WorldKey};
+
+use crate::registry::PackageDependencyResolution;
+
+/// The direction of an edge between the world and an item.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Import,
+    Export,
+}
+
+impl Direction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Import => "import",
+            Self::Export => "export",
+        }
+    }
+}
+
+/// Represents an imported or exported item of the world.
+struct Item {
+    /// The name of the item in the world.
+    name: String,
+    /// Whether the item is imported or exported.
+    direction: Direction,
+}
+
+/// Represents the interface graph of a target world.
+pub struct WorldGraph {
+    /// The crate the world is the target of.
+    name: String,
+    /// The name of the world.
+    world: String,
+    /// The items of the world grouped by the description of their contributor.
+    groups: IndexMap<String, Vec<Item>>,
+}
+
+impl WorldGraph {
+    /// Creates the interface graph of the given target world.
+    pub fn new(
+        name: &str,
+        resolution: &PackageDependencyResolution,
+        resolve: &Resolve,
+        world: WorldId,
+    ) -> Self {
+        let w = &resolve.worlds[world];
+        let world_name = match w.package {
+            Some(pkg) => format!(
+                "{pkg}/{name}",
+                pkg = resolve.packages[pkg].name,
+                name = w.name
+            ),
+            None => w.name.clone(),
+        };
+
+        let mut groups: IndexMap<String, Vec<Item>> = IndexMap::new();
+        let items = w
+            .imports
+            .iter()
+            .map(|i| (i, Direction::Import))
+            .chain(w.exports.iter().map(|i| (i, Direction::Export)));
+
+        for ((key, item), direction) in items {
+            let package = match (key, item) {
+                (_, WorldItem::Type(_)) => continue,
+                (WorldKey::Interface(id), _)
+                | (WorldKey::Name(_), WorldItem::Interface { id, .. }) => {
+                    resolve.interfaces[*id].package
+                }
+                (WorldKey::Name(_), WorldItem::Function(_)) => None,
+            };
+
+            let contributor =
+                describe_contributor(resolution, resolve, package.or(w.package), w.package);
+            groups.entry(contributor).or_default().push(Item {
+                name: resolve.name_world_key(key),
+                direction,
+            });
+        }
+
+        Self {
+            name: name.to_string(),
+            world: world_name,
+            groups,
+        }
+    }
+
+    /// Renders the graph in the given format.
+    pub fn render(&self, format: &str) -> Result<String> {
+        match format {
+            "dot" => Ok(self.render_dot()),
+            "mermaid" => Ok(self.render_mermaid()),
+            _ => bail!("unsupported graph format `{format}`"),
+        }
+    }
+
+    /// Renders the graph in the Graphviz DOT language.
+    fn render_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph \"{name}\" {{", name = escape_dot(&self.name)).unwrap();
+        writeln!(dot, "  rankdir=LR;").unwrap();
+        writeln!(dot, "  node [shape=box];").unwrap();
+        writeln!(
+            dot,
+            "  world [label=\"{world}\", shape=ellipse];",
+            world = escape_dot(&self.world)
+        )
+        .unwrap();
+
+        let mut edges = String::new();
+        let mut node = 0;
+        for (i, (contributor, items)) in self.groups.iter().enumerate() {
+            writeln!(dot, "  subgraph cluster_{i} {{").unwrap();
+            writeln!(
+                dot,
+                "    label=\"{label}\";",
+                label = escape_dot(contributor)
+            )
+            .unwrap();
+            for item in items {
+                writeln!(
+                    dot,
+                    "    n{node} [label=\"{label}\"];",
+                    label = escape_dot(&item.name)
+                )
+                .unwrap();
+
+                let (from, to) = match item.direction {
+                    Direction::Import => (format!("n{node}"), "world".to_string()),
+                    Direction::Export => ("world".to_string(), format!("n{node}")),
+                };
+                writeln!(
+                    edges,
+                    "  {from} -> {to} [label=\"{label}\"];",
+                    label = item.direction.as_str()
+                )
+                .unwrap();
+                node += 1;
+            }
+            writeln!(dot, "  }}").unwrap();
+        }
+
+        dot.push_str(&edges);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Renders the graph as a Mermaid flowchart.
+    fn render_mermaid(&self) -> String {
+        let mut mermaid = String::new();
+        writeln!(mermaid, "---").unwrap();
+        writeln!(mermaid, "title: {name}", name = self.name).unwrap();
+        writeln!(mermaid, "---").unwrap();
+        writeln!(mermaid, "flowchart LR").unwrap();
+        writeln!(
+            mermaid,
+            "  world([\"{world}\"])",
+            world = escape_mermaid(&self.world)
+        )
+        .unwrap();
+
+        let mut edges = String::new();
+        let mut node = 0;
+        for (i, (contributor, items)) in self.groups.iter().enumerate() {
+            writeln!(
+                mermaid,
+                "  subgraph group{i} [\"{label}\"]",
+                label = escape_mermaid(contributor)
+            )
+            .unwrap();
+            for item in items {
+                writeln!(
+                    mermaid,
+                    "    n{node}[\"{label}\"]",
+                    label = escape_mermaid(&item.name)
+                )
+                .unwrap();
+
+                let (from, to) = match item.direction {
+                    Direction::Import => (format!("n{node}"), "world".to_string()),
+                    Direction::Export => ("world".to_string(), format!("n{node}")),
+                };
+                writeln!(
+                    edges,
+                    "  {from} -->|{label}| {to}",
+                    label = item.direction.as_str()
+                )
+                .unwrap();
+                node += 1;
+            }
+            writeln!(mermaid, "  end").unwrap();
+        }
+
+        mermaid.push_str(&edges);
+        mermaid
+    }
+}
+
+/// Describes the contributor of a package of the target world.
+fn describe_contributor(
+    resolution: &PackageDependencyResolution,
+    resolve: &Resolve,
+    package: Option<PackageId>,
+    world_package: Option<PackageId>,
+) -> String {
+    let Some(package) = package else {
+        return "target world".to_string();
+    };
+
+    let name = &resolve.packages[package].name;
+    let find = |map: &'_ DependencyResolutionMap| {
+        map.values()
+            .find(|d| {
+                d.name().namespace().as_ref() == name.namespace
+                    && d.name().name().as_ref() == name.name
+            })
+            .map(describe_resolution)
+    };
+
+    if let Some(dependency) = find(&resolution.resolutions) {
+        format!("component dependency {dependency}")
+    } else if Some(package) == world_package {
+        format!("target package {name}")
+    } else if let Some(dependency) = find(&resolution.target_resolutions) {
+        format!("target dependency {dependency}")
+    } else {
+        format!("package {name}")
+    }
+}
+
+/// Describes the resolution of a dependency.
+pub(crate) fn describe_resolution(dependency: &DependencyResolution) -> String {
+    match dependency {
+        DependencyResolution::Registry(res) => {
+            format!("{name}@{version}", name = res.name, version = res.version)
+        }
+        DependencyResolution::Local(res) => format!("{name} (local)", name = res.name),
+    }
+}
+
+/// Escapes a string for a quoted DOT identifier.
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes a string for a quoted Mermaid label.
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "#quot;")
+}
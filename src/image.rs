@@ -0,0 +1,128 @@
+//! Module for building OCI images of components without a container engine.
+//!
+//! Images follow the [Wasm OCI artifact] layout: a single `application/wasm`
+//! layer holding the component and a `application/vnd.wasm.config.v0+json`
+//! config describing its imports and exports. Images are written as an
+//! [OCI image layout] directory that tools such as `skopeo`, `oras` or
+//! `docker load` (after archiving) can consume.
+//!
+//! [Wasm OCI artifact]: https://tag-runtime.cncf.io/wgs/wasm/deliverables/wasm-oci-artifact/
+//! [OCI image layout]: https://github.com/opencontainers/image-spec/blob/main/image-layout.md
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use oci_client::manifest::{
+    ImageIndexEntry, OciImageIndex, OciImageManifest, Platform, OCI_IMAGE_INDEX_MEDIA_TYPE,
+};
+use oci_wasm::{ToConfig, WasmConfig, COMPONENT_OS, WASM_ARCHITECTURE, WASM_MANIFEST_MEDIA_TYPE};
+use semver::Version;
+use sha2::{Digest, Sha256};
+
+/// The annotation that tells container runtimes to run the image with a
+/// WebAssembly runtime.
+const WASM_VARIANT_ANNOTATION: &str = "module.wasm.image/variant";
+
+/// Writes a blob to the given image layout and returns its digest.
+fn write_blob(dir: &Path, data: &[u8]) -> Result<String> {
+    let hex = format!("{:x}", Sha256::digest(data));
+    let path = dir.join("blobs").join("sha256").join(&hex);
+    fs::write(&path, data)
+        .with_context(|| format!("failed to write blob `{path}`", path = path.display()))?;
+    Ok(format!("sha256:{hex}"))
+}
+
+/// Writes an OCI image layout for a component to the given directory.
+///
+/// Any existing image layout in the directory is replaced.
+///
+/// Returns the digest of the image manifest.
+pub fn write_image_layout(
+    dir: &Path,
+    name: &str,
+    version: &Version,
+    component: Vec<u8>,
+) -> Result<String> {
+    if dir.exists() {
+        fs::remove_dir_all(dir).with_context(|| {
+            format!(
+                "failed to remove existing image layout `{path}`",
+                path = dir.display()
+            )
+        })?;
+    }
+
+    fs::create_dir_all(dir.join("blobs").join("sha256")).with_context(|| {
+        format!(
+            "failed to create image layout directory `{path}`",
+            path = dir.display()
+        )
+    })?;
+
+    let (config, layer) =
+        WasmConfig::from_raw_component(component, None).context("failed to decode component")?;
+    let config = config.to_config()?;
+
+    let annotations = BTreeMap::from([
+        (
+            "org.opencontainers.image.title".to_string(),
+            name.to_string(),
+        ),
+        (
+            "org.opencontainers.image.version".to_string(),
+            version.to_string(),
+        ),
+        (
+            WASM_VARIANT_ANNOTATION.to_string(),
+            "compat-smart".to_string(),
+        ),
+    ]);
+
+    let mut manifest =
+        OciImageManifest::build(std::slice::from_ref(&layer), &config, Some(annotations));
+    manifest.media_type = Some(WASM_MANIFEST_MEDIA_TYPE.to_string());
+
+    write_blob(dir, &config.data)?;
+    write_blob(dir, &layer.data)?;
+
+    let manifest = serde_json::to_vec(&manifest)?;
+    let digest = write_blob(dir, &manifest)?;
+
+    let index = OciImageIndex {
+        schema_version: 2,
+        media_type: Some(OCI_IMAGE_INDEX_MEDIA_TYPE.to_string()),
+        manifests: vec![ImageIndexEntry {
+            media_type: WASM_MANIFEST_MEDIA_TYPE.to_string(),
+            digest: digest.clone(),
+            size: manifest.len() as i64,
+            platform: Some(Platform {
+                architecture: WASM_ARCHITECTURE.to_string(),
+                os: COMPONENT_OS.to_string(),
+                os_version: None,
+                os_features: None,
+                variant: None,
+                features: None,
+            }),
+            annotations: Some(BTreeMap::from([(
+                "org.opencontainers.image.ref.name".to_string(),
+                version.to_string(),
+            )])),
+        }],
+        annotations: None,
+    };
+
+    fs::write(dir.join("index.json"), serde_json::to_vec_pretty(&index)?).with_context(|| {
+        format!(
+            "failed to write image index in `{path}`",
+            path = dir.display()
+        )
+    })?;
+    fs::write(dir.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#).with_context(|| {
+        format!(
+            "failed to write image layout file in `{path}`",
+            path = dir.display()
+        )
+    })?;
+
+    Ok(digest)
+}
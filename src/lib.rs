@@ -4,54 +4,112 @@
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeSet, HashMap, HashSet},
     env,
     fmt::{self, Write},
     fs::{self, File},
-    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom},
+    mem,
     path::{Path, PathBuf},
-    process::{Command, Stdio},
-    sync::Arc,
-    time::SystemTime,
+    process::{Command, ExitStatus, Stdio},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
 };
 
 use anyhow::{bail, Context, Result};
-use bindings::BindingsGenerator;
+use bindings::{BindingsGenerator, TargetCache};
 use cargo_component_core::{
-    lock::{LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
+    lock::{LockFile, LockFileResolver},
+    registry::{Dependency, DependencyResolution},
     terminal::Colors,
 };
 use cargo_config2::{PathAndArgs, TargetTripleRef};
-use cargo_metadata::{Artifact, CrateType, Message, Metadata, MetadataCommand, Package};
+use cargo_metadata::{Artifact, CrateType, Message, Metadata, MetadataCommand, Package, PackageId};
+use consumer::ConsumerGenerator;
+use doc::WitDocs;
+use events::BuildEvent;
+use indexmap::IndexMap;
 use semver::Version;
 use shell_escape::escape;
+use stats::BuildStats;
 use tempfile::NamedTempFile;
+use toml_edit::DocumentMut;
+use warg_client::{
+    storage::{ContentStorage, PublishEntry, PublishInfo},
+    ClientError, FileSystemClient,
+};
+use warg_protocol::registry::PackageName;
+use wasm_encoder::Encode;
 use wasm_metadata::{Link, LinkType, RegistryMetadata};
 use wasm_pkg_client::{
     caching::{CachingClient, FileCache},
+    warg::WargRegistryConfig,
     PackageRef, PublishOpts, Registry,
 };
-use wasmparser::{Parser, Payload};
+use wasmparser::{ComponentExternalKind, Encoding, Parser, Payload, TypeRef, Validator};
 use wit_component::ComponentEncoder;
+use wit_parser::{Resolve, WorldId, WorldItem};
 
-use crate::target::install_wasm32_wasip1;
+use crate::{
+    freshness::{content_hash, write_if_changed, BindingsState, HostFileSystem},
+    state::{AdapterState, WorkspaceState},
+    target::install_wasm32_wasip1,
+};
 
-use config::{CargoArguments, CargoPackageSpec, Config};
-use lock::{acquire_lock_file_ro, acquire_lock_file_rw};
-use metadata::ComponentMetadata;
+use config::{CargoArguments, CargoPackageSpec, Config, Emit};
+use lock::{
+    acquire_lock_file_ro, acquire_lock_file_rw, lock_file_changes, print_lock_file_changes,
+};
+use metadata::{
+    fix_deprecated_keys, is_library, ComponentMetadata, ProcessedBy, Runtime, SizeBudget,
+    SizeBudgetLevel,
+};
+use plugin::PluginManifest;
 use registry::{PackageDependencyResolution, PackageResolutionMap};
 
 mod bindings;
+mod bundle;
 pub mod commands;
 pub mod config;
+mod consumer;
+mod deploy;
+mod doc;
+pub mod events;
+pub mod external;
+mod freshness;
+mod fuzz;
 mod generator;
+mod graph;
+mod image;
 mod lock;
 mod metadata;
+mod plugin;
 mod registry;
+mod serve;
+mod state;
+mod stats;
 mod target;
+mod why;
+mod wit_cache;
+
+/// The environment variable listing the packages whose workspace member
+/// dependencies are being built, used to detect cycles between members.
+const WORKSPACE_MEMBER_BUILDS_ENV_VAR: &str = "CARGO_COMPONENT_WORKSPACE_MEMBER_BUILDS";
+
+/// The rustflags that build position-independent modules with the `dylink.0`
+/// sections needed for dynamic linking.
+const DYNAMIC_LIBRARY_RUSTFLAGS: &[&str] = &[
+    "-Crelocation-model=pic",
+    "-Clink-arg=--experimental-pic",
+    "-Clink-arg=--shared",
+];
 
 fn is_wasm_target(target: &str) -> bool {
-    target == "wasm32-wasi" || target == "wasm32-wasip1" || target == "wasm32-unknown-unknown"
+    target == "wasm32-wasi"
+        || target == "wasm32-wasip1"
+        || target == "wasm32-unknown-unknown"
+        || target == "wasm64-unknown-unknown"
 }
 
 /// Represents a cargo package paired with its component metadata.
@@ -65,10 +123,13 @@ pub struct PackageComponentMetadata<'a> {
 
 impl<'a> PackageComponentMetadata<'a> {
     /// Creates a new package metadata from the given package.
-    pub fn new(package: &'a Package) -> Result<Self> {
+    ///
+    /// If a cargo profile is given, the overrides of the component metadata
+    /// for the profile are applied.
+    pub fn new(package: &'a Package, profile: Option<&str>) -> Result<Self> {
         Ok(Self {
             package,
-            metadata: ComponentMetadata::from_package(package)?,
+            metadata: ComponentMetadata::from_package(package, profile)?,
         })
     }
 }
@@ -83,6 +144,8 @@ enum CargoCommand {
     Test,
     Bench,
     Serve,
+    Doc,
+    Fix,
 }
 
 impl CargoCommand {
@@ -111,6 +174,8 @@ impl fmt::Display for CargoCommand {
             Self::Test => write!(f, "test"),
             Self::Bench => write!(f, "bench"),
             Self::Serve => write!(f, "serve"),
+            Self::Doc => write!(f, "doc"),
+            Self::Fix => write!(f, "fix"),
             Self::Other => write!(f, "<unknown>"),
         }
     }
@@ -125,18 +190,49 @@ impl From<&str> for CargoCommand {
             "t" | "test" => Self::Test,
             "bench" => Self::Bench,
             "serve" => Self::Serve,
+            "d" | "doc" => Self::Doc,
+            "fix" => Self::Fix,
             _ => Self::Other,
         }
     }
 }
 
+/// The status of a cargo command run by `cargo-component`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The command ran to completion.
+    Success,
+    /// The command stopped early and the process should exit with the given code.
+    ///
+    /// This is the case when the `--help` option was given on the command line
+    /// or when cargo or a component run by the command fails.
+    Exit(i32),
+}
+
+impl CommandStatus {
+    /// Gets the exit code for the process.
+    pub fn code(self) -> i32 {
+        match self {
+            Self::Success => 0,
+            Self::Exit(code) => code,
+        }
+    }
+
+    fn from_exit_status(status: ExitStatus) -> Self {
+        if status.success() {
+            Self::Success
+        } else {
+            Self::Exit(status.code().unwrap_or(1))
+        }
+    }
+}
+
 /// Runs the cargo command as specified in the configuration.
 ///
-/// Note: if the command returns a non-zero status, or if the
-/// `--help` option was given on the command line, this
-/// function will exit the process.
+/// Returns the status of the command and any relevant output components.
 ///
-/// Returns any relevant output components.
+/// If the command did not succeed, or if the `--help` option was given
+/// on the command line, no output components are returned.
 pub async fn run_cargo_command(
     client: Arc<CachingClient<FileCache>>,
     config: &Config,
@@ -145,13 +241,12 @@ pub async fn run_cargo_command(
     subcommand: Option<&str>,
     cargo_args: &CargoArguments,
     spawn_args: &[String],
-) -> Result<Vec<PathBuf>> {
-    let import_name_map = generate_bindings(client, config, metadata, packages, cargo_args).await?;
-
-    let cargo_path = std::env::var("CARGO")
-        .map(PathBuf::from)
-        .ok()
-        .unwrap_or_else(|| PathBuf::from("cargo"));
+) -> Result<(CommandStatus, Vec<PathBuf>)> {
+    let mut stats = if cargo_args.stats {
+        Some(BuildStats::new(config.cache_dir()?))
+    } else {
+        None
+    };
 
     let command = if cargo_args.help {
         // Treat `--help` as the help command
@@ -160,10 +255,47 @@ pub async fn run_cargo_command(
         subcommand.map(CargoCommand::from).unwrap_or_default()
     };
 
+    if command == CargoCommand::Fix {
+        fix_deprecations(config, packages)?;
+    } else {
+        warn_deprecations(config, packages)?;
+    }
+
+    if command != CargoCommand::Help {
+        build_workspace_members(config, packages, cargo_args)?;
+    }
+
+    // Dev-dependencies are only imported by the components of tests and benchmarks
+    let bindings = generate_bindings(
+        client.clone(),
+        config,
+        metadata,
+        packages,
+        cargo_args,
+        command.testable(),
+        stats.as_mut(),
+    )
+    .await?;
+
+    let cargo_path = std::env::var("CARGO")
+        .map(PathBuf::from)
+        .ok()
+        .unwrap_or_else(|| PathBuf::from("cargo"));
+
     let (build_args, output_args) = match spawn_args.iter().position(|a| a == "--") {
         Some(position) => spawn_args.split_at(position),
         None => (spawn_args, &[] as _),
     };
+    let mut build_args = remove_component_args(build_args);
+    if command.buildable() {
+        // The message format is translated to one that cargo's JSON messages can be read from
+        build_args = remove_message_format_args(&build_args);
+    }
+    if command == CargoCommand::Doc {
+        // The documentation is opened after the bindings are linked to WIT
+        build_args.retain(|a| a != "--open");
+    }
+
     let needs_runner = !build_args.iter().any(|a| a == "--no-run");
 
     let mut args = build_args.iter().peekable();
@@ -192,32 +324,67 @@ pub async fn run_cargo_command(
     }
     cargo.args(args);
 
+    if matches!(
+        command,
+        CargoCommand::Build | CargoCommand::Run | CargoCommand::Serve
+    ) {
+        // Keep cargo from building the excluded targets for WebAssembly
+        cargo.args(target_selection_args(packages, &build_args));
+    }
+
     let cargo_config = cargo_config2::Config::load()?;
 
-    // Handle the target for buildable commands
-    if command.buildable() {
+    // Handle the target for buildable and documentable commands
+    let target = cargo_args
+        .targets
+        .iter()
+        .map(String::as_str)
+        .chain(
+            cargo_config
+                .build
+                .target
+                .iter()
+                .flatten()
+                .map(|t| t.triple()),
+        )
+        .find(|t| is_wasm_target(t));
+
+    if command.buildable() || command == CargoCommand::Doc {
         install_wasm32_wasip1(config)?;
 
         // Add an implicit wasm32-wasip1 target if there isn't a wasm target present
-        if !cargo_args.targets.iter().any(|t| is_wasm_target(t))
-            && !cargo_config
-                .build
-                .target
-                .as_ref()
-                .is_some_and(|v| v.iter().any(|t| is_wasm_target(t.triple())))
-        {
+        if target.is_none() {
             cargo.arg("--target").arg("wasm32-wasip1");
         }
+    }
 
-        if let Some(format) = &cargo_args.message_format {
-            if format != "json-render-diagnostics" {
-                bail!("unsupported cargo message format `{format}`");
-            }
-        }
+    if command.buildable() && packages.iter().any(|p| builds_dynamic_library(&p.metadata)) {
+        // Dynamic libraries and the modules they are linked with must be
+        // position-independent
+        let mut rustflags = cargo_config
+            .rustflags(target.unwrap_or("wasm32-wasip1"))?
+            .unwrap_or_default();
+        rustflags
+            .flags
+            .extend(DYNAMIC_LIBRARY_RUSTFLAGS.iter().map(ToString::to_string));
+        log::debug!(
+            "building dynamic libraries with rustflags `{flags}`",
+            flags = rustflags.encode_space_separated()?
+        );
+        cargo.env("CARGO_ENCODED_RUSTFLAGS", rustflags.encode()?);
+    }
+
+    if command == CargoCommand::Doc && !build_args.iter().any(|a| a == "--document-private-items") {
+        // The generated bindings are usually a private module of the crate
+        cargo.arg("--document-private-items");
+    }
 
+    if command.buildable() {
         // It will output the message as json so we can extract the wasm files
         // that will be componentized
-        cargo.arg("--message-format").arg("json-render-diagnostics");
+        cargo
+            .arg("--message-format")
+            .arg(cargo_message_format(cargo_args.message_format.as_deref())?.as_ref());
         cargo.stdout(Stdio::piped());
     } else {
         cargo.stdout(Stdio::inherit());
@@ -235,7 +402,16 @@ pub async fn run_cargo_command(
             path = cargo_path.display()
         ))?;
 
-        std::process::exit(status.code().unwrap_or(0));
+        return Ok((CommandStatus::Exit(status.code().unwrap_or(0)), Vec::new()));
+    }
+
+    if command == CargoCommand::Test {
+        check_doctests(config, packages, cargo_args)?;
+    }
+
+    if command.buildable() {
+        check_crate_types(config, packages)?;
+        check_linked_libraries(metadata, packages)?;
     }
 
     if needs_runner && command.testable() {
@@ -250,23 +426,258 @@ pub async fn run_cargo_command(
         None
     };
 
-    let artifacts = spawn_cargo(cargo, &cargo_path, cargo_args, command.buildable())?;
+    let target_dir = metadata.target_directory.as_std_path();
+    let mut state = WorkspaceState::read(&HostFileSystem, target_dir);
+    if command.buildable() {
+        remove_stale_components(packages, &mut state)?;
+    }
+
+    let (status, artifacts) = spawn_cargo(cargo, &cargo_path, cargo_args, command.buildable())?;
+    if status != CommandStatus::Success {
+        return Ok((status, Vec::new()));
+    }
 
     let outputs = componentize_artifacts(
         config,
         metadata,
         &artifacts,
         packages,
-        &import_name_map,
+        (&bindings, &mut state),
         command,
-        output_args,
+        (cargo_args, output_args),
     )?;
+    state.write(&HostFileSystem, target_dir)?;
+
+    if let Some(mut stats) = stats {
+        for output in &outputs {
+            if let Some(core_size) = output.core_size {
+                stats.record_component(&output.path, core_size)?;
+            }
+        }
+
+        let cwd = env::current_dir()
+            .with_context(|| "couldn't get the current directory of the process")?;
+        stats.print(config.terminal(), &cwd)?;
+    }
+
+    if command == CargoCommand::Doc {
+        let doc_dir = metadata
+            .target_directory
+            .as_std_path()
+            .join(target.unwrap_or("wasm32-wasip1"))
+            .join("doc");
+        document_bindings(
+            client,
+            config,
+            metadata,
+            packages,
+            (&doc_dir, cargo_args.open),
+        )
+        .await?;
+        return Ok((CommandStatus::Success, Vec::new()));
+    }
 
     if let Some(runner) = runner {
-        spawn_outputs(config, &runner, output_args, &outputs, command)?;
+        let status = spawn_outputs(
+            config,
+            &runner,
+            output_args,
+            &outputs,
+            command,
+            cargo_args.test_jobs,
+        )?;
+
+        if status != CommandStatus::Success {
+            return Ok((status, Vec::new()));
+        }
+    }
+
+    Ok((
+        CommandStatus::Success,
+        outputs.into_iter().map(|o| o.path).collect(),
+    ))
+}
+
+/// Removes the arguments specific to `cargo-component` that cargo does not accept.
+fn remove_component_args(args: &[String]) -> Vec<String> {
+    let mut filtered = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--test-jobs" || arg == "--output-format" || arg == "--emit" {
+            iter.next();
+            continue;
+        }
+
+        if arg.starts_with("--test-jobs=")
+            || arg.starts_with("--output-format=")
+            || arg.starts_with("--emit=")
+            || arg == "--stats"
+            || arg == "--no-producers"
+            || arg == "--stub-missing-imports"
+        {
+            continue;
+        }
+
+        filtered.push(arg.clone());
+    }
+
+    filtered
+}
+
+fn remove_message_format_args(args: &[String]) -> Vec<String> {
+    let mut filtered = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--message-format" {
+            iter.next();
+            continue;
+        }
+
+        if arg.starts_with("--message-format=") {
+            continue;
+        }
+
+        filtered.push(arg.clone());
+    }
+
+    filtered
+}
+
+/// Gets the message format to pass to cargo for the given `--message-format`.
+///
+/// The human and short formats are rendered by cargo from its JSON diagnostics
+/// so that the JSON messages for the artifacts can still be read.
+fn cargo_message_format(format: Option<&str>) -> Result<Cow<'_, str>> {
+    let Some(format) = format else {
+        return Ok("json-render-diagnostics".into());
+    };
+
+    let values = format.split(',').map(str::trim).collect::<Vec<_>>();
+    match values.as_slice() {
+        ["human"] => Ok("json-render-diagnostics".into()),
+        ["short"] => Ok("json-render-diagnostics,json-diagnostic-short".into()),
+        values if values.iter().all(|v| v.starts_with("json")) => Ok(format.into()),
+        _ => bail!("unsupported cargo message format `{format}`"),
+    }
+}
+
+/// Determines if the given `--message-format` outputs JSON messages.
+fn is_json_message_format(format: &str) -> bool {
+    format.split(',').any(|v| v.trim().starts_with("json"))
+}
+
+fn check_doctests(
+    config: &Config,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+) -> Result<()> {
+    // Doctests are compiled for the host by `rustdoc` and cannot be
+    // componentized, so cargo silently skips them for wasm targets.
+    if cargo_args.doc {
+        bail!(
+            "doctests are not supported by `cargo component test` as they cannot be compiled \
+             to WebAssembly components"
+        );
+    }
+
+    if !cargo_args.runs_doctests() {
+        return Ok(());
+    }
+
+    for PackageComponentMetadata { package, .. } in packages {
+        // Only library targets that can be linked by `rustdoc` have doctests
+        if package.targets.iter().any(|t| {
+            t.doctest
+                && t.crate_types.iter().any(|ty| {
+                    matches!(
+                        ty,
+                        CrateType::Lib | CrateType::RLib | CrateType::DyLib | CrateType::ProcMacro
+                    )
+                })
+        }) {
+            config.terminal().warn(format!(
+                "doctests for package `{name}` will not be run for WebAssembly targets; set \
+                 `doctest = false` in the `[lib]` section of `{manifest}` to silence this warning",
+                name = package.name,
+                manifest = package.manifest_path,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn check_crate_types(config: &Config, packages: &[PackageComponentMetadata<'_>]) -> Result<()> {
+    for PackageComponentMetadata { package, metadata } in packages {
+        if !metadata.section_present {
+            continue;
+        }
+
+        // Only a `cdylib` produces a core module that can be componentized;
+        // any other library is built as usual and then silently skipped
+        for target in package.targets.iter().filter(|t| {
+            is_library(t) && !t.crate_types.contains(&CrateType::CDyLib) && !metadata.excludes(t)
+        }) {
+            let crate_types = std::iter::once("\"cdylib\"".to_string())
+                .chain(target.crate_types.iter().map(|ty| format!("\"{ty}\"")))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            config.terminal().warn(format!(
+                "library `{name}` of package `{package}` will not be componentized as it is not \
+                 built as a `cdylib`; add the following to `{manifest}`:\n\n[lib]\ncrate-type = \
+                 [{crate_types}]\n",
+                name = target.name,
+                package = package.name,
+                manifest = package.manifest_path,
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the libraries linked into the components of packages are
+/// `cdylib` libraries of the workspace.
+fn check_linked_libraries(
+    cargo_metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+) -> Result<()> {
+    for PackageComponentMetadata { package, metadata } in packages {
+        let Some(link) = &metadata.section.link else {
+            continue;
+        };
+
+        for name in &link.libraries {
+            let Some(library) = cargo_metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|p| p.name == *name)
+            else {
+                bail!(
+                    "library `{name}` linked into package `{package}` is not a member of the \
+                     workspace",
+                    package = package.name
+                );
+            };
+
+            if !library
+                .targets
+                .iter()
+                .any(|t| t.crate_types.contains(&CrateType::CDyLib))
+            {
+                bail!(
+                    "library `{name}` linked into package `{package}` is not built as a \
+                     `cdylib`; add `crate-type = [\"cdylib\"]` to the `[lib]` table of \
+                     `{manifest}`",
+                    package = package.name,
+                    manifest = library.manifest_path
+                );
+            }
+        }
     }
 
-    Ok(outputs.into_iter().map(|o| o.path).collect())
+    Ok(())
 }
 
 fn get_runner(cargo_config: &cargo_config2::Config, serve: bool) -> Result<PathAndArgs> {
@@ -331,7 +742,7 @@ fn spawn_cargo(
     cargo: &Path,
     cargo_args: &CargoArguments,
     process_messages: bool,
-) -> Result<Vec<Artifact>> {
+) -> Result<(CommandStatus, Vec<Artifact>)> {
     log::debug!("spawning command {:?}", cmd);
 
     let mut child = cmd.spawn().context(format!(
@@ -346,8 +757,12 @@ fn spawn_cargo(
         for line in reader.lines() {
             let line = line.context("failed to read output from `cargo`")?;
 
-            // If the command line arguments also had `--message-format`, echo the line
-            if cargo_args.message_format.is_some() {
+            // If the command line arguments also had a JSON `--message-format`, echo the line
+            if cargo_args
+                .message_format
+                .as_deref()
+                .is_some_and(is_json_message_format)
+            {
                 println!("{line}");
             }
 
@@ -378,18 +793,90 @@ fn spawn_cargo(
         cargo = cargo.display()
     ))?;
 
-    if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
+    Ok((CommandStatus::from_exit_status(status), artifacts))
+}
+
+/// Gets the arguments selecting the targets cargo builds by default, without
+/// the targets excluded from componentization.
+///
+/// No arguments are returned if targets were selected explicitly or if none of
+/// the default targets are excluded.
+fn target_selection_args(
+    packages: &[PackageComponentMetadata],
+    build_args: &[String],
+) -> Vec<String> {
+    const SELECTION_ARGS: &[&str] = &[
+        "--lib",
+        "--bin",
+        "--bins",
+        "--example",
+        "--examples",
+        "--test",
+        "--tests",
+        "--bench",
+        "--benches",
+        "--all-targets",
+    ];
+
+    if build_args.iter().any(|arg| {
+        let name = arg.split_once('=').map(|(name, _)| name).unwrap_or(arg);
+        SELECTION_ARGS.contains(&name)
+    }) {
+        return Vec::new();
+    }
+
+    let targets = packages.iter().flat_map(|p| {
+        p.package
+            .targets
+            .iter()
+            .filter(|t| is_library(t) || t.is_bin())
+            .map(|t| (t, p.metadata.excludes(t)))
+    });
+
+    if !targets.clone().any(|(_, excluded)| excluded) {
+        return Vec::new();
+    }
+
+    let mut args = Vec::new();
+    if targets
+        .clone()
+        .any(|(target, excluded)| is_library(target) && !excluded)
+    {
+        args.push("--lib".to_string());
+    }
+
+    for (target, excluded) in targets {
+        if target.is_bin() && !excluded {
+            args.push("--bin".to_string());
+            args.push(target.name.clone());
+        }
     }
 
-    Ok(artifacts)
+    args
+}
+
+/// Represents the bindings generated for a package.
+#[derive(Default)]
+struct PackageBindings {
+    /// The map of the import names of the package's component dependencies.
+    import_name_map: HashMap<String, String>,
+    /// The names of the exports of the target world, if the package has one.
+    exports: Option<BTreeSet<String>>,
 }
 
 struct Output {
     /// The path to the output.
     path: PathBuf,
+    /// The name of the target that produced the output.
+    target: String,
     /// The display name if the output is an executable.
     display: Option<String>,
+    /// The size of the core module if the output was componentized from one.
+    core_size: Option<u64>,
+    /// The route configured for the output's package for `cargo component serve`.
+    route: Option<String>,
+    /// The runtime configuration of the output's package.
+    runtime: Runtime,
 }
 
 fn componentize_artifacts(
@@ -397,9 +884,9 @@ fn componentize_artifacts(
     cargo_metadata: &Metadata,
     artifacts: &[Artifact],
     packages: &[PackageComponentMetadata<'_>],
-    import_name_map: &HashMap<String, HashMap<String, String>>,
+    (bindings, state): (&HashMap<PackageId, PackageBindings>, &mut WorkspaceState),
     command: CargoCommand,
-    output_args: &[String],
+    (cargo_args, output_args): (&CargoArguments, &[String]),
 ) -> Result<Vec<Output>> {
     let mut outputs = Vec::new();
     let cwd =
@@ -408,6 +895,14 @@ fn componentize_artifacts(
     // Acquire the lock file to ensure any other cargo-component process waits for this to complete
     let _file_lock = acquire_lock_file_ro(config.terminal(), cargo_metadata)?;
 
+    // Libraries linked into the components of other packages are not
+    // componentized by themselves
+    let linked: HashSet<&str> = packages
+        .iter()
+        .filter_map(|p| p.metadata.section.link.as_ref())
+        .flat_map(|link| link.libraries.iter().map(String::as_str))
+        .collect();
+
     for artifact in artifacts {
         for path in artifact
             .filenames
@@ -422,7 +917,82 @@ fn componentize_artifacts(
                 _ => continue,
             };
 
-            match read_artifact(path.as_std_path(), metadata.section_present)? {
+            if metadata.excludes(&artifact.target) {
+                log::debug!(
+                    "output file `{path}` of excluded target `{name}` will not be componentized",
+                    name = artifact.target.name
+                );
+                continue;
+            }
+
+            if metadata.section.library && is_library(&artifact.target) {
+                write_dynamic_library(config, artifact, path.as_std_path(), &cwd)?;
+                continue;
+            }
+
+            if linked.contains(package.name.as_str()) && is_library(&artifact.target) {
+                log::debug!(
+                    "output file `{path}` of library `{name}` is linked into other components",
+                    name = package.name
+                );
+                continue;
+            }
+
+            let runs = matches!(command, CargoCommand::Run | CargoCommand::Serve);
+            let selected = cargo_args.selects(&artifact.target);
+
+            let mut kind = read_artifact(path.as_std_path(), metadata.section_present)?;
+            let mut libraries = Vec::new();
+            let linked_module = linked_module_path(cargo_metadata, path.as_std_path());
+            if let Some(link) = &metadata.section.link {
+                libraries = linked_libraries(cargo_metadata, artifacts, package, link)?;
+
+                // The output is linked again from its kept core module if only
+                // a library changed
+                if matches!(kind, ArtifactKind::Component) && libraries.iter().any(|l| !l.fresh) {
+                    if let Ok(bytes) = fs::read(&linked_module) {
+                        kind = ArtifactKind::Componentizable(bytes);
+                    }
+                }
+            }
+
+            let mut core_size = None;
+            match kind {
+                ArtifactKind::Module if runs && selected => {
+                    bail!(
+                        "{kind} target `{name}` selected for `cargo component {command}` is not a \
+                         WebAssembly component\n\n\
+                         the output module `{path}` has no component type information; ensure \
+                         package `{package}` has a `[package.metadata.component]` section in \
+                         `{manifest}` or that the target uses its generated bindings",
+                        kind = if artifact.target.is_example() {
+                            "example"
+                        } else {
+                            "bin"
+                        },
+                        name = artifact.target.name,
+                        package = package.name,
+                        manifest = package.manifest_path,
+                    );
+                }
+                ArtifactKind::Module if uses_bindings(package, metadata) => {
+                    config.terminal().warn(format!(
+                        "output module `{path}` of package `{package}` has no `component-type` \
+                         custom section and will not be componentized\n\n\
+                         did you forget to generate bindings with `wit_bindgen::generate!` or to \
+                         include the bindings module (`mod bindings;`)? Packages built with \
+                         `cargo component` also need a `[package.metadata.component]` section in \
+                         `{manifest}`",
+                        path = path
+                            .as_std_path()
+                            .strip_prefix(&cwd)
+                            .unwrap_or(path.as_std_path())
+                            .display(),
+                        package = package.name,
+                        manifest = package.manifest_path,
+                    ))?;
+                    continue;
+                }
                 ArtifactKind::Module => {
                     log::debug!(
                         "output file `{path}` is a WebAssembly module that will not be componentized"
@@ -430,17 +1000,35 @@ fn componentize_artifacts(
                     continue;
                 }
                 ArtifactKind::Componentizable(bytes) => {
-                    componentize(
+                    core_size = Some(bytes.len() as u64);
+                    if !libraries.is_empty() {
+                        fs::create_dir_all(linked_module.parent().unwrap())
+                            .and_then(|_| fs::write(&linked_module, &bytes))
+                            .with_context(|| {
+                                format!(
+                                    "failed to write linked module `{path}`",
+                                    path = linked_module.display()
+                                )
+                            })?;
+                    }
+
+                    let adapter = componentize(
                         config,
                         (cargo_metadata, metadata),
-                        import_name_map
-                            .get(&package.name)
+                        bindings
+                            .get(&package.id)
                             .expect("package already processed"),
                         artifact,
-                        path.as_std_path(),
-                        &cwd,
-                        &bytes,
+                        (path.as_std_path(), &cwd),
+                        (&bytes, libraries),
+                        cargo_args,
                     )?;
+
+                    let adapters = &mut state.package_mut(&package.name).adapters;
+                    match adapter {
+                        Some(adapter) => adapters.insert(path.clone().into(), adapter),
+                        None => adapters.remove(path.as_std_path()),
+                    };
                 }
                 ArtifactKind::Component => {
                     log::debug!("output file `{path}` is already a WebAssembly component");
@@ -451,14 +1039,29 @@ fn componentize_artifacts(
                 }
             }
 
+            if !artifact.profile.test {
+                install_component(
+                    config,
+                    metadata,
+                    &artifact.target.name,
+                    cargo_args.profile_name(),
+                    (path.as_std_path(), &cwd),
+                )?;
+            }
+
             let mut output = Output {
                 path: path.as_std_path().into(),
+                target: artifact.target.name.clone(),
                 display: None,
+                core_size,
+                route: metadata.section.serve.route.clone(),
+                runtime: metadata.section.runtime.clone(),
             };
 
             if command.testable() && artifact.profile.test
-                || (matches!(command, CargoCommand::Run | CargoCommand::Serve)
-                    && !artifact.profile.test)
+                || (runs
+                    && !artifact.profile.test
+                    && (selected || !cargo_args.has_target_selection()))
             {
                 output.display = Some(output_display_name(
                     cargo_metadata,
@@ -477,54 +1080,238 @@ fn componentize_artifacts(
     Ok(outputs)
 }
 
-fn output_display_name(
-    metadata: &Metadata,
-    artifact: &Artifact,
-    path: &Path,
-    cwd: &Path,
-    command: CargoCommand,
-    output_args: &[String],
-) -> String {
-    // The format of the display name is intentionally the same
-    // as what `cargo` formats for running executables.
-    let test_path = &artifact.target.src_path;
-    let short_test_path = test_path
-        .strip_prefix(&metadata.workspace_root)
-        .unwrap_or(test_path);
+/// Copies a component, along with its plugin manifest, to the install
+/// destinations of its package.
+fn install_component(
+    config: &Config,
+    metadata: &ComponentMetadata,
+    target: &str,
+    profile: &str,
+    (path, cwd): (&Path, &Path),
+) -> Result<()> {
+    let file_name = path.file_name().unwrap().to_string_lossy();
+    let destinations = metadata.install_paths(target, profile, &file_name)?;
+    if destinations.is_empty() {
+        return Ok(());
+    }
 
-    if artifact.target.is_test() || artifact.target.is_bench() {
-        format!(
-            "{short_test_path} ({path})",
-            path = path.strip_prefix(cwd).unwrap_or(path).display()
-        )
-    } else if command == CargoCommand::Test {
-        format!(
-            "unittests {short_test_path} ({path})",
-            path = path.strip_prefix(cwd).unwrap_or(path).display()
-        )
-    } else if command == CargoCommand::Bench {
-        format!(
-            "benches {short_test_path} ({path})",
-            path = path.strip_prefix(cwd).unwrap_or(path).display()
-        )
-    } else {
-        let mut s = String::new();
-        write!(&mut s, "`").unwrap();
+    let mut files = vec![(path.to_path_buf(), None)];
+    if metadata.section.plugin.is_some() {
+        files.push((path.with_extension("plugin.json"), Some("plugin.json")));
+    }
 
-        write!(
-            &mut s,
-            "{}",
-            path.strip_prefix(cwd).unwrap_or(path).display()
-        )
-        .unwrap();
+    for destination in destinations {
+        for (source, extension) in &files {
+            let destination = match extension {
+                Some(extension) => destination.with_extension(extension),
+                None => destination.clone(),
+            };
 
-        for arg in output_args.iter().skip(1) {
-            write!(&mut s, " {}", escape(arg.into())).unwrap();
-        }
+            let contents = fs::read(source)
+                .with_context(|| format!("failed to read `{path}`", path = source.display()))?;
 
-        write!(&mut s, "`").unwrap();
-        s
-    }
+            let written = destination
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| write_if_changed(&HostFileSystem, &destination, &contents))
+                .with_context(|| {
+                    format!(
+                        "failed to install `{source}` to `{destination}`",
+                        source = source.display(),
+                        destination = destination.display()
+                    )
+                })?;
+
+            if written {
+                config.terminal().status(
+                    "Installed",
+                    format!(
+                        "`{source}` to `{destination}`",
+                        source = source.strip_prefix(cwd).unwrap_or(source).display(),
+                        destination = destination
+                            .strip_prefix(cwd)
+                            .unwrap_or(&destination)
+                            .display()
+                    ),
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the libraries linked into the outputs of a package from the
+/// artifacts of the build.
+fn linked_libraries(
+    cargo_metadata: &Metadata,
+    artifacts: &[Artifact],
+    package: &Package,
+    link: &metadata::Link,
+) -> Result<Vec<LinkedLibrary>> {
+    link.libraries
+        .iter()
+        .map(|name| {
+            let library = cargo_metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|p| p.name == *name)
+                .expect("linked libraries were checked");
+
+            let Some((artifact, path)) = artifacts
+                .iter()
+                .filter(|a| {
+                    a.package_id == library.id
+                        && !a.profile.test
+                        && a.target.crate_types.contains(&CrateType::CDyLib)
+                })
+                .find_map(|a| {
+                    a.filenames
+                        .iter()
+                        .find(|p| p.extension() == Some("wasm"))
+                        .map(|p| (a, p))
+                })
+            else {
+                bail!(
+                    "library `{name}` linked into package `{package}` was not built; build it \
+                     together with the package (e.g. with `-p {name}` or `--workspace`)",
+                    package = package.name,
+                );
+            };
+
+            let bytes = fs::read(path)
+                .with_context(|| format!("failed to read library module `{path}`"))?;
+
+            Ok(LinkedLibrary {
+                name: format!("lib{name}.so", name = artifact.target.name),
+                bytes,
+                dl_openable: link.dl_openable.contains(name),
+                fresh: artifact.fresh,
+            })
+        })
+        .collect()
+}
+
+/// Determines if the build of a package needs position-independent code for
+/// dynamic linking.
+fn builds_dynamic_library(metadata: &ComponentMetadata) -> bool {
+    metadata.section.library || metadata.section.link.is_some()
+}
+
+/// Writes the output module of a dynamic library under the `lib<name>.so`
+/// name that the `dylink.0` sections of other modules reference it by.
+fn write_dynamic_library(
+    config: &Config,
+    artifact: &Artifact,
+    path: &Path,
+    cwd: &Path,
+) -> Result<()> {
+    let bytes = fs::read(path).with_context(|| {
+        format!(
+            "failed to read output module `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    if !has_custom_section(&bytes, "dylink.0")? {
+        config.terminal().warn(format!(
+            "output module `{path}` of library `{name}` has no `dylink.0` section and cannot be \
+             linked dynamically; the standard library must also be built as position-independent \
+             code (e.g. with `-Z build-std`)",
+            path = path.strip_prefix(cwd).unwrap_or(path).display(),
+            name = artifact.target.name,
+        ))?;
+    }
+
+    let library = path.with_file_name(format!("lib{name}.so", name = artifact.target.name));
+    if !artifact.fresh {
+        config.terminal().status(
+            "Creating",
+            format!(
+                "dynamic library {path}",
+                path = library.strip_prefix(cwd).unwrap_or(&library).display()
+            ),
+        )?;
+    }
+
+    fs::write(&library, bytes).with_context(|| {
+        format!(
+            "failed to write dynamic library `{path}`",
+            path = library.display()
+        )
+    })
+}
+
+/// Gets the path the core module of an output linked with libraries is kept
+/// at, so that the output can be linked again when only a library changed.
+fn linked_module_path(cargo_metadata: &Metadata, path: &Path) -> PathBuf {
+    let target_dir = cargo_metadata.target_directory.as_std_path();
+    let relative = path
+        .strip_prefix(target_dir)
+        .ok()
+        .or_else(|| path.file_name().map(Path::new))
+        .unwrap_or(path);
+    target_dir.join("component").join("link").join(relative)
+}
+
+/// Determines if a package is evidently meant to be built as a component,
+/// because it has a WIT directory or depends on wit-bindgen.
+fn uses_bindings(package: &Package, metadata: &ComponentMetadata) -> bool {
+    metadata.target_path().is_some()
+        || package
+            .dependencies
+            .iter()
+            .any(|d| matches!(d.name.as_str(), "wit-bindgen" | "wit-bindgen-rt"))
+}
+
+fn output_display_name(
+    metadata: &Metadata,
+    artifact: &Artifact,
+    path: &Path,
+    cwd: &Path,
+    command: CargoCommand,
+    output_args: &[String],
+) -> String {
+    // The format of the display name is intentionally the same
+    // as what `cargo` formats for running executables.
+    let test_path = &artifact.target.src_path;
+    let short_test_path = test_path
+        .strip_prefix(&metadata.workspace_root)
+        .unwrap_or(test_path);
+
+    if artifact.target.is_test() || artifact.target.is_bench() {
+        format!(
+            "{short_test_path} ({path})",
+            path = path.strip_prefix(cwd).unwrap_or(path).display()
+        )
+    } else if command == CargoCommand::Test {
+        format!(
+            "unittests {short_test_path} ({path})",
+            path = path.strip_prefix(cwd).unwrap_or(path).display()
+        )
+    } else if command == CargoCommand::Bench {
+        format!(
+            "benches {short_test_path} ({path})",
+            path = path.strip_prefix(cwd).unwrap_or(path).display()
+        )
+    } else {
+        let mut s = String::new();
+        write!(&mut s, "`").unwrap();
+
+        write!(
+            &mut s,
+            "{}",
+            path.strip_prefix(cwd).unwrap_or(path).display()
+        )
+        .unwrap();
+
+        for arg in output_args.iter().skip(1) {
+            write!(&mut s, " {}", escape(arg.into())).unwrap();
+        }
+
+        write!(&mut s, "`").unwrap();
+        s
+    }
 }
 
 fn spawn_outputs(
@@ -533,38 +1320,66 @@ fn spawn_outputs(
     output_args: &[String],
     outputs: &[Output],
     command: CargoCommand,
-) -> Result<()> {
+    jobs: Option<usize>,
+) -> Result<CommandStatus> {
     let executables = outputs
         .iter()
         .filter_map(|output| {
             output
                 .display
                 .as_ref()
-                .map(|display| (display, &output.path))
+                .map(|display| (display, &output.path, &output.target, &output.runtime))
         })
         .collect::<Vec<_>>();
 
-    if matches!(command, CargoCommand::Run | CargoCommand::Serve) && executables.len() > 1 {
-        config.terminal().error(format!(
+    if command == CargoCommand::Serve && executables.len() > 1 {
+        // Multiple components can be served behind a single frontend if each has a route
+        let routes = outputs
+            .iter()
+            .filter_map(|output| {
+                Some(serve::Route {
+                    path: output.route.as_deref()?,
+                    display: output.display.as_deref()?,
+                    component: &output.path,
+                    runtime: &output.runtime,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if routes.len() != executables.len() {
+            bail!(
+                "`cargo component serve` can run at most one component, but multiple were specified\n\n\
+                 hint: set `route` in the `[package.metadata.component.serve]` section of each \
+                 package to serve multiple components behind a single frontend"
+            );
+        }
+
+        serve::serve(config, runner, output_args, routes)
+    } else if command == CargoCommand::Run && executables.len() > 1 {
+        bail!(
             "`cargo component {command}` can run at most one component, but multiple were specified",
-        ))
+        );
     } else if executables.is_empty() {
-        config.terminal().error(format!(
+        bail!(
             "a component {ty} target must be available for `cargo component {command}`",
             ty = if matches!(command, CargoCommand::Run | CargoCommand::Serve) {
-                "bin"
+                "bin or example"
             } else {
                 "test"
             }
-        ))
+        );
+    } else if let Some(jobs) = jobs.filter(|j| command.testable() && *j > 1) {
+        spawn_outputs_concurrently(config, runner, output_args, &executables, jobs)
     } else {
-        for (display, executable) in executables {
+        for (display, executable, _, runtime) in executables {
             config.terminal().status("Running", display)?;
 
             let mut cmd = Command::new(&runner.path);
             cmd.args(&runner.args)
+                .args(runtime.runner_flags())
                 .arg("--")
                 .arg(executable)
+                .args(&runtime.args)
                 .args(output_args.iter().skip(1))
                 .stdout(Stdio::inherit())
                 .stderr(Stdio::inherit());
@@ -581,12 +1396,99 @@ fn spawn_outputs(
             ))?;
 
             if !status.success() {
-                std::process::exit(status.code().unwrap_or(1));
+                return Ok(CommandStatus::from_exit_status(status));
             }
         }
 
-        Ok(())
+        Ok(CommandStatus::Success)
+    }
+}
+
+/// Spawns the given executables with up to `jobs` running at a time.
+///
+/// The output of each executable is prefixed with the name of its target.
+fn spawn_outputs_concurrently(
+    config: &Config,
+    runner: &PathAndArgs,
+    output_args: &[String],
+    executables: &[(&String, &PathBuf, &String, &Runtime)],
+    jobs: usize,
+) -> Result<CommandStatus> {
+    let (sender, receiver) = mpsc::channel();
+    let mut pending = executables.iter();
+    let mut running = 0;
+    let mut failure = None;
+
+    loop {
+        // Stop spawning new executables after the first failure
+        while running < jobs && failure.is_none() {
+            let Some((display, executable, target, runtime)) = pending.next() else {
+                break;
+            };
+
+            config.terminal().status("Running", display)?;
+
+            let mut cmd = Command::new(&runner.path);
+            cmd.args(&runner.args)
+                .args(runtime.runner_flags())
+                .arg("--")
+                .arg(executable)
+                .args(&runtime.args)
+                .args(output_args.iter().skip(1))
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped());
+            log::debug!("spawning command {:?}", cmd);
+
+            let mut child = cmd.spawn().context(format!(
+                "failed to spawn `{runner}`",
+                runner = runner.path.display()
+            ))?;
+
+            let stdout = child.stdout.take().expect("no stdout");
+            let stderr = child.stderr.take().expect("no stderr");
+            let prefix = format!("[{target}]");
+            let sender = sender.clone();
+
+            thread::spawn(move || {
+                let stderr = {
+                    let prefix = prefix.clone();
+                    thread::spawn(move || {
+                        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                            eprintln!("{prefix} {line}");
+                        }
+                    })
+                };
+
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    println!("{prefix} {line}");
+                }
+
+                let _ = stderr.join();
+                let _ = sender.send(child.wait());
+            });
+
+            running += 1;
+        }
+
+        if running == 0 {
+            break;
+        }
+
+        let status = receiver
+            .recv()
+            .expect("channel should not be closed")
+            .context(format!(
+                "failed to wait for `{runner}` to finish",
+                runner = runner.path.display()
+            ))?;
+        running -= 1;
+
+        if !status.success() && failure.is_none() {
+            failure = Some(status.code().unwrap_or(1));
+        }
     }
+
+    Ok(failure.map_or(CommandStatus::Success, CommandStatus::Exit))
 }
 
 enum ArtifactKind {
@@ -600,6 +1502,33 @@ enum ArtifactKind {
     Other,
 }
 
+/// Determines if a core module has a `component-type` custom section, which
+/// describes the world of the bindings it was built with.
+pub(crate) fn has_component_type_section(bytes: &[u8]) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::CustomSection(reader) = payload? {
+            if reader.name().starts_with("component-type") {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Determines if a core module has a custom section with the given name.
+fn has_custom_section(bytes: &[u8], name: &str) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::CustomSection(reader) = payload? {
+            if reader.name() == name {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
 fn read_artifact(path: &Path, mut componentizable: bool) -> Result<ArtifactKind> {
     let mut file = File::open(path).with_context(|| {
         format!(
@@ -630,20 +1559,12 @@ fn read_artifact(path: &Path, mut componentizable: bool) -> Result<ArtifactKind>
         })?;
 
         if !componentizable {
-            let parser = Parser::new(0);
-            for payload in parser.parse_all(&bytes) {
-                if let Payload::CustomSection(reader) = payload.with_context(|| {
-                    format!(
-                        "failed to parse output WebAssembly module `{path}`",
-                        path = path.display()
-                    )
-                })? {
-                    if reader.name().starts_with("component-type") {
-                        componentizable = true;
-                        break;
-                    }
-                }
-            }
+            componentizable = has_component_type_section(&bytes).with_context(|| {
+                format!(
+                    "failed to parse output WebAssembly module `{path}`",
+                    path = path.display()
+                )
+            })?;
         }
 
         if componentizable {
@@ -658,23 +1579,6 @@ fn read_artifact(path: &Path, mut componentizable: bool) -> Result<ArtifactKind>
     }
 }
 
-fn last_modified_time(path: &Path) -> Result<SystemTime> {
-    path.metadata()
-        .with_context(|| {
-            format!(
-                "failed to read file metadata for `{path}`",
-                path = path.display()
-            )
-        })?
-        .modified()
-        .with_context(|| {
-            format!(
-                "failed to retrieve last modified time for `{path}`",
-                path = path.display()
-            )
-        })
-}
-
 /// Loads the workspace metadata based on the given manifest path.
 pub fn load_metadata(manifest_path: Option<&Path>) -> Result<Metadata> {
     let mut command = MetadataCommand::new();
@@ -693,13 +1597,83 @@ pub fn load_metadata(manifest_path: Option<&Path>) -> Result<Metadata> {
     command.exec().context("failed to load cargo metadata")
 }
 
+/// Warns about the deprecated keys in the component metadata of the given
+/// packages.
+fn warn_deprecations(config: &Config, packages: &[PackageComponentMetadata<'_>]) -> Result<()> {
+    for PackageComponentMetadata { metadata, .. } in packages {
+        for deprecation in &metadata.deprecations {
+            config.terminal().warn(format!(
+                "{deprecation} in `{path}`",
+                path = metadata.manifest_path.display()
+            ))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Removes the deprecated keys that have no effect from the manifests of the
+/// given packages, warning about the others.
+fn fix_deprecations(config: &Config, packages: &[PackageComponentMetadata<'_>]) -> Result<()> {
+    for PackageComponentMetadata { metadata, .. } in packages {
+        let path = &metadata.manifest_path;
+        if metadata.deprecations.iter().any(|d| d.fixable) {
+            let mut manifest: DocumentMut = fs::read_to_string(path)
+                .with_context(|| {
+                    format!(
+                        "failed to read manifest file `{path}`",
+                        path = path.display()
+                    )
+                })?
+                .parse()
+                .with_context(|| {
+                    format!(
+                        "failed to parse manifest file `{path}`",
+                        path = path.display()
+                    )
+                })?;
+
+            let fixed = fix_deprecated_keys(&mut manifest);
+            fs::write(path, manifest.to_string()).with_context(|| {
+                format!(
+                    "failed to write manifest file `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            for deprecation in fixed {
+                config.terminal().status(
+                    "Fixed",
+                    format!(
+                        "deprecated key `{key}` in `{path}`",
+                        key = deprecation.key,
+                        path = path.display()
+                    ),
+                )?;
+            }
+        }
+
+        for deprecation in metadata.deprecations.iter().filter(|d| !d.fixable) {
+            config
+                .terminal()
+                .warn(format!("{deprecation} in `{path}`", path = path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Loads the component metadata for the given package specs.
 ///
 /// If `workspace` is true, all workspace packages are loaded.
+///
+/// If a cargo profile is given, the overrides of the component metadata for
+/// the profile are applied.
 pub fn load_component_metadata<'a>(
     metadata: &'a Metadata,
     specs: impl ExactSizeIterator<Item = &'a CargoPackageSpec>,
     workspace: bool,
+    profile: Option<&str>,
 ) -> Result<Vec<PackageComponentMetadata<'a>>> {
     let pkgs = if workspace {
         metadata.workspace_packages()
@@ -728,55 +1702,239 @@ pub fn load_component_metadata<'a>(
     };
 
     pkgs.into_iter()
-        .map(PackageComponentMetadata::new)
+        .map(|package| {
+            let mut package = PackageComponentMetadata::new(package, profile)?;
+            package
+                .metadata
+                .resolve_workspace_members(metadata, profile)?;
+            Ok(package)
+        })
         .collect::<Result<_>>()
 }
 
-async fn generate_bindings(
-    client: Arc<CachingClient<FileCache>>,
+/// Builds the workspace members that the component dependencies of the given
+/// packages refer to.
+///
+/// The members are built by running `cargo component build` for them with
+/// the profile, target, features and cargo options of the current command;
+/// they are built every time so that changes to them are picked up, relying
+/// on cargo to skip the members that are fresh.
+fn build_workspace_members(
     config: &Config,
-    metadata: &Metadata,
     packages: &[PackageComponentMetadata<'_>],
     cargo_args: &CargoArguments,
-) -> Result<HashMap<String, HashMap<String, String>>> {
-    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
-    let lock_file = file_lock
-        .as_ref()
-        .map(|f| {
-            LockFile::read(f.file()).with_context(|| {
-                format!(
-                    "failed to read lock file `{path}`",
-                    path = f.path().display()
-                )
-            })
-        })
-        .transpose()?;
+) -> Result<()> {
+    let building = env::var(WORKSPACE_MEMBER_BUILDS_ENV_VAR).unwrap_or_default();
+    let mut built = HashSet::new();
+    for PackageComponentMetadata { package, metadata } in packages {
+        for (name, dependency) in metadata.all_dependencies() {
+            let (Some(member), Dependency::Local(_)) =
+                (metadata.workspace_members.get(name), dependency)
+            else {
+                continue;
+            };
 
-    let cwd =
-        env::current_dir().with_context(|| "couldn't get the current directory of the process")?;
+            if !built.insert(member) {
+                continue;
+            }
+
+            if building.split(',').any(|b| b == member) {
+                bail!(
+                    "cannot build workspace member `{member}` for component dependency `{name}` \
+                     of package `{package}`: the component dependencies of the workspace \
+                     members form a cycle",
+                    package = package.name
+                );
+            }
+
+            config.terminal().status(
+                "Building",
+                format!(
+                    "workspace member `{member}` for component dependency `{name}` of package \
+                     `{package}`",
+                    package = package.name
+                ),
+            )?;
+
+            let exe = env::current_exe()
+                .context("failed to determine the path of the current executable")?;
+            let mut cmd = Command::new(&exe);
+            cmd.args(["component", "build", "--package", member]);
+            if let Some(manifest_path) = &cargo_args.manifest_path {
+                cmd.arg("--manifest-path").arg(manifest_path);
+            }
+
+            if let Some(profile) = &cargo_args.profile {
+                cmd.args(["--profile", profile]);
+            } else if cargo_args.release {
+                cmd.arg("--release");
+            }
+
+            for target in &cargo_args.targets {
+                cmd.args(["--target", target]);
+            }
+
+            for features in &cargo_args.features {
+                cmd.args(["--features", features]);
+            }
+
+            if let Some(color) = cargo_args.color {
+                cmd.arg(format!("--color={color}"));
+            }
+
+            for config in &cargo_args.configs {
+                cmd.args(["--config", config]);
+            }
+
+            for flag in &cargo_args.unstable_flags {
+                cmd.arg(format!("-Z{flag}"));
+            }
+
+            for (set, flag) in [
+                (cargo_args.all_features, "--all-features"),
+                (cargo_args.no_default_features, "--no-default-features"),
+                (cargo_args.frozen, "--frozen"),
+                (cargo_args.locked, "--locked"),
+                (cargo_args.offline, "--offline"),
+                (cargo_args.quiet, "--quiet"),
+            ] {
+                if set {
+                    cmd.arg(flag);
+                }
+            }
+
+            for _ in 0..cargo_args.verbose {
+                cmd.arg("--verbose");
+            }
+
+            let building = if building.is_empty() {
+                package.name.clone()
+            } else {
+                format!("{building},{package}", package = package.name)
+            };
+            cmd.env(WORKSPACE_MEMBER_BUILDS_ENV_VAR, building);
+            cmd.stdout(Stdio::null());
+
+            log::debug!("spawning {cmd:?}");
+            let status = cmd
+                .status()
+                .with_context(|| format!("failed to spawn `{path}`", path = exe.display()))?;
+            if !status.success() {
+                bail!(
+                    "failed to build workspace member `{member}` for component dependency \
+                     `{name}` of package `{package}`",
+                    package = package.name
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn generate_bindings(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+    dev: bool,
+    mut stats: Option<&mut BuildStats>,
+) -> Result<HashMap<PackageId, PackageBindings>> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()?;
 
     let resolver = lock_file.as_ref().map(LockFileResolver::new);
-    let resolution_map = create_resolution_map(client, packages, resolver).await?;
-    let mut import_name_map = HashMap::new();
+    for PackageComponentMetadata { package, .. } in packages {
+        config.emit(|| BuildEvent::ResolveStarted {
+            package: package.name.clone(),
+        });
+    }
+
+    let resolution_map = create_resolution_map(
+        client,
+        &config.cache_dir()?,
+        packages,
+        resolver,
+        Some(cargo_args),
+        dev,
+    )
+    .await?;
+    if let Some(stats) = stats.as_deref_mut() {
+        for PackageComponentMetadata { package, .. } in packages {
+            let resolution = resolution_map.get(&package.id).expect("missing resolution");
+            stats.record_resolutions(resolution.all().map(|(_, r)| r));
+        }
+    }
+
+    fetch_resolutions(config, packages, &resolution_map).await?;
+
+    let target_dir = metadata.target_directory.as_std_path();
+    let mut state = WorkspaceState::read(&HostFileSystem, target_dir);
+    let mut cache = TargetCache::default();
+    let mut bindings = HashMap::new();
     for PackageComponentMetadata { package, .. } in packages {
         let resolution = resolution_map.get(&package.id).expect("missing resolution");
-        import_name_map.insert(
-            package.name.clone(),
-            generate_package_bindings(config, resolution, &cwd).await?,
+        bindings.insert(
+            package.id.clone(),
+            generate_package_bindings(
+                config,
+                resolution,
+                target_dir,
+                cargo_args.profile_name(),
+                &mut state,
+                &mut cache,
+                stats.as_deref_mut(),
+            )
+            .await?,
         );
     }
+    state.write(&HostFileSystem, target_dir)?;
 
-    // Update the lock file if it exists or if the new lock file is non-empty
+    drop(file_lock);
+    write_lock_file(
+        config,
+        metadata,
+        lock_file,
+        &resolution_map,
+        cargo_args.lock_update_allowed(),
+        cargo_args.locked,
+    )?;
+
+    Ok(bindings)
+}
+
+/// Writes the lock file for the given resolutions.
+///
+/// The lock file is written if it exists or if the new lock file is non-empty.
+fn write_lock_file(
+    config: &Config,
+    metadata: &Metadata,
+    lock_file: Option<LockFile>,
+    resolution_map: &PackageResolutionMap<'_>,
+    lock_update_allowed: bool,
+    locked: bool,
+) -> Result<()> {
     let new_lock_file = resolution_map.to_lock_file();
     if (lock_file.is_some() || !new_lock_file.packages.is_empty())
         && Some(&new_lock_file) != lock_file.as_ref()
     {
-        drop(file_lock);
         let file_lock = acquire_lock_file_rw(
             config.terminal(),
             metadata,
-            cargo_args.lock_update_allowed(),
-            cargo_args.locked,
+            (&lock_file.unwrap_or_default(), &new_lock_file),
+            lock_update_allowed,
+            locked,
         )?;
         new_lock_file
             .write(file_lock.file(), "cargo-component")
@@ -788,19 +1946,319 @@ async fn generate_bindings(
             })?;
     }
 
-    Ok(import_name_map)
+    Ok(())
 }
 
+/// Generates the bindings of the given packages without running cargo.
+///
+/// This is what `cargo component bindings` does. Dev-dependencies are only
+/// imported if `dev` is true.
+pub async fn update_bindings(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    cargo_args: &CargoArguments,
+    dev: bool,
+) -> Result<()> {
+    generate_bindings(client, config, metadata, packages, cargo_args, dev, None).await?;
+    Ok(())
+}
+
+/// Resolves the dependencies of the given packages and fetches their content
+/// into the cache.
+///
+/// The lock file is updated the same way as for a build, but no bindings are
+/// generated.
+///
+/// Returns the number of registry packages fetched.
+pub async fn fetch_dependencies(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    lock_update_allowed: bool,
+    locked: bool,
+) -> Result<usize> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()?;
+
+    let resolver = lock_file.as_ref().map(LockFileResolver::new);
+    let resolution_map =
+        create_resolution_map(client, &config.cache_dir()?, packages, resolver, None, true).await?;
+
+    let mut fetched = HashSet::new();
+    for PackageComponentMetadata { package, .. } in packages {
+        let resolution = resolution_map.get(&package.id).expect("missing resolution");
+        for (_, dependency) in resolution.all() {
+            let DependencyResolution::Registry(res) = dependency else {
+                continue;
+            };
+
+            if fetched.insert((res.package.clone(), res.version.clone())) {
+                dependency.fetch().await?;
+            }
+        }
+    }
+
+    drop(file_lock);
+    write_lock_file(
+        config,
+        metadata,
+        lock_file,
+        &resolution_map,
+        lock_update_allowed,
+        locked,
+    )?;
+
+    Ok(fetched.len())
+}
+
+/// Generates consumer bindings in the given languages for the packages.
+///
+/// The bindings of each package are written to the output directory.
+async fn generate_consumer_bindings(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    (languages, out_dir): (&[String], &Path),
+) -> Result<()> {
+    let cwd =
+        env::current_dir().with_context(|| "couldn't get the current directory of the process")?;
+
+    for_each_target_world(
+        client,
+        config,
+        metadata,
+        packages,
+        |package, _, resolve, world| {
+            let consumer = ConsumerGenerator::new(&package.name, resolve, world);
+
+            fs::create_dir_all(out_dir).with_context(|| {
+                format!(
+                    "failed to create output directory `{path}`",
+                    path = out_dir.display()
+                )
+            })?;
+
+            for language in languages {
+                let path = out_dir.join(format!(
+                    "{name}.{ext}",
+                    name = package.name,
+                    ext = consumer::extension(language)
+                ));
+
+                config.terminal().status(
+                    "Generating",
+                    format!(
+                        "{language} consumer bindings for {name} ({path})",
+                        name = package.name,
+                        path = path.strip_prefix(&cwd).unwrap_or(&path).display()
+                    ),
+                )?;
+
+                let source = consumer.generate(language)?;
+                fs::write(&path, source).with_context(|| {
+                    format!(
+                        "failed to write consumer bindings file `{path}`",
+                        path = path.display()
+                    )
+                })?;
+            }
+
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// Renders the WIT definitions of each package's target world and links
+/// the documentation of the generated bindings to them.
+async fn document_bindings(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    (doc_dir, open): (&Path, bool),
+) -> Result<()> {
+    let cwd =
+        env::current_dir().with_context(|| "couldn't get the current directory of the process")?;
+
+    for_each_target_world(
+        client,
+        config,
+        metadata,
+        packages,
+        |package, _, resolve, world| {
+            let docs = WitDocs::new(resolve, world);
+            let wit_page = Path::new("wit").join(format!("{name}.html", name = package.name));
+            let path = doc_dir.join(&wit_page);
+
+            config.terminal().status(
+                "Documenting",
+                format!(
+                    "WIT definitions for {name} ({path})",
+                    name = package.name,
+                    path = path.strip_prefix(&cwd).unwrap_or(&path).display()
+                ),
+            )?;
+
+            let dir = path.parent().unwrap();
+            fs::create_dir_all(dir).with_context(|| {
+                format!(
+                    "failed to create documentation directory `{dir}`",
+                    dir = dir.display()
+                )
+            })?;
+
+            fs::write(&path, docs.render(&package.name)?).with_context(|| {
+                format!(
+                    "failed to write WIT documentation file `{path}`",
+                    path = path.display()
+                )
+            })?;
+
+            // Each documented crate of the package has its own bindings module
+            let pages = docs.pages();
+            for target in documented_targets(package) {
+                let bindings_dir = doc_dir.join(target.replace('-', "_")).join("bindings");
+                if bindings_dir.is_dir() {
+                    let linked = doc::link_pages(doc_dir, &bindings_dir, &wit_page, &pages)?;
+                    log::debug!(
+                        "linked {linked} documentation page(s) of `{target}` to WIT definitions"
+                    );
+                }
+            }
+
+            Ok(())
+        },
+    )
+    .await?;
+
+    if open {
+        let path = packages
+            .first()
+            .and_then(|p| documented_targets(p.package).next())
+            .map(|target| doc_dir.join(target.replace('-', "_")).join("index.html"))
+            .context("no documentation was generated to open")?;
+
+        config.terminal().status(
+            "Opening",
+            path.strip_prefix(&cwd).unwrap_or(&path).display(),
+        )?;
+        doc::open(&path)?;
+    }
+
+    Ok(())
+}
+
+/// Gets the names of the targets of a package that are documented by `rustdoc`.
+fn documented_targets(package: &Package) -> impl Iterator<Item = &str> {
+    package
+        .targets
+        .iter()
+        .filter(|t| t.is_lib() || t.is_cdylib() || t.is_rlib() || t.is_bin())
+        .map(|t| t.name.as_str())
+}
+
+/// Calls the given function with the dependency resolution and target world
+/// of each component package.
+///
+/// Packages without component metadata or without a target world are skipped.
+async fn for_each_target_world(
+    client: Arc<CachingClient<FileCache>>,
+    config: &Config,
+    metadata: &Metadata,
+    packages: &[PackageComponentMetadata<'_>],
+    mut f: impl FnMut(&Package, &PackageDependencyResolution, &Resolve, WorldId) -> Result<()>,
+) -> Result<()> {
+    let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
+    let lock_file = file_lock
+        .as_ref()
+        .map(|f| {
+            LockFile::read(f.file()).with_context(|| {
+                format!(
+                    "failed to read lock file `{path}`",
+                    path = f.path().display()
+                )
+            })
+        })
+        .transpose()?;
+
+    let resolver = lock_file.as_ref().map(LockFileResolver::new);
+    let resolution_map = create_resolution_map(
+        client,
+        &config.cache_dir()?,
+        packages,
+        resolver,
+        None,
+        false,
+    )
+    .await?;
+    let mut cache = TargetCache::default();
+    for PackageComponentMetadata { package, .. } in packages {
+        let resolution = resolution_map.get(&package.id).expect("missing resolution");
+        if !resolution.metadata.section_present && resolution.metadata.target_path().is_none() {
+            continue;
+        }
+
+        let generator = match BindingsGenerator::new(
+            resolution,
+            metadata.target_directory.as_std_path(),
+            &mut cache,
+            None,
+        )
+        .await?
+        {
+            Some((generator, _)) => generator,
+            None => continue,
+        };
+
+        let (resolve, world) = generator.target_world();
+        f(package, resolution, resolve, world)?;
+    }
+
+    Ok(())
+}
+
+/// Resolves the dependencies of the given packages.
+///
+/// Optional dependencies are enabled by the cargo features selected by the
+/// given arguments; if no arguments are given, all of them are enabled.
+///
+/// Dev-dependencies are enabled if `dev` is true.
 async fn create_resolution_map<'a>(
     client: Arc<CachingClient<FileCache>>,
+    cache_dir: &Path,
     packages: &'a [PackageComponentMetadata<'_>],
     lock_file: Option<LockFileResolver<'_>>,
+    cargo_args: Option<&CargoArguments>,
+    dev: bool,
 ) -> Result<PackageResolutionMap<'a>> {
     let mut map = PackageResolutionMap::default();
 
     for PackageComponentMetadata { package, metadata } in packages {
-        let resolution =
-            PackageDependencyResolution::new(client.clone(), metadata, lock_file).await?;
+        let features = cargo_args.map(|args| args.enabled_features(package));
+        let resolution = PackageDependencyResolution::new(
+            client.clone(),
+            cache_dir,
+            metadata,
+            lock_file,
+            features.as_ref(),
+            dev,
+        )
+        .await?;
 
         map.insert(package.id.clone(), resolution);
     }
@@ -808,94 +2266,641 @@ async fn create_resolution_map<'a>(
     Ok(map)
 }
 
+/// Gets the path of the import name map written for the given package.
+fn import_name_map_path(target_dir: &Path, name: &str) -> PathBuf {
+    target_dir.join("component").join(name).join("imports.json")
+}
+
+/// Gets the path of the bindings file generated for the given package.
+// TODO: make the output path configurable
+fn bindings_path(metadata: &ComponentMetadata) -> PathBuf {
+    metadata
+        .manifest_path
+        .parent()
+        .unwrap()
+        .join("src")
+        .join("bindings.rs")
+}
+
+/// Writes the import name map of a package so that hosts can see which
+/// `unlocked-dep` imports a component requires without composing it.
+fn write_import_name_map(path: &Path, import_name_map: &HashMap<String, String>) -> Result<()> {
+    let sorted: std::collections::BTreeMap<_, _> = import_name_map.iter().collect();
+    let contents = serde_json::to_string_pretty(&sorted)? + "\n";
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).with_context(|| {
+            format!(
+                "failed to create directory `{path}`",
+                path = parent.display()
+            )
+        })?;
+    }
+
+    write_if_changed(&HostFileSystem, path, contents.as_bytes()).with_context(|| {
+        format!(
+            "failed to write import name map `{path}`",
+            path = path.display()
+        )
+    })?;
+
+    Ok(())
+}
+
+/// Fetches the content of the enabled registry dependencies that are not
+/// cached yet, emitting an event for each downloaded package.
+async fn fetch_resolutions(
+    config: &Config,
+    packages: &[PackageComponentMetadata<'_>],
+    resolution_map: &PackageResolutionMap<'_>,
+) -> Result<()> {
+    let cache_dir = config.cache_dir()?;
+    let mut fetched = HashSet::new();
+    for PackageComponentMetadata { package, .. } in packages {
+        let resolution = resolution_map.get(&package.id).expect("missing resolution");
+        for dependency in resolution
+            .target_resolutions
+            .values()
+            .chain(resolution.resolutions.values())
+        {
+            let DependencyResolution::Registry(res) = dependency else {
+                continue;
+            };
+
+            let digest = res.digest.to_string();
+            if cache_dir.join(&digest).is_file() || !fetched.insert(digest) {
+                continue;
+            }
+
+            dependency.fetch().await?;
+            config.emit(|| BuildEvent::PackageDownloaded {
+                name: res.package.to_string(),
+                version: res.version.clone(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
 async fn generate_package_bindings(
     config: &Config,
     resolution: &PackageDependencyResolution<'_>,
-    cwd: &Path,
-) -> Result<HashMap<String, String>> {
+    target_dir: &Path,
+    profile: &str,
+    state: &mut WorkspaceState,
+    cache: &mut TargetCache,
+    stats: Option<&mut BuildStats>,
+) -> Result<PackageBindings> {
     if !resolution.metadata.section_present && resolution.metadata.target_path().is_none() {
         log::debug!(
             "skipping generating bindings for package `{name}`",
             name = resolution.metadata.name
         );
-        return Ok(HashMap::new());
+        return Ok(PackageBindings::default());
+    }
+
+    let imports_path = import_name_map_path(target_dir, &resolution.metadata.name);
+
+    let bindings_path = bindings_path(resolution.metadata);
+    let output_dir = bindings_path.parent().unwrap();
+
+    let state = state.package_mut(&resolution.metadata.name);
+    state.target_dependencies = resolution
+        .target_resolutions
+        .iter()
+        .map(|(name, res)| (name.to_string(), res.into()))
+        .collect();
+    state.dependencies = resolution
+        .resolutions
+        .iter()
+        .map(|(name, res)| (name.to_string(), res.into()))
+        .collect();
+
+    // Skip generating the bindings if none of their inputs changed since they
+    // were last generated and the bindings file was left untouched.
+    let inputs = state.bindings_inputs(&HostFileSystem, resolution.metadata, profile)?;
+    if let Some(bindings) = &state.bindings {
+        if bindings.is_fresh(&HostFileSystem, &inputs, &bindings_path) {
+            log::debug!(
+                "bindings for package `{name}` are up-to-date",
+                name = resolution.metadata.name
+            );
+
+            if let Some(stats) = stats {
+                stats.record_bindings(&fs::read_to_string(&bindings_path).with_context(|| {
+                    format!(
+                        "failed to read bindings file `{path}`",
+                        path = bindings_path.display()
+                    )
+                })?);
+            }
+
+            let import_name_map = bindings.import_name_map.clone().into_iter().collect();
+            write_import_name_map(&imports_path, &import_name_map)?;
+            return Ok(PackageBindings {
+                import_name_map,
+                exports: Some(bindings.exports.clone()),
+            });
+        }
+    }
+
+    for name in &resolution.well_known_target_dependencies {
+        let version = resolution.target_resolutions[name]
+            .version()
+            .expect("well-known packages are resolved from a registry");
+        config.terminal().note(format!(
+            "using `{name}` v{version} from the registry for the local target of package \
+             `{package}`; add it to `[package.metadata.component.target.dependencies]` to \
+             choose its version",
+            package = resolution.metadata.name
+        ))?;
     }
 
     // If there is no wit files and no dependencies, stop generating the bindings file for it.
-    let (generator, import_name_map) = match BindingsGenerator::new(resolution).await? {
-        Some(v) => v,
-        None => return Ok(HashMap::new()),
-    };
+    let (generator, import_name_map) =
+        match BindingsGenerator::new(resolution, target_dir, cache, Some(profile)).await? {
+            Some(v) => v,
+            None => {
+                state.bindings = None;
+                write_import_name_map(&imports_path, &HashMap::new())?;
+                return Ok(PackageBindings::default());
+            }
+        };
+    write_import_name_map(&imports_path, &import_name_map)?;
 
-    // TODO: make the output path configurable
-    let output_dir = resolution
-        .metadata
-        .manifest_path
-        .parent()
-        .unwrap()
-        .join("src");
-    let bindings_path = output_dir.join("bindings.rs");
+    let (resolve, world) = generator.target_world();
+    let exports = world_export_names(resolve, world);
+    let world = world_name(resolve, world);
 
+    let cwd =
+        env::current_dir().with_context(|| "couldn't get the current directory of the process")?;
     config.terminal().status(
         "Generating",
         format!(
             "bindings for {name} ({path})",
             name = resolution.metadata.name,
             path = bindings_path
-                .strip_prefix(cwd)
+                .strip_prefix(&cwd)
                 .unwrap_or(&bindings_path)
                 .display()
         ),
     )?;
 
-    let bindings = generator.generate()?;
-    fs::create_dir_all(&output_dir).with_context(|| {
+    let source_dependencies = wit_source_dependencies(output_dir, generator.source_files());
+    let mut bindings = generator.generate()?;
+    bindings.push_str(&source_dependencies);
+    if let Some(stats) = stats {
+        stats.record_bindings(&bindings);
+    }
+
+    fs::create_dir_all(output_dir).with_context(|| {
         format!(
             "failed to create output directory `{path}`",
             path = output_dir.display()
         )
     })?;
-    if fs::read_to_string(&bindings_path).unwrap_or_default() != bindings {
-        fs::write(&bindings_path, bindings).with_context(|| {
-            format!(
-                "failed to write bindings file `{path}`",
-                path = bindings_path.display()
-            )
-        })?;
+    write_if_changed(&HostFileSystem, &bindings_path, bindings.as_bytes()).with_context(|| {
+        format!(
+            "failed to write bindings file `{path}`",
+            path = bindings_path.display()
+        )
+    })?;
+
+    state.bindings = Some(BindingsState {
+        inputs,
+        world,
+        bindings: content_hash(bindings.as_bytes()),
+        import_name_map: import_name_map
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        exports: exports.clone(),
+    });
+
+    config.emit(|| BuildEvent::BindingsGenerated {
+        package: resolution.metadata.name.clone(),
+        path: bindings_path.clone(),
+    });
+
+    Ok(PackageBindings {
+        import_name_map,
+        exports: Some(exports),
+    })
+}
+
+/// Renders the declarations that make rustc track the local WIT files of the
+/// bindings as inputs of the crate.
+///
+/// Each file is included in an unused constant, which is not emitted, so that
+/// rustc records it in the crate's dep-info and cargo rebuilds the crate when
+/// it changes. Paths are relative to the bindings file so that the bindings
+/// do not depend on the location of the package.
+fn wit_source_dependencies(bindings_dir: &Path, source_files: &[PathBuf]) -> String {
+    let mut paths: Vec<_> = source_files
+        .iter()
+        .map(|file| pathdiff::diff_paths(file, bindings_dir).unwrap_or_else(|| file.clone()))
+        .map(|path| path.display().to_string().replace('\\', "/"))
+        .collect();
+    paths.sort();
+    paths.dedup();
+
+    if paths.is_empty() {
+        return String::new();
     }
 
-    Ok(import_name_map)
+    let mut source = String::from(
+        "\n// The WIT files the bindings were generated from; including them makes\n\
+         // cargo rebuild the crate when they change.\n",
+    );
+    for path in paths {
+        source.push_str(&format!("const _: &[u8] = include_bytes!({path:?});\n"));
+    }
+
+    source
 }
 
-fn adapter_bytes(
-    config: &Config,
+/// Determines if a core module defines or imports a 64-bit memory.
+fn uses_memory64(bytes: &[u8]) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::ImportSection(imports) => {
+                for import in imports {
+                    if let TypeRef::Memory(ty) = import?.ty {
+                        if ty.memory64 {
+                            return Ok(true);
+                        }
+                    }
+                }
+            }
+            Payload::MemorySection(memories) => {
+                for memory in memories {
+                    if memory?.memory64 {
+                        return Ok(true);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(false)
+}
+
+/// Gets the qualified name of a world (e.g. `wasi:cli/command@0.2.0`).
+fn world_name(resolve: &Resolve, world: WorldId) -> String {
+    let world = &resolve.worlds[world];
+    match world.package {
+        Some(package) => {
+            let name = &resolve.packages[package].name;
+            match &name.version {
+                Some(version) => format!(
+                    "{namespace}:{package}/{world}@{version}",
+                    namespace = name.namespace,
+                    package = name.name,
+                    world = world.name
+                ),
+                None => format!("{name}/{world}", world = world.name),
+            }
+        }
+        None => world.name.clone(),
+    }
+}
+
+/// Gets the names of the functions and interfaces exported by a world.
+fn world_export_names(resolve: &Resolve, world: WorldId) -> BTreeSet<String> {
+    resolve.worlds[world]
+        .exports
+        .iter()
+        .filter(|(_, item)| !matches!(item, WorldItem::Type(_)))
+        .map(|(key, _)| resolve.name_world_key(key))
+        .collect()
+}
+
+/// Gets the names of the functions and instances exported by a component.
+///
+/// Only the export section of the outer component is read, so this doesn't
+/// require the imports of the component to be decodable as WIT (which isn't
+/// the case for the `unlocked-dep` imports of component dependencies).
+fn component_export_names(component: &[u8]) -> Result<BTreeSet<String>> {
+    let mut names = BTreeSet::new();
+    let mut depth = 0;
+    for payload in Parser::new(0).parse_all(component) {
+        match payload? {
+            Payload::Version { encoding, .. } if depth == 0 && encoding != Encoding::Component => {
+                bail!("the output is not a component");
+            }
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => depth += 1,
+            Payload::End(_) => depth -= 1,
+            Payload::ComponentExportSection(reader) if depth == 0 => {
+                for export in reader {
+                    let export = export?;
+                    if matches!(
+                        export.kind,
+                        ComponentExternalKind::Func | ComponentExternalKind::Instance
+                    ) {
+                        names.insert(export.name.0.to_string());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(names)
+}
+
+/// Checks that a component exports every function and interface of its
+/// target world.
+///
+/// If `strict` is true, the component must also not export anything else.
+///
+/// The `wasi:cli/run` export of command components is provided by the
+/// adapter rather than the target world and is ignored.
+fn check_world_exports(
+    component: &[u8],
+    expected: &BTreeSet<String>,
+    is_command: bool,
+    strict: bool,
+) -> Result<()> {
+    let actual: BTreeSet<_> = component_export_names(component)
+        .context("failed to read the exports of the component")?
+        .into_iter()
+        .filter(|name| !(is_command && name.starts_with("wasi:cli/run@")))
+        .collect();
+
+    let missing: Vec<_> = expected.difference(&actual).collect();
+    let extra: Vec<_> = actual.difference(expected).collect();
+    if missing.is_empty() && (extra.is_empty() || !strict) {
+        return Ok(());
+    }
+
+    let list = |names: Vec<&String>| {
+        names
+            .iter()
+            .map(|name| format!("`{name}`"))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut message = String::new();
+    let hint = if missing.is_empty() {
+        "the extra exports may come from the bindings of a dependency; remove them or disable \
+         `strict-exports` in the `[package.metadata.component]` table"
+    } else {
+        message.push_str(&format!(
+            "missing exports: {missing}\n",
+            missing = list(missing)
+        ));
+        "ensure the crate exports the component with the generated `bindings` module rather \
+         than bindings generated for another world"
+    };
+
+    if !extra.is_empty() {
+        message.push_str(&format!(
+            "exports not in the target world: {extra}\n",
+            extra = list(extra)
+        ));
+    }
+
+    bail!("{message}{hint}")
+}
+
+/// Adds the configured `processed-by` producers entry to a component.
+fn add_processed_by(component: &[u8], processed_by: &ProcessedBy) -> Result<Vec<u8>> {
+    let (name, version) = match processed_by {
+        ProcessedBy::Enabled(false) => return Ok(component.to_vec()),
+        ProcessedBy::Enabled(true) => (
+            env!("CARGO_PKG_NAME"),
+            option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION")),
+        ),
+        ProcessedBy::Custom { name, version } => (name.as_str(), version.as_str()),
+    };
+
+    let mut producers = wasm_metadata::Producers::empty();
+    producers.add("processed-by", name, version);
+    producers.add_to_wasm(component)
+}
+
+/// Removes the producers sections of a component and of the modules and
+/// components nested in it.
+///
+/// The producers sections record the versions of the tools that produced a
+/// component, so removing them makes the output independent of the versions
+/// of `cargo-component` and `wit-component` used.
+fn strip_producers_sections(component: &[u8]) -> Result<Vec<u8>> {
+    let mut output = Vec::new();
+    let mut stack = Vec::new();
+    for payload in Parser::new(0).parse_all(component) {
+        let payload = payload?;
+        match &payload {
+            Payload::Version { encoding, .. } => {
+                output.extend_from_slice(match encoding {
+                    Encoding::Component => &wasm_encoder::Component::HEADER,
+                    Encoding::Module => &wasm_encoder::Module::HEADER,
+                });
+                continue;
+            }
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => {
+                stack.push(mem::take(&mut output));
+                continue;
+            }
+            Payload::End(_) => {
+                let Some(mut parent) = stack.pop() else {
+                    break;
+                };
+
+                parent.push(if output.starts_with(&wasm_encoder::Component::HEADER) {
+                    wasm_encoder::ComponentSectionId::Component as u8
+                } else {
+                    wasm_encoder::ComponentSectionId::CoreModule as u8
+                });
+                output.encode(&mut parent);
+                output = parent;
+                continue;
+            }
+            Payload::CustomSection(reader) if reader.name() == "producers" => continue,
+            _ => {}
+        }
+
+        if let Some((id, range)) = payload.as_section() {
+            output.push(id);
+            component[range].encode(&mut output);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Determines if a core module imports from WASI preview1 and therefore
+/// requires an adapter.
+fn imports_wasi_preview1(bytes: &[u8]) -> Result<bool> {
+    for payload in Parser::new(0).parse_all(bytes) {
+        if let Payload::ImportSection(imports) = payload? {
+            for import in imports {
+                if import?.module == "wasi_snapshot_preview1" {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+/// Determines if an import of a core module is stubbed by
+/// `--stub-missing-imports`.
+///
+/// WASI imports are provided by the host and the imports of the adapter, the
+/// runtime and the main module are satisfied by the component encoder, so only
+/// the imports of the target world and of its dependencies are stubbed.
+fn is_stubbable_import(module: &str) -> bool {
+    let module = module.strip_prefix("cm32p2|").unwrap_or(module);
+    !(module.starts_with("wasi:")
+        || module.starts_with("[export]")
+        || matches!(
+            module,
+            "wasi_snapshot_preview1" | "env" | "__main_module__" | "cm32p2"
+        ))
+}
+
+/// Represents a core module stubbing the imports of a module name.
+struct StubModule {
+    /// The imported module name being stubbed.
+    module: String,
+    /// The names of the stubbed functions.
+    names: Vec<String>,
+    /// The encoded stub module.
+    bytes: Vec<u8>,
+}
+
+/// Generates core modules stubbing the function imports of a core module.
+///
+/// A stub module is generated for each stubbable module name imported; it
+/// exports a function for each of its imported functions that traps when
+/// called. The stub modules are used as adapters when encoding the component,
+/// so the stubbed imports do not become imports of the component.
+fn stub_modules(bytes: &[u8]) -> Result<Vec<StubModule>> {
+    let mut types = Vec::new();
+    let mut imports: IndexMap<String, Vec<(String, wasmparser::FuncType)>> = IndexMap::new();
+    for payload in Parser::new(0).parse_all(bytes) {
+        match payload? {
+            Payload::TypeSection(reader) => {
+                for group in reader {
+                    for ty in group?.into_types() {
+                        types.push(match ty.composite_type.inner {
+                            wasmparser::CompositeInnerType::Func(ty) => Some(ty),
+                            _ => None,
+                        });
+                    }
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import?;
+                    let TypeRef::Func(index) = import.ty else {
+                        continue;
+                    };
+
+                    if !is_stubbable_import(import.module) {
+                        continue;
+                    }
+
+                    let ty = types
+                        .get(index as usize)
+                        .cloned()
+                        .flatten()
+                        .with_context(|| {
+                            format!(
+                                "import `{name}` of `{module}` is not a function",
+                                name = import.name,
+                                module = import.module
+                            )
+                        })?;
+                    imports
+                        .entry(import.module.to_string())
+                        .or_default()
+                        .push((import.name.to_string(), ty));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let val_type = |ty: &wasmparser::ValType| {
+        Ok(match ty {
+            wasmparser::ValType::I32 => wasm_encoder::ValType::I32,
+            wasmparser::ValType::I64 => wasm_encoder::ValType::I64,
+            wasmparser::ValType::F32 => wasm_encoder::ValType::F32,
+            wasmparser::ValType::F64 => wasm_encoder::ValType::F64,
+            wasmparser::ValType::V128 => wasm_encoder::ValType::V128,
+            wasmparser::ValType::Ref(_) => bail!("imports with reference types cannot be stubbed"),
+        })
+    };
+
+    imports
+        .into_iter()
+        .map(|(module, funcs)| {
+            let mut types = wasm_encoder::TypeSection::new();
+            let mut functions = wasm_encoder::FunctionSection::new();
+            let mut exports = wasm_encoder::ExportSection::new();
+            let mut code = wasm_encoder::CodeSection::new();
+            for (index, (name, ty)) in funcs.iter().enumerate() {
+                let params = ty
+                    .params()
+                    .iter()
+                    .map(val_type)
+                    .collect::<Result<Vec<_>>>()?;
+                let results = ty
+                    .results()
+                    .iter()
+                    .map(val_type)
+                    .collect::<Result<Vec<_>>>()?;
+                types.ty().function(params, results);
+                functions.function(index as u32);
+                exports.export(name, wasm_encoder::ExportKind::Func, index as u32);
+
+                let mut body = wasm_encoder::Function::new([]);
+                body.instruction(&wasm_encoder::Instruction::Unreachable);
+                body.instruction(&wasm_encoder::Instruction::End);
+                code.function(&body);
+            }
+
+            let mut stub = wasm_encoder::Module::new();
+            stub.section(&types)
+                .section(&functions)
+                .section(&exports)
+                .section(&code);
+
+            let names = funcs.into_iter().map(|(name, _)| name).collect();
+            Ok(StubModule {
+                module,
+                names,
+                bytes: stub.finish(),
+            })
+        })
+        .collect()
+}
+
+/// Gets the adapter configured for the given package.
+///
+/// The adapter is not checked against the module being componentized.
+fn configured_adapter(
     metadata: &ComponentMetadata,
     is_command: bool,
 ) -> Result<Cow<'static, [u8]>> {
     if let Some(adapter) = &metadata.section.adapter {
-        if metadata.section.proxy {
-            config.terminal().warn(
-                "ignoring `proxy` setting due to `adapter` setting being present in `Cargo.toml`",
-            )?;
-        }
+        let bytes = fs::read(adapter).with_context(|| {
+            format!(
+                "failed to read module adapter `{path}`",
+                path = adapter.display()
+            )
+        })?;
 
-        return Ok(fs::read(adapter)
-            .with_context(|| {
-                format!(
-                    "failed to read module adapter `{path}`",
-                    path = adapter.display()
-                )
-            })?
-            .into());
+        return Ok(bytes.into());
     }
 
     if is_command {
-        if metadata.section.proxy {
-            config
-                .terminal()
-                .warn("ignoring `proxy` setting in `Cargo.toml` for command component")?;
-        }
-
         Ok(Cow::Borrowed(
             wasi_preview1_component_adapter_provider::WASI_SNAPSHOT_PREVIEW1_COMMAND_ADAPTER,
         ))
@@ -910,21 +2915,114 @@ fn adapter_bytes(
     }
 }
 
+fn adapter_bytes(
+    config: &Config,
+    metadata: &ComponentMetadata,
+    is_command: bool,
+    memory64: bool,
+) -> Result<Cow<'static, [u8]>> {
+    let bytes = configured_adapter(metadata, is_command)?;
+
+    if let Some(adapter) = &metadata.section.adapter {
+        if metadata.section.proxy {
+            config.terminal().warn(
+                "ignoring `proxy` setting due to `adapter` setting being present in `Cargo.toml`",
+            )?;
+        }
+
+        let adapter_memory64 = uses_memory64(&bytes).with_context(|| {
+            format!(
+                "failed to load adapter module `{path}`",
+                path = adapter.display()
+            )
+        })?;
+
+        if adapter_memory64 != memory64 {
+            bail!(
+                "module adapter `{path}` uses a {adapter}-bit memory but the module uses a {module}-bit memory",
+                path = adapter.display(),
+                adapter = if memory64 { 32 } else { 64 },
+                module = if memory64 { 64 } else { 32 },
+            );
+        }
+
+        return Ok(bytes);
+    }
+
+    if memory64 {
+        bail!(
+            "the built-in WASI adapters only support 32-bit memories; set `adapter` in the \
+             `[package.metadata.component]` table of `Cargo.toml` to a memory64 adapter"
+        );
+    }
+
+    if is_command && metadata.section.proxy {
+        config
+            .terminal()
+            .warn("ignoring `proxy` setting in `Cargo.toml` for command component")?;
+    }
+
+    Ok(bytes)
+}
+
+/// Removes the outputs that were componentized with a different adapter than
+/// the one now configured for their package.
+///
+/// Cargo copies the core modules of the removed outputs out again, even if
+/// they are fresh, so that they are componentized with the new adapter.
+fn remove_stale_components(
+    packages: &[PackageComponentMetadata<'_>],
+    state: &mut WorkspaceState,
+) -> Result<()> {
+    for PackageComponentMetadata { package, metadata } in packages {
+        let Some(package_state) = state.packages.get_mut(package.name.as_str()) else {
+            continue;
+        };
+
+        let mut stale = Vec::new();
+        for (path, adapter) in &package_state.adapters {
+            if content_hash(&configured_adapter(metadata, adapter.command)?) != adapter.digest {
+                stale.push(path.clone());
+            }
+        }
+
+        for path in stale {
+            log::debug!(
+                "removing component `{path}` created with a different adapter",
+                path = path.display()
+            );
+
+            package_state.adapters.remove(&path);
+            match fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("failed to remove component `{path}`", path = path.display())
+                    })
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Determines if the given artifact is componentized as a command component.
+fn is_command_artifact(artifact: &Artifact) -> bool {
+    artifact.profile.test || artifact.target.crate_types.contains(&CrateType::Bin)
+}
+
 fn componentize(
     config: &Config,
     (cargo_metadata, metadata): (&Metadata, &ComponentMetadata),
-    import_name_map: &HashMap<String, String>,
+    bindings: &PackageBindings,
     artifact: &Artifact,
-    path: &Path,
-    cwd: &Path,
-    bytes: &[u8],
-) -> Result<()> {
-    let is_command = artifact.profile.test
-        || artifact
-            .target
-            .crate_types
-            .iter()
-            .any(|t| *t == CrateType::Bin);
+    (path, cwd): (&Path, &Path),
+    (bytes, libraries): (&[u8], Vec<LinkedLibrary>),
+    cargo_args: &CargoArguments,
+) -> Result<Option<AdapterState>> {
+    let is_command = is_command_artifact(artifact);
 
     log::debug!(
         "componentizing WebAssembly module `{path}` as a {kind} component (fresh = {fresh})",
@@ -951,39 +3049,68 @@ fn componentize(
         )?;
     }
 
-    let mut encoder = ComponentEncoder::default()
-        .module(bytes)?
-        .import_name_map(import_name_map.clone())
-        .adapter(
-            "wasi_snapshot_preview1",
-            &adapter_bytes(config, metadata, is_command)?,
+    // Without the section the component has none of the imports and exports
+    // of the target world
+    if bindings.exports.is_some() && !has_component_type_section(bytes)? {
+        let reason = if metadata
+            .section
+            .bindings
+            .disable_custom_section_link_helpers
+        {
+            "the section was likely removed by the linker because \
+             `disable_custom_section_link_helpers` is set in \
+             `[package.metadata.component.bindings]`; remove the setting or reference the \
+             generated bindings from the crate"
+        } else {
+            "ensure the crate includes the generated bindings (e.g. with `mod bindings;`) and \
+             that the section is not stripped by the linker or by a post-processing tool"
+        };
+
+        config.terminal().warn(format!(
+            "module `{path}` has no `component-type` custom section describing the target world \
+             of `{manifest}`; {reason}",
+            path = path.strip_prefix(cwd).unwrap_or(path).display(),
+            manifest = metadata.manifest_path.display()
+        ))?;
+    }
+
+    let (component, adapter_state) = encode_component(
+        config,
+        metadata,
+        bytes,
+        EncodeOptions {
+            path,
+            command: is_command,
+            stub_missing_imports: cargo_args.stub_missing_imports,
+            print_status: !artifact.fresh,
+            import_name_map: bindings.import_name_map.clone(),
+            libraries,
+        },
+    )?;
+
+    if let Some(exports) = &bindings.exports {
+        check_world_exports(
+            &component,
+            exports,
+            is_command,
+            metadata.section.strict_exports,
         )
         .with_context(|| {
             format!(
-                "failed to load adapter module `{path}`",
-                path = metadata
-                    .section
-                    .adapter
-                    .as_deref()
-                    .unwrap_or_else(|| Path::new("<built-in>"))
-                    .display()
+                "output component `{path}` does not match the target world of `{manifest}`",
+                path = path.strip_prefix(cwd).unwrap_or(path).display(),
+                manifest = metadata.manifest_path.display()
             )
-        })?
-        .validate(true);
-
-    let mut producers = wasm_metadata::Producers::empty();
-    producers.add(
-        "processed-by",
-        env!("CARGO_PKG_NAME"),
-        option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION")),
-    );
+        })?;
+    }
 
-    let component = producers.add_to_wasm(&encoder.encode()?).with_context(|| {
-        format!(
-            "failed to add metadata to output component `{path}`",
-            path = path.display()
-        )
-    })?;
+    let component =
+        add_producers(&component, metadata, cargo_args.no_producers).with_context(|| {
+            format!(
+                "failed to add metadata to output component `{path}`",
+                path = path.display()
+            )
+        })?;
 
     // To make the write atomic, first write to a temp file and then rename the file
     let temp_dir = cargo_metadata.target_directory.join("tmp");
@@ -1008,9 +3135,352 @@ fn componentize(
         )
     })?;
 
+    config.emit(|| BuildEvent::Componentized {
+        path: path.to_path_buf(),
+        size: component.len() as u64,
+    });
+
+    write_emitted_modules(&cargo_args.emit, path, bytes, &component)?;
+
+    if let Some(budget) = metadata.section.max_size {
+        check_size_budget(
+            config,
+            metadata,
+            artifact,
+            (path, cwd),
+            component.len() as u64,
+            budget,
+        )?;
+    }
+
+    if let Some(plugin) = &metadata.section.plugin {
+        let manifest_path = path.with_extension("plugin.json");
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .with_context(|| format!("invalid component path `{path}`", path = path.display()))?;
+
+        log::debug!(
+            "writing plugin manifest `{path}`",
+            path = manifest_path.display()
+        );
+
+        let manifest = PluginManifest::new(metadata, plugin, file_name, &component)?;
+        fs::write(&manifest_path, manifest.to_json()?).with_context(|| {
+            format!(
+                "failed to write plugin manifest `{path}`",
+                path = manifest_path.display()
+            )
+        })?;
+    }
+
+    Ok(adapter_state)
+}
+
+/// Options for encoding a core module as a component.
+pub(crate) struct EncodeOptions<'a> {
+    /// The path of the output component.
+    pub path: &'a Path,
+    /// Whether the module is encoded as a command component.
+    pub command: bool,
+    /// Whether to stub the imports that the adapter does not satisfy.
+    pub stub_missing_imports: bool,
+    /// Whether to print the status of stubbed imports.
+    pub print_status: bool,
+    /// The names of the imports of the module, by the names used in the
+    /// component.
+    pub import_name_map: HashMap<String, String>,
+    /// The libraries to link the module with.
+    ///
+    /// If empty, the module is encoded as the only module of the component.
+    pub libraries: Vec<LinkedLibrary>,
+}
+
+/// A library linked into a component with shared-everything dynamic linking.
+pub(crate) struct LinkedLibrary {
+    /// The name of the library, as referenced by the `dylink.0` sections of
+    /// the modules that depend on it.
+    pub name: String,
+    /// The core module of the library.
+    pub bytes: Vec<u8>,
+    /// Whether the exports of the library can be looked up with `dlopen`.
+    pub dl_openable: bool,
+    /// Whether the library was up-to-date in the build.
+    pub fresh: bool,
+}
+
+/// Encodes a core module as a component with the adapter and WebAssembly
+/// proposals configured for a package.
+///
+/// This is shared by `cargo component build` and `cargo component embed`, so
+/// both encode the same module the same way.
+pub(crate) fn encode_component(
+    config: &Config,
+    metadata: &ComponentMetadata,
+    bytes: &[u8],
+    options: EncodeOptions,
+) -> Result<(Vec<u8>, Option<AdapterState>)> {
+    if !options.libraries.is_empty() {
+        return link_component(config, metadata, bytes, options);
+    }
+
+    let memory64 = uses_memory64(bytes)?;
+    let mut encoder = ComponentEncoder::default()
+        .module(bytes)?
+        .import_name_map(options.import_name_map);
+
+    // Modules that don't import WASI preview1 (e.g. for `wasm64-unknown-unknown`)
+    // don't need an adapter
+    let mut adapter_state = None;
+    if !memory64 || imports_wasi_preview1(bytes)? {
+        let adapter = adapter_bytes(config, metadata, options.command, memory64)?;
+        adapter_state = Some(AdapterState {
+            command: options.command,
+            digest: content_hash(&adapter),
+        });
+        encoder = encoder
+            .adapter("wasi_snapshot_preview1", &adapter)
+            .with_context(|| {
+                format!(
+                    "failed to load adapter module `{path}`",
+                    path = metadata
+                        .section
+                        .adapter
+                        .as_deref()
+                        .unwrap_or_else(|| Path::new("<built-in>"))
+                        .display()
+                )
+            })?;
+    }
+
+    if options.stub_missing_imports {
+        for StubModule {
+            module,
+            names,
+            bytes: stub,
+        } in stub_modules(bytes)?
+        {
+            if options.print_status {
+                config.terminal().status_with_color(
+                    "Stubbing",
+                    format!(
+                        "import `{module}` ({names}) with functions that trap",
+                        names = names.join(", ")
+                    ),
+                    Colors::Yellow,
+                )?;
+            }
+
+            encoder = encoder
+                .adapter(&module, &stub)
+                .with_context(|| format!("failed to stub import `{module}`"))?;
+        }
+    }
+
+    // Components are validated below with the configured proposals
+    let component = encoder.validate(false).encode().with_context(|| {
+        if memory64 {
+            format!(
+                "failed to encode 64-bit memory module `{path}` as a component",
+                path = options.path.display()
+            )
+        } else {
+            format!(
+                "failed to encode module `{path}` as a component for the target world of `{manifest}`",
+                path = options.path.display(),
+                manifest = metadata.manifest_path.display()
+            )
+        }
+    })?;
+
+    // Validate the component with the proposals configured for the package
+    // rather than the encoder's defaults
+    Validator::new_with_features(metadata.section.features.wasm_features()?)
+        .validate_all(&component)
+        .with_context(|| {
+            format!(
+                "failed to validate output component `{path}`",
+                path = options.path.display()
+            )
+        })?;
+
+    Ok((component, adapter_state))
+}
+
+/// Links a core module with the libraries configured for a package into a
+/// component.
+///
+/// Every module must be a dynamic library with a `dylink.0` section; the
+/// module itself is linked first, followed by the libraries in the configured
+/// order.
+fn link_component(
+    config: &Config,
+    metadata: &ComponentMetadata,
+    bytes: &[u8],
+    options: EncodeOptions,
+) -> Result<(Vec<u8>, Option<AdapterState>)> {
+    if !options.import_name_map.is_empty() {
+        bail!(
+            "components importing dependencies by their locked or unlocked names cannot be \
+             linked with libraries; remove the `link` table from `{manifest}` or the \
+             corresponding dependencies",
+            manifest = metadata.manifest_path.display()
+        );
+    }
+
+    let name = format!(
+        "lib{name}.so",
+        name = options
+            .path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(&metadata.name)
+    );
+
+    let mut linker = wit_component::Linker::default()
+        .validate(false)
+        .stub_missing_functions(options.stub_missing_imports)
+        .library(&name, bytes, false)?;
+
+    for library in &options.libraries {
+        linker = linker.library(&library.name, &library.bytes, library.dl_openable)?;
+    }
+
+    let adapter = adapter_bytes(config, metadata, options.command, uses_memory64(bytes)?)?;
+    let adapter_state = AdapterState {
+        command: options.command,
+        digest: content_hash(&adapter),
+    };
+    linker = linker.adapter("wasi_snapshot_preview1", &adapter)?;
+
+    // Components are validated below with the configured proposals
+    let component = linker.encode().with_context(|| {
+        format!(
+            "failed to link module `{path}` with libraries {libraries} as a component for the \
+             target world of `{manifest}`",
+            path = options.path.display(),
+            libraries = options
+                .libraries
+                .iter()
+                .map(|l| format!("`{name}`", name = l.name))
+                .collect::<Vec<_>>()
+                .join(", "),
+            manifest = metadata.manifest_path.display()
+        )
+    })?;
+
+    Validator::new_with_features(metadata.section.features.wasm_features()?)
+        .validate_all(&component)
+        .with_context(|| {
+            format!(
+                "failed to validate output component `{path}`",
+                path = options.path.display()
+            )
+        })?;
+
+    Ok((component, Some(adapter_state)))
+}
+
+/// Adds or strips the producers sections of a component as configured for a
+/// package.
+pub(crate) fn add_producers(
+    component: &[u8],
+    metadata: &ComponentMetadata,
+    no_producers: bool,
+) -> Result<Vec<u8>> {
+    if no_producers || metadata.section.producers.strip {
+        strip_producers_sections(component)
+    } else {
+        add_processed_by(component, &metadata.section.producers.processed_by)
+    }
+}
+
+/// Writes the modules requested with `--emit` next to an output component.
+///
+/// The core module is written as `<name>.core.wasm` and each adapter module
+/// embedded in the component is written as `<name>.<adapter>.wasm`.
+fn write_emitted_modules(
+    emit: &[Emit],
+    path: &Path,
+    module: &[u8],
+    component: &[u8],
+) -> Result<()> {
+    let write = |path: PathBuf, bytes: &[u8]| {
+        log::debug!("writing emitted module `{path}`", path = path.display());
+        fs::write(&path, bytes).with_context(|| {
+            format!(
+                "failed to write emitted module `{path}`",
+                path = path.display()
+            )
+        })
+    };
+
+    if emit.contains(&Emit::CoreModule) {
+        write(path.with_extension("core.wasm"), module)?;
+    }
+
+    if emit.contains(&Emit::AdapterModule) {
+        let children = match wasm_metadata::Metadata::from_binary(component)? {
+            wasm_metadata::Metadata::Component { children, .. } => children,
+            wasm_metadata::Metadata::Module { .. } => Vec::new(),
+        };
+
+        for child in children {
+            if let wasm_metadata::Metadata::Module {
+                name: Some(name),
+                range,
+                ..
+            } = *child
+            {
+                if let Some(adapter) = name.strip_prefix("wit-component:adapter:") {
+                    write(
+                        path.with_extension(format!("{adapter}.wasm")),
+                        &component[range],
+                    )?;
+                }
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// Checks that a component does not exceed the size budget of its package.
+fn check_size_budget(
+    config: &Config,
+    metadata: &ComponentMetadata,
+    artifact: &Artifact,
+    (path, cwd): (&Path, &Path),
+    size: u64,
+    budget: SizeBudget,
+) -> Result<()> {
+    if size <= budget.size {
+        return Ok(());
+    }
+
+    let hint = if artifact.profile.opt_level == "0" {
+        "build with `--release` to reduce the size of the component, or rerun with `--stats` \
+         to compare the size of the component with the size of its core module"
+    } else {
+        "rerun with `--stats` to compare the size of the component with the size of its core \
+         module"
+    };
+
+    let message = format!(
+        "component `{path}` is {size}, which exceeds the maximum size of {max} configured for \
+         package `{name}`\n\nhint: {hint}",
+        path = path.strip_prefix(cwd).unwrap_or(path).display(),
+        size = stats::format_bytes(size),
+        max = stats::format_bytes(budget.size),
+        name = metadata.name,
+    );
+
+    match budget.level {
+        SizeBudgetLevel::Warn => config.terminal().warn(message),
+        SizeBudgetLevel::Deny => bail!(message),
+    }
+}
+
 /// Represents options for a publish operation.
 pub struct PublishOptions<'a> {
     /// The package to publish.
@@ -1025,6 +3495,8 @@ pub struct PublishOptions<'a> {
     pub path: &'a Path,
     /// Whether to perform a dry run or not.
     pub dry_run: bool,
+    /// Whether to initialize the package if it does not yet exist in the registry.
+    pub init: bool,
 }
 
 fn add_registry_metadata(package: &Package, bytes: &[u8], path: &Path) -> Result<Vec<u8>> {
@@ -1107,16 +3579,55 @@ pub async fn publish(
         format!("component {path}", path = options.path.display()),
     )?;
 
-    let (name, version) = client
+    let result = client
         .client()?
         .publish_release_data(
-            Box::pin(std::io::Cursor::new(bytes)),
+            Box::pin(std::io::Cursor::new(bytes.clone())),
             PublishOpts {
                 package: Some((options.name.to_owned(), options.version.to_owned())),
                 registry: options.registry.cloned(),
             },
         )
-        .await?;
+        .await;
+
+    let (name, version) = match result {
+        Err(wasm_pkg_client::Error::RegistryError(e))
+            if matches!(
+                e.downcast_ref::<ClientError>(),
+                Some(ClientError::MustInitializePackage { .. })
+            ) =>
+        {
+            let registry = options
+                .registry
+                .or_else(|| config.pkg_config().resolve_registry(options.name))
+                .with_context(|| {
+                    format!(
+                        "no registry is configured for package `{name}`",
+                        name = options.name
+                    )
+                })?;
+
+            if !options.init {
+                confirm_package_init(config, options.name, registry)?;
+            }
+
+            config.terminal().status(
+                "Initializing",
+                format!(
+                    "package `{name}` in registry `{registry}`",
+                    name = options.name
+                ),
+            )?;
+
+            // The warg backend of the client holds the lock on the local
+            // registry storage, which the client used to initialize the
+            // package must acquire
+            drop(client);
+            init_and_publish(config, registry, options.name, options.version, bytes).await?;
+            (options.name.clone(), options.version.clone())
+        }
+        result => result?,
+    };
 
     config
         .terminal()
@@ -1125,6 +3636,95 @@ pub async fn publish(
     Ok(())
 }
 
+/// Asks the user whether a package that does not exist in a registry should be
+/// initialized.
+///
+/// Fails if the user declines or cannot be asked because stdin is not a
+/// terminal or output is quiet.
+fn confirm_package_init(config: &Config, name: &PackageRef, registry: &Registry) -> Result<()> {
+    match config.terminal().confirm(format_args!(
+        "package `{name}` does not exist in registry `{registry}`; \
+         initialize it and publish the release?"
+    ))? {
+        Some(true) => Ok(()),
+        Some(false) => bail!(
+            "package `{name}` has not been initialized in registry `{registry}`; \
+             use the `--init` option to initialize it and publish the release"
+        ),
+        None => bail!(
+            "package `{name}` has not been initialized in registry `{registry}` and \
+             initializing it requires confirmation from an interactive terminal; \
+             use the `--init` option to initialize it and publish the release"
+        ),
+    }
+}
+
+/// Initializes a package in a warg registry and publishes its first release as
+/// a single record.
+async fn init_and_publish(
+    config: &Config,
+    registry: &Registry,
+    name: &PackageRef,
+    version: &Version,
+    bytes: Vec<u8>,
+) -> Result<()> {
+    let warg_conf = match config.pkg_config().registry_config(registry) {
+        Some(reg_config) => WargRegistryConfig::try_from(reg_config)?,
+        None => WargRegistryConfig::default(),
+    };
+
+    let url = wasm_pkg_client::RegistryMetadata::fetch_or_default(registry)
+        .await
+        .protocol_config::<serde_json::Value>("warg")?
+        .and_then(|warg| warg.get("url")?.as_str().map(ToOwned::to_owned))
+        .unwrap_or_else(|| {
+            if registry.host() == "localhost" || registry.host() == "127.0.0.1" {
+                format!("http://{registry}")
+            } else {
+                format!("https://{registry}")
+            }
+        });
+
+    let client = FileSystemClient::new_with_config(
+        Some(&url),
+        &warg_conf.client_config,
+        warg_conf.auth_token,
+    )
+    .await?;
+
+    let content = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::iter([Ok(bytes::Bytes::from(bytes))])),
+            None,
+        )
+        .await?;
+
+    let package = PackageName::new(name.to_string())?;
+    let info = PublishInfo {
+        name: package.clone(),
+        head: None,
+        entries: vec![
+            PublishEntry::Init,
+            PublishEntry::Release {
+                version: version.clone(),
+                content,
+            },
+        ],
+    };
+
+    let record_id = match &warg_conf.signing_key {
+        Some(key) => client.publish_with_info(key, info).await?,
+        None => client.sign_with_keyring_and_publish(Some(info)).await?,
+    };
+
+    client
+        .wait_for_publish(&package, &record_id, Duration::from_secs(1))
+        .await?;
+
+    Ok(())
+}
+
 /// Update the dependencies in the lock file.
 ///
 /// This updates only `Cargo-component.lock`.
@@ -1138,7 +3738,8 @@ pub async fn update_lockfile(
     dry_run: bool,
 ) -> Result<()> {
     // Read the current lock file and generate a new one
-    let map = create_resolution_map(client, packages, None).await?;
+    let map =
+        create_resolution_map(client, &config.cache_dir()?, packages, None, None, true).await?;
 
     let file_lock = acquire_lock_file_ro(config.terminal(), metadata)?;
     let orig_lock_file = file_lock
@@ -1156,111 +3757,14 @@ pub async fn update_lockfile(
 
     let new_lock_file = map.to_lock_file();
 
-    for old_pkg in &orig_lock_file.packages {
-        let new_pkg = match new_lock_file
-            .packages
-            .binary_search_by_key(&old_pkg.key(), LockedPackage::key)
-            .map(|index| &new_lock_file.packages[index])
-        {
-            Ok(pkg) => pkg,
-            Err(_) => {
-                // The package is no longer a dependency
-                for old_ver in &old_pkg.versions {
-                    config.terminal().status_with_color(
-                        if dry_run { "Would remove" } else { "Removing" },
-                        format!(
-                            "dependency `{name}` v{version}",
-                            name = old_pkg.name,
-                            version = old_ver.version,
-                        ),
-                        Colors::Red,
-                    )?;
-                }
-                continue;
-            }
-        };
-
-        for old_ver in &old_pkg.versions {
-            let new_ver = match new_pkg
-                .versions
-                .binary_search_by_key(&old_ver.key(), LockedPackageVersion::key)
-                .map(|index| &new_pkg.versions[index])
-            {
-                Ok(ver) => ver,
-                Err(_) => {
-                    // The version of the package is no longer a dependency
-                    config.terminal().status_with_color(
-                        if dry_run { "Would remove" } else { "Removing" },
-                        format!(
-                            "dependency `{name}` v{version}",
-                            name = old_pkg.name,
-                            version = old_ver.version,
-                        ),
-                        Colors::Red,
-                    )?;
-                    continue;
-                }
-            };
-
-            // The version has changed
-            if old_ver.version != new_ver.version {
-                config.terminal().status_with_color(
-                    if dry_run { "Would update" } else { "Updating" },
-                    format!(
-                        "dependency `{name}` v{old} -> v{new}",
-                        name = old_pkg.name,
-                        old = old_ver.version,
-                        new = new_ver.version
-                    ),
-                    Colors::Cyan,
-                )?;
-            }
-        }
-    }
-
-    for new_pkg in &new_lock_file.packages {
-        let old_pkg = match orig_lock_file
-            .packages
-            .binary_search_by_key(&new_pkg.key(), LockedPackage::key)
-            .map(|index| &orig_lock_file.packages[index])
-        {
-            Ok(pkg) => pkg,
-            Err(_) => {
-                // The package is new
-                for new_ver in &new_pkg.versions {
-                    config.terminal().status_with_color(
-                        if dry_run { "Would add" } else { "Adding" },
-                        format!(
-                            "dependency `{name}` v{version}",
-                            name = new_pkg.name,
-                            version = new_ver.version,
-                        ),
-                        Colors::Green,
-                    )?;
-                }
-                continue;
-            }
-        };
-
-        for new_ver in &new_pkg.versions {
-            if old_pkg
-                .versions
-                .binary_search_by_key(&new_ver.key(), LockedPackageVersion::key)
-                .map(|index| &old_pkg.versions[index])
-                .is_err()
-            {
-                // The version is new
-                config.terminal().status_with_color(
-                    if dry_run { "Would add" } else { "Adding" },
-                    format!(
-                        "dependency `{name}` v{version}",
-                        name = new_pkg.name,
-                        version = new_ver.version,
-                    ),
-                    Colors::Green,
-                )?;
-            }
-        }
+    // The changes are printed when acquiring the lock file if an update is not allowed
+    if dry_run || lock_update_allowed {
+        print_lock_file_changes(
+            config.terminal(),
+            &lock_file_changes(&orig_lock_file, &new_lock_file),
+            dry_run,
+            false,
+        )?;
     }
 
     if dry_run {
@@ -1271,8 +3775,13 @@ pub async fn update_lockfile(
         // Update the lock file
         if new_lock_file != orig_lock_file {
             drop(file_lock);
-            let file_lock =
-                acquire_lock_file_rw(config.terminal(), metadata, lock_update_allowed, locked)?;
+            let file_lock = acquire_lock_file_rw(
+                config.terminal(),
+                metadata,
+                (&orig_lock_file, &new_lock_file),
+                lock_update_allowed,
+                locked,
+            )?;
             new_lock_file
                 .write(file_lock.file(), "cargo-component")
                 .with_context(|| {
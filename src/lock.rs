@@ -2,10 +2,11 @@
 
 use anyhow::Result;
 use cargo_component_core::{
-    lock::FileLock,
+    lock::{FileLock, LockFile, LockedPackage, LockedPackageVersion},
     terminal::{Colors, Terminal},
 };
 use cargo_metadata::Metadata;
+use wasm_pkg_client::PackageRef;
 
 /// The name of the lock file.
 pub const LOCK_FILE_NAME: &str = "Cargo-component.lock";
@@ -34,13 +35,167 @@ pub(crate) fn acquire_lock_file_ro(
     }
 }
 
+/// Represents a change to a locked package version between two lock files.
+pub(crate) enum LockChange<'a> {
+    /// The version was added.
+    Add {
+        name: &'a PackageRef,
+        version: &'a LockedPackageVersion,
+    },
+    /// The version was removed.
+    Remove {
+        name: &'a PackageRef,
+        version: &'a LockedPackageVersion,
+    },
+    /// The version was updated to a different version or digest.
+    Update {
+        name: &'a PackageRef,
+        old: &'a LockedPackageVersion,
+        new: &'a LockedPackageVersion,
+    },
+}
+
+/// Gets the changes from the original lock file to the new lock file.
+pub(crate) fn lock_file_changes<'a>(orig: &'a LockFile, new: &'a LockFile) -> Vec<LockChange<'a>> {
+    let mut changes = Vec::new();
+
+    for old_pkg in &orig.packages {
+        let new_pkg = match new
+            .packages
+            .binary_search_by_key(&old_pkg.key(), LockedPackage::key)
+            .map(|index| &new.packages[index])
+        {
+            Ok(pkg) => pkg,
+            Err(_) => {
+                // The package is no longer a dependency
+                changes.extend(old_pkg.versions.iter().map(|version| LockChange::Remove {
+                    name: &old_pkg.name,
+                    version,
+                }));
+                continue;
+            }
+        };
+
+        for old_ver in &old_pkg.versions {
+            match new_pkg
+                .versions
+                .binary_search_by_key(&old_ver.key(), LockedPackageVersion::key)
+                .map(|index| &new_pkg.versions[index])
+            {
+                // The version has changed
+                Ok(new_ver) if new_ver != old_ver => changes.push(LockChange::Update {
+                    name: &old_pkg.name,
+                    old: old_ver,
+                    new: new_ver,
+                }),
+                Ok(_) => {}
+                // The version of the package is no longer a dependency
+                Err(_) => changes.push(LockChange::Remove {
+                    name: &old_pkg.name,
+                    version: old_ver,
+                }),
+            }
+        }
+    }
+
+    for new_pkg in &new.packages {
+        let old_pkg = orig
+            .packages
+            .binary_search_by_key(&new_pkg.key(), LockedPackage::key)
+            .map(|index| &orig.packages[index])
+            .ok();
+
+        // Add any new package or any new version of the package
+        changes.extend(
+            new_pkg
+                .versions
+                .iter()
+                .filter(|new_ver| {
+                    old_pkg.is_none_or(|old_pkg| {
+                        old_pkg
+                            .versions
+                            .binary_search_by_key(&new_ver.key(), LockedPackageVersion::key)
+                            .is_err()
+                    })
+                })
+                .map(|version| LockChange::Add {
+                    name: &new_pkg.name,
+                    version,
+                }),
+        );
+    }
+
+    changes
+}
+
+/// Prints the changes between two lock files.
+///
+/// If `digests` is `true`, the content digest of each version is included.
+pub(crate) fn print_lock_file_changes(
+    terminal: &Terminal,
+    changes: &[LockChange<'_>],
+    dry_run: bool,
+    digests: bool,
+) -> Result<()> {
+    for change in changes {
+        match change {
+            LockChange::Add { name, version } => terminal.status_with_color(
+                if dry_run { "Would add" } else { "Adding" },
+                format!(
+                    "dependency `{name}` v{v}{digest}",
+                    v = version.version,
+                    digest = digest_suffix(version, digests)
+                ),
+                Colors::Green,
+            )?,
+            LockChange::Remove { name, version } => terminal.status_with_color(
+                if dry_run { "Would remove" } else { "Removing" },
+                format!(
+                    "dependency `{name}` v{v}{digest}",
+                    v = version.version,
+                    digest = digest_suffix(version, digests)
+                ),
+                Colors::Red,
+            )?,
+            LockChange::Update { name, old, new } => terminal.status_with_color(
+                if dry_run { "Would update" } else { "Updating" },
+                format!(
+                    "dependency `{name}` v{old_v}{old_digest} -> v{new_v}{new_digest}",
+                    old_v = old.version,
+                    old_digest = digest_suffix(old, digests || old.version == new.version),
+                    new_v = new.version,
+                    new_digest = digest_suffix(new, digests || old.version == new.version),
+                ),
+                Colors::Cyan,
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+fn digest_suffix(version: &LockedPackageVersion, digests: bool) -> String {
+    if digests {
+        format!(" ({digest})", digest = version.digest)
+    } else {
+        String::new()
+    }
+}
+
+/// Acquires the lock file for writing the changes from the original lock file.
+///
+/// If the lock file is not allowed to be updated, the changes that would be
+/// made are printed before returning an error.
 pub(crate) fn acquire_lock_file_rw(
     terminal: &Terminal,
     metadata: &Metadata,
+    (orig, new): (&LockFile, &LockFile),
     lock_update_allowed: bool,
     locked: bool,
 ) -> Result<FileLock> {
     if !lock_update_allowed {
+        print_lock_file_changes(terminal, &lock_file_changes(orig, new), true, true)?;
+
         let flag = if locked { "--locked" } else { "--frozen" };
         anyhow::bail!(
             "the lock file {path} needs to be updated but {flag} was passed to prevent this\n\
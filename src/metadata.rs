@@ -1,27 +1,46 @@
 //! Module for component metadata representation in `Cargo.toml`.
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt, fs,
     path::{Path, PathBuf},
     str::FromStr,
-    time::SystemTime,
 };
 
 use anyhow::{bail, Context, Result};
 use cargo_component_core::registry::{Dependency, RegistryPackage};
-use cargo_metadata::Package;
+use cargo_metadata::{Metadata, Package, Target as CargoTarget};
+use heck::ToShoutySnakeCase;
 use semver::{Version, VersionReq};
 use serde::{
     de::{self, value::MapAccessDeserializer},
     Deserialize,
 };
 use serde_json::from_value;
-use url::Url;
-use wasm_pkg_client::PackageRef;
+use toml_edit::DocumentMut;
+use wasm_pkg_client::{PackageRef, Registry};
+use wasmparser::WasmFeatures;
+
+use crate::{
+    config::default_namespace,
+    freshness::{content_hash, FileSystem, HostFileSystem},
+    is_wasm_target,
+};
 
 /// The default directory to look for a target WIT file.
 pub const DEFAULT_WIT_DIR: &str = "wit";
 
+/// The directory next to a single-file local target, or in a local target
+/// directory using the `wit-deps` layout, to look for the foreign packages it
+/// depends on.
+pub const TARGET_DEPS_DIR: &str = "deps";
+
+/// The manifest of the `wit-deps` dependencies of a local target directory.
+pub const WIT_DEPS_MANIFEST: &str = "deps.toml";
+
+/// The lock file of the `wit-deps` dependencies of a local target directory.
+pub const WIT_DEPS_LOCK: &str = "deps.lock";
+
 /// The supported ownership model for generated types.
 #[derive(Default, Debug, Clone, Copy, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -66,6 +85,18 @@ pub struct Bindings {
     pub ownership: Ownership,
     /// Additional derives to apply to generated binding types.
     pub derives: Vec<String>,
+    /// If true, implement `arbitrary::Arbitrary` for generated binding types
+    /// and generate a `bindings::arbitrary` module for writing generative
+    /// tests.
+    ///
+    /// Requires the package to depend on the `arbitrary` crate with the
+    /// `derive` feature enabled.
+    pub arbitrary: bool,
+    /// If true, generate a `bindings::proptest` module with `proptest`
+    /// strategies for the generated binding types; implies `arbitrary`.
+    ///
+    /// Requires the package to also depend on the `proptest` crate.
+    pub proptest: bool,
     /// If true, code generation should qualify any features that depend on
     /// `std` with `cfg(feature = "std")`.
     pub std_feature: bool,
@@ -76,6 +107,10 @@ pub struct Bindings {
     pub raw_strings: bool,
     /// Names of functions to skip generating bindings for.
     pub skip: Vec<String>,
+    /// Settings applied to the bindings of individual imported interfaces,
+    /// keyed by the name of the interface (e.g. `my:pkg/iface`).
+    #[serde(alias = "interface-settings")]
+    pub interface_settings: HashMap<String, InterfaceSettings>,
     /// If true, generate stub implementations for any exported functions,
     /// interfaces, and/or resources.
     pub stubs: bool,
@@ -88,6 +123,10 @@ pub struct Bindings {
     /// Indicates that all interfaces not specified in `with` should be
     /// generated.
     pub generate_all: bool,
+    /// If true, imported interfaces that are not referenced by the Rust
+    /// sources of the package are left out of the bindings, unless another
+    /// interface of the bindings depends on them.
+    pub trim_imports: bool,
     /// Add the specified suffix to the name of the custome section containing
     /// the component type.
     pub type_section_suffix: Option<String>,
@@ -104,6 +143,16 @@ pub struct Bindings {
     pub pub_export_macro: bool,
     /// Whether to generate unused structures, not generated by default (false)
     pub generate_unused_types: bool,
+    /// If true, the documentation of each generated item references the WIT
+    /// interface or world that defines it.
+    pub doc_origin: bool,
+    /// If true, the component exports a `cargo-component:metadata/version`
+    /// interface returning the crate version and the git revision it was
+    /// built from.
+    pub version_export: bool,
+    /// The name of the type implementing the exports in source generated from
+    /// the target world by `cargo component new`; defaults to `Component`.
+    pub implementor: Option<String>,
     /// Whether or not to generate helper function/constants to help link custom
     /// sections into the final output.
     ///
@@ -112,30 +161,96 @@ pub struct Bindings {
     pub disable_custom_section_link_helpers: bool,
 }
 
+impl Bindings {
+    /// Validates the settings that are copied into the generated bindings
+    /// source.
+    pub fn validate(&self) -> Result<()> {
+        fn is_identifier(s: &str) -> bool {
+            s.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+
+        // The suffix is part of the name of a custom section in a string literal
+        if let Some(suffix) = &self.type_section_suffix {
+            if suffix.is_empty()
+                || !suffix
+                    .chars()
+                    .all(|c| c.is_ascii_graphic() && c != '"' && c != '\\')
+            {
+                bail!(
+                    "`type_section_suffix` `{suffix}` must only contain printable ASCII \
+                     characters other than `\"` and `\\`"
+                );
+            }
+        }
+
+        if let Some(name) = &self.export_macro_name {
+            if !is_identifier(name) {
+                bail!("`export_macro_name` `{name}` is not a valid Rust identifier");
+            }
+        }
+
+        if let Some(module) = &self.default_bindings_module {
+            if !module
+                .strip_prefix("::")
+                .unwrap_or(module)
+                .split("::")
+                .all(is_identifier)
+            {
+                bail!("`default_bindings_module` `{module}` is not a valid Rust path");
+            }
+        }
+
+        Ok(())
+    }
+}
+
 impl Default for Bindings {
     fn default() -> Self {
         Self {
             format: true,
             ownership: Default::default(),
             derives: Default::default(),
+            arbitrary: Default::default(),
+            proptest: Default::default(),
             std_feature: false,
             raw_strings: Default::default(),
             skip: Default::default(),
+            interface_settings: Default::default(),
             stubs: Default::default(),
             export_prefix: Default::default(),
             with: Default::default(),
             generate_all: true,
+            trim_imports: Default::default(),
             type_section_suffix: Default::default(),
             disable_run_ctors_once_workaround: Default::default(),
             default_bindings_module: Default::default(),
             export_macro_name: Default::default(),
             pub_export_macro: Default::default(),
             generate_unused_types: Default::default(),
+            doc_origin: Default::default(),
+            version_export: Default::default(),
+            implementor: Default::default(),
             disable_custom_section_link_helpers: Default::default(),
         }
     }
 }
 
+/// Bindings settings applied to a single imported interface.
+///
+/// The bindings of the interface are generated separately with these settings
+/// in addition to the settings of the package.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterfaceSettings {
+    /// Additional derives to apply to the types of the interface.
+    pub derives: Vec<String>,
+    /// Names of functions of the interface to skip generating bindings for.
+    pub skip: Vec<String>,
+}
+
 /// The target of a component.
 ///
 /// The target defines the world of the component being developed.
@@ -175,7 +290,7 @@ pub enum Target {
 
 impl Target {
     /// Gets the dependencies of the target.
-    pub fn dependencies(&self) -> Cow<HashMap<PackageRef, Dependency>> {
+    pub fn dependencies(&self) -> Cow<'_, HashMap<PackageRef, Dependency>> {
         match self {
             Self::Package { name, package, .. } => Cow::Owned(HashMap::from_iter([(
                 name.clone(),
@@ -326,6 +441,486 @@ impl<'de> Deserialize<'de> for Target {
     }
 }
 
+/// The level of a component size budget.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeBudgetLevel {
+    /// Warn when a component exceeds the budget.
+    #[default]
+    Warn,
+    /// Fail the build when a component exceeds the budget.
+    Deny,
+}
+
+/// Represents the maximum size of the components of a package.
+///
+/// The budget is either a size (e.g. `"2MiB"` or a number of bytes) or a
+/// table with `size` and `level` fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeBudget {
+    /// The maximum size in bytes.
+    pub size: u64,
+    /// The level of the budget.
+    pub level: SizeBudgetLevel,
+}
+
+impl FromStr for SizeBudget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+
+        let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "kb" => 1000,
+            "kib" => 1 << 10,
+            "mb" => 1000 * 1000,
+            "mib" => 1 << 20,
+            "gb" => 1000 * 1000 * 1000,
+            "gib" => 1 << 30,
+            _ => {
+                return Err(format!(
+                    "invalid size `{s}`: expected a number followed by an optional unit \
+                     (`B`, `KB`, `KiB`, `MB`, `MiB`, `GB` or `GiB`)"
+                ))
+            }
+        };
+
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid size `{s}`: expected a number"))?;
+
+        Ok(Self {
+            size: (number * multiplier as f64) as u64,
+            level: SizeBudgetLevel::default(),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for SizeBudget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = SizeBudget;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(formatter, "a size or a table")
+            }
+
+            fn visit_u64<E>(self, size: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(SizeBudget {
+                    size,
+                    level: SizeBudgetLevel::default(),
+                })
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                SizeBudget::from_str(s).map_err(de::Error::custom)
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::MapAccess<'de>,
+            {
+                #[derive(Deserialize)]
+                #[serde(deny_unknown_fields)]
+                struct Entry {
+                    size: SizeBudget,
+                    #[serde(default)]
+                    level: SizeBudgetLevel,
+                }
+
+                let entry = Entry::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(SizeBudget {
+                    size: entry.size.size,
+                    level: entry.level,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(Visitor)
+    }
+}
+
+/// Represents cargo targets of a package that are not componentized.
+///
+/// A filter is a target name optionally prefixed with the kind of the target
+/// (`lib`, `bin`, `example`, `test` or `bench`), such as `bin:helper`; a name
+/// of `*` matches every target of the kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetFilter {
+    /// The kind of the targets to match, if any.
+    pub kind: Option<String>,
+    /// The name of the targets to match, or `*` to match every name.
+    pub name: String,
+}
+
+impl TargetFilter {
+    /// Determines if the filter matches the given cargo target.
+    ///
+    /// Cargo names library targets after the crate, with hyphens replaced by
+    /// underscores, so a library also matches by the hyphenated name.
+    pub fn matches(&self, target: &CargoTarget) -> bool {
+        let kind = match self.kind.as_deref() {
+            None => true,
+            Some("lib") => is_library(target),
+            Some("bin") => target.is_bin(),
+            Some("example") => target.is_example(),
+            Some("test") => target.is_test(),
+            Some("bench") => target.is_bench(),
+            Some(_) => false,
+        };
+
+        kind && (self.name == "*"
+            || self.name == target.name
+            || (is_library(target) && self.name.replace('-', "_") == target.name))
+    }
+}
+
+/// Determines if the given cargo target is the library target of a package.
+pub fn is_library(target: &CargoTarget) -> bool {
+    target.is_lib()
+        || target.is_cdylib()
+        || target.is_rlib()
+        || target.is_dylib()
+        || target.is_staticlib()
+}
+
+impl FromStr for TargetFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, name) = match s.split_once(':') {
+            Some((kind, name)) => (Some(kind), name),
+            None => (None, s),
+        };
+
+        if let Some(kind) = kind {
+            if !matches!(kind, "lib" | "bin" | "example" | "test" | "bench") {
+                return Err(format!(
+                    "invalid target kind `{kind}` in `{s}`: expected `lib`, `bin`, `example`, \
+                     `test` or `bench`"
+                ));
+            }
+        }
+
+        if name.is_empty() {
+            return Err(format!(
+                "invalid target filter `{s}`: expected a target name"
+            ));
+        }
+
+        Ok(Self {
+            kind: kind.map(Into::into),
+            name: name.into(),
+        })
+    }
+}
+
+impl fmt::Display for TargetFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Some(kind) => write!(f, "{kind}:{name}", name = self.name),
+            None => write!(f, "{name}", name = self.name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetFilter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Configuration for `cargo component serve`.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Serve {
+    /// The path prefix routed to the component when serving multiple
+    /// components behind a single frontend (e.g. `/api`).
+    pub route: Option<String>,
+}
+
+/// The access level of a published package.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Access {
+    /// The package may be published to any registry.
+    #[default]
+    Public,
+    /// The package may only be published to an explicitly configured registry.
+    Private,
+}
+
+/// Configuration for `cargo component publish`.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Publish {
+    /// The registry to publish to.
+    ///
+    /// The `--registry` option takes precedence over this setting.
+    pub registry: Option<Registry>,
+    /// The name to publish the package as, overriding the name of `package`.
+    pub package: Option<String>,
+    /// The namespace to publish the package to, overriding the namespace of `package`.
+    pub namespace: Option<String>,
+    /// The access level of the published package.
+    pub access: Access,
+}
+
+/// Configuration of the runtime for `cargo component run`, `test` and `serve`.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Runtime {
+    /// The environment variables to pass to the component.
+    pub env: BTreeMap<String, String>,
+    /// The directories to preopen for the component.
+    ///
+    /// Each directory is in the form `HOST[::GUEST]`; host directories are
+    /// relative to the manifest directory.
+    pub dirs: Vec<String>,
+    /// The arguments to pass to the component before any given on the
+    /// command line.
+    pub args: Vec<String>,
+    /// The WASI proposals to enable or disable in the runtime.
+    pub wasi: BTreeMap<String, bool>,
+}
+
+impl Runtime {
+    /// Gets the flags to pass to the runner before the component.
+    pub fn runner_flags(&self) -> Vec<String> {
+        let mut flags = Vec::new();
+
+        for (name, enabled) in &self.wasi {
+            flags.push("-S".to_string());
+            flags.push(format!("{name}={v}", v = if *enabled { "y" } else { "n" }));
+        }
+
+        for (key, value) in &self.env {
+            flags.push("--env".to_string());
+            flags.push(format!("{key}={value}"));
+        }
+
+        for dir in &self.dirs {
+            flags.push("--dir".to_string());
+            flags.push(dir.clone());
+        }
+
+        flags
+    }
+}
+
+/// Configuration for `cargo component deploy`.
+///
+/// The provider determines how the built component is deployed.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "provider", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum Deploy {
+    /// Push the component to an OCI registry.
+    Oci {
+        /// The reference to push to; the package version is used as the tag
+        /// if the reference has none.
+        reference: String,
+        /// The annotations to add to the image manifest.
+        #[serde(default)]
+        annotations: BTreeMap<String, String>,
+    },
+    /// Push a Spin application containing the component to an OCI registry
+    /// with `spin registry push`.
+    Spin {
+        /// The reference to push to; the package version is used as the tag
+        /// if the reference has none.
+        reference: String,
+        /// The path to the Spin application manifest, relative to the
+        /// manifest directory.
+        manifest: Option<PathBuf>,
+    },
+    /// Deploy a Spin application containing the component to Fermyon Cloud
+    /// with `spin cloud deploy`.
+    FermyonCloud {
+        /// The path to the Spin application manifest, relative to the
+        /// manifest directory.
+        manifest: Option<PathBuf>,
+    },
+    /// Push the component to an OCI registry with `wash push` and, if an
+    /// application manifest is given, deploy it with `wash app deploy`.
+    Wasmcloud {
+        /// The reference to push to; the package version is used as the tag
+        /// if the reference has none.
+        reference: String,
+        /// The path to the wadm application manifest, relative to the
+        /// manifest directory.
+        manifest: Option<PathBuf>,
+    },
+}
+
+/// Configuration for installing the components of a package after they are
+/// built.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Install {
+    /// The paths the components are copied to, relative to the manifest
+    /// directory.
+    ///
+    /// The `{name}`, `{version}` and `{profile}` placeholders are replaced
+    /// with the name of the cargo target, the version of the package and the
+    /// name of the cargo profile. A path ending with `/` is a directory the
+    /// component is copied into.
+    pub destinations: Vec<String>,
+    /// The cargo profiles whose components are installed.
+    ///
+    /// Components of every profile are installed if empty.
+    pub profiles: Vec<String>,
+}
+
+impl Install {
+    /// Validates the placeholders of the destination paths.
+    pub fn validate(&self) -> Result<()> {
+        for destination in &self.destinations {
+            expand_destination(destination, "name", "0.0.0", "dev")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Replaces the placeholders of an install destination path.
+fn expand_destination(
+    destination: &str,
+    name: &str,
+    version: &str,
+    profile: &str,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(destination.len());
+    let mut rest = destination;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            bail!("install destination `{destination}` has an unterminated placeholder");
+        };
+
+        expanded.push_str(match &rest[start + 1..end] {
+            "name" => name,
+            "version" => version,
+            "profile" => profile,
+            placeholder => bail!(
+                "install destination `{destination}` has an unknown placeholder \
+                 `{{{placeholder}}}`: expected `{{name}}`, `{{version}}` or `{{profile}}`"
+            ),
+        });
+        rest = &rest[end + 1..];
+    }
+
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Configuration of the proposals used by the component.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Features {
+    /// The WebAssembly proposals to enable or disable when validating the component.
+    ///
+    /// Proposals not listed here use the validator's defaults.
+    pub wasm: BTreeMap<String, bool>,
+}
+
+impl Features {
+    /// Gets the WebAssembly features to validate the component with.
+    pub fn wasm_features(&self) -> Result<WasmFeatures> {
+        let mut features = WasmFeatures::default();
+        for (name, enabled) in &self.wasm {
+            let feature = WasmFeatures::from_name(&name.to_shouty_snake_case())
+                .with_context(|| format!("unknown WebAssembly feature `{name}`"))?;
+            features.set(feature, *enabled);
+        }
+
+        Ok(features)
+    }
+}
+
+/// The `processed-by` entry added to the producers section of components.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum ProcessedBy {
+    /// Whether to add the entry for `cargo-component`.
+    Enabled(bool),
+    /// A custom entry to add instead of the entry for `cargo-component`.
+    Custom {
+        /// The name of the tool.
+        name: String,
+        /// The version of the tool.
+        version: String,
+    },
+}
+
+impl Default for ProcessedBy {
+    fn default() -> Self {
+        Self::Enabled(true)
+    }
+}
+
+/// Configuration of the producers section of components.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Producers {
+    /// The `processed-by` entry to add for `cargo-component`.
+    pub processed_by: ProcessedBy,
+    /// Whether to remove every producers section from components, as with
+    /// `--no-producers`.
+    pub strip: bool,
+}
+
+/// Configuration of the libraries linked into the components of a package.
+///
+/// The core modules of the package and of the libraries are linked into a
+/// single component with shared-everything dynamic linking, so every module
+/// must be built as a dynamic library.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Link {
+    /// The workspace packages whose `cdylib` modules are linked, in order of
+    /// priority for symbols exported by more than one library.
+    pub libraries: Vec<String>,
+    /// The libraries whose exports can be looked up with `dlopen`/`dlsym`.
+    pub dl_openable: Vec<String>,
+}
+
+/// The settings of the component section that can be overridden for a cargo
+/// profile.
+const PROFILE_OVERRIDES: &[&str] = &["adapter", "producers", "bindings"];
+
+/// Configuration of the target world for a cargo profile.
+///
+/// The overrides of the component section for the profile are applied when the
+/// metadata is loaded and are not part of this section.
+#[derive(Default, Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileSection {
+    /// The worlds whose imports and exports are merged into the target world
+    /// when building with the profile.
+    pub include: Vec<String>,
+}
+
 /// Represents the `package.metadata.component` section in `Cargo.toml`.
 #[derive(Default, Debug, Clone, Deserialize)]
 #[serde(default, deny_unknown_fields)]
@@ -338,14 +933,373 @@ pub struct ComponentSection {
     pub adapter: Option<PathBuf>,
     /// The dependencies of the component.
     pub dependencies: HashMap<PackageRef, Dependency>,
-    /// The registries to use for the component.
-    pub registries: HashMap<String, Url>,
+    /// The dependencies of the component that are only imported when
+    /// building tests and benchmarks.
+    #[serde(rename = "dev-dependencies")]
+    pub dev_dependencies: HashMap<PackageRef, Dependency>,
+    /// The mirror registries to fall back to, in order, for the packages of a namespace.
+    pub mirrors: HashMap<String, Vec<Registry>>,
     /// The configuration for bindings generation.
     pub bindings: Bindings,
     /// Whether to use the built-in `wasi:http/proxy` adapter for the component.
     ///
     /// This should only be `true` when `adapter` is None.
     pub proxy: bool,
+    /// The maximum size of the components produced for the package.
+    #[serde(rename = "max-size")]
+    pub max_size: Option<SizeBudget>,
+    /// Whether to reject components that export anything not declared by
+    /// the target world.
+    #[serde(rename = "strict-exports")]
+    pub strict_exports: bool,
+    /// The cargo targets of the package that are not componentized.
+    #[serde(rename = "exclude-targets")]
+    pub exclude_targets: Vec<TargetFilter>,
+    /// Custom metadata for the plugin manifest of the component.
+    ///
+    /// When present, a plugin manifest is written next to each component.
+    pub plugin: Option<serde_json::Map<String, serde_json::Value>>,
+    /// The configuration for `cargo component serve`.
+    pub serve: Serve,
+    /// The runtime configuration for running the component.
+    pub runtime: Runtime,
+    /// The configuration for `cargo component publish`.
+    pub publish: Publish,
+    /// The proposals used by the component.
+    pub features: Features,
+    /// The configuration for `cargo component deploy`.
+    pub deploy: Option<Deploy>,
+    /// The configuration for installing the components of the package.
+    pub install: Install,
+    /// The configuration of the producers section of components.
+    pub producers: Producers,
+    /// The libraries linked into the components of the package.
+    pub link: Option<Link>,
+    /// Whether the library of the package is built as a dynamic library to be
+    /// linked into components, rather than as a component.
+    pub library: bool,
+    /// The target world configuration of each cargo profile.
+    pub profile: HashMap<String, ProfileSection>,
+}
+
+/// Removes the `optional` and `feature` settings from the dependency entries
+/// of a component section.
+///
+/// Returns the names of the optional dependencies and their features, if specified.
+fn take_optional_dependencies(
+    component: &mut serde_json::Value,
+) -> Result<Vec<(String, Option<String>)>> {
+    let Some(dependencies) = component
+        .get_mut("dependencies")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut optional = Vec::new();
+    for (name, dependency) in dependencies {
+        let Some(entry) = dependency.as_object_mut() else {
+            continue;
+        };
+
+        let feature = match entry.remove("feature") {
+            Some(serde_json::Value::String(feature)) => Some(feature),
+            Some(_) => bail!("the `feature` field of dependency `{name}` must be a string"),
+            None => None,
+        };
+
+        match entry.remove("optional") {
+            Some(serde_json::Value::Bool(true)) => optional.push((name.clone(), feature)),
+            Some(serde_json::Value::Bool(false)) | None if feature.is_none() => {}
+            Some(serde_json::Value::Bool(_)) | None => {
+                bail!("the `feature` field of dependency `{name}` requires `optional = true`")
+            }
+            Some(_) => bail!("the `optional` field of dependency `{name}` must be a boolean"),
+        }
+    }
+
+    Ok(optional)
+}
+
+/// Removes the overrides of the component section from the profile tables of
+/// a component section, applying those of the given profile.
+///
+/// Tables are merged with the table they override, other values replace the
+/// value they override.
+fn take_profile_overrides(component: &mut serde_json::Value, profile: Option<&str>) {
+    let Some(profiles) = component
+        .get_mut("profile")
+        .and_then(serde_json::Value::as_object_mut)
+    else {
+        return;
+    };
+
+    let mut overrides = serde_json::Map::new();
+    for (name, table) in profiles {
+        let Some(table) = table.as_object_mut() else {
+            continue;
+        };
+
+        for key in PROFILE_OVERRIDES {
+            if let Some(value) = table.remove(*key) {
+                if Some(name.as_str()) == profile {
+                    overrides.insert(key.to_string(), value);
+                }
+            }
+        }
+    }
+
+    if let Some(component) = component.as_object_mut() {
+        for (key, value) in overrides {
+            log::debug!(
+                "overriding `{key}` for profile `{profile}`",
+                profile = profile.unwrap_or_default()
+            );
+            merge_value(
+                component.entry(key).or_insert(serde_json::Value::Null),
+                value,
+            );
+        }
+    }
+}
+
+/// Merges a value into another, merging tables recursively.
+fn merge_value(base: &mut serde_json::Value, value: serde_json::Value) {
+    match (base, value) {
+        (serde_json::Value::Object(base), serde_json::Value::Object(value)) => {
+            for (key, value) in value {
+                merge_value(base.entry(key).or_insert(serde_json::Value::Null), value);
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
+
+/// Removes the `locked` settings from the dependency and dev-dependency entries
+/// of a component section.
+///
+/// Returns the names of the dependencies that are locked.
+fn take_locked_dependencies(component: &mut serde_json::Value) -> Result<Vec<String>> {
+    let mut locked = Vec::new();
+    for table in ["dependencies", "dev-dependencies"] {
+        let Some(dependencies) = component
+            .get_mut(table)
+            .and_then(serde_json::Value::as_object_mut)
+        else {
+            continue;
+        };
+
+        for (name, dependency) in dependencies {
+            let Some(entry) = dependency.as_object_mut() else {
+                continue;
+            };
+
+            match entry.remove("locked") {
+                Some(serde_json::Value::Bool(true)) => locked.push(name.clone()),
+                Some(serde_json::Value::Bool(false)) | None => {}
+                Some(_) => bail!("the `locked` field of dependency `{name}` must be a boolean"),
+            }
+        }
+    }
+
+    Ok(locked)
+}
+
+/// Removes the `import-name` settings from the dependency entries of a
+/// component section.
+///
+/// Returns the names of the dependencies and the names to import them as.
+fn take_import_names(component: &mut serde_json::Value) -> Result<Vec<(String, String)>> {
+    let mut names = Vec::new();
+    for table in ["dependencies", "dev-dependencies"] {
+        let Some(dependencies) = component
+            .get_mut(table)
+            .and_then(serde_json::Value::as_object_mut)
+        else {
+            continue;
+        };
+
+        for (name, dependency) in dependencies {
+            let Some(entry) = dependency.as_object_mut() else {
+                continue;
+            };
+
+            match entry.remove("import-name") {
+                Some(serde_json::Value::String(import_name)) => {
+                    wit_parser::validate_id(&import_name).with_context(|| {
+                        format!(
+                            "the `import-name` field of dependency `{name}` is not a legal WIT identifier"
+                        )
+                    })?;
+                    names.push((name.clone(), import_name));
+                }
+                None => {}
+                Some(_) => {
+                    bail!("the `import-name` field of dependency `{name}` must be a string")
+                }
+            }
+        }
+    }
+
+    Ok(names)
+}
+
+/// Replaces the `workspace-member` settings of the dependency and
+/// dev-dependency entries of a component section with a `path` to the name of
+/// the member, to be resolved once the workspace is known.
+///
+/// Returns the names of the dependencies and the workspace members they refer to.
+fn take_workspace_members(component: &mut serde_json::Value) -> Result<Vec<(String, String)>> {
+    let mut members = Vec::new();
+    for table in ["dependencies", "dev-dependencies"] {
+        let Some(dependencies) = component
+            .get_mut(table)
+            .and_then(serde_json::Value::as_object_mut)
+        else {
+            continue;
+        };
+
+        for (name, dependency) in dependencies {
+            let Some(entry) = dependency.as_object_mut() else {
+                continue;
+            };
+
+            let member = match entry.remove("workspace-member") {
+                Some(serde_json::Value::String(member)) => member,
+                None => continue,
+                Some(_) => {
+                    bail!("the `workspace-member` field of dependency `{name}` must be a string")
+                }
+            };
+
+            if let Some(key) = entry.keys().next() {
+                bail!(
+                    "cannot specify both `workspace-member` and `{key}` fields in dependency `{name}`"
+                );
+            }
+
+            entry.insert("path".into(), member.clone().into());
+            members.push((name.clone(), member));
+        }
+    }
+
+    Ok(members)
+}
+
+/// Represents a deprecated key of the component section.
+struct DeprecatedKey {
+    /// The path of the key in the component section.
+    path: &'static [&'static str],
+    /// Describes what replaces the key.
+    replacement: &'static str,
+    /// Whether the key has no effect, so that `cargo component fix` can
+    /// remove it.
+    removable: bool,
+}
+
+/// The deprecated keys of the component section.
+///
+/// Deprecated keys are removed from the section when the metadata is loaded
+/// and reported as warnings, rather than being rejected or silently ignored.
+const DEPRECATED_KEYS: &[DeprecatedKey] = &[
+    DeprecatedKey {
+        path: &["registries"],
+        replacement: "registries are configured with `default_registry` and \
+                      `[namespace_registries]` in the wasm-pkg configuration file",
+        removable: false,
+    },
+    DeprecatedKey {
+        path: &["target", "document"],
+        replacement: "WIT documents no longer exist; the target world is selected by \
+                      `world` alone",
+        removable: true,
+    },
+    DeprecatedKey {
+        path: &["bindings", "implementor"],
+        replacement: "exports are implemented with \
+                      `bindings::export!(Component with_types_in bindings)`",
+        removable: true,
+    },
+];
+
+/// Represents a deprecated key found in the component section of a manifest.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    /// The path of the key in the manifest.
+    pub key: String,
+    /// Describes what replaces the key.
+    pub replacement: &'static str,
+    /// Whether `cargo component fix` removes the key from the manifest.
+    pub fixable: bool,
+}
+
+impl Deprecation {
+    fn new(key: &DeprecatedKey) -> Self {
+        Self {
+            key: format!("package.metadata.component.{}", key.path.join(".")),
+            replacement: key.replacement,
+            fixable: key.removable,
+        }
+    }
+}
+
+impl fmt::Display for Deprecation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "key `{key}` is deprecated: {replacement}",
+            key = self.key,
+            replacement = self.replacement
+        )?;
+
+        if self.fixable {
+            write!(f, "; run `cargo component fix` to remove it")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Removes the deprecated keys from a component section.
+///
+/// Returns the deprecations found.
+fn take_deprecated_keys(component: &mut serde_json::Value) -> Vec<Deprecation> {
+    let mut deprecations = Vec::new();
+    for key in DEPRECATED_KEYS {
+        let (last, parents) = key.path.split_last().unwrap();
+        let table = parents
+            .iter()
+            .try_fold(&mut *component, |value, name| value.get_mut(*name))
+            .and_then(serde_json::Value::as_object_mut);
+
+        if table.and_then(|t| t.remove(*last)).is_some() {
+            deprecations.push(Deprecation::new(key));
+        }
+    }
+
+    deprecations
+}
+
+/// Removes the deprecated keys that have no effect from the component section
+/// of the given manifest.
+///
+/// Returns the deprecations that were fixed.
+pub fn fix_deprecated_keys(manifest: &mut DocumentMut) -> Vec<Deprecation> {
+    let mut fixed = Vec::new();
+    for key in DEPRECATED_KEYS.iter().filter(|k| k.removable) {
+        let (last, parents) = key.path.split_last().unwrap();
+        let table = ["package", "metadata", "component"]
+            .iter()
+            .chain(parents)
+            .try_fold(manifest.as_item_mut(), |item, name| item.get_mut(*name))
+            .and_then(|item| item.as_table_like_mut());
+
+        if table.and_then(|t| t.remove(last)).is_some() {
+            fixed.push(Deprecation::new(key));
+        }
+    }
+
+    fixed
 }
 
 /// Represents cargo metadata for a WebAssembly component.
@@ -357,26 +1311,72 @@ pub struct ComponentMetadata {
     pub version: Version,
     /// The path to the cargo manifest file.
     pub manifest_path: PathBuf,
-    /// The last modified time of the manifest file.
-    pub modified_at: SystemTime,
+    /// The hash of the contents of the manifest file.
+    pub digest: String,
     /// The component section in `Cargo.toml`.
     pub section: ComponentSection,
     /// Whether the component section was present in `Cargo.toml`.
     pub section_present: bool,
+    /// The cargo features that enable the optional component dependencies.
+    pub optional_dependencies: HashMap<PackageRef, String>,
+    /// The component dependencies imported by their exact locked version and digest.
+    pub locked_dependencies: HashSet<PackageRef>,
+    /// The names that component dependencies are imported as in the target world.
+    pub import_names: HashMap<PackageRef, String>,
+    /// The workspace members that component dependencies refer to.
+    pub workspace_members: HashMap<PackageRef, String>,
+    /// The namespace of the package when the manifest does not name one.
+    pub namespace: String,
+    /// The deprecated keys found in the component section.
+    pub deprecations: Vec<Deprecation>,
 }
 
 impl ComponentMetadata {
     /// Creates a new component metadata for the given cargo package.
-    pub fn from_package(package: &Package) -> Result<Self> {
+    ///
+    /// If a cargo profile is given, the overrides of the component section for
+    /// the profile are applied.
+    pub fn from_package(package: &Package, profile: Option<&str>) -> Result<Self> {
         log::debug!(
             "searching for component metadata in manifest `{path}`",
             path = package.manifest_path
         );
 
         let mut section_present = false;
+        let mut optional = Vec::new();
+        let mut locked = Vec::new();
+        let mut import_names = Vec::new();
+        let mut members = Vec::new();
+        let mut deprecations = Vec::new();
         let mut section: ComponentSection = match package.metadata.get("component").cloned() {
-            Some(component) => {
+            Some(mut component) => {
                 section_present = true;
+                take_profile_overrides(&mut component, profile);
+                deprecations = take_deprecated_keys(&mut component);
+                optional = take_optional_dependencies(&mut component).with_context(|| {
+                    format!(
+                        "failed to deserialize component metadata from `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+                locked = take_locked_dependencies(&mut component).with_context(|| {
+                    format!(
+                        "failed to deserialize component metadata from `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+                import_names = take_import_names(&mut component).with_context(|| {
+                    format!(
+                        "failed to deserialize component metadata from `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+                members = take_workspace_members(&mut component).with_context(|| {
+                    format!(
+                        "failed to deserialize component metadata from `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
                 from_value(component).with_context(|| {
                     format!(
                         "failed to deserialize component metadata from `{path}`",
@@ -403,7 +1403,15 @@ impl ComponentMetadata {
                     path = package.manifest_path
                 )
             })?;
-        let modified_at = crate::last_modified_time(package.manifest_path.as_std_path())?;
+        let digest = HostFileSystem
+            .read(package.manifest_path.as_std_path())
+            .map(|contents| content_hash(&contents))
+            .with_context(|| {
+                format!(
+                    "failed to read manifest `{path}`",
+                    path = package.manifest_path
+                )
+            })?;
 
         // Make all paths stored in the metadata relative to the manifest directory.
         if let Target::Local {
@@ -421,23 +1429,377 @@ impl ComponentMetadata {
             }
         }
 
-        for dependency in section.dependencies.values_mut() {
-            if let Dependency::Local(path) = dependency {
-                *path = manifest_dir.join(path.as_path());
+        let workspace_members = members
+            .into_iter()
+            .map(|(name, member)| {
+                let name: PackageRef = name.parse().with_context(|| {
+                    format!(
+                        "invalid dependency name `{name}` in `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+                Ok((name, member))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        for (name, dependency) in section
+            .dependencies
+            .iter_mut()
+            .chain(section.dev_dependencies.iter_mut())
+        {
+            match dependency {
+                // The paths of workspace members are resolved with the workspace
+                Dependency::Local(_) if workspace_members.contains_key(name) => {}
+                Dependency::Local(path) => *path = manifest_dir.join(path.as_path()),
+                Dependency::Package(_) => {}
             }
         }
 
+        if let Some(name) = section
+            .dev_dependencies
+            .keys()
+            .find(|name| section.dependencies.contains_key(*name))
+        {
+            bail!(
+                "component dependency `{name}` cannot be both a dependency and a dev-dependency in `{path}`",
+                path = package.manifest_path
+            );
+        }
+
         if let Some(adapter) = section.adapter.as_mut() {
             *adapter = manifest_dir.join(adapter.as_path());
         }
 
+        if let Some(
+            Deploy::Spin {
+                manifest: Some(path),
+                ..
+            }
+            | Deploy::FermyonCloud {
+                manifest: Some(path),
+            }
+            | Deploy::Wasmcloud {
+                manifest: Some(path),
+                ..
+            },
+        ) = section.deploy.as_mut()
+        {
+            *path = manifest_dir.join(path.as_path());
+        }
+
+        for dir in section.runtime.dirs.iter_mut() {
+            // Preserve the guest path as given when making the host path absolute
+            let (host, guest) = dir.split_once("::").unwrap_or((dir.as_str(), dir.as_str()));
+            *dir = format!("{host}::{guest}", host = manifest_dir.join(host).display());
+        }
+
+        let mut optional_dependencies = HashMap::new();
+        for (name, feature) in optional {
+            let name: PackageRef = name.parse().with_context(|| {
+                format!(
+                    "invalid dependency name `{name}` in `{path}`",
+                    path = package.manifest_path
+                )
+            })?;
+            let feature = feature.unwrap_or_else(|| name.name().to_string());
+            if !package.features.contains_key(&feature) {
+                bail!(
+                    "optional component dependency `{name}` requires feature `{feature}` to be declared in `{path}`",
+                    path = package.manifest_path
+                );
+            }
+
+            optional_dependencies.insert(name, feature);
+        }
+
+        let mut locked_dependencies = HashSet::new();
+        for name in locked {
+            let name: PackageRef = name.parse().with_context(|| {
+                format!(
+                    "invalid dependency name `{name}` in `{path}`",
+                    path = package.manifest_path
+                )
+            })?;
+            let dependency = section
+                .dependencies
+                .get(&name)
+                .or_else(|| section.dev_dependencies.get(&name));
+            if !matches!(dependency, Some(Dependency::Package(_))) {
+                bail!(
+                    "component dependency `{name}` in `{path}` must be a registry dependency to be locked",
+                    path = package.manifest_path
+                );
+            }
+
+            locked_dependencies.insert(name);
+        }
+
+        let import_names = import_names
+            .into_iter()
+            .map(|(name, import_name)| {
+                let name: PackageRef = name.parse().with_context(|| {
+                    format!(
+                        "invalid dependency name `{name}` in `{path}`",
+                        path = package.manifest_path
+                    )
+                })?;
+                Ok((name, import_name))
+            })
+            .collect::<Result<_>>()?;
+
+        section.bindings.validate().with_context(|| {
+            format!(
+                "invalid `bindings` table in `{path}`",
+                path = package.manifest_path
+            )
+        })?;
+
+        section.install.validate().with_context(|| {
+            format!(
+                "invalid `install` table in `{path}`",
+                path = package.manifest_path
+            )
+        })?;
+
+        section.features.wasm_features().with_context(|| {
+            format!(
+                "invalid `features.wasm` table in `{path}`",
+                path = package.manifest_path
+            )
+        })?;
+
+        if let Some(link) = &section.link {
+            if let Some(name) = link
+                .dl_openable
+                .iter()
+                .find(|name| !link.libraries.contains(name))
+            {
+                bail!(
+                    "dl-openable library `{name}` in `{path}` is not listed in `link.libraries`",
+                    path = package.manifest_path
+                );
+            }
+        }
+
+        if let Some(filter) = section
+            .exclude_targets
+            .iter()
+            .find(|f| f.name != "*" && !package.targets.iter().any(|t| f.matches(t)))
+        {
+            bail!(
+                "excluded target `{filter}` in `{path}` does not match any target of the package",
+                path = package.manifest_path
+            );
+        }
+
+        if let Some(route) = &section.serve.route {
+            if !route.starts_with('/') {
+                bail!(
+                    "serve route `{route}` in `{path}` must start with `/`",
+                    path = package.manifest_path
+                );
+            }
+        }
+
         Ok(Self {
             name: package.name.clone(),
             version: package.version.clone(),
             manifest_path: package.manifest_path.clone().into(),
-            modified_at,
+            digest,
             section,
             section_present,
+            optional_dependencies,
+            locked_dependencies,
+            import_names,
+            workspace_members,
+            namespace: default_namespace()?,
+            deprecations,
+        })
+    }
+
+    /// Resolves the component dependencies on workspace members to the
+    /// components built for the members with the given cargo profile.
+    ///
+    /// The most recently built component of a member is used, whatever the
+    /// WebAssembly target it was built for; if the member was not built yet,
+    /// the dependency refers to the component built for `wasm32-wasip1`.
+    pub fn resolve_workspace_members(
+        &mut self,
+        metadata: &Metadata,
+        profile: Option<&str>,
+    ) -> Result<()> {
+        let profile_dir = match profile {
+            None | Some("dev") | Some("test") => "debug",
+            Some("bench") => "release",
+            Some(profile) => profile,
+        };
+
+        for (name, member) in &self.workspace_members {
+            if *member == self.name {
+                bail!(
+                    "component dependency `{name}` in `{path}` cannot refer to its own package",
+                    path = self.manifest_path.display()
+                );
+            }
+
+            let package = metadata
+                .workspace_packages()
+                .into_iter()
+                .find(|p| p.name == *member)
+                .with_context(|| {
+                    format!(
+                        "component dependency `{name}` in `{path}` refers to `{member}`, which is \
+                         not a member of the workspace",
+                        path = self.manifest_path.display()
+                    )
+                })?;
+
+            let target = package
+                .targets
+                .iter()
+                .find(|t| t.is_cdylib() || t.is_lib())
+                .with_context(|| {
+                    format!(
+                        "workspace member `{member}` of component dependency `{name}` in \
+                         `{path}` has no library target",
+                        path = self.manifest_path.display()
+                    )
+                })?;
+
+            let file_name = format!("{name}.wasm", name = target.name.replace('-', "_"));
+            let target_dir = metadata.target_directory.as_std_path();
+            let built = fs::read_dir(target_dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| {
+                    let entry = entry.ok()?;
+                    if !is_wasm_target(entry.file_name().to_str()?) {
+                        return None;
+                    }
+
+                    let path = entry.path().join(profile_dir).join(&file_name);
+                    let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+                    Some((modified, path))
+                })
+                .max()
+                .map(|(_, path)| path);
+
+            let path = built.unwrap_or_else(|| {
+                target_dir
+                    .join("wasm32-wasip1")
+                    .join(profile_dir)
+                    .join(&file_name)
+            });
+
+            log::debug!(
+                "component dependency `{name}` refers to component `{path}` of workspace member `{member}`",
+                path = path.display()
+            );
+
+            for dependencies in [
+                &mut self.section.dependencies,
+                &mut self.section.dev_dependencies,
+            ] {
+                if let Some(Dependency::Local(p)) = dependencies.get_mut(name) {
+                    *p = path.clone();
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the paths the component of the given cargo target is installed to
+    /// when built with the given cargo profile.
+    pub fn install_paths(
+        &self,
+        target: &str,
+        profile: &str,
+        file_name: &str,
+    ) -> Result<Vec<PathBuf>> {
+        let install = &self.section.install;
+        if !install.profiles.is_empty() && !install.profiles.iter().any(|p| p == profile) {
+            return Ok(Vec::new());
+        }
+
+        let manifest_dir = self.manifest_path.parent().unwrap();
+        let version = self.version.to_string();
+        install
+            .destinations
+            .iter()
+            .map(|destination| {
+                let expanded = expand_destination(destination, target, &version, profile)?;
+                let mut path = manifest_dir.join(&expanded);
+                if expanded.ends_with('/') {
+                    path.push(file_name);
+                }
+
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// Determines if the given cargo target is excluded from componentization.
+    pub fn excludes(&self, target: &CargoTarget) -> bool {
+        self.section
+            .exclude_targets
+            .iter()
+            .any(|filter| filter.matches(target))
+    }
+
+    /// Determines if the given component dependency is enabled by the given
+    /// cargo features.
+    ///
+    /// Dependencies that are not optional are always enabled.
+    pub fn dependency_enabled(&self, name: &PackageRef, features: &HashSet<String>) -> bool {
+        self.optional_dependencies
+            .get(name)
+            .is_none_or(|feature| features.contains(feature))
+    }
+
+    /// Iterates over the component dependencies and dev-dependencies.
+    pub fn all_dependencies(&self) -> impl Iterator<Item = (&PackageRef, &Dependency)> {
+        self.section
+            .dependencies
+            .iter()
+            .chain(self.section.dev_dependencies.iter())
+    }
+
+    /// Gets the name to publish the package as.
+    ///
+    /// The namespace and name of the `package` setting are overridden by
+    /// the `namespace` and `package` settings of the `publish` table; without
+    /// either, the default namespace of the user configuration is used.
+    pub fn publish_package(&self) -> Result<PackageRef> {
+        let publish = &self.section.publish;
+        let namespace = publish
+            .namespace
+            .as_deref()
+            .or(self
+                .section
+                .package
+                .as_ref()
+                .map(|p| p.namespace().as_ref()))
+            .unwrap_or(&self.namespace);
+        let name = publish.package.as_deref().or(self
+            .section
+            .package
+            .as_ref()
+            .map(|p| p.name().as_ref()));
+
+        let Some(name) = name else {
+            bail!(
+                "package `{name}` is missing a `package.metadata.component.package` setting in manifest `{path}`",
+                name = self.name,
+                path = self.manifest_path.display()
+            );
+        };
+
+        format!("{namespace}:{name}").parse().with_context(|| {
+            format!(
+                "invalid package name `{namespace}:{name}` to publish in manifest `{path}`",
+                path = self.manifest_path.display()
+            )
         })
     }
 
@@ -455,7 +1817,7 @@ impl ComponentMetadata {
     ///
     /// Returns `None` if the target is a registry package or
     /// if a path is not specified and the default path does not exist.
-    pub fn target_path(&self) -> Option<Cow<Path>> {
+    pub fn target_path(&self) -> Option<Cow<'_, Path>> {
         match &self.section.target {
             Target::Local {
                 path: Some(path), ..
@@ -0,0 +1,115 @@
+//! Module for generating plugin manifests.
+//!
+//! A plugin manifest is a JSON file written next to a component that
+//! summarizes its imports and exports so that host applications can
+//! register the component as a plugin without decoding it.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, WorldItem, WorldKey};
+
+use crate::metadata::ComponentMetadata;
+
+/// The version of the plugin manifest schema.
+///
+/// This must be incremented for any change that is not backwards compatible.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Represents a plugin manifest.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PluginManifest<'a> {
+    /// The version of the manifest schema.
+    pub schema_version: u32,
+    /// The name of the crate.
+    pub name: &'a str,
+    /// The version of the crate.
+    pub version: String,
+    /// The component package name, if one is configured.
+    pub package: Option<String>,
+    /// The file name of the component.
+    pub component: &'a str,
+    /// The imports of the component.
+    pub imports: Vec<PluginItem>,
+    /// The exports of the component.
+    pub exports: Vec<PluginItem>,
+    /// The custom metadata from `[package.metadata.component.plugin]`.
+    pub metadata: &'a Map<String, Value>,
+}
+
+/// Represents an import or export of a plugin.
+#[derive(Serialize)]
+pub struct PluginItem {
+    /// The name of the item.
+    ///
+    /// Interfaces from packages are named by their fully-qualified name
+    /// including the version (e.g. `wasi:cli/run@0.2.0`).
+    pub name: String,
+    /// The kind of the item, either `interface` or `function`.
+    pub kind: &'static str,
+}
+
+impl<'a> PluginManifest<'a> {
+    /// Creates a plugin manifest for the given component.
+    pub fn new(
+        metadata: &'a ComponentMetadata,
+        plugin: &'a Map<String, Value>,
+        component: &'a str,
+        bytes: &[u8],
+    ) -> Result<Self> {
+        let (resolve, world) = match wit_component::decode(bytes)
+            .with_context(|| format!("failed to decode component `{component}`"))?
+        {
+            DecodedWasm::Component(resolve, world) => (resolve, world),
+            DecodedWasm::WitPackage(..) => {
+                bail!("`{component}` is a WIT package and not a component")
+            }
+        };
+
+        let world = &resolve.worlds[world];
+        Ok(Self {
+            schema_version: SCHEMA_VERSION,
+            name: &metadata.name,
+            version: metadata.version.to_string(),
+            package: metadata.section.package.as_ref().map(ToString::to_string),
+            component,
+            imports: items(&resolve, &world.imports),
+            exports: items(&resolve, &world.exports),
+            metadata: plugin,
+        })
+    }
+
+    /// Serializes the manifest to pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String> {
+        let mut json =
+            serde_json::to_string_pretty(self).context("failed to serialize plugin manifest")?;
+        json.push('\n');
+        Ok(json)
+    }
+}
+
+fn items<'a>(
+    resolve: &Resolve,
+    items: impl IntoIterator<Item = (&'a WorldKey, &'a WorldItem)>,
+) -> Vec<PluginItem> {
+    let mut items = items
+        .into_iter()
+        .filter_map(|(key, item)| {
+            let kind = match item {
+                WorldItem::Interface { .. } => "interface",
+                WorldItem::Function(_) => "function",
+                WorldItem::Type(_) => return None,
+            };
+
+            Some(PluginItem {
+                name: resolve.name_world_key(key),
+                kind,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    items.sort_by(|a, b| a.name.cmp(&b.name));
+    items
+}
@@ -1,19 +1,35 @@
 //! Module for interacting with component registries.
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    path::Path,
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use cargo_component_core::{
     lock::{LockFile, LockFileResolver, LockedPackage, LockedPackageVersion},
-    registry::{DependencyResolution, DependencyResolutionMap, DependencyResolver},
+    registry::{
+        DecodedDependency, Dependency, DependencyResolution, DependencyResolutionMap,
+        DependencyResolver, RegistryPackage,
+    },
 };
 use cargo_metadata::PackageId;
-use semver::Version;
+use semver::{Version, VersionReq};
 use wasm_pkg_client::{
     caching::{CachingClient, FileCache},
     ContentDigest, PackageRef,
 };
+use wit_component::DecodedWasm;
+use wit_parser::UnresolvedPackageGroup;
+
+use crate::{
+    bindings::{local_target_deps_dir, parse_deps_dir, DepsDirPackage},
+    metadata::{ComponentMetadata, Target},
+};
 
-use crate::metadata::ComponentMetadata;
+/// The namespaces of the well-known packages that are resolved from the
+/// registry when a local target references them without a target dependency.
+const WELL_KNOWN_NAMESPACES: &[&str] = &["wasi"];
 
 /// Represents a resolution of dependencies for a Cargo package.
 #[derive(Debug, Clone)]
@@ -22,70 +38,331 @@ pub struct PackageDependencyResolution<'a> {
     pub metadata: &'a ComponentMetadata,
     /// Resolutions for the package's target dependencies.
     pub target_resolutions: DependencyResolutionMap,
+    /// The names of the well-known packages referenced by the package's local
+    /// target that were resolved without a target dependency.
+    ///
+    /// Their resolutions are in `target_resolutions`.
+    pub well_known_target_dependencies: Vec<PackageRef>,
     /// Resolutions for the package's component dependencies.
     pub resolutions: DependencyResolutionMap,
+    /// Resolutions for the package's optional component dependencies that
+    /// are not enabled by a cargo feature and for its dev-dependencies when
+    /// not building tests.
+    ///
+    /// These are locked, but are not imported by the component.
+    pub disabled_resolutions: DependencyResolutionMap,
 }
 
 impl<'a> PackageDependencyResolution<'a> {
     /// Creates a new package dependency resolution for the given package.
     ///
-    /// Returns `Ok(None)` if the package is not a component package.
+    /// Optional dependencies are only enabled if their feature is in the
+    /// given set of cargo features; if `features` is `None`, all optional
+    /// dependencies are enabled.
+    ///
+    /// Dev-dependencies are only enabled if `dev` is true.
+    ///
+    /// Packages served by mirror registries are cached in the given cache
+    /// directory of the client.
     pub async fn new(
         client: Arc<CachingClient<FileCache>>,
+        cache_dir: &Path,
         metadata: &'a ComponentMetadata,
         lock_file: Option<LockFileResolver<'_>>,
+        features: Option<&HashSet<String>>,
+        dev: bool,
     ) -> Result<PackageDependencyResolution<'a>> {
+        let (resolutions, disabled_resolutions) =
+            Self::resolve_deps(client.clone(), cache_dir, metadata, lock_file)
+                .await?
+                .into_iter()
+                .partition(|(name, _)| {
+                    (dev || !metadata.section.dev_dependencies.contains_key(name))
+                        && features
+                            .is_none_or(|features| metadata.dependency_enabled(name, features))
+                });
+
+        let (target_resolutions, well_known_target_dependencies) =
+            Self::resolve_target_deps(client, cache_dir, metadata, lock_file).await?;
+
         Ok(Self {
             metadata,
-            target_resolutions: Self::resolve_target_deps(client.clone(), metadata, lock_file)
-                .await?,
-            resolutions: Self::resolve_deps(client, metadata, lock_file).await?,
+            target_resolutions,
+            well_known_target_dependencies,
+            resolutions,
+            disabled_resolutions,
         })
     }
 
     /// Iterates over all dependency resolutions of the package.
+    ///
+    /// This includes the resolutions of disabled dependencies.
     pub fn all(&self) -> impl Iterator<Item = (&PackageRef, &DependencyResolution)> {
         self.target_resolutions
             .iter()
             .chain(self.resolutions.iter())
+            .chain(self.disabled_resolutions.iter())
     }
 
+    /// Resolves the target dependencies of a package.
+    ///
+    /// Returns the resolutions along with the names of the well-known packages
+    /// that were resolved for the local target without a target dependency.
     async fn resolve_target_deps(
         client: Arc<CachingClient<FileCache>>,
+        cache_dir: &Path,
         metadata: &ComponentMetadata,
         lock_file: Option<LockFileResolver<'_>>,
-    ) -> Result<DependencyResolutionMap> {
+    ) -> Result<(DependencyResolutionMap, Vec<PackageRef>)> {
         let target_deps = metadata.section.target.dependencies();
-        if target_deps.is_empty() {
+        let well_known = Self::well_known_target_deps(metadata)?;
+        if target_deps.is_empty() && well_known.is_empty() {
             return Ok(Default::default());
         }
 
-        let mut resolver = DependencyResolver::new_with_client(client, lock_file)?;
+        let mut resolver = Self::resolver(client.clone(), cache_dir, metadata, lock_file)?;
 
         for (name, dependency) in target_deps.iter() {
             resolver.add_dependency(name, dependency).await?;
         }
 
-        resolver.resolve().await
+        let mut resolutions = resolver.resolve().await?;
+        if let Target::Package { name, .. } = &metadata.section.target {
+            Self::resolve_target_package_deps(
+                client.clone(),
+                cache_dir,
+                metadata,
+                lock_file,
+                name,
+                &mut resolutions,
+            )
+            .await?;
+        }
+
+        if well_known.is_empty() {
+            return Ok((resolutions, Vec::new()));
+        }
+
+        let mut resolver = Self::resolver(client, cache_dir, metadata, lock_file)?;
+        for (name, dependency) in &well_known {
+            log::debug!(
+                "resolving well-known package `{name}` referenced by the local target of package `{package}`",
+                package = metadata.name
+            );
+            resolver
+                .add_dependency(name, dependency)
+                .await
+                .with_context(|| {
+                    format!("failed to resolve well-known package `{name}` referenced by the local target")
+                })?;
+        }
+
+        let resolved = resolver
+            .resolve()
+            .await
+            .context("failed to resolve the well-known packages referenced by the local target")?;
+        let names = resolved.keys().cloned().collect();
+        resolutions.extend(resolved);
+        Ok((resolutions, names))
+    }
+
+    /// Gets the well-known packages referenced by the local target of a
+    /// package that are neither target dependencies nor in the `deps`
+    /// directory of the target.
+    ///
+    /// A package referenced at a version is resolved at exactly that version,
+    /// otherwise at its latest version. Failures to parse the local target are
+    /// left to be reported when its target world is created.
+    fn well_known_target_deps(
+        metadata: &ComponentMetadata,
+    ) -> Result<BTreeMap<PackageRef, Dependency>> {
+        let Some(path) = metadata.target_path() else {
+            return Ok(Default::default());
+        };
+
+        let result = if path.is_dir() {
+            UnresolvedPackageGroup::parse_dir(&path)
+        } else {
+            UnresolvedPackageGroup::parse_file(&path)
+        };
+        let Ok(root) = result else {
+            return Ok(Default::default());
+        };
+
+        let mut defined: HashSet<(String, String)> = metadata
+            .section
+            .target
+            .dependencies()
+            .keys()
+            .map(|name| (name.namespace().to_string(), name.name().to_string()))
+            .collect();
+
+        let mut groups = vec![root];
+        if let Some(dir) = local_target_deps_dir(&path).filter(|dir| dir.is_dir()) {
+            let Ok(packages) = parse_deps_dir(&dir) else {
+                return Ok(Default::default());
+            };
+
+            for (resolution, package) in packages {
+                let name = resolution.name();
+                defined.insert((name.namespace().to_string(), name.name().to_string()));
+                if let DepsDirPackage::Wit(group) = package {
+                    groups.push(group);
+                }
+            }
+        }
+
+        let packages = || {
+            groups
+                .iter()
+                .flat_map(|g| std::iter::once(&g.main).chain(&g.nested))
+        };
+        defined.extend(packages().map(|p| (p.name.namespace.to_string(), p.name.name.to_string())));
+
+        let mut well_known = BTreeMap::new();
+        for name in packages().flat_map(|p| p.foreign_deps.keys()) {
+            if !WELL_KNOWN_NAMESPACES.contains(&name.namespace.as_str())
+                || defined.contains(&(name.namespace.to_string(), name.name.to_string()))
+            {
+                continue;
+            }
+
+            let version = match &name.version {
+                Some(version) => VersionReq::parse(&format!("={version}"))?,
+                None => VersionReq::STAR,
+            };
+
+            let package: PackageRef = format!(
+                "{namespace}:{name}",
+                namespace = name.namespace,
+                name = name.name
+            )
+            .parse()?;
+
+            // Prefer a referenced version over the latest version
+            match well_known.get(&package) {
+                Some(Dependency::Package(RegistryPackage {
+                    version: existing, ..
+                })) if *existing != VersionReq::STAR => {}
+                _ => {
+                    well_known.insert(
+                        package,
+                        Dependency::Package(RegistryPackage {
+                            name: None,
+                            version,
+                            registry: None,
+                        }),
+                    );
+                }
+            }
+        }
+
+        Ok(well_known)
+    }
+
+    /// Resolves the transitive foreign dependencies of a registry target package.
+    ///
+    /// Each foreign package referenced by the target package is resolved from
+    /// the registry at the exact version it references.
+    async fn resolve_target_package_deps(
+        client: Arc<CachingClient<FileCache>>,
+        cache_dir: &Path,
+        metadata: &ComponentMetadata,
+        lock_file: Option<LockFileResolver<'_>>,
+        name: &PackageRef,
+        resolutions: &mut DependencyResolutionMap,
+    ) -> Result<()> {
+        let mut pending = vec![name.clone()];
+        while let Some(name) = pending.pop() {
+            let decoded = resolutions[&name].decode().await?;
+            let DecodedDependency::Wasm {
+                decoded: DecodedWasm::WitPackage(resolve, pkg),
+                ..
+            } = decoded
+            else {
+                continue;
+            };
+
+            let mut dependencies = Vec::new();
+            for (id, package) in &resolve.packages {
+                let Some(version) = package.name.version.as_ref().filter(|_| id != pkg) else {
+                    continue;
+                };
+
+                let dependency: PackageRef = format!(
+                    "{namespace}:{name}",
+                    namespace = package.name.namespace,
+                    name = package.name.name
+                )
+                .parse()?;
+                if resolutions.contains_key(&dependency) {
+                    continue;
+                }
+
+                log::debug!(
+                    "resolving dependency `{dependency}@{version}` of target package `{name}`"
+                );
+                dependencies.push((
+                    dependency,
+                    Dependency::Package(RegistryPackage {
+                        name: None,
+                        version: VersionReq::parse(&format!("={version}"))?,
+                        registry: None,
+                    }),
+                ));
+            }
+
+            if dependencies.is_empty() {
+                continue;
+            }
+
+            let mut resolver = Self::resolver(client.clone(), cache_dir, metadata, lock_file)?;
+            for (dependency, package) in &dependencies {
+                resolver.add_dependency(dependency, package).await?;
+            }
+
+            let resolved = resolver.resolve().await.with_context(|| {
+                format!("failed to resolve the dependencies of target package `{name}`")
+            })?;
+            for (dependency, resolution) in resolved {
+                pending.push(dependency.clone());
+                resolutions.insert(dependency, resolution);
+            }
+        }
+
+        Ok(())
     }
 
     async fn resolve_deps(
         client: Arc<CachingClient<FileCache>>,
+        cache_dir: &Path,
         metadata: &ComponentMetadata,
         lock_file: Option<LockFileResolver<'_>>,
     ) -> Result<DependencyResolutionMap> {
-        if metadata.section.dependencies.is_empty() {
+        if metadata.all_dependencies().next().is_none() {
             return Ok(Default::default());
         }
 
-        let mut resolver = DependencyResolver::new_with_client(client, lock_file)?;
+        let mut resolver = Self::resolver(client, cache_dir, metadata, lock_file)?;
 
-        for (name, dependency) in &metadata.section.dependencies {
+        for (name, dependency) in metadata.all_dependencies() {
             resolver.add_dependency(name, dependency).await?;
         }
 
         resolver.resolve().await
     }
+
+    /// Creates a dependency resolver falling back to the mirrors of the package.
+    fn resolver<'b>(
+        client: Arc<CachingClient<FileCache>>,
+        cache_dir: &Path,
+        metadata: &ComponentMetadata,
+        lock_file: Option<LockFileResolver<'b>>,
+    ) -> Result<DependencyResolver<'b>> {
+        Ok(DependencyResolver::new_with_client(client, lock_file)?
+            .with_mirrors(metadata.section.mirrors.clone())
+            .with_cache_dir(cache_dir.to_owned()))
+    }
 }
 
 /// Represents a mapping between all component packages and their dependency resolutions.
@@ -113,7 +390,7 @@ impl<'a> PackageResolutionMap<'a> {
     /// Converts the resolution map into a lock file.
     pub fn to_lock_file(&self) -> LockFile {
         type PackageKey = (PackageRef, Option<String>);
-        type VersionsMap = HashMap<String, (Version, ContentDigest)>;
+        type VersionsMap = HashMap<String, (Version, ContentDigest, Option<String>)>;
         let mut packages: HashMap<PackageKey, VersionsMap> = HashMap::new();
 
         for resolution in self.0.values() {
@@ -130,10 +407,10 @@ impl<'a> PackageResolutionMap<'a> {
                             .or_default()
                             .insert(
                                 pkg.requirement.to_string(),
-                                (pkg.version.clone(), pkg.digest.clone()),
+                                (pkg.version.clone(), pkg.digest.clone(), pkg.source.clone()),
                             );
 
-                        if let Some((prev, _)) = prev {
+                        if let Some((prev, _, _)) = prev {
                             // The same requirements should resolve to the same version
                             assert!(prev == pkg.version)
                         }
@@ -148,11 +425,14 @@ impl<'a> PackageResolutionMap<'a> {
             .map(|((name, registry), versions)| {
                 let mut versions: Vec<LockedPackageVersion> = versions
                     .into_iter()
-                    .map(|(requirement, (version, digest))| LockedPackageVersion {
-                        requirement,
-                        version,
-                        digest,
-                    })
+                    .map(
+                        |(requirement, (version, digest, source))| LockedPackageVersion {
+                            requirement,
+                            version,
+                            digest,
+                            source,
+                        },
+                    )
                     .collect();
 
                 versions.sort_by(|a, b| a.key().cmp(b.key()));
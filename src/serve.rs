@@ -0,0 +1,212 @@
+//! Module for serving multiple components behind a single HTTP frontend.
+//!
+//! Each component is served by its own runner process listening on a
+//! local address; a small reverse proxy listening on the frontend address
+//! forwards each request to the component with the longest matching route.
+
+use std::{
+    env,
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use cargo_config2::PathAndArgs;
+
+use crate::{config::Config, metadata::Runtime, CommandStatus};
+
+/// The environment variable used to override the frontend address.
+pub const SERVE_ADDR_ENV_VAR: &str = "CARGO_COMPONENT_SERVE_ADDR";
+
+/// The default frontend address; this is the same default as `wasmtime serve`.
+const DEFAULT_SERVE_ADDR: &str = "0.0.0.0:8080";
+
+/// Represents a component to serve at a route.
+pub struct Route<'a> {
+    /// The path prefix routed to the component.
+    pub path: &'a str,
+    /// The display name of the component.
+    pub display: &'a str,
+    /// The path to the component.
+    pub component: &'a Path,
+    /// The runtime configuration of the component.
+    pub runtime: &'a Runtime,
+}
+
+/// The runner processes serving the components.
+///
+/// The processes are killed when dropped.
+struct Backends(Vec<Child>);
+
+impl Drop for Backends {
+    fn drop(&mut self) {
+        for child in &mut self.0 {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Serves the given components behind a single frontend.
+///
+/// This returns when any of the runners exit.
+pub fn serve(
+    config: &Config,
+    runner: &PathAndArgs,
+    output_args: &[String],
+    mut routes: Vec<Route<'_>>,
+) -> Result<CommandStatus> {
+    // Sort the longest routes first so that the most specific route matches
+    routes.sort_by(|a, b| b.path.len().cmp(&a.path.len()).then(a.path.cmp(b.path)));
+    if let Some(w) = routes.windows(2).find(|w| w[0].path == w[1].path) {
+        bail!(
+            "route `{route}` is configured for both {first} and {second}",
+            route = w[0].path,
+            first = w[0].display,
+            second = w[1].display,
+        );
+    }
+
+    let addr = env::var(SERVE_ADDR_ENV_VAR).unwrap_or_else(|_| DEFAULT_SERVE_ADDR.to_string());
+    let listener =
+        TcpListener::bind(&addr).with_context(|| format!("failed to bind to address `{addr}`"))?;
+
+    let mut backends = Backends(Vec::new());
+    let mut table = Vec::new();
+    for route in &routes {
+        let backend = local_addr()?;
+        config.terminal().status(
+            "Serving",
+            format!(
+                "{display} at `{path}`",
+                display = route.display,
+                path = route.path
+            ),
+        )?;
+
+        let mut cmd = Command::new(&runner.path);
+        cmd.args(&runner.args)
+            .arg("--addr")
+            .arg(backend.to_string())
+            .args(route.runtime.runner_flags())
+            .arg("--")
+            .arg(route.component)
+            .args(&route.runtime.args)
+            .args(output_args.iter().skip(1))
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+        log::debug!("spawning command {:?}", cmd);
+
+        backends.0.push(cmd.spawn().context(format!(
+            "failed to spawn `{runner}`",
+            runner = runner.path.display()
+        ))?);
+        table.push((route.path.to_string(), backend));
+    }
+
+    config.terminal().status(
+        "Listening",
+        format!(
+            "on http://{addr}",
+            addr = listener.local_addr().map(|a| a.to_string()).unwrap_or(addr)
+        ),
+    )?;
+
+    let table = Arc::new(table);
+    thread::spawn(move || {
+        for stream in listener.incoming().map_while(Result::ok) {
+            let table = table.clone();
+            thread::spawn(move || {
+                if let Err(e) = forward(stream, &table) {
+                    log::debug!("failed to forward request: {e:#}");
+                }
+            });
+        }
+    });
+
+    loop {
+        for child in &mut backends.0 {
+            if let Some(status) = child.try_wait().context(format!(
+                "failed to wait for `{runner}` to finish",
+                runner = runner.path.display()
+            ))? {
+                return Ok(CommandStatus::from_exit_status(status));
+            }
+        }
+
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+/// Finds an unused local address for a runner to listen on.
+fn local_addr() -> Result<SocketAddr> {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .context("failed to find an unused local address")
+}
+
+/// Determines if a request path matches the given route.
+fn route_matches(route: &str, path: &str) -> bool {
+    match path.strip_prefix(route) {
+        Some(rest) => route.ends_with('/') || rest.is_empty() || rest.starts_with(['/', '?']),
+        None => false,
+    }
+}
+
+/// Forwards a single request to the runner serving the matching route.
+///
+/// Requests are forwarded with `Connection: close` so that the response
+/// ends when the runner closes the connection.
+fn forward(mut client: TcpStream, table: &[(String, SocketAddr)]) -> Result<()> {
+    let mut reader = BufReader::new(client.try_clone()?);
+
+    let mut head = String::new();
+    reader.read_line(&mut head)?;
+    let path = head.split(' ').nth(1).unwrap_or_default();
+
+    let Some((_, addr)) = table.iter().find(|(route, _)| route_matches(route, path)) else {
+        return respond(&mut client, "404 Not Found");
+    };
+
+    let Ok(mut backend) = TcpStream::connect(addr) else {
+        return respond(&mut client, "502 Bad Gateway");
+    };
+
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim_end().is_empty() {
+            break;
+        }
+
+        if !line.to_ascii_lowercase().starts_with("connection:") {
+            head.push_str(&line);
+        }
+    }
+
+    head.push_str("Connection: close\r\n\r\n");
+    backend.write_all(head.as_bytes())?;
+
+    // Forward any request body while the response is being copied back
+    let mut body = backend.try_clone()?;
+    thread::spawn(move || {
+        let _ = io::copy(&mut reader, &mut body);
+    });
+
+    io::copy(&mut backend, &mut client)?;
+    let _ = client.shutdown(Shutdown::Both);
+    Ok(())
+}
+
+/// Responds to a request with an empty body and the given status.
+fn respond(client: &mut TcpStream, status: &str) -> Result<()> {
+    write!(
+        client,
+        "HTTP/1.1 {status}\r\ncontent-length: 0\r\nconnection: close\r\n\r\n"
+    )?;
+    Ok(())
+}
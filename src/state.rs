@@ -0,0 +1,247 @@
+//! Module for the state of the last build of a workspace.
+//!
+//! The state is stored in `target/component/state.json` and records, for each
+//! component package, the component dependencies it was last resolved to, the
+//! state of its generated bindings and the digests of the adapters its outputs
+//! were componentized with.
+//!
+//! A state written by a different version of `cargo-component` is discarded,
+//! so that everything it recorded is generated again after an upgrade.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use blake3::Hasher;
+use cargo_component_core::registry::DependencyResolution;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bindings_path,
+    freshness::{
+        hash_path, hash_target_path, rust_sources, update, write_if_changed, BindingsState,
+        FileSystem,
+    },
+    metadata::ComponentMetadata,
+};
+
+/// The name of the file the workspace state is stored in.
+pub const STATE_FILE_NAME: &str = "state.json";
+
+/// Gets the version of `cargo-component` recorded in the state.
+fn tool_version() -> &'static str {
+    option_env!("CARGO_VERSION_INFO").unwrap_or(env!("CARGO_PKG_VERSION"))
+}
+
+/// Represents the state of the last build of a workspace.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WorkspaceState {
+    /// The version of `cargo-component` that wrote the state.
+    pub version: String,
+    /// The state of each component package, by package name.
+    pub packages: BTreeMap<String, PackageState>,
+}
+
+impl Default for WorkspaceState {
+    fn default() -> Self {
+        Self {
+            version: tool_version().to_string(),
+            packages: Default::default(),
+        }
+    }
+}
+
+impl WorkspaceState {
+    /// Gets the path of the state of the workspace with the given target
+    /// directory.
+    pub fn path(target_dir: &Path) -> PathBuf {
+        target_dir.join("component").join(STATE_FILE_NAME)
+    }
+
+    /// Reads the state of the workspace with the given target directory.
+    ///
+    /// A missing or invalid state, or one written by a different version of
+    /// `cargo-component`, is treated as an empty state.
+    pub fn read(fs: &dyn FileSystem, target_dir: &Path) -> Self {
+        let path = Self::path(target_dir);
+        let state = fs
+            .read(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice::<Self>(&contents).ok());
+
+        match state {
+            Some(state) if state.version == tool_version() => state,
+            Some(state) => {
+                log::debug!(
+                    "discarding state `{path}` written by cargo-component {version}",
+                    path = path.display(),
+                    version = state.version
+                );
+                Self::default()
+            }
+            None => Self::default(),
+        }
+    }
+
+    /// Writes the state of the workspace with the given target directory.
+    pub fn write(&self, fs: &dyn FileSystem, target_dir: &Path) -> Result<()> {
+        let path = Self::path(target_dir);
+        let contents = serde_json::to_vec_pretty(self)?;
+        fs.create_dir_all(path.parent().unwrap())
+            .and_then(|_| write_if_changed(fs, &path, &contents))
+            .with_context(|| {
+                format!(
+                    "failed to write workspace state `{path}`",
+                    path = path.display()
+                )
+            })?;
+        Ok(())
+    }
+
+    /// Gets the state of the given package, creating an empty one if the
+    /// package has no state yet.
+    pub fn package_mut(&mut self, name: &str) -> &mut PackageState {
+        self.packages.entry(name.to_string()).or_default()
+    }
+}
+
+/// Represents the state of the last build of a component package.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PackageState {
+    /// The resolutions of the target dependencies of the package.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub target_dependencies: BTreeMap<String, ResolutionState>,
+    /// The resolutions of the enabled component dependencies of the package.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, ResolutionState>,
+    /// The state of the generated bindings of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bindings: Option<BindingsState>,
+    /// The adapters the outputs of the package were componentized with, by
+    /// output path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub adapters: BTreeMap<PathBuf, AdapterState>,
+}
+
+impl PackageState {
+    /// Computes the hash of the inputs the bindings of the package are
+    /// generated from.
+    ///
+    /// The hash covers the manifest, the selected profile, the local WIT of the
+    /// package (with the `deps` directory of a single-file target) and the
+    /// contents of its recorded component dependencies. When unreferenced
+    /// imports are trimmed from the bindings, it also covers the Rust sources
+    /// next to the bindings file.
+    pub fn bindings_inputs(
+        &self,
+        fs: &dyn FileSystem,
+        metadata: &ComponentMetadata,
+        profile: &str,
+    ) -> Result<String> {
+        let mut hasher = Hasher::new();
+        update(&mut hasher, &metadata.digest);
+        update(&mut hasher, profile);
+        if let Some(path) = metadata.target_path() {
+            hash_target_path(fs, &mut hasher, &path)?;
+        }
+
+        if metadata.section.bindings.trim_imports {
+            let bindings_path = bindings_path(metadata);
+            let dir = bindings_path.parent().unwrap();
+            for source in rust_sources(fs, dir, &bindings_path).with_context(|| {
+                format!(
+                    "failed to read source directory `{dir}`",
+                    dir = dir.display()
+                )
+            })? {
+                hash_path(fs, &mut hasher, dir, &source)?;
+            }
+        }
+
+        for (kind, dependencies) in [
+            ("target", &self.target_dependencies),
+            ("component", &self.dependencies),
+        ] {
+            for (name, resolution) in dependencies {
+                update(&mut hasher, kind);
+                update(&mut hasher, name);
+                match resolution {
+                    ResolutionState::Registry { digest, .. } => update(&mut hasher, digest),
+                    ResolutionState::Local { path } => hash_path(fs, &mut hasher, path, path)?,
+                }
+            }
+        }
+
+        Ok(hasher.finalize().to_hex().to_string())
+    }
+
+    /// Determines if the bindings of the package at the given path are
+    /// up-to-date.
+    pub fn bindings_fresh(
+        &self,
+        fs: &dyn FileSystem,
+        metadata: &ComponentMetadata,
+        profile: &str,
+        bindings_path: &Path,
+    ) -> Result<bool> {
+        let Some(bindings) = &self.bindings else {
+            return Ok(false);
+        };
+
+        Ok(bindings.is_fresh(
+            fs,
+            &self.bindings_inputs(fs, metadata, profile)?,
+            bindings_path,
+        ))
+    }
+}
+
+/// Represents the adapter an output was componentized with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct AdapterState {
+    /// Whether the output was componentized as a command component.
+    pub command: bool,
+    /// The hash of the adapter module.
+    pub digest: String,
+}
+
+/// Represents the recorded resolution of a component dependency.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", tag = "kind")]
+pub enum ResolutionState {
+    /// The dependency was resolved from a registry package.
+    Registry {
+        /// The name of the package.
+        package: String,
+        /// The resolved version of the package.
+        version: Version,
+        /// The digest of the package contents.
+        digest: String,
+    },
+    /// The dependency was resolved from a local path.
+    Local {
+        /// The path to the dependency.
+        path: PathBuf,
+    },
+}
+
+impl From<&DependencyResolution> for ResolutionState {
+    fn from(resolution: &DependencyResolution) -> Self {
+        match resolution {
+            DependencyResolution::Registry(res) => Self::Registry {
+                package: res.package.to_string(),
+                version: res.version.clone(),
+                digest: res.digest.to_string(),
+            },
+            DependencyResolution::Local(res) => Self::Local {
+                path: res.path.clone(),
+            },
+        }
+    }
+}
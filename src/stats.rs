@@ -0,0 +1,192 @@
+//! Module for collecting build statistics.
+//!
+//! Statistics are only collected when `--stats` is given on the command
+//! line; they are printed to the terminal and never sent anywhere.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use cargo_component_core::{registry::DependencyResolution, terminal::Terminal};
+
+/// Represents the size of a componentized output.
+struct ComponentSize {
+    /// The path to the component.
+    path: PathBuf,
+    /// The size of the core module the component was created from.
+    core: u64,
+    /// The size of the component.
+    component: u64,
+}
+
+/// Statistics collected while running a cargo command.
+pub struct BuildStats {
+    /// The directory of the package content cache.
+    cache_dir: PathBuf,
+    /// The names of the files in the content cache before the build.
+    cached: HashSet<String>,
+    /// The number of resolved local dependencies.
+    local: usize,
+    /// Maps the content digest of each resolved registry package to whether
+    /// its content was already cached before the build.
+    registry: HashMap<String, bool>,
+    /// The number of lines of generated bindings.
+    bindings_lines: usize,
+    /// The sizes of the componentized outputs.
+    components: Vec<ComponentSize>,
+}
+
+impl BuildStats {
+    /// Creates a new, empty set of statistics using the given cache directory.
+    ///
+    /// The contents of the cache directory are recorded so that cache hits
+    /// can be determined after packages are fetched during resolution.
+    pub fn new(cache_dir: PathBuf) -> Self {
+        let cached = fs::read_dir(&cache_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .collect();
+
+        Self {
+            cache_dir,
+            cached,
+            local: 0,
+            registry: HashMap::new(),
+            bindings_lines: 0,
+            components: Vec::new(),
+        }
+    }
+
+    /// Records the given dependency resolutions.
+    pub fn record_resolutions<'a>(
+        &mut self,
+        resolutions: impl Iterator<Item = &'a DependencyResolution>,
+    ) {
+        for resolution in resolutions {
+            match resolution {
+                DependencyResolution::Registry(res) => {
+                    let digest = res.digest.to_string();
+                    if !self.registry.contains_key(&digest) {
+                        let cached = self.cached.contains(&digest);
+                        self.registry.insert(digest, cached);
+                    }
+                }
+                DependencyResolution::Local(_) => self.local += 1,
+            }
+        }
+    }
+
+    /// Records generated bindings source.
+    pub fn record_bindings(&mut self, source: &str) {
+        self.bindings_lines += source.lines().count();
+    }
+
+    /// Records a component created from a core module of the given size.
+    pub fn record_component(&mut self, path: &Path, core: u64) -> Result<()> {
+        let component = fs::metadata(path)?.len();
+        self.components.push(ComponentSize {
+            path: path.to_path_buf(),
+            core,
+            component,
+        });
+        Ok(())
+    }
+
+    /// Prints the statistics to the given terminal.
+    pub fn print(&self, terminal: &Terminal, cwd: &Path) -> Result<()> {
+        let cache_hits = self.registry.values().filter(|cached| **cached).count();
+
+        // Registry packages that were not cached were downloaded into the cache
+        let downloaded: u64 = self
+            .registry
+            .iter()
+            .filter(|(_, cached)| !**cached)
+            .filter_map(|(digest, _)| fs::metadata(self.cache_dir.join(digest)).ok())
+            .map(|m| m.len())
+            .sum();
+
+        terminal.status(
+            "Statistics",
+            format!(
+                "resolved {count} package{s} ({registry} from registries, {local} local)",
+                count = self.registry.len() + self.local,
+                s = if self.registry.len() + self.local == 1 {
+                    ""
+                } else {
+                    "s"
+                },
+                registry = self.registry.len(),
+                local = self.local,
+            ),
+        )?;
+
+        terminal.status(
+            "Statistics",
+            format!(
+                "downloaded {downloaded}, cache hit rate {rate} ({cache_hits} of {total} registry packages)",
+                downloaded = format_bytes(downloaded),
+                rate = if self.registry.is_empty() {
+                    "n/a".to_string()
+                } else {
+                    format!("{:.0}%", cache_hits as f64 * 100.0 / self.registry.len() as f64)
+                },
+                total = self.registry.len(),
+            ),
+        )?;
+
+        terminal.status(
+            "Statistics",
+            format!(
+                "generated {lines} line{s} of bindings",
+                lines = self.bindings_lines,
+                s = if self.bindings_lines == 1 { "" } else { "s" }
+            ),
+        )?;
+
+        for ComponentSize {
+            path,
+            core,
+            component,
+        } in &self.components
+        {
+            terminal.status(
+                "Statistics",
+                format!(
+                    "component `{path}` is {component} (core module {core}, {change:+.1}%)",
+                    path = path.strip_prefix(cwd).unwrap_or(path).display(),
+                    component = format_bytes(*component),
+                    core = format_bytes(*core),
+                    change = if *core == 0 {
+                        0.0
+                    } else {
+                        (*component as f64 - *core as f64) * 100.0 / *core as f64
+                    },
+                ),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Formats a size in bytes using binary units.
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["KiB", "MiB", "GiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {unit}", unit = UNITS[unit])
+}
@@ -0,0 +1,265 @@
+//! Module for explaining why a package is in the dependency resolution of a
+//! component package.
+//!
+//! The explanation is an inverted tree rooted at the queried package, similar
+//! to `cargo tree -i`: its children are the manifest entries that depend on
+//! the package directly, the items of the target world that need it, and the
+//! packages that depend on it, each with their own explanation.
+
+use std::fmt::Write;
+
+use cargo_component_core::registry::{DependencyResolution, DependencyResolutionMap};
+use wasm_pkg_client::PackageRef;
+use wit_parser::{PackageId, Resolve, WorldId, WorldItem, WorldKey};
+
+use crate::{graph::describe_resolution, metadata::Target, registry::PackageDependencyResolution};
+
+/// Represents a node of the explanation tree.
+struct Node {
+    /// The label of the node.
+    label: String,
+    /// The reasons for the node.
+    children: Vec<Node>,
+}
+
+/// Explains why a package is in the dependency resolution of a component
+/// package.
+pub struct Why {
+    root: Node,
+}
+
+impl Why {
+    /// Explains why the given package is in the resolution of the component
+    /// package with the given name.
+    ///
+    /// Returns `None` if the package is not in the resolution.
+    pub fn new(
+        name: &str,
+        query: &PackageRef,
+        resolution: &PackageDependencyResolution,
+        resolve: &Resolve,
+        world: WorldId,
+    ) -> Option<Self> {
+        let package = resolve.packages.iter().find_map(|(id, p)| {
+            (p.name.namespace == query.namespace().as_ref() && p.name.name == query.name().as_ref())
+                .then_some(id)
+        });
+
+        let explainer = Explainer {
+            resolution,
+            resolve,
+            world,
+        };
+
+        let mut children = explainer.manifest_entries(query);
+        let label = match package {
+            Some(id) => {
+                children.extend(explainer.explain(id, &mut vec![id]));
+                explainer
+                    .find_resolution(query)
+                    .map(describe_resolution)
+                    .unwrap_or_else(|| resolve.packages[id].name.to_string())
+            }
+            None => describe_resolution(explainer.find_resolution(query)?),
+        };
+
+        Some(Self {
+            root: Node {
+                label: format!("{label} in package `{name}`"),
+                children,
+            },
+        })
+    }
+
+    /// Renders the explanation as a tree.
+    pub fn render(&self) -> String {
+        let mut output = String::new();
+        writeln!(output, "{label}", label = self.root.label).unwrap();
+        render_children(&mut output, &self.root.children, "");
+        output
+    }
+}
+
+struct Explainer<'a> {
+    resolution: &'a PackageDependencyResolution<'a>,
+    resolve: &'a Resolve,
+    world: WorldId,
+}
+
+impl Explainer<'_> {
+    /// Explains why a package of the resolve is needed.
+    ///
+    /// The stack holds the packages being explained to avoid cycles.
+    fn explain(&self, package: PackageId, stack: &mut Vec<PackageId>) -> Vec<Node> {
+        let mut nodes = Vec::new();
+        let world = &self.resolve.worlds[self.world];
+
+        let items = world
+            .imports
+            .iter()
+            .map(|i| (i, "imports"))
+            .chain(world.exports.iter().map(|i| (i, "exports")));
+        for ((key, item), direction) in items {
+            let id = match (key, item) {
+                (WorldKey::Interface(id), _)
+                | (WorldKey::Name(_), WorldItem::Interface { id, .. }) => *id,
+                _ => continue,
+            };
+
+            if self.resolve.interfaces[id].package == Some(package) {
+                nodes.push(Node {
+                    label: format!(
+                        "world `{world}` {direction} `{item}`",
+                        world = self.world_name(),
+                        item = self.resolve.name_world_key(key)
+                    ),
+                    children: Vec::new(),
+                });
+            }
+        }
+
+        for (id, dependent) in self.resolve.packages.iter() {
+            if stack.contains(&id) || !self.resolve.package_direct_deps(id).any(|d| d == package) {
+                continue;
+            }
+
+            // The target package is only of interest for the world items
+            // of its own interfaces; its imports are explained already
+            let target = world.package == Some(id);
+            let mut children = if target {
+                Vec::new()
+            } else {
+                format!(
+                    "{ns}:{name}",
+                    ns = dependent.name.namespace,
+                    name = dependent.name.name
+                )
+                .parse::<PackageRef>()
+                .map(|q| self.manifest_entries(&q))
+                .unwrap_or_default()
+            };
+
+            stack.push(id);
+            children.extend(self.explain(id, stack));
+            stack.pop();
+
+            if target && children.is_empty() {
+                continue;
+            }
+
+            nodes.push(Node {
+                label: format!(
+                    "used by {name}{suffix}",
+                    name = dependent.name,
+                    suffix = if target { " (the target package)" } else { "" }
+                ),
+                children,
+            });
+        }
+
+        nodes
+    }
+
+    /// Gets the manifest entries that depend on the given package directly.
+    fn manifest_entries(&self, query: &PackageRef) -> Vec<Node> {
+        let section = &self.resolution.metadata.section;
+        let mut nodes = Vec::new();
+        let mut push = |map: &DependencyResolutionMap, describe: &dyn Fn(&PackageRef) -> String| {
+            for (key, dependency) in map {
+                if key == query || resolved_package(dependency) == query {
+                    nodes.push(Node {
+                        label: describe(key),
+                        children: Vec::new(),
+                    });
+                }
+            }
+        };
+
+        push(
+            &self.resolution.target_resolutions,
+            &|key| match &section.target {
+                Target::Package { name, .. } if name == key => {
+                    format!("the `target` setting `{key}`")
+                }
+                _ => format!("`[package.metadata.component.target.dependencies]` entry `{key}`"),
+            },
+        );
+        push(&self.resolution.resolutions, &|key| {
+            format!(
+                "`[package.metadata.component.{table}]` entry `{key}`",
+                table = table_name(section.dev_dependencies.contains_key(key))
+            )
+        });
+        push(&self.resolution.disabled_resolutions, &|key| {
+            format!(
+                "`[package.metadata.component.{table}]` entry `{key}` (locked, but not imported \
+                 by the component)",
+                table = table_name(section.dev_dependencies.contains_key(key))
+            )
+        });
+
+        nodes
+    }
+
+    /// Finds the resolution of the given package in the resolution maps.
+    fn find_resolution(&self, query: &PackageRef) -> Option<&DependencyResolution> {
+        [
+            &self.resolution.target_resolutions,
+            &self.resolution.resolutions,
+            &self.resolution.disabled_resolutions,
+        ]
+        .into_iter()
+        .flat_map(|map| map.iter())
+        .find(|(key, dependency)| *key == query || resolved_package(dependency) == query)
+        .map(|(_, dependency)| dependency)
+    }
+
+    fn world_name(&self) -> String {
+        let world = &self.resolve.worlds[self.world];
+        match world.package {
+            Some(pkg) => format!(
+                "{pkg}/{name}",
+                pkg = self.resolve.packages[pkg].name,
+                name = world.name
+            ),
+            None => world.name.clone(),
+        }
+    }
+}
+
+/// Gets the name of the package a dependency resolved to.
+fn resolved_package(dependency: &DependencyResolution) -> &PackageRef {
+    match dependency {
+        DependencyResolution::Registry(res) => &res.package,
+        DependencyResolution::Local(res) => &res.name,
+    }
+}
+
+fn table_name(dev: bool) -> &'static str {
+    if dev {
+        "dev-dependencies"
+    } else {
+        "dependencies"
+    }
+}
+
+fn render_children(output: &mut String, children: &[Node], prefix: &str) {
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        writeln!(
+            output,
+            "{prefix}{branch}{label}",
+            branch = if last { "└── " } else { "├── " },
+            label = child.label
+        )
+        .unwrap();
+        render_children(
+            output,
+            &child.children,
+            &format!(
+                "{prefix}{indent}",
+                indent = if last { "    " } else { "│   " }
+            ),
+        );
+    }
+}
@@ -0,0 +1,204 @@
+//! Module for caching the target worlds parsed from local WIT.
+//!
+//! Parsing and resolving a large local WIT tree (e.g. one vendoring all of
+//! the WASI worlds) dominates the time spent generating bindings. The resolved
+//! target world is therefore stored under
+//! `target/component/wit-cache/<package>`, keyed by a BLAKE3 hash of the content of
+//! every WIT file and target dependency it was created from, and reused until
+//! any of them changes.
+//!
+//! Each package of the resolved target world is stored in its binary encoding,
+//! in the order the packages were resolved.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use blake3::Hasher;
+use cargo_component_core::registry::DependencyResolution;
+use serde::{Deserialize, Serialize};
+use wit_component::DecodedWasm;
+use wit_parser::{Resolve, WorldId};
+
+use crate::{
+    freshness::{hash_path, hash_target_path, update, FileSystem},
+    registry::PackageDependencyResolution,
+};
+
+/// The name of the file describing a cache entry.
+const ENTRY_FILE_NAME: &str = "target.json";
+
+/// Represents the description of a cached target world.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CachedTarget {
+    /// The name of the package of the target world.
+    package: String,
+    /// The name of the target world.
+    world: String,
+    /// The number of packages stored in the entry.
+    packages: usize,
+    /// The WIT files the target world was parsed from.
+    source_files: Vec<PathBuf>,
+}
+
+/// Represents the cache entry of the target world of a package.
+pub struct WitCache {
+    /// The directory containing the cache entries of the package.
+    dir: PathBuf,
+    /// The name of the package whose target world is cached.
+    name: String,
+    /// The key of the cache entry.
+    key: String,
+}
+
+impl WitCache {
+    /// Creates the cache entry for the local target world of a package.
+    ///
+    /// The key of the entry is computed from the WIT at the given path (and
+    /// the `deps` directory next to a single-file target), the target
+    /// dependencies of the package and the selected world.
+    pub fn new(
+        fs: &dyn FileSystem,
+        target_dir: &Path,
+        resolution: &PackageDependencyResolution<'_>,
+        path: &Path,
+        world: Option<&str>,
+    ) -> Result<Self> {
+        let mut hasher = Hasher::new();
+        update(&mut hasher, env!("CARGO_PKG_VERSION"));
+        update(&mut hasher, world.unwrap_or_default());
+        hash_target_path(fs, &mut hasher, path)?;
+
+        let mut dependencies: Vec<_> = resolution.target_resolutions.iter().collect();
+        dependencies.sort_by_key(|(name, _)| name.to_string());
+        for (name, dependency) in dependencies {
+            update(&mut hasher, name.to_string());
+            match dependency {
+                DependencyResolution::Registry(res) => update(&mut hasher, res.digest.to_string()),
+                DependencyResolution::Local(res) => {
+                    hash_path(fs, &mut hasher, &res.path, &res.path)?
+                }
+            }
+        }
+
+        Ok(Self {
+            dir: target_dir
+                .join("component")
+                .join("wit-cache")
+                .join(&resolution.metadata.name),
+            name: resolution.metadata.name.clone(),
+            key: hasher.finalize().to_hex().to_string(),
+        })
+    }
+
+    /// Gets the directory of the cache entry.
+    fn entry_dir(&self) -> PathBuf {
+        self.dir.join(&self.key)
+    }
+
+    /// Loads the cached target world, if present.
+    ///
+    /// A missing or unreadable entry is treated as a cache miss.
+    pub fn load(&self) -> Option<(Resolve, WorldId, Vec<PathBuf>)> {
+        let dir = self.entry_dir();
+        if !dir.is_dir() {
+            return None;
+        }
+
+        match Self::read_entry(&dir) {
+            Ok(entry) => {
+                log::debug!(
+                    "using cached target world for package `{name}` from `{dir}`",
+                    name = self.name,
+                    dir = dir.display()
+                );
+                Some(entry)
+            }
+            Err(e) => {
+                log::debug!(
+                    "ignoring invalid WIT cache entry `{dir}`: {e:?}",
+                    dir = dir.display()
+                );
+                None
+            }
+        }
+    }
+
+    fn read_entry(dir: &Path) -> Result<(Resolve, WorldId, Vec<PathBuf>)> {
+        let target: CachedTarget = serde_json::from_slice(&fs::read(dir.join(ENTRY_FILE_NAME))?)?;
+
+        let mut merged = Resolve::default();
+        for i in 0..target.packages {
+            let bytes = fs::read(dir.join(format!("{i}.wasm")))?;
+            let DecodedWasm::WitPackage(resolve, _) = wit_component::decode(&bytes)? else {
+                bail!("cached package {i} is not a WIT package");
+            };
+
+            merged.merge(resolve)?;
+        }
+
+        let world = merged
+            .packages
+            .iter()
+            .find(|(_, p)| p.name.to_string() == target.package)
+            .and_then(|(_, p)| p.worlds.get(&target.world).copied())
+            .with_context(|| {
+                format!(
+                    "world `{world}` of package `{package}` is missing",
+                    world = target.world,
+                    package = target.package
+                )
+            })?;
+
+        Ok((merged, world, target.source_files))
+    }
+
+    /// Stores a target world in the cache, replacing previous entries of the
+    /// package.
+    ///
+    /// Failures are not fatal as the target world is parsed again on a miss.
+    pub fn store(&self, resolve: &Resolve, world: WorldId, source_files: &[PathBuf]) {
+        if let Err(e) = self.write_entry(resolve, world, source_files) {
+            log::debug!(
+                "failed to cache target world for package `{name}`: {e:?}",
+                name = self.name
+            );
+        }
+    }
+
+    fn write_entry(
+        &self,
+        resolve: &Resolve,
+        world: WorldId,
+        source_files: &[PathBuf],
+    ) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+
+        let dir = self.entry_dir();
+        fs::create_dir_all(&dir)?;
+
+        for (i, (id, _)) in resolve.packages.iter().enumerate() {
+            let bytes = wit_component::encode(resolve, id)?;
+            fs::write(dir.join(format!("{i}.wasm")), bytes)?;
+        }
+
+        let world = &resolve.worlds[world];
+        let target = CachedTarget {
+            package: resolve.packages[world.package.context("world has no package")?]
+                .name
+                .to_string(),
+            world: world.name.clone(),
+            packages: resolve.packages.len(),
+            source_files: source_files.to_vec(),
+        };
+
+        // The description is written last so that partial entries are ignored
+        fs::write(dir.join(ENTRY_FILE_NAME), serde_json::to_vec(&target)?)?;
+        Ok(())
+    }
+}
@@ -2,13 +2,12 @@ use std::{fs, rc::Rc};
 
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_core::command::CONFIG_FILE_ENV_VAR;
+use cargo_component_test_support::*;
 use predicates::{prelude::*, str::contains};
 use tempfile::TempDir;
 use toml_edit::value;
-
-use crate::support::*;
-
-mod support;
+use wasm_pkg_client::RegistryMapping;
 
 #[test]
 fn help() {
@@ -43,6 +42,63 @@ async fn validate_the_package_exists() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn validate_the_retry_configuration() -> Result<()> {
+    let (server, _, _) = spawn_server(["foo"]).await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+
+    project
+        .cargo_component(["add", "foo:bar"])
+        .env("CARGO_COMPONENT_NET_RETRY", "many")
+        .assert()
+        .stderr(contains(
+            "invalid number of retries `many` in `CARGO_COMPONENT_NET_RETRY`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_retries_unreachable_registries() -> Result<()> {
+    let (server, mut config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+
+    config.set_namespace_registry(
+        "test".parse()?,
+        RegistryMapping::Registry("localhost:1".parse()?),
+    );
+    let config_file = project.root().join("unreachable-config.json");
+    config.to_file(&config_file).await?;
+
+    project
+        .cargo_component(["add", "test:bar"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .env("CARGO_COMPONENT_NET_RETRY", "1")
+        .assert()
+        .stderr(contains("(attempt 1 of 2)"))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_does_not_retry_missing_packages() -> Result<()> {
+    let (server, _, _) = spawn_server(["foo"]).await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+
+    project
+        .cargo_component(["add", "foo:bar"])
+        .assert()
+        .stderr(contains("package `foo:bar` was not found").and(contains("retrying").not()))
+        .failure();
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn validate_the_version_exists() -> Result<()> {
     let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
@@ -71,6 +127,35 @@ async fn validate_the_version_exists() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_dev_dependencies() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config, "test:bar", "1.1.0", "(component)").await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+
+    project
+        .cargo_component(["add", "--dev", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.1.0`"))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains("[package.metadata.component.dev-dependencies]").eval(&manifest));
+    assert!(contains(r#""test:bar" = "1.1.0""#).eval(&manifest));
+
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains(
+            "cannot add dependency `test:bar` as it conflicts with an existing dependency",
+        ))
+        .failure();
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn adds_dependencies_to_target_component() -> Result<()> {
     let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
@@ -192,3 +277,224 @@ resolver = "2"
     validate_add_from_path(&p2)?;
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_dependencies_as_crates() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config, "test:bar", "1.1.0", "(component)").await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+
+    project
+        .cargo_component(["add", "--as-crate", "test:bar"])
+        .assert()
+        .stderr(contains(
+            "Added dependency `test:bar` with version `1.1.0` as crate `test-bar`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    let edition = manifest
+        .lines()
+        .find(|l| l.starts_with("edition = "))
+        .unwrap()
+        .to_string();
+    assert!(contains(r#"test-bar = { path = "crates/test-bar" }"#).eval(&manifest));
+    assert!(!contains("[workspace]").eval(&manifest));
+    assert!(!contains(r#""test:bar" = "1.1.0""#).eval(&manifest));
+
+    let crate_dir = project.root().join("crates/test-bar");
+    let manifest = fs::read_to_string(crate_dir.join("Cargo.toml"))?;
+    assert!(contains(r#""test:bar" = "1.1.0""#).eval(&manifest));
+    assert!(contains(edition).eval(&manifest));
+    assert!(contains(r#"wit-bindgen-rt = { version = "^"#)
+        .and(contains(r#"features = ["bitflags"] }"#))
+        .eval(&manifest));
+    assert!(crate_dir.join("src/bindings.rs").is_file());
+    assert!(!crate_dir.join("target").exists());
+
+    project
+        .cargo_component(["add", "--as-crate", "test:bar"])
+        .assert()
+        .stderr(contains(
+            "cannot add dependency `test:bar` as a crate because directory",
+        ))
+        .failure();
+
+    project.cargo_component(["build"]).assert().success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_dependencies_as_crates_to_a_workspace() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config, "test:bar", "1.1.0", "(component)").await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    let workspace_manifest = project.root().parent().unwrap().join("Cargo.toml");
+    fs::write(
+        &workspace_manifest,
+        r#"[workspace]
+members = ["foo"]
+resolver = "2"
+"#,
+    )?;
+
+    project
+        .cargo_component(["add", "--as-crate", "test:bar"])
+        .assert()
+        .stderr(contains(
+            "Added dependency `test:bar` with version `1.1.0` as crate `test-bar`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(&workspace_manifest)?;
+    assert!(contains(r#"members = ["foo", "foo/crates/test-bar"]"#).eval(&manifest));
+
+    project.cargo_component(["build"]).assert().success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_dependencies_pinned_to_a_digest() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config.clone(), "test:bar", "1.0.0", "(component)").await?;
+    publish_component(
+        config,
+        "test:bar",
+        "1.1.0",
+        "(component (import \"baz\" (func)))",
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar@=1.0.0"])
+        .assert()
+        .stderr(contains(
+            "Added dependency `test:bar` with version `=1.0.0`",
+        ))
+        .success();
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains("Fetched 1 component dependency package(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    let start = lock_file.find("sha256:").unwrap();
+    let digest = &lock_file[start..start + "sha256:".len() + 64];
+
+    let project = server.project("baz", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", &format!("test:bar@{digest}")])
+        .assert()
+        .stderr(contains(format!(
+            "Added dependency `test:bar` with version `=1.0.0` pinned to digest `{digest}`"
+        )))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#""test:bar" = "=1.0.0""#).eval(&manifest));
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(contains("version = \"1.0.0\"").eval(&lock_file));
+    assert!(contains(digest).eval(&lock_file));
+
+    project.cargo_component(["build"]).assert().success();
+
+    project
+        .cargo_component([
+            "add",
+            "--name",
+            "test:bar2",
+            &format!("test:bar@sha256:{zeros}", zeros = "0".repeat(64)),
+        ])
+        .assert()
+        .stderr(contains(format!(
+            "component registry package `test:bar` has no release with digest `sha256:{zeros}`",
+            zeros = "0".repeat(64)
+        )))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn adds_a_target_world() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+interface handler {
+    handle: func();
+}
+
+world service {
+    export handler;
+}
+
+world cli {
+    export run: func();
+}"#,
+    )
+    .await?;
+
+    let project = server.project("foo", false, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "--target", "test:bar"])
+        .assert()
+        .stderr(contains(
+            "package `test:bar` has multiple worlds; use `--world` to select one of:\n  service (exports: test:bar/handler@1.0.0)\n  cli (exports: run)",
+        ))
+        .failure();
+
+    project
+        .cargo_component(["add", "--target", "test:bar", "--world", "clii"])
+        .assert()
+        .stderr(contains(
+            "help: a world with a similar name exists: `test:bar@1.0.0/cli`",
+        ))
+        .failure();
+
+    project
+        .cargo_component(["add", "--target", "test:bar", "--world", "cli"])
+        .assert()
+        .stderr(contains(
+            "Added target world `cli` of package `test:bar` with version `1.0.0`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(
+        r#"[package.metadata.component.target]
+package = "test:bar"
+version = "1.0.0"
+world = "cli""#
+    )
+    .eval(&manifest));
+
+    let project = server.project("baz", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "--target", "test:bar", "--world", "service"])
+        .assert()
+        .stderr(contains(
+            "Added target world `service` of package `test:bar` with version `1.0.0`",
+        ))
+        .success();
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#"world = "service""#).eval(&manifest));
+
+    Ok(())
+}
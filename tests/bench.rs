@@ -2,12 +2,9 @@ use std::fs;
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::str::contains;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 #[cfg_attr(
     windows,
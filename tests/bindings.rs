@@ -0,0 +1,620 @@
+use std::{fs, process::Command};
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use toml_edit::{value, Array, InlineTable, Item, Table};
+
+#[test]
+fn it_generates_consumer_bindings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:consumer;
+
+interface types {
+    record options {
+        reverse: bool,
+        repeat: u8,
+    }
+
+    transform: func(s: string, options: options) -> result<string, string>;
+}
+
+world foo {
+    export types;
+    export add: func(a: u64, b: u64) -> u64;
+}",
+    )?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js", "--lang", "python"])
+        .assert()
+        .stderr(contains("Generating js consumer bindings for foo"))
+        .stderr(contains("Generating python consumer bindings for foo"))
+        .success();
+
+    let ts = fs::read_to_string(project.build_dir().join("bindings/foo.d.ts"))?;
+    assert!(ts.contains("export function add(a: bigint, b: bigint): bigint;"));
+    assert!(ts.contains("export namespace MyConsumerTypes {"));
+    assert!(ts.contains("export function transform(s: string, options: Options): string;"));
+    assert!(ts.contains("export const types: typeof MyConsumerTypes;"));
+
+    let py = fs::read_to_string(project.build_dir().join("bindings/foo.pyi"))?;
+    assert!(py.contains("class Options:\n    reverse: bool\n    repeat: int\n"));
+    assert!(py.contains("def transform(self, s: str, options: Options) -> str:"));
+    assert!(py.contains("class Exports(Protocol):\n    types: Types\n"));
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires `tsc` and `mypy`; run with `--ignored`"]
+fn it_generates_consumer_bindings_that_type_check() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:consumer;
+
+interface types {
+    record options {
+        reverse: bool,
+        repeat: u8,
+    }
+
+    variant shape {
+        circle(f32),
+        rectangle(tuple<f32, f32>),
+        empty,
+    }
+
+    enum color {
+        red,
+        light-green,
+    }
+
+    flags permissions {
+        read,
+        write,
+    }
+
+    resource counter {
+        constructor(start: u32);
+        increment: func() -> u32;
+        from-list: static func(values: list<u32>) -> counter;
+    }
+
+    type names = list<string>;
+
+    transform: func(s: string, options: options) -> result<string, string>;
+    area: func(shape: shape, color: option<color>) -> f64;
+    check: func(p: permissions, names: names, bytes: list<u8>) -> tuple<bool, s64>;
+    total: func(c: borrow<counter>) -> result<u32>;
+}
+
+world foo {
+    use types.{color};
+    export types;
+    export add: func(a: u64, b: u64) -> u64;
+    export pick: func(c: color) -> option<list<string>>;
+}
+",
+    )?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js", "--lang", "python"])
+        .assert()
+        .success();
+
+    let bindings = project.build_dir().join("bindings");
+
+    Command::new(if cfg!(windows) { "tsc.cmd" } else { "tsc" })
+        .args(["--noEmit", "--strict"])
+        .arg(bindings.join("foo.d.ts"))
+        .assert()
+        .success();
+
+    Command::new("mypy")
+        .arg("--strict")
+        .arg(bindings.join("foo.pyi"))
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_generates_a_c_header() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:consumer;
+
+interface types {
+    greet: func(name: string) -> string;
+}
+
+world foo {
+    export types;
+    export add: func(a: u32, b: u32) -> u32;
+}",
+    )?;
+
+    project
+        .cargo_component(["bindings", "--lang", "c"])
+        .assert()
+        .stderr(contains("Generating c consumer bindings for foo"))
+        .success();
+
+    let header = fs::read_to_string(project.build_dir().join("bindings/foo.h"))?;
+    assert!(header.contains("} foo_t;"));
+    assert!(header.contains("static inline wasmtime_error_t *foo_instantiate("));
+    assert!(header.contains(
+        "foo_t *self, wasmtime_context_t *context, uint32_t a, uint32_t b, uint32_t *ret) {"
+    ));
+    assert!(header.contains(
+        "error = foo_get_func(&out->instance, context, \"my:consumer/types\", \"greet\","
+    ));
+    assert!(header.contains("static inline wasmtime_error_t *foo_my_consumer_types_greet("));
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_consumer_bindings_to_the_output_directory() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js", "--out-dir", "host"])
+        .assert()
+        .success();
+
+    let ts = fs::read_to_string(project.root().join("host/foo.d.ts"))?;
+    assert!(ts.contains("export function helloWorld(): string;"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_imports_optional_dependencies_with_their_feature() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"(component
+    (core module $m (func (export "f") (result i32) i32.const 0))
+    (core instance $i (instantiate $m))
+    (func (export "get-answer") (result u32) (canon lift (core func $i "f")))
+)"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let mut dependency = InlineTable::new();
+        dependency.insert("version", "1.0.0".into());
+        dependency.insert("optional", true.into());
+        doc["package"]["metadata"]["component"]["dependencies"]["test:bar"] = value(dependency);
+        doc["features"]["bar"] = value(Array::new());
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("get_answer"));
+
+    // The optional dependency is still locked
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("name = \"test:bar\""));
+
+    project
+        .cargo_component(["bindings", "--features", "bar"])
+        .assert()
+        .success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("get_answer"));
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("get_answer"));
+    assert_eq!(
+        fs::read_to_string(project.root().join("Cargo-component.lock"))?,
+        lock_file
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_requires_the_feature_of_an_optional_dependency() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependency = InlineTable::new();
+        dependency.insert("path", "bar.wasm".into());
+        dependency.insert("optional", true.into());
+        dependency.insert("feature", "with-bar".into());
+        doc["package"]["metadata"]["component"]["dependencies"]["test:bar"] = value(dependency);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["bindings"])
+        .assert()
+        .stderr(contains(
+            "optional component dependency `test:bar` requires feature `with-bar` to be declared",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_imports_dev_dependencies_only_for_tests() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(
+        config,
+        "test:mock",
+        "1.0.0",
+        r#"(component
+    (core module $m (func (export "f") (result i32) i32.const 0))
+    (core instance $i (instantiate $m))
+    (func (export "get-answer") (result u32) (canon lift (core func $i "f")))
+)"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "--dev", "test:mock"])
+        .assert()
+        .success();
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("get_answer"));
+
+    project
+        .cargo_component(["bindings", "--dev"])
+        .assert()
+        .success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("get_answer"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resolves_the_dependencies_of_a_target_package() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    let types = r#"package test:types@1.0.0;
+
+interface types {
+    /// A point in the plane.
+    record point {
+        x: u32,
+        y: u32,
+    }
+}"#;
+    publish_wit(config.clone(), "test:types", "1.0.0", types).await?;
+    publish_wit_with_deps(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+world foo {
+    use test:types/types@1.0.0.{point};
+    export origin: func() -> point;
+}"#,
+        &[types],
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar@1.0.0"])?;
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("name = \"test:bar\""));
+    assert!(lock_file.contains("name = \"test:types\""));
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("A point in the plane."));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_includes_worlds_from_target_dependencies() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+interface logging {
+    log: func(message: string);
+}
+
+world base {
+    import logging;
+    import a: func() -> u32;
+    export run: func();
+}"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "--target", "test:bar"])
+        .assert()
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo;
+
+world example {
+    include test:bar/base@1.0.0 with { a as b }
+    export hello: func() -> string;
+}"#,
+    )?;
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub fn b()"));
+    assert!(!bindings.contains("pub fn a()"));
+    assert!(bindings.contains("pub fn log("));
+    assert!(bindings.contains("fn run()"));
+    assert!(bindings.contains("fn hello()"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_includes_worlds_without_the_dependency_version() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+world base {
+    import a: func() -> u32;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "--target", "test:bar"])
+        .assert()
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo;
+
+world example {
+    include test:bar/base with { a as b }
+}"#,
+    )?;
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub fn b()"));
+
+    Ok(())
+}
+
+#[test]
+fn it_suggests_the_closest_world() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        r#"package component:foo;
+
+world example {
+    export hello: func() -> string;
+}
+
+world other {
+    export goodbye: func() -> string;
+}"#,
+    )?;
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["target"]["world"] = value("exampel");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["bindings"])
+        .assert()
+        .stderr(contains("no world named `exampel` in package"))
+        .stderr(contains("available worlds:"))
+        .stderr(contains("component:foo/other"))
+        .stderr(contains(
+            "help: a world with a similar name exists: `component:foo/example`",
+        ))
+        .failure();
+
+    // A world may be qualified by the package name alone
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["target"]["world"] = value("foo/other");
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["bindings"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("fn goodbye()"));
+
+    Ok(())
+}
+
+#[test]
+fn it_caches_the_local_target_world() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    let wit = "package my:consumer;
+
+world foo {
+    /// Adds two numbers.
+    export add: func(a: u64, b: u64) -> u64;
+}";
+    fs::write(project.root().join("wit/world.wit"), wit)?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js"])
+        .assert()
+        .success();
+
+    let uncached = fs::read_to_string(project.build_dir().join("bindings/foo.d.ts"))?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js"])
+        .env("CARGO_COMPONENT_LOG", "debug")
+        .assert()
+        .stderr(contains("using cached target world for package `foo`"))
+        .success();
+
+    let cached = fs::read_to_string(project.build_dir().join("bindings/foo.d.ts"))?;
+    assert_eq!(cached, uncached);
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        wit.replace("u64", "u32"),
+    )?;
+
+    project
+        .cargo_component(["bindings", "--lang", "js"])
+        .assert()
+        .success();
+
+    let ts = fs::read_to_string(project.build_dir().join("bindings/foo.d.ts"))?;
+    assert!(ts.contains("export function add(a: number, b: number): number;"));
+
+    Ok(())
+}
+
+#[test]
+fn it_tracks_wit_files_as_crate_inputs() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value("wit/deps/foo-bar");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("wit/deps/foo-bar"))?;
+    fs::write(
+        project.root().join("wit/deps/foo-bar/bar.wit"),
+        "package foo:bar;
+
+interface baz {
+    baz: func() -> u32;
+}",
+    )?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+world example {
+    import foo:bar/baz;
+    export hello-world: func() -> string;
+}",
+    )?;
+
+    project.cargo_component(["bindings"]).assert().success();
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("const _: &[u8] = include_bytes!(\"../wit/deps/foo-bar/bar.wit\");"));
+    assert!(bindings.contains("const _: &[u8] = include_bytes!(\"../wit/world.wit\");"));
+
+    Ok(())
+}
+
+#[test]
+fn it_exports_the_crate_version() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["version_export"] = value(true);
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["bindings"]).assert().success();
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub mod cargo_component {"));
+    assert!(bindings.contains("impl<T> exports::cargo_component::metadata::version::Guest for T {"));
+    assert!(bindings.contains("_rt::String::from(env!(\"CARGO_PKG_VERSION\"))"));
+    assert!(bindings.contains("const REVISION: Option<&str> = "));
+
+    project
+        .cargo_component(["graph"])
+        .assert()
+        .stdout(contains("n1 [label=\"cargo-component:metadata/version\"];"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_prints_resolver_decisions_when_verbose() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+world foo {
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar@1.0.0"])?;
+
+    project
+        .cargo_component(["bindings", "-v"])
+        .assert()
+        .stderr(contains(
+            "Resolved `test:bar` to v1.0.0 (the locked version)",
+        ))
+        .stderr(contains("Considering").not())
+        .success();
+
+    project
+        .cargo_component(["bindings", "-vv"])
+        .assert()
+        .stderr(contains("Considering releases of `test:bar`: v1.0.0"))
+        .success();
+
+    project
+        .cargo_component(["bindings", "--quiet"])
+        .assert()
+        .stderr(contains("Generating").not())
+        .stderr(contains("Resolved").not())
+        .success();
+
+    Ok(())
+}
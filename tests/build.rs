@@ -2,13 +2,10 @@ use std::{fs, process::Command, rc::Rc};
 
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::{prelude::PredicateBooleanExt, str::contains};
 use tempfile::TempDir;
-use toml_edit::{value, Array, Item, Table};
-
-use crate::support::*;
-
-mod support;
+use toml_edit::{value, Array, InlineTable, Item, Table};
 
 #[test]
 fn it_builds_debug() -> Result<()> {
@@ -61,6 +58,37 @@ fn it_builds_a_bin_project() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_builds_examples() -> Result<()> {
+    let project = Project::new("foo", false)?;
+    project.file(
+        "examples/demo.rs",
+        r#"
+fn main() {
+    println!("Hello from an example!");
+}"#,
+    )?;
+
+    project
+        .cargo_component(["build", "--examples"])
+        .assert()
+        .stderr(contains(
+            "Creating component target/wasm32-wasip1/debug/examples/demo.wasm",
+        ))
+        .success();
+
+    validate_component(
+        &project
+            .build_dir()
+            .join("wasm32-wasip1")
+            .join("debug")
+            .join("examples")
+            .join("demo.wasm"),
+    )?;
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_a_workspace() -> Result<()> {
     let dir = Rc::new(TempDir::new()?);
@@ -158,6 +186,294 @@ fn it_adds_a_producers_field() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_customizes_the_processed_by_field() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut processed_by = InlineTable::new();
+        processed_by.insert("name", "my-pipeline".into());
+        processed_by.insert("version", "1.2.3".into());
+        doc["package"]["metadata"]["component"]["producers"]["processed-by"] = value(processed_by);
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let wasm = fs::read(project.debug_wasm("foo"))?;
+    let section = wasm_metadata::Producers::from_wasm(&wasm)?.expect("missing producers section");
+    let processed_by = section
+        .get("processed-by")
+        .expect("missing processed-by field");
+    assert_eq!(
+        processed_by.get("my-pipeline").map(String::as_str),
+        Some("1.2.3")
+    );
+    assert!(processed_by.get(env!("CARGO_PKG_NAME")).is_none());
+
+    Ok(())
+}
+
+#[test]
+fn it_strips_producers_sections() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["build", "--no-producers"])
+        .assert()
+        .success();
+
+    let path = project.debug_wasm("foo");
+    validate_component(&path)?;
+
+    let wasm = fs::read(&path)?;
+    for payload in wasmparser::Parser::new(0).parse_all(&wasm) {
+        if let wasmparser::Payload::CustomSection(reader) = payload? {
+            assert_ne!(reader.name(), "producers");
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_diffs_the_exports_against_the_target_world() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.cargo_component(["bindings"]).assert().success();
+
+    // Export the component with bindings generated for a previous world
+    fs::rename(
+        project.root().join("src/bindings.rs"),
+        project.root().join("src/old_bindings.rs"),
+    )?;
+    let lib = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        lib.replace("bindings", "old_bindings"),
+    )?;
+
+    let wit = fs::read_to_string(project.root().join("wit/world.wit"))?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        wit.replace("hello-world", "goodbye"),
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("does not match the target world of"))
+        .stderr(contains("missing exports: `goodbye`"))
+        .stderr(contains("exports not in the target world: `hello-world`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_extra_exports_in_strict_mode() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    let wit = fs::read_to_string(project.root().join("wit/world.wit"))?;
+
+    // Generate bindings for another world and export it as well
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:extra;\n\nworld extra {\n    export extra-func: func() -> u32;\n}\n",
+    )?;
+    project.cargo_component(["bindings"]).assert().success();
+    fs::rename(
+        project.root().join("src/bindings.rs"),
+        project.root().join("src/extra_bindings.rs"),
+    )?;
+    fs::write(project.root().join("wit/world.wit"), wit)?;
+
+    let mut lib = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    lib.push_str(
+        "
+#[allow(warnings)]
+mod extra_bindings;
+
+struct Extra;
+
+impl extra_bindings::Guest for Extra {
+    fn extra_func() -> u32 {
+        0
+    }
+}
+
+extra_bindings::export!(Extra with_types_in extra_bindings);
+",
+    );
+    fs::write(project.root().join("src/lib.rs"), lib)?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["strict-exports"] = value(true);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("does not match the target world of"))
+        .stderr(contains("exports not in the target world: `extra-func`"))
+        .stderr(contains("missing exports").not())
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_stubs_missing_imports() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+interface greeter {
+    greet: func(name: string) -> string;
+}
+
+world example {
+    import greeter;
+    import log: func(msg: string);
+    export hello-world: func() -> string;
+}
+",
+    )?;
+
+    let lib = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        lib.replace(
+            "\"Hello, World!\".to_string()",
+            "bindings::log(\"hello\");\n        bindings::component::foo::greeter::greet(\"World\")",
+        ),
+    )?;
+
+    project
+        .cargo_component(["build", "--stub-missing-imports"])
+        .assert()
+        .stderr(contains(
+            "Stubbing import `component:foo/greeter` (greet) with functions that trap",
+        ))
+        .stderr(contains(
+            "Stubbing import `$root` (log) with functions that trap",
+        ))
+        .success();
+
+    let bytes = fs::read(project.debug_wasm("foo"))?;
+    let wit_component::DecodedWasm::Component(resolve, world) = wit_component::decode(&bytes)?
+    else {
+        panic!("expected a component");
+    };
+    let imports: Vec<_> = resolve.worlds[world]
+        .imports
+        .keys()
+        .map(|key| resolve.name_world_key(key))
+        .collect();
+    assert!(!imports
+        .iter()
+        .any(|i| i == "component:foo/greeter" || i == "log"));
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Stubbing").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_includes_worlds_for_the_dev_profile() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let component = &mut doc["package"]["metadata"]["component"];
+        component["target"]["world"] = value("example");
+        component["profile"]["dev"]["include"] = value(Array::from_iter(["debug"]));
+        Ok(doc)
+    })?;
+
+    let wit = fs::read_to_string(project.root().join("wit/world.wit"))?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        format!(
+            "{wit}
+interface debug-introspection {{
+    dump: func() -> string;
+}}
+
+world debug {{
+    export debug-introspection;
+}}
+"
+        ),
+    )?;
+
+    let mut lib = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    lib.push_str(
+        "
+#[cfg(debug_assertions)]
+impl bindings::exports::component::foo::debug_introspection::Guest for Component {
+    fn dump() -> String {
+        String::new()
+    }
+}
+",
+    );
+    fs::write(project.root().join("src/lib.rs"), lib)?;
+
+    project.cargo_component(["build"]).assert().success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("debug_introspection"));
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component(["build", "--release"])
+        .assert()
+        .success();
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("debug_introspection"));
+    validate_component(&project.release_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_applies_profile_overrides() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let release = &mut doc["package"]["metadata"]["component"]["profile"]["release"];
+        release["producers"]["strip"] = value(true);
+        doc["package"]["metadata"]["component"]["profile"]["dev"]["adapter"] =
+            value("not-a-module.wasm");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("failed to read module adapter"))
+        .failure();
+
+    project
+        .cargo_component(["build", "--release"])
+        .assert()
+        .success();
+
+    let path = project.release_wasm("foo");
+    validate_component(&path)?;
+
+    let wasm = fs::read(&path)?;
+    for payload in wasmparser::Parser::new(0).parse_all(&wasm) {
+        if let wasmparser::Payload::CustomSection(reader) = payload? {
+            assert_ne!(reader.name(), "producers");
+        }
+    }
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_wasm32_unknown_unknown_from_cli() -> Result<()> {
     let project = Project::new("foo", true)?;
@@ -264,6 +580,47 @@ fn it_regenerates_target_if_wit_changed() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn it_skips_generating_unchanged_bindings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    // `cargo component new` already generated up-to-date bindings, so
+    // remove them for the first build to generate them again
+    fs::remove_file(project.root().join("src/bindings.rs"))?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Generating bindings"))
+        .success();
+
+    // Rewriting the WIT with the same contents keeps the bindings
+    let wit = project.root().join("wit/world.wit");
+    fs::write(&wit, fs::read(&wit)?)?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Generating bindings").not())
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    // Editing the bindings regenerates them
+    let bindings = project.root().join("src/bindings.rs");
+    fs::write(&bindings, "")?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Generating bindings"))
+        .success();
+
+    assert!(!fs::read_to_string(&bindings)?.is_empty());
+
+    Ok(())
+}
+
 #[test]
 fn it_builds_with_local_wit_deps() -> Result<()> {
     let project = Project::new("foo", true)?;
@@ -359,37 +716,174 @@ bindings::export!(Component with_types_in bindings);
 }
 
 #[test]
-fn empty_world_with_dep_valid() -> Result<()> {
-    let project = Project::new("dep", true)?;
+fn it_builds_a_single_file_target_with_sibling_deps() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["target"]["path"] = value("wit/world.wit");
+        Ok(doc)
+    })?;
 
+    fs::create_dir_all(project.root().join("wit/deps/foo-bar"))?;
     fs::write(
-        project.root().join("wit/world.wit"),
-        "
-            package foo:bar;
-
-            world the-world {
-                flags foo {
-                    bar
-                }
+        project.root().join("wit/deps/foo-bar/bar.wit"),
+        "package foo:bar@1.0.0;
 
-                export hello: func() -> list<foo>;
-            }
-        ",
+interface baz {
+    use bar:baz/qux.{ty};
+    baz: func() -> ty;
+}",
     )?;
 
     fs::write(
-        project.root().join("src/lib.rs"),
-        "
-            #[allow(warnings)]
-            mod bindings;
-            use bindings::{Guest, Foo};
-            struct Component;
+        project.root().join("wit/deps/qux.wit"),
+        "package bar:baz;
 
-            impl Guest for Component {
-                fn hello() -> Vec<Foo> {
-                    vec![Foo::BAR]
-                }
-            }
+interface qux {
+    type ty = u32;
+}",
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+world example {
+    import foo:bar/baz@1.0.0;
+    import bar:baz/qux;
+    export hello-world: func() -> string;
+}",
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    fs::remove_file(project.root().join("wit/deps/qux.wit"))?;
+    fs::remove_dir_all(project.root().join("wit/deps/foo-bar"))?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "depends on foreign packages that were not found:\n      bar:baz\n      foo:bar@1.0.0",
+        ))
+        .stderr(contains(format!(
+            "help: add them to `[package.metadata.component.target.dependencies]` or to the `{dir}` directory",
+            dir = project.root().join("wit").join("deps").display()
+        )))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_a_target_with_a_wit_deps_layout() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/deps.toml"),
+        "foo-bar = \"https://example.com/foo-bar.tar.gz\"\n",
+    )?;
+    fs::write(
+        project.root().join("wit/deps.lock"),
+        "[baz-qux]\nsha256 = \"0000\"\n\n[foo-bar]\nurl = \"https://example.com/foo-bar.tar.gz\"\nsha256 = \"0000\"\n",
+    )?;
+
+    fs::create_dir_all(project.root().join("wit/deps/foo-bar"))?;
+    fs::write(
+        project.root().join("wit/deps/foo-bar/bar.wit"),
+        "package foo:bar;
+
+interface baz {
+    use baz:qux/qux.{ty};
+    baz: func() -> ty;
+}",
+    )?;
+
+    fs::create_dir_all(project.root().join("wit/deps/baz-qux"))?;
+    fs::write(
+        project.root().join("wit/deps/baz-qux/qux.wit"),
+        "package baz:qux;
+
+interface qux {
+    type ty = u32;
+}",
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+world example {
+    import foo:bar/baz;
+    export hello-world: func() -> string;
+}",
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    fs::remove_dir_all(project.root().join("wit/deps/baz-qux"))?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(format!(
+            "the `wit-deps` dependencies of local target `{wit}` were not found in `{deps}`:\n      baz-qux",
+            wit = project.root().join("wit").display(),
+            deps = project.root().join("wit").join("deps").display()
+        )))
+        .stderr(contains("help: run `wit-deps` to fetch them"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn empty_world_with_dep_valid() -> Result<()> {
+    let project = Project::new("dep", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+            package foo:bar;
+
+            world the-world {
+                flags foo {
+                    bar
+                }
+
+                export hello: func() -> list<foo>;
+            }
+        ",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        "
+            #[allow(warnings)]
+            mod bindings;
+            use bindings::{Guest, Foo};
+            struct Component;
+
+            impl Guest for Component {
+                fn hello() -> Vec<Foo> {
+                    vec![Foo::BAR]
+                }
+            }
 
             bindings::export!(Component with_types_in bindings);
         ",
@@ -435,147 +929,367 @@ fn empty_world_with_dep_valid() -> Result<()> {
 }
 
 #[test]
-fn it_builds_with_resources() -> Result<()> {
-    let project = Project::new("foo", true)?;
+fn it_renames_component_dependencies_that_conflict_with_imports() -> Result<()> {
+    let project = Project::new("dep", true)?;
+    project.cargo_component(["build"]).assert().success();
+
+    let dep = project.debug_wasm("dep");
+    validate_component(&dep)?;
+
+    let project = Project::with_dir(project.dir().clone(), "main", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value(dep.display().to_string());
+        doc["package"]["metadata"]["component"]["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
 
     fs::write(
         project.root().join("wit/world.wit"),
         "
-            package foo:bar;
+            package component:main;
 
-            world bar {
-                export baz: interface {
-                    resource keyed-integer {
-                        constructor(x: u32);
-                        get: func() -> u32;
-                        set: func(x: u32);
-                        key: static func() -> string;
-                    }
+            world example {
+                import foo-bar: interface {
+                    ping: func();
                 }
+
+                export hello-world: func() -> string;
             }
         ",
     )?;
 
-    fs::write(
-        project.root().join("src/lib.rs"),
-        r#"
+    let source = |module: &str| {
+        format!(
+            "
             #[allow(warnings)]
             mod bindings;
 
-            use std::cell::Cell;
+            use bindings::Guest;
 
             struct Component;
 
-            impl bindings::exports::baz::Guest for Component {
-                type KeyedInteger = KeyedInteger;
-            }
+            impl Guest for Component {{
+                fn hello_world() -> String {{
+                    bindings::foo_bar::ping();
+                    bindings::{module}::hello_world()
+                }}
+            }}
 
             bindings::export!(Component with_types_in bindings);
+        "
+        )
+    };
 
-            pub struct KeyedInteger(Cell<u32>);
-
-            impl bindings::exports::baz::GuestKeyedInteger for KeyedInteger {
-                fn new(x: u32) -> Self {
-                    Self(Cell::new(x))
-                }
+    fs::write(project.root().join("src/lib.rs"), source("foo_bar2"))?;
 
-                fn get(&self) -> u32 {
-                    self.0.get()
-                }
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "warning: dependency `foo:bar` conflicts with an import in the target world and is imported as `foo:bar2`",
+        ))
+        .success();
 
-                fn set(&self, x: u32) {
-                    self.0.set(x);
-                }
+    let text = wasmprinter::print_file(project.debug_wasm("main"))?;
+    assert!(text.contains("(import \"unlocked-dep=<foo:bar>\""));
 
-                fn key() -> String {
-                    "my-key".to_string()
-                }
-            }
-        "#,
-    )?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["foo:bar"]["import-name"] =
+            value("greeter");
+        Ok(doc)
+    })?;
+    fs::write(project.root().join("src/lib.rs"), source("foo_greeter"))?;
 
     project.cargo_component(["build"]).assert().success();
+    validate_component(&project.debug_wasm("main"))?;
 
-    let dep = project.debug_wasm("foo");
-    validate_component(&dep)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["foo:bar"]["import-name"] =
+            value("bar");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "import name `bar` of dependency `foo:bar` conflicts with an import in the target world",
+        ))
+        .failure();
 
     Ok(())
 }
 
 #[test]
-fn it_builds_resources_with_specified_ownership_model() -> Result<()> {
-    let project = Project::new("foo", true)?;
-    project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["bindings"]["ownership"] =
-            value("borrowing-duplicate-if-necessary");
-        Ok(doc)
-    })?;
-
-    fs::write(
-        project.root().join("wit/world.wit"),
+fn it_shares_types_across_component_dependencies() -> Result<()> {
+    let b = Project::new("b", true)?;
+    b.file(
+        "wit/world.wit",
         "
-            package foo:bar;
+            package my:b;
 
-            world bar {
-                export baz: interface {
-                    resource keyed-integer {
-                        constructor(x: u32);
-                        get: func() -> u32;
-                        set: func(x: u32);
-                        key: static func() -> string;
-                    }
+            interface types {
+                record point {
+                    x: u32,
+                    y: u32,
                 }
+
+                origin: func() -> point;
+            }
+
+            world b {
+                export types;
             }
         ",
     )?;
-
-    fs::write(
-        project.root().join("src/lib.rs"),
-        r#"
+    b.file(
+        "src/lib.rs",
+        "
             #[allow(warnings)]
             mod bindings;
 
-            use std::cell::Cell;
+            use bindings::exports::my::b::types::{Guest, Point};
 
             struct Component;
 
-            impl bindings::exports::baz::Guest for Component {
-                type KeyedInteger = KeyedInteger;
+            impl Guest for Component {
+                fn origin() -> Point {
+                    Point { x: 0, y: 0 }
+                }
             }
 
             bindings::export!(Component with_types_in bindings);
+        ",
+    )?;
+    b.cargo_component(["build"]).assert().success();
+    let b_wasm = b.debug_wasm("b");
 
-            pub struct KeyedInteger(Cell<u32>);
-
-            impl bindings::exports::baz::GuestKeyedInteger for KeyedInteger {
-                fn new(x: u32) -> Self {
-                    Self(Cell::new(x))
-                }
+    let a = Project::with_dir(b.dir().clone(), "a", true, Vec::<String>::new())?;
+    a.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["target"]["dependencies"]["my:b"]["path"] =
+            value("../b/wit");
+        Ok(doc)
+    })?;
+    a.file(
+        "wit/world.wit",
+        "
+            package my:a;
 
-                fn get(&self) -> u32 {
-                    self.0.get()
-                }
+            interface shapes {
+                use my:b/types.{point};
 
-                fn set(&self, x: u32) {
-                    self.0.set(x);
-                }
+                center: func() -> point;
+            }
 
-                fn key() -> String {
-                    "my-key".to_string()
-                }
+            world a {
+                export shapes;
             }
-        "#,
+        ",
     )?;
+    a.file(
+        "src/lib.rs",
+        "
+            #[allow(warnings)]
+            mod bindings;
 
-    project.cargo_component(["build"]).assert().success();
+            use bindings::exports::my::a::shapes::{Guest, Point};
 
-    let dep = project.debug_wasm("foo");
-    validate_component(&dep)?;
+            struct Component;
 
-    Ok(())
-}
+            impl Guest for Component {
+                fn center() -> Point {
+                    Point { x: 1, y: 1 }
+                }
+            }
 
-#[test]
+            bindings::export!(Component with_types_in bindings);
+        ",
+    )?;
+    a.cargo_component(["build"]).assert().success();
+    let a_wasm = a.debug_wasm("a");
+
+    let project = Project::with_dir(b.dir().clone(), "main", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["my:a"]["path"] = value(a_wasm.display().to_string());
+        dependencies["my:b"]["path"] = value(b_wasm.display().to_string());
+        doc["package"]["metadata"]["component"]["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+    project.file(
+        "src/lib.rs",
+        "
+            #[allow(warnings)]
+            mod bindings;
+
+            use bindings::Guest;
+
+            struct Component;
+
+            impl Guest for Component {
+                fn hello_world() -> String {
+                    // The points returned by both dependencies have the same type
+                    let points = [
+                        bindings::my::b::my_b_types::origin(),
+                        bindings::my::a::my_a_shapes::center(),
+                    ];
+                    format!(\"{len}\", len = points.len())
+                }
+            }
+
+            bindings::export!(Component with_types_in bindings);
+        ",
+    )?;
+
+    project.cargo_component(["build"]).assert().success();
+    validate_component(&project.debug_wasm("main"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_resources() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+            package foo:bar;
+
+            world bar {
+                export baz: interface {
+                    resource keyed-integer {
+                        constructor(x: u32);
+                        get: func() -> u32;
+                        set: func(x: u32);
+                        key: static func() -> string;
+                    }
+                }
+            }
+        ",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+            #[allow(warnings)]
+            mod bindings;
+
+            use std::cell::Cell;
+
+            struct Component;
+
+            impl bindings::exports::baz::Guest for Component {
+                type KeyedInteger = KeyedInteger;
+            }
+
+            bindings::export!(Component with_types_in bindings);
+
+            pub struct KeyedInteger(Cell<u32>);
+
+            impl bindings::exports::baz::GuestKeyedInteger for KeyedInteger {
+                fn new(x: u32) -> Self {
+                    Self(Cell::new(x))
+                }
+
+                fn get(&self) -> u32 {
+                    self.0.get()
+                }
+
+                fn set(&self, x: u32) {
+                    self.0.set(x);
+                }
+
+                fn key() -> String {
+                    "my-key".to_string()
+                }
+            }
+        "#,
+    )?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let dep = project.debug_wasm("foo");
+    validate_component(&dep)?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_resources_with_specified_ownership_model() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["ownership"] =
+            value("borrowing-duplicate-if-necessary");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+            package foo:bar;
+
+            world bar {
+                export baz: interface {
+                    resource keyed-integer {
+                        constructor(x: u32);
+                        get: func() -> u32;
+                        set: func(x: u32);
+                        key: static func() -> string;
+                    }
+                }
+            }
+        ",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+            #[allow(warnings)]
+            mod bindings;
+
+            use std::cell::Cell;
+
+            struct Component;
+
+            impl bindings::exports::baz::Guest for Component {
+                type KeyedInteger = KeyedInteger;
+            }
+
+            bindings::export!(Component with_types_in bindings);
+
+            pub struct KeyedInteger(Cell<u32>);
+
+            impl bindings::exports::baz::GuestKeyedInteger for KeyedInteger {
+                fn new(x: u32) -> Self {
+                    Self(Cell::new(x))
+                }
+
+                fn get(&self) -> u32 {
+                    self.0.get()
+                }
+
+                fn set(&self, x: u32) {
+                    self.0.set(x);
+                }
+
+                fn key() -> String {
+                    "my-key".to_string()
+                }
+            }
+        "#,
+    )?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let dep = project.debug_wasm("foo");
+    validate_component(&dep)?;
+
+    Ok(())
+}
+
+#[test]
 fn it_builds_with_a_component_dependency() -> Result<()> {
     let comp1 = Project::new("comp1", true)?;
 
@@ -676,44 +1390,1692 @@ impl Guest for Component {
     }
 }
 
-bindings::export!(Component with_types_in bindings);
-"#,
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    comp2
+        .cargo_component(["build", "--release"])
+        .assert()
+        .stderr(contains("Finished `release` profile [optimized] target(s)"))
+        .success();
+
+    let path: std::path::PathBuf = comp2.release_wasm("comp2");
+    validate_component(&path)?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_workspace_member_dependency() -> Result<()> {
+    let dir = Rc::new(TempDir::new()?);
+
+    let comp1 = Project::with_dir(dir.clone(), "comp1", true, Vec::<String>::new())?;
+    fs::write(
+        comp1.root().join("wit/world.wit"),
+        "
+package my:comp1;
+
+world generator {
+    export rand: func() -> u32;
+}
+",
+    )?;
+
+    fs::write(
+        comp1.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn rand() -> u32 {
+        4
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    let comp2 = Project::with_dir(dir.clone(), "comp2", true, Vec::<String>::new())?;
+    comp2.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"]["workspace-member"] =
+            value("comp1");
+        Ok(doc)
+    })?;
+
+    fs::write(
+        comp2.root().join("wit/world.wit"),
+        "
+package my:comp2;
+
+world generator {
+    export rand: func() -> u32;
+}
+",
+    )?;
+
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+
+use bindings::{Guest, my_comp1};
+
+struct Component;
+
+impl Guest for Component {
+    fn rand() -> u32 {
+        my_comp1::rand() + 1
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    let project = Project::new_uninitialized(dir.clone(), dir.path().to_owned());
+    project.file(
+        "Cargo.toml",
+        r#"[workspace]
+members = ["comp1", "comp2"]
+resolver = "2"
+"#,
+    )?;
+
+    project
+        .cargo_component(["build", "-p", "comp2"])
+        .assert()
+        .stderr(contains(
+            "Building workspace member `comp1` for component dependency `my:comp1` of package `comp2`",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("comp1"))?;
+    validate_component(&project.debug_wasm("comp2"))?;
+
+    // A change to the member is picked up by the dependent package
+    fs::write(
+        comp1.root().join("wit/world.wit"),
+        "
+package my:comp1;
+
+world generator {
+    export rand: func() -> u32;
+    export seed: func() -> u32;
+}
+",
+    )?;
+
+    fs::write(
+        comp1.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn rand() -> u32 {
+        4
+    }
+
+    fn seed() -> u32 {
+        2
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    fs::write(
+        comp2.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+
+use bindings::{Guest, my_comp1};
+
+struct Component;
+
+impl Guest for Component {
+    fn rand() -> u32 {
+        my_comp1::rand() + my_comp1::seed()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build", "-p", "comp2"])
+        .assert()
+        .stderr(contains(
+            "Building workspace member `comp1` for component dependency `my:comp1` of package `comp2`",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("comp2"))?;
+
+    // The cargo options of the command are passed along to the member build
+    let lib = comp1.root().join("src/lib.rs");
+    let source = fs::read_to_string(&lib)?;
+    fs::write(
+        &lib,
+        format!(
+            "{source}\n#[cfg(forwarded)]\ncompile_error!(\"built with the forwarded config\");\n"
+        ),
+    )?;
+
+    project
+        .cargo_component([
+            "build",
+            "-p",
+            "comp2",
+            "--config",
+            r#"build.rustflags=["--cfg", "forwarded"]"#,
+        ])
+        .assert()
+        .stderr(contains("built with the forwarded config"))
+        .stderr(contains("failed to build workspace member `comp1`"))
+        .failure();
+
+    comp2.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["dependencies"]["my:comp1"]["workspace-member"] =
+            value("comp3");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build", "-p", "comp2"])
+        .assert()
+        .stderr(contains("component dependency `my:comp1` in `"))
+        .stderr(contains(
+            "refers to `comp3`, which is not a member of the workspace",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_adapter() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["adapter"] = value("not-a-valid-path");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("error: failed to read module adapter"))
+        .failure();
+
+    let project = Project::new("foo", true)?;
+    let adapter_path = "adapter/wasi_snapshot_preview1.wasm";
+    project.file(
+        adapter_path,
+        wasi_preview1_component_adapter_provider::WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["adapter"] = value(adapter_path);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_adapter_is_not_wasm() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["adapter"] = value("foo.wasm");
+        Ok(doc)
+    })?;
+
+    fs::write(project.root().join("foo.wasm"), "not wasm")?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("error: failed to load adapter module"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_adds_additional_derives() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["derives"] =
+            value(Array::from_iter(["serde::Serialize", "serde::Deserialize"]));
+        Ok(doc)
+    })?;
+
+    std::process::Command::new("cargo")
+        .args(["add", "serde", "--features", "derive"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+    std::process::Command::new("cargo")
+        .args(["add", "serde_json"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:derive;
+
+interface foo {
+    record bar {
+        value: u32,
+    }
+}
+
+world foo-world {
+    use foo.{bar};
+
+    export baz: func(thing: bar) -> list<u8>;
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::Guest;
+use bindings::my::derive::foo::Bar;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz(thing: Bar) -> Vec<u8> {
+        let stuff = serde_json::to_vec(&thing).unwrap();
+        // Check we got the derived deserialize
+        let _thing: Bar = serde_json::from_slice(&stuff).unwrap();
+        stuff
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_applies_interface_settings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut settings = InlineTable::new();
+        settings.insert("derives", Array::from_iter(["serde::Serialize"]).into());
+        settings.insert("skip", Array::from_iter(["unused"]).into());
+        doc["package"]["metadata"]["component"]["bindings"]["interface_settings"]
+            ["my:derive/foo"] = value(settings);
+        Ok(doc)
+    })?;
+
+    std::process::Command::new("cargo")
+        .args(["add", "serde", "--features", "derive"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+    std::process::Command::new("cargo")
+        .args(["add", "serde_json"])
+        .current_dir(project.root())
+        .assert()
+        .success();
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:derive;
+
+interface foo {
+    record bar {
+        value: u32,
+    }
+
+    get: func() -> bar;
+    unused: func();
+}
+
+interface other {
+    use foo.{bar};
+
+    record qux {
+        value: u32,
+    }
+
+    convert: func(thing: bar) -> qux;
+}
+
+world foo-world {
+    import foo;
+    import other;
+
+    export baz: func() -> list<u8>;
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::interface_settings::my_derive_foo::{get, Bar};
+use bindings::my::derive::other::{convert, Qux};
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz() -> Vec<u8> {
+        let thing: Bar = get();
+        let stuff = serde_json::to_vec(&thing).unwrap();
+        let _qux: Qux = convert(thing);
+        stuff
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("pub fn unused()"));
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["interface_settings"]
+            ["my:derive/foo"]["skip"] = value(Array::from_iter(["missing"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "function `missing` to skip is not defined by interface `my:derive/foo`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_trims_unreferenced_imports() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["trim_imports"] = value(true);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:trim;
+
+interface used {
+    get: func() -> u32;
+}
+
+interface unused {
+    get: func() -> u32;
+}
+
+world foo-world {
+    import used;
+    import unused;
+
+    export baz: func() -> u32;
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::my::trim::used;
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz() -> u32 {
+        used::get()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub mod used"));
+    assert!(!bindings.contains("pub mod unused"));
+
+    // Referencing the interface generates its bindings again
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::my::trim::{unused, used};
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn baz() -> u32 {
+        used::get() + unused::get()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub mod unused"));
+
+    Ok(())
+}
+
+#[test]
+fn it_warns_about_modules_without_component_types() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.file("src/lib.rs", "pub fn unused() {}\n")?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "has no `component-type` custom section describing the target world",
+        ))
+        .stderr(contains(
+            "ensure the crate includes the generated bindings (e.g. with `mod bindings;`)",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_bindings_settings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["type_section_suffix"] = value("\"foo");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("invalid `bindings` table in `"))
+        .stderr(contains(
+            "`type_section_suffix` `\"foo` must only contain printable ASCII characters",
+        ))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        let bindings = &mut doc["package"]["metadata"]["component"]["bindings"];
+        bindings["type_section_suffix"] = value("-foo");
+        bindings["export_macro_name"] = value("export-it");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "`export_macro_name` `export-it` is not a valid Rust identifier",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_implements_arbitrary_for_binding_types() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["arbitrary"] = value(true);
+        doc["dependencies"]["arbitrary"] = value(InlineTable::from_iter([
+            ("version", toml_edit::Value::from("1")),
+            ("features", Array::from_iter(["derive"]).into()),
+        ]));
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:arbitrary;
+
+interface types {
+    flags permissions {
+        read,
+        write,
+    }
+
+    enum color {
+        red,
+        green,
+    }
+
+    variant shape {
+        circle(f32),
+        square(u32),
+        none,
+    }
+
+    record bar {
+        name: string,
+        permissions: permissions,
+        color: color,
+        shapes: list<shape>,
+    }
+
+    resource counter {
+        constructor(start: u32);
+        get: func() -> u32;
+    }
+
+    record tally {
+        name: string,
+        counter: counter,
+    }
+
+    variant slot {
+        empty,
+        tally(tally),
+    }
+
+    record slots {
+        items: list<slot>,
+        bar: bar,
+    }
+
+    count: func(slots: slots) -> u32;
+}
+
+world foo-world {
+    use types.{bar};
+    import types;
+
+    export generate: func(data: list<u8>) -> option<bar>;
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::Guest;
+use bindings::my::arbitrary::types::Bar;
+
+struct Component;
+
+impl Guest for Component {
+    fn generate(data: Vec<u8>) -> Option<Bar> {
+        bindings::arbitrary::from_bytes(&data).ok()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    // Types carrying resource handles, directly or not, do not derive `Arbitrary`
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    let lines: Vec<_> = bindings.lines().map(str::trim_start).collect();
+    for (item, derived) in [
+        ("pub struct Bar {", true),
+        ("pub enum Shape {", true),
+        ("pub struct Tally {", false),
+        ("pub enum Slot {", false),
+        ("pub struct Slots {", false),
+    ] {
+        let index = lines
+            .iter()
+            .position(|l| l.starts_with(item))
+            .with_context(|| format!("`{item}` was not generated"))?;
+        let attrs = lines[..index]
+            .iter()
+            .rev()
+            .take_while(|l| l.starts_with("#[") || l.starts_with("///"));
+        assert_eq!(
+            attrs.clone().any(|l| l.contains("::arbitrary::Arbitrary")),
+            derived,
+            "unexpected derives of `{item}`"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_generates_proptest_strategies() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["proptest"] = value(true);
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["bindings"]).assert().success();
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("pub mod arbitrary {"));
+    assert!(bindings.contains("pub mod proptest {"));
+    assert!(bindings.contains("pub fn strategy<T>() -> impl Strategy<Value = T>"));
+
+    Ok(())
+}
+
+#[test]
+fn it_documents_the_wit_origin_of_bindings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["doc_origin"] = value(true);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:docs@1.0.0;
+
+interface foo {
+    /// A bar.
+    record bar {
+        value: u32,
+    }
+
+    baz: func(bar: bar);
+}
+
+world foo-world {
+    export foo;
+    export qux: func() -> u32;
+}
+",
+    )?;
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+use bindings::exports::my::docs::foo::{Bar, Guest as Foo};
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn qux() -> u32 {
+        0
+    }
+}
+
+impl Foo for Component {
+    fn baz(_bar: Bar) {}
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(bindings.contains("/// A bar.\n"));
+    assert!(bindings.contains("/// Defined in WIT by `my:docs/foo@1.0.0#bar`."));
+    assert!(bindings.contains("/// Defined in WIT by `my:docs/foo@1.0.0#baz`."));
+    assert!(bindings.contains("/// Defined in WIT by `my:docs/foo-world@1.0.0#qux`."));
+
+    // The origins must not change the type information embedded in the component
+    let component_type = |bindings: &str| {
+        bindings
+            .split("__WIT_BINDGEN_COMPONENT_TYPE")
+            .nth(1)
+            .and_then(|s| s.split("\";\n").next())
+            .map(ToOwned::to_owned)
+    };
+    let documented = component_type(&bindings);
+    assert!(documented.is_some());
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["bindings"]["doc_origin"] = value(false);
+        Ok(doc)
+    })?;
+    project.cargo_component(["bindings"]).assert().success();
+
+    let bindings = fs::read_to_string(project.root().join("src/bindings.rs"))?;
+    assert!(!bindings.contains("Defined in WIT by"));
+    assert_eq!(component_type(&bindings), documented);
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_versioned_wit() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+            package foo:bar@1.2.3;
+
+            interface foo {
+                f: func();
+            }
+
+            world bar {
+                export foo;
+            }
+        ",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+            #[allow(warnings)]
+            mod bindings;
+
+            struct Component;
+
+            impl bindings::exports::foo::bar::foo::Guest for Component {
+                fn f() {}
+            }
+
+            bindings::export!(Component with_types_in bindings);
+        "#,
+    )?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let dep = project.debug_wasm("foo");
+    validate_component(&dep)?;
+
+    Ok(())
+}
+
+#[test]
+fn it_warns_on_proxy_setting_for_command() -> Result<()> {
+    let project = Project::new("foo", false)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["proxy"] = value(true);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "warning: ignoring `proxy` setting in `Cargo.toml` for command component",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_warns_with_proxy_and_adapter_settings() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    let adapter_path = "adapter/wasi_snapshot_preview1.wasm";
+    project.file(
+        adapter_path,
+        wasi_preview1_component_adapter_provider::WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
+    )?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["proxy"] = value(true);
+        doc["package"]["metadata"]["component"]["adapter"] = value(adapter_path);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("warning: ignoring `proxy` setting due to `adapter` setting being present in `Cargo.toml`"))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_proxy_adapter() -> Result<()> {
+    let project = Project::new_with_args("foo", true, ["--proxy"])?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let text = wasmprinter::print_file(project.debug_wasm("foo"))?;
+    assert!(
+        !text.contains("wasi:cli/environment"),
+        "proxy wasm should have no reference to `wasi:cli/environment`"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_recomponentizes_when_the_adapter_changes() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let text = wasmprinter::print_file(project.debug_wasm("foo"))?;
+    assert!(text.contains("wasi:cli/environment"));
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["proxy"] = value(true);
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let text = wasmprinter::print_file(project.debug_wasm("foo"))?;
+    assert!(
+        !text.contains("wasi:cli/environment"),
+        "proxy wasm should have no reference to `wasi:cli/environment`"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_records_the_workspace_state() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.cargo_component(["build"]).assert().success();
+
+    let path = project.build_dir().join("component/state.json");
+    let mut state: serde_json::Value = serde_json::from_slice(&fs::read(&path)?)?;
+    let package = &state["packages"]["foo"];
+    assert!(package["bindings"]["inputs"].is_string());
+    assert_eq!(
+        package["adapters"][project.debug_wasm("foo").to_str().unwrap()]["command"],
+        false
+    );
+
+    // A state written by another version generates everything again
+    state["version"] = "0.0.0".into();
+    fs::write(&path, serde_json::to_vec(&state)?)?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Generating bindings"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_does_not_generate_bindings_for_cargo_projects() -> Result<()> {
+    let dir = TempDir::new()?;
+
+    for (name, args) in [("foo", &["new", "--lib"] as &[_]), ("bar", &["new"])] {
+        let mut cmd = Command::new("cargo");
+        cmd.current_dir(dir.path());
+        cmd.args(args);
+        cmd.arg(name);
+        cmd.assert().stderr(contains("Creating")).success();
+
+        let mut cmd = cargo_component(["build"]);
+        cmd.current_dir(dir.path().join(name));
+        cmd.assert()
+            .stderr(contains("Generating bindings").not())
+            .success();
+    }
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_prints_build_statistics() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+world foo {
+    import foo: func() -> string;
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar@1.0.0"])?;
+    fs::remove_dir_all(project.cache_dir())?;
+
+    project
+        .cargo_component(["build", "--stats"])
+        .assert()
+        .stderr(contains(
+            "Statistics resolved 1 package (1 from registries, 0 local)",
+        ))
+        .stderr(contains("cache hit rate 0% (0 of 1 registry packages)"))
+        .stderr(contains("lines of bindings"))
+        .stderr(contains(
+            "Statistics component `target/wasm32-wasip1/debug/component.wasm` is",
+        ))
+        .success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    project
+        .cargo_component(["build", "--stats"])
+        .assert()
+        .stderr(contains(
+            "downloaded 0 B, cache hit rate 100% (1 of 1 registry packages)",
+        ))
+        .success();
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Statistics").not())
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_downloads_corrupt_cached_content_again() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+world foo {
+    import foo: func() -> string;
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar@1.0.0"])?;
+    project.cargo_component(["build"]).assert().success();
+
+    // Truncate the cached content, as a failed download could have left it
+    let mut truncated = 0;
+    for entry in fs::read_dir(project.cache_dir())? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("sha256:"))
+        {
+            let contents = fs::read(&path)?;
+            fs::write(&path, &contents[..contents.len() / 2])?;
+            truncated += 1;
+        }
+    }
+    assert_eq!(truncated, 1);
+
+    fs::remove_dir_all(project.build_dir())?;
+    project.cargo_component(["build"]).assert().success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resolves_well_known_packages_of_a_local_target() -> Result<()> {
+    let (server, config, _) = spawn_server(["wasi"]).await?;
+
+    for version in ["0.1.0", "0.1.1"] {
+        publish_wit(
+            config.clone(),
+            "wasi:logging",
+            version,
+            &format!(
+                "package wasi:logging@{version};
+
+interface logging {{
+    log: func(message: string);
+}}"
+            ),
+        )
+        .await?;
+    }
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+world example {
+    import wasi:logging/logging@0.1.0;
+    export hello-world: func() -> string;
+}",
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "note: using `wasi:logging` v0.1.0 from the registry for the local target of package `foo`",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains(r#"name = "wasi:logging""#));
+    assert!(lock_file.contains(r#"version = "0.1.0""#));
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("note: using `wasi:logging`").not())
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_reuses_the_target_world_of_a_shared_target_package() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+world foo {
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    let foo = server.project("foo", true, ["--target", "test:bar@1.0.0"])?;
+    let baz = server.project("baz", true, ["--target", "test:bar@1.0.0"])?;
+    let workspace = Project::new_uninitialized(foo.dir.clone(), foo.dir.path().to_owned());
+    workspace.file(
+        "Cargo.toml",
+        r#"[workspace]
+members = ["foo", "baz"]
+resolver = "2"
+"#,
+    )?;
+
+    for project in [&foo, &baz] {
+        project.file(
+            "src/lib.rs",
+            r#"#[allow(warnings)]
+mod bindings;
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn bar() -> String {
+        "bar".to_string()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+        )?;
+    }
+
+    foo.cargo_component(["build", "--workspace"])
+        .env("CARGO_COMPONENT_LOG", "debug")
+        .assert()
+        .stderr(contains(
+            "reusing target world of package `test:bar` for package `baz`",
+        ))
+        .success();
+
+    validate_component(&workspace.debug_wasm("foo"))?;
+    validate_component(&workspace.debug_wasm("baz"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_checks_the_size_budget() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["max-size"] = value("1KiB");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "warning: component `target/wasm32-wasip1/debug/foo.wasm` is",
+        ))
+        .stderr(contains(
+            "which exceeds the maximum size of 1.0 KiB configured for package `foo`",
+        ))
+        .stderr(contains("hint: build with `--release`"))
+        .success();
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project.update_manifest(|mut doc| {
+        let budget = &mut doc["package"]["metadata"]["component"]["max-size"];
+        *budget = Item::Table(Table::new());
+        budget["size"] = value("1KiB");
+        budget["level"] = value("deny");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build", "--release"])
+        .assert()
+        .stderr(contains(
+            "error: component `target/wasm32-wasip1/release/foo.wasm` is",
+        ))
+        .stderr(contains("hint: rerun with `--stats`"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["max-size"] = value("100 MiB");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("maximum size").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_a_plugin_manifest() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let plugin = &mut doc["package"]["metadata"]["component"]["plugin"];
+        *plugin = Item::Table(Table::new());
+        plugin["api-version"] = value(2);
+        plugin["display-name"] = value("Foo");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    let path = project
+        .build_dir()
+        .join("wasm32-wasip1")
+        .join("debug")
+        .join("foo.plugin.json");
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path)?)?;
+
+    assert_eq!(manifest["schema-version"], 1);
+    assert_eq!(manifest["name"], "foo");
+    assert_eq!(manifest["version"], "0.1.0");
+    assert_eq!(manifest["package"], "component:foo");
+    assert_eq!(manifest["component"], "foo.wasm");
+    assert_eq!(
+        manifest["exports"],
+        serde_json::json!([{ "name": "hello-world", "kind": "function" }])
+    );
+    assert!(manifest["imports"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|i| i["kind"] == "interface" && i["name"].as_str().unwrap().starts_with("wasi:")));
+    assert_eq!(
+        manifest["metadata"],
+        serde_json::json!({ "api-version": 2, "display-name": "Foo" })
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_installs_components() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let install = &mut doc["package"]["metadata"]["component"]["install"];
+        *install = Item::Table(Table::new());
+        install["destinations"] = value(Array::from_iter([
+            "../app/plugins/{name}-{version}.wasm",
+            "../app/{profile}/",
+        ]));
+        doc["package"]["metadata"]["component"]["plugin"] = Item::Table(Table::new());
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(
+            contains("Installed `target/wasm32-wasip1/debug/foo.wasm` to `").and(contains(
+                "Installed `target/wasm32-wasip1/debug/foo.plugin.json` to `",
+            )),
+        )
+        .success();
+
+    let app = project.dir().path().join("app");
+    validate_component(&app.join("plugins/foo-0.1.0.wasm"))?;
+    validate_component(&app.join("dev/foo.wasm"))?;
+    assert!(app.join("plugins/foo-0.1.0.plugin.json").is_file());
+    assert!(app.join("dev/foo.plugin.json").is_file());
+
+    // Unchanged components are not installed again
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Installed").not())
+        .success();
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["install"]["profiles"] =
+            value(Array::from_iter(["release"]));
+        Ok(doc)
+    })?;
+
+    fs::remove_dir_all(&app)?;
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Installed").not())
+        .success();
+
+    assert!(!app.exists());
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["install"]["destinations"] =
+            value(Array::from_iter(["../app/{package}.wasm"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("invalid `install` table").and(contains(
+            "install destination `../app/{package}.wasm` has an unknown placeholder `{package}`",
+        )))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_relative_serve_routes() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let serve = &mut doc["package"]["metadata"]["component"]["serve"];
+        *serve = Item::Table(Table::new());
+        serve["route"] = value("api");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("serve route `api`").and(contains("must start with `/`")))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_with_a_short_message_format() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.file(
+        "src/lib.rs",
+        r#"#[allow(warnings)]
+mod bindings;
+
+use bindings::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn hello_world() -> String {
+        let unused = 1;
+        "Hello, World!".to_string()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["build", "--message-format", "short"])
+        .assert()
+        .stderr(contains(
+            "src/lib.rs:10:13: warning: unused variable: `unused`",
+        ))
+        .stdout(contains("\"reason\"").not())
+        .success();
+
+    validate_component(&project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component(["build", "--message-format=xml"])
+        .assert()
+        .stderr(contains("unsupported cargo message format `xml`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_validates_with_the_configured_wasm_features() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let mut wasm = Table::new();
+        wasm["component-model"] = value(false);
+        let mut features = Table::new();
+        features["wasm"] = Item::Table(wasm);
+        doc["package"]["metadata"]["component"]["features"] = Item::Table(features);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("failed to validate output component"))
+        .failure();
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["features"]["wasm"]["component-model"] =
+            value(true);
+        doc["package"]["metadata"]["component"]["features"]["wasm"]["threads"] = value(true);
+        Ok(doc)
+    })?;
+
+    project.cargo_component(["build"]).assert().success();
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unknown_wasm_features() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let mut wasm = Table::new();
+        wasm["teleportation"] = value(true);
+        let mut features = Table::new();
+        features["wasm"] = Item::Table(wasm);
+        doc["package"]["metadata"]["component"]["features"] = Item::Table(features);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("unknown WebAssembly feature `teleportation`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_prints_github_annotations_for_manifest_errors() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project.update_manifest(|mut doc| {
+        let mut wasm = Table::new();
+        wasm["teleportation"] = value(true);
+        let mut features = Table::new();
+        features["wasm"] = Item::Table(wasm);
+        doc["package"]["metadata"]["component"]["features"] = Item::Table(features);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build", "--output-format", "github"])
+        .assert()
+        .stdout(contains(
+            "::error file=Cargo.toml::invalid `features.wasm` table",
+        ))
+        .stderr(contains("unknown WebAssembly feature `teleportation`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_prints_github_annotations_for_wit_errors() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;\n\nworld example {\n    export hello-world: func() -> strin;\n}\n",
+    )?;
+
+    project
+        .cargo_component(["build", "--output-format=github"])
+        .assert()
+        .stdout(contains("::error file=wit/world.wit,line=4,col="))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_renders_wit_errors_with_snippets() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value("bar.wit");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("bar.wit"),
+        "package foo:bar;\n\ninterface baz {\n    baz: func() -> strin;\n}\n",
+    )?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "error: name `strin` is not defined
+ --> bar.wit:4:20
+  |
+4 |     baz: func() -> strin;
+  |                    ^^^^^
+  |
+  = note: ",
+        ))
+        .stderr(contains("  = note: failed to parse dependency `foo:bar`"))
+        .failure();
+
+    Ok(())
+}
+
+/// Gets whether a nightly toolchain with the `rust-src` component is
+/// available for building the standard library for `wasm64` targets.
+fn nightly_with_rust_src() -> bool {
+    Command::new("rustc")
+        .args(["+nightly", "--print", "sysroot"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| {
+            std::path::PathBuf::from(String::from_utf8_lossy(&output.stdout).trim())
+                .join("lib/rustlib/src/rust/library")
+                .is_dir()
+        })
+        .unwrap_or(false)
+}
+
+#[test]
+fn it_builds_for_wasm64() -> Result<()> {
+    if !nightly_with_rust_src() {
+        eprintln!("skipping test as a nightly toolchain with `rust-src` is not available");
+        return Ok(());
+    }
+
+    let project = Project::new("foo", true)?;
+    project.file(
+        ".cargo/config.toml",
+        "[unstable]\nbuild-std = [\"std\", \"panic_abort\"]\n",
     )?;
 
-    comp2
-        .cargo_component(["build", "--release"])
-        .assert()
-        .stderr(contains("Finished `release` profile [optimized] target(s)"))
-        .success();
-
-    let path: std::path::PathBuf = comp2.release_wasm("comp2");
-    validate_component(&path)?;
+    let output = project
+        .cargo_component(["build", "--target", "wasm64-unknown-unknown"])
+        .env_remove("CARGO")
+        .env("RUSTUP_TOOLCHAIN", "nightly")
+        .output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // The bindings must compile for the target before componentization
+    assert!(stderr.contains("Creating component"), "{stderr}");
+    if output.status.success() {
+        validate_component(
+            &project
+                .build_dir()
+                .join("wasm64-unknown-unknown/debug/foo.wasm"),
+        )?;
+    } else {
+        assert!(
+            stderr.contains("failed to encode 64-bit memory module"),
+            "{stderr}"
+        );
+    }
 
     Ok(())
 }
 
 #[test]
-fn it_builds_with_adapter() -> Result<()> {
+fn it_excludes_targets_from_componentization() -> Result<()> {
     let project = Project::new("foo", true)?;
-    project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["adapter"] = value("not-a-valid-path");
-        Ok(doc)
-    })?;
+    project.file(
+        "src/bin/helper.rs",
+        "use std::os::unix::process::CommandExt;
+
+fn main() {
+    std::process::Command::new(\"true\").exec();
+}
+",
+    )?;
 
     project
         .cargo_component(["build"])
         .assert()
-        .stderr(contains("error: failed to read module adapter"))
+        .stderr(contains("could not compile `foo` (bin \"helper\")"))
         .failure();
 
-    let project = Project::new("foo", true)?;
-    let adapter_path = "adapter/wasi_snapshot_preview1.wasm";
-    project.file(
-        adapter_path,
-        wasi_preview1_component_adapter_provider::WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
-    )?;
     project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["adapter"] = value(adapter_path);
+        doc["package"]["metadata"]["component"]["exclude-targets"] =
+            value(Array::from_iter(["bin:helper"]));
         Ok(doc)
     })?;
 
@@ -724,94 +3086,123 @@ fn it_builds_with_adapter() -> Result<()> {
             "Finished `dev` profile [unoptimized + debuginfo] target(s)",
         ))
         .success();
-
     validate_component(&project.debug_wasm("foo"))?;
+    assert!(!project.debug_wasm("helper").exists());
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["exclude-targets"] =
+            value(Array::from_iter(["bin:missing"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("excluded target `bin:missing` in `"))
+        .stderr(contains("does not match any target of the package"))
+        .failure();
 
     Ok(())
 }
 
 #[test]
-fn it_errors_if_adapter_is_not_wasm() -> Result<()> {
+fn it_warns_about_libraries_that_are_not_cdylibs() -> Result<()> {
     let project = Project::new("foo", true)?;
     project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["adapter"] = value("foo.wasm");
+        doc["lib"]["crate-type"] = value(Array::from_iter(["rlib"]));
         Ok(doc)
     })?;
 
-    fs::write(project.root().join("foo.wasm"), "not wasm")?;
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "library `foo` of package `foo` will not be componentized as it is not built as a \
+             `cdylib`",
+        ))
+        .stderr(contains("[lib]\ncrate-type = [\"cdylib\", \"rlib\"]"))
+        .success();
+    assert!(!project.debug_wasm("foo").exists());
+
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["exclude-targets"] =
+            value(Array::from_iter(["lib:*"]));
+        Ok(doc)
+    })?;
 
     project
         .cargo_component(["build"])
         .assert()
-        .stderr(contains("error: failed to load adapter module"))
-        .failure();
+        .stderr(contains("will not be componentized").not())
+        .success();
 
     Ok(())
 }
 
 #[test]
-fn it_adds_additional_derives() -> Result<()> {
-    let project = Project::new("foo", true)?;
+fn it_builds_libraries_with_a_different_name_than_the_package() -> Result<()> {
+    let project = Project::new("foo-bar", true)?;
     project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["bindings"]["derives"] =
-            value(Array::from_iter(["serde::Serialize", "serde::Deserialize"]));
+        doc["lib"]["name"] = value("baz");
         Ok(doc)
     })?;
 
-    std::process::Command::new("cargo")
-        .args(["add", "serde", "--features", "derive"])
-        .current_dir(project.root())
+    project
+        .cargo_component(["build"])
         .assert()
+        .stderr(contains(
+            "Creating component target/wasm32-wasip1/debug/baz.wasm",
+        ))
         .success();
-    std::process::Command::new("cargo")
-        .args(["add", "serde_json"])
-        .current_dir(project.root())
+    validate_component(&project.debug_wasm("baz"))?;
+
+    project.update_manifest(|mut doc| {
+        doc["lib"].as_table_mut().unwrap().remove("name");
+        doc["package"]["metadata"]["component"]["exclude-targets"] =
+            value(Array::from_iter(["lib:foo-bar"]));
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["build"])
         .assert()
+        .stderr(contains("Creating component").not())
         .success();
 
-    fs::write(
-        project.root().join("wit/world.wit"),
-        "
-package my:derive;
-
-interface foo {
-    record bar {
-        value: u32,
-    }
+    Ok(())
 }
 
-world foo-world {
-    use foo.{bar};
-
-    export baz: func(thing: bar) -> list<u8>;
-}
-",
-    )?;
-    fs::write(
-        project.root().join("src/lib.rs"),
-        r#"
-#[allow(warnings)]
-mod bindings;
-use bindings::Guest;
-use bindings::my::derive::foo::Bar;
+#[test]
+fn it_warns_about_modules_that_cannot_be_componentized() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]
+            .as_table_like_mut()
+            .unwrap()
+            .remove("component");
+        Ok(doc)
+    })?;
+    project.file("src/lib.rs", "pub fn unused() {}\n")?;
 
-struct Component;
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "output module `target/wasm32-wasip1/debug/foo.wasm` of package `foo` has no \
+             `component-type` custom section and will not be componentized",
+        ))
+        .stderr(contains("did you forget to generate bindings"))
+        .success();
 
-impl Guest for Component {
-    fn baz(thing: Bar) -> Vec<u8> {
-        let stuff = serde_json::to_vec(&thing).unwrap();
-        // Check we got the derived deserialize
-        let _thing: Bar = serde_json::from_slice(&stuff).unwrap();
-        stuff
-    }
+    Ok(())
 }
 
-bindings::export!(Component with_types_in bindings);
-"#,
-    )?;
+#[test]
+fn it_emits_core_and_adapter_modules() -> Result<()> {
+    let project = Project::new("foo", true)?;
 
     project
-        .cargo_component(["build"])
+        .cargo_component(["build", "--emit", "core-module,adapter-module"])
         .assert()
         .stderr(contains(
             "Finished `dev` profile [unoptimized + debuginfo] target(s)",
@@ -820,138 +3211,151 @@ bindings::export!(Component with_types_in bindings);
 
     validate_component(&project.debug_wasm("foo"))?;
 
+    let dir = project.build_dir().join("wasm32-wasip1").join("debug");
+    for name in ["foo.core.wasm", "foo.wasi_snapshot_preview1.wasm"] {
+        let bytes = fs::read(dir.join(name))?;
+        assert!(
+            bytes.starts_with(b"\0asm\x01\0\0\0"),
+            "`{name}` is not a module"
+        );
+    }
+
     Ok(())
 }
 
 #[test]
-fn it_builds_with_versioned_wit() -> Result<()> {
-    let project = Project::new("foo", true)?;
-
-    fs::write(
-        project.root().join("wit/world.wit"),
-        "
-            package foo:bar@1.2.3;
-
-            interface foo {
-                f: func();
-            }
+fn it_checks_the_libraries_linked_into_a_component() -> Result<()> {
+    let dir = Rc::new(TempDir::new()?);
+    let project = Project::with_dir(dir.clone(), "foo", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let mut libraries = Array::new();
+        libraries.push("bar");
+        doc["package"]["metadata"]["component"]["link"]["libraries"] = value(libraries);
+        Ok(doc)
+    })?;
 
-            world bar {
-                export foo;
-            }
-        ",
+    let workspace = Project::new_uninitialized(dir.clone(), dir.path().to_owned());
+    workspace.file(
+        "bar/Cargo.toml",
+        r#"[package]
+name = "bar"
+version = "0.1.0"
+edition = "2021"
+"#,
+    )?;
+    workspace.file("bar/src/lib.rs", "")?;
+    workspace.file(
+        "Cargo.toml",
+        r#"[workspace]
+members = ["foo", "bar"]
+resolver = "2"
+"#,
     )?;
 
-    fs::write(
-        project.root().join("src/lib.rs"),
-        r#"
-            #[allow(warnings)]
-            mod bindings;
-
-            struct Component;
+    workspace
+        .cargo_component(["build", "--workspace"])
+        .assert()
+        .stderr(contains(
+            "library `bar` linked into package `foo` is not built as a `cdylib`",
+        ))
+        .failure();
 
-            impl bindings::exports::foo::bar::foo::Guest for Component {
-                fn f() {}
-            }
+    project.update_manifest(|mut doc| {
+        let mut libraries = Array::new();
+        libraries.push("baz");
+        doc["package"]["metadata"]["component"]["link"]["libraries"] = value(libraries);
+        Ok(doc)
+    })?;
 
-            bindings::export!(Component with_types_in bindings);
-        "#,
-    )?;
+    workspace
+        .cargo_component(["build", "--workspace"])
+        .assert()
+        .stderr(contains(
+            "library `baz` linked into package `foo` is not a member of the workspace",
+        ))
+        .failure();
 
-    project.cargo_component(["build"]).assert().success();
+    project.update_manifest(|mut doc| {
+        let mut dl_openable = Array::new();
+        dl_openable.push("bar");
+        doc["package"]["metadata"]["component"]["link"]["dl-openable"] = value(dl_openable);
+        Ok(doc)
+    })?;
 
-    let dep = project.debug_wasm("foo");
-    validate_component(&dep)?;
+    workspace
+        .cargo_component(["build", "--workspace"])
+        .assert()
+        .stderr(contains("dl-openable library `bar` in `"))
+        .failure();
 
     Ok(())
 }
 
 #[test]
-fn it_warns_on_proxy_setting_for_command() -> Result<()> {
-    let project = Project::new("foo", false)?;
+fn it_builds_dynamic_libraries_with_position_independent_code() -> Result<()> {
+    let project = Project::new("foo", true)?;
     project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["proxy"] = value(true);
+        doc["package"]["metadata"]["component"]["library"] = value(true);
         Ok(doc)
     })?;
 
+    // Linking also requires a position-independent standard library, so only
+    // the flags passed to rustc are checked
     project
-        .cargo_component(["build"])
+        .cargo_component(["build", "-v"])
         .assert()
         .stderr(contains(
-            "warning: ignoring `proxy` setting in `Cargo.toml` for command component",
+            "-Crelocation-model=pic -Clink-arg=--experimental-pic -Clink-arg=--shared",
         ))
-        .success();
-
-    validate_component(&project.debug_wasm("foo"))?;
+        .stderr(contains("Creating component").not());
 
     Ok(())
 }
 
 #[test]
-fn it_warns_with_proxy_and_adapter_settings() -> Result<()> {
+fn it_warns_about_and_fixes_deprecated_metadata_keys() -> Result<()> {
     let project = Project::new("foo", true)?;
-    let adapter_path = "adapter/wasi_snapshot_preview1.wasm";
-    project.file(
-        adapter_path,
-        wasi_preview1_component_adapter_provider::WASI_SNAPSHOT_PREVIEW1_REACTOR_ADAPTER,
-    )?;
     project.update_manifest(|mut doc| {
-        doc["package"]["metadata"]["component"]["proxy"] = value(true);
-        doc["package"]["metadata"]["component"]["adapter"] = value(adapter_path);
+        doc["package"]["metadata"]["component"]["registries"]["default"] =
+            value("https://example.com");
+        doc["package"]["metadata"]["component"]["bindings"]["implementor"] = value("Component");
         Ok(doc)
     })?;
 
     project
         .cargo_component(["build"])
         .assert()
-        .stderr(contains("warning: ignoring `proxy` setting due to `adapter` setting being present in `Cargo.toml`"))
+        .stderr(contains(
+            "key `package.metadata.component.registries` is deprecated: registries are configured",
+        ))
+        .stderr(contains(
+            "key `package.metadata.component.bindings.implementor` is deprecated: exports are \
+             implemented with `bindings::export!(Component with_types_in bindings)`; run \
+             `cargo component fix` to remove it",
+        ))
         .success();
 
-    validate_component(&project.debug_wasm("foo"))?;
-
-    Ok(())
-}
-
-#[test]
-fn it_builds_with_proxy_adapter() -> Result<()> {
-    let project = Project::new_with_args("foo", true, ["--proxy"])?;
-
     project
-        .cargo_component(["build"])
+        .cargo_component(["fix", "--allow-no-vcs"])
         .assert()
         .stderr(contains(
-            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+            "Fixed deprecated key `package.metadata.component.bindings.implementor`",
+        ))
+        .stderr(contains(
+            "key `package.metadata.component.registries` is deprecated",
         ))
         .success();
 
-    validate_component(&project.debug_wasm("foo"))?;
-
-    let text = wasmprinter::print_file(project.debug_wasm("foo"))?;
-    assert!(
-        !text.contains("wasi:cli/environment"),
-        "proxy wasm should have no reference to `wasi:cli/environment`"
-    );
-
-    Ok(())
-}
-
-#[test]
-fn it_does_not_generate_bindings_for_cargo_projects() -> Result<()> {
-    let dir = TempDir::new()?;
-
-    for (name, args) in [("foo", &["new", "--lib"] as &[_]), ("bar", &["new"])] {
-        let mut cmd = Command::new("cargo");
-        cmd.current_dir(dir.path());
-        cmd.args(args);
-        cmd.arg(name);
-        cmd.assert().stderr(contains("Creating")).success();
+    let manifest = project.read_manifest()?;
+    let component = &manifest["package"]["metadata"]["component"];
+    assert!(component["bindings"].get("implementor").is_none());
+    assert!(component.get("registries").is_some());
 
-        let mut cmd = cargo_component(["build"]);
-        cmd.current_dir(dir.path().join(name));
-        cmd.assert()
-            .stderr(contains("Generating bindings").not())
-            .success();
-    }
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("bindings.implementor").not())
+        .success();
 
     Ok(())
 }
@@ -0,0 +1,80 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_core::command::CACHE_DIR_ENV_VAR;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+#[test]
+fn help() {
+    for arg in ["help bundle", "bundle -h", "bundle --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Export or import a bundle of the locked registry dependencies",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_exports_and_imports_a_bundle() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config, "test:bar", "1.0.0", "(component)").await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    project.cargo_component(["update"]).assert().success();
+
+    let bundle = project.root().join("deps.bundle");
+    project
+        .cargo_component(["bundle", "export", "deps.bundle"])
+        .assert()
+        .stderr(contains("Exported 1 package(s) and 0 adapter(s)"))
+        .success();
+
+    // Import into an empty cache and export again without the registry
+    drop(server);
+    let cache = project.root().join("offline-cache");
+    project
+        .cargo_component(["bundle", "import", "deps.bundle"])
+        .env(CACHE_DIR_ENV_VAR, &cache)
+        .assert()
+        .stderr(contains("Imported 1 package(s) and 0 adapter(s)"))
+        .success();
+
+    project
+        .cargo_component(["bundle", "export", "offline.bundle"])
+        .env(CACHE_DIR_ENV_VAR, &cache)
+        .assert()
+        .stderr(contains("Exported 1 package(s) and 0 adapter(s)"))
+        .success();
+
+    assert_eq!(
+        fs::read(&bundle)?,
+        fs::read(project.root().join("offline.bundle"))?
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_bundles() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    fs::write(project.root().join("invalid.bundle"), "not a bundle")?;
+
+    project
+        .cargo_component(["bundle", "import", "invalid.bundle"])
+        .assert()
+        .stderr(contains("file is not a cargo-component bundle"))
+        .failure();
+
+    Ok(())
+}
@@ -2,13 +2,10 @@ use std::{fmt::Write, fs, rc::Rc};
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::{boolean::PredicateBooleanExt, str::contains};
 use tempfile::TempDir;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 fn it_checks_a_new_project() -> Result<()> {
     let project = Project::new("foo", true)?;
@@ -0,0 +1,62 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+#[test]
+fn help() {
+    for arg in [
+        "help containerize",
+        "containerize -h",
+        "containerize --help",
+    ] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Build a component and write it as an OCI image without a container engine",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_writes_an_image_layout() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["containerize"])
+        .assert()
+        .stderr(contains("Containerized image for `foo`"))
+        .success();
+
+    let dir = project.root().join("target").join("oci").join("foo");
+    assert!(dir.join("oci-layout").is_file());
+
+    let index: serde_json::Value = serde_json::from_slice(&fs::read(dir.join("index.json"))?)?;
+    let manifest = &index["manifests"][0];
+    assert_eq!(
+        manifest["mediaType"],
+        "application/vnd.oci.image.manifest.v1+json"
+    );
+    assert_eq!(manifest["platform"]["architecture"], "wasm");
+
+    let digest = manifest["digest"].as_str().unwrap();
+    let manifest: serde_json::Value = serde_json::from_slice(&fs::read(
+        dir.join("blobs")
+            .join("sha256")
+            .join(digest.trim_start_matches("sha256:")),
+    )?)?;
+    assert_eq!(
+        manifest["config"]["mediaType"],
+        "application/vnd.wasm.config.v0+json"
+    );
+    assert_eq!(manifest["layers"][0]["mediaType"], "application/wasm");
+    assert_eq!(
+        manifest["annotations"]["module.wasm.image/variant"],
+        "compat-smart"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use toml_edit::{value, Item, Table};
+
+#[test]
+fn help() {
+    for arg in ["help deploy", "deploy -h", "deploy --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Build a component and deploy it with its configured provider",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_requires_a_deployment_configuration() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["deploy"])
+        .assert()
+        .stderr(contains("package `foo` has no deployment configuration"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unknown_providers() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut deploy = Table::new();
+        deploy["provider"] = value("carrier-pigeon");
+        doc["package"]["metadata"]["component"]["deploy"] = Item::Table(deploy);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["deploy"])
+        .assert()
+        .stderr(contains("unknown variant `carrier-pigeon`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_builds_and_skips_deploying_on_dry_run() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut deploy = Table::new();
+        deploy["provider"] = value("wasmcloud");
+        deploy["reference"] = value("localhost:5000/foo");
+        deploy["manifest"] = value("wadm.yaml");
+        doc["package"]["metadata"]["component"]["deploy"] = Item::Table(deploy);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["deploy", "--dry-run"])
+        .assert()
+        .stderr(contains("Skipping `wash push localhost:5000/foo:0.1.0"))
+        .stderr(contains("Skipping `wash app deploy"))
+        .success();
+
+    Ok(())
+}
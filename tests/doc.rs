@@ -2,12 +2,9 @@ use std::fs;
 
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::str::contains;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 fn it_documents() -> Result<()> {
     let project = Project::new("foo", true)?;
@@ -20,7 +17,7 @@ fn it_documents() -> Result<()> {
         ))
         .success();
 
-    let doc = project.build_dir().join("doc");
+    let doc = project.build_dir().join("wasm32-wasip1").join("doc");
 
     let path = doc.join("src").join("foo").join("lib.rs.html");
     let content = fs::read(&path).with_context(|| {
@@ -33,3 +30,71 @@ fn it_documents() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_links_bindings_to_wit_definitions() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package my:docs@1.0.0;
+
+/// Strings!
+interface strings {
+    /// Options for transforming strings.
+    record options {
+        reverse: bool,
+    }
+
+    transform: func(s: string, options: options) -> string;
+}
+
+world foo {
+    import strings;
+    export hello: func() -> string;
+}",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"#[allow(warnings)]
+mod bindings;
+
+use bindings::{my::docs::strings, Guest};
+
+struct Component;
+
+impl Guest for Component {
+    fn hello() -> String {
+        strings::transform("hello", strings::Options { reverse: true })
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["doc", "--open"])
+        .env("BROWSER", "echo")
+        .assert()
+        .stderr(contains("Documenting WIT definitions for foo"))
+        .stdout(contains("foo/index.html"))
+        .success();
+
+    let doc = project.build_dir().join("wasm32-wasip1").join("doc");
+    let wit = fs::read_to_string(doc.join("wit").join("foo.html"))?;
+    assert!(wit.contains(r#"<span id="my:docs/strings@1.0.0#options">  record options {</span>"#));
+    assert!(wit.contains(r#"<span id="my:docs/foo@1.0.0">world foo {</span>"#));
+
+    let options = fs::read_to_string(doc.join("foo/bindings/my/docs/strings/struct.Options.html"))?;
+    assert!(options.contains("Options for transforming strings."));
+    assert!(options.contains(
+        r#"href="../../../../../wit/foo.html#my:docs/strings@1.0.0%23options"><code>my:docs/strings@1.0.0#options</code></a>"#
+    ));
+
+    let bindings = fs::read_to_string(doc.join("foo/bindings/index.html"))?;
+    assert!(bindings.contains(r#"href="../../wit/foo.html#my:docs/foo@1.0.0""#));
+
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn help() {
+    for arg in ["help embed", "embed -h", "embed --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains("Encode a core WebAssembly module as a component"))
+            .success();
+    }
+}
+
+#[test]
+fn it_embeds_a_world_in_a_module() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(
+        dir.path().join("world.wit"),
+        "package test:embed;\n\nworld example {\n  export answer: func() -> u32;\n}\n",
+    )?;
+    fs::write(
+        dir.path().join("module.wasm"),
+        wat::parse_str(
+            r#"(module
+    (memory (export "memory") 1)
+    (func (export "answer") (result i32) i32.const 42)
+)"#,
+        )?,
+    )?;
+
+    cargo_component(["embed", "module.wasm", "--wit", "world.wit"])
+        .current_dir(dir.path())
+        .assert()
+        .stderr(contains("Creating component module.component.wasm"))
+        .success();
+
+    let path = dir.path().join("module.component.wasm");
+    validate_component(&path)?;
+
+    let text = wasmprinter::print_file(&path)?;
+    assert!(text.contains("(export (;1;) \"answer\" (func 0))"));
+    assert!(text.contains("processed-by"));
+
+    cargo_component([
+        "embed",
+        "module.wasm",
+        "--wit",
+        "world.wit",
+        "--world",
+        "example",
+        "--no-producers",
+        "-o",
+        "out.wasm",
+    ])
+    .current_dir(dir.path())
+    .assert()
+    .success();
+
+    validate_component(&dir.path().join("out.wasm"))?;
+    let text = wasmprinter::print_file(dir.path().join("out.wasm"))?;
+    assert!(!text.contains("processed-by"));
+
+    Ok(())
+}
+
+#[test]
+fn it_embeds_a_module_built_by_cargo() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["build", "--emit", "core-module"])
+        .assert()
+        .success();
+
+    project
+        .cargo_component([
+            "embed",
+            "target/wasm32-wasip1/debug/foo.core.wasm",
+            "--no-producers",
+            "-o",
+            "embedded.wasm",
+        ])
+        .assert()
+        .stderr(contains("Creating component embedded.wasm"))
+        .success();
+
+    validate_component(&project.root().join("embedded.wasm"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_warns_about_modules_without_a_world() -> Result<()> {
+    let dir = TempDir::new()?;
+    fs::write(
+        dir.path().join("module.wasm"),
+        wat::parse_str("(module (memory (export \"memory\") 1))")?,
+    )?;
+
+    cargo_component(["embed", "module.wasm"])
+        .current_dir(dir.path())
+        .assert()
+        .stderr(contains(
+            "module `module.wasm` has no `component-type` custom section",
+        ))
+        .success();
+
+    validate_component(&dir.path().join("module.component.wasm"))?;
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use std::sync::mpsc;
+
+use anyhow::Result;
+use cargo_component::{
+    config::{CargoArguments, Config},
+    events::BuildEvent,
+    load_component_metadata, load_metadata, run_cargo_command, CommandStatus,
+};
+use cargo_component_core::terminal::{Color, Terminal, Verbosity};
+use cargo_component_test_support::*;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_emits_build_events() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    let manifest_path = project.root().join("Cargo.toml");
+
+    // `cargo component new` already generated up-to-date bindings, so
+    // remove them for the build to generate them again
+    std::fs::remove_file(project.root().join("src/bindings.rs"))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let config = Config::new_or_empty(
+        Terminal::new(Verbosity::Quiet, Color::Never),
+        Some(project.root().join("config.toml")),
+    )
+    .await?
+    .with_cache_dir(Some(project.cache_dir()))
+    .with_events(sender);
+
+    let metadata = load_metadata(Some(&manifest_path))?;
+    let packages = load_component_metadata(&metadata, [].iter(), false, Some("dev"))?;
+    let cargo_args = CargoArguments {
+        manifest_path: Some(manifest_path.clone()),
+        ..Default::default()
+    };
+    let spawn_args = [
+        "build".to_string(),
+        "--manifest-path".to_string(),
+        manifest_path.display().to_string(),
+    ];
+
+    let client = config.client(false).await?;
+    let (status, _) = run_cargo_command(
+        client,
+        &config,
+        &metadata,
+        &packages,
+        Some("build"),
+        &cargo_args,
+        &spawn_args,
+    )
+    .await?;
+    assert_eq!(status, CommandStatus::Success);
+    drop(config);
+
+    let path = project.debug_wasm("foo");
+    let size = std::fs::metadata(&path)?.len();
+    assert_eq!(
+        receiver.iter().collect::<Vec<_>>(),
+        [
+            BuildEvent::ResolveStarted {
+                package: "foo".to_string(),
+            },
+            BuildEvent::BindingsGenerated {
+                package: "foo".to_string(),
+                path: project.root().join("src/bindings.rs"),
+            },
+            BuildEvent::Componentized { path, size },
+        ]
+    );
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+#![cfg(unix)]
+
+use std::{env, fs, os::unix::fs::PermissionsExt};
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use tempfile::TempDir;
+
+#[test]
+fn it_dispatches_to_external_subcommands() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    let bin = TempDir::new()?;
+    let script = bin.path().join("cargo-component-ship");
+    fs::write(
+        &script,
+        "#!/bin/sh\necho \"args: $@\"\necho \"$CARGO_COMPONENT_METADATA\"\n",
+    )?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let path = env::join_paths(
+        std::iter::once(bin.path().to_path_buf())
+            .chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )?;
+
+    project
+        .cargo_component(["ship", "--release", "--env", "prod"])
+        .env("PATH", &path)
+        .assert()
+        .stdout(contains("args: ship --release --env prod"))
+        .stdout(contains("\"schema-version\":1"))
+        .stdout(contains("\"profile\":\"release\""))
+        .stdout(contains("\"name\":\"foo\""))
+        .stdout(contains("wasm32-wasip1/release/foo.wasm"))
+        .success();
+
+    // Artifacts are in the directory cargo uses for the selected profile
+    project
+        .cargo_component(["ship", "--profile", "bench"])
+        .env("PATH", &path)
+        .assert()
+        .stdout(contains("\"profile\":\"bench\""))
+        .stdout(contains("wasm32-wasip1/release/foo.wasm"))
+        .success();
+    project
+        .cargo_component(["ship", "--profile=ci"])
+        .env("PATH", &path)
+        .assert()
+        .stdout(contains("\"profile\":\"ci\""))
+        .stdout(contains("wasm32-wasip1/ci/foo.wasm"))
+        .success();
+
+    // Commands handled by `cargo-component` are never dispatched
+    let script = bin.path().join("cargo-component-build");
+    fs::write(&script, "#!/bin/sh\necho dispatched\nexit 1\n")?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+    project
+        .cargo_component(["build", "--help"])
+        .env("PATH", &path)
+        .assert()
+        .stdout(contains("dispatched").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_forwards_the_exit_code_of_external_subcommands() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    let bin = TempDir::new()?;
+    let script = bin.path().join("cargo-component-fail");
+    fs::write(&script, "#!/bin/sh\nexit 3\n")?;
+    fs::set_permissions(&script, fs::Permissions::from_mode(0o755))?;
+
+    let path = env::join_paths(
+        std::iter::once(bin.path().to_path_buf())
+            .chain(env::split_paths(&env::var_os("PATH").unwrap_or_default())),
+    )?;
+
+    project
+        .cargo_component(["fail"])
+        .env("PATH", &path)
+        .assert()
+        .code(3);
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+#[test]
+fn help() {
+    for arg in ["help fetch", "fetch -h", "fetch --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Fetch the dependencies of a package into the cache without building it",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_fetches_dependencies_and_updates_the_lock_file() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config, "test:bar", "1.0.0", "(component)").await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains("Fetched 1 component dependency package(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("name = \"test:bar\""));
+
+    // The content should now be served from the cache
+    drop(server);
+    project
+        .cargo_component(["fetch", "--frozen"])
+        .assert()
+        .stderr(contains("Fetched 1 component dependency package(s)"))
+        .success();
+
+    Ok(())
+}
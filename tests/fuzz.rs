@@ -0,0 +1,134 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+fn fuzz_project() -> Result<Project> {
+    let project = Project::new("foo", true)?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "
+package my:fuzz;
+
+interface strings {
+    record options {
+        reverse: bool,
+        repeat: u8,
+    }
+
+    transform: func(s: string, options: options) -> string;
+    check: func(s: string) -> result<_, string>;
+}
+
+world foo {
+    export strings;
+    export add: func(a: u32, b: u32) -> u32;
+}",
+    )?;
+
+    fs::write(
+        project.root().join("src/lib.rs"),
+        r#"
+#[allow(warnings)]
+mod bindings;
+
+use bindings::exports::my::fuzz::strings::{Guest as Strings, Options};
+
+struct Component;
+
+impl bindings::Guest for Component {
+    fn add(a: u32, b: u32) -> u32 {
+        a.wrapping_add(b)
+    }
+}
+
+impl Strings for Component {
+    fn transform(s: String, options: Options) -> String {
+        let s = if options.reverse { s.chars().rev().collect() } else { s };
+        s.repeat(options.repeat as usize % 4)
+    }
+
+    fn check(s: String) -> Result<(), String> {
+        if s.contains('a') {
+            panic!("found an `a`");
+        }
+        Ok(())
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    Ok(project)
+}
+
+#[test]
+fn it_fuzzes_exported_functions() -> Result<()> {
+    let project = fuzz_project()?;
+
+    project
+        .cargo_component(["fuzz", "add", "--seed", "1", "-n", "20"])
+        .assert()
+        .stderr(contains("Fuzzing `add` with 20 inputs"))
+        .success();
+
+    project
+        .cargo_component(["fuzz", "transform", "--seed", "1", "-n", "20"])
+        .assert()
+        .stderr(contains(
+            "Fuzzing `my:fuzz/strings.transform` with 20 inputs",
+        ))
+        .success();
+
+    assert!(project.root().join("fuzz/corpus/add").is_dir());
+    assert!(project
+        .root()
+        .join("fuzz/corpus/my_fuzz_strings_transform")
+        .is_dir());
+
+    Ok(())
+}
+
+#[test]
+fn it_saves_and_replays_crashing_inputs() -> Result<()> {
+    let project = fuzz_project()?;
+
+    project
+        .cargo_component(["fuzz", "check", "--seed", "1", "-n", "100"])
+        .assert()
+        .stderr(contains("crashes `my:fuzz/strings.check`"))
+        .stderr(contains("found 1 crashing input (seed 1)"))
+        .failure();
+
+    let corpus = project.root().join("fuzz/corpus/my_fuzz_strings_check");
+    let saved = fs::read_dir(&corpus)?.collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(saved.len(), 1);
+    assert!(fs::read_to_string(saved[0].path())?.starts_with("my:fuzz/strings.check(\""));
+
+    project
+        .cargo_component(["fuzz", "check", "-n", "0"])
+        .assert()
+        .stderr(contains("still crashes `my:fuzz/strings.check`"))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_if_the_function_is_not_exported() -> Result<()> {
+    let project = fuzz_project()?;
+
+    project
+        .cargo_component(["fuzz", "missing"])
+        .assert()
+        .stderr(contains(
+            "package `foo` does not export a function named `missing` that can be fuzzed",
+        ))
+        .failure();
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! End-to-end tests replaying recorded registry interactions.
+//!
+//! The cassettes are in `tests/fixtures/registry`; run the tests with
+//! `CARGO_COMPONENT_REGISTRY_CASSETTE_MODE=record` to record them anew.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+/// Gets the directory of the cassette with the given name.
+fn cassette(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests/fixtures/registry", name]
+        .iter()
+        .collect()
+}
+
+const BAR_1_0_0: &str = "package test:bar@1.0.0;
+
+interface baz {
+    qux: func();
+}
+";
+
+const BAR_1_1_0: &str = "package test:bar@1.1.0;
+
+interface baz {
+    qux: func();
+    quux: func();
+}
+";
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resolves_dependencies_from_a_recorded_registry() -> Result<()> {
+    let server = spawn_recorded_server(cassette("resolution")).await?;
+    if let Some(server) = server.recording() {
+        publish_wit(server.config(), "test:bar", "1.0.0", BAR_1_0_0).await?;
+        publish_wit(server.config(), "test:bar", "1.1.0", BAR_1_1_0).await?;
+    }
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.1.0`"))
+        .success();
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains("Fetched 1 component dependency package(s)"))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("version = \"1.1.0\""));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_skips_yanked_versions_from_a_recorded_registry() -> Result<()> {
+    let server = spawn_recorded_server(cassette("yanked")).await?;
+    if let Some(server) = server.recording() {
+        publish_wit(server.config(), "test:bar", "1.0.0", BAR_1_0_0).await?;
+        publish_wit(server.config(), "test:bar", "1.1.0", BAR_1_1_0).await?;
+        server.yank("test:bar", "1.1.0").await?;
+    }
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component(["add", "--name", "test:bar2", "test:bar@1.1.0"])
+        .assert()
+        .stderr(contains(
+            "component registry package `test:bar` has no release matching version requirement `^1.1.0`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_digest_mismatches_from_a_recorded_registry() -> Result<()> {
+    let server = spawn_recorded_server(cassette("digest-mismatch")).await?;
+    if let Some(server) = server.recording() {
+        publish_wit(server.config(), "test:bar", "1.0.0", BAR_1_0_0).await?;
+    }
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains("Fetched 1 component dependency package(s)"))
+        .success();
+
+    let path = project.root().join("Cargo-component.lock");
+    let lock_file = fs::read_to_string(&path)?;
+    let start = lock_file.find("sha256:").unwrap() + "sha256:".len();
+    let digest = &lock_file[start..start + 64];
+    fs::write(&path, lock_file.replace(digest, &"0".repeat(64)))?;
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains(format!(
+            "but the lock file specifies digest `sha256:{zeros}`",
+            zeros = "0".repeat(64)
+        )))
+        .failure();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rejects_tampered_content_from_a_recorded_cassette() -> Result<()> {
+    let server = spawn_recorded_server(cassette("tampered-content")).await?;
+    if let Some(server) = server.recording() {
+        publish_wit(server.config(), "test:bar", "1.0.0", BAR_1_0_0).await?;
+    }
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    if server.recording().is_some() {
+        project.cargo_component(["fetch"]).assert().success();
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(server.cassette().join("content"))? {
+        fs::write(entry?.path(), BAR_1_1_0)?;
+    }
+
+    project
+        .cargo_component(["fetch"])
+        .assert()
+        .stderr(contains("but digest `sha256:"))
+        .stderr(contains("was expected"))
+        .failure();
+
+    Ok(())
+}
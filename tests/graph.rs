@@ -0,0 +1,98 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use toml_edit::{value, Item, Table};
+
+#[test]
+fn help() {
+    for arg in ["help graph", "graph -h", "graph --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Print the import and export graph of a component's target world",
+            ))
+            .success();
+    }
+}
+
+fn graph_project() -> Result<Project> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value("bar.wit");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("bar.wit"),
+        "package foo:bar;\n\ninterface baz {\n    baz: func() -> string;\n}\n",
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+interface greeter {
+    greet: func() -> string;
+}
+
+world example {
+    import foo:bar/baz;
+    export greeter;
+    export hello-world: func() -> string;
+}
+",
+    )?;
+
+    Ok(project)
+}
+
+#[test]
+fn it_prints_a_dot_graph() -> Result<()> {
+    let project = graph_project()?;
+
+    project
+        .cargo_component(["graph"])
+        .assert()
+        .stdout(contains("digraph \"foo\" {"))
+        .stdout(contains(
+            "  world [label=\"component:foo/example\", shape=ellipse];",
+        ))
+        .stdout(contains(
+            "  subgraph cluster_0 {\n    label=\"target dependency foo:bar (local)\";\n    n0 [label=\"foo:bar/baz\"];\n  }",
+        ))
+        .stdout(contains(
+            "  subgraph cluster_1 {\n    label=\"target package component:foo\";\n    n1 [label=\"hello-world\"];\n    n2 [label=\"component:foo/greeter\"];\n  }",
+        ))
+        .stdout(contains("  n0 -> world [label=\"import\"];"))
+        .stdout(contains("  world -> n1 [label=\"export\"];"))
+        .stdout(contains("  world -> n2 [label=\"export\"];"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_prints_a_mermaid_graph() -> Result<()> {
+    let project = graph_project()?;
+
+    project
+        .cargo_component(["graph", "--format", "mermaid"])
+        .assert()
+        .stdout(contains("flowchart LR\n  world([\"component:foo/example\"])"))
+        .stdout(contains(
+            "  subgraph group0 [\"target dependency foo:bar (local)\"]\n    n0[\"foo:bar/baz\"]\n  end",
+        ))
+        .stdout(contains("  n0 -->|import| world"))
+        .stdout(contains("  world -->|export| n1"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use toml_edit::{value, InlineTable};
+
+#[test]
+fn help() {
+    for arg in ["help imports", "imports -h", "imports --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Print the `unlocked-dep` import names a component requires of its host",
+            ))
+            .success();
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_prints_and_writes_the_import_names() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"(component
+    (core module $m (func (export "f") (result i32) i32.const 0))
+    (core instance $i (instantiate $m))
+    (func (export "get-answer") (result u32) (canon lift (core func $i "f")))
+)"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .success();
+
+    project
+        .cargo_component(["imports"])
+        .assert()
+        .stdout(contains("foo:\n"))
+        .stdout(contains(
+            "  test-bar => unlocked-dep=<test:bar@{>=1.0.0 <1.1.0}>\n",
+        ))
+        .success();
+
+    let imports = fs::read_to_string(project.build_dir().join("component/foo/imports.json"))?;
+    assert_eq!(
+        imports,
+        "{\n  \"test-bar\": \"unlocked-dep=<test:bar@{>=1.0.0 <1.1.0}>\"\n}\n"
+    );
+
+    project
+        .cargo_component(["imports", "--json"])
+        .assert()
+        .stdout(contains(
+            "\"foo\": {\n    \"test-bar\": \"unlocked-dep=<test:bar@{>=1.0.0 <1.1.0}>\"\n  }",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_imports_locked_dependencies_with_their_digest() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"(component
+    (core module $m (func (export "f") (result i32) i32.const 0))
+    (core instance $i (instantiate $m))
+    (func (export "get-answer") (result u32) (canon lift (core func $i "f")))
+)"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let mut dependency = InlineTable::new();
+        dependency.insert("version", "1.0.0".into());
+        dependency.insert("locked", true.into());
+        doc["package"]["metadata"]["component"]["dependencies"]["test:bar"] = value(dependency);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["imports"])
+        .assert()
+        .stdout(contains(
+            "  test-bar => locked-dep=<test:bar@1.0.0>,integrity=<sha256-",
+        ))
+        .success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(lock_file.contains("digest = \"sha256:"));
+
+    Ok(())
+}
+
+#[test]
+fn it_requires_locked_dependencies_to_be_registry_dependencies() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependency = InlineTable::new();
+        dependency.insert("path", "bar.wasm".into());
+        dependency.insert("locked", true.into());
+        doc["package"]["metadata"]["component"]["dependencies"]["test:bar"] = value(dependency);
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["imports"])
+        .assert()
+        .stderr(contains("component dependency `test:bar` in"))
+        .stderr(contains("must be a registry dependency to be locked"))
+        .failure();
+
+    Ok(())
+}
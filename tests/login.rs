@@ -0,0 +1,154 @@
+use std::{ffi::OsStr, fs, path::Path};
+
+use anyhow::Result;
+use assert_cmd::{prelude::*, Command};
+use cargo_component_core::command::CONFIG_FILE_ENV_VAR;
+use cargo_component_test_support::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use tempfile::TempDir;
+
+fn cargo_component_with_config<I, S>(config: &Path, args: I) -> Command
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut cmd = Command::from_std(cargo_component(args));
+    cmd.env(CONFIG_FILE_ENV_VAR, config);
+    cmd
+}
+
+#[test]
+fn help() {
+    for arg in ["help login", "login -h", "login --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Log in to a registry by storing an auth token or signing key",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_logs_in_and_out_of_a_registry() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+
+    cargo_component_with_config(
+        &config,
+        ["login", "--registry", "example.com", "--token-stdin"],
+    )
+    .write_stdin("my-token\n")
+    .assert()
+    .stderr(contains("Logged in to registry `example.com`"))
+    .success();
+
+    cargo_component_with_config(
+        &config,
+        [
+            "login",
+            "--registry",
+            "example.com",
+            "--signing-key",
+            "--token-stdin",
+        ],
+    )
+    .write_stdin(test_signing_key())
+    .assert()
+    .success();
+
+    assert!(fs::read_to_string(&config)?.contains("my-token"));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(fs::metadata(&config)?.permissions().mode() & 0o777, 0o600);
+    }
+
+    cargo_component_with_config(&config, ["whoami", "--registry", "example.com"])
+        .assert()
+        .stdout(
+            contains("example.com: auth token").and(contains("example.com: signing key `sha256:")),
+        )
+        .success();
+
+    cargo_component_with_config(&config, ["logout", "--registry", "example.com"])
+        .assert()
+        .stderr(contains("Logged out of registry `example.com`"))
+        .success();
+
+    assert!(!fs::read_to_string(&config)?.contains("my-token"));
+
+    cargo_component_with_config(&config, ["whoami", "--registry", "example.com"])
+        .assert()
+        .stderr(contains("not logged in to registry `example.com`"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_stores_oci_credentials() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+
+    cargo_component_with_config(
+        &config,
+        [
+            "login",
+            "--registry",
+            "ghcr.io",
+            "--username",
+            "user",
+            "--token-stdin",
+        ],
+    )
+    .write_stdin("password\n")
+    .assert()
+    .success();
+
+    cargo_component_with_config(&config, ["whoami", "--registry", "ghcr.io"])
+        .assert()
+        .stdout(contains("ghcr.io: user `user`"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_an_invalid_signing_key() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+
+    cargo_component_with_config(
+        &config,
+        [
+            "login",
+            "--registry",
+            "example.com",
+            "--signing-key",
+            "--token-stdin",
+        ],
+    )
+    .write_stdin("not-a-key\n")
+    .assert()
+    .stderr(contains("failed to parse signing key"))
+    .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_requires_an_existing_config_file_for_other_commands() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+
+    cargo_component_with_config(&config, ["whoami", "--registry", "example.com"])
+        .assert()
+        .stderr(contains("error reading config file"))
+        .failure();
+
+    assert!(!config.exists());
+
+    Ok(())
+}
@@ -2,13 +2,10 @@ use std::rc::Rc;
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::{prelude::PredicateBooleanExt, str::contains};
 use tempfile::TempDir;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 fn it_prints_metadata() -> Result<()> {
     let project = Project::new("foo", true)?;
@@ -2,13 +2,14 @@ use std::fs;
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
+use cargo_component_core::command::{
+    CACHE_DIR_ENV_VAR, CONFIG_FILE_ENV_VAR, REGISTRY_CASSETTE_ENV_VAR,
+};
+use cargo_component_test_support::*;
 use predicates::{str::contains, Predicate};
+use sha2::{Digest, Sha256};
 use tempfile::TempDir;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 fn help() {
     for arg in ["help new", "new -h", "new --help"] {
@@ -54,7 +55,7 @@ fn it_creates_the_expected_files() -> Result<()> {
 
     let proj_dir = dir.path().join("foo");
 
-    assert!(proj_dir.join("Cargo.toml").is_file());
+    assert!(fs::read_to_string(proj_dir.join("Cargo.toml"))?.contains("doctest = false"));
     assert!(proj_dir.join("wit/world.wit").is_file());
     assert!(proj_dir.join("src").join("lib.rs").is_file());
     assert!(!proj_dir.join("src").join("main.rs").is_file());
@@ -253,6 +254,68 @@ world foo {
     Ok(())
 }
 
+#[test]
+fn it_errors_on_targets_with_async_types() -> Result<()> {
+    for (kind, ty) in [("future", "future<u32>"), ("stream", "stream<u8>")] {
+        // The async types cannot be encoded as a binary WIT package, which
+        // the test registry requires, so the target is replayed from a
+        // cassette as WIT source
+        let dir = TempDir::new()?;
+        let name = format!("test:{kind}-target");
+        let wit = format!(
+            "package {name}@1.0.0;
+
+world foo {{
+    export get: func() -> {ty};
+}}"
+        );
+        let digest = format!("sha256:{:x}", Sha256::digest(&wit));
+
+        let cassette = dir.path().join("cassette");
+        fs::create_dir_all(cassette.join("content"))?;
+        fs::write(
+            cassette.join("content").join(digest.replace(':', "-")),
+            &wit,
+        )?;
+        fs::write(
+            cassette.join("cassette.toml"),
+            format!(
+                r#"[[list]]
+package = "{name}"
+
+[[list.versions]]
+version = "1.0.0"
+
+[[release]]
+package = "{name}"
+version = "1.0.0"
+digest = "{digest}"
+"#
+            ),
+        )?;
+        fs::write(dir.path().join("config.toml"), "")?;
+
+        cargo_component([
+            "new",
+            "--lib",
+            "--target",
+            &format!("{name}@1.0.0"),
+            "component",
+        ])
+        .current_dir(dir.path())
+        .env(CONFIG_FILE_ENV_VAR, dir.path().join("config.toml"))
+        .env(CACHE_DIR_ENV_VAR, dir.path().join("cache"))
+        .env(REGISTRY_CASSETTE_ENV_VAR, &cassette)
+        .assert()
+        .stderr(contains(format!(
+            "unsupported {kind} type found in WIT package"
+        )))
+        .failure();
+    }
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_errors_if_target_does_not_exist() -> Result<()> {
     let (server, _, _) = spawn_server(["foo"]).await?;
@@ -268,6 +331,170 @@ async fn it_errors_if_target_does_not_exist() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_targets_a_world_pinned_to_a_digest() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+world foo {
+    export run: func();
+}"#,
+    )
+    .await?;
+
+    let project = server.project("first", true, ["--target", "test:bar/foo@1.0.0"])?;
+    project.cargo_component(["build"]).assert().success();
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    let start = lock_file.find("sha256:").unwrap();
+    let digest = &lock_file[start..start + "sha256:".len() + 64];
+
+    let project = server.project(
+        "component",
+        true,
+        ["--target", &format!("test:bar/foo@{digest}")],
+    )?;
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#"target = "test:bar/foo@=1.0.0""#).eval(&manifest));
+
+    project.cargo_component(["build"]).assert().success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    let lock_file = fs::read_to_string(project.root().join("Cargo-component.lock"))?;
+    assert!(contains(digest).eval(&lock_file));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_pins_curated_wasi_targets() -> Result<()> {
+    let (server, config, _) = spawn_server(["wasi"]).await?;
+
+    publish_wit(
+        config,
+        "wasi:keyvalue",
+        "0.2.0-draft2",
+        r#"package wasi:keyvalue@0.2.0-draft2;
+
+interface store {
+    resource bucket {
+        get: func(key: string) -> option<list<u8>>;
+    }
+}
+
+interface watcher {
+    use store.{bucket};
+
+    on-set: func(bucket: bucket, key: string, value: list<u8>);
+    on-delete: func(bucket: bucket, key: string);
+}
+
+world imports {
+    import store;
+}
+
+world watch-service {
+    include imports;
+    export watcher;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "wasi:keyvalue"])?;
+
+    let manifest = fs::read_to_string(project.root().join("Cargo.toml"))?;
+    assert!(contains(r#"target = "wasi:keyvalue/watch-service@=0.2.0-draft2""#).eval(&manifest));
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(
+        source.starts_with("//! A component that is notified of changes to a key-value store.\n")
+    );
+    assert!(contains("fn on_set(").eval(&source));
+    assert!(contains("fn on_delete(").eval(&source));
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_infers_the_fields_of_resources() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+
+interface counters {
+    record point {
+        x: s32,
+        y: s32,
+    }
+
+    resource counter {
+        constructor(value: u32, origin: point);
+        value: func() -> u32;
+        set-value: func(value: u32);
+        get-origin: func() -> point;
+        set-origin: func(origin: point);
+        increment: func(by: option<u32>) -> result<u32, string>;
+    }
+
+    resource empty {
+        make: static func() -> empty;
+    }
+}
+
+world foo {
+    export counters;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar/foo@1.0.0"])?;
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(contains("mod counter {").eval(&source));
+    assert!(contains("pub struct Counter {").eval(&source));
+    assert!(contains("value: Cell<u32>,").eval(&source));
+    assert!(contains("origin: RefCell<Point>,").eval(&source));
+    assert!(contains("value: Cell::new(value),").eval(&source));
+    assert!(contains("self.value.get()").eval(&source));
+    assert!(contains("*self.origin.borrow_mut() = origin;").eval(&source));
+    assert!(
+        contains("/// `increment: func(by: option<u32>) -> result<u32, string>`").eval(&source)
+    );
+    assert!(contains("/// `make: static func() -> empty`").eval(&source));
+    assert!(contains("pub struct Empty;").eval(&source));
+    assert!(contains("type Counter = counter::Counter;").eval(&source));
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+    validate_component(&project.debug_wasm("component"))?;
+
+    Ok(())
+}
+
 #[test]
 fn it_supports_the_command_option() -> Result<()> {
     let dir = TempDir::new()?;
@@ -305,3 +532,130 @@ fn it_supports_the_proxy_option() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn it_supports_the_containerize_option() -> Result<()> {
+    let dir = TempDir::new()?;
+
+    cargo_component(["new", "--lib", "--containerize", "foo-bar"])
+        .current_dir(dir.path())
+        .assert()
+        .try_success()?;
+
+    let containerfile = fs::read_to_string(dir.path().join("foo-bar/Containerfile"))?;
+    assert!(containerfile.contains("FROM scratch"));
+    assert!(containerfile.contains("COPY target/wasm32-wasip1/release/foo_bar.wasm /foo_bar.wasm"));
+
+    Ok(())
+}
+
+#[test]
+fn it_supports_the_implementor_option() -> Result<()> {
+    let project = Project::new_with_args("foo", true, ["--implementor", "Foo"])?;
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(source.contains("struct Foo;"));
+    assert!(source.contains("impl Guest for Foo {"));
+    assert!(source.contains("bindings::export!(Foo with_types_in bindings);"));
+    assert!(
+        fs::read_to_string(project.root().join("Cargo.toml"))?.contains("implementor = \"Foo\"")
+    );
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains("Finished `dev` profile"))
+        .success();
+    validate_component(&project.debug_wasm("foo"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_implementors() -> Result<()> {
+    let dir = TempDir::new()?;
+
+    for implementor in ["1Foo", "Foo-Bar", "struct", "Self"] {
+        cargo_component(["new", "--lib", "--implementor", implementor, "foo"])
+            .current_dir(dir.path())
+            .assert()
+            .stderr(contains(format!(
+                "implementor `{implementor}` is not a legal Rust identifier"
+            )))
+            .failure();
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_applies_the_new_settings_of_the_user_config() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+    fs::write(
+        &config,
+        r#"[new]
+wit-bindgen-rt = "0.36.0"
+
+[new.profile.release]
+lto = true
+opt-level = "s"
+strip = true
+"#,
+    )?;
+
+    cargo_component(["new", "--lib", "foo"])
+        .current_dir(dir.path())
+        .env("CARGO_COMPONENT_USER_CONFIG_FILE", &config)
+        .assert()
+        .success();
+
+    let manifest = fs::read_to_string(dir.path().join("foo/Cargo.toml"))?;
+    assert!(
+        contains(r#"wit-bindgen-rt = { version = "0.36.0", features = ["bitflags"] }"#)
+            .eval(&manifest)
+    );
+    assert!(
+        contains("[profile.release]\nlto = true\nopt-level = \"s\"\nstrip = true\n")
+            .eval(&manifest)
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_uses_the_default_namespace_of_the_user_config() -> Result<()> {
+    let dir = TempDir::new()?;
+    let config = dir.path().join("config.toml");
+    fs::write(&config, "namespace = \"my-org\"\n")?;
+
+    cargo_component(["new", "--lib", "foo"])
+        .current_dir(dir.path())
+        .env("CARGO_COMPONENT_USER_CONFIG_FILE", &config)
+        .assert()
+        .success();
+
+    let manifest = fs::read_to_string(dir.path().join("foo/Cargo.toml"))?;
+    assert!(contains(r#"package = "my-org:foo""#).eval(&manifest));
+    let wit = fs::read_to_string(dir.path().join("foo/wit/world.wit"))?;
+    assert!(contains("package my-org:foo;").eval(&wit));
+
+    cargo_component(["new", "--lib", "bar", "--namespace", "other"])
+        .current_dir(dir.path())
+        .env("CARGO_COMPONENT_USER_CONFIG_FILE", &config)
+        .assert()
+        .success();
+
+    let manifest = fs::read_to_string(dir.path().join("bar/Cargo.toml"))?;
+    assert!(contains(r#"package = "other:bar""#).eval(&manifest));
+
+    fs::write(&config, "namespace = \"My Org\"\n")?;
+    cargo_component(["new", "--lib", "baz"])
+        .current_dir(dir.path())
+        .env("CARGO_COMPONENT_USER_CONFIG_FILE", &config)
+        .assert()
+        .stderr(contains("namespace `My Org` is not a legal WIT identifier"))
+        .failure();
+
+    Ok(())
+}
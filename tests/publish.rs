@@ -2,16 +2,13 @@ use std::fs;
 
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use futures::stream::TryStreamExt;
 use predicates::str::contains;
-use toml_edit::{value, Array};
+use toml_edit::{value, Array, Item, Table};
 use wasm_metadata::LinkType;
 use wasm_pkg_client::Client;
 
-use crate::support::*;
-
-mod support;
-
 #[test]
 fn help() {
     for arg in ["help publish", "publish -h", "publish --help"] {
@@ -229,3 +226,150 @@ async fn it_publishes_with_registry_metadata() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_with_the_configured_package_name() -> Result<()> {
+    let (server, _, _) = spawn_server(Vec::<String>::new()).await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        let publish = &mut doc["package"]["metadata"]["component"]["publish"];
+        *publish = Item::Table(Table::new());
+        publish["namespace"] = value("test");
+        publish["package"] = value("bar");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["publish"])
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Published package `test:bar` v0.1.0"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_requires_a_registry_for_private_packages() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let publish = &mut doc["package"]["metadata"]["component"]["publish"];
+        *publish = Item::Table(Table::new());
+        publish["access"] = value("private");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["publish"])
+        .assert()
+        .stderr(contains(
+            "package `component:foo` has private access and must be published to an explicit registry",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_an_invalid_package_name() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let publish = &mut doc["package"]["metadata"]["component"]["publish"];
+        *publish = Item::Table(Table::new());
+        publish["namespace"] = value("Not_Valid");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["publish"])
+        .assert()
+        .stderr(contains(
+            "invalid package name `Not_Valid:foo` to publish in manifest",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_a_package_that_does_not_match_the_wit_package() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["package"] = value("other:foo");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["publish", "--dry-run"])
+        .assert()
+        .stderr(contains("package `other:foo` in manifest"))
+        .stderr(contains(
+            "does not match WIT package `component:foo` of local target",
+        ))
+        .failure();
+
+    project
+        .cargo_component(["publish", "--dry-run", "--allow-mismatch"])
+        .assert()
+        .stderr(contains(
+            "warning: package `other:foo` does not match WIT package `component:foo`",
+        ))
+        .stderr(contains("not publishing component to the registry"))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_initializes_a_package_on_first_publish() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config,
+        "test:world",
+        "1.0.0",
+        r#"package test:%world@1.0.0;
+world foo {
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    server.disable_auto_package_init()?;
+
+    let project = server.project(
+        "foo",
+        true,
+        ["--namespace", "test", "--target", "test:world"],
+    )?;
+
+    project
+        .cargo_component(["publish"])
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains(
+            "package `test:foo` has not been initialized in registry",
+        ))
+        .stderr(contains(
+            "requires confirmation from an interactive terminal",
+        ))
+        .failure();
+
+    project
+        .cargo_component(["publish", "--quiet"])
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("use the `--init` option"))
+        .failure();
+
+    project
+        .cargo_component(["publish", "--init"])
+        .env("CARGO_COMPONENT_PUBLISH_KEY", test_signing_key())
+        .assert()
+        .stderr(contains("Initializing package `test:foo` in registry"))
+        .stderr(contains("Published package `test:foo` v0.1.0"))
+        .success();
+
+    Ok(())
+}
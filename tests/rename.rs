@@ -0,0 +1,159 @@
+use std::{fs, process::Command, rc::Rc};
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use tempfile::TempDir;
+
+#[test]
+fn help() {
+    for arg in ["help rename", "rename -h", "rename --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains("Rename a WebAssembly component package"))
+            .success();
+    }
+}
+
+#[test]
+fn it_renames_a_component() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.file(
+        "wit/world.wit",
+        r#"package component:foo;
+
+interface greet {
+    hello: func() -> string;
+}
+
+world example {
+    use greet.{};
+    export greet;
+}
+"#,
+    )?;
+    project.file(
+        "src/lib.rs",
+        r#"#[allow(warnings)]
+mod bindings;
+
+use bindings::exports::component::foo::greet::Guest;
+
+struct Component;
+
+impl Guest for Component {
+    fn hello() -> String {
+        "Hello, World!".to_string()
+    }
+}
+
+bindings::export!(Component with_types_in bindings);
+"#,
+    )?;
+
+    project
+        .cargo_component(["rename", "foo-bar"])
+        .assert()
+        .stderr(contains(
+            "Renamed package `foo` to `foo-bar` (WIT package `component:foo-bar`)",
+        ))
+        .success();
+
+    let manifest = project.read_manifest()?;
+    assert_eq!(manifest["package"]["name"].as_str(), Some("foo-bar"));
+    assert_eq!(
+        manifest["package"]["metadata"]["component"]["package"].as_str(),
+        Some("component:foo-bar")
+    );
+
+    let wit = fs::read_to_string(project.root().join("wit/world.wit"))?;
+    assert!(wit.starts_with("package component:foo-bar;"));
+
+    let source = fs::read_to_string(project.root().join("src/lib.rs"))?;
+    assert!(source.contains("use bindings::exports::component::foo_bar::greet::Guest;"));
+
+    project
+        .cargo_component(["build"])
+        .assert()
+        .stderr(contains(
+            "Finished `dev` profile [unoptimized + debuginfo] target(s)",
+        ))
+        .success();
+
+    validate_component(&project.debug_wasm("foo_bar"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_updates_the_dependents_of_a_renamed_component() -> Result<()> {
+    let dir = Rc::new(TempDir::new()?);
+    let project = Project::new_uninitialized(dir.clone(), dir.path().to_owned());
+
+    project.file(
+        "Cargo.toml",
+        r#"[workspace]
+members = ["foo", "app"]
+resolver = "2"
+"#,
+    )?;
+    project.file(
+        "app/Cargo.toml",
+        r#"[package]
+name = "app"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+foo = { path = "../foo" }
+"#,
+    )?;
+    project.file("app/src/lib.rs", "")?;
+
+    project
+        .cargo_component(["new", "--lib", "foo"])
+        .assert()
+        .stderr(contains("Updated manifest of package `foo`"))
+        .success();
+
+    Command::new("cargo")
+        .arg("generate-lockfile")
+        .current_dir(dir.path())
+        .assert()
+        .success();
+
+    project
+        .cargo_component(["rename", "-p", "foo", "bar"])
+        .assert()
+        .stderr(contains("Renamed package `foo` to `bar`"))
+        .success();
+
+    let manifest = fs::read_to_string(dir.path().join("app/Cargo.toml"))?;
+    assert!(manifest.contains(r#"foo = { path = "../foo", package = "bar" }"#));
+
+    let lock = fs::read_to_string(dir.path().join("Cargo.lock"))?;
+    assert!(lock.contains("name = \"bar\""));
+    assert!(!lock.contains("name = \"foo\""));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_names() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["rename", "1foo"])
+        .assert()
+        .stderr(contains("package name `1foo` is not a legal crate name"))
+        .failure();
+
+    project
+        .cargo_component(["rename", "foo"])
+        .assert()
+        .stderr(contains("package `foo` is already named `foo`"))
+        .failure();
+
+    Ok(())
+}
@@ -2,12 +2,9 @@ use std::fs;
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
-use predicates::str::contains;
-use toml_edit::{value, Item, Table};
-
-use crate::support::*;
-
-mod support;
+use cargo_component_test_support::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use toml_edit::{value, Array, Item, Table};
 
 #[test]
 fn it_runs_with_command_component() -> Result<()> {
@@ -110,3 +107,154 @@ bindings::export!(Component with_types_in bindings);
 
     Ok(())
 }
+
+#[test]
+fn it_runs_the_selected_bin() -> Result<()> {
+    let project = Project::new("qux", false)?;
+
+    fs::write(
+        project.root().join("src/main.rs"),
+        r#"
+fn main() {
+    println!("[guest] running main");
+}"#,
+    )?;
+
+    fs::create_dir_all(project.root().join("src/bin"))?;
+    fs::write(
+        project.root().join("src/bin/other.rs"),
+        r#"
+fn main() {
+    println!("[guest] running other");
+}"#,
+    )?;
+
+    project
+        .cargo_component(["run", "--bin", "other"])
+        .assert()
+        .stdout(contains("[guest] running other"))
+        .stdout(contains("[guest] running main").not())
+        .success();
+
+    validate_component(&project.debug_wasm("other"))?;
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_when_multiple_bins_would_run() -> Result<()> {
+    let project = Project::new("grault", false)?;
+
+    fs::create_dir_all(project.root().join("src/bin"))?;
+    fs::write(
+        project.root().join("src/bin/other.rs"),
+        r#"
+fn main() {
+    println!("[guest] running other");
+}"#,
+    )?;
+
+    project
+        .cargo_component(["run", "--bins"])
+        .env("CARGO_TARGET_WASM32_WASIP1_RUNNER", "true")
+        .assert()
+        .stderr(contains(
+            "`cargo component run` can run at most one component, but multiple were specified",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_errors_when_the_selected_bin_is_not_a_component() -> Result<()> {
+    let project = Project::new("quux", false)?;
+    project.update_manifest(|mut doc| {
+        doc["package"].as_table_mut().unwrap().remove("metadata");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["run", "--bin", "quux"])
+        .assert()
+        .stderr(contains(
+            "bin target `quux` selected for `cargo component run` is not a WebAssembly component",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_runs_the_selected_example() -> Result<()> {
+    let project = Project::new("corge", false)?;
+
+    fs::create_dir_all(project.root().join("examples"))?;
+    fs::write(
+        project.root().join("examples/demo.rs"),
+        r#"
+fn main() {
+    if std::env::args().any(|v| v == "--verbose") {
+        println!("[guest] running example 'demo'");
+    }
+}"#,
+    )?;
+
+    project
+        .cargo_component(["run", "--example", "demo"])
+        .arg("--")
+        .arg("--verbose")
+        .assert()
+        .stderr(contains(
+            "Running `target/wasm32-wasip1/debug/examples/demo.wasm --verbose`",
+        ))
+        .stdout(contains("[guest] running example 'demo'"))
+        .success();
+
+    validate_component(
+        &project
+            .build_dir()
+            .join("wasm32-wasip1")
+            .join("debug")
+            .join("examples")
+            .join("demo.wasm"),
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn it_runs_with_the_configured_runtime() -> Result<()> {
+    let project = Project::new("grault", false)?;
+    project.update_manifest(|mut doc| {
+        let runtime = &mut doc["package"]["metadata"]["component"]["runtime"];
+        *runtime = Item::Table(Table::new());
+        runtime["env"]["GREETING"] = value("hello");
+        runtime["dirs"] = value(Array::from_iter(["data::/data"]));
+        runtime["args"] = value(Array::from_iter(["--verbose"]));
+        Ok(doc)
+    })?;
+
+    fs::create_dir_all(project.root().join("data"))?;
+    fs::write(project.root().join("data/name.txt"), "world")?;
+    fs::write(
+        project.root().join("src/main.rs"),
+        r#"
+fn main() {
+    let greeting = std::env::var("GREETING").unwrap();
+    let name = std::fs::read_to_string("/data/name.txt").unwrap();
+    let args = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    println!("[guest] {greeting}, {name}! ({args})");
+}"#,
+    )?;
+
+    project
+        .cargo_component(["run"])
+        .arg("--")
+        .arg("--extra")
+        .assert()
+        .stdout(contains("[guest] hello, world! (--verbose --extra)"))
+        .success();
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+
+#[test]
+fn help() {
+    for arg in ["help status", "status -h", "status --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Print a summary of the state of component packages",
+            ))
+            .success();
+    }
+}
+
+#[test]
+fn it_prints_the_status_of_a_component() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.cargo_component(["build"]).assert().success();
+
+    project
+        .cargo_component(["status"])
+        .assert()
+        .stdout(contains("foo v0.1.0"))
+        .stdout(contains("  world:    component:foo/example\n"))
+        .stdout(contains("  bindings: up-to-date (src/bindings.rs)\n"))
+        .stdout(contains("  adapter:  built-in reactor ("))
+        .stdout(contains(
+            "    target/wasm32-wasip1/debug/foo.wasm: up-to-date\n",
+        ))
+        .success();
+
+    let wit = project.root().join("wit/world.wit");
+    fs::write(&wit, fs::read_to_string(&wit)? + "\n// changed\n")?;
+
+    project
+        .cargo_component(["status"])
+        .assert()
+        .stdout(contains("  bindings: out of date (src/bindings.rs)\n"))
+        .stdout(contains("    target/wasm32-wasip1/debug/foo.wasm: stale\n"))
+        .success();
+
+    fs::remove_file(project.debug_wasm("foo"))?;
+
+    project
+        .cargo_component(["status"])
+        .assert()
+        .stdout(contains(
+            "    target/wasm32-wasip1/debug/foo.wasm: missing\n",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_prints_the_locked_dependencies() -> Result<()> {
+    let (server, config, registry) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(
+        config,
+        "test:bar",
+        "1.0.0",
+        r#"(component
+    (core module $m (func (export "f") (result i32) i32.const 0))
+    (core instance $i (instantiate $m))
+    (func (export "get-answer") (result u32) (canon lift (core func $i "f")))
+)"#,
+    )
+    .await?;
+
+    let project = server.project("foo", true, Vec::<String>::new())?;
+    project
+        .cargo_component(["add", "test:bar"])
+        .assert()
+        .success();
+
+    project
+        .cargo_component(["status"])
+        .assert()
+        .stdout(contains("  bindings: out of date (src/bindings.rs)\n"))
+        .stdout(contains("    test:bar ^1.0.0 => not locked\n"))
+        .success();
+
+    project.cargo_component(["bindings"]).assert().success();
+
+    project
+        .cargo_component(["status"])
+        .assert()
+        .stdout(contains("  bindings: up-to-date (src/bindings.rs)\n"))
+        .stdout(contains(format!(
+            "    test:bar ^1.0.0 => 1.0.0 from {registry}\n"
+        )))
+        .success();
+
+    Ok(())
+}
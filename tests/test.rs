@@ -2,11 +2,9 @@ use std::fs;
 
 use anyhow::Result;
 use assert_cmd::prelude::*;
-use predicates::str::contains;
-
-use crate::support::*;
-
-mod support;
+use cargo_component_test_support::*;
+use predicates::{prelude::PredicateBooleanExt, str::contains};
+use toml_edit::{value, Array};
 
 #[test]
 fn it_runs_test_with_command_component() -> Result<()> {
@@ -130,3 +128,90 @@ pub fn test_random_component() {
 
     Ok(())
 }
+
+#[test]
+fn it_errors_when_running_doctests() -> Result<()> {
+    let project = Project::new("foo", true)?;
+
+    project
+        .cargo_component(["test", "--doc"])
+        .assert()
+        .stderr(contains(
+            "doctests are not supported by `cargo component test`",
+        ))
+        .failure();
+
+    Ok(())
+}
+
+#[test]
+fn it_warns_about_doctests_for_rlib_targets() -> Result<()> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        doc["lib"]["crate-type"] = value(Array::from_iter(["cdylib", "rlib"]));
+        doc["lib"].as_table_mut().unwrap().remove("doctest");
+        Ok(doc)
+    })?;
+
+    project
+        .cargo_component(["test", "--no-run"])
+        .assert()
+        .stderr(contains(
+            "doctests for package `foo` will not be run for WebAssembly targets",
+        ))
+        .success();
+
+    project
+        .cargo_component(["test", "--no-run", "--lib"])
+        .assert()
+        .stderr(contains("doctests").not())
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_runs_tests_concurrently() -> Result<()> {
+    let project = Project::new("foo", false)?;
+    project
+        .file(
+            "src/main.rs",
+            r#"
+fn main() {}
+
+#[test]
+fn it_works_in_main() {}
+"#,
+        )?
+        .file(
+            "tests/other.rs",
+            r#"
+#[test]
+fn it_works_in_other() {}
+"#,
+        )?;
+
+    project
+        .cargo_component(["test", "--test-jobs", "2"])
+        .assert()
+        .stdout(contains("[foo] test it_works_in_main ... ok"))
+        .stdout(contains("[other] test it_works_in_other ... ok"))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_an_invalid_number_of_test_jobs() -> Result<()> {
+    let project = Project::new("foo", false)?;
+
+    project
+        .cargo_component(["test", "--test-jobs", "0"])
+        .assert()
+        .stderr(contains(
+            "invalid value `0` for '--test-jobs <N>': expected a positive integer",
+        ))
+        .failure();
+
+    Ok(())
+}
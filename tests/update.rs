@@ -2,12 +2,11 @@ use std::fs;
 
 use anyhow::{Context, Result};
 use assert_cmd::prelude::*;
+use cargo_component_core::command::CONFIG_FILE_ENV_VAR;
+use cargo_component_test_support::*;
 use predicates::{prelude::PredicateBooleanExt, str::contains};
-use toml_edit::value;
-
-use crate::support::*;
-
-mod support;
+use toml_edit::{value, Array};
+use wasm_pkg_client::RegistryMapping;
 
 #[test]
 fn help() {
@@ -244,6 +243,50 @@ world foo {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn update_with_compatible_changes_prints_diff_when_locked() -> Result<()> {
+    let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_wit(
+        config.clone(),
+        "test:bar",
+        "1.0.0",
+        r#"package test:bar@1.0.0;
+world foo {
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    let project = server.project("component", true, ["--target", "test:bar@1.0.0"])?;
+    project.cargo_component(["update"]).assert().success();
+
+    publish_wit(
+        config,
+        "test:bar",
+        "1.1.0",
+        r#"package test:bar@1.1.0;
+world foo {
+    import baz: func() -> string;
+    export bar: func() -> string;
+}"#,
+    )
+    .await?;
+
+    project
+        .cargo_component(["update", "--locked"])
+        .assert()
+        .failure()
+        .stderr(
+            contains("Would update dependency `test:bar` v1.0.0 (sha256:")
+                .and(contains(") -> v1.1.0 (sha256:"))
+                .and(contains("--locked was passed to prevent this"))
+                .and(contains("Updating dependency").not()),
+        );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn update_with_changed_dependencies() -> Result<()> {
     let (server, config, _) = spawn_server(Vec::<String>::new()).await?;
@@ -305,3 +348,95 @@ async fn update_with_changed_dependencies() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn update_falls_back_to_a_mirror() -> Result<()> {
+    let (server, mut config, registry) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config.clone(), "test:bar", "1.0.0", "(component)").await?;
+
+    let project = server.project("component", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["mirrors"]["test"] =
+            value(Array::from_iter([registry.to_string()]));
+        Ok(doc)
+    })?;
+
+    // Map the `test` namespace to a registry that is unreachable
+    config.set_namespace_registry(
+        "test".parse()?,
+        RegistryMapping::Registry("localhost:1".parse()?),
+    );
+    let config_file = project.root().join("unreachable-config.json");
+    config.to_file(&config_file).await?;
+
+    project
+        .cargo_component(["add", "test:bar"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .env("CARGO_COMPONENT_NET_RETRY", "0")
+        .assert()
+        .stderr(contains(format!("falling back to mirror `{registry}`")))
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    project
+        .cargo_component(["update"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .env("CARGO_COMPONENT_NET_RETRY", "0")
+        .assert()
+        .success();
+
+    let path = project.root().join("Cargo-component.lock");
+    let contents = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read lock file `{path}`", path = path.display()))?;
+    assert!(contents.contains(&format!("source = \"{registry}\"")));
+
+    // Packages served by the mirror are cached for offline builds
+    project
+        .cargo_component(["build"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .env("CARGO_COMPONENT_NET_RETRY", "0")
+        .assert()
+        .success();
+
+    project
+        .cargo_component(["build", "--offline"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .assert()
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_falls_back_to_a_mirror_before_retrying() -> Result<()> {
+    let (server, mut config, registry) = spawn_server(Vec::<String>::new()).await?;
+
+    publish_component(config.clone(), "test:bar", "1.0.0", "(component)").await?;
+
+    let project = server.project("component", true, Vec::<String>::new())?;
+    project.update_manifest(|mut doc| {
+        doc["package"]["metadata"]["component"]["mirrors"]["test"] =
+            value(Array::from_iter([registry.to_string()]));
+        Ok(doc)
+    })?;
+
+    config.set_namespace_registry(
+        "test".parse()?,
+        RegistryMapping::Registry("localhost:1".parse()?),
+    );
+    let config_file = project.root().join("unreachable-config.json");
+    config.to_file(&config_file).await?;
+
+    project
+        .cargo_component(["add", "test:bar"])
+        .env(CONFIG_FILE_ENV_VAR, &config_file)
+        .env("CARGO_COMPONENT_NET_RETRY", "3")
+        .assert()
+        .stderr(contains(format!("falling back to mirror `{registry}`")))
+        .stderr(contains("retrying").not())
+        .stderr(contains("Added dependency `test:bar` with version `1.0.0`"))
+        .success();
+
+    Ok(())
+}
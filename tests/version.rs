@@ -1,9 +1,7 @@
-use crate::support::*;
 use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
 use predicates::str::contains;
 
-mod support;
-
 #[test]
 fn help() {
     for arg in ["-V", "--version"] {
@@ -0,0 +1,110 @@
+use std::fs;
+
+use anyhow::Result;
+use assert_cmd::prelude::*;
+use cargo_component_test_support::*;
+use predicates::str::contains;
+use toml_edit::{value, Item, Table};
+
+#[test]
+fn help() {
+    for arg in ["help why", "why -h", "why --help"] {
+        cargo_component(arg.split_whitespace())
+            .assert()
+            .stdout(contains(
+                "Explain why a package is in the dependency resolution of a component",
+            ))
+            .success();
+    }
+}
+
+fn why_project() -> Result<Project> {
+    let project = Project::new("foo", true)?;
+    project.update_manifest(|mut doc| {
+        let mut dependencies = Table::new();
+        dependencies["foo:bar"]["path"] = value("bar.wit");
+        dependencies["foo:baz"]["path"] = value("baz.wit");
+
+        let target =
+            doc["package"]["metadata"]["component"]["target"].or_insert(Item::Table(Table::new()));
+        target["dependencies"] = Item::Table(dependencies);
+        Ok(doc)
+    })?;
+
+    fs::write(
+        project.root().join("bar.wit"),
+        "package foo:bar;\n\ninterface types {\n    type id = u32;\n}\n",
+    )?;
+
+    fs::write(
+        project.root().join("baz.wit"),
+        "package foo:baz;\n\ninterface lookup {\n    use foo:bar/types.{id};\n    find: func(id: id) -> string;\n}\n",
+    )?;
+
+    fs::write(
+        project.root().join("wit/world.wit"),
+        "package component:foo;
+
+world example {
+    import foo:baz/lookup;
+    export hello-world: func() -> string;
+}
+",
+    )?;
+
+    Ok(project)
+}
+
+#[test]
+fn it_explains_a_direct_dependency() -> Result<()> {
+    let project = why_project()?;
+
+    project
+        .cargo_component(["why", "foo:baz"])
+        .assert()
+        .stdout(contains(
+            "foo:baz (local) in package `foo`
+├── `[package.metadata.component.target.dependencies]` entry `foo:baz`
+└── world `component:foo/example` imports `foo:baz/lookup`
+",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_explains_a_transitive_dependency() -> Result<()> {
+    let project = why_project()?;
+
+    project
+        .cargo_component(["why", "foo:bar"])
+        .assert()
+        .stdout(contains(
+            "foo:bar (local) in package `foo`
+├── `[package.metadata.component.target.dependencies]` entry `foo:bar`
+├── world `component:foo/example` imports `foo:bar/types`
+└── used by foo:baz
+    ├── `[package.metadata.component.target.dependencies]` entry `foo:baz`
+    └── world `component:foo/example` imports `foo:baz/lookup`
+",
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_for_a_package_not_in_the_resolution() -> Result<()> {
+    let project = why_project()?;
+
+    project
+        .cargo_component(["why", "foo:qux"])
+        .assert()
+        .stderr(contains(
+            "package `foo:qux` is not in the dependency resolution of any selected component package",
+        ))
+        .failure();
+
+    Ok(())
+}